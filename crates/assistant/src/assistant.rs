@@ -3,6 +3,8 @@
 pub mod assistant_panel;
 pub mod assistant_settings;
 mod context;
+mod context_export;
+mod context_search;
 pub mod context_store;
 mod inline_assistant;
 mod model_selector;
@@ -15,8 +17,11 @@ pub mod slash_command_settings;
 mod slash_command_working_set;
 mod streaming_diff;
 mod terminal_inline_assistant;
+pub mod tool_settings;
 mod tool_working_set;
 mod tools;
+mod usage_report;
+pub mod usage_settings;
 
 use crate::slash_command::project_command::ProjectSlashCommandFeatureFlag;
 pub use crate::slash_command_working_set::{SlashCommandId, SlashCommandWorkingSet};
@@ -28,6 +33,7 @@ use assistant_tool::ToolRegistry;
 use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub use context::*;
+pub use context_search::{ContextSearchMatch, ContextSearchPreview};
 pub use context_store::*;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
@@ -42,14 +48,18 @@ pub(crate) use model_selector::*;
 pub use patch::*;
 pub use prompts::PromptBuilder;
 use prompts::PromptLoadingParams;
-use semantic_index::{CloudEmbeddingProvider, SemanticDb};
+use semantic_index::{
+    CloudEmbeddingProvider, EmbeddingProvider, LocalEmbeddingModel, SemanticDb,
+    SemanticIndexSettings,
+};
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::search_command::SearchSlashCommandFeatureFlag;
 use slash_command::{
     auto_command, cargo_workspace_command, default_command, delta_command, diagnostics_command,
     docs_command, fetch_command, file_command, now_command, project_command, prompt_command,
-    search_command, selection_command, symbols_command, tab_command, terminal_command,
+    repo_map_command, search_command, selection_command, symbols_command, tab_command,
+    terminal_command,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -58,6 +68,8 @@ use util::ResultExt;
 
 use crate::slash_command::streaming_example_command;
 use crate::slash_command_settings::SlashCommandSettings;
+use crate::tool_settings::ToolSettings;
+use crate::usage_settings::UsageSettings;
 
 actions!(
     assistant,
@@ -67,12 +79,19 @@ actions!(
         Split,
         CopyCode,
         CycleMessageRole,
+        ForkMessage,
+        RegenerateMessage,
+        SelectPreviousMessageVariant,
+        SelectNextMessageVariant,
         QuoteSelection,
         InsertIntoEditor,
         ToggleFocus,
         InsertActivePrompt,
         DeployHistory,
         DeployPromptLibrary,
+        DeployUsageReport,
+        ExportContext,
+        ImportContext,
         ConfirmCommand,
         NewContext,
         ToggleModelSelector,
@@ -205,6 +224,9 @@ pub fn init(
     cx.set_global(Assistant::default());
     AssistantSettings::register(cx);
     SlashCommandSettings::register(cx);
+    ToolSettings::register(cx);
+    UsageSettings::register(cx);
+    SemanticIndexSettings::register(cx);
 
     // TODO: remove this when 0.148.0 is released.
     if AssistantSettings::get_global(cx).using_outdated_settings_version {
@@ -230,10 +252,29 @@ pub fn init(
                 return Ok(());
             }
 
-            let embedding_provider = CloudEmbeddingProvider::new(client.clone());
+            let local_model = cx.update(|cx| {
+                SemanticIndexSettings::get_global(cx)
+                    .local_embedding_model
+                    .clone()
+            })?;
+            // Embeddings of different models can't be compared, so each model has its own database.
+            let (embedding_provider, db_name): (Arc<dyn EmbeddingProvider>, _) =
+                if let Some(model_dir) = local_model {
+                    let model = LocalEmbeddingModel::from_dir(&model_dir);
+                    let provider = model.provider(cx.background_executor().clone())?;
+                    (
+                        provider,
+                        format!("semantic-index-db.{}.1.mdb", model.name()),
+                    )
+                } else {
+                    (
+                        Arc::new(CloudEmbeddingProvider::new(client.clone())),
+                        "semantic-index-db.1.mdb".to_string(),
+                    )
+                };
             let semantic_index = SemanticDb::new(
-                paths::embeddings_dir().join("semantic-index-db.0.mdb"),
-                Arc::new(embedding_provider),
+                paths::embeddings_dir().join(db_name),
+                embedding_provider,
                 &mut cx,
             )
             .await?;
@@ -341,6 +382,7 @@ fn register_slash_commands(prompt_builder: Option<Arc<PromptBuilder>>, cx: &mut
     slash_command_registry.register_command(file_command::FileSlashCommand, true);
     slash_command_registry.register_command(delta_command::DeltaSlashCommand, true);
     slash_command_registry.register_command(symbols_command::OutlineSlashCommand, true);
+    slash_command_registry.register_command(repo_map_command::RepoMapSlashCommand::default(), true);
     slash_command_registry.register_command(tab_command::TabSlashCommand, true);
     slash_command_registry
         .register_command(cargo_workspace_command::CargoWorkspaceSlashCommand, true);
@@ -429,6 +471,12 @@ fn update_slash_commands_from_settings(cx: &mut AppContext) {
 fn register_tools(cx: &mut AppContext) {
     let tool_registry = ToolRegistry::global(cx);
     tool_registry.register_tool(tools::now_tool::NowTool);
+    tool_registry.register_tool(tools::read_file_tool::ReadFileTool);
+    tool_registry.register_tool(tools::list_worktree_tool::ListWorktreeTool);
+    tool_registry.register_tool(tools::search_project_tool::SearchProjectTool);
+    tool_registry.register_tool(tools::diagnostics_tool::DiagnosticsTool);
+    tool_registry.register_tool(tools::edit_file_tool::EditFileTool);
+    tool_registry.register_tool(tools::run_task_tool::RunTaskTool);
 }
 
 pub fn humanize_token_count(count: usize) -> String {
@@ -1,8 +1,12 @@
 use crate::slash_command::file_command::codeblock_fence_for_path;
 use crate::slash_command_working_set::SlashCommandWorkingSet;
+use crate::tool_settings::ToolSettings;
+use crate::usage_report::{UsageReport, UsageReportModal, UsageTotals};
+use crate::usage_settings::UsageSettings;
 use crate::ToolWorkingSet;
 use crate::{
     assistant_settings::{AssistantDockPosition, AssistantSettings},
+    context_export::export_markdown,
     humanize_token_count,
     prompt_library::open_prompt_library,
     prompts::PromptBuilder,
@@ -15,14 +19,18 @@ use crate::{
     terminal_inline_assistant::TerminalInlineAssistant,
     Assist, AssistantPatch, AssistantPatchStatus, CacheStatus, ConfirmCommand, Content, Context,
     ContextEvent, ContextId, ContextStore, ContextStoreEvent, CopyCode, CycleMessageRole,
-    DeployHistory, DeployPromptLibrary, Edit, InlineAssistant, InsertDraggedFiles,
-    InsertIntoEditor, InvokedSlashCommandId, InvokedSlashCommandStatus, Message, MessageId,
-    MessageMetadata, MessageStatus, ModelPickerDelegate, ModelSelector, NewContext,
-    ParsedSlashCommand, PendingSlashCommandStatus, QuoteSelection, RemoteContextMetadata,
-    RequestType, SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
+    DeployHistory, DeployPromptLibrary, DeployUsageReport, Edit, ExportContext, ForkMessage,
+    ImportContext, InlineAssistant, InsertDraggedFiles, InsertIntoEditor, InvokedSlashCommandId,
+    InvokedSlashCommandStatus, Message, MessageId, MessageMetadata, MessageStatus, MessageUsage,
+    ModelPickerDelegate, ModelSelector, NewContext, ParsedSlashCommand, PendingSlashCommandStatus,
+    PendingToolUse, QuoteSelection, RegenerateMessage, RemoteContextMetadata, RequestType,
+    SelectNextMessageVariant, SelectPreviousMessageVariant, Split, ToggleFocus,
+    ToggleModelSelector,
 };
 use anyhow::Result;
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
+use assistant_tool::ToolPermission;
+use chrono::Local;
 use client::{proto, zed_urls, Client, Status};
 use collections::{hash_map, BTreeSet, HashMap, HashSet};
 use editor::{
@@ -40,11 +48,11 @@ use fs::Fs;
 use futures::FutureExt;
 use gpui::{
     canvas, div, img, percentage, point, prelude::*, pulsating_between, size, Action, Animation,
-    AnimationExt, AnyElement, AnyView, AppContext, AsyncWindowContext, ClipboardEntry,
+    AnimationExt, AnyElement, AnyView, AppContext, AsyncWindowContext, ClickEvent, ClipboardEntry,
     ClipboardItem, CursorStyle, Empty, Entity, EventEmitter, ExternalPaths, FocusHandle,
-    FocusableView, FontWeight, InteractiveElement, IntoElement, Model, ParentElement, Pixels,
-    Render, RenderImage, SharedString, Size, StatefulInteractiveElement, Styled, Subscription,
-    Task, Transformation, UpdateGlobal, View, WeakModel, WeakView,
+    FocusableView, FontWeight, InteractiveElement, IntoElement, Model, ParentElement,
+    PathPromptOptions, Pixels, Render, RenderImage, SharedString, Size, StatefulInteractiveElement,
+    Styled, Subscription, Task, Transformation, UpdateGlobal, View, WeakModel, WeakView,
 };
 use indexed_docs::IndexedDocsStore;
 use language::{
@@ -78,8 +86,8 @@ use text::SelectionGoal;
 use ui::{
     prelude::*,
     utils::{format_distance_from_now, DateTimeType},
-    Avatar, ButtonLike, ContextMenu, Disclosure, ElevationIndex, KeyBinding, ListItem,
-    ListItemSpacing, PopoverMenu, PopoverMenuHandle, TintColor, Tooltip,
+    Avatar, ButtonLike, ContextMenu, Disclosure, ElevationIndex, HighlightedLabel, KeyBinding,
+    ListItem, ListItemSpacing, PopoverMenu, PopoverMenuHandle, TintColor, Tooltip,
 };
 use util::{maybe, ResultExt};
 use workspace::{
@@ -92,7 +100,7 @@ use workspace::{
     ToolbarItemLocation, ToolbarItemView, Workspace,
 };
 use workspace::{searchable::SearchableItemHandle, DraggedTab};
-use zed_actions::InlineAssist;
+use zed_actions::{InlineAssist, QuoteTerminalOutput};
 
 pub fn init(cx: &mut AppContext) {
     workspace::FollowableViewRegistry::register::<ContextEditor>(cx);
@@ -109,6 +117,7 @@ pub fn init(cx: &mut AppContext) {
                 })
                 .register_action(AssistantPanel::inline_assist)
                 .register_action(ContextEditor::quote_selection)
+                .register_action(ContextEditor::quote_terminal_output)
                 .register_action(ContextEditor::insert_selection)
                 .register_action(ContextEditor::copy_code)
                 .register_action(ContextEditor::insert_dragged_files)
@@ -154,7 +163,7 @@ pub struct AssistantPanel {
 #[derive(Clone)]
 enum ContextMetadata {
     Remote(RemoteContextMetadata),
-    Saved(SavedContextMetadata),
+    Saved(ContextSearchMatch),
 }
 
 struct SavedContextPickerDelegate {
@@ -202,7 +211,7 @@ impl PickerDelegate for SavedContextPickerDelegate {
     }
 
     fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
-        "Search...".into()
+        "Search titles and messages (filter with model:, after: and before:)...".into()
     }
 
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
@@ -278,27 +287,63 @@ impl PickerDelegate for SavedContextPickerDelegate {
                             }),
                     )
             }
-            ContextMetadata::Saved(context) => div()
-                .flex()
+            ContextMetadata::Saved(context) => v_flex()
                 .w_full()
-                .justify_between()
-                .gap_2()
                 .child(
-                    h_flex()
-                        .flex_1()
-                        .child(Label::new(context.title.clone()).size(LabelSize::Small))
-                        .overflow_x_hidden(),
+                    div()
+                        .flex()
+                        .w_full()
+                        .justify_between()
+                        .gap_2()
+                        .child(
+                            h_flex()
+                                .flex_1()
+                                .child(
+                                    Label::new(context.metadata.title.clone())
+                                        .size(LabelSize::Small),
+                                )
+                                .overflow_x_hidden(),
+                        )
+                        .child(
+                            Label::new(format_distance_from_now(
+                                DateTimeType::Local(context.metadata.mtime),
+                                false,
+                                true,
+                                true,
+                            ))
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                        ),
                 )
-                .child(
-                    Label::new(format_distance_from_now(
-                        DateTimeType::Local(context.mtime),
-                        false,
-                        true,
-                        true,
-                    ))
-                    .color(Color::Muted)
-                    .size(LabelSize::Small),
-                ),
+                .children(context.preview.as_ref().map(|preview| {
+                    let role = match preview.role {
+                        Role::User => "You",
+                        Role::Assistant => "Assistant",
+                        Role::System => "System",
+                    };
+                    let highlight_indices = preview
+                        .highlights
+                        .iter()
+                        .flat_map(|range| {
+                            preview.text[range.clone()]
+                                .char_indices()
+                                .map(move |(ix, _)| range.start + ix)
+                        })
+                        .collect();
+                    h_flex()
+                        .gap_1()
+                        .overflow_x_hidden()
+                        .child(
+                            Label::new(format!("{role}:"))
+                                .color(Color::Muted)
+                                .size(LabelSize::XSmall),
+                        )
+                        .child(
+                            HighlightedLabel::new(preview.text.clone(), highlight_indices)
+                                .color(Color::Muted)
+                                .size(LabelSize::XSmall),
+                        )
+                })),
         };
         Some(
             ListItem::new(ix)
@@ -477,6 +522,9 @@ impl AssistantPanel {
                                     menu.context(focus_handle.clone())
                                         .action("New Chat", Box::new(NewContext))
                                         .action("History", Box::new(DeployHistory))
+                                        .action("Import Context…", Box::new(ImportContext))
+                                        .action("Export Context…", Box::new(ExportContext))
+                                        .action("Usage Report", Box::new(DeployUsageReport))
                                         .action("Prompt Library", Box::new(DeployPromptLibrary))
                                         .action("Configure", Box::new(ShowConfiguration))
                                         .action(zoom_label, Box::new(ToggleZoom))
@@ -1188,6 +1236,49 @@ impl AssistantPanel {
         }
     }
 
+    fn export_context(&mut self, _: &ExportContext, cx: &mut ViewContext<Self>) {
+        if let Some(context_editor) = self.active_context_editor(cx) {
+            context_editor.update(cx, |context_editor, cx| context_editor.export_markdown(cx));
+        }
+    }
+
+    fn import_context(&mut self, _: &ImportContext, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Some(paths) = paths.await?? else {
+                return Ok(());
+            };
+
+            for path in paths {
+                let import = this.update(&mut cx, |this, cx| {
+                    this.context_store
+                        .update(cx, |store, cx| store.import_context(path, cx))
+                })?;
+                let imported_path = import.await?;
+                this.update(&mut cx, |this, cx| {
+                    this.open_saved_context(imported_path, cx)
+                })?
+                .await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn deploy_usage_report(&mut self, _: &DeployUsageReport, cx: &mut ViewContext<Self>) {
+        let contexts = self.context_store.read(cx).context_usages(cx);
+        let report = UsageReport::new(&contexts, UsageSettings::get_global(cx), &Local);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| UsageReportModal::new(report, cx))
+            })
+            .ok();
+    }
+
     fn deploy_prompt_library(&mut self, _: &DeployPromptLibrary, cx: &mut ViewContext<Self>) {
         open_prompt_library(self.languages.clone(), cx).detach_and_log_err(cx);
     }
@@ -1358,7 +1449,10 @@ impl Render for AssistantPanel {
                 cx.listener(|this, _: &ShowConfiguration, cx| this.show_configuration_tab(cx)),
             )
             .on_action(cx.listener(AssistantPanel::deploy_history))
+            .on_action(cx.listener(AssistantPanel::import_context))
+            .on_action(cx.listener(AssistantPanel::export_context))
             .on_action(cx.listener(AssistantPanel::deploy_prompt_library))
+            .on_action(cx.listener(AssistantPanel::deploy_usage_report))
             .on_action(cx.listener(AssistantPanel::toggle_model_selector))
             .child(registrar.size_full().child(self.pane.clone()))
             .into_any_element()
@@ -1719,6 +1813,126 @@ impl ContextEditor {
         });
     }
 
+    fn fork_message(&mut self, _: &ForkMessage, cx: &mut ViewContext<Self>) {
+        let Some(message_id) = self.message_id_at_cursor(cx) else {
+            return;
+        };
+        self.fork_message_with_id(message_id, cx);
+    }
+
+    fn fork_message_with_id(&mut self, message_id: MessageId, cx: &mut ViewContext<Self>) {
+        let forked = self
+            .context
+            .update(cx, |context, cx| context.fork_message(message_id, cx));
+        if forked.is_some() {
+            // Place the cursor at the end of the forked message, so it can be edited right away.
+            let end = self.context.read(cx).buffer().read(cx).len();
+            self.editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                    selections.select_ranges([end..end])
+                });
+            });
+        }
+    }
+
+    fn regenerate_message(&mut self, _: &RegenerateMessage, cx: &mut ViewContext<Self>) {
+        let Some(message_id) = self.message_id_at_cursor(cx) else {
+            return;
+        };
+        self.regenerate_message_with_id(message_id, cx);
+    }
+
+    fn regenerate_message_with_id(&mut self, message_id: MessageId, cx: &mut ViewContext<Self>) {
+        self.last_error = None;
+        if let Some(user_message) = self
+            .context
+            .update(cx, |context, cx| context.regenerate_message(message_id, cx))
+        {
+            let cursor = user_message
+                .start
+                .to_offset(self.context.read(cx).buffer().read(cx));
+            self.editor.update(cx, |editor, cx| {
+                editor.change_selections(
+                    Some(Autoscroll::Strategy(AutoscrollStrategy::Fit)),
+                    cx,
+                    |selections| selections.select_ranges([cursor..cursor]),
+                );
+            });
+            cx.defer(|this, _| this.scroll_position = None);
+        }
+        cx.notify();
+    }
+
+    fn select_previous_message_variant(
+        &mut self,
+        _: &SelectPreviousMessageVariant,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(message_id) = self.message_id_at_cursor(cx) else {
+            return;
+        };
+        if let Some((active_variant, _)) = self.context.read(cx).message_variants(message_id, cx) {
+            if active_variant > 0 {
+                self.select_message_variant(message_id, active_variant - 1, cx);
+            }
+        }
+    }
+
+    fn select_next_message_variant(
+        &mut self,
+        _: &SelectNextMessageVariant,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(message_id) = self.message_id_at_cursor(cx) else {
+            return;
+        };
+        if let Some((active_variant, variant_count)) =
+            self.context.read(cx).message_variants(message_id, cx)
+        {
+            if active_variant + 1 < variant_count {
+                self.select_message_variant(message_id, active_variant + 1, cx);
+            }
+        }
+    }
+
+    fn select_message_variant(
+        &mut self,
+        message_id: MessageId,
+        variant_ix: usize,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(first_message_id) = self.context.update(cx, |context, cx| {
+            context.select_message_variant(message_id, variant_ix, cx)
+        }) else {
+            return;
+        };
+
+        let context = self.context.read(cx);
+        let Some(start) = context
+            .messages(cx)
+            .find(|message| message.id == first_message_id)
+            .map(|message| message.offset_range.start)
+        else {
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                selections.select_ranges([start..start])
+            });
+        });
+    }
+
+    fn message_id_at_cursor(&self, cx: &mut ViewContext<Self>) -> Option<MessageId> {
+        let cursor = self.editor.update(cx, |editor, cx| {
+            editor.selections.newest::<usize>(cx).head()
+        });
+        self.context
+            .read(cx)
+            .messages_for_offsets([cursor], cx)
+            .pop()
+            .map(|message| message.id)
+    }
+
     fn cursors(&self, cx: &mut WindowContext) -> Vec<usize> {
         let selections = self
             .editor
@@ -1802,6 +2016,42 @@ impl ContextEditor {
         }
     }
 
+    fn run_tool_use(&mut self, tool_use: PendingToolUse, cx: &mut ViewContext<Self>) {
+        if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
+            let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+
+            self.context.update(cx, |context, cx| {
+                context.insert_tool_output(tool_use.id, task, cx);
+            });
+        }
+    }
+
+    fn confirm_tool_use(
+        &mut self,
+        tool_use_id: &Arc<str>,
+        allow: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(tool_use) = self
+            .context
+            .read(cx)
+            .get_tool_use_by_id(tool_use_id)
+            .filter(|tool_use| tool_use.status.is_awaiting_confirmation())
+            .cloned()
+        else {
+            return;
+        };
+
+        if allow {
+            self.run_tool_use(tool_use, cx);
+        } else {
+            self.context.update(cx, |context, cx| {
+                let output = format!("The user denied running `{}`.", tool_use.name);
+                context.insert_tool_output(tool_use.id, Task::ready(Ok(output)), cx);
+            });
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn run_command(
         &mut self,
@@ -1906,8 +2156,20 @@ impl ContextEditor {
                                 ),
                                 ..Default::default()
                             };
-                            let render_trailer =
-                                move |_row, _unfold, _cx: &mut WindowContext| Empty.into_any();
+                            let render_trailer = {
+                                let context_editor = cx.view().downgrade();
+                                let context = self.context.downgrade();
+                                let tool_use_id = tool_use.id.clone();
+                                move |row, _unfold, cx: &mut WindowContext| {
+                                    render_tool_use_confirmation_trailer(
+                                        row,
+                                        &tool_use_id,
+                                        &context,
+                                        &context_editor,
+                                        cx,
+                                    )
+                                }
+                            };
 
                             let start = buffer
                                 .anchor_in_excerpt(excerpt_id, tool_use.source_range.start)
@@ -2085,12 +2347,21 @@ impl ContextEditor {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
-
-                        self.context.update(cx, |context, cx| {
-                            context.insert_tool_output(tool_use.id.clone(), task, cx);
-                        });
+                    let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                        continue;
+                    };
+                    match ToolSettings::get_global(cx).permission(tool.as_ref()) {
+                        ToolPermission::Allow => self.run_tool_use(tool_use, cx),
+                        ToolPermission::Confirm => self.context.update(cx, |context, cx| {
+                            context.request_tool_use_confirmation(&tool_use.id, cx);
+                        }),
+                        ToolPermission::Deny => self.context.update(cx, |context, cx| {
+                            let output = format!(
+                                "Running `{}` is denied by the `assistant_tools.permissions` setting.",
+                                tool_use.name
+                            );
+                            context.insert_tool_output(tool_use.id, Task::ready(Ok(output)), cx);
+                        }),
                     }
                 }
             }
@@ -2632,7 +2903,134 @@ impl ContextEditor {
             .child("to cancel")
     }
 
+    fn render_branch_controls(
+        message_id: MessageId,
+        can_regenerate: bool,
+        context: &Model<Context>,
+        context_editor: &WeakView<Self>,
+        cx: &mut WindowContext,
+    ) -> impl IntoElement {
+        let variants = context
+            .read(cx)
+            .message_variants(message_id, cx)
+            .filter(|(_, variant_count)| *variant_count > 1);
+        let select_variant = |variant_ix: usize| {
+            let context_editor = context_editor.clone();
+            move |_: &ClickEvent, cx: &mut WindowContext| {
+                context_editor
+                    .update(cx, |this, cx| {
+                        this.select_message_variant(message_id, variant_ix, cx)
+                    })
+                    .ok();
+            }
+        };
+
+        h_flex()
+            .gap_0p5()
+            .children(variants.map(|(active_variant, variant_count)| {
+                h_flex()
+                    .gap_0p5()
+                    .items_center()
+                    .child(
+                        IconButton::new("previous-variant", IconName::ChevronLeft)
+                            .icon_size(IconSize::XSmall)
+                            .disabled(active_variant == 0)
+                            .tooltip(|cx| {
+                                Tooltip::for_action(
+                                    "Previous Variant",
+                                    &SelectPreviousMessageVariant,
+                                    cx,
+                                )
+                            })
+                            .on_click(select_variant(active_variant.saturating_sub(1))),
+                    )
+                    .child(
+                        Label::new(format!("{}/{}", active_variant + 1, variant_count))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        IconButton::new("next-variant", IconName::ChevronRight)
+                            .icon_size(IconSize::XSmall)
+                            .disabled(active_variant + 1 >= variant_count)
+                            .tooltip(|cx| {
+                                Tooltip::for_action("Next Variant", &SelectNextMessageVariant, cx)
+                            })
+                            .on_click(select_variant(active_variant + 1)),
+                    )
+            }))
+            .child(
+                IconButton::new("fork-message", IconName::Route)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .tooltip(|cx| Tooltip::for_action("Fork From Here", &ForkMessage, cx))
+                    .on_click({
+                        let context_editor = context_editor.clone();
+                        move |_, cx| {
+                            context_editor
+                                .update(cx, |this, cx| this.fork_message_with_id(message_id, cx))
+                                .ok();
+                        }
+                    }),
+            )
+            .when(can_regenerate, |this| {
+                this.child(
+                    IconButton::new("regenerate-message", IconName::RotateCw)
+                        .icon_size(IconSize::XSmall)
+                        .icon_color(Color::Muted)
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Regenerate Response", &RegenerateMessage, cx)
+                        })
+                        .on_click({
+                            let context_editor = context_editor.clone();
+                            move |_, cx| {
+                                context_editor
+                                    .update(cx, |this, cx| {
+                                        this.regenerate_message_with_id(message_id, cx)
+                                    })
+                                    .ok();
+                            }
+                        }),
+                )
+            })
+    }
+
+    fn render_message_usage(usage: &MessageUsage, cx: &mut WindowContext) -> impl IntoElement {
+        let mut totals = UsageTotals::default();
+        totals.add(usage, UsageSettings::get_global(cx));
+        let tokens = usage.tokens;
+        let input_tokens = tokens.input_tokens
+            + tokens.cache_creation_input_tokens
+            + tokens.cache_read_input_tokens;
+        let mut label = format!(
+            "{} in · {} out",
+            humanize_token_count(input_tokens as usize),
+            humanize_token_count(tokens.output_tokens as usize)
+        );
+        if totals.unpriced_requests == 0 {
+            label.push_str(&format!(" · {}", totals.cost_label()));
+        }
+        let details = format!(
+            "{}: {} input, {} cache write, {} cache read and {} output tokens",
+            usage.model,
+            tokens.input_tokens,
+            tokens.cache_creation_input_tokens,
+            tokens.cache_read_input_tokens,
+            tokens.output_tokens
+        );
+
+        div()
+            .id("message-usage")
+            .child(
+                Label::new(label)
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted),
+            )
+            .tooltip(move |cx| Tooltip::with_meta("Token Usage", None, details.clone(), cx))
+    }
+
     fn update_message_headers(&mut self, cx: &mut ViewContext<Self>) {
+        let context_editor = cx.view().downgrade();
         self.editor.update(cx, |editor, cx| {
             let buffer = editor.buffer().read(cx).snapshot(cx);
 
@@ -2647,6 +3045,7 @@ impl ContextEditor {
             let render_block = |message: MessageMetadata| -> RenderBlock {
                 Arc::new({
                     let context = self.context.clone();
+                    let context_editor = context_editor.clone();
 
                     move |cx| {
                         let message_id = MessageId(message.timestamp);
@@ -2740,6 +3139,14 @@ impl ContextEditor {
                             )
                             .children(note);
 
+                        let branch_controls = Self::render_branch_controls(
+                            message_id,
+                            message.role == Role::Assistant && !llm_loading,
+                            &context,
+                            &context_editor,
+                            cx,
+                        );
+
                         h_flex()
                             .id(("message_header", message_id.as_u64()))
                             .pl(cx.gutter_dimensions.full_width())
@@ -2822,6 +3229,19 @@ impl ContextEditor {
                                 ),
                                 _ => None,
                             })
+                            .children(
+                                message
+                                    .usage
+                                    .as_ref()
+                                    .map(|usage| Self::render_message_usage(usage, cx)),
+                            )
+                            .child(
+                                h_flex()
+                                    .flex_1()
+                                    .justify_end()
+                                    .pr_4()
+                                    .child(branch_controls),
+                            )
                             .into_any_element()
                     }
                 })
@@ -3051,11 +3471,29 @@ impl ContextEditor {
         _: &QuoteSelection,
         cx: &mut ViewContext<Workspace>,
     ) {
-        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+        let Some(creases) = selections_creases(workspace, cx) else {
             return;
         };
+        Self::insert_quoted_creases(workspace, creases, cx);
+    }
 
-        let Some(creases) = selections_creases(workspace, cx) else {
+    fn quote_terminal_output(
+        workspace: &mut Workspace,
+        _: &QuoteTerminalOutput,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(crease) = terminal_output_crease(workspace, cx) else {
+            return;
+        };
+        Self::insert_quoted_creases(workspace, vec![crease], cx);
+    }
+
+    fn insert_quoted_creases(
+        workspace: &mut Workspace,
+        creases: Vec<(String, String)>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
             return;
         };
 
@@ -3433,6 +3871,19 @@ impl ContextEditor {
         });
     }
 
+    fn export_markdown(&mut self, cx: &mut ViewContext<Self>) {
+        let markdown = export_markdown(&self.context.read(cx).serialize(cx));
+        let fs = self.fs.clone();
+        let path = cx.prompt_for_new_path(util::paths::home_dir());
+        cx.spawn(|_, _| async move {
+            if let Some(path) = path.await?? {
+                fs.atomic_write(path, markdown).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn title(&self, cx: &AppContext) -> Cow<str> {
         self.context
             .read(cx)
@@ -3982,6 +4433,47 @@ fn find_surrounding_code_block(snapshot: &BufferSnapshot, offset: usize) -> Opti
     None
 }
 
+/// Returns the selected text of the focused terminal, falling back to the
+/// output of its last finished command.
+fn terminal_output_crease(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+) -> Option<(String, String)> {
+    let terminal_view = workspace
+        .panel::<TerminalPanel>(cx)
+        .filter(|panel| panel.read(cx).focus_handle(cx).contains_focused(cx))
+        .and_then(|panel| {
+            panel
+                .read(cx)
+                .pane()?
+                .read(cx)
+                .active_item()?
+                .downcast::<TerminalView>()
+        })
+        .or_else(|| {
+            workspace
+                .active_item(cx)
+                .and_then(|item| item.act_as::<TerminalView>(cx))
+        })?;
+
+    let terminal = terminal_view.read(cx).terminal().read(cx);
+    let output = terminal
+        .last_content
+        .selection_text
+        .clone()
+        .filter(|text| !text.is_empty())
+        .or_else(|| terminal.last_command_output())?;
+    let output = output.trim_end();
+    if output.is_empty() {
+        return None;
+    }
+
+    Some((
+        format!("```console\n{output}\n```"),
+        "Terminal Output".to_string(),
+    ))
+}
+
 pub fn selections_creases(
     workspace: &mut workspace::Workspace,
     cx: &mut ViewContext<Workspace>,
@@ -4139,6 +4631,10 @@ impl Render for ContextEditor {
             .on_action(cx.listener(ContextEditor::edit))
             .on_action(cx.listener(ContextEditor::assist))
             .on_action(cx.listener(ContextEditor::split))
+            .on_action(cx.listener(ContextEditor::fork_message))
+            .on_action(cx.listener(ContextEditor::regenerate_message))
+            .on_action(cx.listener(ContextEditor::select_previous_message_variant))
+            .on_action(cx.listener(ContextEditor::select_next_message_variant))
             .size_full()
             .children(self.render_notice(cx))
             .child(
@@ -4510,6 +5006,59 @@ impl ContextEditorToolbarItem {
     }
 }
 
+impl ContextEditorToolbarItem {
+    fn render_usage(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let context = &self
+            .active_context_editor
+            .as_ref()?
+            .upgrade()?
+            .read(cx)
+            .context;
+        let settings = UsageSettings::get_global(cx);
+        let mut totals = UsageTotals::default();
+        for usage in context.read(cx).usages(cx) {
+            totals.add(usage, settings);
+        }
+        if totals.requests == 0 {
+            return None;
+        }
+
+        let label = if totals.unpriced_requests < totals.requests {
+            totals.cost_label()
+        } else {
+            format!(
+                "{} tokens",
+                humanize_token_count(totals.tokens.total_tokens() as usize)
+            )
+        };
+        let details = format!(
+            "{} requests: {} input, {} cached and {} output tokens",
+            totals.requests,
+            humanize_token_count(totals.tokens.input_tokens as usize),
+            humanize_token_count(
+                (totals.tokens.cache_creation_input_tokens + totals.tokens.cache_read_input_tokens)
+                    as usize
+            ),
+            humanize_token_count(totals.tokens.output_tokens as usize)
+        );
+        Some(
+            Button::new("context-usage", label)
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .color(Color::Muted)
+                .tooltip(move |cx| {
+                    Tooltip::with_meta(
+                        "Context Usage",
+                        Some(&DeployUsageReport),
+                        details.clone(),
+                        cx,
+                    )
+                })
+                .on_click(|_, cx| cx.dispatch_action(DeployUsageReport.boxed_clone())),
+        )
+    }
+}
+
 impl Render for ContextEditorToolbarItem {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let left_side = h_flex()
@@ -4601,6 +5150,7 @@ impl Render for ContextEditorToolbarItem {
                 )
                 .with_handle(self.model_selector_menu_handle.clone()),
             )
+            .children(self.render_usage(cx))
             .children(self.render_remaining_tokens(cx));
 
         h_flex()
@@ -4656,7 +5206,7 @@ impl ContextHistory {
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let picker = cx.new_view(|cx| {
-            Picker::uniform_list(
+            Picker::list(
                 SavedContextPickerDelegate::new(project, context_store.clone()),
                 cx,
             )
@@ -4692,9 +5242,9 @@ impl ContextHistory {
                         .open_remote_context(metadata.id.clone(), cx)
                         .detach_and_log_err(cx);
                 }
-                ContextMetadata::Saved(metadata) => {
+                ContextMetadata::Saved(context) => {
                     assistant_panel
-                        .open_saved_context(metadata.path.clone(), cx)
+                        .open_saved_context(context.metadata.path.clone(), cx)
                         .detach_and_log_err(cx);
                 }
             })
@@ -5022,6 +5572,54 @@ fn render_pending_slash_command_gutter_decoration(
     icon.into_any_element()
 }
 
+fn render_tool_use_confirmation_trailer(
+    row: MultiBufferRow,
+    tool_use_id: &Arc<str>,
+    context: &WeakModel<Context>,
+    context_editor: &WeakView<ContextEditor>,
+    cx: &mut WindowContext,
+) -> AnyElement {
+    let awaiting_confirmation = context
+        .upgrade()
+        .and_then(|context| {
+            context
+                .read(cx)
+                .get_tool_use_by_id(tool_use_id)
+                .map(|tool_use| tool_use.status.is_awaiting_confirmation())
+        })
+        .unwrap_or(false);
+    if !awaiting_confirmation {
+        return Empty.into_any();
+    }
+
+    let confirm = |allow: bool| {
+        let context_editor = context_editor.clone();
+        let tool_use_id = tool_use_id.clone();
+        move |_: &ClickEvent, cx: &mut WindowContext| {
+            context_editor
+                .update(cx, |context_editor, cx| {
+                    context_editor.confirm_tool_use(&tool_use_id, allow, cx)
+                })
+                .ok();
+        }
+    };
+
+    h_flex()
+        .gap_1()
+        .child(
+            Button::new(("allow-tool-use", row.0), "Allow")
+                .style(ButtonStyle::Filled)
+                .label_size(LabelSize::Small)
+                .on_click(confirm(true)),
+        )
+        .child(
+            Button::new(("deny-tool-use", row.0), "Deny")
+                .label_size(LabelSize::Small)
+                .on_click(confirm(false)),
+        )
+        .into_any_element()
+}
+
 fn render_docs_slash_command_trailer(
     row: MultiBufferRow,
     command: ParsedSlashCommand,
@@ -12,6 +12,7 @@ use anyhow::{anyhow, Context as _, Result};
 use assistant_slash_command::{
     SlashCommandContent, SlashCommandEvent, SlashCommandOutputSection, SlashCommandResult,
 };
+use chrono::{DateTime, Utc};
 use client::{self, proto, telemetry::Telemetry};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet};
@@ -26,11 +27,15 @@ use gpui::{
 use language::{AnchorRangeExt, Bias, Buffer, LanguageRegistry, OffsetRangeExt, Point, ToOffset};
 use language_model::{
     logging::report_assistant_event,
-    provider::cloud::{MaxMonthlySpendReachedError, PaymentRequiredError},
+    provider::{
+        cloud::{MaxMonthlySpendReachedError, PaymentRequiredError},
+        ollama::PROVIDER_ID as OLLAMA_PROVIDER_ID,
+        open_ai::PROVIDER_ID as OPEN_AI_PROVIDER_ID,
+    },
     LanguageModel, LanguageModelCacheConfiguration, LanguageModelCompletionEvent,
     LanguageModelImage, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelRequestTool, LanguageModelToolResult, LanguageModelToolUse, MessageContent, Role,
-    StopReason,
+    StopReason, TokenUsage,
 };
 use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
@@ -111,6 +116,10 @@ pub enum ContextOperation {
         section: SlashCommandOutputSection<language::Anchor>,
         version: clock::Global,
     },
+    UpdateMessageBranch {
+        branch: MessageBranch,
+        version: clock::Global,
+    },
     BufferOperation(language::Operation),
 }
 
@@ -137,6 +146,7 @@ impl ContextOperation {
                         ),
                         timestamp: id.0,
                         cache: None,
+                        usage: message.usage.map(MessageUsage::from_proto),
                     },
                     version: language::proto::deserialize_version(&insert.version),
                 })
@@ -152,6 +162,7 @@ impl ContextOperation {
                         update.timestamp.context("invalid timestamp")?,
                     ),
                     cache: None,
+                    usage: update.usage.map(MessageUsage::from_proto),
                 },
                 version: language::proto::deserialize_version(&update.version),
             }),
@@ -208,6 +219,12 @@ impl ContextOperation {
                     version: language::proto::deserialize_version(&message.version),
                 })
             }
+            proto::context_operation::Variant::UpdateMessageBranch(message) => {
+                Ok(Self::UpdateMessageBranch {
+                    branch: MessageBranch::from_proto(message.branch.context("missing branch")?)?,
+                    version: language::proto::deserialize_version(&message.version),
+                })
+            }
             proto::context_operation::Variant::BufferOperation(op) => Ok(Self::BufferOperation(
                 language::proto::deserialize_operation(
                     op.operation.context("invalid buffer operation")?,
@@ -230,6 +247,7 @@ impl ContextOperation {
                             start: Some(language::proto::serialize_anchor(&anchor.start)),
                             role: metadata.role.to_proto() as i32,
                             status: Some(metadata.status.to_proto()),
+                            usage: metadata.usage.as_ref().map(MessageUsage::to_proto),
                         }),
                         version: language::proto::serialize_version(version),
                     },
@@ -247,6 +265,7 @@ impl ContextOperation {
                         status: Some(metadata.status.to_proto()),
                         timestamp: Some(language::proto::serialize_timestamp(metadata.timestamp)),
                         version: language::proto::serialize_version(version),
+                        usage: metadata.usage.as_ref().map(MessageUsage::to_proto),
                     },
                 )),
             },
@@ -319,6 +338,14 @@ impl ContextOperation {
                     },
                 )),
             },
+            Self::UpdateMessageBranch { branch, version } => proto::ContextOperation {
+                variant: Some(proto::context_operation::Variant::UpdateMessageBranch(
+                    proto::context_operation::UpdateMessageBranch {
+                        branch: Some(branch.to_proto()),
+                        version: language::proto::serialize_version(version),
+                    },
+                )),
+            },
             Self::BufferOperation(operation) => proto::ContextOperation {
                 variant: Some(proto::context_operation::Variant::BufferOperation(
                     proto::context_operation::BufferOperation {
@@ -335,6 +362,7 @@ impl ContextOperation {
             Self::UpdateMessage { metadata, .. } => metadata.timestamp,
             Self::UpdateSummary { summary, .. } => summary.timestamp,
             Self::SlashCommandStarted { id, .. } => id.0,
+            Self::UpdateMessageBranch { branch, .. } => branch.timestamp,
             Self::SlashCommandOutputSectionAdded { timestamp, .. }
             | Self::SlashCommandFinished { timestamp, .. } => *timestamp,
             Self::BufferOperation(_) => {
@@ -351,7 +379,8 @@ impl ContextOperation {
             | Self::UpdateSummary { version, .. }
             | Self::SlashCommandStarted { version, .. }
             | Self::SlashCommandOutputSectionAdded { version, .. }
-            | Self::SlashCommandFinished { version, .. } => version,
+            | Self::SlashCommandFinished { version, .. }
+            | Self::UpdateMessageBranch { version, .. } => version,
             Self::BufferOperation(_) => {
                 panic!("reading the version of a buffer operation is not supported")
             }
@@ -427,6 +456,9 @@ pub struct MessageMetadata {
     pub(crate) timestamp: clock::Lamport,
     #[serde(skip)]
     pub cache: Option<MessageCacheMetadata>,
+    /// The tokens consumed by the completion that produced this message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<MessageUsage>,
 }
 
 impl From<&Message> for MessageMetadata {
@@ -436,6 +468,7 @@ impl From<&Message> for MessageMetadata {
             status: message.status.clone(),
             timestamp: message.id.0,
             cache: message.cache.clone(),
+            usage: message.usage.clone(),
         }
     }
 }
@@ -456,6 +489,42 @@ impl MessageMetadata {
     }
 }
 
+/// The tokens consumed by a completion, along with the model that produced it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MessageUsage {
+    /// The ID of the language model that produced the message.
+    pub model: String,
+    pub tokens: TokenUsage,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl MessageUsage {
+    fn from_proto(usage: proto::ContextMessageUsage) -> Self {
+        Self {
+            model: usage.model,
+            tokens: TokenUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_creation_input_tokens: usage.cache_creation_input_tokens,
+                cache_read_input_tokens: usage.cache_read_input_tokens,
+            },
+            completed_at: DateTime::from_timestamp_millis(usage.completed_at_ms as i64)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn to_proto(&self) -> proto::ContextMessageUsage {
+        proto::ContextMessageUsage {
+            model: self.model.clone(),
+            input_tokens: self.tokens.input_tokens,
+            output_tokens: self.tokens.output_tokens,
+            cache_creation_input_tokens: self.tokens.cache_creation_input_tokens,
+            cache_read_input_tokens: self.tokens.cache_read_input_tokens,
+            completed_at_ms: self.completed_at.timestamp_millis() as u64,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Message {
     pub offset_range: Range<usize>,
@@ -465,6 +534,7 @@ pub struct Message {
     pub role: Role,
     pub status: MessageStatus,
     pub cache: Option<MessageCacheMetadata>,
+    pub usage: Option<MessageUsage>,
 }
 
 #[derive(Debug, Clone)]
@@ -493,6 +563,24 @@ impl Content {
         }
     }
 
+    fn with_range(self, range: Range<language::Anchor>) -> Self {
+        match self {
+            Self::Image {
+                image_id,
+                render_image,
+                image,
+                ..
+            } => Self::Image {
+                anchor: range.start,
+                image_id,
+                render_image,
+                image,
+            },
+            Self::ToolUse { tool_use, .. } => Self::ToolUse { range, tool_use },
+            Self::ToolResult { tool_use_id, .. } => Self::ToolResult { range, tool_use_id },
+        }
+    }
+
     fn cmp(&self, other: &Self, buffer: &BufferSnapshot) -> Ordering {
         let self_range = self.range();
         let other_range = other.range();
@@ -552,6 +640,8 @@ pub struct Context {
     message_anchors: Vec<MessageAnchor>,
     contents: Vec<Content>,
     messages_metadata: HashMap<MessageId, MessageMetadata>,
+    message_branches: Vec<MessageBranch>,
+    models: Vec<String>,
     summary: Option<ContextSummary>,
     pending_summary: Task<Option<()>>,
     completion_count: usize,
@@ -652,6 +742,8 @@ impl Context {
             message_anchors: Default::default(),
             contents: Default::default(),
             messages_metadata: Default::default(),
+            message_branches: Vec::new(),
+            models: Vec::new(),
             parsed_slash_commands: Vec::new(),
             invoked_slash_commands: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
@@ -693,6 +785,7 @@ impl Context {
                 status: MessageStatus::Done,
                 timestamp: first_message_id.0,
                 cache: None,
+                usage: None,
             },
         );
         this.message_anchors.push(message);
@@ -717,6 +810,17 @@ impl Context {
                     metadata: self.messages_metadata[&message.id].clone(),
                 })
                 .collect(),
+            message_branches: self
+                .message_branches
+                .iter()
+                .filter(|branch| {
+                    branch.parent.map_or(true, |parent| {
+                        self.messages(cx).any(|message| message.id == parent)
+                    })
+                })
+                .cloned()
+                .collect(),
+            models: self.models.clone(),
             summary: self
                 .summary
                 .as_ref()
@@ -768,6 +872,7 @@ impl Context {
             cx,
         );
         this.path = Some(path);
+        this.models = saved_context.models.clone();
         this.buffer.update(cx, |buffer, cx| {
             buffer.set_text(saved_context.text.as_str(), cx)
         });
@@ -861,6 +966,7 @@ impl Context {
     fn flush_ops(&mut self, cx: &mut ModelContext<Context>) {
         let mut changed_messages = HashSet::default();
         let mut summary_changed = false;
+        let mut branches_changed = false;
 
         self.pending_ops.sort_unstable_by_key(|op| op.timestamp());
         for op in mem::take(&mut self.pending_ops) {
@@ -956,6 +1062,9 @@ impl Context {
                         }
                     }
                 }
+                ContextOperation::UpdateMessageBranch { branch, .. } => {
+                    branches_changed |= self.insert_message_branch(branch);
+                }
                 ContextOperation::BufferOperation(_) => unreachable!(),
             }
 
@@ -974,6 +1083,11 @@ impl Context {
             cx.emit(ContextEvent::SummaryChanged);
             cx.notify();
         }
+
+        if branches_changed && changed_messages.is_empty() {
+            cx.emit(ContextEvent::MessagesEdited);
+            cx.notify();
+        }
     }
 
     fn can_apply_op(&self, op: &ContextOperation, cx: &AppContext) -> bool {
@@ -998,6 +1112,9 @@ impl Context {
                 self.has_received_operations_for_anchor_range(section.range.clone(), cx)
             }
             ContextOperation::SlashCommandFinished { .. } => true,
+            ContextOperation::UpdateMessageBranch { branch, .. } => branch
+                .parent
+                .map_or(true, |parent| self.messages_metadata.contains_key(&parent)),
             ContextOperation::BufferOperation(_) => {
                 panic!("buffer operations should always be applied")
             }
@@ -2146,6 +2263,18 @@ impl Context {
         );
     }
 
+    /// Marks the tool use as waiting for the user to allow or deny running it.
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &Arc<str>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::AwaitingConfirmation;
+            cx.notify();
+        }
+    }
+
     pub fn insert_tool_output(
         &mut self,
         tool_use_id: Arc<str>,
@@ -2240,7 +2369,12 @@ impl Context {
 
         let mut request = self.to_completion_request(request_type, cx);
 
-        if cx.has_flag::<ToolUseFeatureFlag>() {
+        // Tool use is still behind a feature flag for the other providers.
+        let provider_id = provider.id();
+        let offers_tools = [OLLAMA_PROVIDER_ID, OPEN_AI_PROVIDER_ID]
+            .contains(&provider_id.0.as_ref())
+            || cx.has_flag::<ToolUseFeatureFlag>();
+        if offers_tools {
             request.tools = self
                 .tools
                 .tools(cx)
@@ -2253,6 +2387,11 @@ impl Context {
                 .collect();
         }
 
+        let model_id = model.id().0.to_string();
+        if !self.models.contains(&model_id) {
+            self.models.push(model_id.clone());
+        }
+
         let assistant_message = self
             .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
             .unwrap();
@@ -2269,6 +2408,7 @@ impl Context {
                 let stream = model.stream_completion(request, &cx);
                 let assistant_message_id = assistant_message.id;
                 let mut response_latency = None;
+                let mut token_usage = None;
                 let stream_completion = async {
                     let request_start = Instant::now();
                     let mut events = stream.await?;
@@ -2298,6 +2438,9 @@ impl Context {
                                     LanguageModelCompletionEvent::Stop(reason) => {
                                         stop_reason = reason;
                                     }
+                                    LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                                        token_usage = Some(usage);
+                                    }
                                     LanguageModelCompletionEvent::Text(chunk) => {
                                         buffer.edit(
                                             [(
@@ -2369,6 +2512,16 @@ impl Context {
                 let result = stream_completion.await;
 
                 this.update(&mut cx, |this, cx| {
+                    if let Some(tokens) = token_usage {
+                        this.update_metadata(assistant_message_id, cx, |metadata| {
+                            metadata.usage = Some(MessageUsage {
+                                model: model_id,
+                                tokens,
+                                completed_at: Utc::now(),
+                            });
+                        });
+                    }
+
                     let error_message = if let Some(error) = result.as_ref().err() {
                         if error.is::<PaymentRequiredError>() {
                             cx.emit(ContextEvent::ShowPaymentRequiredError);
@@ -2609,6 +2762,433 @@ impl Context {
         }
     }
 
+    /// Returns the index of the active variant of the given message, along with
+    /// the number of variants, if the conversation was ever forked at it.
+    pub fn message_variants(
+        &self,
+        message_id: MessageId,
+        cx: &AppContext,
+    ) -> Option<(usize, usize)> {
+        let (parent, _) = self.message_with_parent(message_id, cx)?;
+        let branch = self
+            .message_branches
+            .iter()
+            .find(|branch| branch.parent == parent)?;
+        Some((branch.active_variant, branch.variants.len()))
+    }
+
+    /// Forks the conversation at the given message, keeping the current
+    /// conversation from that message onward as a variant and starting a new
+    /// one with a copy of the message.
+    pub fn fork_message(
+        &mut self,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<MessageId> {
+        if !self.pending_completions.is_empty() {
+            return None;
+        }
+
+        let (parent, message) = self.message_with_parent(message_id, cx)?;
+        let mut text = self
+            .buffer
+            .read(cx)
+            .text_for_range(message.offset_range.clone())
+            .collect::<String>();
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        let mut branch = self.stash_active_variant(parent, &message, cx);
+        // The copy keeps the slash command output, images and tool uses of the message.
+        let stashed_variant = &branch.variants[branch.active_variant];
+        let forked_message = MessageVariant {
+            messages: vec![SavedMessage {
+                id: message.id,
+                start: 0,
+                metadata: MessageMetadata {
+                    role: message.role,
+                    status: MessageStatus::Done,
+                    timestamp: message.id.0,
+                    cache: None,
+                    usage: None,
+                },
+            }],
+            branches: Vec::new(),
+            slash_command_output_sections: stashed_variant
+                .slash_command_output_sections
+                .iter()
+                .filter(|section| section.range.end <= text.len())
+                .cloned()
+                .collect(),
+            contents: stashed_variant.contents.truncated(text.len()),
+            text,
+        };
+        branch.variants.push(MessageVariant::default());
+        branch.active_variant = branch.variants.len() - 1;
+        self.set_message_branch(branch, cx);
+        self.restore_variant(parent, forked_message, cx)
+    }
+
+    /// Generates a new response in place of the given assistant message,
+    /// keeping the existing response as a variant.
+    pub fn regenerate_message(
+        &mut self,
+        message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<MessageAnchor> {
+        if !self.pending_completions.is_empty() {
+            return None;
+        }
+
+        let provider = LanguageModelRegistry::read_global(cx).active_provider()?;
+        if !provider.is_authenticated(cx) {
+            return None;
+        }
+
+        let (parent, message) = self.message_with_parent(message_id, cx)?;
+        if message.role != Role::Assistant || parent.is_none() {
+            return None;
+        }
+
+        let mut branch = self.stash_active_variant(parent, &message, cx);
+        branch.variants.push(MessageVariant::default());
+        branch.active_variant = branch.variants.len() - 1;
+        self.set_message_branch(branch, cx);
+        self.assist(RequestType::Chat, cx)
+    }
+
+    /// Replaces the conversation from the given message onward with another
+    /// variant of it, returning the ID of the variant's first message.
+    pub fn select_message_variant(
+        &mut self,
+        message_id: MessageId,
+        variant_ix: usize,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<MessageId> {
+        if !self.pending_completions.is_empty() {
+            return None;
+        }
+
+        let (parent, message) = self.message_with_parent(message_id, cx)?;
+        let branch = self
+            .message_branches
+            .iter()
+            .find(|branch| branch.parent == parent)?;
+        if variant_ix == branch.active_variant || variant_ix >= branch.variants.len() {
+            return None;
+        }
+
+        let mut branch = self.stash_active_variant(parent, &message, cx);
+        let variant = mem::take(&mut branch.variants[variant_ix]);
+        branch.active_variant = variant_ix;
+        self.set_message_branch(branch, cx);
+        self.restore_variant(parent, variant, cx)
+    }
+
+    /// Returns the usage of every completion in this context, including the
+    /// ones that produced messages in inactive branches.
+    pub fn usages<'a>(&'a self, cx: &'a AppContext) -> Vec<&'a MessageUsage> {
+        let mut usages = self
+            .messages(cx)
+            .filter_map(|message| self.messages_metadata.get(&message.id)?.usage.as_ref())
+            .collect::<Vec<_>>();
+        collect_branch_usages(&self.message_branches, &mut usages);
+        usages
+    }
+
+    fn message_with_parent(
+        &self,
+        message_id: MessageId,
+        cx: &AppContext,
+    ) -> Option<(Option<MessageId>, Message)> {
+        let mut parent = None;
+        for message in self.messages(cx) {
+            if message.id == message_id {
+                return Some((parent, message));
+            }
+            parent = Some(message.id);
+        }
+        None
+    }
+
+    /// Removes the conversation from the given message onward, storing it in the
+    /// active slot of the branch that follows `parent`.
+    fn stash_active_variant(
+        &mut self,
+        parent: Option<MessageId>,
+        message: &Message,
+        cx: &mut ModelContext<Self>,
+    ) -> MessageBranch {
+        let start = message.offset_range.start;
+        let buffer = self.buffer.read(cx);
+        let text = buffer
+            .text_for_range(start..buffer.len())
+            .collect::<String>();
+        let messages = self
+            .messages(cx)
+            .skip_while(|candidate| candidate.id != message.id)
+            .map(|message| SavedMessage {
+                id: message.id,
+                start: message.offset_range.start - start,
+                metadata: self.messages_metadata[&message.id].clone(),
+            })
+            .collect::<Vec<_>>();
+
+        // So do the slash command output, images and tool uses within those messages, with
+        // ranges relative to the start of the variant.
+        let (stashed_sections, sections): (Vec<_>, Vec<_>) =
+            mem::take(&mut self.slash_command_output_sections)
+                .into_iter()
+                .partition(|section| section.range.start.to_offset(buffer) >= start);
+        self.slash_command_output_sections = sections;
+        let slash_command_output_sections = stashed_sections
+            .into_iter()
+            .filter(|section| section.is_valid(buffer))
+            .map(|section| {
+                let range = section.range.to_offset(buffer);
+                SlashCommandOutputSection {
+                    range: range.start - start..range.end - start,
+                    icon: section.icon,
+                    label: section.label,
+                    metadata: section.metadata,
+                }
+            })
+            .collect();
+        let (stashed_contents, contents): (Vec<_>, Vec<_>) = mem::take(&mut self.contents)
+            .into_iter()
+            .partition(|content| content.range().start.to_offset(buffer) >= start);
+        self.contents = contents;
+        let (stashed_tool_uses, tool_uses): (HashMap<_, _>, HashMap<_, _>) =
+            mem::take(&mut self.pending_tool_uses_by_id)
+                .into_iter()
+                .partition(|(_, tool_use)| tool_use.source_range.start.to_offset(buffer) >= start);
+        self.pending_tool_uses_by_id = tool_uses;
+        let relative_range = |range: &Range<language::Anchor>| {
+            let range = range.to_offset(buffer);
+            range.start - start..range.end - start
+        };
+        let contents = VariantContents {
+            contents: stashed_contents
+                .into_iter()
+                .map(|content| (relative_range(&content.range()), content))
+                .collect(),
+            pending_tool_uses: stashed_tool_uses
+                .into_values()
+                .map(|tool_use| (relative_range(&tool_use.source_range), tool_use))
+                .collect(),
+        };
+
+        // Branches of messages that are being removed travel with the variant.
+        let message_ids = messages
+            .iter()
+            .map(|message| message.id)
+            .collect::<HashSet<_>>();
+        let (nested_branches, branches): (Vec<_>, Vec<_>) = mem::take(&mut self.message_branches)
+            .into_iter()
+            .partition(|branch| {
+                branch
+                    .parent
+                    .map_or(false, |parent| message_ids.contains(&parent))
+            });
+        self.message_branches = branches;
+        for branch in &nested_branches {
+            self.set_message_branch(
+                MessageBranch {
+                    variants: Vec::new(),
+                    ..branch.clone()
+                },
+                cx,
+            );
+        }
+
+        self.buffer.update(cx, |buffer, cx| {
+            let removal_start =
+                if parent.is_some() && buffer.reversed_chars_at(start).next() == Some('\n') {
+                    start - 1
+                } else {
+                    start
+                };
+            let end = buffer.len();
+            buffer.edit([(removal_start..end, "")], None, cx);
+        });
+
+        let variant = MessageVariant {
+            text,
+            messages,
+            branches: nested_branches,
+            slash_command_output_sections,
+            contents,
+        };
+        let mut branch = self
+            .message_branches
+            .iter()
+            .find(|branch| branch.parent == parent)
+            .cloned()
+            .unwrap_or_else(|| MessageBranch {
+                parent,
+                variants: Vec::new(),
+                active_variant: 0,
+                timestamp: clock::Lamport::default(),
+            });
+        if let Some(slot) = branch.variants.get_mut(branch.active_variant) {
+            *slot = variant;
+        } else {
+            branch.variants.push(variant);
+            branch.active_variant = branch.variants.len() - 1;
+        }
+        branch
+    }
+
+    /// Appends the given variant to the end of the context, which must follow
+    /// `parent`, returning the ID of the variant's first message.
+    fn restore_variant(
+        &mut self,
+        parent: Option<MessageId>,
+        variant: MessageVariant,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<MessageId> {
+        if variant.messages.is_empty() {
+            return None;
+        }
+
+        let start = self.buffer.update(cx, |buffer, cx| {
+            let end = buffer.len();
+            if parent.is_some() {
+                buffer.edit([(end..end, format!("\n{}", variant.text))], None, cx);
+                end + 1
+            } else {
+                buffer.edit([(end..end, variant.text)], None, cx);
+                end
+            }
+        });
+
+        let mut first_message_id = None;
+        let mut new_message_ids = HashMap::default();
+        for (ix, message) in variant.messages.into_iter().enumerate() {
+            let new_message_id = if ix == 0 && parent.is_none() {
+                // The first message of the context can't be removed, so it takes
+                // on the metadata of the variant's first message instead.
+                let first_message_id = MessageId(clock::Lamport::default());
+                self.update_metadata(first_message_id, cx, |metadata| {
+                    metadata.role = message.metadata.role;
+                    metadata.status = message.metadata.status.clone();
+                    metadata.usage = message.metadata.usage.clone();
+                });
+                first_message_id
+            } else {
+                let version = self.version.clone();
+                let anchor = MessageAnchor {
+                    id: MessageId(self.next_timestamp()),
+                    start: self.buffer.read(cx).anchor_before(start + message.start),
+                };
+                let metadata = MessageMetadata {
+                    role: message.metadata.role,
+                    status: message.metadata.status,
+                    timestamp: anchor.id.0,
+                    cache: None,
+                    usage: message.metadata.usage,
+                };
+                self.insert_message(anchor.clone(), metadata.clone(), cx);
+                self.push_op(
+                    ContextOperation::InsertMessage {
+                        anchor: anchor.clone(),
+                        metadata,
+                        version,
+                    },
+                    cx,
+                );
+                anchor.id
+            };
+            first_message_id.get_or_insert(new_message_id);
+            new_message_ids.insert(message.id, new_message_id);
+        }
+
+        for mut branch in variant.branches {
+            if let Some(parent) = branch
+                .parent
+                .and_then(|parent| new_message_ids.get(&parent).copied())
+            {
+                branch.parent = Some(parent);
+                self.set_message_branch(branch, cx);
+            }
+        }
+
+        for section in variant.slash_command_output_sections {
+            let buffer = self.buffer.read(cx);
+            let section = SlashCommandOutputSection {
+                range: buffer.anchor_after(start + section.range.start)
+                    ..buffer.anchor_before(start + section.range.end),
+                icon: section.icon,
+                label: section.label,
+                metadata: section.metadata,
+            };
+            self.insert_slash_command_output_section(section, cx);
+        }
+
+        // Images and tool uses are anchored like they were before being stashed.
+        let buffer = self.buffer.read(cx);
+        let anchor_range = |range: Range<language::Anchor>, offsets: Range<usize>| {
+            buffer.anchor_at(start + offsets.start, range.start.bias)
+                ..buffer.anchor_at(start + offsets.end, range.end.bias)
+        };
+        let contents = variant
+            .contents
+            .contents
+            .into_iter()
+            .map(|(offsets, content)| {
+                let range = anchor_range(content.range(), offsets);
+                content.with_range(range)
+            })
+            .collect::<Vec<_>>();
+        for (offsets, mut tool_use) in variant.contents.pending_tool_uses {
+            tool_use.source_range = anchor_range(tool_use.source_range.clone(), offsets);
+            self.pending_tool_uses_by_id
+                .insert(tool_use.id.clone(), tool_use);
+        }
+        for content in contents {
+            self.insert_content(content, cx);
+        }
+
+        first_message_id
+    }
+
+    fn set_message_branch(&mut self, mut branch: MessageBranch, cx: &mut ModelContext<Self>) {
+        let version = self.version.clone();
+        branch.timestamp = self.next_timestamp();
+        self.insert_message_branch(branch.clone());
+        self.push_op(
+            ContextOperation::UpdateMessageBranch { branch, version },
+            cx,
+        );
+        cx.emit(ContextEvent::MessagesEdited);
+        cx.notify();
+    }
+
+    /// Stores the given branch unless a more recent version of it is already
+    /// present. Branches without variants remove the existing branch.
+    fn insert_message_branch(&mut self, branch: MessageBranch) -> bool {
+        let existing_ix = self
+            .message_branches
+            .iter()
+            .position(|existing| existing.parent == branch.parent);
+        match existing_ix {
+            Some(ix) if self.message_branches[ix].timestamp >= branch.timestamp => false,
+            Some(ix) if branch.variants.is_empty() => {
+                self.message_branches.remove(ix);
+                true
+            }
+            Some(ix) => {
+                self.message_branches[ix] = branch;
+                true
+            }
+            None if branch.variants.is_empty() => false,
+            None => {
+                self.message_branches.push(branch);
+                true
+            }
+        }
+    }
+
     pub fn update_metadata(
         &mut self,
         id: MessageId,
@@ -2687,6 +3267,7 @@ impl Context {
             status,
             timestamp: anchor.id.0,
             cache: None,
+            usage: None,
         };
         self.insert_message(anchor.clone(), metadata.clone(), cx);
         self.push_op(
@@ -2779,6 +3360,7 @@ impl Context {
                 status: MessageStatus::Done,
                 timestamp: suffix.id.0,
                 cache: None,
+                usage: None,
             };
             self.insert_message(suffix.clone(), suffix_metadata.clone(), cx);
             self.push_op(
@@ -2829,6 +3411,7 @@ impl Context {
                         status: MessageStatus::Done,
                         timestamp: selection.id.0,
                         cache: None,
+                        usage: None,
                     };
                     self.insert_message(selection.clone(), selection_metadata.clone(), cx);
                     self.push_op(
@@ -3037,6 +3620,7 @@ impl Context {
                     role: metadata.role,
                     status: metadata.status.clone(),
                     cache: metadata.cache.clone(),
+                    usage: metadata.usage.clone(),
                 });
             }
             None
@@ -3217,6 +3801,7 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
+    AwaitingConfirmation,
     Running { _task: Shared<Task<()>> },
     Error(String),
 }
@@ -3225,15 +3810,212 @@ impl PendingToolUseStatus {
     pub fn is_idle(&self) -> bool {
         matches!(self, PendingToolUseStatus::Idle)
     }
+
+    pub fn is_awaiting_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::AwaitingConfirmation)
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SavedMessage {
     pub id: MessageId,
     pub start: usize,
     pub metadata: MessageMetadata,
 }
 
+impl SavedMessage {
+    fn from_proto(message: proto::SavedContextMessage) -> Result<Self> {
+        Ok(Self {
+            id: MessageId(language::proto::deserialize_timestamp(
+                message.id.context("invalid id")?,
+            )),
+            start: message.start as usize,
+            metadata: MessageMetadata {
+                role: Role::from_proto(message.role),
+                status: MessageStatus::from_proto(message.status.context("invalid status")?),
+                timestamp: language::proto::deserialize_timestamp(
+                    message.timestamp.context("invalid timestamp")?,
+                ),
+                cache: None,
+                usage: message.usage.map(MessageUsage::from_proto),
+            },
+        })
+    }
+
+    fn to_proto(&self) -> proto::SavedContextMessage {
+        proto::SavedContextMessage {
+            id: Some(language::proto::serialize_timestamp(self.id.0)),
+            start: self.start as u64,
+            role: self.metadata.role.to_proto() as i32,
+            status: Some(self.metadata.status.to_proto()),
+            timestamp: Some(language::proto::serialize_timestamp(
+                self.metadata.timestamp,
+            )),
+            usage: self.metadata.usage.as_ref().map(MessageUsage::to_proto),
+        }
+    }
+}
+
+/// A version of the conversation that starts at a forked message and runs to
+/// the end of the context.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MessageVariant {
+    pub text: String,
+    /// The messages of this variant, with offsets relative to the start of `text`.
+    pub messages: Vec<SavedMessage>,
+    /// The branches of messages within this variant.
+    #[serde(default)]
+    pub branches: Vec<MessageBranch>,
+    /// The output sections of slash commands within this variant, relative to the start of `text`.
+    #[serde(default)]
+    pub slash_command_output_sections: Vec<SlashCommandOutputSection<usize>>,
+    /// Like the images and tool uses of the context itself, those of the variant are neither
+    /// saved nor replicated.
+    #[serde(skip)]
+    pub(crate) contents: VariantContents,
+}
+
+impl MessageVariant {
+    fn from_proto(variant: proto::ContextMessageVariant) -> Result<Self> {
+        Ok(Self {
+            text: variant.text,
+            messages: variant
+                .messages
+                .into_iter()
+                .map(SavedMessage::from_proto)
+                .collect::<Result<_>>()?,
+            branches: variant
+                .branches
+                .into_iter()
+                .map(MessageBranch::from_proto)
+                .collect::<Result<_>>()?,
+            slash_command_output_sections: variant
+                .slash_command_output_sections
+                .into_iter()
+                .map(|section| {
+                    Ok(SlashCommandOutputSection {
+                        range: section.start as usize..section.end as usize,
+                        icon: section.icon_name.parse()?,
+                        label: section.label.into(),
+                        metadata: section
+                            .metadata
+                            .and_then(|metadata| serde_json::from_str(&metadata).log_err()),
+                    })
+                })
+                .collect::<Result<_>>()?,
+            contents: VariantContents::default(),
+        })
+    }
+
+    fn to_proto(&self) -> proto::ContextMessageVariant {
+        proto::ContextMessageVariant {
+            text: self.text.clone(),
+            messages: self.messages.iter().map(SavedMessage::to_proto).collect(),
+            branches: self.branches.iter().map(MessageBranch::to_proto).collect(),
+            slash_command_output_sections: self
+                .slash_command_output_sections
+                .iter()
+                .map(|section| {
+                    let icon_name: &'static str = section.icon.into();
+                    proto::SavedSlashCommandOutputSection {
+                        start: section.range.start as u64,
+                        end: section.range.end as u64,
+                        icon_name: icon_name.to_string(),
+                        label: section.label.to_string(),
+                        metadata: section
+                            .metadata
+                            .as_ref()
+                            .and_then(|metadata| serde_json::to_string(metadata).log_err()),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The images and tool uses of a [`MessageVariant`], with ranges relative to the start of its text.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct VariantContents {
+    contents: Vec<(Range<usize>, Content)>,
+    pending_tool_uses: Vec<(Range<usize>, PendingToolUse)>,
+}
+
+impl VariantContents {
+    /// Returns the contents that end within the first `len` bytes of the variant.
+    fn truncated(&self, len: usize) -> Self {
+        Self {
+            contents: self
+                .contents
+                .iter()
+                .filter(|(range, _)| range.end <= len)
+                .cloned()
+                .collect(),
+            pending_tool_uses: self
+                .pending_tool_uses
+                .iter()
+                .filter(|(range, _)| range.end <= len)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// The alternative versions of the conversation that follow a message.
+///
+/// Only the active variant is present in the context's buffer, so its slot in
+/// `variants` is left empty until another variant is selected.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageBranch {
+    /// The message after which the variants diverge, or `None` if they diverge
+    /// at the start of the context.
+    pub parent: Option<MessageId>,
+    pub variants: Vec<MessageVariant>,
+    pub active_variant: usize,
+    pub(crate) timestamp: clock::Lamport,
+}
+
+fn collect_branch_usages<'a>(branches: &'a [MessageBranch], usages: &mut Vec<&'a MessageUsage>) {
+    for variant in branches.iter().flat_map(|branch| &branch.variants) {
+        usages.extend(
+            variant
+                .messages
+                .iter()
+                .filter_map(|message| message.metadata.usage.as_ref()),
+        );
+        collect_branch_usages(&variant.branches, usages);
+    }
+}
+
+impl MessageBranch {
+    fn from_proto(branch: proto::ContextMessageBranch) -> Result<Self> {
+        Ok(Self {
+            parent: branch
+                .parent
+                .map(|parent| MessageId(language::proto::deserialize_timestamp(parent))),
+            variants: branch
+                .variants
+                .into_iter()
+                .map(MessageVariant::from_proto)
+                .collect::<Result<_>>()?,
+            active_variant: branch.active_variant as usize,
+            timestamp: language::proto::deserialize_timestamp(
+                branch.timestamp.context("invalid timestamp")?,
+            ),
+        })
+    }
+
+    fn to_proto(&self) -> proto::ContextMessageBranch {
+        proto::ContextMessageBranch {
+            parent: self
+                .parent
+                .map(|parent| language::proto::serialize_timestamp(parent.0)),
+            variants: self.variants.iter().map(MessageVariant::to_proto).collect(),
+            active_variant: self.active_variant as u32,
+            timestamp: Some(language::proto::serialize_timestamp(self.timestamp)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SavedContext {
     pub id: Option<ContextId>,
@@ -3241,6 +4023,11 @@ pub struct SavedContext {
     pub version: String,
     pub text: String,
     pub messages: Vec<SavedMessage>,
+    #[serde(default)]
+    pub message_branches: Vec<MessageBranch>,
+    /// The IDs of the models that responded in this context.
+    #[serde(default)]
+    pub models: Vec<String>,
     pub summary: String,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
@@ -3249,6 +4036,18 @@ pub struct SavedContext {
 impl SavedContext {
     pub const VERSION: &'static str = "0.4.0";
 
+    /// Returns the usage of every completion in this context, including the
+    /// ones that produced messages in inactive branches.
+    pub fn usages(&self) -> Vec<&MessageUsage> {
+        let mut usages = self
+            .messages
+            .iter()
+            .filter_map(|message| message.metadata.usage.as_ref())
+            .collect::<Vec<_>>();
+        collect_branch_usages(&self.message_branches, &mut usages);
+        usages
+    }
+
     pub fn from_json(json: &str) -> Result<Self> {
         let saved_context_json = serde_json::from_str::<serde_json::Value>(json)?;
         match saved_context_json
@@ -3304,6 +4103,7 @@ impl SavedContext {
                         status: message.metadata.status,
                         timestamp: message.metadata.timestamp,
                         cache: None,
+                        usage: message.metadata.usage,
                     },
                     version: version.clone(),
                 });
@@ -3321,12 +4121,23 @@ impl SavedContext {
                     status: metadata.status,
                     timestamp,
                     cache: None,
+                    usage: metadata.usage,
                 },
                 version: version.clone(),
             });
             version.observe(timestamp);
         }
 
+        for mut branch in self.message_branches {
+            let timestamp = next_timestamp.tick();
+            branch.timestamp = timestamp;
+            operations.push(ContextOperation::UpdateMessageBranch {
+                branch,
+                version: version.clone(),
+            });
+            version.observe(timestamp);
+        }
+
         let buffer = buffer.read(cx);
         for section in self.slash_command_output_sections {
             let timestamp = next_timestamp.tick();
@@ -3413,10 +4224,13 @@ impl SavedContextV0_3_0 {
                             status: metadata.status.clone(),
                             timestamp,
                             cache: None,
+                            usage: None,
                         },
                     })
                 })
                 .collect(),
+            message_branches: Vec::new(),
+            models: Vec::new(),
             summary: self.summary,
             slash_command_output_sections: self.slash_command_output_sections,
         }
@@ -3482,7 +4296,7 @@ impl SavedContextV0_1_0 {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct SavedContextMetadata {
     pub title: String,
     pub path: PathBuf,
@@ -1,16 +1,17 @@
-use super::{AssistantEdit, MessageCacheMetadata};
+use super::{AssistantEdit, Content, MessageCacheMetadata, PendingToolUse, PendingToolUseStatus};
 use crate::slash_command_working_set::SlashCommandWorkingSet;
 use crate::ToolWorkingSet;
 use crate::{
     assistant_panel, prompt_library, slash_command::file_command, AssistantEditKind, CacheStatus,
     Context, ContextEvent, ContextId, ContextOperation, InvokedSlashCommandId, MessageId,
-    MessageStatus, PromptBuilder,
+    MessageStatus, MessageUsage, PromptBuilder, SavedContext,
 };
 use anyhow::Result;
 use assistant_slash_command::{
     ArgumentCompletion, SlashCommand, SlashCommandContent, SlashCommandEvent, SlashCommandOutput,
     SlashCommandOutputSection, SlashCommandRegistry, SlashCommandResult,
 };
+use client::proto;
 use collections::{HashMap, HashSet};
 use fs::FakeFs;
 use futures::{
@@ -19,7 +20,9 @@ use futures::{
 };
 use gpui::{AppContext, Model, SharedString, Task, TestAppContext, WeakView};
 use language::{Buffer, BufferSnapshot, LanguageRegistry, LspAdapterDelegate};
-use language_model::{LanguageModelCacheConfiguration, LanguageModelRegistry, Role};
+use language_model::{
+    LanguageModelCacheConfiguration, LanguageModelRegistry, LanguageModelToolUse, Role, TokenUsage,
+};
 use parking_lot::Mutex;
 use pretty_assertions::assert_eq;
 use project::Project;
@@ -1157,6 +1160,345 @@ async fn test_serialization(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+fn test_message_branches(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry.clone(),
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    let buffer = context.read(cx).buffer.clone();
+
+    let message_0 = context.read(cx).message_anchors[0].id;
+    let message_1 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    let message_2 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "hello"), (1..1, "hi")], None, cx)
+    });
+    assert_eq!(buffer.read(cx).text(), "hello\nhi\n");
+    assert_eq!(
+        messages(&context, cx),
+        vec![
+            (message_0, Role::User, 0..6),
+            (message_1.id, Role::Assistant, 6..9),
+            (message_2.id, Role::User, 9..9),
+        ]
+    );
+    assert_eq!(context.read(cx).message_variants(message_1.id, cx), None);
+
+    // Attach slash command output and a tool use to the second message.
+    context.update(cx, |context, cx| {
+        let buffer = context.buffer.read(cx);
+        let range = buffer.anchor_after(6)..buffer.anchor_before(8);
+        context.insert_slash_command_output_section(
+            SlashCommandOutputSection {
+                range: range.clone(),
+                icon: IconName::Ai,
+                label: "hi".into(),
+                metadata: None,
+            },
+            cx,
+        );
+        context.insert_content(
+            Content::ToolUse {
+                range: range.clone(),
+                tool_use: LanguageModelToolUse {
+                    id: "tool-1".into(),
+                    name: "echo".into(),
+                    input: json!({}),
+                },
+            },
+            cx,
+        );
+        context.pending_tool_uses_by_id.insert(
+            "tool-1".into(),
+            PendingToolUse {
+                id: "tool-1".into(),
+                name: "echo".into(),
+                input: json!({}),
+                status: PendingToolUseStatus::Idle,
+                source_range: range,
+            },
+        );
+    });
+    assert_eq!(
+        attachments(&context, cx),
+        (vec![6..8], vec![6..8], vec![6..8])
+    );
+
+    // Forking a message keeps a copy of it, but drops the messages after it.
+    let forked_message = context
+        .update(cx, |context, cx| context.fork_message(message_1.id, cx))
+        .unwrap();
+    assert_eq!(buffer.read(cx).text(), "hello\nhi");
+    assert_eq!(
+        messages(&context, cx),
+        vec![
+            (message_0, Role::User, 0..6),
+            (forked_message, Role::Assistant, 6..8),
+        ]
+    );
+    assert_eq!(
+        context.read(cx).message_variants(forked_message, cx),
+        Some((1, 2))
+    );
+    assert_eq!(
+        attachments(&context, cx),
+        (vec![6..8], vec![6..8], vec![6..8])
+    );
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(6..8, "hey")], None, cx));
+    assert_eq!(buffer.read(cx).text(), "hello\nhey");
+
+    // Selecting the original variant restores the original messages.
+    let restored_message = context
+        .update(cx, |context, cx| {
+            context.select_message_variant(forked_message, 0, cx)
+        })
+        .unwrap();
+    assert_eq!(buffer.read(cx).text(), "hello\nhi\n");
+    let restored_messages = messages(&context, cx);
+    assert_eq!(
+        restored_messages
+            .iter()
+            .map(|(_, role, range)| (*role, range.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Role::User, 0..6),
+            (Role::Assistant, 6..9),
+            (Role::User, 9..9),
+        ]
+    );
+    assert_eq!(restored_messages[1].0, restored_message);
+    assert_eq!(
+        context.read(cx).message_variants(restored_message, cx),
+        Some((0, 2))
+    );
+    assert_eq!(
+        attachments(&context, cx),
+        (vec![6..8], vec![6..8], vec![6..8])
+    );
+
+    // Branches survive serialization.
+    let serialized_context = context.read(cx).serialize(cx);
+    let deserialized_context = cx.new_model(|cx| {
+        Context::deserialize(
+            serialized_context,
+            Default::default(),
+            registry.clone(),
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            None,
+            None,
+            cx,
+        )
+    });
+    let deserialized_buffer = deserialized_context.read(cx).buffer.clone();
+    assert_eq!(
+        deserialized_context
+            .read(cx)
+            .message_variants(restored_message, cx),
+        Some((0, 2))
+    );
+    deserialized_context.update(cx, |context, cx| {
+        context.select_message_variant(restored_message, 1, cx)
+    });
+    assert_eq!(deserialized_buffer.read(cx).text(), "hello\nhey");
+}
+
+#[gpui::test]
+fn test_message_branch_replication(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context_id = ContextId::new();
+    let ops = Rc::new(RefCell::new(Vec::new()));
+    let contexts = (0..2)
+        .map(|replica_id| {
+            let context = cx.new_model(|cx| {
+                Context::new(
+                    context_id.clone(),
+                    replica_id as ReplicaId,
+                    language::Capability::ReadWrite,
+                    registry.clone(),
+                    prompt_builder.clone(),
+                    Arc::new(SlashCommandWorkingSet::default()),
+                    Arc::new(ToolWorkingSet::default()),
+                    None,
+                    None,
+                    cx,
+                )
+            });
+            cx.subscribe(&context, {
+                let ops = ops.clone();
+                move |_, event, _| {
+                    if let ContextEvent::Operation(op) = event {
+                        ops.borrow_mut().push((replica_id, op.to_proto()));
+                    }
+                }
+            })
+            .detach();
+            context
+        })
+        .collect::<Vec<_>>();
+
+    let message_0 = contexts[0].read(cx).message_anchors[0].id;
+    let message_1 = contexts[0].update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    contexts[0].update(cx, |context, cx| {
+        context
+            .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+            .unwrap();
+        context.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "hello"), (1..1, "hi")], None, cx)
+        });
+        let buffer = context.buffer.read(cx);
+        let section = SlashCommandOutputSection {
+            range: buffer.anchor_after(6)..buffer.anchor_before(8),
+            icon: IconName::Ai,
+            label: "hi".into(),
+            metadata: None,
+        };
+        context.insert_slash_command_output_section(section, cx);
+    });
+    sync_replicas(&contexts, &ops, cx);
+    assert_eq!(contexts[1].read(cx).buffer.read(cx).text(), "hello\nhi\n");
+
+    // The branch created by forking a message on one replica appears on the other.
+    let forked_message = contexts[0]
+        .update(cx, |context, cx| context.fork_message(message_1.id, cx))
+        .unwrap();
+    sync_replicas(&contexts, &ops, cx);
+    for context in &contexts {
+        assert_eq!(context.read(cx).buffer.read(cx).text(), "hello\nhi");
+        assert_eq!(
+            context.read(cx).message_variants(forked_message, cx),
+            Some((1, 2))
+        );
+    }
+    assert_eq!(messages(&contexts[0], cx), messages(&contexts[1], cx));
+
+    // Selecting the original variant on the other replica restores its messages and
+    // slash command output on both.
+    let restored_message = contexts[1]
+        .update(cx, |context, cx| {
+            context.select_message_variant(forked_message, 0, cx)
+        })
+        .unwrap();
+    sync_replicas(&contexts, &ops, cx);
+    for context in &contexts {
+        assert_eq!(context.read(cx).buffer.read(cx).text(), "hello\nhi\n");
+        assert_eq!(
+            context.read(cx).message_variants(restored_message, cx),
+            Some((0, 2))
+        );
+        let context = context.read(cx);
+        let buffer = context.buffer.read(cx);
+        assert_eq!(
+            context
+                .slash_command_output_sections()
+                .iter()
+                .filter(|section| section.is_valid(buffer))
+                .map(|section| section.range.to_offset(buffer))
+                .collect::<Vec<_>>(),
+            vec![6..8]
+        );
+    }
+    assert_eq!(messages(&contexts[0], cx), messages(&contexts[1], cx));
+}
+
+#[gpui::test]
+fn test_message_usage(cx: &mut AppContext) {
+    let settings_store = SettingsStore::test(cx);
+    LanguageModelRegistry::test(cx);
+    cx.set_global(settings_store);
+    assistant_panel::init(cx);
+    let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+    let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+    let context = cx.new_model(|cx| {
+        Context::local(
+            registry.clone(),
+            None,
+            None,
+            prompt_builder.clone(),
+            Arc::new(SlashCommandWorkingSet::default()),
+            Arc::new(ToolWorkingSet::default()),
+            cx,
+        )
+    });
+    let buffer = context.read(cx).buffer.clone();
+
+    let message_0 = context.read(cx).message_anchors[0].id;
+    let message_1 = context.update(cx, |context, cx| {
+        context
+            .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+            .unwrap()
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "hello"), (1..1, "hi")], None, cx)
+    });
+
+    let usage = MessageUsage {
+        model: "claude-3-5-sonnet".into(),
+        tokens: TokenUsage {
+            input_tokens: 10,
+            output_tokens: 2,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+        completed_at: chrono::Utc::now(),
+    };
+    context.update(cx, |context, cx| {
+        context.update_metadata(message_1.id, cx, |metadata| {
+            metadata.usage = Some(usage.clone());
+        })
+    });
+    assert_eq!(context.read(cx).usages(cx), vec![&usage]);
+
+    // Usage is kept for messages in inactive branches, without being counted twice.
+    let forked_message = context
+        .update(cx, |context, cx| context.fork_message(message_1.id, cx))
+        .unwrap();
+    assert_eq!(context.read(cx).usages(cx), vec![&usage]);
+    context.update(cx, |context, cx| {
+        context.select_message_variant(forked_message, 0, cx)
+    });
+    assert_eq!(context.read(cx).usages(cx), vec![&usage]);
+
+    // Usage survives serialization.
+    let serialized_context = context.read(cx).serialize(cx);
+    let json = serde_json::to_string(&serialized_context).unwrap();
+    let saved_context = SavedContext::from_json(&json).unwrap();
+    assert_eq!(saved_context.usages(), vec![&usage]);
+}
+
 #[gpui::test(iterations = 100)]
 async fn test_random_context_collaboration(cx: &mut TestAppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -1603,6 +1945,53 @@ fn test_mark_cache_anchors(cx: &mut AppContext) {
     );
 }
 
+/// Delivers the operations of each replica to the other ones until none are left.
+fn sync_replicas(
+    contexts: &[Model<Context>],
+    ops: &RefCell<Vec<(usize, proto::ContextOperation)>>,
+    cx: &mut AppContext,
+) {
+    loop {
+        let pending_ops = ops.take();
+        if pending_ops.is_empty() {
+            break;
+        }
+        for (replica_id, context) in contexts.iter().enumerate() {
+            let ops_to_apply = pending_ops
+                .iter()
+                .filter(|(sender_id, _)| *sender_id != replica_id)
+                .map(|(_, op)| ContextOperation::from_proto(op.clone()).unwrap())
+                .collect::<Vec<_>>();
+            context.update(cx, |context, cx| context.apply_ops(ops_to_apply, cx));
+        }
+    }
+}
+
+/// Returns the ranges of the slash command output sections, contents and pending tool uses of the context.
+fn attachments(
+    context: &Model<Context>,
+    cx: &AppContext,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>, Vec<Range<usize>>) {
+    let context = context.read(cx);
+    let buffer = context.buffer.read(cx);
+    (
+        context
+            .slash_command_output_sections()
+            .iter()
+            .map(|section| section.range.to_offset(buffer))
+            .collect(),
+        context
+            .contents(cx)
+            .map(|content| content.range().to_offset(buffer))
+            .collect(),
+        context
+            .pending_tool_uses()
+            .into_iter()
+            .map(|tool_use| tool_use.source_range.to_offset(buffer))
+            .collect(),
+    )
+}
+
 fn messages(context: &Model<Context>, cx: &AppContext) -> Vec<(MessageId, Role, Range<usize>)> {
     context
         .read(cx)
@@ -0,0 +1,351 @@
+use crate::{MessageId, MessageMetadata, MessageStatus, SavedContext, SavedMessage};
+use anyhow::{anyhow, Result};
+use language_model::Role;
+use serde_json::Value;
+
+/// Renders a saved context as Markdown, with a heading for each message.
+pub fn export_markdown(context: &SavedContext) -> String {
+    let mut markdown = String::new();
+    if !context.summary.is_empty() {
+        markdown.push_str(&format!("# {}\n\n", context.summary.trim()));
+    }
+
+    for (ix, message) in context.messages.iter().enumerate() {
+        let end = context
+            .messages
+            .get(ix + 1)
+            .map_or(context.text.len(), |next| next.start);
+        let Some(text) = context.text.get(message.start..end) else {
+            continue;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let heading = match message.metadata.role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => "System",
+        };
+        markdown.push_str(&format!("## {heading}\n\n{text}\n\n"));
+    }
+
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+    markdown
+}
+
+/// Imports a conversation from Markdown, or from the JSON of a saved context or
+/// of another tool's conversation export.
+pub fn import_context(content: &str, is_markdown: bool) -> Result<SavedContext> {
+    if is_markdown {
+        return import_markdown(content);
+    }
+
+    if let Ok(context) = SavedContext::from_json(content) {
+        return Ok(context);
+    }
+
+    let json = serde_json::from_str::<Value>(content)?;
+    import_json(&json)
+}
+
+/// Imports Markdown with `## User`, `## Assistant` and `## System` headings, as
+/// produced by [`export_markdown`]. A leading `#` heading becomes the title.
+fn import_markdown(content: &str) -> Result<SavedContext> {
+    let mut title = None;
+    let mut messages = Vec::<(Role, String)>::new();
+    for line in content.lines() {
+        let role = line.strip_prefix("## ").and_then(|heading| {
+            match heading.trim().to_lowercase().as_str() {
+                "user" | "you" => Some(Role::User),
+                "assistant" => Some(Role::Assistant),
+                "system" => Some(Role::System),
+                _ => None,
+            }
+        });
+
+        if let Some(role) = role {
+            messages.push((role, String::new()));
+        } else if let Some((_, text)) = messages.last_mut() {
+            text.push_str(line);
+            text.push('\n');
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            title.get_or_insert_with(|| heading.trim().to_string());
+        }
+    }
+
+    if messages.is_empty() {
+        return Err(anyhow!("no messages found in Markdown"));
+    }
+
+    Ok(saved_context(title, messages))
+}
+
+/// Imports the conversation formats used by common chat tools:
+///
+/// - A list of `{ role, content }` messages, either on its own or in a `messages`
+///   field, as used by the OpenAI and Anthropic APIs.
+/// - A conversation from a ChatGPT export, which stores messages in a `mapping` tree.
+/// - A conversation from a Claude export, which stores messages in `chat_messages`.
+fn import_json(json: &Value) -> Result<SavedContext> {
+    // Exports containing several conversations only import the first one.
+    let conversation = match json {
+        Value::Array(items) if items.iter().all(is_conversation) => items
+            .first()
+            .ok_or_else(|| anyhow!("no conversations found"))?,
+        _ => json,
+    };
+
+    let title = ["title", "name", "summary"]
+        .iter()
+        .find_map(|key| conversation.get(key)?.as_str())
+        .map(|title| title.to_string());
+
+    let mut messages = Vec::new();
+    if let Some(mapping) = conversation.get("mapping").and_then(Value::as_object) {
+        // ChatGPT stores messages as a tree, so follow the current branch back to its root.
+        let mut node_id = conversation
+            .get("current_node")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("conversation has no current node"))?;
+        while let Some(node) = mapping.get(node_id) {
+            if let Some(message) = node.get("message") {
+                let role = message.pointer("/author/role").and_then(Value::as_str);
+                let text = message
+                    .pointer("/content/parts")
+                    .map(content_text)
+                    .unwrap_or_default();
+                if let Some(role) = role.and_then(parse_role) {
+                    messages.push((role, text));
+                }
+            }
+            match node.get("parent").and_then(Value::as_str) {
+                Some(parent) => node_id = parent,
+                None => break,
+            }
+        }
+        messages.reverse();
+    } else if let Some(chat_messages) = conversation.get("chat_messages").and_then(Value::as_array)
+    {
+        for message in chat_messages {
+            let role = message.get("sender").and_then(Value::as_str);
+            let text = message
+                .get("text")
+                .or_else(|| message.get("content"))
+                .map(content_text)
+                .unwrap_or_default();
+            if let Some(role) = role.and_then(parse_role) {
+                messages.push((role, text));
+            }
+        }
+    } else {
+        if let Some(system) = conversation.get("system") {
+            messages.push((Role::System, content_text(system)));
+        }
+        let items = conversation
+            .get("messages")
+            .unwrap_or(conversation)
+            .as_array()
+            .ok_or_else(|| anyhow!("unrecognized conversation format"))?;
+        for message in items {
+            let role = message.get("role").and_then(Value::as_str);
+            let text = message.get("content").map(content_text).unwrap_or_default();
+            if let Some(role) = role.and_then(parse_role) {
+                messages.push((role, text));
+            }
+        }
+    }
+
+    messages.retain(|(_, text)| !text.trim().is_empty());
+    if messages.is_empty() {
+        return Err(anyhow!("no messages found in conversation"));
+    }
+
+    Ok(saved_context(title, messages))
+}
+
+fn is_conversation(value: &Value) -> bool {
+    value.get("mapping").is_some() || value.get("chat_messages").is_some()
+}
+
+fn parse_role(role: &str) -> Option<Role> {
+    match role {
+        "user" | "human" => Some(Role::User),
+        "assistant" | "model" | "ai" => Some(Role::Assistant),
+        "system" | "developer" => Some(Role::System),
+        _ => None,
+    }
+}
+
+/// Extracts the text of a message's content, which is either a string or a
+/// list of strings and content blocks.
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.as_str()),
+                Value::Object(_) => part.get("text")?.as_str(),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn saved_context(title: Option<String>, messages: Vec<(Role, String)>) -> SavedContext {
+    let title = title.filter(|title| !title.is_empty()).unwrap_or_else(|| {
+        messages
+            .iter()
+            .find(|(role, _)| *role == Role::User)
+            .or(messages.first())
+            .and_then(|(_, text)| text.lines().find(|line| !line.trim().is_empty()))
+            .map(|line| line.trim().chars().take(60).collect())
+            .unwrap_or_else(|| "Imported Context".into())
+    });
+
+    let mut text = String::new();
+    let mut saved_messages = Vec::with_capacity(messages.len());
+    for (ix, (role, content)) in messages.into_iter().enumerate() {
+        if ix > 0 {
+            text.push('\n');
+        }
+        let timestamp = clock::Lamport {
+            replica_id: 0,
+            value: ix as u32,
+        };
+        saved_messages.push(SavedMessage {
+            id: MessageId(timestamp),
+            start: text.len(),
+            metadata: MessageMetadata {
+                role,
+                status: MessageStatus::Done,
+                timestamp,
+                cache: None,
+            },
+        });
+        text.push_str(content.trim());
+    }
+
+    SavedContext {
+        id: None,
+        zed: "context".into(),
+        version: SavedContext::VERSION.into(),
+        text,
+        messages: saved_messages,
+        message_branches: Vec::new(),
+        models: Vec::new(),
+        summary: title,
+        slash_command_output_sections: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let context = saved_context(
+            Some("Greetings".into()),
+            vec![
+                (Role::System, "Be brief.".into()),
+                (Role::User, "Hello!\n\nHow are you?".into()),
+                (Role::Assistant, "Fine, thanks.".into()),
+            ],
+        );
+        assert_eq!(
+            context.text,
+            "Be brief.\nHello!\n\nHow are you?\nFine, thanks."
+        );
+
+        let markdown = export_markdown(&context);
+        assert_eq!(
+            markdown,
+            "# Greetings\n\n## System\n\nBe brief.\n\n## User\n\nHello!\n\nHow are you?\n\n## Assistant\n\nFine, thanks.\n"
+        );
+
+        let imported = import_context(&markdown, true).unwrap();
+        assert_eq!(imported.summary, "Greetings");
+        assert_eq!(imported.text, context.text);
+        assert_eq!(imported.messages, context.messages);
+    }
+
+    #[test]
+    fn test_import_json() {
+        let imported = import_context(
+            &json!({
+                "system": "Be brief.",
+                "messages": [
+                    { "role": "user", "content": "What is Rust?" },
+                    {
+                        "role": "assistant",
+                        "content": [{ "type": "text", "text": "A programming language." }]
+                    },
+                    { "role": "tool", "content": "ignored" },
+                ]
+            })
+            .to_string(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(imported.summary, "What is Rust?");
+        assert_eq!(
+            imported.text,
+            "Be brief.\nWhat is Rust?\nA programming language."
+        );
+        assert_eq!(
+            imported
+                .messages
+                .iter()
+                .map(|message| (message.metadata.role, message.start))
+                .collect::<Vec<_>>(),
+            vec![(Role::System, 0), (Role::User, 10), (Role::Assistant, 24)]
+        );
+
+        let imported = import_context(
+            &json!([{
+                "title": "Greetings",
+                "current_node": "c",
+                "mapping": {
+                    "a": { "message": null, "parent": null },
+                    "b": {
+                        "message": { "author": { "role": "user" }, "content": { "parts": ["Hi"] } },
+                        "parent": "a"
+                    },
+                    "c": {
+                        "message": { "author": { "role": "assistant" }, "content": { "parts": ["Hello"] } },
+                        "parent": "b"
+                    }
+                }
+            }])
+            .to_string(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(imported.summary, "Greetings");
+        assert_eq!(imported.text, "Hi\nHello");
+
+        let imported = import_context(
+            &json!({
+                "name": "Claude",
+                "chat_messages": [
+                    { "sender": "human", "text": "Hi" },
+                    { "sender": "assistant", "text": "Hello" },
+                ]
+            })
+            .to_string(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(imported.summary, "Claude");
+        assert_eq!(imported.text, "Hi\nHello");
+
+        assert!(import_context("{}", false).is_err());
+    }
+}
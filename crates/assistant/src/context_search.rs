@@ -0,0 +1,455 @@
+use crate::{MessageUsage, SavedContext, SavedContextMetadata};
+use chrono::{DateTime, Local, NaiveDate};
+use collections::{BTreeMap, HashMap, HashSet};
+use language_model::Role;
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// The number of bytes of context to show around the first match in a preview.
+const PREVIEW_CONTEXT_LEN: usize = 40;
+const PREVIEW_LEN: usize = 160;
+
+/// A query over the saved contexts, as typed into the history view.
+///
+/// Besides plain search terms, the query can contain `model:<name>`,
+/// `after:<yyyy-mm-dd>` and `before:<yyyy-mm-dd>` filters.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContextSearchQuery {
+    pub terms: Vec<String>,
+    pub model: Option<String>,
+    pub after: Option<NaiveDate>,
+    pub before: Option<NaiveDate>,
+}
+
+impl ContextSearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut result = Self::default();
+        for word in query.split_whitespace() {
+            if let Some(model) = word.strip_prefix("model:") {
+                result.model = Some(model.to_lowercase()).filter(|model| !model.is_empty());
+            } else if let Some(date) = word
+                .strip_prefix("after:")
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            {
+                result.after = Some(date);
+            } else if let Some(date) = word
+                .strip_prefix("before:")
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            {
+                result.before = Some(date);
+            } else {
+                result
+                    .terms
+                    .extend(words(word).map(|(_, word)| word.to_lowercase()));
+            }
+        }
+        result
+    }
+
+    /// Returns the search terms joined back together, for matching against titles.
+    pub fn text(&self) -> String {
+        self.terms.join(" ")
+    }
+
+    fn matches_filters(&self, mtime: &DateTime<Local>, models: &[String]) -> bool {
+        let date = mtime.date_naive();
+        if self.after.map_or(false, |after| date < after) {
+            return false;
+        }
+        if self.before.map_or(false, |before| date > before) {
+            return false;
+        }
+        if let Some(model) = self.model.as_ref() {
+            return models
+                .iter()
+                .any(|candidate| candidate.to_lowercase().contains(model));
+        }
+        true
+    }
+}
+
+/// A saved context that matched a search, along with a preview of the match.
+#[derive(Debug, Clone)]
+pub struct ContextSearchMatch {
+    pub metadata: SavedContextMetadata,
+    pub preview: Option<ContextSearchPreview>,
+}
+
+/// An excerpt of the message that matched a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSearchPreview {
+    pub role: Role,
+    pub text: String,
+    /// The ranges of `text` that matched the search terms.
+    pub highlights: Vec<Range<usize>>,
+}
+
+struct IndexedContext {
+    mtime: DateTime<Local>,
+    models: Vec<String>,
+    messages: Vec<(Role, String)>,
+    usages: Vec<MessageUsage>,
+}
+
+/// An inverted index over the messages of the saved contexts, so searching the
+/// history doesn't need to read every context from disk.
+#[derive(Default)]
+pub struct ContextSearchIndex {
+    contexts: HashMap<PathBuf, IndexedContext>,
+    terms: BTreeMap<String, HashSet<PathBuf>>,
+}
+
+impl ContextSearchIndex {
+    /// Returns whether the index contains the context at the given path, as of the given mtime.
+    pub fn contains(&self, path: &Path, mtime: &DateTime<Local>) -> bool {
+        self.contexts
+            .get(path)
+            .map_or(false, |context| context.mtime == *mtime)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, mtime: DateTime<Local>, context: &SavedContext) {
+        self.remove(&path);
+
+        let mut messages = Vec::with_capacity(context.messages.len());
+        for (ix, message) in context.messages.iter().enumerate() {
+            let end = context
+                .messages
+                .get(ix + 1)
+                .map_or(context.text.len(), |next| next.start);
+            if let Some(text) = context.text.get(message.start..end) {
+                let text = text.trim();
+                if !text.is_empty() {
+                    messages.push((message.metadata.role, text.to_string()));
+                }
+            }
+        }
+
+        let texts = messages
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .chain([context.summary.as_str()]);
+        for text in texts {
+            for (_, word) in words(text) {
+                self.terms
+                    .entry(word.to_lowercase())
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+
+        self.contexts.insert(
+            path,
+            IndexedContext {
+                mtime,
+                models: context.models.clone(),
+                messages,
+                usages: context.usages().into_iter().cloned().collect(),
+            },
+        );
+    }
+
+    /// Returns the usage of the completions in the context at the given path.
+    pub fn usages(&self, path: &Path) -> &[MessageUsage] {
+        self.contexts
+            .get(path)
+            .map_or(&[], |context| context.usages.as_slice())
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        if self.contexts.remove(path).is_some() {
+            self.terms.retain(|_, paths| {
+                paths.remove(path);
+                !paths.is_empty()
+            });
+        }
+    }
+
+    /// Removes every context that isn't in the given list of paths.
+    pub fn retain(&mut self, paths: &HashSet<PathBuf>) {
+        let removed = self
+            .contexts
+            .keys()
+            .filter(|path| !paths.contains(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed {
+            self.remove(&path);
+        }
+    }
+
+    /// Returns whether the given context passes the query's filters, regardless of its terms.
+    pub fn matches_filters(
+        &self,
+        query: &ContextSearchQuery,
+        metadata: &SavedContextMetadata,
+    ) -> bool {
+        let models = self
+            .contexts
+            .get(&metadata.path)
+            .map_or(&[][..], |context| context.models.as_slice());
+        query.matches_filters(&metadata.mtime, models)
+    }
+
+    /// Returns the contexts among `metadata` that match the query, most relevant first.
+    pub fn search(
+        &self,
+        query: &ContextSearchQuery,
+        metadata: &[SavedContextMetadata],
+    ) -> Vec<ContextSearchMatch> {
+        let candidates = self.candidates(&query.terms);
+        let mut matches = Vec::new();
+        for metadata in metadata {
+            if !self.matches_filters(query, metadata) {
+                continue;
+            }
+            if candidates
+                .as_ref()
+                .map_or(false, |candidates| !candidates.contains(&metadata.path))
+            {
+                continue;
+            }
+
+            let mut score = 0;
+            let mut preview = None;
+            let messages = self
+                .contexts
+                .get(&metadata.path)
+                .map_or(&[][..], |context| context.messages.as_slice());
+            for (role, text) in messages {
+                let highlights = matching_words(text, &query.terms);
+                if highlights.is_empty() {
+                    continue;
+                }
+                score += highlights.len();
+                if preview.is_none() {
+                    preview = Some(build_preview(*role, text, &highlights));
+                }
+            }
+            matches.push((
+                score,
+                ContextSearchMatch {
+                    metadata: metadata.clone(),
+                    preview,
+                },
+            ));
+        }
+
+        // Sorting is stable, so contexts with the same score stay in order of recency.
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.into_iter().map(|(_, mat)| mat).collect()
+    }
+
+    /// Returns the paths of the contexts containing every term, or `None` if there are no terms.
+    fn candidates(&self, terms: &[String]) -> Option<HashSet<PathBuf>> {
+        let mut result: Option<HashSet<PathBuf>> = None;
+        for term in terms {
+            let mut paths = HashSet::default();
+            for (word, word_paths) in self.terms.range(term.clone()..) {
+                if !word.starts_with(term.as_str()) {
+                    break;
+                }
+                paths.extend(word_paths.iter().cloned());
+            }
+            result = Some(match result {
+                Some(result) => result.intersection(&paths).cloned().collect(),
+                None => paths,
+            });
+        }
+        result
+    }
+}
+
+/// Returns the alphanumeric words in the given text, along with their byte ranges.
+fn words(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while chars.next_if(|(_, c)| !c.is_alphanumeric()).is_some() {}
+        let (start, _) = chars.next()?;
+        let mut end = text.len();
+        while let Some((ix, c)) = chars.peek() {
+            if !c.is_alphanumeric() {
+                end = *ix;
+                break;
+            }
+            chars.next();
+        }
+        Some((start..end, &text[start..end]))
+    })
+}
+
+fn matching_words(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    words(text)
+        .filter(|(_, word)| {
+            let word = word.to_lowercase();
+            terms.iter().any(|term| word.starts_with(term.as_str()))
+        })
+        .map(|(range, _)| range)
+        .collect()
+}
+
+fn build_preview(role: Role, text: &str, highlights: &[Range<usize>]) -> ContextSearchPreview {
+    let first_match = highlights[0].start;
+    let mut start = first_match.saturating_sub(PREVIEW_CONTEXT_LEN);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + PREVIEW_LEN).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    ContextSearchPreview {
+        role,
+        text: text[start..end].replace('\n', " "),
+        highlights: highlights
+            .iter()
+            .filter(|range| range.start >= start && range.end <= end)
+            .map(|range| range.start - start..range.end - start)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageId, MessageMetadata, MessageStatus, SavedMessage};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            ContextSearchQuery::parse("model:GPT-4o rust  after:2024-05-01 lifetimes before:nope"),
+            ContextSearchQuery {
+                terms: vec![
+                    "rust".into(),
+                    "lifetimes".into(),
+                    "before".into(),
+                    "nope".into()
+                ],
+                model: Some("gpt-4o".into()),
+                after: NaiveDate::from_ymd_opt(2024, 5, 1),
+                before: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let mut index = ContextSearchIndex::default();
+        let rust = saved_context(
+            "Rust lifetimes",
+            &[
+                (Role::User, "How do lifetimes work in Rust?\n"),
+                (
+                    Role::Assistant,
+                    "Lifetimes describe how long references are valid.",
+                ),
+            ],
+            &["gpt-4o"],
+        );
+        let python = saved_context(
+            "Python typing",
+            &[(Role::User, "How do I annotate a Python function?")],
+            &["claude-3-5-sonnet"],
+        );
+        let rust_metadata = metadata("Rust lifetimes", 2024, 6, 1);
+        let python_metadata = metadata("Python typing", 2024, 4, 1);
+        index.insert(rust_metadata.path.clone(), rust_metadata.mtime, &rust);
+        index.insert(python_metadata.path.clone(), python_metadata.mtime, &python);
+        let all_metadata = [rust_metadata.clone(), python_metadata.clone()];
+
+        let search = |query: &str| {
+            index
+                .search(&ContextSearchQuery::parse(query), &all_metadata)
+                .into_iter()
+                .map(|mat| (mat.metadata.title, mat.preview))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search("lifetime"),
+            vec![(
+                "Rust lifetimes".to_string(),
+                Some(ContextSearchPreview {
+                    role: Role::User,
+                    text: "How do lifetimes work in Rust?".into(),
+                    highlights: vec![7..16],
+                })
+            )]
+        );
+        assert_eq!(
+            search("how"),
+            vec![
+                (
+                    "Rust lifetimes".to_string(),
+                    Some(ContextSearchPreview {
+                        role: Role::User,
+                        text: "How do lifetimes work in Rust?".into(),
+                        highlights: vec![0..3],
+                    })
+                ),
+                (
+                    "Python typing".to_string(),
+                    Some(ContextSearchPreview {
+                        role: Role::User,
+                        text: "How do I annotate a Python function?".into(),
+                        highlights: vec![0..3],
+                    })
+                )
+            ]
+        );
+        assert_eq!(search("how model:claude").len(), 1);
+        assert_eq!(search("how after:2024-05-01")[0].0, "Rust lifetimes");
+        assert_eq!(search("how before:2024-05-01")[0].0, "Python typing");
+        assert_eq!(search("how rust python"), vec![]);
+
+        index.remove(&rust_metadata.path);
+        assert_eq!(search("lifetimes"), vec![]);
+    }
+
+    fn saved_context(summary: &str, messages: &[(Role, &str)], models: &[&str]) -> SavedContext {
+        let mut text = String::new();
+        let mut saved_messages = Vec::new();
+        for (ix, (role, content)) in messages.iter().enumerate() {
+            let timestamp = clock::Lamport {
+                replica_id: 0,
+                value: ix as u32,
+            };
+            saved_messages.push(SavedMessage {
+                id: MessageId(timestamp),
+                start: text.len(),
+                metadata: MessageMetadata {
+                    role: *role,
+                    status: MessageStatus::Done,
+                    timestamp,
+                    cache: None,
+                },
+            });
+            text.push_str(content);
+        }
+        SavedContext {
+            id: None,
+            zed: "context".into(),
+            version: SavedContext::VERSION.into(),
+            text,
+            messages: saved_messages,
+            message_branches: Vec::new(),
+            models: models.iter().map(|model| model.to_string()).collect(),
+            summary: summary.into(),
+            slash_command_output_sections: Vec::new(),
+        }
+    }
+
+    fn metadata(title: &str, year: i32, month: u32, day: u32) -> SavedContextMetadata {
+        SavedContextMetadata {
+            title: title.into(),
+            path: PathBuf::from(format!("/contexts/{title} - 1.zed.json")),
+            mtime: Local.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap(),
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use crate::slash_command::context_server_command;
 use crate::{
-    prompts::PromptBuilder, slash_command_working_set::SlashCommandWorkingSet, Context,
-    ContextEvent, ContextId, ContextOperation, ContextVersion, SavedContext, SavedContextMetadata,
+    context_export::import_context,
+    context_search::{ContextSearchIndex, ContextSearchMatch, ContextSearchQuery},
+    prompts::PromptBuilder,
+    slash_command_working_set::SlashCommandWorkingSet,
+    usage_report::ContextUsages,
+    Context, ContextEvent, ContextId, ContextOperation, ContextVersion, SavedContext,
+    SavedContextMetadata,
 };
 use crate::{tools, SlashCommandId, ToolId, ToolWorkingSet};
 use anyhow::{anyhow, Context as _, Result};
 use client::{proto, telemetry::Telemetry, Client, TypedEnvelope};
 use clock::ReplicaId;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use context_servers::manager::ContextServerManager;
 use context_servers::ContextServerFactoryRegistry;
 use fs::Fs;
@@ -17,6 +22,7 @@ use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
 use language::LanguageRegistry;
+use parking_lot::RwLock;
 use paths::contexts_dir;
 use project::Project;
 use regex::Regex;
@@ -48,6 +54,7 @@ pub struct RemoteContextMetadata {
 pub struct ContextStore {
     contexts: Vec<ContextHandle>,
     contexts_metadata: Vec<SavedContextMetadata>,
+    search_index: Arc<RwLock<ContextSearchIndex>>,
     context_server_manager: Model<ContextServerManager>,
     context_server_slash_command_ids: HashMap<Arc<str>, Vec<SlashCommandId>>,
     context_server_tool_ids: HashMap<Arc<str>, Vec<ToolId>>,
@@ -117,6 +124,7 @@ impl ContextStore {
                 let mut this = Self {
                     contexts: Vec::new(),
                     contexts_metadata: Vec::new(),
+                    search_index: Default::default(),
                     context_server_manager,
                     context_server_slash_command_ids: HashMap::default(),
                     context_server_tool_ids: HashMap::default(),
@@ -705,33 +713,123 @@ impl ContextStore {
         .detach_and_log_err(cx);
     }
 
-    pub fn search(&self, query: String, cx: &AppContext) -> Task<Vec<SavedContextMetadata>> {
+    /// Returns the usage of the completions in every open and saved context.
+    pub fn context_usages(&self, cx: &AppContext) -> Vec<ContextUsages> {
+        let mut result = Vec::new();
+        let mut open_paths = HashSet::default();
+        for context in self.contexts.iter().filter_map(ContextHandle::upgrade) {
+            let context = context.read(cx);
+            if let Some(path) = context.path() {
+                open_paths.insert(path.to_path_buf());
+            }
+            result.push(ContextUsages {
+                title: context
+                    .summary()
+                    .map(|summary| summary.text.clone())
+                    .unwrap_or_default()
+                    .into(),
+                usages: context.usages(cx).into_iter().cloned().collect(),
+            });
+        }
+
+        let search_index = self.search_index.read();
+        for metadata in &self.contexts_metadata {
+            if !open_paths.contains(&metadata.path) {
+                result.push(ContextUsages {
+                    title: metadata.title.clone().into(),
+                    usages: search_index.usages(&metadata.path).to_vec(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Searches the titles and message contents of the saved contexts.
+    ///
+    /// See [`ContextSearchQuery`] for the filters supported in the query.
+    pub fn search(&self, query: String, cx: &AppContext) -> Task<Vec<ContextSearchMatch>> {
         let metadata = self.contexts_metadata.clone();
+        let search_index = self.search_index.clone();
         let executor = cx.background_executor().clone();
         cx.background_executor().spawn(async move {
-            if query.is_empty() {
-                metadata
-            } else {
-                let candidates = metadata
+            let query = ContextSearchQuery::parse(&query);
+            let content_matches = search_index.read().search(&query, &metadata);
+            if query.terms.is_empty() {
+                return content_matches;
+            }
+
+            // Titles are matched fuzzily, so they can match even when the contents don't.
+            let candidates = metadata
+                .iter()
+                .enumerate()
+                .map(|(id, metadata)| StringMatchCandidate::new(id, metadata.title.clone()))
+                .collect::<Vec<_>>();
+            let title_matches = fuzzy::match_strings(
+                &candidates,
+                &query.text(),
+                false,
+                100,
+                &Default::default(),
+                executor,
+            )
+            .await;
+
+            let mut matches = Vec::new();
+            for title_match in title_matches {
+                let metadata = &metadata[title_match.candidate_id];
+                if let Some(content_match) = content_matches
                     .iter()
-                    .enumerate()
-                    .map(|(id, metadata)| StringMatchCandidate::new(id, metadata.title.clone()))
-                    .collect::<Vec<_>>();
-                let matches = fuzzy::match_strings(
-                    &candidates,
-                    &query,
-                    false,
-                    100,
-                    &Default::default(),
-                    executor,
-                )
-                .await;
+                    .find(|content_match| content_match.metadata.path == metadata.path)
+                {
+                    matches.push(content_match.clone());
+                } else if search_index.read().matches_filters(&query, metadata) {
+                    matches.push(ContextSearchMatch {
+                        metadata: metadata.clone(),
+                        preview: None,
+                    });
+                }
+            }
+            for content_match in content_matches {
+                if !matches
+                    .iter()
+                    .any(|existing| existing.metadata.path == content_match.metadata.path)
+                {
+                    matches.push(content_match);
+                }
+            }
+            matches
+        })
+    }
 
-                matches
-                    .into_iter()
-                    .map(|mat| metadata[mat.candidate_id].clone())
-                    .collect()
+    /// Imports a conversation from the file at the given path into the saved
+    /// contexts, returning the path of the new saved context.
+    pub fn import_context(&self, path: PathBuf, cx: &ModelContext<Self>) -> Task<Result<PathBuf>> {
+        let fs = self.fs.clone();
+        cx.background_executor().spawn(async move {
+            let content = fs.load(&path).await?;
+            let is_markdown = path.extension().map_or(false, |extension| {
+                extension == "md" || extension == "markdown"
+            });
+            let mut context = import_context(&content, is_markdown)
+                .with_context(|| format!("failed to import {path:?}"))?;
+            context.id = Some(ContextId::new());
+
+            fs.create_dir(contexts_dir().as_ref()).await?;
+            let title = context.summary.replace(['/', '\\'], "-");
+            let mut discriminant = 1;
+            let mut new_path;
+            loop {
+                new_path =
+                    contexts_dir().join(format!("{} - {}.zed.json", title.trim(), discriminant));
+                if fs.is_file(&new_path).await {
+                    discriminant += 1;
+                } else {
+                    break;
+                }
             }
+            fs.atomic_write(new_path.clone(), serde_json::to_string(&context)?)
+                .await?;
+            Ok(new_path)
         })
     }
 
@@ -777,6 +875,35 @@ impl ContextStore {
             }
             contexts.sort_unstable_by_key(|context| Reverse(context.mtime));
 
+            // Only contexts that changed since they were last indexed need to be read.
+            let search_index = this.update(&mut cx, |this, _| this.search_index.clone())?;
+            for context in &contexts {
+                if search_index.read().contains(&context.path, &context.mtime) {
+                    continue;
+                }
+
+                let Some(text) = fs.load(&context.path).await.log_err() else {
+                    continue;
+                };
+                let saved_context = cx
+                    .background_executor()
+                    .spawn(async move { SavedContext::from_json(&text) })
+                    .await;
+                if let Some(saved_context) = saved_context.log_err() {
+                    search_index.write().insert(
+                        context.path.clone(),
+                        context.mtime,
+                        &saved_context,
+                    );
+                }
+            }
+            search_index.write().retain(
+                &contexts
+                    .iter()
+                    .map(|context| context.path.clone())
+                    .collect::<HashSet<_>>(),
+            );
+
             this.update(&mut cx, |this, cx| {
                 this.contexts_metadata = contexts;
                 cx.notify();
@@ -31,6 +31,7 @@ pub mod file_command;
 pub mod now_command;
 pub mod project_command;
 pub mod prompt_command;
+pub mod repo_map_command;
 pub mod search_command;
 pub mod selection_command;
 pub mod streaming_example_command;
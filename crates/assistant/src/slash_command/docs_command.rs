@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
@@ -8,12 +8,13 @@ use assistant_slash_command::{
     ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
     SlashCommandResult,
 };
+use futures::FutureExt;
 use gpui::{AppContext, BackgroundExecutor, Model, Task, WeakView};
 use indexed_docs::{
-    DocsDotRsProvider, IndexedDocsRegistry, IndexedDocsStore, LocalRustdocProvider, PackageName,
-    ProviderId,
+    DocsDotRsProvider, IndexedDocsRegistry, IndexedDocsStore, LocalNpmDocsProvider,
+    LocalPythonDocsProvider, LocalRustdocProvider, PackageName, ProviderId,
 };
-use language::{BufferSnapshot, LspAdapterDelegate};
+use language::{BufferSnapshot, LanguageName, LspAdapterDelegate};
 use project::{Project, ProjectPath};
 use ui::prelude::*;
 use util::{maybe, ResultExt};
@@ -93,6 +94,72 @@ impl DocsSlashCommand {
         }
     }
 
+    /// Ensures that the indexed doc providers for the Python and npm packages
+    /// installed in the project are registered, if the project uses them.
+    fn ensure_package_doc_providers_are_registered(
+        &self,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut AppContext,
+    ) {
+        let indexed_docs_registry = IndexedDocsRegistry::global(cx);
+        let needs_python = indexed_docs_registry
+            .get_provider_store(LocalPythonDocsProvider::id())
+            .is_none();
+        let needs_npm = indexed_docs_registry
+            .get_provider_store(LocalNpmDocsProvider::id())
+            .is_none();
+        if !needs_python && !needs_npm {
+            return;
+        }
+
+        let Some(project) = workspace
+            .and_then(|workspace| workspace.upgrade())
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return;
+        };
+        let Some(worktree) = project.read(cx).worktrees(cx).next() else {
+            return;
+        };
+        let fs = project.read(cx).fs().clone();
+        let worktree = worktree.read(cx);
+        let project_root = worktree.abs_path().to_path_buf();
+
+        let is_python_project = [
+            "pyproject.toml",
+            "setup.py",
+            "requirements.txt",
+            "uv.lock",
+            "poetry.lock",
+            "Pipfile",
+        ]
+        .into_iter()
+        .any(|path| worktree.entry_for_path(path).is_some());
+        if needs_python && is_python_project {
+            let toolchain =
+                project
+                    .read(cx)
+                    .active_toolchain(worktree.id(), LanguageName::new("Python"), cx);
+            let interpreter = cx
+                .background_executor()
+                .spawn(async move {
+                    let toolchain = toolchain.await?;
+                    Some(PathBuf::from(toolchain.path.as_ref()))
+                })
+                .shared();
+            indexed_docs_registry.register_provider(Box::new(LocalPythonDocsProvider::new(
+                fs.clone(),
+                project_root.clone(),
+                interpreter,
+            )));
+        }
+
+        if needs_npm && worktree.entry_for_path("package.json").is_some() {
+            indexed_docs_registry
+                .register_provider(Box::new(LocalNpmDocsProvider::new(fs, project_root)));
+        }
+    }
+
     /// Runs just-in-time indexing for a given package, in case the slash command
     /// is run without any entries existing in the index.
     fn run_just_in_time_indexing(
@@ -167,7 +234,8 @@ impl SlashCommand for DocsSlashCommand {
         workspace: Option<WeakView<Workspace>>,
         cx: &mut WindowContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
-        self.ensure_rust_doc_providers_are_registered(workspace, cx);
+        self.ensure_rust_doc_providers_are_registered(workspace.clone(), cx);
+        self.ensure_package_doc_providers_are_registered(workspace, cx);
 
         let indexed_docs_registry = IndexedDocsRegistry::global(cx);
         let args = DocsSlashCommandArgs::parse(arguments);
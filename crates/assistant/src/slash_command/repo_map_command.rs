@@ -0,0 +1,581 @@
+use super::file_command::FileCommandMetadata;
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommand, SlashCommandOutput, SlashCommandOutputSection,
+    SlashCommandResult,
+};
+use collections::{HashMap, HashSet};
+use fs::Fs;
+use futures::{stream, StreamExt};
+use gpui::{AsyncWindowContext, Task, WeakView};
+use language::{Buffer, BufferSnapshot, LanguageRegistry, LineEnding, LspAdapterDelegate};
+use parking_lot::Mutex;
+use project::ProjectPath;
+use rope::Rope;
+use std::{
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+    time::SystemTime,
+};
+use ui::{IconName, WindowContext};
+use workspace::Workspace;
+
+/// The number of tokens the map may use when no budget is given.
+const DEFAULT_TOKEN_BUDGET: usize = 1024;
+
+/// Files larger than this are left out of the map.
+const MAX_FILE_SIZE: u64 = 512 * 1024;
+
+/// Lists the most relevant definitions in the project, ranked by how often
+/// they are referenced from the files in the current context.
+///
+/// The outline of each file is cached and only recomputed when its modification
+/// time changes, so running the command again is cheap.
+#[derive(Default)]
+pub(crate) struct RepoMapSlashCommand {
+    cache: Arc<Mutex<HashMap<ProjectPath, Arc<FileTags>>>>,
+}
+
+/// The definitions and references found in a single file.
+#[derive(Debug, Default)]
+struct FileTags {
+    mtime: Option<SystemTime>,
+    definitions: Vec<Definition>,
+    /// The number of times each identifier occurs in the file.
+    references: HashMap<Arc<str>, usize>,
+}
+
+#[derive(Debug)]
+struct Definition {
+    name: Arc<str>,
+    /// The text of the outline item, e.g. `pub fn name`.
+    text: String,
+    depth: usize,
+    /// The index of the enclosing definition, if any.
+    parent: Option<usize>,
+}
+
+impl FileTags {
+    fn new(snapshot: &BufferSnapshot, mtime: Option<SystemTime>) -> Self {
+        let mut definitions = Vec::new();
+        let mut ancestors = Vec::<usize>::new();
+        if let Some(outline) = snapshot.outline(None) {
+            for item in outline.items {
+                let Some(name) = item
+                    .name_ranges
+                    .last()
+                    .and_then(|range| item.text.get(range.clone()))
+                    .and_then(|name| identifiers(name).last())
+                    .map(Arc::<str>::from)
+                else {
+                    continue;
+                };
+
+                ancestors.retain(|ix| definitions[*ix].depth < item.depth);
+                definitions.push(Definition {
+                    name,
+                    text: item.text,
+                    depth: item.depth,
+                    parent: ancestors.last().copied(),
+                });
+                ancestors.push(definitions.len() - 1);
+            }
+        }
+
+        let mut references = HashMap::default();
+        for identifier in identifiers(&snapshot.text()) {
+            *references.entry(identifier.into()).or_default() += 1;
+        }
+
+        Self {
+            mtime,
+            definitions,
+            references,
+        }
+    }
+}
+
+/// Splits the text into the identifiers it contains.
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.len() > 1 && word.chars().next().map_or(false, |c| !c.is_ascii_digit()))
+}
+
+impl SlashCommand for RepoMapSlashCommand {
+    fn name(&self) -> String {
+        "repomap".into()
+    }
+
+    fn description(&self) -> String {
+        "Insert a map of the project's most relevant symbols".into()
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::ListTree
+    }
+
+    fn menu_text(&self) -> String {
+        self.description()
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _arguments: &[String],
+        _cancel: Arc<AtomicBool>,
+        _workspace: Option<WeakView<Workspace>>,
+        _cx: &mut WindowContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn accepts_arguments(&self) -> bool {
+        true
+    }
+
+    fn run(
+        self: Arc<Self>,
+        arguments: &[String],
+        context_slash_command_output_sections: &[SlashCommandOutputSection<language::Anchor>],
+        context_buffer: BufferSnapshot,
+        workspace: WeakView<Workspace>,
+        _delegate: Option<Arc<dyn LspAdapterDelegate>>,
+        cx: &mut WindowContext,
+    ) -> Task<SlashCommandResult> {
+        let token_budget = match arguments.first() {
+            Some(argument) => match argument.parse::<usize>() {
+                Ok(budget) => budget,
+                Err(_) => return Task::ready(Err(anyhow!("invalid token budget: {argument}"))),
+            },
+            None => DEFAULT_TOKEN_BUDGET,
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let project = workspace.read(cx).project().read(cx);
+        let fs = project.fs().clone();
+        let languages = project.languages().clone();
+        let mut files = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let snapshot = worktree.read(cx).snapshot();
+            for entry in snapshot.files(false, 0) {
+                if entry.size > MAX_FILE_SIZE {
+                    continue;
+                }
+                files.push(ProjectFile {
+                    project_path: ProjectPath {
+                        worktree_id: snapshot.id(),
+                        path: entry.path.clone(),
+                    },
+                    full_path: Path::new(snapshot.root_name()).join(&entry.path),
+                    abs_path: snapshot.abs_path().join(&entry.path),
+                    mtime: entry.mtime,
+                });
+            }
+        }
+
+        let context_paths = context_slash_command_output_sections
+            .iter()
+            .filter_map(|section| {
+                let metadata = section.metadata.clone()?;
+                let metadata = serde_json::from_value::<FileCommandMetadata>(metadata).ok()?;
+                Some(PathBuf::from(metadata.path))
+            })
+            .collect::<HashSet<_>>();
+
+        let cache = self.cache.clone();
+        cx.spawn(|cx| async move {
+            // Only the files that changed since the command last ran need to be parsed again.
+            let (fresh, stale): (Vec<_>, Vec<_>) = {
+                let mut cache = cache.lock();
+                let paths = files
+                    .iter()
+                    .map(|file| &file.project_path)
+                    .collect::<HashSet<_>>();
+                cache.retain(|path, _| paths.contains(path));
+                files.into_iter().partition(|file| {
+                    cache
+                        .get(&file.project_path)
+                        .map_or(false, |tags| tags.mtime == file.mtime)
+                })
+            };
+
+            let parsed = stream::iter(stale)
+                .map(|file| {
+                    let fs = fs.clone();
+                    let languages = languages.clone();
+                    let mut cx = cx.clone();
+                    async move {
+                        // Files without an outline are cached too, so they aren't loaded again.
+                        let tags = parse_file(&file, fs, languages, &mut cx)
+                            .await
+                            .unwrap_or_else(|| FileTags {
+                                mtime: file.mtime,
+                                ..Default::default()
+                            });
+                        (file, Arc::new(tags))
+                    }
+                })
+                .buffer_unordered(cx.background_executor().num_cpus())
+                .collect::<Vec<_>>()
+                .await;
+
+            let files = {
+                let mut cache = cache.lock();
+                let mut files = Vec::new();
+                for (file, tags) in parsed {
+                    cache.insert(file.project_path.clone(), tags.clone());
+                    files.push((file.full_path, tags));
+                }
+                for file in fresh {
+                    if let Some(tags) = cache.get(&file.project_path) {
+                        files.push((file.full_path, tags.clone()));
+                    }
+                }
+                files
+            };
+
+            cx.background_executor()
+                .spawn(async move {
+                    let context_text = context_buffer.text();
+                    let map = build_repo_map(&files, &context_paths, &context_text, token_budget)
+                        .context("no symbols found in the project")?;
+                    let text = format!("Repository map:\n{map}");
+                    Ok(SlashCommandOutput {
+                        sections: vec![SlashCommandOutputSection {
+                            range: 0..text.len(),
+                            icon: IconName::ListTree,
+                            label: "Repository map".into(),
+                            metadata: None,
+                        }],
+                        text,
+                        run_commands_in_text: false,
+                    }
+                    .to_event_stream())
+                })
+                .await
+        })
+    }
+}
+
+struct ProjectFile {
+    project_path: ProjectPath,
+    full_path: PathBuf,
+    abs_path: PathBuf,
+    mtime: Option<SystemTime>,
+}
+
+async fn parse_file(
+    file: &ProjectFile,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    cx: &mut AsyncWindowContext,
+) -> Option<FileTags> {
+    let language = languages
+        .language_for_file_path(&file.project_path.path)
+        .await
+        .ok()?;
+    language.grammar()?.outline_config.as_ref()?;
+    let mut text = fs.load(&file.abs_path).await.ok()?;
+    LineEnding::normalize(&mut text);
+    let snapshot = cx
+        .update(|cx| {
+            Buffer::build_snapshot(
+                Rope::from(text.as_str()),
+                Some(language),
+                Some(languages),
+                cx,
+            )
+        })
+        .ok()?;
+    let mtime = file.mtime;
+    let tags = cx
+        .background_executor()
+        .spawn(async move { FileTags::new(&snapshot.await, mtime) })
+        .await;
+    Some(tags)
+}
+
+/// Renders the highest ranked definitions of the given files, grouped by file,
+/// using at most roughly `token_budget` tokens.
+///
+/// Files are ranked with a PageRank over the graph of references between them,
+/// personalized toward the files in the context and the files whose path is
+/// mentioned in it. Identifiers mentioned in the context weigh more. Files
+/// already in the context are left out, since their contents are available.
+fn build_repo_map(
+    files: &[(PathBuf, Arc<FileTags>)],
+    context_paths: &HashSet<PathBuf>,
+    context_text: &str,
+    token_budget: usize,
+) -> Option<String> {
+    let mentioned_identifiers = identifiers(context_text).collect::<HashSet<_>>();
+    let mut definers = HashMap::<&str, Vec<usize>>::default();
+    for (file_ix, (_, tags)) in files.iter().enumerate() {
+        for definition in &tags.definitions {
+            let files = definers.entry(definition.name.as_ref()).or_default();
+            if files.last() != Some(&file_ix) {
+                files.push(file_ix);
+            }
+        }
+    }
+
+    // Each edge goes from a file referencing an identifier to a file defining it.
+    let mut edges = Vec::<(usize, usize, &str, f64)>::new();
+    for (file_ix, (_, tags)) in files.iter().enumerate() {
+        for (identifier, count) in &tags.references {
+            let Some(definers) = definers.get(identifier.as_ref()) else {
+                continue;
+            };
+
+            let mut weight = (*count as f64).sqrt();
+            if mentioned_identifiers.contains(identifier.as_ref()) {
+                weight *= 10.;
+            }
+            if identifier.starts_with('_') || definers.len() > 5 {
+                weight *= 0.1;
+            }
+            for definer_ix in definers {
+                if *definer_ix != file_ix {
+                    edges.push((file_ix, *definer_ix, identifier.as_ref(), weight));
+                }
+            }
+        }
+    }
+
+    let personalization = files
+        .iter()
+        .map(|(path, _)| {
+            let in_context = context_paths.contains(path)
+                || context_text.contains(path.to_string_lossy().as_ref());
+            if in_context {
+                1.
+            } else {
+                0.
+            }
+        })
+        .collect::<Vec<_>>();
+    let file_ranks = page_rank(files.len(), &edges, personalization);
+
+    // Distribute the rank of each file across the definitions it references.
+    let mut out_weights = vec![0.; files.len()];
+    for (source, _, _, weight) in &edges {
+        out_weights[*source] += weight;
+    }
+    let mut definition_ranks = HashMap::<(usize, &str), f64>::default();
+    for (source, target, name, weight) in &edges {
+        *definition_ranks.entry((*target, *name)).or_default() +=
+            file_ranks[*source] * weight / out_weights[*source];
+    }
+
+    let mut ranked_definitions = Vec::new();
+    for (file_ix, (path, tags)) in files.iter().enumerate() {
+        if context_paths.contains(path) {
+            continue;
+        }
+        for (definition_ix, definition) in tags.definitions.iter().enumerate() {
+            // Unreferenced definitions keep a small share of their file's rank,
+            // so that important files still show their top-level items.
+            let rank = definition_ranks
+                .get(&(file_ix, definition.name.as_ref()))
+                .copied()
+                .unwrap_or(0.)
+                + file_ranks[file_ix] * 1e-3 / (definition.depth + 1) as f64;
+            ranked_definitions.push((rank, file_ix, definition_ix));
+        }
+    }
+    ranked_definitions.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    // Select definitions greedily, along with their ancestors, until the budget is spent.
+    let char_budget = token_budget * 4;
+    let mut used_chars = 0;
+    let mut selected = HashMap::<usize, HashSet<usize>>::default();
+    let mut file_order = Vec::new();
+    for (_, file_ix, definition_ix) in ranked_definitions {
+        let (path, tags) = &files[file_ix];
+        let selected_in_file = selected.get(&file_ix);
+        let mut added = Vec::new();
+        let mut cost = 0;
+        let mut next = Some(definition_ix);
+        while let Some(ix) = next {
+            if selected_in_file.map_or(false, |selected| selected.contains(&ix)) {
+                break;
+            }
+            let definition = &tags.definitions[ix];
+            cost += definition.depth * 2 + definition.text.len() + 3;
+            added.push(ix);
+            next = definition.parent;
+        }
+        if added.is_empty() {
+            continue;
+        }
+        if selected_in_file.is_none() {
+            cost += path.to_string_lossy().len() + 2;
+        }
+        if used_chars + cost > char_budget {
+            continue;
+        }
+
+        used_chars += cost;
+        if selected_in_file.is_none() {
+            file_order.push(file_ix);
+        }
+        selected.entry(file_ix).or_default().extend(added);
+    }
+
+    if file_order.is_empty() {
+        return None;
+    }
+
+    let mut map = String::new();
+    for file_ix in file_order {
+        let (path, tags) = &files[file_ix];
+        let selected = &selected[&file_ix];
+        map.push_str(&format!("{}:\n", path.display()));
+        for (ix, definition) in tags.definitions.iter().enumerate() {
+            if selected.contains(&ix) {
+                map.push_str(&"  ".repeat(definition.depth));
+                map.push_str("- ");
+                map.push_str(&definition.text);
+                map.push('\n');
+            }
+        }
+    }
+    Some(map)
+}
+
+/// Computes the PageRank of each node of a weighted graph. When no node is
+/// personalized, random jumps are distributed uniformly.
+fn page_rank(
+    node_count: usize,
+    edges: &[(usize, usize, &str, f64)],
+    mut personalization: Vec<f64>,
+) -> Vec<f64> {
+    const DAMPING: f64 = 0.85;
+    const ITERATIONS: usize = 32;
+
+    if node_count == 0 {
+        return Vec::new();
+    }
+
+    let total = personalization.iter().sum::<f64>();
+    if total > 0. {
+        personalization
+            .iter_mut()
+            .for_each(|weight| *weight /= total);
+    } else {
+        personalization = vec![1. / node_count as f64; node_count];
+    }
+
+    let mut out_weights = vec![0.; node_count];
+    for (source, _, _, weight) in edges {
+        out_weights[*source] += weight;
+    }
+
+    let mut ranks = personalization.clone();
+    for _ in 0..ITERATIONS {
+        // Nodes without outgoing edges jump back according to the personalization.
+        let dangling_rank = ranks
+            .iter()
+            .zip(&out_weights)
+            .filter(|(_, weight)| **weight == 0.)
+            .map(|(rank, _)| rank)
+            .sum::<f64>();
+        let mut next_ranks = personalization
+            .iter()
+            .map(|weight| (1. - DAMPING + DAMPING * dangling_rank) * weight)
+            .collect::<Vec<_>>();
+        for (source, target, _, weight) in edges {
+            next_ranks[*target] += DAMPING * ranks[*source] * weight / out_weights[*source];
+        }
+        ranks = next_ranks;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(name: &str, text: &str, depth: usize, parent: Option<usize>) -> Definition {
+        Definition {
+            name: name.into(),
+            text: text.into(),
+            depth,
+            parent,
+        }
+    }
+
+    fn file(path: &str, definitions: Vec<Definition>, text: &str) -> (PathBuf, Arc<FileTags>) {
+        let mut references = HashMap::default();
+        for identifier in identifiers(text) {
+            *references.entry(identifier.into()).or_default() += 1;
+        }
+        (
+            PathBuf::from(path),
+            Arc::new(FileTags {
+                mtime: None,
+                definitions,
+                references,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_build_repo_map() {
+        let files = [
+            file(
+                "app/src/main.rs",
+                vec![definition("main", "fn main", 0, None)],
+                "fn main() { let config = Config::load(); run(config); }",
+            ),
+            file(
+                "app/src/config.rs",
+                vec![
+                    definition("Config", "pub struct Config", 0, None),
+                    definition("Config", "impl Config", 0, None),
+                    definition("load", "pub fn load", 1, Some(1)),
+                ],
+                "pub struct Config {} impl Config { pub fn load() -> Self {} }",
+            ),
+            file(
+                "app/src/runner.rs",
+                vec![definition("run", "pub fn run", 0, None)],
+                "pub fn run(config: Config) {}",
+            ),
+            file(
+                "app/src/unused.rs",
+                vec![definition("unused", "fn unused", 0, None)],
+                "fn unused() {}",
+            ),
+        ];
+
+        let context_paths = HashSet::from_iter([PathBuf::from("app/src/main.rs")]);
+        let map = build_repo_map(&files, &context_paths, "", 1024).unwrap();
+        assert_eq!(
+            map,
+            concat!(
+                "app/src/config.rs:\n",
+                "- pub struct Config\n",
+                "- impl Config\n",
+                "  - pub fn load\n",
+                "app/src/runner.rs:\n",
+                "- pub fn run\n",
+                "app/src/unused.rs:\n",
+                "- fn unused\n",
+            )
+        );
+
+        // With a small budget, only the most referenced definitions are kept.
+        let map = build_repo_map(&files, &context_paths, "", 10).unwrap();
+        assert_eq!(map, "app/src/config.rs:\n- pub struct Config\n");
+
+        // Identifiers mentioned in the context are preferred.
+        let map = build_repo_map(&files, &context_paths, "How does run work?", 10).unwrap();
+        assert_eq!(map, "app/src/runner.rs:\n- pub fn run\n");
+    }
+}
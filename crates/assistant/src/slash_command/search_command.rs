@@ -110,7 +110,7 @@ impl SlashCommand for SearchSlashCommand {
         cx.spawn(|cx| async move {
             let results = project_index
                 .read_with(&cx, |project_index, cx| {
-                    project_index.search(vec![query.clone()], limit.unwrap_or(5), cx)
+                    project_index.hybrid_search(vec![query.clone()], limit.unwrap_or(5), cx)
                 })?
                 .await?;
 
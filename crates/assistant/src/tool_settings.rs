@@ -0,0 +1,40 @@
+use anyhow::Result;
+use assistant_tool::{Tool, ToolPermission};
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// Settings for the tools the assistant can use.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct ToolSettings {
+    /// Whether each tool can run without asking the user, by tool name.
+    /// Tools not listed here use their own default.
+    #[serde(default)]
+    pub permissions: HashMap<String, ToolPermission>,
+}
+
+impl ToolSettings {
+    pub fn permission(&self, tool: &dyn Tool) -> ToolPermission {
+        self.permissions
+            .get(&tool.name())
+            .copied()
+            .unwrap_or_else(|| tool.default_permission())
+    }
+}
+
+impl Settings for ToolSettings {
+    const KEY: Option<&'static str> = Some("assistant_tools");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
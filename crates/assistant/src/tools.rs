@@ -1,2 +1,8 @@
 pub mod context_server_tool;
+pub mod diagnostics_tool;
+pub mod edit_file_tool;
+pub mod list_worktree_tool;
 pub mod now_tool;
+pub mod read_file_tool;
+pub mod run_task_tool;
+pub mod search_project_tool;
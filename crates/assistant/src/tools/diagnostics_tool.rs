@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+use crate::slash_command::diagnostics_command::{collect_diagnostics, Options};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// A glob pattern of the files to get the diagnostics of, starting with the name of
+    /// the worktree root directory, e.g. `zed/crates/**/*.rs`. Covers the whole project when omitted.
+    path: Option<String>,
+    /// Whether to include warnings, on top of errors.
+    #[serde(default)]
+    include_warnings: bool,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors, and optionally the warnings, reported by the language servers \
+         for the files of the project, along with the lines they are reported at."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let path_matcher = match input.path.map(|path| PathMatcher::new(&[path])).transpose() {
+            Ok(path_matcher) => path_matcher,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = workspace.read(cx).project().clone();
+        let diagnostics = collect_diagnostics(
+            project,
+            Options {
+                include_warnings: input.include_warnings,
+                path_matcher,
+            },
+            cx,
+        );
+
+        cx.spawn(|_| async move {
+            Ok(diagnostics
+                .await?
+                .map(|output| output.text)
+                .unwrap_or_else(|| "No diagnostics found.".to_string()))
+        })
+    }
+}
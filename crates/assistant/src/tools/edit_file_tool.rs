@@ -0,0 +1,181 @@
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolPermission};
+use editor::{ProposedChangeLocation, ProposedChangesEditor};
+use gpui::{Task, VisualContext as _, WeakView, WindowContext};
+use language::{Point, ToOffset, ToPoint};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// How many lines around each edit are shown in the proposed changes.
+const CONTEXT_LINES: u32 = 3;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolInput {
+    /// The path of the file to edit, starting with the name of the worktree root directory,
+    /// e.g. `zed/crates/zed/src/main.rs`.
+    path: PathBuf,
+    /// A short description of the changes.
+    title: String,
+    /// The edits to make to the file.
+    edits: Vec<EditFileToolEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolEdit {
+    /// The text to replace, which has to occur exactly once in the file.
+    /// Include enough of the surrounding lines to make it unique.
+    old_text: String,
+    /// The text to replace it with.
+    new_text: String,
+}
+
+pub struct EditFileTool;
+
+impl Tool for EditFileTool {
+    fn name(&self) -> String {
+        "edit_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes edits to a file of the project. The edits are shown to the user, \
+         who reviews them and decides which ones to apply."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Confirm
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: EditFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let Some(project_path) = project.read(cx).find_project_path(&input.path, cx) else {
+            return Task::ready(Err(anyhow!("no such file: {}", input.path.display())));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|mut cx| async move {
+            let buffer = open_buffer.await?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+            let text = snapshot.text();
+
+            let mut edits = Vec::new();
+            for edit in &input.edits {
+                let range = find_unique_range(&text, &edit.old_text)?;
+                edits.push((range, edit.new_text.clone()));
+            }
+            edits.sort_by_key(|(range, _)| range.start);
+            for pair in edits.windows(2) {
+                anyhow::ensure!(
+                    pair[0].0.end <= pair[1].0.start,
+                    "the edits overlap each other"
+                );
+            }
+
+            let max_row = snapshot.max_point().row;
+            let context_ranges = edits
+                .iter()
+                .map(|(range, _)| {
+                    let start_row = range
+                        .start
+                        .to_point(&snapshot)
+                        .row
+                        .saturating_sub(CONTEXT_LINES);
+                    let end_row = (range.end.to_point(&snapshot).row + CONTEXT_LINES).min(max_row);
+                    Point::new(start_row, 0).to_offset(&snapshot)
+                        ..Point::new(end_row, snapshot.line_len(end_row)).to_offset(&snapshot)
+                })
+                .collect::<Vec<_>>();
+
+            let editor = cx.new_view(|cx| {
+                ProposedChangesEditor::new(
+                    input.title.clone(),
+                    vec![ProposedChangeLocation {
+                        buffer: buffer.clone(),
+                        ranges: context_ranges,
+                    }],
+                    Some(project.clone()),
+                    cx,
+                )
+            })?;
+            editor.update(&mut cx, |editor, cx| {
+                let branch = editor
+                    .branch_buffer_for_base(&buffer)
+                    .context("no branch of the file to propose the edits in")?;
+                branch.update(cx, |branch, cx| {
+                    branch.edit(edits.iter().cloned(), None, cx)
+                });
+                editor.recalculate_all_buffer_diffs();
+                anyhow::Ok(())
+            })??;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, cx)
+            })?;
+
+            Ok(format!(
+                "Proposed {} edits to {} to the user, who decides which ones to apply.",
+                edits.len(),
+                input.path.display()
+            ))
+        })
+    }
+}
+
+/// Finds the range of `old_text` in `text`, making sure it is not ambiguous.
+fn find_unique_range(text: &str, old_text: &str) -> Result<Range<usize>> {
+    if old_text.is_empty() {
+        anyhow::ensure!(
+            text.is_empty(),
+            "an empty text to replace can only be used in an empty file"
+        );
+        return Ok(0..0);
+    }
+
+    let mut matches = text.match_indices(old_text);
+    let (start, _) = matches
+        .next()
+        .with_context(|| format!("the text to replace was not found:\n{old_text}"))?;
+    anyhow::ensure!(
+        matches.next().is_none(),
+        "the text to replace occurs more than once, include more of the surrounding lines:\n{old_text}"
+    );
+    Ok(start..start + old_text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_unique_range() {
+        let text = "fn a() {}\nfn b() {}\nfn a2() {}\n";
+        assert_eq!(find_unique_range(text, "fn b() {}").unwrap(), 10..19);
+        assert!(find_unique_range(text, "fn a").is_err());
+        assert!(find_unique_range(text, "fn c").is_err());
+        assert!(find_unique_range(text, "").is_err());
+        assert_eq!(find_unique_range("", "").unwrap(), 0..0);
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// Listings are cut off after this many entries.
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListWorktreeToolInput {
+    /// The directory to list, starting with the name of the worktree root directory.
+    /// Lists every worktree of the project when omitted.
+    path: Option<PathBuf>,
+}
+
+pub struct ListWorktreeTool;
+
+impl Tool for ListWorktreeTool {
+    fn name(&self) -> String {
+        "list_worktree".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories of the project, or of one of its directories, recursively. \
+         Directories end with a slash and files ignored by git are left out."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListWorktreeToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListWorktreeToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().read(cx);
+
+        let directory = match &input.path {
+            Some(path) => match project.find_project_path(path, cx) {
+                Some(project_path) => Some(project_path),
+                None => return Task::ready(Err(anyhow!("no such directory: {}", path.display()))),
+            },
+            None => None,
+        };
+
+        let mut output = String::new();
+        let mut entry_count = 0;
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let directory_path = match &directory {
+                Some(directory) if directory.worktree_id != worktree.id() => continue,
+                Some(directory) => directory.path.as_ref(),
+                None => Path::new(""),
+            };
+            let root_name = Path::new(worktree.root_name());
+            for entry in worktree
+                .entries(false, 0)
+                .filter(|entry| entry.path.starts_with(directory_path))
+            {
+                entry_count += 1;
+                if entry_count > MAX_ENTRIES {
+                    continue;
+                }
+                write!(output, "{}", root_name.join(&entry.path).display()).unwrap();
+                if entry.is_dir() {
+                    output.push('/');
+                }
+                output.push('\n');
+            }
+        }
+
+        if entry_count == 0 {
+            return Task::ready(Err(anyhow!("no entries found")));
+        }
+        if entry_count > MAX_ENTRIES {
+            writeln!(
+                output,
+                "... {} more entries, list a subdirectory to see them",
+                entry_count - MAX_ENTRIES
+            )
+            .unwrap();
+        }
+        Task::ready(Ok(output))
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::Point;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// Files larger than this have to be read in ranges of lines.
+const MAX_FILE_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file to read, starting with the name of the worktree root directory,
+    /// e.g. `zed/crates/zed/src/main.rs`.
+    path: PathBuf,
+    /// The first line to read, starting at 1. Defaults to the start of the file.
+    start_line: Option<u32>,
+    /// The last line to read, inclusive. Defaults to the end of the file.
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Reads a file of the project, or a range of its lines.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let Some(project_path) = project.read(cx).find_project_path(&input.path, cx) else {
+            return Task::ready(Err(anyhow!("no such file: {}", input.path.display())));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let (is_private, snapshot) = buffer.read_with(&cx, |buffer, _| {
+                let is_private = buffer.file().map_or(false, |file| file.is_private());
+                (is_private, buffer.snapshot())
+            })?;
+            // Private files, such as `.env`, may contain secrets that must not be sent to the
+            // model, whatever the tool's permission is.
+            if is_private {
+                return Err(anyhow!(
+                    "{} is a private file and can't be read",
+                    input.path.display()
+                ));
+            }
+
+            let max_row = snapshot.max_point().row;
+            let start_row = input
+                .start_line
+                .map_or(0, |line| line.saturating_sub(1))
+                .min(max_row);
+            let end_row = input
+                .end_line
+                .map_or(max_row, |line| line.saturating_sub(1))
+                .min(max_row);
+            if start_row > end_row {
+                return Err(anyhow!(
+                    "the start line {} is after the end line {}",
+                    start_row + 1,
+                    end_row + 1
+                ));
+            }
+            let range = Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(end_row));
+            if input.start_line.is_none()
+                && input.end_line.is_none()
+                && snapshot.len() > MAX_FILE_LEN
+            {
+                return Err(anyhow!(
+                    "{} has {} lines, which is too large to read at once, read a range of lines instead",
+                    input.path.display(),
+                    max_row + 1
+                ));
+            }
+
+            Ok(snapshot.text_for_range(range).collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{SemanticVersion, TestAppContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_private_files_are_not_read(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".env": "SECRET=hunter2",
+                "main.rs": "fn main() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let workspace = workspace.downgrade();
+
+        let output = cx
+            .update(|cx| {
+                Arc::new(ReadFileTool).run(json!({ "path": "root/main.rs" }), workspace.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(output, "fn main() {}");
+
+        let error = cx
+            .update(|cx| Arc::new(ReadFileTool).run(json!({ "path": "root/.env" }), workspace, cx))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("private"), "{error}");
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{Tool, ToolPermission};
+use collections::HashSet;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use terminal::TaskStatus;
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+/// How many of the last lines of the task output are returned.
+const MAX_OUTPUT_LINES: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunTaskToolInput {
+    /// The label of the task to run.
+    label: String,
+}
+
+pub struct RunTaskTool;
+
+impl Tool for RunTaskTool {
+    fn name(&self) -> String {
+        "run_task".into()
+    }
+
+    fn description(&self) -> String {
+        "Runs one of the tasks of the project, such as a build or a test run, in a terminal \
+         and returns its output once it finishes. Fails with the labels of the available tasks \
+         when there is no task with the label given."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RunTaskToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Confirm
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: RunTaskToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let Some(terminal_panel) = workspace.read(cx).panel::<TerminalPanel>(cx) else {
+            return Task::ready(Err(anyhow!("no terminal panel to run the task in")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let task_context = workspace.update(cx, |workspace, cx| {
+            editor::tasks::task_context(workspace, cx)
+        });
+
+        cx.spawn(|mut cx| async move {
+            let task_context = task_context.await;
+            let resolved_task = project.update(&mut cx, |project, cx| {
+                let inventory = project
+                    .task_store()
+                    .read(cx)
+                    .task_inventory()
+                    .cloned()
+                    .context("tasks are not available in this project")?;
+                let inventory = inventory.read(cx);
+                let worktree_ids = project
+                    .visible_worktrees(cx)
+                    .map(|worktree| Some(worktree.read(cx).id()))
+                    .collect::<Vec<_>>();
+                let templates = if worktree_ids.is_empty() {
+                    inventory.list_tasks(None, None, None, cx)
+                } else {
+                    worktree_ids
+                        .into_iter()
+                        .flat_map(|worktree_id| inventory.list_tasks(None, None, worktree_id, cx))
+                        .collect()
+                };
+
+                let Some((task_source_kind, template)) = templates
+                    .iter()
+                    .find(|(_, template)| template.label == input.label)
+                else {
+                    let mut labels = templates
+                        .iter()
+                        .map(|(_, template)| template.label.as_str())
+                        .collect::<HashSet<_>>()
+                        .into_iter()
+                        .collect::<Vec<_>>();
+                    labels.sort_unstable();
+                    return Err(anyhow!(
+                        "no task labeled `{}`, the available tasks are: {}",
+                        input.label,
+                        labels.join(", ")
+                    ));
+                };
+                inventory
+                    .resolve_task_with_dependencies(None, task_source_kind, template, &task_context)
+                    .with_context(|| format!("failed to resolve task `{}`", input.label))
+            })??;
+
+            let spawn_in_terminal = resolved_task
+                .resolved
+                .with_context(|| format!("task `{}` has nothing to run", input.label))?;
+            anyhow::ensure!(
+                spawn_in_terminal.depends_on.is_empty(),
+                "task `{}` depends on other tasks, which have to be run from the tasks UI",
+                input.label
+            );
+
+            let terminal = terminal_panel
+                .update(&mut cx, |terminal_panel, cx| {
+                    terminal_panel.spawn_task(&spawn_in_terminal, cx)
+                })?
+                .await?;
+            terminal
+                .read_with(&cx, |terminal, cx| terminal.wait_for_ready_task(cx))?
+                .await;
+
+            let (status, output) = terminal.read_with(&cx, |terminal, _| {
+                (
+                    terminal.task().map(|task| task.status),
+                    terminal.scrollback_text(MAX_OUTPUT_LINES),
+                )
+            })?;
+            let status = match status {
+                Some(TaskStatus::Completed { success: true }) => "succeeded",
+                Some(TaskStatus::Completed { success: false }) => "failed",
+                Some(TaskStatus::Running) => "is still running in the background",
+                _ => "was cancelled",
+            };
+            Ok(format!(
+                "Task `{}` {status}. Its output:\n```\n{}\n```",
+                input.label,
+                output.unwrap_or_default().trim_end()
+            ))
+        })
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use futures::StreamExt;
+use gpui::{Task, WeakView, WindowContext};
+use language::{Point, ToPoint};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+/// Results are cut off after this many matching lines.
+const MAX_MATCHES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchProjectToolInput {
+    /// The text or regular expression to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// Whether the search is case sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Glob patterns of the files to search, e.g. `**/*.rs`. Searches every file when empty.
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+pub struct SearchProjectTool;
+
+impl Tool for SearchProjectTool {
+    fn name(&self) -> String {
+        "search_project".into()
+    }
+
+    fn description(&self) -> String {
+        "Searches the files of the project for a text or a regular expression, \
+         returning the matching lines as `path:line: text`."
+            .into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchProjectToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SearchProjectToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let query = PathMatcher::new(&input.include)
+            .map_err(|err| anyhow!(err))
+            .and_then(|files_to_include| {
+                if input.regex {
+                    SearchQuery::regex(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                } else {
+                    SearchQuery::text(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                }
+            });
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let mut results = workspace
+            .read(cx)
+            .project()
+            .update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            while let Some(result) = results.next().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    break;
+                };
+                let (path, snapshot) = buffer.read_with(&cx, |buffer, cx| {
+                    let path = buffer
+                        .file()
+                        .filter(|file| !file.is_private())
+                        .map(|file| file.full_path(cx).to_string_lossy().into_owned());
+                    (path, buffer.snapshot())
+                })?;
+                let Some(path) = path else {
+                    continue;
+                };
+
+                let mut last_row = None;
+                for range in ranges {
+                    let row = range.start.to_point(&snapshot).row;
+                    if last_row == Some(row) {
+                        continue;
+                    }
+                    last_row = Some(row);
+                    match_count += 1;
+                    if match_count <= MAX_MATCHES {
+                        let line = snapshot
+                            .text_for_range(
+                                Point::new(row, 0)..Point::new(row, snapshot.line_len(row)),
+                            )
+                            .collect::<String>();
+                        writeln!(output, "{path}:{}: {}", row + 1, line.trim()).unwrap();
+                    }
+                }
+            }
+
+            if match_count == 0 {
+                return Ok("No matches found.".to_string());
+            }
+            if match_count > MAX_MATCHES {
+                writeln!(
+                    output,
+                    "... {} more matches, narrow down the search to see them",
+                    match_count - MAX_MATCHES
+                )
+                .unwrap();
+            }
+            Ok(output)
+        })
+    }
+}
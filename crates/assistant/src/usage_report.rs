@@ -0,0 +1,318 @@
+use crate::{humanize_token_count, usage_settings::UsageSettings, MessageUsage};
+use chrono::{NaiveDate, TimeZone};
+use collections::BTreeMap;
+use gpui::{DismissEvent, EventEmitter, FocusHandle, FocusableView, SharedString};
+use language_model::TokenUsage;
+use ui::{prelude::*, Modal, ModalHeader, Section, SectionHeader};
+use workspace::ModalView;
+
+/// The completions made in a single context.
+pub struct ContextUsages {
+    pub title: SharedString,
+    pub usages: Vec<MessageUsage>,
+}
+
+/// The combined usage of a set of completions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsageTotals {
+    pub requests: usize,
+    pub tokens: TokenUsage,
+    /// The cost in US dollars of the completions whose model has a price.
+    pub cost: f64,
+    /// The number of completions whose model has no configured price.
+    pub unpriced_requests: usize,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, usage: &MessageUsage, settings: &UsageSettings) {
+        self.requests += 1;
+        self.tokens += usage.tokens;
+        match settings.cost(usage) {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced_requests += 1,
+        }
+    }
+
+    /// Formats the cost, marking it as a lower bound when some of the
+    /// completions have no price.
+    pub fn cost_label(&self) -> String {
+        if self.unpriced_requests == self.requests {
+            return "—".into();
+        }
+
+        let cost = if self.cost > 0. && self.cost < 0.01 {
+            format!("${:.4}", self.cost)
+        } else {
+            format!("${:.2}", self.cost)
+        };
+        if self.unpriced_requests > 0 {
+            format!("{cost}+")
+        } else {
+            cost
+        }
+    }
+}
+
+/// A summary of the completions made by the assistant, by day and by context.
+#[derive(Debug, Default, PartialEq)]
+pub struct UsageReport {
+    pub total: UsageTotals,
+    /// The totals of each day with completions, most recent first.
+    pub days: Vec<(NaiveDate, UsageTotals)>,
+    /// The totals of each context with completions, most expensive first.
+    pub contexts: Vec<(SharedString, UsageTotals)>,
+}
+
+impl UsageReport {
+    /// Summarizes the given contexts, grouping completions by their day in the given time zone.
+    pub fn new<Tz: TimeZone>(
+        contexts: &[ContextUsages],
+        settings: &UsageSettings,
+        tz: &Tz,
+    ) -> Self {
+        let mut report = Self::default();
+        let mut days = BTreeMap::<NaiveDate, UsageTotals>::default();
+        for context in contexts {
+            if context.usages.is_empty() {
+                continue;
+            }
+
+            let mut context_totals = UsageTotals::default();
+            for usage in &context.usages {
+                let day = usage.completed_at.with_timezone(tz).date_naive();
+                days.entry(day).or_default().add(usage, settings);
+                context_totals.add(usage, settings);
+                report.total.add(usage, settings);
+            }
+            report
+                .contexts
+                .push((context.title.clone(), context_totals));
+        }
+
+        report.days = days.into_iter().rev().collect();
+        report.contexts.sort_by(|(_, a), (_, b)| {
+            b.cost
+                .total_cmp(&a.cost)
+                .then_with(|| b.tokens.total_tokens().cmp(&a.tokens.total_tokens()))
+        });
+        report
+    }
+}
+
+pub struct UsageReportModal {
+    report: UsageReport,
+    focus_handle: FocusHandle,
+}
+
+impl UsageReportModal {
+    pub fn new(report: UsageReport, cx: &mut ViewContext<Self>) -> Self {
+        Self {
+            report,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_row(
+        label: impl Into<SharedString>,
+        totals: &UsageTotals,
+        cx: &WindowContext,
+    ) -> impl IntoElement {
+        let column = |text: String| {
+            div()
+                .w(rems(4.5))
+                .flex_none()
+                .flex()
+                .justify_end()
+                .child(Label::new(text).size(LabelSize::Small).color(Color::Muted))
+        };
+        let cached_tokens =
+            totals.tokens.cache_creation_input_tokens + totals.tokens.cache_read_input_tokens;
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .py_0p5()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_x_hidden()
+                    .whitespace_nowrap()
+                    .child(Label::new(label).size(LabelSize::Small)),
+            )
+            .child(column(humanize_token_count(
+                totals.tokens.input_tokens as usize,
+            )))
+            .child(column(humanize_token_count(cached_tokens as usize)))
+            .child(column(humanize_token_count(
+                totals.tokens.output_tokens as usize,
+            )))
+            .child(column(totals.cost_label()))
+    }
+
+    fn render_column_headers() -> impl IntoElement {
+        let column = |text: &'static str| {
+            div()
+                .w(rems(4.5))
+                .flex_none()
+                .flex()
+                .justify_end()
+                .child(Label::new(text).size(LabelSize::XSmall).color(Color::Muted))
+        };
+        h_flex()
+            .w_full()
+            .gap_2()
+            .child(div().flex_1())
+            .child(column("Input"))
+            .child(column("Cached"))
+            .child(column("Output"))
+            .child(column("Cost"))
+    }
+}
+
+impl EventEmitter<DismissEvent> for UsageReportModal {}
+
+impl FocusableView for UsageReportModal {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for UsageReportModal {}
+
+impl Render for UsageReportModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content =
+            if self.report.total.requests == 0 {
+                Section::new().child(
+                    Label::new("No token usage has been recorded yet.")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            } else {
+                Section::new()
+                    .header(SectionHeader::new(format!(
+                        "{} requests, {} tokens, {}",
+                        self.report.total.requests,
+                        humanize_token_count(self.report.total.tokens.total_tokens() as usize),
+                        self.report.total.cost_label()
+                    )))
+                    .child(
+                        v_flex()
+                            .id("usage-report")
+                            .max_h(rems(28.))
+                            .overflow_y_scroll()
+                            .gap_1()
+                            .child(Self::render_column_headers())
+                            .child(Label::new("By Day").size(LabelSize::Small))
+                            .children(self.report.days.iter().map(|(day, totals)| {
+                                Self::render_row(day.format("%Y-%m-%d").to_string(), totals, cx)
+                            }))
+                            .child(div().h_2())
+                            .child(Label::new("By Context").size(LabelSize::Small))
+                            .children(self.report.contexts.iter().map(|(title, totals)| {
+                                Self::render_row(title.clone(), totals, cx)
+                            })),
+                    )
+            };
+
+        v_flex()
+            .key_context("UsageReport")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::cancel))
+            .elevation_3(cx)
+            .w(rems(40.))
+            .child(
+                Modal::new("usage-report-modal", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline("Assistant Usage")
+                            .show_dismiss_button(true),
+                    )
+                    .section(content),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage_settings::ModelPrice;
+    use chrono::Utc;
+
+    #[test]
+    fn test_usage_report() {
+        let mut settings = UsageSettings::default();
+        settings.prices.insert(
+            "claude".into(),
+            ModelPrice {
+                input: 3.,
+                output: 15.,
+                cache_write: Some(3.75),
+                cache_read: Some(0.3),
+            },
+        );
+
+        let usage = |model: &str, day: u32, input_tokens, output_tokens, cache_read| MessageUsage {
+            model: model.into(),
+            tokens: TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: cache_read,
+            },
+            completed_at: Utc.with_ymd_and_hms(2024, 10, day, 12, 0, 0).unwrap(),
+        };
+        let contexts = [
+            ContextUsages {
+                title: "Cheap".into(),
+                usages: vec![usage("claude", 1, 1_000, 100, 0)],
+            },
+            ContextUsages {
+                title: "Expensive".into(),
+                usages: vec![
+                    usage("claude", 1, 10_000, 2_000, 100_000),
+                    usage("llama", 2, 5_000, 500, 0),
+                ],
+            },
+            ContextUsages {
+                title: "Unused".into(),
+                usages: Vec::new(),
+            },
+        ];
+
+        let report = UsageReport::new(&contexts, &settings, &Utc);
+        assert_eq!(report.total.requests, 3);
+        assert_eq!(report.total.tokens.input_tokens, 16_000);
+        assert_eq!(report.total.unpriced_requests, 1);
+        assert_eq!(report.total.cost_label(), "$0.09+");
+
+        assert_eq!(
+            report
+                .days
+                .iter()
+                .map(|(day, totals)| (day.to_string(), totals.requests, totals.cost_label()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2024-10-02".to_string(), 1, "—".to_string()),
+                ("2024-10-01".to_string(), 2, "$0.09".to_string()),
+            ]
+        );
+        assert_eq!(
+            report
+                .contexts
+                .iter()
+                .map(|(title, totals)| (title.to_string(), totals.cost_label()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Expensive".to_string(), "$0.09+".to_string()),
+                ("Cheap".to_string(), "$0.0045".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use collections::HashMap;
+use gpui::AppContext;
+use language_model::TokenUsage;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::MessageUsage;
+
+/// Settings for tracking the tokens used by the assistant.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct UsageSettings {
+    /// The price of each model's tokens, by model ID.
+    #[serde(default)]
+    pub prices: HashMap<String, ModelPrice>,
+}
+
+/// The price of a model's tokens, in US dollars per million tokens.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, JsonSchema)]
+pub struct ModelPrice {
+    /// The price of input tokens.
+    #[serde(default)]
+    pub input: f64,
+    /// The price of output tokens.
+    #[serde(default)]
+    pub output: f64,
+    /// The price of input tokens written to the prompt cache. Defaults to the
+    /// price of input tokens.
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+    /// The price of input tokens read from the prompt cache. Defaults to the
+    /// price of input tokens.
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, tokens: &TokenUsage) -> f64 {
+        let cost = tokens.input_tokens as f64 * self.input
+            + tokens.output_tokens as f64 * self.output
+            + tokens.cache_creation_input_tokens as f64 * self.cache_write.unwrap_or(self.input)
+            + tokens.cache_read_input_tokens as f64 * self.cache_read.unwrap_or(self.input);
+        cost / 1_000_000.
+    }
+}
+
+impl UsageSettings {
+    /// Returns the cost of the given usage in US dollars, or `None` if the
+    /// model that produced it has no configured price.
+    pub fn cost(&self, usage: &MessageUsage) -> Option<f64> {
+        let price = self.prices.get(&usage.model)?;
+        Some(price.cost(&usage.tokens))
+    }
+}
+
+impl Settings for UsageSettings {
+    const KEY: Option<&'static str> = Some("assistant_usage");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
@@ -4,6 +4,8 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use gpui::{AppContext, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use workspace::Workspace;
 
 pub use tool_registry::*;
@@ -12,6 +14,18 @@ pub fn init(cx: &mut AppContext) {
     ToolRegistry::default_global(cx);
 }
 
+/// Whether a tool can be run when a language model asks for it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking.
+    Allow,
+    /// Ask the user before running the tool.
+    Confirm,
+    /// Never run the tool.
+    Deny,
+}
+
 /// A tool that can be used by a language model.
 pub trait Tool: 'static + Send + Sync {
     /// Returns the name of the tool.
@@ -25,6 +39,13 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns whether the tool can run without asking the user, unless configured otherwise.
+    ///
+    /// Tools that write or execute anything should ask for confirmation.
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Allow
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
@@ -214,6 +214,7 @@ gpui::actions!(
         Cancel,
         CancelLanguageServerWork,
         ConfirmRename,
+        ConfirmRenameWithPreview,
         ContextMenuFirst,
         ContextMenuLast,
         ContextMenuNext,
@@ -304,6 +305,7 @@ gpui::actions!(
         PageDown,
         PageUp,
         Paste,
+        PreviewAllReferences,
         PreviousInlineCompletion,
         Redo,
         RedoSelection,
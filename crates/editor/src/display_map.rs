@@ -849,6 +849,15 @@ impl DisplaySnapshot {
         )
     }
 
+    /// Changes whenever inlays, folds or tabs are updated, which moves text between display rows.
+    pub(crate) fn transform_versions(&self) -> [usize; 3] {
+        [
+            self.inlay_snapshot.version,
+            self.fold_snapshot.version,
+            self.tab_snapshot.version,
+        ]
+    }
+
     pub fn highlighted_chunks<'a>(
         &'a self,
         display_rows: Range<DisplayRow>,
@@ -62,6 +62,14 @@ impl Inlay {
         }
     }
 
+    pub fn color(id: InlayId, position: Anchor) -> Self {
+        Self {
+            id,
+            position,
+            text: crate::document_colors::SWATCH_TEXT.into(),
+        }
+    }
+
     pub fn suggestion<T: Into<Rope>>(id: usize, position: Anchor, text: T) -> Self {
         Self {
             id: InlayId::Suggestion(id),
@@ -348,6 +356,8 @@ impl<'a> Iterator for InlayChunks<'a> {
                 let mut highlight_style = match inlay.id {
                     InlayId::Suggestion(_) => self.highlight_styles.suggestion,
                     InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    // Swatches are colored by their inlay highlights.
+                    InlayId::Color(_) => None,
                 };
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
@@ -0,0 +1,449 @@
+//! Color swatches for the color literals that language servers report via `textDocument/documentColor`.
+//!
+//! Each swatch is an inlay drawn in front of its color literal. Clicking it opens a color picker
+//! that replaces the literal with one of the server's `textDocument/colorPresentation` edits.
+
+use std::{iter, ops::Range, time::Duration};
+
+use futures::future;
+use gpui::{
+    div, prelude::*, px, AnyElement, HighlightStyle, Hsla, Model, Pixels, Point, Rgba, Task, View,
+    ViewContext, WeakView,
+};
+use language::{Bias, Buffer};
+use multi_buffer::Anchor;
+use project::{ColorPresentation, DocumentColor};
+use ui::{h_flex, v_flex, ContextMenu};
+use util::{post_inc, ResultExt};
+
+use crate::{
+    display_map::Inlay, hover_links::InlayHighlight, mouse_context_menu::MouseContextMenu, Editor,
+    EditorMode, EditorSnapshot, InlayId, PointForPosition,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The text of the inlay that is colored to show a swatch.
+pub(crate) const SWATCH_TEXT: &str = "■ ";
+
+const PALETTE_HUES: usize = 12;
+const PALETTE_STEPS: usize = 9;
+
+#[derive(Default)]
+pub(crate) struct DocumentColors {
+    swatches: Vec<ColorSwatch>,
+    update_task: Option<Task<()>>,
+    picker_task: Option<Task<()>>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ColorSwatch {
+    pub inlay_id: InlayId,
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
+
+impl Editor {
+    pub(crate) fn refresh_document_colors(&mut self, cx: &mut ViewContext<Self>) {
+        if self.mode != EditorMode::Full {
+            return;
+        }
+        let Some(provider) = self.semantics_provider.clone() else {
+            return;
+        };
+
+        self.document_colors.update_task = Some(cx.spawn(|editor, mut cx| async move {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+            let Ok((buffers, tasks)) = editor.update(&mut cx, |editor, cx| {
+                editor
+                    .buffer
+                    .read(cx)
+                    .all_buffers()
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let task = provider.document_colors(&buffer, cx)?;
+                        Some((buffer, task))
+                    })
+                    .unzip::<_, _, Vec<_>, Vec<_>>()
+            }) else {
+                return;
+            };
+
+            let colors = buffers
+                .into_iter()
+                .zip(future::join_all(tasks).await)
+                .filter_map(|(buffer, colors)| Some((buffer, colors.log_err()?)))
+                .collect::<Vec<_>>();
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.show_document_colors(colors, cx)
+                })
+                .ok();
+        }));
+    }
+
+    fn show_document_colors(
+        &mut self,
+        colors: Vec<(Model<Buffer>, Vec<DocumentColor>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let multibuffer = self.buffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        let mut swatches = Vec::new();
+        for (buffer, colors) in colors {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(&buffer, cx) {
+                let context = excerpt_range.context;
+                for color in &colors {
+                    if color
+                        .range
+                        .start
+                        .cmp(&context.start, &buffer_snapshot)
+                        .is_lt()
+                        || color.range.end.cmp(&context.end, &buffer_snapshot).is_gt()
+                    {
+                        continue;
+                    }
+                    let (Some(start), Some(end)) = (
+                        snapshot.anchor_in_excerpt(excerpt_id, color.range.start),
+                        snapshot.anchor_in_excerpt(excerpt_id, color.range.end),
+                    ) else {
+                        continue;
+                    };
+                    swatches.push(ColorSwatch {
+                        inlay_id: InlayId::Color(post_inc(&mut self.next_inlay_id)),
+                        range: start..end,
+                        color: color.color,
+                    });
+                }
+            }
+        }
+
+        let to_remove = self
+            .document_colors
+            .swatches
+            .drain(..)
+            .map(|swatch| swatch.inlay_id)
+            .collect();
+        let to_insert = swatches
+            .iter()
+            .map(|swatch| Inlay::color(swatch.inlay_id, swatch.range.start))
+            .collect();
+        self.splice_inlays(to_remove, to_insert, cx);
+
+        self.clear_highlights::<DocumentColors>(cx);
+        for swatch in &swatches {
+            self.highlight_inlays::<DocumentColors>(
+                vec![InlayHighlight {
+                    inlay: swatch.inlay_id,
+                    inlay_position: swatch.range.start,
+                    range: 0..SWATCH_TEXT.len(),
+                }],
+                HighlightStyle {
+                    color: Some(hsla_from_lsp(swatch.color)),
+                    ..HighlightStyle::default()
+                },
+                cx,
+            );
+        }
+        self.document_colors.swatches = swatches;
+    }
+
+    /// Returns the swatch under the given position, if any.
+    pub(crate) fn color_swatch_at(
+        &self,
+        point: &PointForPosition,
+        snapshot: &EditorSnapshot,
+    ) -> Option<ColorSwatch> {
+        if point.column_overshoot_after_line_end > 0 {
+            return None;
+        }
+        let offset = snapshot.display_point_to_inlay_offset(point.exact_unclipped, Bias::Left);
+        self.document_colors
+            .swatches
+            .iter()
+            .find(|swatch| {
+                // Swatches are right-biased, so they start at the inlay offset of their position.
+                let start = snapshot.anchor_to_inlay_offset(swatch.range.start);
+                start <= offset && offset.0 < start.0 + SWATCH_TEXT.len()
+            })
+            .cloned()
+    }
+
+    /// Shows a color picker for the swatch, offering the server's presentations of `color`.
+    pub(crate) fn deploy_color_picker(
+        &mut self,
+        swatch: ColorSwatch,
+        color: lsp::Color,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(provider) = self.semantics_provider.clone() else {
+            return;
+        };
+        let Some(buffer) = swatch
+            .range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.buffer.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        let Some(presentations) = provider.color_presentations(
+            &buffer,
+            swatch.range.start.text_anchor..swatch.range.end.text_anchor,
+            color,
+            cx,
+        ) else {
+            return;
+        };
+
+        self.document_colors.picker_task = Some(cx.spawn(|editor, mut cx| async move {
+            let presentations = presentations.await.log_err().unwrap_or_default();
+            editor
+                .update(&mut cx, |editor, cx| {
+                    let menu =
+                        color_picker_menu(swatch.clone(), color, position, presentations, cx);
+                    editor.mouse_context_menu = MouseContextMenu::pinned_to_editor(
+                        editor,
+                        swatch.range.start,
+                        position,
+                        menu,
+                        cx,
+                    );
+                    cx.notify();
+                })
+                .ok();
+        }));
+    }
+
+    fn apply_color_presentation(
+        &mut self,
+        swatch: &ColorSwatch,
+        presentation: ColorPresentation,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let excerpt_id = swatch.range.start.excerpt_id;
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        // Without an edit, the label replaces the color.
+        let text_edit = presentation.text_edit.unwrap_or_else(|| {
+            (
+                swatch.range.start.text_anchor..swatch.range.end.text_anchor,
+                presentation.label,
+            )
+        });
+        let edits = iter::once(text_edit)
+            .chain(presentation.additional_text_edits)
+            .filter_map(|(range, new_text)| {
+                let start = snapshot.anchor_in_excerpt(excerpt_id, range.start)?;
+                let end = snapshot.anchor_in_excerpt(excerpt_id, range.end)?;
+                Some((start..end, new_text))
+            })
+            .collect::<Vec<_>>();
+        self.transact(cx, |editor, cx| {
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        });
+    }
+}
+
+fn color_picker_menu(
+    swatch: ColorSwatch,
+    color: lsp::Color,
+    position: Point<Pixels>,
+    presentations: Vec<ColorPresentation>,
+    cx: &mut ViewContext<Editor>,
+) -> View<ContextMenu> {
+    let editor = cx.view().downgrade();
+    ContextMenu::build(cx, |mut menu, _| {
+        menu = menu.custom_row({
+            let editor = editor.clone();
+            let swatch = swatch.clone();
+            move |_| render_palette(&editor, &swatch, color, position)
+        });
+        if !presentations.is_empty() {
+            menu = menu.separator();
+        }
+        for presentation in presentations {
+            let editor = editor.clone();
+            let swatch = swatch.clone();
+            menu = menu.entry(presentation.label.clone(), None, move |cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        editor.apply_color_presentation(&swatch, presentation.clone(), cx)
+                    })
+                    .ok();
+            });
+        }
+        menu
+    })
+}
+
+/// Renders the colors that can be picked instead of `color`.
+fn render_palette(
+    editor: &WeakView<Editor>,
+    swatch: &ColorSwatch,
+    color: lsp::Color,
+    position: Point<Pixels>,
+) -> AnyElement {
+    let rows = palette(hsla_from_lsp(color));
+    v_flex()
+        .gap_1()
+        .py_1()
+        .children(rows.into_iter().enumerate().map(|(row_ix, row)| {
+            h_flex()
+                .gap_1()
+                .children(row.into_iter().enumerate().map(|(column_ix, candidate)| {
+                    let editor = editor.clone();
+                    let swatch = swatch.clone();
+                    div()
+                        .id(("color-picker-swatch", row_ix * PALETTE_HUES + column_ix))
+                        .size(px(14.))
+                        .rounded_sm()
+                        .border_1()
+                        .border_color(gpui::black().opacity(0.2))
+                        .bg(candidate)
+                        .cursor_pointer()
+                        .on_click(move |_, cx| {
+                            let color = lsp_from_hsla(candidate);
+                            editor
+                                .update(cx, |editor, cx| {
+                                    editor.deploy_color_picker(swatch.clone(), color, position, cx)
+                                })
+                                .ok();
+                        })
+                }))
+        }))
+        .into_any_element()
+}
+
+/// Returns rows of colors derived from the given one: the color at other hues,
+/// at other lightnesses, and at other opacities.
+fn palette(color: Hsla) -> [Vec<Hsla>; 3] {
+    // Grays don't have a hue, so show their hues at full saturation.
+    let saturation = if color.s < 0.05 { 1. } else { color.s };
+    let lightness = color.l.clamp(0.2, 0.8);
+    let hues = (0..PALETTE_HUES)
+        .map(|ix| Hsla {
+            h: ix as f32 / PALETTE_HUES as f32,
+            s: saturation,
+            l: lightness,
+            a: color.a,
+        })
+        .collect();
+    let lightnesses = (0..PALETTE_STEPS)
+        .map(|ix| Hsla {
+            l: (ix + 1) as f32 / (PALETTE_STEPS + 1) as f32,
+            ..color
+        })
+        .collect();
+    let alphas = (0..PALETTE_STEPS)
+        .map(|ix| Hsla {
+            a: (ix + 1) as f32 / PALETTE_STEPS as f32,
+            ..color
+        })
+        .collect();
+    [hues, lightnesses, alphas]
+}
+
+fn hsla_from_lsp(color: lsp::Color) -> Hsla {
+    Rgba {
+        r: color.red,
+        g: color.green,
+        b: color.blue,
+        a: color.alpha,
+    }
+    .into()
+}
+
+fn lsp_from_hsla(color: Hsla) -> lsp::Color {
+    let rgba = color.to_rgb();
+    lsp::Color {
+        red: rgba.r,
+        green: rgba.g,
+        blue: rgba.b,
+        alpha: rgba.a,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext, DisplayPoint,
+        DisplayRow,
+    };
+    use futures::StreamExt;
+    use indoc::indoc;
+
+    #[gpui::test]
+    async fn test_document_colors(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {r##"let red = "#ff0000";ˇ"##});
+        let color_range = cx.lsp_range(indoc! {r##"let red = «"#ff0000"»;"##});
+        let mut requests =
+            cx.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _, _| async move {
+                Ok(vec![lsp::ColorInformation {
+                    range: color_range,
+                    color: lsp::Color {
+                        red: 1.,
+                        green: 0.,
+                        blue: 0.,
+                        alpha: 1.,
+                    },
+                }])
+            });
+        cx.executor().advance_clock(UPDATE_DEBOUNCE);
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(cx.display_text(), r##"let red = ■ "#ff0000";"##);
+
+        // Only the swatch itself can be clicked.
+        let point_at_column = |column| {
+            let point = DisplayPoint::new(DisplayRow(0), column);
+            PointForPosition {
+                previous_valid: point,
+                next_valid: point,
+                exact_unclipped: point,
+                column_overshoot_after_line_end: 0,
+            }
+        };
+        let swatch = cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            assert!(editor
+                .color_swatch_at(&point_at_column(9), &snapshot)
+                .is_none());
+            assert!(editor
+                .color_swatch_at(&point_at_column(10 + SWATCH_TEXT.len() as u32), &snapshot)
+                .is_none());
+            editor
+                .color_swatch_at(&point_at_column(10), &snapshot)
+                .unwrap()
+        });
+        assert_eq!(swatch.color.red, 1.);
+
+        cx.update_editor(|editor, cx| {
+            editor.apply_color_presentation(
+                &swatch,
+                ColorPresentation {
+                    label: r##""#00ff00""##.into(),
+                    text_edit: None,
+                    additional_text_edits: Vec::new(),
+                },
+                cx,
+            )
+        });
+        assert_eq!(cx.buffer_text(), r##"let red = "#00ff00";"##);
+    }
+}
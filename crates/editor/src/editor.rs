@@ -18,6 +18,7 @@ mod blink_manager;
 mod clangd_ext;
 mod debounced_delay;
 pub mod display_map;
+mod document_colors;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
@@ -32,10 +33,12 @@ mod inline_completion_provider;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod minimap;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
+mod refactor_preview;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
@@ -60,6 +63,7 @@ use convert_case::{Case, Casing};
 use debounced_delay::DebouncedDelay;
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
+use document_colors::DocumentColors;
 pub use editor_settings::{
     CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine, SearchSettings, ShowScrollbar,
 };
@@ -101,16 +105,20 @@ use linked_editing_ranges::refresh_linked_ranges;
 pub use proposed_changes_editor::{
     ProposedChangeLocation, ProposedChangesEditor, ProposedChangesEditorToolbar,
 };
+use refactor_preview::{RefactorPreview, ReferencesPreview};
 use similar::{ChangeTag, TextDiff};
 use std::iter::Peekable;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
 
-use hover_links::{find_file, HoverLink, HoveredLinkState, InlayHighlight};
+use hover_links::{
+    document_link_target, find_file, DocumentLinks, HoverLink, HoveredLinkState, InlayHighlight,
+};
 pub use lsp::CompletionContext;
 use lsp::{
     CompletionItemKind, CompletionTriggerKind, DiagnosticSeverity, InsertTextFormat,
     LanguageServerId, LanguageServerName,
 };
+use minimap::MinimapCache;
 use mouse_context_menu::MouseContextMenu;
 use movement::TextLayoutDetails;
 pub use multi_buffer::{
@@ -125,8 +133,9 @@ use parking_lot::{Mutex, RwLock};
 use project::{
     lsp_store::{FormatTarget, FormatTrigger},
     project_settings::{GitGutterSetting, ProjectSettings},
-    CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Item, Location,
-    LocationLink, Project, ProjectTransaction, TaskSourceKind,
+    CodeAction, ColorPresentation, Completion, CompletionIntent, DocumentColor, DocumentHighlight,
+    DocumentLink, InlayHint, Item, Location, LocationLink, Project, ProjectTransaction,
+    TaskSourceKind, WorkspaceEditPreview,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
@@ -257,6 +266,7 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     Suggestion(usize),
     Hint(usize),
+    Color(usize),
 }
 
 impl InlayId {
@@ -264,6 +274,7 @@ impl InlayId {
         match self {
             Self::Suggestion(id) => *id,
             Self::Hint(id) => *id,
+            Self::Color(id) => *id,
         }
     }
 }
@@ -307,10 +318,20 @@ pub fn init(cx: &mut AppContext) {
     workspace::register_serializable_item::<Editor>(cx);
 
     cx.observe_new_views(
-        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
             workspace.register_action(Editor::new_file);
             workspace.register_action(Editor::new_file_vertical);
             workspace.register_action(Editor::new_file_horizontal);
+
+            // Show the edits language servers made ahead of renaming, creating or deleting
+            // files, such as updated imports.
+            cx.subscribe(workspace.project(), |workspace, _, event, cx| {
+                if let project::Event::FileOperationEditsApplied { title, transaction } = event {
+                    let entries = transaction.0.clone().into_iter().collect();
+                    Editor::open_transaction_in_multibuffer(workspace, entries, title.clone(), cx);
+                }
+            })
+            .detach();
         },
     )
     .detach();
@@ -601,6 +622,7 @@ pub struct Editor {
     background_highlights: TreeMap<TypeId, BackgroundHighlight>,
     gutter_highlights: TreeMap<TypeId, GutterHighlight>,
     scrollbar_marker_state: ScrollbarMarkerState,
+    minimap_cache: MinimapCache,
     active_indent_guides_state: ActiveIndentGuidesState,
     nav_history: Option<ItemNavHistory>,
     context_menu: RwLock<Option<ContextMenu>>,
@@ -616,6 +638,7 @@ pub struct Editor {
     code_actions_task: Option<Task<Result<()>>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    document_colors: DocumentColors,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -632,6 +655,7 @@ pub struct Editor {
     hover_state: HoverState,
     gutter_hovered: bool,
     hovered_link_state: Option<HoveredLinkState>,
+    document_links: DocumentLinks,
     inline_completion_provider: Option<RegisteredInlineCompletionProvider>,
     code_action_providers: Vec<Arc<dyn CodeActionProvider>>,
     active_inline_completion: Option<CompletionState>,
@@ -2024,6 +2048,8 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::LanguageServerAdded(..) = event {
+                        editor.refresh_document_colors(cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -2119,6 +2145,7 @@ impl Editor {
             background_highlights: Default::default(),
             gutter_highlights: TreeMap::default(),
             scrollbar_marker_state: ScrollbarMarkerState::default(),
+            minimap_cache: MinimapCache::default(),
             active_indent_guides_state: ActiveIndentGuidesState::default(),
             nav_history: None,
             context_menu: RwLock::new(None),
@@ -2136,6 +2163,7 @@ impl Editor {
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            document_colors: Default::default(),
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
@@ -2155,6 +2183,7 @@ impl Editor {
             remote_id: None,
             hover_state: Default::default(),
             hovered_link_state: Default::default(),
+            document_links: Default::default(),
             inline_completion_provider: None,
             active_inline_completion: None,
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
@@ -2213,6 +2242,7 @@ impl Editor {
             active_line_trailer_provider: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
+        this.refresh_document_colors(cx);
         this._subscriptions.extend(project_subscriptions);
 
         this.end_selection(cx);
@@ -2389,6 +2419,10 @@ impl Editor {
         self.workspace.as_ref()?.0.upgrade()
     }
 
+    pub fn project(&self) -> Option<&Model<Project>> {
+        self.project.as_ref()
+    }
+
     pub fn title<'a>(&self, cx: &'a AppContext) -> Cow<'a, str> {
         self.buffer().read(cx).title(cx)
     }
@@ -5026,12 +5060,7 @@ impl Editor {
         title: String,
         mut cx: AsyncWindowContext,
     ) -> Result<()> {
-        let mut entries = transaction.0.into_iter().collect::<Vec<_>>();
-        cx.update(|cx| {
-            entries.sort_unstable_by_key(|(buffer, _)| {
-                buffer.read(cx).file().map(|f| f.path().clone())
-            });
-        })?;
+        let entries = transaction.0.into_iter().collect::<Vec<_>>();
 
         // If the project transaction's edits are all contained within this editor, then
         // avoid opening a new editor to display them.
@@ -5066,6 +5095,24 @@ impl Editor {
             return Ok(());
         }
 
+        workspace.update(&mut cx, |workspace, cx| {
+            Self::open_transaction_in_multibuffer(workspace, entries, title, cx)
+        })?;
+
+        Ok(())
+    }
+
+    /// Opens a multibuffer showing the edits of a project transaction, where they can be
+    /// undone together.
+    pub(crate) fn open_transaction_in_multibuffer(
+        workspace: &mut Workspace,
+        mut entries: Vec<(Model<Buffer>, language::Transaction)>,
+        title: String,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        entries
+            .sort_unstable_by_key(|(buffer, _)| buffer.read(cx).file().map(|f| f.path().clone()));
+
         let mut ranges_to_highlight = Vec::new();
         let excerpt_buffer = cx.new_model(|cx| {
             let mut multibuffer = MultiBuffer::new(Capability::ReadWrite).with_title(title);
@@ -5084,23 +5131,19 @@ impl Editor {
             }
             multibuffer.push_transaction(entries.iter().map(|(b, t)| (b, t)), cx);
             multibuffer
-        })?;
-
-        workspace.update(&mut cx, |workspace, cx| {
-            let project = workspace.project().clone();
-            let editor =
-                cx.new_view(|cx| Editor::for_multibuffer(excerpt_buffer, Some(project), true, cx));
-            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
-            editor.update(cx, |editor, cx| {
-                editor.highlight_background::<Self>(
-                    &ranges_to_highlight,
-                    |theme| theme.editor_highlighted_line_background,
-                    cx,
-                );
-            });
-        })?;
+        });
 
-        Ok(())
+        let project = workspace.project().clone();
+        let editor =
+            cx.new_view(|cx| Editor::for_multibuffer(excerpt_buffer, Some(project), true, cx));
+        workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+        editor.update(cx, |editor, cx| {
+            editor.highlight_background::<Self>(
+                &ranges_to_highlight,
+                |theme| theme.editor_highlighted_line_background,
+                cx,
+            );
+        });
     }
 
     pub fn clear_code_action_providers(&mut self) {
@@ -10104,6 +10147,32 @@ impl Editor {
                         Task::ready(Ok(TargetTaskResult::Location(None)))
                     }
                 }
+                HoverLink::DocumentLink(buffer, link) => {
+                    let provider = self.semantics_provider.clone();
+                    let project = self.project.clone();
+                    cx.spawn(|editor, mut cx| async move {
+                        let Some(target) = document_link_target(
+                            &buffer,
+                            link,
+                            provider.as_ref(),
+                            project,
+                            &mut cx,
+                        )
+                        .await
+                        else {
+                            return Ok(TargetTaskResult::Location(None));
+                        };
+                        let navigated = editor
+                            .update(&mut cx, |editor, cx| {
+                                editor.navigate_to_hover_links(kind, vec![target], split, cx)
+                            })?
+                            .await?;
+                        Ok(match navigated {
+                            Navigated::Yes => TargetTaskResult::AlreadyNavigated,
+                            Navigated::No => TargetTaskResult::Location(None),
+                        })
+                    })
+                }
             };
             cx.spawn(|editor, mut cx| async move {
                 let target = match target_task.await.context("target resolution task")? {
@@ -10189,6 +10258,7 @@ impl Editor {
                                 HoverLink::InlayHint(_, _) => None,
                                 HoverLink::Url(_) => None,
                                 HoverLink::File(_) => None,
+                                HoverLink::DocumentLink(..) => None,
                             })
                             .unwrap_or(tab_kind.to_string());
                         let location_tasks = definitions
@@ -10200,6 +10270,7 @@ impl Editor {
                                 }
                                 HoverLink::Url(_) => Task::ready(Ok(None)),
                                 HoverLink::File(_) => Task::ready(Ok(None)),
+                                HoverLink::DocumentLink(..) => Task::ready(Ok(None)),
                             })
                             .collect::<Vec<_>>();
                         (title, location_tasks, editor.workspace().clone())
@@ -10282,6 +10353,24 @@ impl Editor {
         &mut self,
         _: &FindAllReferences,
         cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<Navigated>>> {
+        self.open_all_references(false, cx)
+    }
+
+    pub fn preview_all_references(
+        &mut self,
+        _: &PreviewAllReferences,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<Navigated>>> {
+        self.open_all_references(true, cx)
+    }
+
+    /// Looks up the references of the symbol under the newest selection and opens them either
+    /// in a multibuffer, or grouped by file in a [`ReferencesPreview`].
+    fn open_all_references(
+        &mut self,
+        in_preview: bool,
+        cx: &mut ViewContext<Self>,
     ) -> Option<Task<Result<Navigated>>> {
         let selection = self.selections.newest::<usize>(cx);
         let multi_buffer = self.buffer.read(cx);
@@ -10353,7 +10442,15 @@ impl Editor {
                         )
                     })
                     .unwrap();
-                Self::open_locations_in_multibuffer(workspace, locations, title, false, cx);
+                if in_preview {
+                    let workspace_handle = cx.view().downgrade();
+                    let references_preview = cx.new_view(|cx| {
+                        ReferencesPreview::new(workspace_handle, locations, title, cx)
+                    });
+                    workspace.add_item_to_active_pane(Box::new(references_preview), None, true, cx);
+                } else {
+                    Self::open_locations_in_multibuffer(workspace, locations, title, false, cx);
+                }
                 Navigated::Yes
             })
         }))
@@ -10630,6 +10727,22 @@ impl Editor {
         &mut self,
         _: &ConfirmRename,
         cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        self.confirm_rename_impl(false, cx)
+    }
+
+    pub fn confirm_rename_with_preview(
+        &mut self,
+        _: &ConfirmRenameWithPreview,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        self.confirm_rename_impl(true, cx)
+    }
+
+    fn confirm_rename_impl(
+        &mut self,
+        show_preview: bool,
+        cx: &mut ViewContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let rename = self.take_rename(false, cx)?;
         let workspace = self.workspace()?.downgrade();
@@ -10647,13 +10760,60 @@ impl Editor {
 
         let old_name = rename.old_name;
         let new_name = rename.editor.read(cx).text(cx);
+        let title = format!("Rename: {} → {}", old_name, new_name);
+        let semantics_provider = self.semantics_provider.clone()?;
+
+        // When the edits can be previewed, only apply them right away if the user didn't ask
+        // to review them and none of them needs to be confirmed.
+        if let Some((project, preview)) = self
+            .project
+            .clone()
+            .zip(semantics_provider.preview_rename(&buffer, start, new_name.clone(), cx))
+        {
+            return Some(cx.spawn(|editor, mut cx| async move {
+                let preview = preview.await?;
+                let needs_confirmation = preview
+                    .changes
+                    .iter()
+                    .any(|change| preview.needs_confirmation(change));
+                if show_preview || needs_confirmation {
+                    workspace.update(&mut cx, |workspace, cx| {
+                        let workspace_handle = cx.view().downgrade();
+                        let refactor_preview = cx.new_view(|cx| {
+                            RefactorPreview::new(project, workspace_handle, preview, title, cx)
+                        });
+                        workspace.add_item_to_active_pane(
+                            Box::new(refactor_preview),
+                            None,
+                            true,
+                            cx,
+                        );
+                    })?;
+                    return Ok(());
+                }
 
-        let rename = self.semantics_provider.as_ref()?.perform_rename(
-            &buffer,
-            start,
-            new_name.clone(),
-            cx,
-        )?;
+                let project_transaction = project
+                    .update(&mut cx, |project, cx| {
+                        project.apply_workspace_edit_preview(preview, cx)
+                    })?
+                    .await?;
+                Self::open_project_transaction(
+                    &editor,
+                    workspace,
+                    project_transaction,
+                    title,
+                    cx.clone(),
+                )
+                .await?;
+
+                editor.update(&mut cx, |editor, cx| {
+                    editor.refresh_document_highlights(cx);
+                })?;
+                Ok(())
+            }));
+        }
+
+        let rename = semantics_provider.perform_rename(&buffer, start, new_name, cx)?;
 
         Some(cx.spawn(|editor, mut cx| async move {
             let project_transaction = rename.await?;
@@ -10661,7 +10821,7 @@ impl Editor {
                 &editor,
                 workspace,
                 project_transaction,
-                format!("Rename: {} → {}", old_name, new_name),
+                title,
                 cx.clone(),
             )
             .await?;
@@ -12590,6 +12750,7 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                self.refresh_document_colors(cx);
                 if self.has_active_inline_completion(cx) {
                     self.update_visible_inline_completion(cx);
                 }
@@ -12647,9 +12808,11 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.refresh_document_colors(cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                self.refresh_document_colors(cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -12665,6 +12828,7 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                self.refresh_document_colors(cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -13711,6 +13875,41 @@ pub trait SemanticsProvider {
         new_name: String,
         cx: &mut AppContext,
     ) -> Option<Task<Result<ProjectTransaction>>>;
+
+    fn preview_rename(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        new_name: String,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<WorkspaceEditPreview>>>;
+
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<DocumentLink>>>>;
+
+    fn resolve_document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Option<lsp::Url>>>>;
+
+    fn document_colors(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<DocumentColor>>>>;
+
+    fn color_presentations(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<text::Anchor>,
+        color: lsp::Color,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<ColorPresentation>>>>;
 }
 
 pub trait CompletionProvider {
@@ -14048,6 +14247,60 @@ impl SemanticsProvider for Model<Project> {
             project.perform_rename(buffer.clone(), position, new_name, cx)
         }))
     }
+
+    fn preview_rename(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        new_name: String,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<WorkspaceEditPreview>>> {
+        if !self.read(cx).is_local() {
+            return None;
+        }
+        Some(self.update(cx, |project, cx| {
+            project.preview_rename(buffer.clone(), position, new_name, cx)
+        }))
+    }
+
+    fn document_links(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<DocumentLink>>>> {
+        Some(self.update(cx, |project, cx| project.document_links(buffer, cx)))
+    }
+
+    fn resolve_document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Option<lsp::Url>>>> {
+        Some(self.update(cx, |project, cx| {
+            project.resolve_document_link(buffer, link, cx)
+        }))
+    }
+
+    fn document_colors(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<DocumentColor>>>> {
+        Some(self.update(cx, |project, cx| project.document_colors(buffer, cx)))
+    }
+
+    fn color_presentations(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<text::Anchor>,
+        color: lsp::Color,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<ColorPresentation>>>> {
+        Some(self.update(cx, |project, cx| {
+            project.color_presentations(buffer, range, color, cx)
+        }))
+    }
 }
 
 fn inlay_hint_settings(
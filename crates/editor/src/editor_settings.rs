@@ -15,6 +15,7 @@ pub struct EditorSettings {
     pub completion_documentation_secondary_query_debounce: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -107,6 +108,12 @@ pub struct Scrollbar {
     pub cursors: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub enabled: bool,
+    pub width: f32,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -212,6 +219,8 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -333,6 +342,19 @@ pub struct ScrollbarContent {
     pub cursors: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// Whether to show a downscaled overview of the buffer next to the scrollbar.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The width of the minimap, in pixels.
+    ///
+    /// Default: 80
+    pub width: Option<f32>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
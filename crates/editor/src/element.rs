@@ -14,6 +14,7 @@ use crate::{
     hunk_diff::{diff_hunk_to_display, DisplayDiffHunk},
     hunk_status,
     items::BufferSearchHighlights,
+    minimap::{MinimapCacheKey, MinimapLayout},
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
     scroll::scroll_amount::ScrollAmount,
     BlockId, ChunkReplacement, CodeActionsMenu, CursorShape, CustomBlockId, DisplayPoint,
@@ -29,13 +30,13 @@ use collections::{BTreeMap, HashMap, HashSet};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
     anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
-    transparent_black, Action, AnchorCorner, AnyElement, AvailableSpace, Bounds, ClipboardItem,
-    ContentMask, Corners, CursorStyle, DispatchPhase, Edges, Element, ElementInputHandler, Entity,
-    FontId, GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Length,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad,
-    ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size,
-    StatefulInteractiveElement, Style, Styled, TextRun, TextStyleRefinement, View, ViewContext,
-    WeakView, WindowContext,
+    transparent_black, AccessibilityNode, AccessibilityProperties, AccessibilityRole, Action,
+    AnchorCorner, AnyElement, AvailableSpace, Bounds, ClipboardItem, ContentMask, Corners,
+    CursorStyle, DispatchPhase, Edges, Element, ElementInputHandler, Entity, FontId,
+    GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Length, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels,
+    ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size, StatefulInteractiveElement,
+    Style, Styled, TextRun, TextStyleRefinement, View, ViewContext, WeakView, WindowContext,
 };
 use gpui::{ClickEvent, Subscription};
 use itertools::Itertools;
@@ -424,6 +425,13 @@ impl EditorElement {
                 cx.propagate();
             }
         });
+        register_action(view, cx, |editor, action, cx| {
+            if let Some(task) = editor.confirm_rename_with_preview(action, cx) {
+                task.detach_and_log_err(cx);
+            } else {
+                cx.propagate();
+            }
+        });
         register_action(view, cx, |editor, action, cx| {
             if let Some(task) = editor.find_all_references(action, cx) {
                 task.detach_and_log_err(cx);
@@ -431,6 +439,13 @@ impl EditorElement {
                 cx.propagate();
             }
         });
+        register_action(view, cx, |editor, action, cx| {
+            if let Some(task) = editor.preview_all_references(action, cx) {
+                task.detach_and_log_err(cx);
+            } else {
+                cx.propagate();
+            }
+        });
         register_action(view, cx, Editor::show_signature_help);
         register_action(view, cx, Editor::next_inline_completion);
         register_action(view, cx, Editor::previous_inline_completion);
@@ -539,6 +554,16 @@ impl EditorElement {
 
         let point_for_position =
             position_map.point_for_position(text_hitbox.bounds, event.position);
+        if click_count == 1 && !modifiers.modified() {
+            if let Some(swatch) =
+                editor.color_swatch_at(&point_for_position, &position_map.snapshot)
+            {
+                let color = swatch.color;
+                editor.deploy_color_picker(swatch, color, event.position, cx);
+                cx.stop_propagation();
+                return;
+            }
+        }
         let position = point_for_position.previous_valid;
         if modifiers.shift && modifiers.alt {
             editor.select(
@@ -1226,6 +1251,238 @@ impl EditorElement {
         })
     }
 
+    fn minimap_width(&self, snapshot: &EditorSnapshot, cx: &WindowContext) -> Pixels {
+        let minimap = EditorSettings::get_global(cx).minimap;
+        if snapshot.mode == EditorMode::Full && minimap.enabled {
+            px(minimap.width.max(0.))
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        rows_per_page: f32,
+        max_scroll_top: f32,
+        cursors: &[(DisplayPoint, Hsla)],
+        cx: &mut WindowContext,
+    ) -> Option<MinimapLayout> {
+        let minimap_width = self.minimap_width(snapshot, cx);
+        if minimap_width == Pixels::ZERO {
+            return None;
+        }
+
+        let scrollbar_left = self.scrollbar_left(&bounds);
+        let minimap_bounds = Bounds::from_corners(
+            point(scrollbar_left - minimap_width, bounds.origin.y),
+            point(scrollbar_left, bounds.lower_left().y),
+        );
+
+        // When the buffer doesn't fit in the minimap, the minimap scrolls proportionally to the
+        // editor, so that its first and last rows are shown when the editor is scrolled to the
+        // top and bottom.
+        let max_row = snapshot.max_point().row();
+        let minimap_rows = MinimapLayout::row_count(bounds.size.height);
+        let overflow = (max_row.as_f32() + 1. - minimap_rows).max(0.);
+        let (scroll_top, viewport_row_height) = if max_scroll_top > 0. {
+            let scroll_fraction = (scroll_position.y / max_scroll_top).clamp(0., 1.);
+            let minimap_rows_per_row = (1. - overflow / max_scroll_top).max(0.1);
+            (
+                scroll_fraction * overflow,
+                MinimapLayout::ROW_HEIGHT * minimap_rows_per_row,
+            )
+        } else {
+            (0., MinimapLayout::ROW_HEIGHT)
+        };
+
+        let start_row = DisplayRow(scroll_top as u32);
+        let end_row = DisplayRow(cmp::min(
+            (scroll_top + minimap_rows).ceil() as u32 + 1,
+            max_row.next_row().0,
+        ));
+        let cache_key = MinimapCacheKey {
+            edit_count: snapshot.buffer_snapshot.edit_count(),
+            non_text_state_update_count: snapshot.buffer_snapshot.non_text_state_update_count(),
+            transform_versions: snapshot.display_snapshot.transform_versions(),
+            max_row,
+            editor_width: bounds.size.width,
+            max_columns: ((minimap_width - MinimapLayout::MARKER_WIDTH * 2.)
+                / MinimapLayout::COLUMN_WIDTH)
+                .max(0.) as u32,
+            theme: cx.theme().name.clone(),
+        };
+        let syntax = cx.theme().syntax().clone();
+        let text_color = self.style.text.color;
+        let tiles = self.editor.update(cx, |editor, _| {
+            editor.minimap_cache.tiles(
+                cache_key,
+                start_row..end_row,
+                &snapshot.display_snapshot,
+                &syntax,
+                text_color,
+            )
+        });
+
+        let mut layout = MinimapLayout {
+            hitbox: cx.insert_hitbox(minimap_bounds, false),
+            tiles,
+            markers: Vec::new(),
+            scroll_top,
+            visible_row_range: scroll_position.y..scroll_position.y + rows_per_page,
+            viewport_row_height,
+        };
+        layout.markers =
+            self.collect_minimap_markers(&layout, snapshot, start_row..end_row, cursors, cx);
+        Some(layout)
+    }
+
+    /// Collects the same markers as the scrollbar, for the rows shown in the minimap.
+    fn collect_minimap_markers(
+        &self,
+        layout: &MinimapLayout,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        cursors: &[(DisplayPoint, Hsla)],
+        cx: &WindowContext,
+    ) -> Vec<PaintQuad> {
+        let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
+        let theme = cx.theme();
+        let width = layout.hitbox.size.width;
+        let left_edge = Pixels::ZERO..MinimapLayout::MARKER_WIDTH;
+        let right_edge = width - MinimapLayout::MARKER_WIDTH..width;
+        let mut markers = Vec::new();
+        let mut push_marker = |rows: Range<DisplayRow>, x: Range<Pixels>, color: Hsla| {
+            markers.push(fill(layout.marker_bounds(rows, x), color));
+        };
+
+        let start_anchor = if rows.start == DisplayRow(0) {
+            Anchor::min()
+        } else {
+            snapshot
+                .buffer_snapshot
+                .anchor_before(DisplayPoint::new(rows.start, 0).to_offset(snapshot, Bias::Left))
+        };
+        let end_anchor = if rows.end > snapshot.max_point().row() {
+            Anchor::max()
+        } else {
+            snapshot
+                .buffer_snapshot
+                .anchor_before(DisplayPoint::new(rows.end, 0).to_offset(snapshot, Bias::Right))
+        };
+
+        let editor = self.editor.read(cx);
+        let search_range = start_anchor..end_anchor;
+        let mut highlighted_rows = Vec::new();
+        if scrollbar_settings.search_results {
+            highlighted_rows.push((
+                editor.background_highlight_row_ranges::<BufferSearchHighlights>(
+                    search_range.clone(),
+                    &snapshot.display_snapshot,
+                    usize::MAX,
+                ),
+                theme.status().info,
+            ));
+        }
+        if scrollbar_settings.selected_symbol {
+            let mut color = theme.status().info;
+            color.fade_out(0.5);
+            for ranges in [
+                editor.background_highlight_row_ranges::<DocumentHighlightRead>(
+                    search_range.clone(),
+                    &snapshot.display_snapshot,
+                    usize::MAX,
+                ),
+                editor.background_highlight_row_ranges::<DocumentHighlightWrite>(
+                    search_range.clone(),
+                    &snapshot.display_snapshot,
+                    usize::MAX,
+                ),
+            ] {
+                highlighted_rows.push((ranges, color));
+            }
+        }
+        for (ranges, color) in highlighted_rows {
+            for range in ranges {
+                push_marker(
+                    range.start().row()..range.end().row(),
+                    Pixels::ZERO..width,
+                    color.opacity(0.4),
+                );
+            }
+        }
+
+        if scrollbar_settings.git_diff && editor.is_singleton(cx) {
+            let start_row = MultiBufferRow(start_anchor.to_point(&snapshot.buffer_snapshot).row);
+            let end_row = MultiBufferRow(end_anchor.to_point(&snapshot.buffer_snapshot).row + 1);
+            for hunk in snapshot
+                .buffer_snapshot
+                .git_diff_hunks_in_range(start_row..end_row)
+            {
+                let start = MultiBufferPoint::new(hunk.row_range.start.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let mut end = MultiBufferPoint::new(hunk.row_range.end.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                if end != start {
+                    end.0 -= 1;
+                }
+                let color = match hunk_status(&hunk) {
+                    DiffHunkStatus::Added => theme.status().created,
+                    DiffHunkStatus::Modified => theme.status().modified,
+                    DiffHunkStatus::Removed => theme.status().deleted,
+                };
+                push_marker(start..end, left_edge.clone(), color);
+            }
+        }
+
+        if scrollbar_settings.diagnostics {
+            let start = start_anchor.to_point(&snapshot.buffer_snapshot);
+            let end = end_anchor.to_point(&snapshot.buffer_snapshot);
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<_, Point>(start..end, false)
+                // Paint the most severe diagnostics last.
+                .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => theme.status().error,
+                    DiagnosticSeverity::WARNING => theme.status().warning,
+                    DiagnosticSeverity::INFORMATION => theme.status().info,
+                    _ => theme.status().hint,
+                };
+                push_marker(
+                    diagnostic
+                        .range
+                        .start
+                        .to_display_point(&snapshot.display_snapshot)
+                        .row()
+                        ..diagnostic
+                            .range
+                            .end
+                            .to_display_point(&snapshot.display_snapshot)
+                            .row(),
+                    right_edge.clone(),
+                    color,
+                );
+            }
+        }
+
+        if scrollbar_settings.cursors {
+            for (cursor, color) in cursors {
+                if rows.contains(&cursor.row()) {
+                    push_marker(cursor.row()..cursor.row(), Pixels::ZERO..width, *color);
+                }
+            }
+        }
+
+        markers
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -3096,8 +3353,12 @@ impl EditorElement {
                         .scrollbar_layout
                         .as_ref()
                         .map_or(false, |scrollbar| scrollbar.visible);
+                    let content_right = match layout.minimap_layout.as_ref() {
+                        Some(minimap) => Some(minimap.hitbox.left()),
+                        None => show_scrollbars.then(|| self.scrollbar_left(&layout.hitbox.bounds)),
+                    };
                     if x < layout.text_hitbox.origin.x
-                        || (show_scrollbars && x > self.scrollbar_left(&layout.hitbox.bounds))
+                        || content_right.map_or(false, |content_right| x > content_right)
                     {
                         continue;
                     }
@@ -3710,6 +3971,127 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let Some(minimap) = layout.minimap_layout.as_ref() else {
+            return;
+        };
+
+        let bounds = minimap.hitbox.bounds;
+        let viewport_bounds = minimap.viewport_bounds();
+        cx.paint_layer(bounds, |cx| {
+            cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
+                cx.paint_quad(quad(
+                    bounds,
+                    Corners::default(),
+                    cx.theme().colors().editor_background,
+                    Edges {
+                        top: Pixels::ZERO,
+                        right: Pixels::ZERO,
+                        bottom: Pixels::ZERO,
+                        left: ScrollbarLayout::BORDER_WIDTH,
+                    },
+                    cx.theme().colors().scrollbar_track_border,
+                ));
+
+                for tile in &minimap.tiles {
+                    for run in &tile.runs {
+                        let run_bounds = minimap.run_bounds(run);
+                        if run_bounds.bottom() >= bounds.top()
+                            && run_bounds.top() <= bounds.bottom()
+                        {
+                            cx.paint_quad(fill(run_bounds, run.color.opacity(0.7)));
+                        }
+                    }
+                }
+
+                for marker in &minimap.markers {
+                    cx.paint_quad(marker.clone());
+                }
+
+                cx.paint_quad(fill(
+                    viewport_bounds,
+                    cx.theme().colors().scrollbar_thumb_background,
+                ));
+            });
+        });
+
+        cx.set_cursor_style(CursorStyle::Arrow, &minimap.hitbox);
+
+        let viewport_row_height = minimap.viewport_row_height;
+        let scroll_top = minimap.scroll_top;
+        let rows_per_page = minimap.visible_row_range.end - minimap.visible_row_range.start;
+
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let mut mouse_position = cx.mouse_position();
+            move |event: &MouseMoveEvent, phase, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if event.pressed_button == Some(MouseButton::Left)
+                        && editor.scroll_manager.is_dragging_minimap()
+                    {
+                        let mut position = editor.scroll_position(cx);
+                        position.y += (event.position.y - mouse_position.y) / viewport_row_height;
+                        position.y = position.y.max(0.);
+                        editor.set_scroll_position(position, cx);
+                        cx.stop_propagation();
+                    } else {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                let hitbox = minimap.hitbox.clone();
+                move |event: &MouseDownEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(cx)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the viewport centers the clicked row, after which
+                        // dragging moves the viewport.
+                        let y = event.position.y;
+                        if y < viewport_bounds.top() || viewport_bounds.bottom() < y {
+                            let row = scroll_top + (y - hitbox.top()) / MinimapLayout::ROW_HEIGHT;
+                            let mut position = editor.scroll_position(cx);
+                            position.y = (row - rows_per_page / 2.).max(0.);
+                            editor.set_scroll_position(position, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -4857,6 +5239,69 @@ enum Invisible {
 }
 
 impl EditorElement {
+    /// Describes the editor to assistive technologies as a text input whose value is the
+    /// visible text, along with the newest selection when it is on screen.
+    fn accessibility_node(
+        &self,
+        bounds: Bounds<Pixels>,
+        layout: &EditorLayout,
+        cx: &mut WindowContext,
+    ) -> AccessibilityNode {
+        let snapshot = &layout.position_map.snapshot;
+        let visible_rows = layout.visible_display_row_range.start
+            ..layout
+                .visible_display_row_range
+                .end
+                .min(snapshot.max_point().row().next_row());
+
+        let mut value = String::new();
+        let mut row_offsets = Vec::new();
+        for row in visible_rows.start.0..visible_rows.end.0 {
+            if !row_offsets.is_empty() {
+                value.push('\n');
+            }
+            row_offsets.push(value.len());
+            value.push_str(&snapshot.line(DisplayRow(row)));
+        }
+
+        let (selection, placeholder, read_only) = self.editor.update(cx, |editor, cx| {
+            (
+                editor.selections.newest_display(cx),
+                editor.placeholder_text(cx).map(SharedString::from),
+                editor.read_only(cx),
+            )
+        });
+        let offset = |point: DisplayPoint| {
+            let row = point.row().0.checked_sub(visible_rows.start.0)?;
+            let row_offset = *row_offsets.get(row as usize)?;
+            Some(row_offset + point.column() as usize)
+        };
+        let text_selection = offset(selection.tail())
+            .zip(offset(selection.head()))
+            .map(|(tail, head)| tail..head);
+
+        let role = match layout.mode {
+            EditorMode::Full => AccessibilityRole::MultilineTextInput,
+            EditorMode::SingleLine { .. } | EditorMode::AutoHeight { .. } => {
+                AccessibilityRole::TextInput
+            }
+        };
+        AccessibilityNode {
+            properties: AccessibilityProperties {
+                role,
+                name: placeholder,
+                value: Some(value.into()),
+                text_selection,
+                read_only,
+                ..Default::default()
+            },
+            bounds,
+            focusable: true,
+            clickable: false,
+            children: Vec::new(),
+        }
+    }
+
     /// Returns the rem size to use when rendering the [`EditorElement`].
     ///
     /// This allows UI elements to scale based on the `buffer_font_size`.
@@ -5037,7 +5482,7 @@ impl Element for EditorElement {
                     let text_width = bounds.size.width - gutter_dimensions.width;
 
                     let right_margin = if snapshot.mode == EditorMode::Full {
-                        EditorElement::SCROLLBAR_WIDTH
+                        EditorElement::SCROLLBAR_WIDTH + self.minimap_width(&snapshot, cx)
                     } else {
                         px(0.)
                     };
@@ -5450,6 +5895,15 @@ impl Element for EditorElement {
                         non_visible_cursors,
                         cx,
                     );
+                    let minimap_layout = self.layout_minimap(
+                        &snapshot,
+                        bounds,
+                        scroll_position,
+                        height_in_lines,
+                        max_scroll_top,
+                        &cursors,
+                        cx,
+                    );
 
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
@@ -5659,6 +6113,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbar_layout,
+                        minimap_layout,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -5734,37 +6189,50 @@ impl Element for EditorElement {
                     }
                 }
             });
+        // Describing the editor copies all of its visible text, so only do it when an assistive
+        // technology is listening.
+        let accessibility_node = cx
+            .is_accessibility_active()
+            .then(|| self.accessibility_node(bounds, layout, cx));
         let rem_size = self.rem_size(cx);
-        cx.with_rem_size(rem_size, |cx| {
-            cx.with_text_style(Some(text_style), |cx| {
-                cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
-                    self.paint_mouse_listeners(layout, hovered_hunk, cx);
-                    self.paint_background(layout, cx);
-                    self.paint_indent_guides(layout, cx);
-
-                    if layout.gutter_hitbox.size.width > Pixels::ZERO {
-                        self.paint_blamed_display_rows(layout, cx);
-                        self.paint_line_numbers(layout, cx);
-                    }
+        let paint_contents = |cx: &mut WindowContext| {
+            cx.with_rem_size(rem_size, |cx| {
+                cx.with_text_style(Some(text_style), |cx| {
+                    cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
+                        self.paint_mouse_listeners(layout, hovered_hunk, cx);
+                        self.paint_background(layout, cx);
+                        self.paint_indent_guides(layout, cx);
+
+                        if layout.gutter_hitbox.size.width > Pixels::ZERO {
+                            self.paint_blamed_display_rows(layout, cx);
+                            self.paint_line_numbers(layout, cx);
+                        }
 
-                    self.paint_text(layout, cx);
+                        self.paint_text(layout, cx);
 
-                    if layout.gutter_hitbox.size.width > Pixels::ZERO {
-                        self.paint_gutter_highlights(layout, cx);
-                        self.paint_gutter_indicators(layout, cx);
-                    }
+                        if layout.gutter_hitbox.size.width > Pixels::ZERO {
+                            self.paint_gutter_highlights(layout, cx);
+                            self.paint_gutter_indicators(layout, cx);
+                        }
 
-                    if !layout.blocks.is_empty() {
-                        cx.with_element_namespace("blocks", |cx| {
-                            self.paint_blocks(layout, cx);
-                        });
-                    }
+                        if !layout.blocks.is_empty() {
+                            cx.with_element_namespace("blocks", |cx| {
+                                self.paint_blocks(layout, cx);
+                            });
+                        }
 
-                    self.paint_scrollbar(layout, cx);
-                    self.paint_mouse_context_menu(layout, cx);
-                });
+                        self.paint_minimap(layout, cx);
+                        self.paint_scrollbar(layout, cx);
+                        self.paint_mouse_context_menu(layout, cx);
+                    });
+                })
             })
-        })
+        };
+        if let Some(node) = accessibility_node {
+            cx.with_accessibility_node(node, Some(&focus_handle), paint_contents);
+        } else {
+            paint_contents(cx);
+        }
     }
 }
 
@@ -5794,6 +6262,7 @@ pub struct EditorLayout {
     gutter_dimensions: GutterDimensions,
     content_origin: gpui::Point<Pixels>,
     scrollbar_layout: Option<ScrollbarLayout>,
+    minimap_layout: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -6402,12 +6871,14 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::MinimapContent,
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
     use language::language_settings;
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use ui::Context;
     use util::test::sample_text;
@@ -6685,6 +7156,79 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(MinimapContent {
+                        enabled: Some(true),
+                        width: Some(60.),
+                    });
+                });
+            });
+        });
+
+        let text = (0..10_000)
+            .map(|row| format!("{}line {row}", " ".repeat(row % 4)))
+            .join("\n");
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple(&text, cx);
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        let draw = |cx: &mut VisualTestContext| {
+            cx.draw(point(px(0.), px(0.)), size(px(500.), px(500.)), |_| {
+                EditorElement::new(&editor, style.clone())
+            })
+            .1
+        };
+
+        let state = draw(cx);
+        let minimap = state.minimap_layout.as_ref().unwrap();
+        assert_eq!(minimap.hitbox.size.width, px(60.));
+        assert_eq!(minimap.scroll_top, 0.);
+        assert_eq!(minimap.viewport_bounds().top(), minimap.hitbox.top());
+        // Only the tile covering the rows that fit in the minimap is built.
+        assert_eq!(minimap.tiles.len(), 1);
+        assert_eq!(
+            minimap.tiles[0].runs[..4]
+                .iter()
+                .map(|run| (run.row.0, run.start_column, run.len))
+                .collect::<Vec<_>>(),
+            [(0, 0, 4), (0, 5, 1), (1, 1, 4), (1, 6, 1)]
+        );
+
+        // Scrolling to the bottom of the editor scrolls the minimap to its last rows.
+        window
+            .update(cx, |editor, cx| {
+                editor.set_scroll_position(point(0., 9999.), cx)
+            })
+            .unwrap();
+        let state = draw(cx);
+        let minimap = state.minimap_layout.as_ref().unwrap();
+        let minimap_rows = MinimapLayout::row_count(minimap.hitbox.size.height);
+        assert_eq!(minimap.scroll_top, 10_000. - minimap_rows);
+        assert_eq!(minimap.tiles.len(), 2);
+        editor.update(cx, |editor, _| {
+            assert_eq!(editor.minimap_cache.cached_tile_count(), 3)
+        });
+
+        // Editing the buffer rebuilds the visible tiles.
+        editor.update(cx, |editor, cx| {
+            editor
+                .buffer()
+                .update(cx, |buffer, cx| buffer.edit([(0..0, "x")], None, cx))
+        });
+        draw(cx);
+        editor.update(cx, |editor, _| {
+            assert_eq!(editor.minimap_cache.cached_tile_count(), 2)
+        });
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
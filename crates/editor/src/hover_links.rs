@@ -2,17 +2,20 @@ use crate::{
     hover_popover::{self, InlayHover},
     scroll::ScrollAmount,
     Anchor, Editor, EditorSnapshot, FindAllReferences, GoToDefinition, GoToTypeDefinition,
-    GotoDefinitionKind, InlayId, Navigated, PointForPosition, SelectPhase,
+    GotoDefinitionKind, InlayId, Navigated, PointForPosition, SelectPhase, SemanticsProvider,
 };
-use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
+use collections::HashMap;
+use futures::{future::Shared, FutureExt as _};
+use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext, WeakView};
 use language::{Bias, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
-    HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip, LocationLink, Project,
-    ResolveState, ResolvedPath,
+    DocumentLink, HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip,
+    LocationLink, Project, ResolveState, ResolvedPath,
 };
-use std::ops::Range;
+use std::{ops::Range, rc::Rc, sync::Arc};
+use text::BufferId;
 use theme::ActiveTheme as _;
 use util::{maybe, ResultExt, TryFutureExt as _};
 
@@ -66,6 +69,15 @@ pub enum HoverLink {
     File(ResolvedPath),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
+    /// A link reported by the language server, whose target may only be resolved when it is followed.
+    DocumentLink(Model<language::Buffer>, DocumentLink),
+}
+
+/// The links that language servers reported for the buffers hovered with the modifier held,
+/// requested once per buffer version.
+#[derive(Default)]
+pub(crate) struct DocumentLinks {
+    links: HashMap<BufferId, (clock::Global, Shared<Task<Option<Arc<[DocumentLink]>>>>)>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -481,7 +493,7 @@ pub fn show_link_definition(
         || hovered_link_state
             .links
             .first()
-            .is_some_and(|d| matches!(d, HoverLink::Url(_)));
+            .is_some_and(|d| matches!(d, HoverLink::Url(_) | HoverLink::DocumentLink(..)));
 
     if same_kind {
         if is_cached && (hovered_link_state.last_trigger_point == trigger_point)
@@ -506,7 +518,18 @@ pub fn show_link_definition(
         async move {
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
+                    if let Some((link_range, link)) =
+                        find_document_link(&this, &buffer, buffer_position, &mut cx).await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+                        Some((range, vec![link]))
+                    } else if let Some((url_range, url)) =
+                        find_url(&buffer, buffer_position, cx.clone())
+                    {
                         this.update(&mut cx, |_, _| {
                             let range = maybe!({
                                 let start =
@@ -625,6 +648,90 @@ pub fn show_link_definition(
     editor.hovered_link_state = Some(hovered_link_state);
 }
 
+impl Editor {
+    /// Returns the links that language servers reported for the buffer, reusing the ones
+    /// requested for its current version.
+    fn document_links(
+        &mut self,
+        buffer: &Model<language::Buffer>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Shared<Task<Option<Arc<[DocumentLink]>>>>> {
+        let provider = self.semantics_provider.clone()?;
+        let (buffer_id, version) = {
+            let buffer = buffer.read(cx);
+            (buffer.remote_id(), buffer.version())
+        };
+        if let Some((cached_version, links)) = self.document_links.links.get(&buffer_id) {
+            if *cached_version == version {
+                return Some(links.clone());
+            }
+        }
+
+        let task = provider.document_links(buffer, cx)?;
+        let links = cx
+            .spawn(|_, _| async move { task.await.log_err().map(Arc::from) })
+            .shared();
+        self.document_links
+            .links
+            .insert(buffer_id, (version, links.clone()));
+        Some(links)
+    }
+}
+
+/// Returns the link that the language server reported at the given position, if any.
+pub(crate) async fn find_document_link(
+    editor: &WeakView<Editor>,
+    buffer: &Model<language::Buffer>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let links = editor
+        .update(cx, |editor, cx| editor.document_links(buffer, cx))
+        .ok()??
+        .await?;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let link = links.iter().find(|link| {
+        link.range.start.cmp(&position, &snapshot).is_le()
+            && link.range.end.cmp(&position, &snapshot).is_ge()
+    })?;
+    Some((
+        link.range.clone(),
+        HoverLink::DocumentLink(buffer.clone(), link.clone()),
+    ))
+}
+
+/// Resolves the target of a followed document link, if the server left it out, and returns
+/// the file or URL it points to.
+pub(crate) async fn document_link_target(
+    buffer: &Model<language::Buffer>,
+    link: DocumentLink,
+    provider: Option<&Rc<dyn SemanticsProvider>>,
+    project: Option<Model<Project>>,
+    cx: &mut AsyncWindowContext,
+) -> Option<HoverLink> {
+    let target = match link.target.clone() {
+        Some(target) => target,
+        None => cx
+            .update(|cx| provider?.resolve_document_link(buffer, link, cx))
+            .ok()??
+            .await
+            .log_err()??,
+    };
+
+    if target.scheme() == "file" {
+        let path = target.to_file_path().ok()?;
+        let resolved_path = project?
+            .update(cx, |project, cx| {
+                project.resolve_abs_path(&path.to_string_lossy(), cx)
+            })
+            .ok()?
+            .await?;
+        Some(HoverLink::File(resolved_path))
+    } else {
+        Some(HoverLink::Url(target.to_string()))
+    }
+}
+
 pub(crate) fn find_url(
     buffer: &Model<language::Buffer>,
     position: text::Anchor,
@@ -838,7 +945,10 @@ mod tests {
     use gpui::Modifiers;
     use indoc::indoc;
     use language::language_settings::InlayHintSettings;
-    use lsp::request::{GotoDefinition, GotoTypeDefinition};
+    use lsp::request::{
+        DocumentLinkRequest, DocumentLinkResolve, GotoDefinition, GotoTypeDefinition,
+    };
+    use serde_json::json;
     use util::assert_set_eq;
     use workspace::item::Item;
 
@@ -1408,6 +1518,137 @@ mod tests {
         assert_eq!(cx.opened_url(), Some("https://zed.dev/releases".into()));
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            use serde::Deserializeˇ;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            use «serde»::Deserialize;
+        "});
+        let mut requests =
+            cx.handle_request::<DocumentLinkRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: Some("https://docs.rs/serde".parse().unwrap()),
+                    tooltip: None,
+                    data: None,
+                }]))
+            });
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            use seˇrde::Deserialize;
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            use «serdeˇ»::Deserialize;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+    }
+
+    #[gpui::test]
+    async fn test_document_links_resolved_when_followed(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            use serde::Deserializeˇ;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            use «serde»::Deserialize;
+        "});
+        let mut link_requests =
+            cx.handle_request::<DocumentLinkRequest, _, _>(move |_, _, _| async move {
+                Ok(Some(vec![
+                    lsp::DocumentLink {
+                        range: link_range,
+                        target: None,
+                        tooltip: None,
+                        data: Some(json!("serde")),
+                    },
+                    lsp::DocumentLink {
+                        range: lsp::Range::new(
+                            lsp::Position::new(0, 11),
+                            lsp::Position::new(0, 22),
+                        ),
+                        target: None,
+                        tooltip: None,
+                        data: Some(json!("Deserialize")),
+                    },
+                ]))
+            });
+        let mut resolve_requests =
+            cx.handle_request::<DocumentLinkResolve, _, _>(move |_, link, _| async move {
+                let name = link.data.as_ref().and_then(|data| data.as_str()).unwrap();
+                Ok(lsp::DocumentLink {
+                    target: Some(format!("https://docs.rs/{name}").parse().unwrap()),
+                    ..link
+                })
+            });
+
+        // Links are requested once per buffer version, and not resolved while hovering.
+        let serde_coord = cx.pixel_position(indoc! {"
+            use seˇrde::Deserialize;
+        "});
+        let deserialize_coord = cx.pixel_position(indoc! {"
+            use serde::Deseˇrialize;
+        "});
+        cx.simulate_mouse_move(serde_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.simulate_mouse_move(deserialize_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            use serde::«Deserializeˇ»;
+        "});
+        cx.simulate_mouse_move(serde_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            use «serdeˇ»::Deserialize;
+        "});
+        assert!(link_requests.next().await.is_some());
+        assert!(link_requests.try_next().is_err());
+
+        // Only the clicked link is resolved.
+        cx.simulate_click(serde_coord, Modifiers::secondary_key());
+        resolve_requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+        assert!(resolve_requests.try_next().is_err());
+
+        // Editing the buffer requests the links again.
+        cx.update_editor(|editor, cx| editor.insert(" ", cx));
+        cx.simulate_mouse_move(deserialize_coord, None, Modifiers::secondary_key());
+        cx.run_until_parked();
+        assert!(link_requests.next().await.is_some());
+    }
+
     #[gpui::test]
     async fn test_surrounding_filename(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
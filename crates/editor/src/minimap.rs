@@ -0,0 +1,222 @@
+//! The minimap is a downscaled overview of the buffer, drawn next to the scrollbar.
+//!
+//! Each display row is drawn as a strip of colored blocks, one pixel wide per character.
+//! Rows are grouped into tiles that are built from the [`DisplaySnapshot`] once and reused
+//! until the snapshot changes, so that scrolling through large files only builds the tiles
+//! that come into view.
+
+use crate::{display_map::DisplaySnapshot, DisplayRow};
+use collections::HashMap;
+use gpui::{point, px, Bounds, Hitbox, Hsla, PaintQuad, Pixels, SharedString};
+use std::{ops::Range, sync::Arc};
+use theme::SyntaxTheme;
+
+/// The number of display rows in a tile.
+const TILE_ROWS: u32 = 256;
+
+/// Tiles outside of the minimap are dropped once more than this many are cached.
+const MAX_CACHED_TILES: usize = 16;
+
+/// Identifies the contents of a [`DisplaySnapshot`] that the tiles were built from.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MinimapCacheKey {
+    pub edit_count: usize,
+    pub non_text_state_update_count: usize,
+    pub transform_versions: [usize; 3],
+    pub max_row: DisplayRow,
+    /// Soft wrapping depends on the width of the editor.
+    pub editor_width: Pixels,
+    pub max_columns: u32,
+    pub theme: SharedString,
+}
+
+#[derive(Default)]
+pub(crate) struct MinimapCache {
+    key: Option<MinimapCacheKey>,
+    tiles: HashMap<u32, Arc<MinimapTile>>,
+}
+
+impl MinimapCache {
+    /// Returns the tiles covering the given rows, building the ones that aren't cached.
+    pub fn tiles(
+        &mut self,
+        key: MinimapCacheKey,
+        rows: Range<DisplayRow>,
+        snapshot: &DisplaySnapshot,
+        syntax: &SyntaxTheme,
+        text_color: Hsla,
+    ) -> Vec<Arc<MinimapTile>> {
+        if self.key.as_ref() != Some(&key) {
+            self.tiles.clear();
+            self.key = Some(key.clone());
+        }
+        if rows.is_empty() {
+            return Vec::new();
+        }
+
+        let tile_range = rows.start.0 / TILE_ROWS..(rows.end.0 - 1) / TILE_ROWS + 1;
+        if self.tiles.len() > MAX_CACHED_TILES {
+            self.tiles.retain(|ix, _| tile_range.contains(ix));
+        }
+
+        tile_range
+            .map(|ix| {
+                self.tiles
+                    .entry(ix)
+                    .or_insert_with(|| {
+                        let start_row = DisplayRow(ix * TILE_ROWS);
+                        let end_row = DisplayRow(((ix + 1) * TILE_ROWS).min(key.max_row.0 + 1));
+                        Arc::new(MinimapTile::build(
+                            start_row..end_row,
+                            snapshot,
+                            syntax,
+                            text_color,
+                            key.max_columns,
+                        ))
+                    })
+                    .clone()
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub fn cached_tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+/// A group of consecutive display rows, reduced to the colored runs of text they contain.
+pub(crate) struct MinimapTile {
+    pub runs: Vec<MinimapRun>,
+}
+
+/// Consecutive characters of a display row that aren't whitespace and share a color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MinimapRun {
+    pub row: DisplayRow,
+    pub start_column: u32,
+    pub len: u32,
+    pub color: Hsla,
+}
+
+impl MinimapTile {
+    fn build(
+        rows: Range<DisplayRow>,
+        snapshot: &DisplaySnapshot,
+        syntax: &SyntaxTheme,
+        text_color: Hsla,
+        max_columns: u32,
+    ) -> Self {
+        let mut runs = Vec::new();
+        let mut current_run: Option<MinimapRun> = None;
+        let mut row = rows.start;
+        let mut column = 0;
+        for chunk in snapshot.chunks(rows, true, Default::default()) {
+            let color = chunk
+                .syntax_highlight_id
+                .and_then(|id| id.style(syntax)?.color)
+                .unwrap_or(text_color);
+
+            for ch in chunk.text.chars() {
+                if ch == '\n' {
+                    runs.extend(current_run.take());
+                    row.0 += 1;
+                    column = 0;
+                    continue;
+                }
+
+                if ch.is_whitespace() || column >= max_columns {
+                    runs.extend(current_run.take());
+                } else if let Some(run) = current_run
+                    .as_mut()
+                    .filter(|run| run.color == color && run.start_column + run.len == column)
+                {
+                    run.len += 1;
+                } else {
+                    runs.extend(current_run.replace(MinimapRun {
+                        row,
+                        start_column: column,
+                        len: 1,
+                        color,
+                    }));
+                }
+                column += 1;
+            }
+        }
+        runs.extend(current_run);
+
+        Self { runs }
+    }
+}
+
+pub(crate) struct MinimapLayout {
+    pub hitbox: Hitbox,
+    pub tiles: Vec<Arc<MinimapTile>>,
+    pub markers: Vec<PaintQuad>,
+    /// The display row drawn at the top of the minimap, which moves along with the editor's
+    /// scroll position when the buffer is taller than the minimap.
+    pub scroll_top: f32,
+    /// The display rows visible in the editor.
+    pub visible_row_range: Range<f32>,
+    /// How far the viewport moves within the minimap when the editor scrolls by one row.
+    pub viewport_row_height: Pixels,
+}
+
+impl MinimapLayout {
+    pub const ROW_HEIGHT: Pixels = px(2.);
+    pub const COLUMN_WIDTH: Pixels = px(1.);
+    pub const MARKER_WIDTH: Pixels = px(2.);
+
+    /// The number of display rows that fit in the minimap.
+    pub fn row_count(height: Pixels) -> f32 {
+        height / Self::ROW_HEIGHT
+    }
+
+    pub fn y_for_row(&self, row: f32) -> Pixels {
+        self.hitbox.top() + (row - self.scroll_top) * Self::ROW_HEIGHT
+    }
+
+    /// The region of the minimap showing the rows that are visible in the editor.
+    pub fn viewport_bounds(&self) -> Bounds<Pixels> {
+        Bounds::from_corners(
+            point(
+                self.hitbox.left(),
+                self.y_for_row(self.visible_row_range.start),
+            ),
+            point(
+                self.hitbox.right(),
+                self.y_for_row(self.visible_row_range.end),
+            ),
+        )
+    }
+
+    pub fn run_bounds(&self, run: &MinimapRun) -> Bounds<Pixels> {
+        let origin = point(
+            self.hitbox.left() + Self::MARKER_WIDTH + run.start_column as f32 * Self::COLUMN_WIDTH,
+            self.y_for_row(run.row.as_f32()),
+        );
+        Bounds::from_corners(
+            origin,
+            point(
+                (origin.x + run.len as f32 * Self::COLUMN_WIDTH)
+                    .min(self.hitbox.right() - Self::MARKER_WIDTH),
+                origin.y + Self::ROW_HEIGHT * 0.75,
+            ),
+        )
+    }
+
+    /// The bounds of a marker spanning the given rows, horizontally positioned relative to
+    /// the left edge of the minimap.
+    pub fn marker_bounds(&self, rows: Range<DisplayRow>, x: Range<Pixels>) -> Bounds<Pixels> {
+        Bounds::from_corners(
+            point(
+                self.hitbox.left() + x.start,
+                self.y_for_row(rows.start.as_f32()),
+            ),
+            point(
+                self.hitbox.left() + x.end,
+                self.y_for_row(rows.end.as_f32() + 1.),
+            ),
+        )
+    }
+}
@@ -464,4 +464,49 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
     ) -> Option<Task<gpui::Result<project::ProjectTransaction>>> {
         None
     }
+
+    fn preview_rename(
+        &self,
+        _: &Model<Buffer>,
+        _: text::Anchor,
+        _: String,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<project::WorkspaceEditPreview>>> {
+        None
+    }
+
+    fn document_links(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Vec<project::DocumentLink>>>> {
+        None
+    }
+
+    fn resolve_document_link(
+        &self,
+        _: &Model<Buffer>,
+        _: project::DocumentLink,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Option<lsp::Url>>>> {
+        None
+    }
+
+    fn document_colors(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Vec<project::DocumentColor>>>> {
+        None
+    }
+
+    fn color_presentations(
+        &self,
+        _: &Model<Buffer>,
+        _: Range<text::Anchor>,
+        _: lsp::Color,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Vec<project::ColorPresentation>>>> {
+        None
+    }
 }
@@ -0,0 +1,993 @@
+use crate::{scroll::Autoscroll, Editor};
+use gpui::{
+    uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    UniformListScrollHandle, WeakView,
+};
+use language::{Buffer, Point, ToPoint as _};
+use lsp::{ResourceOp, Url};
+use project::{Location, Project, WorkspaceEditChange, WorkspaceEditPreview};
+use std::ops::Range;
+use text::Anchor;
+use ui::{prelude::*, Checkbox, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{item::ItemEvent, Item, Workspace};
+
+/// Lists the changes of a workspace edit grouped by file, so that individual edits or whole
+/// files can be excluded before the edit is applied.
+///
+/// References don't modify anything, so they are listed by [`ReferencesPreview`] instead.
+pub struct RefactorPreview {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    preview: WorkspaceEditPreview,
+    /// Whether each of the preview's changes will be applied, by index.
+    included: Vec<bool>,
+    rows: Vec<PreviewRow>,
+    title: SharedString,
+    applying: bool,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+enum PreviewRow {
+    Resource {
+        change_ix: usize,
+        description: SharedString,
+    },
+    File {
+        path: SharedString,
+        change_ixs: Vec<usize>,
+    },
+    Edit {
+        change_ix: usize,
+        buffer: Model<Buffer>,
+        range: Range<Anchor>,
+        row: u32,
+        old_text: SharedString,
+        new_text: SharedString,
+    },
+}
+
+impl RefactorPreview {
+    pub fn new(
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        preview: WorkspaceEditPreview,
+        title: impl Into<SharedString>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        // Changes that the language server wants to be confirmed are opt-in.
+        let included = preview
+            .changes
+            .iter()
+            .map(|change| !preview.needs_confirmation(change))
+            .collect();
+        let rows = Self::build_rows(&project, &preview, cx);
+        Self {
+            project,
+            workspace,
+            preview,
+            included,
+            rows,
+            title: title.into(),
+            applying: false,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        }
+    }
+
+    /// Resource operations come first, followed by the text edits grouped by the buffer they
+    /// modify, in the order in which the buffers first appear in the workspace edit.
+    fn build_rows(
+        project: &Model<Project>,
+        preview: &WorkspaceEditPreview,
+        cx: &AppContext,
+    ) -> Vec<PreviewRow> {
+        let mut rows = Vec::new();
+        let mut edits_by_buffer = Vec::<(Model<Buffer>, Vec<usize>)>::new();
+        for (change_ix, change) in preview.changes.iter().enumerate() {
+            match change {
+                WorkspaceEditChange::Resource(op) => rows.push(PreviewRow::Resource {
+                    change_ix,
+                    description: describe_resource_op(project, op, cx).into(),
+                }),
+                WorkspaceEditChange::Edit { buffer, .. } => {
+                    match edits_by_buffer.iter_mut().find(|(b, _)| *b == *buffer) {
+                        Some((_, change_ixs)) => change_ixs.push(change_ix),
+                        None => edits_by_buffer.push((buffer.clone(), vec![change_ix])),
+                    }
+                }
+            }
+        }
+
+        for (buffer, change_ixs) in edits_by_buffer {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            rows.push(PreviewRow::File {
+                path: buffer_path(&buffer, cx),
+                change_ixs: change_ixs.clone(),
+            });
+
+            for change_ix in change_ixs {
+                let WorkspaceEditChange::Edit { edits, .. } = &preview.changes[change_ix] else {
+                    continue;
+                };
+                let (Some((first, _)), Some((last, _))) = (edits.first(), edits.last()) else {
+                    continue;
+                };
+                let range = first.start..last.end;
+
+                // A single LSP edit may have been split into several smaller ones, so rebuild
+                // the replacement text of the whole range.
+                let mut new_text = String::new();
+                let mut offset = first.start;
+                for (edit_range, text) in edits {
+                    new_text.extend(buffer_snapshot.text_for_range(offset..edit_range.start));
+                    new_text.push_str(text);
+                    offset = edit_range.end;
+                }
+
+                rows.push(PreviewRow::Edit {
+                    change_ix,
+                    buffer: buffer.clone(),
+                    row: range.start.to_point(&buffer_snapshot).row,
+                    old_text: buffer_snapshot
+                        .text_for_range(range.clone())
+                        .collect::<String>()
+                        .into(),
+                    new_text: new_text.into(),
+                    range,
+                });
+            }
+        }
+        rows
+    }
+
+    fn selection_for(&self, change_ixs: &[usize]) -> Selection {
+        let included_count = change_ixs.iter().filter(|ix| self.included[**ix]).count();
+        if included_count == 0 {
+            Selection::Unselected
+        } else if included_count == change_ixs.len() {
+            Selection::Selected
+        } else {
+            Selection::Indeterminate
+        }
+    }
+
+    fn toggle_changes(&mut self, change_ixs: &[usize], cx: &mut ViewContext<Self>) {
+        let include = self.selection_for(change_ixs) != Selection::Selected;
+        for change_ix in change_ixs {
+            self.included[*change_ix] = include;
+        }
+        cx.notify();
+    }
+
+    fn apply(&mut self, cx: &mut ViewContext<Self>) {
+        if self.applying {
+            return;
+        }
+        self.applying = true;
+        cx.notify();
+
+        let preview = WorkspaceEditPreview {
+            changes: self
+                .preview
+                .changes
+                .iter()
+                .zip(&self.included)
+                .filter(|(_, included)| **included)
+                .map(|(change, _)| change.clone())
+                .collect(),
+            annotations: self.preview.annotations.clone(),
+        };
+        let apply = self.project.update(cx, |project, cx| {
+            project.apply_workspace_edit_preview(preview, cx)
+        });
+        let workspace = self.workspace.clone();
+        let title = self.title.to_string();
+        cx.spawn(|this, mut cx| async move {
+            let result = apply.await;
+            this.update(&mut cx, |this, cx| {
+                this.applying = false;
+                cx.notify();
+            })?;
+            let project_transaction = result?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let entries = project_transaction.0.into_iter().collect::<Vec<_>>();
+                if !entries.is_empty() {
+                    Editor::open_transaction_in_multibuffer(workspace, entries, title, cx);
+                }
+            })?;
+            this.update(&mut cx, |_, cx| cx.emit(ItemEvent::CloseItem))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn discard(&mut self, cx: &mut ViewContext<Self>) {
+        cx.emit(ItemEvent::CloseItem);
+    }
+
+    fn render_annotation(&self, change_ix: usize) -> Option<impl IntoElement> {
+        let change = &self.preview.changes[change_ix];
+        let annotation = self.preview.annotation(change)?;
+        let description = annotation.description.clone();
+        Some(
+            h_flex()
+                .id(("refactor-preview-annotation", change_ix))
+                .gap_1()
+                .when(self.preview.needs_confirmation(change), |this| {
+                    this.child(
+                        Icon::new(IconName::Warning)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                })
+                .child(
+                    Label::new(annotation.label.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .when_some(description, |this, description| {
+                    this.tooltip(move |cx| Tooltip::text(description.clone(), cx))
+                }),
+        )
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        match &self.rows[ix] {
+            PreviewRow::Resource {
+                change_ix,
+                description,
+            } => {
+                let change_ix = *change_ix;
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(
+                        Checkbox::new(
+                            ("refactor-preview-checkbox", ix),
+                            self.selection_for(&[change_ix]),
+                        )
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.toggle_changes(&[change_ix], cx)),
+                        ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Icon::new(IconName::FileGeneric).size(IconSize::Small))
+                            .child(Label::new(description.clone()))
+                            .children(self.render_annotation(change_ix)),
+                    )
+                    .into_any_element()
+            }
+            PreviewRow::File { path, change_ixs } => {
+                let change_ixs = change_ixs.clone();
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .start_slot(
+                        Checkbox::new(
+                            ("refactor-preview-checkbox", ix),
+                            self.selection_for(&change_ixs),
+                        )
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.toggle_changes(&change_ixs, cx)),
+                        ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Icon::new(IconName::File).size(IconSize::Small))
+                            .child(Label::new(path.clone())),
+                    )
+                    .into_any_element()
+            }
+            PreviewRow::Edit {
+                change_ix,
+                buffer,
+                range,
+                row,
+                old_text,
+                new_text,
+            } => {
+                let change_ix = *change_ix;
+                let buffer = buffer.clone();
+                let range = range.clone();
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .indent_level(1)
+                    .start_slot(
+                        Checkbox::new(
+                            ("refactor-preview-checkbox", ix),
+                            self.selection_for(&[change_ix]),
+                        )
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.toggle_changes(&[change_ix], cx)),
+                        ),
+                    )
+                    .on_click(cx.listener(move |this, _, cx| {
+                        open_range(&this.workspace, buffer.clone(), range.clone(), cx)
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new(format!("{}", row + 1))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .when(!old_text.is_empty(), |this| {
+                                this.child(
+                                    Label::new(old_text.clone())
+                                        .color(Color::Deleted)
+                                        .strikethrough(true),
+                                )
+                            })
+                            .when(!new_text.is_empty(), |this| {
+                                this.child(Label::new(new_text.clone()).color(Color::Created))
+                            })
+                            .children(self.render_annotation(change_ix)),
+                    )
+                    .into_any_element()
+            }
+        }
+    }
+}
+
+fn buffer_path(buffer: &Model<Buffer>, cx: &AppContext) -> SharedString {
+    buffer
+        .read(cx)
+        .file()
+        .map(|file| file.full_path(cx).to_string_lossy().to_string())
+        .unwrap_or_else(|| "untitled".to_string())
+        .into()
+}
+
+/// Opens the buffer in the active pane and selects the given range in it.
+fn open_range(
+    workspace: &WeakView<Workspace>,
+    buffer: Model<Buffer>,
+    range: Range<Anchor>,
+    cx: &mut WindowContext,
+) {
+    workspace
+        .update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let range = {
+                let buffer = buffer.read(cx);
+                range.start.to_point(buffer)..range.end.to_point(buffer)
+            };
+            let editor = workspace.open_project_item::<Editor>(pane, buffer, true, true, cx);
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([range])
+                });
+            });
+        })
+        .log_err();
+}
+
+fn describe_resource_op(project: &Model<Project>, op: &ResourceOp, cx: &AppContext) -> String {
+    let path = |uri: &Url| {
+        let Ok(abs_path) = uri.to_file_path() else {
+            return uri.to_string();
+        };
+        match project.read(cx).find_worktree(&abs_path, cx) {
+            Some((_, relative_path)) => relative_path.to_string_lossy().to_string(),
+            None => abs_path.to_string_lossy().to_string(),
+        }
+    };
+    match op {
+        ResourceOp::Create(op) => format!("Create {}", path(&op.uri)),
+        ResourceOp::Rename(op) => format!("Rename {} → {}", path(&op.old_uri), path(&op.new_uri)),
+        ResourceOp::Delete(op) => format!("Delete {}", path(&op.uri)),
+    }
+}
+
+impl Render for RefactorPreview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let included_count = self.included.iter().filter(|included| **included).count();
+        v_flex()
+            .key_context("RefactorPreview")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(self.title.clone()))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Button::new("discard-refactor", "Discard")
+                                    .on_click(cx.listener(|this, _, cx| this.discard(cx))),
+                            )
+                            .child(
+                                Button::new(
+                                    "apply-refactor",
+                                    format!("Apply {included_count} of {}", self.included.len()),
+                                )
+                                .style(ButtonStyle::Filled)
+                                .disabled(self.applying || included_count == 0)
+                                .on_click(cx.listener(|this, _, cx| this.apply(cx))),
+                            ),
+                    ),
+            )
+            .child(
+                uniform_list(
+                    cx.view().clone(),
+                    "refactor-preview-rows",
+                    self.rows.len(),
+                    |this, range, cx| range.map(|ix| this.render_row(ix, cx)).collect(),
+                )
+                .track_scroll(self.scroll_handle.clone())
+                .flex_grow(),
+            )
+    }
+}
+
+impl FocusableView for RefactorPreview {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for RefactorPreview {}
+
+impl Item for RefactorPreview {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Replace))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+/// Lists the references found with `PreviewAllReferences` grouped by file, together with the
+/// line each of them is on.
+pub struct ReferencesPreview {
+    workspace: WeakView<Workspace>,
+    rows: Vec<ReferenceRow>,
+    title: SharedString,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+enum ReferenceRow {
+    File {
+        path: SharedString,
+        reference_count: usize,
+    },
+    Reference {
+        buffer: Model<Buffer>,
+        range: Range<Anchor>,
+        row: u32,
+        line: SharedString,
+    },
+}
+
+impl ReferencesPreview {
+    pub fn new(
+        workspace: WeakView<Workspace>,
+        locations: Vec<Location>,
+        title: impl Into<SharedString>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self {
+            workspace,
+            rows: Self::build_rows(locations, cx),
+            title: title.into(),
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        }
+    }
+
+    /// Groups the references by the buffer they are in, in the order in which the buffers first
+    /// appear in the locations, and sorts the references of each buffer by their position.
+    fn build_rows(locations: Vec<Location>, cx: &AppContext) -> Vec<ReferenceRow> {
+        let mut ranges_by_buffer = Vec::<(Model<Buffer>, Vec<Range<Anchor>>)>::new();
+        for location in locations {
+            match ranges_by_buffer
+                .iter_mut()
+                .find(|(buffer, _)| *buffer == location.buffer)
+            {
+                Some((_, ranges)) => ranges.push(location.range),
+                None => ranges_by_buffer.push((location.buffer, vec![location.range])),
+            }
+        }
+
+        let mut rows = Vec::new();
+        for (buffer, mut ranges) in ranges_by_buffer {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, &buffer_snapshot));
+            rows.push(ReferenceRow::File {
+                path: buffer_path(&buffer, cx),
+                reference_count: ranges.len(),
+            });
+            for range in ranges {
+                let row = range.start.to_point(&buffer_snapshot).row;
+                let line = buffer_snapshot
+                    .text_for_range(
+                        Point::new(row, 0)..Point::new(row, buffer_snapshot.line_len(row)),
+                    )
+                    .collect::<String>();
+                rows.push(ReferenceRow::Reference {
+                    buffer: buffer.clone(),
+                    range,
+                    row,
+                    line: line.trim().to_string().into(),
+                });
+            }
+        }
+        rows
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        match &self.rows[ix] {
+            ReferenceRow::File {
+                path,
+                reference_count,
+            } => ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Icon::new(IconName::File).size(IconSize::Small))
+                        .child(Label::new(path.clone()))
+                        .child(
+                            Label::new(reference_count.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .into_any_element(),
+            ReferenceRow::Reference {
+                buffer,
+                range,
+                row,
+                line,
+            } => {
+                let buffer = buffer.clone();
+                let range = range.clone();
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .indent_level(1)
+                    .on_click(cx.listener(move |this, _, cx| {
+                        open_range(&this.workspace, buffer.clone(), range.clone(), cx)
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Label::new(format!("{}", row + 1))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(Label::new(line.clone())),
+                    )
+                    .into_any_element()
+            }
+        }
+    }
+}
+
+impl Render for ReferencesPreview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("ReferencesPreview")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(self.title.clone())),
+            )
+            .child(
+                uniform_list(
+                    cx.view().clone(),
+                    "references-preview-rows",
+                    self.rows.len(),
+                    |this, range, cx| range.map(|ix| this.render_row(ix, cx)).collect(),
+                )
+                .track_scroll(self.scroll_handle.clone())
+                .flex_grow(),
+            )
+    }
+}
+
+impl FocusableView for ReferencesPreview {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for ReferencesPreview {}
+
+impl Item for ReferencesPreview {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::MagnifyingGlass))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{ConfirmRename, PreviewAllReferences, Rename},
+        editor_tests::{init_test, rust_lang},
+        Navigated,
+    };
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language::{FakeLspAdapter, ToOffset as _};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::ops::Deref as _;
+
+    #[gpui::test]
+    async fn test_apply_only_included_changes(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let (project, workspace, mut fake_servers) = init_rename_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        handle_rename(&fake_server);
+
+        let preview = project
+            .update(cx, |project, cx| {
+                project.preview_rename(buffer.clone(), 7, "FOUR".to_string(), cx)
+            })
+            .await
+            .unwrap();
+        let refactor_preview = workspace
+            .update(cx, |_, cx| {
+                let workspace = cx.view().downgrade();
+                cx.new_view(|cx| {
+                    RefactorPreview::new(project.clone(), workspace, preview, "Rename", cx)
+                })
+            })
+            .unwrap();
+
+        refactor_preview.update(cx, |refactor_preview, cx| {
+            // The change whose annotation needs confirmation starts excluded.
+            assert_eq!(refactor_preview.included, [true, true, false, true]);
+
+            // Exclude a single edit of two.rs, include the one that needed confirmation, and
+            // exclude the whole of three.rs.
+            refactor_preview.toggle_changes(&[1], cx);
+            refactor_preview.toggle_changes(&[2], cx);
+            let three_change_ixs = file_change_ixs(refactor_preview, "three.rs");
+            assert_eq!(three_change_ixs, [3]);
+            refactor_preview.toggle_changes(&three_change_ixs, cx);
+
+            assert_eq!(refactor_preview.included, [true, false, true, false]);
+            assert_eq!(
+                refactor_preview.selection_for(&file_change_ixs(refactor_preview, "two.rs")),
+                Selection::Indeterminate
+            );
+            refactor_preview.apply(cx);
+        });
+        cx.run_until_parked();
+
+        for (path, expected_text) in [
+            ("/dir/one.rs", "const FOUR: usize = 1;"),
+            ("/dir/two.rs", "const TWO: usize = one::ONE + one::FOUR;"),
+            ("/dir/three.rs", "const THREE: usize = one::ONE;"),
+        ] {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            assert_eq!(
+                buffer.read_with(cx, |buffer, _| buffer.text()),
+                expected_text
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_confirm_rename_opens_preview_when_confirmation_is_needed(
+        cx: &mut TestAppContext,
+    ) {
+        init_test(cx, |_| {});
+        let (_, workspace, mut fake_servers) = init_rename_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                let worktree_id = workspace
+                    .project()
+                    .read(cx)
+                    .worktrees(cx)
+                    .next()
+                    .unwrap()
+                    .read(cx)
+                    .id();
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+
+        let prepare_rename = editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([7..7]));
+            editor.rename(&Rename, cx).unwrap()
+        });
+        fake_server
+            .handle_request::<lsp::request::PrepareRenameRequest, _, _>(|_, _| async move {
+                Ok(Some(lsp::PrepareRenameResponse::Range(lsp::Range::new(
+                    lsp::Position::new(0, 6),
+                    lsp::Position::new(0, 9),
+                ))))
+            })
+            .next()
+            .await
+            .unwrap();
+        prepare_rename.await.unwrap();
+        editor.update(cx, |editor, cx| {
+            let rename = editor.pending_rename().unwrap();
+            rename.editor.update(cx, |rename_editor, cx| {
+                rename_editor.buffer().update(cx, |rename_buffer, cx| {
+                    rename_buffer.edit([(0..3, "FOUR")], None, cx);
+                });
+            });
+        });
+
+        handle_rename(&fake_server);
+        let confirm_rename = editor.update(cx, |editor, cx| {
+            Editor::confirm_rename(editor, &ConfirmRename, cx).unwrap()
+        });
+        confirm_rename.await.unwrap();
+        cx.run_until_parked();
+
+        // Nothing is applied, and the preview is opened instead.
+        assert_eq!(
+            editor.update(cx, |editor, cx| editor.text(cx)),
+            "const ONE: usize = 1;"
+        );
+        let refactor_preview = workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .active_item(cx)
+                    .and_then(|item| item.downcast::<RefactorPreview>())
+            })
+            .unwrap()
+            .expect("the refactor preview to be the active item");
+        refactor_preview.update(cx, |refactor_preview, _| {
+            assert_eq!(refactor_preview.included, [true, true, false, true]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_preview_all_references_groups_them_by_file(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let (_, workspace, mut fake_servers) = init_rename_test(cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace.deref(), cx);
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                let worktree_id = workspace
+                    .project()
+                    .read(cx)
+                    .worktrees(cx)
+                    .next()
+                    .unwrap()
+                    .read(cx)
+                    .id();
+                workspace.open_path((worktree_id, "one.rs"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::request::References, _, _>(|_, _| async move {
+            let location = |uri: &str, start, end| lsp::Location {
+                uri: lsp::Url::from_file_path(uri).unwrap(),
+                range: lsp::Range::new(lsp::Position::new(0, start), lsp::Position::new(0, end)),
+            };
+            Ok(Some(vec![
+                location("/dir/two.rs", 35, 38),
+                location("/dir/three.rs", 26, 29),
+                location("/dir/two.rs", 24, 27),
+                location("/dir/one.rs", 6, 9),
+            ]))
+        });
+
+        let preview_all_references = editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| s.select_ranges([7..7]));
+            editor
+                .preview_all_references(&PreviewAllReferences, cx)
+                .unwrap()
+        });
+        assert_eq!(preview_all_references.await.unwrap(), Navigated::Yes);
+        cx.run_until_parked();
+
+        let references_preview = workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .active_item(cx)
+                    .and_then(|item| item.downcast::<ReferencesPreview>())
+            })
+            .unwrap()
+            .expect("the references preview to be the active item");
+        references_preview.update(cx, |references_preview, cx| {
+            let rows = references_preview
+                .rows
+                .iter()
+                .map(|row| match row {
+                    ReferenceRow::File {
+                        path,
+                        reference_count,
+                    } => format!("{path} ({reference_count})"),
+                    ReferenceRow::Reference {
+                        buffer,
+                        range,
+                        row,
+                        line,
+                    } => {
+                        let buffer = buffer.read(cx);
+                        let offset = range.start.to_offset(buffer);
+                        format!("  {}:{offset} {line}", row + 1)
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                rows,
+                [
+                    "dir/two.rs (2)",
+                    "  1:24 const TWO: usize = one::ONE + one::ONE;",
+                    "  1:35 const TWO: usize = one::ONE + one::ONE;",
+                    "dir/three.rs (1)",
+                    "  1:26 const THREE: usize = one::ONE;",
+                    "dir/one.rs (1)",
+                    "  1:6 const ONE: usize = 1;",
+                ]
+            );
+        });
+    }
+
+    async fn init_rename_test(
+        cx: &mut TestAppContext,
+    ) -> (
+        Model<Project>,
+        gpui::WindowHandle<Workspace>,
+        futures::channel::mpsc::UnboundedReceiver<lsp::FakeLanguageServer>,
+    ) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE;",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(rust_lang());
+        let fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    rename_provider: Some(lsp::OneOf::Right(lsp::RenameOptions {
+                        prepare_provider: Some(true),
+                        work_done_progress_options: Default::default(),
+                    })),
+                    references_provider: Some(lsp::OneOf::Left(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        (project, workspace, fake_servers)
+    }
+
+    /// Renames `ONE` everywhere, with one of the references in two.rs needing confirmation.
+    fn handle_rename(fake_server: &lsp::FakeLanguageServer) {
+        fake_server.handle_request::<lsp::request::Rename, _, _>(|params, _| async move {
+            let new_name = params.new_name;
+            let edit = |uri: &str, edits| {
+                lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
+                    text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                        uri: lsp::Url::from_file_path(uri).unwrap(),
+                        version: None,
+                    },
+                    edits,
+                })
+            };
+            let text_edit = |start, end| {
+                lsp::TextEdit::new(
+                    lsp::Range::new(lsp::Position::new(0, start), lsp::Position::new(0, end)),
+                    new_name.clone(),
+                )
+            };
+            Ok(Some(lsp::WorkspaceEdit {
+                document_changes: Some(lsp::DocumentChanges::Operations(vec![
+                    edit("/dir/one.rs", vec![lsp::Edit::Plain(text_edit(6, 9))]),
+                    edit(
+                        "/dir/two.rs",
+                        vec![
+                            lsp::Edit::Plain(text_edit(24, 27)),
+                            lsp::Edit::Annotated(lsp::AnnotatedTextEdit {
+                                text_edit: text_edit(35, 38),
+                                annotation_id: "unsure".to_string(),
+                            }),
+                        ],
+                    ),
+                    edit("/dir/three.rs", vec![lsp::Edit::Plain(text_edit(26, 29))]),
+                ])),
+                change_annotations: Some(
+                    [(
+                        "unsure".to_string(),
+                        lsp::ChangeAnnotation {
+                            label: "Possible reference".to_string(),
+                            needs_confirmation: Some(true),
+                            description: None,
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                ..Default::default()
+            }))
+        });
+    }
+
+    fn file_change_ixs(refactor_preview: &RefactorPreview, file_name: &str) -> Vec<usize> {
+        refactor_preview
+            .rows
+            .iter()
+            .find_map(|row| match row {
+                PreviewRow::File { path, change_ixs } if path.ends_with(file_name) => {
+                    Some(change_ixs.clone())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+}
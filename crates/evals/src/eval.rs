@@ -1,3 +1,5 @@
+mod index;
+
 use ::fs::{Fs, RealFs};
 use anyhow::Result;
 use clap::Parser;
@@ -54,6 +56,11 @@ enum Commands {
         #[arg(long)]
         repo: Option<String>,
     },
+    /// Build, search or export the semantic index of a project.
+    Index {
+        #[command(subcommand)]
+        command: index::IndexCommand,
+    },
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -126,6 +133,16 @@ fn main() -> Result<()> {
                 })
                 .detach();
             }
+            Commands::Index { command } => {
+                cx.spawn(|mut cx| async move {
+                    if let Err(err) = index::run_index_command(command, &mut cx).await {
+                        eprintln!("Error: {}", err);
+                        exit(1);
+                    }
+                    exit(0);
+                })
+                .detach();
+            }
         }
     });
 
@@ -0,0 +1,222 @@
+use crate::wait_for_indexing_complete;
+use ::fs::{Fs, RealFs};
+use anyhow::{anyhow, Context as _, Result};
+use client::{Client, UserStore};
+use clock::RealSystemClock;
+use feature_flags::FeatureFlagAppExt as _;
+use git::GitHostingProviderRegistry;
+use gpui::{AsyncAppContext, Context as _, Model};
+use language::LanguageRegistry;
+use node_runtime::NodeRuntime;
+use open_ai::OpenAiEmbeddingModel;
+use project::Project;
+use semantic_index::{
+    EmbeddingProvider, LocalEmbeddingModel, OllamaEmbeddingModel, OllamaEmbeddingProvider,
+    OpenAiEmbeddingProvider, ProjectIndex, SemanticDb,
+};
+use settings::SettingsStore;
+use std::{
+    io::{self, Write as _},
+    path::PathBuf,
+    sync::Arc,
+};
+
+#[derive(clap::Subcommand)]
+pub enum IndexCommand {
+    /// Index a project, or update its existing index.
+    Build {
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+    /// Search an indexed project, updating its index first.
+    Search {
+        #[command(flatten)]
+        index: IndexArgs,
+        query: String,
+        #[arg(long, default_value_t = 8)]
+        limit: usize,
+        /// Rank chunks by embedding similarity only, without keyword scoring.
+        #[arg(long)]
+        semantic: bool,
+    },
+    /// Write every chunk of a project's index to stdout, as JSON lines.
+    Export {
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct IndexArgs {
+    /// The directory of the project.
+    project: PathBuf,
+    /// The directory in which the index is stored.
+    #[arg(long, default_value = "target/index_db")]
+    db: PathBuf,
+    /// The provider used to embed chunks. Indices built with different
+    /// providers are stored separately.
+    #[arg(long, value_enum, default_value_t = Provider::Local)]
+    provider: Provider,
+    /// The directory containing `model.onnx` and `tokenizer.json`, for the local provider.
+    #[arg(long)]
+    model: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Provider {
+    Local,
+    Ollama,
+    OpenAi,
+}
+
+pub async fn run_index_command(command: IndexCommand, cx: &mut AsyncAppContext) -> Result<()> {
+    match command {
+        IndexCommand::Build { index } => {
+            let start = std::time::Instant::now();
+            open_project_index(&index, cx).await?;
+            eprintln!("Indexed {:?} in {:?}", index.project, start.elapsed());
+        }
+        IndexCommand::Search {
+            index,
+            query,
+            limit,
+            semantic,
+        } => {
+            let project_index = open_project_index(&index, cx).await?;
+            let (results, fs) = project_index.update(cx, |project_index, cx| {
+                let results = if semantic {
+                    project_index.search(vec![query], limit, cx)
+                } else {
+                    project_index.hybrid_search(vec![query], limit, cx)
+                };
+                (results, project_index.fs())
+            })?;
+            let results = SemanticDb::load_results(results.await?, &fs, cx).await?;
+            for result in results {
+                println!(
+                    "{}:{}-{}",
+                    result.full_path.display(),
+                    result.row_range.start() + 1,
+                    result.row_range.end() + 1
+                );
+                println!("{}\n", result.excerpt_content);
+            }
+        }
+        IndexCommand::Export { index } => {
+            let project_index = open_project_index(&index, cx).await?;
+            let chunks = project_index
+                .update(cx, |project_index, cx| project_index.export(cx))?
+                .await?;
+            let mut stdout = io::stdout().lock();
+            for chunk in chunks {
+                serde_json::to_writer(&mut stdout, &chunk)?;
+                writeln!(stdout)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Opens the index of the given project and waits for it to be up to date.
+async fn open_project_index(
+    args: &IndexArgs,
+    cx: &mut AsyncAppContext,
+) -> Result<Model<ProjectIndex>> {
+    let http_client = cx.update(|cx| {
+        let mut store = SettingsStore::new(cx);
+        store
+            .set_default_settings(settings::default_settings().as_ref(), cx)
+            .unwrap();
+        cx.set_global(store);
+        client::init_settings(cx);
+        language::init(cx);
+        Project::init_settings(cx);
+        // Indexing is only enabled for staff.
+        cx.update_flags(true, vec![]);
+        cx.http_client()
+    })?;
+
+    let (embedding_provider, db_name): (Arc<dyn EmbeddingProvider>, _) = match args.provider {
+        Provider::Local => {
+            let model_dir = args
+                .model
+                .as_ref()
+                .ok_or_else(|| anyhow!("the local provider requires --model"))?;
+            let model = LocalEmbeddingModel::from_dir(model_dir);
+            let provider = model.provider(cx.background_executor().clone())?;
+            (provider, format!("local-{}", model.name()))
+        }
+        Provider::Ollama => (
+            Arc::new(OllamaEmbeddingProvider::new(
+                http_client,
+                OllamaEmbeddingModel::NomicEmbedText,
+            )),
+            "ollama".to_string(),
+        ),
+        Provider::OpenAi => {
+            let api_key = std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY is not set")?;
+            (
+                Arc::new(OpenAiEmbeddingProvider::new(
+                    http_client,
+                    OpenAiEmbeddingModel::TextEmbedding3Small,
+                    open_ai::OPEN_AI_API_URL.to_string(),
+                    api_key,
+                )),
+                "openai".to_string(),
+            )
+        }
+    };
+
+    let project_dir = args
+        .project
+        .canonicalize()
+        .with_context(|| format!("project not found at {:?}", args.project))?;
+    let git_hosting_provider_registry = Arc::new(GitHostingProviderRegistry::new());
+    let fs = Arc::new(RealFs::new(git_hosting_provider_registry, None)) as Arc<dyn Fs>;
+    let client = cx.update(|cx| {
+        Client::new(
+            Arc::new(RealSystemClock),
+            Arc::new(http_client::HttpClientWithUrl::new(
+                cx.http_client(),
+                "https://zed.dev",
+                None,
+            )),
+            cx,
+        )
+    })?;
+    let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx))?;
+    let node_runtime = NodeRuntime::unavailable();
+    let language_registry = Arc::new(LanguageRegistry::new(cx.background_executor().clone()));
+    cx.update(|cx| languages::init(language_registry.clone(), node_runtime.clone(), cx))?;
+
+    let project = cx.update(|cx| {
+        Project::local(
+            client,
+            node_runtime,
+            user_store,
+            language_registry,
+            fs,
+            None,
+            cx,
+        )
+    })?;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(&project_dir, true, cx)
+        })?
+        .await?;
+    worktree
+        .update(cx, |worktree, _| {
+            worktree.as_local().unwrap().scan_complete()
+        })?
+        .await;
+
+    let db_path = args.db.join(format!("{db_name}.db"));
+    let mut semantic_index = SemanticDb::new(db_path, embedding_provider, cx).await?;
+    let project_index = cx.update(|cx| semantic_index.create_project_index(project, cx))?;
+    wait_for_indexing_complete(&project_index, cx, None).await;
+
+    // The project index is owned by the database, which must outlive this command.
+    cx.update(|cx| cx.set_global(semantic_index))?;
+    Ok(project_index)
+}
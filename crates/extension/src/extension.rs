@@ -95,6 +95,36 @@ pub trait Extension: Send + Sync + 'static {
         package_name: Arc<str>,
         kv_store: Arc<dyn KeyValueStoreDelegate>,
     ) -> Result<()>;
+
+    async fn language_model_provider_models(
+        &self,
+        provider_id: Arc<str>,
+        api_key: Option<String>,
+    ) -> Result<Vec<LanguageModelInfo>>;
+
+    async fn language_model_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: CompletionRequest,
+    ) -> Result<usize>;
+
+    /// Starts streaming a completion, returning the ID of the completion stream.
+    async fn language_model_stream_completion(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: CompletionRequest,
+        api_key: Option<String>,
+    ) -> Result<u64>;
+
+    /// Returns the next event of the completion stream, or `None` once the completion is done.
+    async fn language_model_next_completion_event(
+        &self,
+        stream_id: u64,
+    ) -> Result<Option<CompletionEvent>>;
+
+    async fn language_model_drop_completion_stream(&self, stream_id: u64) -> Result<()>;
 }
 
 pub fn parse_wasm_extension_version(
@@ -82,6 +82,8 @@ pub struct ExtensionManifest {
     #[serde(default)]
     pub indexed_docs_providers: BTreeMap<Arc<str>, IndexedDocsProviderEntry>,
     #[serde(default)]
+    pub language_model_providers: BTreeMap<Arc<str>, LanguageModelProviderManifestEntry>,
+    #[serde(default)]
     pub snippets: Option<PathBuf>,
 }
 
@@ -149,6 +151,24 @@ pub struct SlashCommandManifestEntry {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct IndexedDocsProviderEntry {}
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct LanguageModelProviderManifestEntry {
+    /// The name of the provider to display in the UI.
+    pub name: String,
+    /// How the user authenticates with the provider, if it requires an API key.
+    #[serde(default)]
+    pub auth: Option<LanguageModelProviderAuthEntry>,
+}
+
+/// The API key a language model provider requires. Keys are stored in the keychain under
+/// the ID of the extension and the provider, so that extensions can't read each other's keys.
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct LanguageModelProviderAuthEntry {
+    /// The environment variable that the API key can be read from instead.
+    #[serde(default)]
+    pub env_var: Option<String>,
+}
+
 impl ExtensionManifest {
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
@@ -214,6 +234,78 @@ fn manifest_from_old_manifest(
         context_servers: BTreeMap::default(),
         slash_commands: BTreeMap::default(),
         indexed_docs_providers: BTreeMap::default(),
+        language_model_providers: BTreeMap::default(),
         snippets: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language_model_providers() {
+        let manifest: ExtensionManifest = toml::from_str(
+            r#"
+            id = "my-extension"
+            name = "My Extension"
+            version = "0.1.0"
+            schema_version = 1
+
+            [language_model_providers.my-llm]
+            name = "My LLM"
+
+            [language_model_providers.my-llm.auth]
+            env_var = "MY_LLM_API_KEY"
+
+            [language_model_providers.local-llm]
+            name = "Local LLM"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.language_model_providers,
+            BTreeMap::from_iter([
+                (
+                    "local-llm".into(),
+                    LanguageModelProviderManifestEntry {
+                        name: "Local LLM".into(),
+                        auth: None,
+                    }
+                ),
+                (
+                    "my-llm".into(),
+                    LanguageModelProviderManifestEntry {
+                        name: "My LLM".into(),
+                        auth: Some(LanguageModelProviderAuthEntry {
+                            env_var: Some("MY_LLM_API_KEY".into()),
+                        }),
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_language_model_provider_without_env_var() {
+        let manifest: ExtensionManifest = toml::from_str(
+            r#"
+            id = "my-extension"
+            name = "My Extension"
+            version = "0.1.0"
+            schema_version = 1
+
+            [language_model_providers.my-llm]
+            name = "My LLM"
+            auth = {}
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.language_model_providers["my-llm"].auth,
+            Some(LanguageModelProviderAuthEntry::default())
+        );
+    }
+}
@@ -1,8 +1,10 @@
+mod language_model;
 mod lsp;
 mod slash_command;
 
 use std::ops::Range;
 
+pub use language_model::*;
 pub use lsp::*;
 pub use slash_command::*;
 
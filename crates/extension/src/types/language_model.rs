@@ -0,0 +1,109 @@
+/// A language model offered by a language model provider.
+#[derive(Debug, Clone)]
+pub struct LanguageModelInfo {
+    /// The ID of the model, as sent to the provider's API.
+    pub id: String,
+    /// The name of the model to display in the model selector.
+    pub display_name: String,
+    /// The maximum number of tokens in the context window of the model.
+    pub max_token_count: usize,
+    /// The maximum number of tokens the model can generate in a single response.
+    pub max_output_tokens: Option<u32>,
+}
+
+/// The role of a message in a completion request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageRole {
+    User,
+    Assistant,
+    System,
+}
+
+/// A tool that the model may use.
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    /// The name of the tool.
+    pub name: String,
+    /// The description of what the tool does.
+    pub description: String,
+    /// The JSON schema of the tool's input.
+    pub input_schema: String,
+}
+
+/// A use of a tool requested by the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolUse {
+    /// The ID of this tool use.
+    pub id: String,
+    /// The name of the tool.
+    pub name: String,
+    /// The input for the tool, as a JSON string.
+    pub input: String,
+}
+
+/// The result of a tool use, sent back to the model.
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    /// The ID of the tool use this is the result of.
+    pub tool_use_id: String,
+    /// Whether the tool use failed.
+    pub is_error: bool,
+    /// The output of the tool.
+    pub content: String,
+}
+
+/// A piece of content in a message.
+#[derive(Debug, Clone)]
+pub enum MessageContent {
+    /// Text content.
+    Text(String),
+    /// A tool use requested by the model.
+    ToolUse(ToolUse),
+    /// The result of a tool use.
+    ToolResult(ToolResult),
+}
+
+/// A message in a completion request.
+#[derive(Debug, Clone)]
+pub struct RequestMessage {
+    /// The role of the message.
+    pub role: MessageRole,
+    /// The contents of the message.
+    pub content: Vec<MessageContent>,
+    /// Whether this message should be cached, if the provider supports prompt caching.
+    pub cache: bool,
+}
+
+/// A request for a completion from a language model.
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    /// The messages to complete.
+    pub messages: Vec<RequestMessage>,
+    /// The tools the model may use.
+    pub tools: Vec<ToolDefinition>,
+    /// The sequences at which the model should stop generating.
+    pub stop: Vec<String>,
+    /// The sampling temperature to use.
+    pub temperature: Option<f32>,
+}
+
+/// The reason why the model stopped generating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    EndTurn,
+    MaxTokens,
+    ToolUse,
+}
+
+/// An event in a streamed completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionEvent {
+    /// The model started a new message.
+    StartMessage { message_id: String },
+    /// The model produced some text.
+    Text(String),
+    /// The model requested the use of a tool.
+    ToolUse(ToolUse),
+    /// The model stopped generating.
+    Stop(StopReason),
+}
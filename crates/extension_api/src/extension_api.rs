@@ -1,10 +1,13 @@
 //! The Zed Rust Extension API allows you write extensions for [Zed](https://zed.dev/) in Rust.
 
 pub mod http_client;
+pub mod language_model;
 pub mod settings;
 
 use core::fmt;
+use std::collections::BTreeMap;
 
+use language_model::{CompletionEvent, CompletionRequest, CompletionStream, LanguageModelInfo};
 use wit::*;
 
 pub use serde_json;
@@ -156,6 +159,40 @@ pub trait Extension: Send + Sync {
     ) -> Result<(), String> {
         Err("`index_docs` not implemented".to_string())
     }
+
+    /// Returns the models offered by the specified language model provider.
+    fn language_model_provider_models(
+        &self,
+        _provider_id: &LanguageModelProviderId,
+        _api_key: Option<String>,
+    ) -> Result<Vec<LanguageModelInfo>, String> {
+        Err("`language_model_provider_models` not implemented".to_string())
+    }
+
+    /// Returns the number of tokens in the given request for the specified model.
+    ///
+    /// Zed falls back to an estimate when this isn't implemented.
+    fn language_model_count_tokens(
+        &self,
+        _provider_id: &LanguageModelProviderId,
+        _model_id: &str,
+        _request: &CompletionRequest,
+    ) -> Result<u32, String> {
+        Err("`language_model_count_tokens` not implemented".to_string())
+    }
+
+    /// Starts streaming a completion from the specified model.
+    ///
+    /// Requests to the provider's API should be made with the [`http_client`] module.
+    fn language_model_stream_completion(
+        &mut self,
+        _provider_id: &LanguageModelProviderId,
+        _model_id: &str,
+        _request: CompletionRequest,
+        _api_key: Option<String>,
+    ) -> Result<Box<dyn CompletionStream>, String> {
+        Err("`language_model_stream_completion` not implemented".to_string())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -185,6 +222,13 @@ fn extension() -> &'static mut dyn Extension {
 
 static mut EXTENSION: Option<Box<dyn Extension>> = None;
 
+static mut COMPLETION_STREAMS: BTreeMap<u64, Box<dyn CompletionStream>> = BTreeMap::new();
+static mut NEXT_COMPLETION_STREAM_ID: u64 = 0;
+
+fn completion_streams() -> &'static mut BTreeMap<u64, Box<dyn CompletionStream>> {
+    unsafe { &mut COMPLETION_STREAMS }
+}
+
 #[cfg(target_arch = "wasm32")]
 #[link_section = "zed:api-version"]
 #[doc(hidden)]
@@ -195,7 +239,7 @@ mod wit {
 
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.2.0",
+        path: "./wit/since_v0.3.0",
     });
 }
 
@@ -298,6 +342,58 @@ impl wit::Guest for Component {
     ) -> Result<(), String> {
         extension().index_docs(provider, package, database)
     }
+
+    fn language_model_provider_models(
+        provider_id: String,
+        api_key: Option<String>,
+    ) -> Result<Vec<LanguageModelInfo>, String> {
+        let provider_id = LanguageModelProviderId(provider_id);
+        extension().language_model_provider_models(&provider_id, api_key)
+    }
+
+    fn language_model_count_tokens(
+        provider_id: String,
+        model_id: String,
+        request: CompletionRequest,
+    ) -> Result<u32, String> {
+        let provider_id = LanguageModelProviderId(provider_id);
+        extension().language_model_count_tokens(&provider_id, &model_id, &request)
+    }
+
+    fn language_model_stream_completion(
+        provider_id: String,
+        model_id: String,
+        request: CompletionRequest,
+        api_key: Option<String>,
+    ) -> Result<u64, String> {
+        let provider_id = LanguageModelProviderId(provider_id);
+        let stream = extension().language_model_stream_completion(
+            &provider_id,
+            &model_id,
+            request,
+            api_key,
+        )?;
+
+        let stream_id = unsafe {
+            NEXT_COMPLETION_STREAM_ID += 1;
+            NEXT_COMPLETION_STREAM_ID
+        };
+        completion_streams().insert(stream_id, stream);
+        Ok(stream_id)
+    }
+
+    fn language_model_next_completion_event(
+        stream_id: u64,
+    ) -> Result<Option<CompletionEvent>, String> {
+        completion_streams()
+            .get_mut(&stream_id)
+            .ok_or_else(|| format!("no completion stream with ID {stream_id}"))?
+            .next_event()
+    }
+
+    fn language_model_drop_completion_stream(stream_id: u64) {
+        completion_streams().remove(&stream_id);
+    }
 }
 
 /// The ID of a language server.
@@ -332,6 +428,22 @@ impl fmt::Display for ContextServerId {
     }
 }
 
+/// The ID of a language model provider.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct LanguageModelProviderId(String);
+
+impl AsRef<str> for LanguageModelProviderId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for LanguageModelProviderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl CodeLabelSpan {
     /// Returns a [`CodeLabelSpan::CodeRange`].
     pub fn code_range(range: impl Into<wit::Range>) -> Self {
@@ -0,0 +1,15 @@
+//! Types for providing language models to the Assistant.
+
+pub use crate::wit::zed::extension::language_model::{
+    CompletionEvent, CompletionRequest, LanguageModelInfo, MessageContent, MessageRole,
+    RequestMessage, StopReason, ToolDefinition, ToolResult, ToolUse,
+};
+
+/// A completion being streamed from a language model.
+///
+/// Completion streams usually wrap an [`HttpResponseStream`](crate::http_client::HttpResponseStream)
+/// and translate the chunks of the response into [`CompletionEvent`]s.
+pub trait CompletionStream {
+    /// Returns the next event of the completion, or `None` once the completion is done.
+    fn next_event(&mut self) -> Result<Option<CompletionEvent>, String>;
+}
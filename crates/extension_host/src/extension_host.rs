@@ -150,6 +150,23 @@ pub trait ExtensionRegistrationHooks: Send + Sync + 'static {
 
     fn register_docs_provider(&self, _extension: Arc<dyn Extension>, _provider_id: Arc<str>) {}
 
+    fn register_language_model_provider(
+        &self,
+        _extension: Arc<dyn Extension>,
+        _provider_id: Arc<str>,
+        _provider: extension::LanguageModelProviderManifestEntry,
+        _cx: &mut AppContext,
+    ) {
+    }
+
+    fn remove_language_model_provider(
+        &self,
+        _extension_id: Arc<str>,
+        _provider_id: Arc<str>,
+        _cx: &mut AppContext,
+    ) {
+    }
+
     fn register_snippets(&self, _path: &PathBuf, _snippet_contents: &str) -> Result<()> {
         Ok(())
     }
@@ -1093,6 +1110,13 @@ impl ExtensionStore {
                         .remove_lsp_adapter(&language, language_server_name);
                 }
             }
+            for provider_id in extension.manifest.language_model_providers.keys() {
+                self.registration_hooks.remove_language_model_provider(
+                    extension.manifest.id.clone(),
+                    provider_id.clone(),
+                    cx,
+                );
+            }
         }
 
         self.wasm_extensions
@@ -1273,6 +1297,15 @@ impl ExtensionStore {
                         this.registration_hooks
                             .register_docs_provider(extension.clone(), provider_id.clone());
                     }
+
+                    for (provider_id, provider) in &manifest.language_model_providers {
+                        this.registration_hooks.register_language_model_provider(
+                            extension.clone(),
+                            provider_id.clone(),
+                            provider.clone(),
+                            cx,
+                        );
+                    }
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
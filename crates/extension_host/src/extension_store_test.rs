@@ -7,6 +7,10 @@ use crate::{
 use anyhow::Result;
 use async_compression::futures::bufread::GzipEncoder;
 use collections::BTreeMap;
+use extension::{
+    CompletionEvent, CompletionRequest, Extension, MessageContent, MessageRole, RequestMessage,
+    StopReason, ToolDefinition, ToolUse,
+};
 use fs::{FakeFs, Fs, RealFs};
 use futures::{io::BufReader, AsyncReadExt, StreamExt};
 use gpui::{BackgroundExecutor, Context, SemanticVersion, SharedString, Task, TestAppContext};
@@ -34,6 +38,7 @@ struct TestExtensionRegistrationHooks {
     executor: BackgroundExecutor,
     language_registry: Arc<LanguageRegistry>,
     theme_registry: Arc<ThemeRegistry>,
+    language_model_providers: Mutex<Vec<(Arc<dyn Extension>, Arc<str>)>>,
 }
 
 impl ExtensionRegistrationHooks for TestExtensionRegistrationHooks {
@@ -104,6 +109,18 @@ impl ExtensionRegistrationHooks for TestExtensionRegistrationHooks {
         self.language_registry
             .remove_lsp_adapter(language_name, server_name);
     }
+
+    fn register_language_model_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        _provider: extension::LanguageModelProviderManifestEntry,
+        _cx: &mut gpui::AppContext,
+    ) {
+        self.language_model_providers
+            .lock()
+            .push((extension, provider_id));
+    }
 }
 
 #[cfg(test)]
@@ -240,6 +257,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         context_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        language_model_providers: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -267,6 +285,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         context_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        language_model_providers: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -343,6 +362,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
         executor: cx.executor(),
         language_registry: language_registry.clone(),
         theme_registry: theme_registry.clone(),
+        language_model_providers: Mutex::default(),
     });
     let node_runtime = NodeRuntime::unavailable();
 
@@ -430,6 +450,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 context_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
                 indexed_docs_providers: BTreeMap::default(),
+                language_model_providers: BTreeMap::default(),
                 snippets: None,
             }),
             dev: false,
@@ -564,6 +585,7 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
         executor: cx.executor(),
         language_registry: language_registry.clone(),
         theme_registry: theme_registry.clone(),
+        language_model_providers: Mutex::default(),
     });
     let node_runtime = NodeRuntime::unavailable();
 
@@ -658,7 +680,7 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
         ExtensionStore::new(
             extensions_dir.clone(),
             Some(cache_dir),
-            registration_hooks,
+            registration_hooks.clone(),
             fs.clone(),
             extension_client.clone(),
             builder_client,
@@ -695,6 +717,83 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
         .await
         .unwrap();
 
+    // The extension's language model provider is driven through the Wasm host.
+    let (extension, provider_id) = registration_hooks.language_model_providers.lock()[0].clone();
+    assert_eq!(provider_id.as_ref(), "test-llm");
+    let models = extension
+        .language_model_provider_models(provider_id.clone(), Some("the-api-key".into()))
+        .await
+        .unwrap();
+    assert_eq!(
+        models
+            .iter()
+            .map(|model| (model.id.as_str(), model.max_token_count))
+            .collect::<Vec<_>>(),
+        [("echo", 1000)]
+    );
+
+    let request = CompletionRequest {
+        messages: vec![RequestMessage {
+            role: MessageRole::User,
+            content: vec![MessageContent::Text("Hello there".into())],
+            cache: false,
+        }],
+        tools: vec![ToolDefinition {
+            name: "reply".into(),
+            description: "Replies to the user".into(),
+            input_schema: json!({ "type": "object" }).to_string(),
+        }],
+        stop: Vec::new(),
+        temperature: Some(1.0),
+    };
+    let token_count = extension
+        .language_model_count_tokens(provider_id.clone(), "echo".into(), request.clone())
+        .await
+        .unwrap();
+    assert_eq!(token_count, 2);
+
+    let stream_id = extension
+        .language_model_stream_completion(
+            provider_id.clone(),
+            "echo".into(),
+            request,
+            Some("the-api-key".into()),
+        )
+        .await
+        .unwrap();
+    let mut completion_events = Vec::new();
+    while let Some(event) = extension
+        .language_model_next_completion_event(stream_id)
+        .await
+        .unwrap()
+    {
+        completion_events.push(event);
+    }
+    extension
+        .language_model_drop_completion_stream(stream_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        completion_events,
+        [
+            CompletionEvent::StartMessage {
+                message_id: "echo-the-api-key".into()
+            },
+            CompletionEvent::Text("Hello there".into()),
+            CompletionEvent::ToolUse(ToolUse {
+                id: "tool-use-1".into(),
+                name: "reply".into(),
+                input: json!({ "text": "Hello there" }).to_string(),
+            }),
+            CompletionEvent::Stop(StopReason::ToolUse),
+        ]
+    );
+    // The stream is gone once dropped.
+    assert!(extension
+        .language_model_next_completion_event(stream_id)
+        .await
+        .is_err());
+
     let mut fake_servers = language_registry.register_fake_language_server(
         LanguageServerName("gleam".into()),
         lsp::ServerCapabilities {
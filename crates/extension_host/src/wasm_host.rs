@@ -4,8 +4,9 @@ use crate::{ExtensionManifest, ExtensionRegistrationHooks};
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_trait::async_trait;
 use extension::{
-    CodeLabel, Command, Completion, KeyValueStoreDelegate, SlashCommand,
-    SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, WorktreeDelegate,
+    CodeLabel, Command, Completion, CompletionEvent, CompletionRequest, KeyValueStoreDelegate,
+    LanguageModelInfo, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol,
+    WorktreeDelegate,
 };
 use fs::{normalize_path, Fs};
 use futures::future::LocalBoxFuture;
@@ -278,6 +279,111 @@ impl extension::Extension for WasmExtension {
         })
         .await
     }
+
+    async fn language_model_provider_models(
+        &self,
+        provider_id: Arc<str>,
+        api_key: Option<String>,
+    ) -> Result<Vec<LanguageModelInfo>> {
+        self.call(|extension, store| {
+            async move {
+                let models = extension
+                    .call_language_model_provider_models(
+                        store,
+                        provider_id.as_ref(),
+                        api_key.as_deref(),
+                    )
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(models.into_iter().map(Into::into).collect())
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn language_model_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: CompletionRequest,
+    ) -> Result<usize> {
+        self.call(|extension, store| {
+            async move {
+                let token_count = extension
+                    .call_language_model_count_tokens(
+                        store,
+                        provider_id.as_ref(),
+                        model_id.as_ref(),
+                        &request.into(),
+                    )
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(token_count as usize)
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn language_model_stream_completion(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: CompletionRequest,
+        api_key: Option<String>,
+    ) -> Result<u64> {
+        self.call(|extension, store| {
+            async move {
+                let stream_id = extension
+                    .call_language_model_stream_completion(
+                        store,
+                        provider_id.as_ref(),
+                        model_id.as_ref(),
+                        &request.into(),
+                        api_key.as_deref(),
+                    )
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(stream_id)
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn language_model_next_completion_event(
+        &self,
+        stream_id: u64,
+    ) -> Result<Option<CompletionEvent>> {
+        self.call(|extension, store| {
+            async move {
+                let event = extension
+                    .call_language_model_next_completion_event(store, stream_id)
+                    .await?
+                    .map_err(|err| anyhow!("{err}"))?;
+
+                Ok(event.map(Into::into))
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    async fn language_model_drop_completion_stream(&self, stream_id: u64) -> Result<()> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_language_model_drop_completion_stream(store, stream_id)
+                    .await
+            }
+            .boxed()
+        })
+        .await
+    }
 }
 
 pub struct WasmState {
@@ -12,6 +12,8 @@ use fs::Fs;
 use gpui::{AppContext, BackgroundExecutor, Model, Task};
 use indexed_docs::{ExtensionIndexedDocsProvider, IndexedDocsRegistry, ProviderId};
 use language::{LanguageRegistry, LanguageServerBinaryStatus, LoadedLanguage};
+use language_model::provider::extension::ExtensionLanguageModelProvider;
+use language_model::LanguageModelRegistry;
 use snippet_provider::SnippetRegistry;
 use theme::{ThemeRegistry, ThemeSettings};
 use ui::SharedString;
@@ -24,6 +26,7 @@ pub struct ConcreteExtensionRegistrationHooks {
     snippet_registry: Arc<SnippetRegistry>,
     language_registry: Arc<LanguageRegistry>,
     context_server_factory_registry: Model<ContextServerFactoryRegistry>,
+    language_model_registry: Model<LanguageModelRegistry>,
     executor: BackgroundExecutor,
 }
 
@@ -35,6 +38,7 @@ impl ConcreteExtensionRegistrationHooks {
         snippet_registry: Arc<SnippetRegistry>,
         language_registry: Arc<LanguageRegistry>,
         context_server_factory_registry: Model<ContextServerFactoryRegistry>,
+        language_model_registry: Model<LanguageModelRegistry>,
         cx: &AppContext,
     ) -> Arc<dyn extension_host::ExtensionRegistrationHooks> {
         Arc::new(Self {
@@ -44,6 +48,7 @@ impl ConcreteExtensionRegistrationHooks {
             snippet_registry,
             language_registry,
             context_server_factory_registry,
+            language_model_registry,
             executor: cx.background_executor().clone(),
         })
     }
@@ -143,6 +148,31 @@ impl extension_host::ExtensionRegistrationHooks for ConcreteExtensionRegistratio
             )));
     }
 
+    fn register_language_model_provider(
+        &self,
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        provider: extension::LanguageModelProviderManifestEntry,
+        cx: &mut AppContext,
+    ) {
+        let provider = ExtensionLanguageModelProvider::new(extension, provider_id, provider, cx);
+        self.language_model_registry
+            .update(cx, |registry, cx| registry.register_provider(provider, cx));
+    }
+
+    fn remove_language_model_provider(
+        &self,
+        extension_id: Arc<str>,
+        provider_id: Arc<str>,
+        cx: &mut AppContext,
+    ) {
+        let provider_id =
+            ExtensionLanguageModelProvider::registered_id(&extension_id, &provider_id);
+        self.language_model_registry.update(cx, |registry, cx| {
+            registry.unregister_provider(provider_id, cx)
+        });
+    }
+
     fn register_snippets(&self, path: &PathBuf, snippet_contents: &str) -> Result<()> {
         self.snippet_registry
             .register_snippets(path, snippet_contents)
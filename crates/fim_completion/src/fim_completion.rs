@@ -0,0 +1,14 @@
+mod fim_completion_provider;
+mod fim_completion_settings;
+mod prompt;
+
+use gpui::AppContext;
+use settings::Settings;
+
+pub use fim_completion_provider::*;
+pub use fim_completion_settings::*;
+pub use prompt::*;
+
+pub fn init(cx: &mut AppContext) {
+    FimCompletionSettings::register(cx);
+}
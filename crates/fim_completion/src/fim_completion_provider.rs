@@ -0,0 +1,580 @@
+use crate::{ContextFile, FimApi, FimCompletionSettings, FimPrompt};
+use anyhow::{anyhow, Result};
+use client::telemetry::Telemetry;
+use collections::HashMap;
+use editor::{CompletionProposal, Direction, InlayProposal, InlineCompletionProvider};
+use gpui::{AppContext, AsyncAppContext, EntityId, Model, ModelContext, Task, WeakModel};
+use http_client::HttpClient;
+use language::{
+    language_settings::all_language_settings, Anchor, Bias, Buffer, BufferSnapshot, ToOffset,
+};
+use project::Project;
+use settings::Settings;
+use std::{collections::VecDeque, path::Path, sync::Arc};
+use util::TryFutureExt as _;
+
+/// How many completions are kept around for cursor positions visited before.
+const MAX_CACHED_COMPLETIONS: usize = 64;
+
+/// Like the OpenAI language model provider, the key is read from this variable first.
+const OPENAI_API_KEY_VAR: &str = "OPENAI_API_KEY";
+
+pub struct FimCompletionProvider {
+    project: Option<WeakModel<Project>>,
+    completion: Option<FimCompletion>,
+    cache: CompletionCache,
+    file_extension: Option<String>,
+    /// The key for an OpenAI-compatible API, along with the URL it was read for.
+    open_ai_api_key: Option<(String, Option<String>)>,
+    pending_refresh: Task<Option<()>>,
+    telemetry: Option<Arc<Telemetry>>,
+}
+
+struct FimCompletion {
+    buffer_id: EntityId,
+    /// The cursor position the completion was requested at.
+    position: Anchor,
+    text: Arc<str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    buffer_id: EntityId,
+    version: clock::Global,
+    offset: usize,
+}
+
+/// Completions received for recent cursor positions, evicted oldest first.
+#[derive(Default)]
+struct CompletionCache {
+    completions: HashMap<CacheKey, Arc<str>>,
+    insertion_order: VecDeque<CacheKey>,
+}
+
+impl CompletionCache {
+    fn get(&self, key: &CacheKey) -> Option<Arc<str>> {
+        self.completions.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: CacheKey, text: Arc<str>) {
+        if self.completions.insert(key.clone(), text).is_none() {
+            self.insertion_order.push_back(key);
+            if self.insertion_order.len() > MAX_CACHED_COMPLETIONS {
+                if let Some(oldest) = self.insertion_order.pop_front() {
+                    self.completions.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+impl FimCompletionProvider {
+    /// Creates a provider that includes the other files open in the project given in its prompts.
+    pub fn new(project: Option<&Model<Project>>) -> Self {
+        Self {
+            project: project.map(|project| project.downgrade()),
+            completion: None,
+            cache: CompletionCache::default(),
+            file_extension: None,
+            open_ai_api_key: None,
+            pending_refresh: Task::ready(None),
+            telemetry: None,
+        }
+    }
+
+    pub fn with_telemetry(mut self, telemetry: Arc<Telemetry>) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    fn set_completion(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        text: Arc<str>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.completion = Some(FimCompletion {
+            buffer_id: buffer.entity_id(),
+            position,
+            text,
+        });
+        self.file_extension = buffer.read(cx).file().and_then(|file| {
+            Some(
+                Path::new(file.file_name(cx))
+                    .extension()?
+                    .to_str()?
+                    .to_string(),
+            )
+        });
+        cx.notify();
+    }
+
+    fn report_completion_event(&self, accepted: bool) {
+        if self.completion.is_some() {
+            if let Some(telemetry) = self.telemetry.as_ref() {
+                telemetry.report_inline_completion_event(
+                    Self::name().to_string(),
+                    accepted,
+                    self.file_extension.clone(),
+                );
+            }
+        }
+    }
+
+    /// Picks the other open files to include in the prompt, preferring the ones in the
+    /// same language and closest to the buffer being edited.
+    fn context_files(
+        &self,
+        buffer: &Model<Buffer>,
+        settings: &FimCompletionSettings,
+        cx: &AppContext,
+    ) -> Vec<ContextFile> {
+        if settings.max_context_files == 0 {
+            return Vec::new();
+        }
+        let Some(project) = self.project.as_ref().and_then(|project| project.upgrade()) else {
+            return Vec::new();
+        };
+
+        let current_buffer = buffer.read(cx);
+        let current_language = current_buffer.language().map(|language| language.name());
+        let current_path = current_buffer.file().map(|file| file.path().clone());
+        let mut candidates = project
+            .read(cx)
+            .opened_buffers(cx)
+            .into_iter()
+            .filter(|candidate| candidate.entity_id() != buffer.entity_id())
+            .filter_map(|candidate| {
+                let candidate = candidate.read(cx);
+                let file = candidate.file()?;
+                if file.is_private()
+                    || !all_language_settings(Some(file), cx)
+                        .inline_completions_enabled_for_path(file.path())
+                {
+                    return None;
+                }
+                let same_language =
+                    candidate.language().map(|language| language.name()) == current_language;
+                let shared_components = current_path.as_ref().map_or(0, |current_path| {
+                    current_path
+                        .components()
+                        .zip(file.path().components())
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                Some((
+                    same_language,
+                    shared_components,
+                    file.path().clone(),
+                    candidate.snapshot(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)).then_with(|| a.2.cmp(&b.2)));
+
+        candidates
+            .into_iter()
+            .take(settings.max_context_files)
+            .map(|(_, _, path, snapshot)| {
+                let end = snapshot.clip_offset(
+                    settings.max_context_file_chars.min(snapshot.len()),
+                    Bias::Left,
+                );
+                ContextFile {
+                    path: path.to_string_lossy().into_owned(),
+                    content: snapshot.text_for_range(0..end).collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn build_prompt(
+    snapshot: &BufferSnapshot,
+    offset: usize,
+    context_files: Vec<ContextFile>,
+    settings: &FimCompletionSettings,
+    cx: &AppContext,
+) -> FimPrompt {
+    let prefix_start = snapshot.clip_offset(
+        offset.saturating_sub(settings.max_prefix_chars),
+        Bias::Right,
+    );
+    let suffix_end = snapshot.clip_offset(
+        offset
+            .saturating_add(settings.max_suffix_chars)
+            .min(snapshot.len()),
+        Bias::Left,
+    );
+    FimPrompt {
+        path: snapshot
+            .file()
+            .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_string()),
+        prefix: snapshot.text_for_range(prefix_start..offset).collect(),
+        suffix: snapshot.text_for_range(offset..suffix_end).collect(),
+        context_files,
+    }
+}
+
+/// Reads the key for an OpenAI-compatible API from the environment, or from the credentials the
+/// OpenAI language model provider saved for the same URL. Local servers usually need none.
+async fn load_open_ai_api_key(api_url: &str, cx: &AsyncAppContext) -> Result<Option<String>> {
+    if let Ok(api_key) = std::env::var(OPENAI_API_KEY_VAR) {
+        return Ok(Some(api_key));
+    }
+    let credentials = cx.update(|cx| cx.read_credentials(api_url))?.await?;
+    Ok(credentials
+        .map(|(_, api_key)| String::from_utf8(api_key))
+        .transpose()?)
+}
+
+async fn request_completion(
+    http_client: &dyn HttpClient,
+    prompt: String,
+    api_key: Option<&str>,
+    settings: &FimCompletionSettings,
+) -> Result<String> {
+    let api_url = settings
+        .api_url()
+        .ok_or_else(|| anyhow!("`fim_completions.api_url` has to be set for the `open_ai` api"))?;
+    match settings.api {
+        FimApi::Ollama => {
+            let request = ollama::GenerateRequest {
+                model: settings.model.clone(),
+                prompt,
+                raw: true,
+                stream: false,
+                keep_alive: Default::default(),
+                options: Some(ollama::ChatOptions {
+                    num_predict: Some(settings.max_tokens as isize),
+                    stop: Some(settings.stop.clone()),
+                    temperature: Some(settings.temperature),
+                    ..Default::default()
+                }),
+            };
+            Ok(ollama::generate(http_client, api_url, request)
+                .await?
+                .response)
+        }
+        FimApi::OpenAi => {
+            let request = open_ai::TextCompletionRequest {
+                model: settings.model.clone(),
+                prompt,
+                stream: false,
+                max_tokens: Some(settings.max_tokens),
+                stop: settings.stop.clone(),
+                temperature: settings.temperature,
+            };
+            let response = open_ai::text_completion(http_client, api_url, api_key, request).await?;
+            Ok(response
+                .choices
+                .into_iter()
+                .next()
+                .map(|choice| choice.text)
+                .unwrap_or_default())
+        }
+    }
+}
+
+impl InlineCompletionProvider for FimCompletionProvider {
+    fn name() -> &'static str {
+        "fim"
+    }
+
+    fn is_enabled(&self, buffer: &Model<Buffer>, cursor_position: Anchor, cx: &AppContext) -> bool {
+        let buffer = buffer.read(cx);
+        let file = buffer.file();
+        let language = buffer.language_at(cursor_position);
+        let settings = all_language_settings(file, cx);
+        settings.inline_completions_enabled(language.as_ref(), file.map(|f| f.path().as_ref()), cx)
+    }
+
+    fn refresh(
+        &mut self,
+        buffer: Model<Buffer>,
+        cursor_position: Anchor,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = cursor_position.to_offset(&snapshot);
+        let cache_key = CacheKey {
+            buffer_id: buffer.entity_id(),
+            version: snapshot.version().clone(),
+            offset,
+        };
+        if let Some(text) = self.cache.get(&cache_key) {
+            self.pending_refresh = Task::ready(None);
+            self.set_completion(&buffer, cursor_position, text, cx);
+            return;
+        }
+
+        let settings = FimCompletionSettings::get_global(cx).clone();
+        let context_files = self.context_files(&buffer, &settings, cx);
+        let prompt = build_prompt(&snapshot, offset, context_files, &settings, cx)
+            .render(&settings.template, &settings.context_file_template);
+        let http_client = cx.http_client();
+        let open_ai_api_key = self
+            .open_ai_api_key
+            .clone()
+            .filter(|(api_url, _)| Some(api_url.as_str()) == settings.api_url());
+        // Replacing the pending task drops the previous request.
+        self.pending_refresh = cx.spawn(|this, mut cx| {
+            async move {
+                if debounce {
+                    cx.background_executor().timer(settings.debounce()).await;
+                }
+
+                let api_key = match (settings.api, settings.api_url(), open_ai_api_key) {
+                    (FimApi::OpenAi, _, Some((_, api_key))) => api_key,
+                    (FimApi::OpenAi, Some(api_url), None) => {
+                        let api_key = load_open_ai_api_key(api_url, &cx).await?;
+                        let api_url = api_url.to_string();
+                        this.update(&mut cx, |this, _| {
+                            this.open_ai_api_key = Some((api_url, api_key.clone()));
+                        })?;
+                        api_key
+                    }
+                    _ => None,
+                };
+                let text =
+                    request_completion(http_client.as_ref(), prompt, api_key.as_deref(), &settings)
+                        .await?;
+                let text: Arc<str> = text.trim_end().into();
+                this.update(&mut cx, |this, cx| {
+                    this.cache.insert(cache_key, text.clone());
+                    this.set_completion(&buffer, cursor_position, text, cx);
+                })
+            }
+            .log_err()
+        });
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Model<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut ModelContext<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut ModelContext<Self>) {
+        self.report_completion_event(true);
+        self.pending_refresh = Task::ready(None);
+        self.completion = None;
+    }
+
+    fn discard(
+        &mut self,
+        should_report_inline_completion_event: bool,
+        _cx: &mut ModelContext<Self>,
+    ) {
+        if should_report_inline_completion_event {
+            self.report_completion_event(false);
+        }
+        self.pending_refresh = Task::ready(None);
+        self.completion = None;
+    }
+
+    fn active_completion_text<'a>(
+        &'a self,
+        buffer: &Model<Buffer>,
+        cursor_position: Anchor,
+        cx: &'a AppContext,
+    ) -> Option<CompletionProposal> {
+        let completion = self.completion.as_ref()?;
+        if completion.buffer_id != buffer.entity_id() {
+            return None;
+        }
+        let buffer = buffer.read(cx);
+        if !completion.position.is_valid(buffer) {
+            return None;
+        }
+
+        // Keep showing the rest of the completion while the user types it out.
+        let position = completion.position.to_offset(buffer);
+        let cursor_offset = cursor_position.to_offset(buffer);
+        if cursor_offset < position {
+            return None;
+        }
+        let typed_text = buffer
+            .text_for_range(position..cursor_offset)
+            .collect::<String>();
+        let completion_text = completion.text.strip_prefix(typed_text.as_str())?;
+        if completion_text.trim().is_empty() {
+            return None;
+        }
+
+        Some(CompletionProposal {
+            inlays: vec![InlayProposal::Suggestion(
+                cursor_position.bias_right(buffer),
+                completion_text.into(),
+            )],
+            text: completion_text.into(),
+            delete_range: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use gpui::{Context, TestAppContext};
+    use http_client::{AsyncBody, FakeHttpClient, Response};
+    use language::Point;
+    use settings::SettingsStore;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    #[gpui::test]
+    async fn test_fim_completion(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let (prompt_tx, mut prompt_rx) = futures::channel::mpsc::unbounded();
+        let http_client = FakeHttpClient::create({
+            let request_count = request_count.clone();
+            move |mut request| {
+                let request_count = request_count.clone();
+                let prompt_tx = prompt_tx.clone();
+                async move {
+                    assert_eq!(request.uri().path(), "/api/generate");
+                    request_count.fetch_add(1, SeqCst);
+                    let mut body = String::new();
+                    futures::AsyncReadExt::read_to_string(request.body_mut(), &mut body).await?;
+                    let body: serde_json::Value = serde_json::from_str(&body)?;
+                    assert_eq!(body["raw"], true);
+                    prompt_tx.unbounded_send(body["prompt"].as_str().unwrap().to_string())?;
+                    Ok(Response::builder().status(200).body(AsyncBody::from(
+                        serde_json::json!({ "response": "ld!\");\n", "done": true }).to_string(),
+                    ))?)
+                }
+            }
+        });
+        cx.update(|cx| cx.set_http_client(http_client));
+
+        let buffer =
+            cx.new_model(|cx| Buffer::local("fn main() {\n    println!(\"Hello, wor\n}", cx));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 24)));
+        let provider = cx.new_model(|_| FimCompletionProvider::new(None));
+
+        // Refreshing again before the debounce timeout elapses cancels the first request.
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, true, cx);
+            provider.refresh(buffer.clone(), cursor, true, cx);
+        });
+        cx.executor()
+            .advance_clock(cx.update(|cx| FimCompletionSettings::get_global(cx).debounce()));
+        cx.run_until_parked();
+        assert_eq!(request_count.load(SeqCst), 1);
+        assert_eq!(
+            prompt_rx.next().await.unwrap(),
+            "<|file_sep|>untitled\n<|fim_prefix|>fn main() {\n    println!(\"Hello, wor<|fim_suffix|>\n}<|fim_middle|>"
+        );
+        assert_eq!(
+            active_completion_text(&provider, &buffer, cursor, cx),
+            Some("ld!\");".into())
+        );
+
+        // Completions are cached per cursor position.
+        provider.update(cx, |provider, cx| {
+            provider.discard(false, cx);
+            provider.refresh(buffer.clone(), cursor, true, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(request_count.load(SeqCst), 1);
+        assert_eq!(
+            active_completion_text(&provider, &buffer, cursor, cx),
+            Some("ld!\");".into())
+        );
+
+        // The rest of the completion is shown while typing it out.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 24)..Point::new(1, 24), "ld")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 26)));
+        assert_eq!(
+            active_completion_text(&provider, &buffer, cursor, cx),
+            Some("!\");".into())
+        );
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 26)..Point::new(1, 26), "?")], None, cx)
+        });
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(1, 27)));
+        assert_eq!(active_completion_text(&provider, &buffer, cursor, cx), None);
+    }
+
+    #[gpui::test]
+    async fn test_open_ai_completion(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<FimCompletionSettings>(cx, |settings| {
+                    settings.api = Some(FimApi::OpenAi);
+                    settings.api_url = Some("http://localhost:8080/v1/".into());
+                    settings.model = Some("qwen2.5-coder".into());
+                    settings.stop = Some(vec!["\n\n".into()]);
+                });
+            });
+        });
+
+        let http_client = FakeHttpClient::create(|mut request| async move {
+            assert_eq!(request.uri().path(), "/v1/completions");
+            let mut body = String::new();
+            futures::AsyncReadExt::read_to_string(request.body_mut(), &mut body).await?;
+            let body: serde_json::Value = serde_json::from_str(&body)?;
+            assert_eq!(body["model"], "qwen2.5-coder");
+            assert_eq!(body["stream"], false);
+            assert_eq!(body["stop"], serde_json::json!(["\n\n"]));
+            assert_eq!(
+                body["prompt"],
+                "<|file_sep|>untitled\n<|fim_prefix|>let x = <|fim_suffix|>;<|fim_middle|>"
+            );
+            Ok(Response::builder().status(200).body(AsyncBody::from(
+                serde_json::json!({
+                    "choices": [{ "text": "42\n", "finish_reason": "stop" }]
+                })
+                .to_string(),
+            ))?)
+        });
+        cx.update(|cx| cx.set_http_client(http_client));
+
+        let buffer = cx.new_model(|cx| Buffer::local("let x = ;", cx));
+        let cursor = buffer.read_with(cx, |buffer, _| buffer.anchor_after(Point::new(0, 8)));
+        let provider = cx.new_model(|_| FimCompletionProvider::new(None));
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), cursor, false, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            active_completion_text(&provider, &buffer, cursor, cx),
+            Some("42".into())
+        );
+    }
+
+    fn active_completion_text(
+        provider: &Model<FimCompletionProvider>,
+        buffer: &Model<Buffer>,
+        cursor: Anchor,
+        cx: &mut TestAppContext,
+    ) -> Option<String> {
+        provider.read_with(cx, |provider, cx| {
+            provider
+                .active_completion_text(buffer, cursor, cx)
+                .map(|completion| completion.text.to_string())
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            language::init(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The API used to request fill-in-the-middle completions.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FimApi {
+    /// Ollama's `/api/generate` endpoint, with the prompt sent raw.
+    #[default]
+    Ollama,
+    /// The `/completions` endpoint of an OpenAI-compatible server, such as llama.cpp or vLLM.
+    OpenAi,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FimCompletionSettings {
+    pub api: FimApi,
+    pub api_url: Option<String>,
+    pub model: String,
+    pub template: String,
+    pub context_file_template: String,
+    pub stop: Vec<String>,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub debounce_ms: u64,
+    pub max_prefix_chars: usize,
+    pub max_suffix_chars: usize,
+    pub max_context_files: usize,
+    pub max_context_file_chars: usize,
+}
+
+impl FimCompletionSettings {
+    pub fn api_url(&self) -> Option<&str> {
+        match (&self.api_url, self.api) {
+            (Some(api_url), _) => Some(api_url.trim_end_matches('/')),
+            (None, FimApi::Ollama) => Some(ollama::OLLAMA_API_URL),
+            (None, FimApi::OpenAi) => None,
+        }
+    }
+
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+}
+
+/// Settings for fill-in-the-middle inline completions.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FimCompletionSettingsContent {
+    /// The API used to request completions.
+    ///
+    /// Default: ollama
+    pub api: Option<FimApi>,
+    /// The URL of the API. Defaults to the local Ollama server for `ollama`,
+    /// and has to be set for `open_ai`.
+    ///
+    /// Default: null
+    pub api_url: Option<String>,
+    /// The model to request completions from.
+    ///
+    /// Default: "qwen2.5-coder:1.5b"
+    pub model: Option<String>,
+    /// The prompt sent to the model. `{prefix}` and `{suffix}` are replaced with the
+    /// text before and after the cursor, `{path}` with the path of the file and
+    /// `{context}` with the nearby open files.
+    ///
+    /// Default: "{context}<|file_sep|>{path}\n<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"
+    pub template: Option<String>,
+    /// How each nearby open file is rendered into `{context}`. `{path}` and
+    /// `{content}` are replaced with the path and the text of the file.
+    ///
+    /// Default: "<|file_sep|>{path}\n{content}\n"
+    pub context_file_template: Option<String>,
+    /// Sequences that stop the completion.
+    pub stop: Option<Vec<String>>,
+    /// The maximum number of tokens to generate.
+    ///
+    /// Default: 128
+    pub max_tokens: Option<u32>,
+    /// The sampling temperature.
+    ///
+    /// Default: 0.2
+    pub temperature: Option<f32>,
+    /// How long to wait after the last edit before requesting a completion, in milliseconds.
+    ///
+    /// Default: 150
+    pub debounce_ms: Option<u64>,
+    /// How many characters before the cursor to include in the prompt.
+    ///
+    /// Default: 4096
+    pub max_prefix_chars: Option<usize>,
+    /// How many characters after the cursor to include in the prompt.
+    ///
+    /// Default: 1024
+    pub max_suffix_chars: Option<usize>,
+    /// How many other open files to include in the prompt.
+    ///
+    /// Default: 3
+    pub max_context_files: Option<usize>,
+    /// How many characters of each other open file to include in the prompt.
+    ///
+    /// Default: 2048
+    pub max_context_file_chars: Option<usize>,
+}
+
+impl Settings for FimCompletionSettings {
+    const KEY: Option<&'static str> = Some("fim_completions");
+
+    type FileContent = FimCompletionSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,110 @@
+/// An open file, other than the one being edited, included in the prompt as context.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContextFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// The text around the cursor that a fill-in-the-middle prompt is built from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FimPrompt {
+    pub path: String,
+    pub prefix: String,
+    pub suffix: String,
+    pub context_files: Vec<ContextFile>,
+}
+
+impl FimPrompt {
+    /// Renders the prompt with the templates from the settings.
+    ///
+    /// Placeholders are substituted in a single pass, so placeholders appearing in the
+    /// buffer text itself are left alone.
+    pub fn render(&self, template: &str, context_file_template: &str) -> String {
+        let context = self
+            .context_files
+            .iter()
+            .map(|file| {
+                render_template(
+                    context_file_template,
+                    &[("path", &file.path), ("content", &file.content)],
+                )
+            })
+            .collect::<String>();
+        render_template(
+            template,
+            &[
+                ("context", &context),
+                ("path", &self.path),
+                ("prefix", &self.prefix),
+                ("suffix", &self.suffix),
+            ],
+        )
+    }
+}
+
+fn render_template(template: &str, variables: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            let (_, value) = variables.iter().find(|(variable, _)| *variable == name)?;
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt() {
+        let prompt = FimPrompt {
+            path: "src/main.rs".to_string(),
+            prefix: "fn main() {\n    let {suffix} = ".to_string(),
+            suffix: "\n}".to_string(),
+            context_files: vec![
+                ContextFile {
+                    path: "src/a.rs".to_string(),
+                    content: "mod a;".to_string(),
+                },
+                ContextFile {
+                    path: "src/b.rs".to_string(),
+                    content: "mod b;".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            prompt.render(
+                "{context}<|file_sep|>{path}\n<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>",
+                "<|file_sep|>{path}\n{content}\n",
+            ),
+            "<|file_sep|>src/a.rs\nmod a;\n<|file_sep|>src/b.rs\nmod b;\n\
+             <|file_sep|>src/main.rs\n\
+             <|fim_prefix|>fn main() {\n    let {suffix} = <|fim_suffix|>\n}<|fim_middle|>",
+            "placeholders in the buffer text should not be substituted"
+        );
+
+        assert_eq!(
+            prompt.render("<PRE> {prefix} <SUF>{suffix} <MID>{unknown}{", ""),
+            "<PRE> fn main() {\n    let {suffix} =  <SUF>\n} <MID>{unknown}{",
+            "unknown placeholders and unmatched braces should be kept as is"
+        );
+    }
+}
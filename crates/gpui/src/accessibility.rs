@@ -0,0 +1,645 @@
+use crate::{hash, Bounds, FocusId, GlobalElementId, Pixels, SharedString};
+use collections::{FxHashMap, FxHashSet};
+use std::{fmt, ops::Range};
+
+/// The identifier of a node in a window's [`AccessibilityTree`], which is stable across frames
+/// as long as the element that produced it is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccessibilityNodeId(pub u64);
+
+impl AccessibilityNodeId {
+    /// The identifier of the node representing the window itself.
+    pub const ROOT: Self = Self(0);
+
+    pub(crate) fn for_element(global_id: &GlobalElementId) -> Self {
+        Self(hash(global_id).max(1))
+    }
+
+    fn for_child(parent: Self, index: usize, role: AccessibilityRole) -> Self {
+        Self(hash(&(parent, index, role)).max(1))
+    }
+}
+
+/// What kind of control or content an element represents to assistive technologies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum AccessibilityRole {
+    Window,
+    #[default]
+    Group,
+    Button,
+    CheckBox,
+    RadioButton,
+    Switch,
+    Link,
+    Label,
+    Heading,
+    Image,
+    TextInput,
+    MultilineTextInput,
+    List,
+    ListItem,
+    Tree,
+    TreeItem,
+    Table,
+    Row,
+    Cell,
+    TabList,
+    Tab,
+    TabPanel,
+    Menu,
+    MenuItem,
+    Toolbar,
+    Dialog,
+    Tooltip,
+    ScrollView,
+    ProgressIndicator,
+    Slider,
+}
+
+impl AccessibilityRole {
+    /// Whether elements with this role are named after the text they contain when they
+    /// aren't given a name explicitly.
+    pub fn is_named_from_contents(&self) -> bool {
+        matches!(
+            self,
+            Self::Button
+                | Self::CheckBox
+                | Self::RadioButton
+                | Self::Switch
+                | Self::Link
+                | Self::Label
+                | Self::Heading
+                | Self::ListItem
+                | Self::TreeItem
+                | Self::Cell
+                | Self::Tab
+                | Self::MenuItem
+                | Self::Tooltip
+        )
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Window => "window",
+            Self::Group => "group",
+            Self::Button => "button",
+            Self::CheckBox => "checkbox",
+            Self::RadioButton => "radio button",
+            Self::Switch => "switch",
+            Self::Link => "link",
+            Self::Label => "label",
+            Self::Heading => "heading",
+            Self::Image => "image",
+            Self::TextInput => "text input",
+            Self::MultilineTextInput => "multiline text input",
+            Self::List => "list",
+            Self::ListItem => "list item",
+            Self::Tree => "tree",
+            Self::TreeItem => "tree item",
+            Self::Table => "table",
+            Self::Row => "row",
+            Self::Cell => "cell",
+            Self::TabList => "tab list",
+            Self::Tab => "tab",
+            Self::TabPanel => "tab panel",
+            Self::Menu => "menu",
+            Self::MenuItem => "menu item",
+            Self::Toolbar => "toolbar",
+            Self::Dialog => "dialog",
+            Self::Tooltip => "tooltip",
+            Self::ScrollView => "scroll view",
+            Self::ProgressIndicator => "progress indicator",
+            Self::Slider => "slider",
+        }
+    }
+}
+
+/// The properties an element exposes to assistive technologies.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessibilityProperties {
+    /// What kind of control or content the element represents.
+    pub role: AccessibilityRole,
+    /// The name read out for the element. When this is `None`, roles that are
+    /// [named from their contents](AccessibilityRole::is_named_from_contents) use the text
+    /// painted inside of them.
+    pub name: Option<SharedString>,
+    /// Additional information about the element, such as a tooltip.
+    pub description: Option<SharedString>,
+    /// The current value of the element, such as the text of an input.
+    pub value: Option<SharedString>,
+    /// The selected range in `value`, as UTF-8 byte offsets. The end of the range is where
+    /// the cursor is, so it can come before the start.
+    pub text_selection: Option<Range<usize>>,
+    /// Whether a checkbox, switch or toggle button is checked.
+    pub checked: Option<bool>,
+    /// Whether a collapsible element, such as a tree item, is expanded.
+    pub expanded: Option<bool>,
+    /// Whether the element is selected within a list, tree or tab list.
+    pub selected: Option<bool>,
+    /// Whether the element can't be interacted with.
+    pub disabled: bool,
+    /// Whether the value of the element can't be edited.
+    pub read_only: bool,
+}
+
+/// A node in an [`AccessibilityTree`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessibilityNode {
+    /// The properties declared by the element.
+    pub properties: AccessibilityProperties,
+    /// The bounds of the element in the window.
+    pub bounds: Bounds<Pixels>,
+    /// Whether the element can be focused.
+    pub focusable: bool,
+    /// Whether the element can be clicked.
+    pub clickable: bool,
+    /// The nodes for the elements painted within this one, in paint order.
+    pub children: Vec<AccessibilityNodeId>,
+}
+
+/// An accessibility tree describing the contents of a window, in a form that can be handed
+/// to the platform's accessibility APIs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityTree {
+    /// The identifier of the node representing the window.
+    pub root: AccessibilityNodeId,
+    /// The node that currently has keyboard focus, which is the root when nothing is focused.
+    pub focus: AccessibilityNodeId,
+    /// Every node in the tree.
+    pub nodes: FxHashMap<AccessibilityNodeId, AccessibilityNode>,
+}
+
+impl Default for AccessibilityTree {
+    fn default() -> Self {
+        let root = AccessibilityNode {
+            properties: AccessibilityProperties {
+                role: AccessibilityRole::Window,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Self {
+            root: AccessibilityNodeId::ROOT,
+            focus: AccessibilityNodeId::ROOT,
+            nodes: FxHashMap::from_iter([(AccessibilityNodeId::ROOT, root)]),
+        }
+    }
+}
+
+/// The changes between two versions of an [`AccessibilityTree`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessibilityTreeUpdate {
+    /// The nodes that were added or changed.
+    pub nodes: Vec<(AccessibilityNodeId, AccessibilityNode)>,
+    /// The nodes that are no longer part of the tree.
+    pub removed: Vec<AccessibilityNodeId>,
+    /// The node that has keyboard focus.
+    pub focus: AccessibilityNodeId,
+    /// The root of the tree.
+    pub root: AccessibilityNodeId,
+}
+
+/// An action requested by an assistive technology.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessibilityAction {
+    /// Move keyboard focus to the node.
+    Focus,
+    /// Activate the node as if it had been clicked.
+    Click,
+}
+
+/// A request from an assistive technology to perform an action on a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessibilityActionRequest {
+    /// The node the action is performed on.
+    pub target: AccessibilityNodeId,
+    /// The action to perform.
+    pub action: AccessibilityAction,
+}
+
+#[derive(Clone)]
+pub(crate) enum AccessibilityOperation {
+    Push {
+        id: Option<AccessibilityNodeId>,
+        node: AccessibilityNode,
+        focus_id: Option<FocusId>,
+    },
+    Text {
+        text: SharedString,
+        bounds: Bounds<Pixels>,
+    },
+    Pop,
+}
+
+struct PendingNode {
+    id: AccessibilityNodeId,
+    node: AccessibilityNode,
+    named_from_contents: bool,
+}
+
+impl AccessibilityTree {
+    /// Builds the tree from the operations recorded while painting a frame. Returns the tree,
+    /// along with the focus handle of each focusable node.
+    pub(crate) fn build(
+        root: AccessibilityProperties,
+        operations: &[AccessibilityOperation],
+        focus: Option<FocusId>,
+    ) -> (Self, FxHashMap<AccessibilityNodeId, FocusId>) {
+        let mut tree = Self {
+            root: AccessibilityNodeId::ROOT,
+            focus: AccessibilityNodeId::ROOT,
+            nodes: FxHashMap::default(),
+        };
+        let mut focus_ids = FxHashMap::default();
+        let mut stack = vec![PendingNode {
+            id: AccessibilityNodeId::ROOT,
+            node: AccessibilityNode {
+                properties: root,
+                ..Default::default()
+            },
+            named_from_contents: false,
+        }];
+        let mut ids = FxHashSet::from_iter([AccessibilityNodeId::ROOT]);
+
+        for operation in operations {
+            match operation {
+                AccessibilityOperation::Push { id, node, focus_id } => {
+                    let parent = stack.last_mut().unwrap();
+                    let id = id
+                        .filter(|id| !ids.contains(id))
+                        .unwrap_or_else(|| unique_child_id(parent, node.properties.role, &ids));
+                    ids.insert(id);
+                    parent.node.children.push(id);
+                    if let Some(focus_id) = focus_id {
+                        focus_ids.insert(id, *focus_id);
+                        if Some(*focus_id) == focus {
+                            tree.focus = id;
+                        }
+                    }
+                    stack.push(PendingNode {
+                        id,
+                        named_from_contents: node.properties.name.is_none()
+                            && node.properties.role.is_named_from_contents(),
+                        node: node.clone(),
+                    });
+                }
+                AccessibilityOperation::Text { text, bounds } => {
+                    let parent = stack.last_mut().unwrap();
+                    if parent.named_from_contents {
+                        let name = parent
+                            .node
+                            .properties
+                            .name
+                            .get_or_insert_with(Default::default);
+                        *name = if name.is_empty() {
+                            text.clone()
+                        } else {
+                            format!("{name} {text}").into()
+                        };
+                    } else {
+                        let id = unique_child_id(parent, AccessibilityRole::Label, &ids);
+                        ids.insert(id);
+                        parent.node.children.push(id);
+                        tree.nodes.insert(
+                            id,
+                            AccessibilityNode {
+                                properties: AccessibilityProperties {
+                                    role: AccessibilityRole::Label,
+                                    name: Some(text.clone()),
+                                    ..Default::default()
+                                },
+                                bounds: *bounds,
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+                AccessibilityOperation::Pop => {
+                    if stack.len() > 1 {
+                        let pending = stack.pop().unwrap();
+                        tree.nodes.insert(pending.id, pending.node);
+                    }
+                }
+            }
+        }
+
+        // Close any nodes that weren't popped, including the root.
+        while let Some(pending) = stack.pop() {
+            tree.nodes.insert(pending.id, pending.node);
+        }
+
+        (tree, focus_ids)
+    }
+
+    /// Returns the changes needed to turn this tree into the given one, if there are any.
+    pub fn diff(&self, new: &Self) -> Option<AccessibilityTreeUpdate> {
+        let mut nodes = new
+            .nodes
+            .iter()
+            .filter(|(id, node)| self.nodes.get(id) != Some(node))
+            .map(|(id, node)| (*id, node.clone()))
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|(id, _)| *id);
+        let mut removed = self
+            .nodes
+            .keys()
+            .filter(|id| !new.nodes.contains_key(id))
+            .copied()
+            .collect::<Vec<_>>();
+        removed.sort();
+
+        if nodes.is_empty()
+            && removed.is_empty()
+            && self.focus == new.focus
+            && self.root == new.root
+        {
+            return None;
+        }
+
+        Some(AccessibilityTreeUpdate {
+            nodes,
+            removed,
+            focus: new.focus,
+            root: new.root,
+        })
+    }
+
+    /// Applies the changes from an update produced by [`AccessibilityTree::diff`].
+    pub fn apply(&mut self, update: AccessibilityTreeUpdate) {
+        for id in update.removed {
+            self.nodes.remove(&id);
+        }
+        self.nodes.extend(update.nodes);
+        self.focus = update.focus;
+        self.root = update.root;
+    }
+
+    /// Returns the node with the given identifier.
+    pub fn node(&self, id: AccessibilityNodeId) -> Option<&AccessibilityNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns the identifiers of the nodes with the given name, in tree order.
+    pub fn find_by_name(&self, name: &str) -> Vec<AccessibilityNodeId> {
+        let mut found = Vec::new();
+        self.visit(self.root, 0, &mut |id, node, _| {
+            if node.properties.name.as_deref() == Some(name) {
+                found.push(id);
+            }
+        });
+        found
+    }
+
+    fn visit(
+        &self,
+        id: AccessibilityNodeId,
+        depth: usize,
+        f: &mut impl FnMut(AccessibilityNodeId, &AccessibilityNode, usize),
+    ) {
+        if let Some(node) = self.nodes.get(&id) {
+            f(id, node, depth);
+            for child in &node.children {
+                self.visit(*child, depth + 1, f);
+            }
+        }
+    }
+}
+
+fn unique_child_id(
+    parent: &PendingNode,
+    role: AccessibilityRole,
+    ids: &FxHashSet<AccessibilityNodeId>,
+) -> AccessibilityNodeId {
+    let mut index = parent.node.children.len();
+    loop {
+        let id = AccessibilityNodeId::for_child(parent.id, index, role);
+        if !ids.contains(&id) {
+            return id;
+        }
+        index += 1;
+    }
+}
+
+/// Renders the tree as an indented outline, which is useful for asserting on it in tests.
+impl fmt::Display for AccessibilityTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut result = Ok(());
+        self.visit(self.root, 0, &mut |id, node, depth| {
+            if result.is_ok() {
+                result = write_node(f, node, depth, id == self.focus);
+            }
+        });
+        result
+    }
+}
+
+fn write_node(
+    f: &mut fmt::Formatter<'_>,
+    node: &AccessibilityNode,
+    depth: usize,
+    focused: bool,
+) -> fmt::Result {
+    let properties = &node.properties;
+    write!(
+        f,
+        "{:indent$}{}",
+        "",
+        properties.role.label(),
+        indent = depth * 2
+    )?;
+    if let Some(name) = &properties.name {
+        write!(f, " {name:?}")?;
+    }
+    if let Some(value) = &properties.value {
+        write!(f, " value={value:?}")?;
+    }
+    if let Some(selection) = &properties.text_selection {
+        write!(f, " selection={selection:?}")?;
+    }
+
+    let mut states = Vec::new();
+    if focused {
+        states.push("focused");
+    }
+    match properties.checked {
+        Some(true) => states.push("checked"),
+        Some(false) => states.push("unchecked"),
+        None => {}
+    }
+    match properties.expanded {
+        Some(true) => states.push("expanded"),
+        Some(false) => states.push("collapsed"),
+        None => {}
+    }
+    if properties.selected == Some(true) {
+        states.push("selected");
+    }
+    if properties.disabled {
+        states.push("disabled");
+    }
+    if properties.read_only {
+        states.push("read-only");
+    }
+    if !states.is_empty() {
+        write!(f, " ({})", states.join(", "))?;
+    }
+    writeln!(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, div, FocusHandle, InteractiveElement as _, IntoElement, ParentElement as _,
+        Render, StatefulInteractiveElement as _, TestAppContext, ViewContext,
+    };
+
+    fn push(role: AccessibilityRole, name: Option<&str>) -> AccessibilityOperation {
+        AccessibilityOperation::Push {
+            id: None,
+            node: AccessibilityNode {
+                properties: AccessibilityProperties {
+                    role,
+                    name: name.map(|name| name.to_string().into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            focus_id: None,
+        }
+    }
+
+    fn text(text: &'static str) -> AccessibilityOperation {
+        AccessibilityOperation::Text {
+            text: text.into(),
+            bounds: Bounds::default(),
+        }
+    }
+
+    #[test]
+    fn test_build_and_diff() {
+        let operations = vec![
+            push(AccessibilityRole::Toolbar, Some("Tools")),
+            push(AccessibilityRole::Button, None),
+            text("Save"),
+            text("All"),
+            AccessibilityOperation::Pop,
+            AccessibilityOperation::Pop,
+            text("Ready"),
+        ];
+        let (tree, _) =
+            AccessibilityTree::build(AccessibilityProperties::default(), &operations, None);
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "group (focused)\n",
+                "  toolbar \"Tools\"\n",
+                "    button \"Save All\"\n",
+                "  label \"Ready\"\n",
+            )
+        );
+
+        // Rebuilding the same tree doesn't produce any changes.
+        let (same_tree, _) =
+            AccessibilityTree::build(AccessibilityProperties::default(), &operations, None);
+        assert_eq!(tree.diff(&same_tree), None);
+
+        let mut operations = operations;
+        operations[3] = text("Changes");
+        operations.truncate(6);
+        let (new_tree, _) =
+            AccessibilityTree::build(AccessibilityProperties::default(), &operations, None);
+        let update = tree.diff(&new_tree).unwrap();
+        let button = tree.find_by_name("Save All")[0];
+        assert_eq!(
+            update.nodes.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            {
+                let mut changed = vec![AccessibilityNodeId::ROOT, button];
+                changed.sort();
+                changed
+            }
+        );
+        assert_eq!(update.removed.len(), 1);
+
+        let mut patched_tree = tree.clone();
+        patched_tree.apply(update);
+        assert_eq!(patched_tree, new_tree);
+    }
+
+    struct Options {
+        focus_handle: FocusHandle,
+        soft_wrap: bool,
+    }
+
+    impl Render for Options {
+        fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .role(AccessibilityRole::Toolbar)
+                .aria_label("Options")
+                .child(
+                    div()
+                        .id("save")
+                        .role(AccessibilityRole::Button)
+                        .track_focus(&self.focus_handle)
+                        .child("Save"),
+                )
+                .child(
+                    div()
+                        .id("soft-wrap")
+                        .role(AccessibilityRole::CheckBox)
+                        .aria_checked(self.soft_wrap)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.soft_wrap = !this.soft_wrap;
+                            cx.notify();
+                        }))
+                        .child("Soft wrap"),
+                )
+                .child("Changes are saved automatically")
+        }
+    }
+
+    #[gpui::test]
+    fn test_window_accessibility_tree(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|cx| Options {
+            focus_handle: cx.focus_handle(),
+            soft_wrap: false,
+        });
+        // The tree isn't built, nor are its nodes recorded, until an assistive technology connects.
+        assert_eq!(cx.accessibility_tree(), AccessibilityTree::default());
+        cx.update(|cx| assert!(cx.window.rendered_frame.accessibility_operations.is_empty()));
+
+        cx.simulate_accessibility_activation();
+        let tree = cx.accessibility_tree();
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "window (focused)\n",
+                "  toolbar \"Options\"\n",
+                "    button \"Save\"\n",
+                "    checkbox \"Soft wrap\" (unchecked)\n",
+                "    label \"Changes are saved automatically\"\n",
+            )
+        );
+
+        let save = tree.find_by_name("Save")[0];
+        cx.simulate_accessibility_action(save, AccessibilityAction::Focus);
+        let soft_wrap = tree.find_by_name("Soft wrap")[0];
+        cx.simulate_accessibility_action(soft_wrap, AccessibilityAction::Click);
+
+        // Node identifiers are stable across frames, and the tree published to the platform
+        // reflects the focus and the new state of the checkbox.
+        let tree = cx.accessibility_tree();
+        assert_eq!(tree.focus, save);
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "window\n",
+                "  toolbar \"Options\"\n",
+                "    button \"Save\" (focused)\n",
+                "    checkbox \"Soft wrap\" (checked)\n",
+                "    label \"Changes are saved automatically\"\n",
+            )
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNodeId, AccessibilityTree,
     Action, AnyView, AnyWindowHandle, AppCell, AppContext, AsyncAppContext, AvailableSpace,
     BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, Context, DrawPhase, Drawable,
     Element, Empty, Entity, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Model,
@@ -7,9 +8,12 @@ use crate::{
     TestPlatform, TestWindow, TextSystem, View, ViewContext, VisualContext, WindowBounds,
     WindowContext, WindowHandle, WindowOptions,
 };
-use anyhow::{anyhow, bail};
+use anyhow::{anyhow, bail, Context as _};
 use futures::{channel::oneshot, Stream, StreamExt};
-use std::{cell::RefCell, future::Future, ops::Deref, rc::Rc, sync::Arc, time::Duration};
+use image::RgbaImage;
+use std::{
+    cell::RefCell, future::Future, ops::Deref, path::Path, rc::Rc, sync::Arc, time::Duration,
+};
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
 /// an implementation of `Context` with additional methods that are useful in tests.
@@ -414,6 +418,26 @@ impl TestAppContext {
             .clone()
     }
 
+    /// Renders the contents of the given window to an image on the CPU, at the window's
+    /// scale factor.
+    pub fn capture_screenshot(&mut self, window: AnyWindowHandle) -> RgbaImage {
+        self.run_until_parked();
+        self.update_window(window, |_, cx| {
+            if cx.window.dirty.get() {
+                cx.draw();
+            }
+            let size = cx.viewport_size().to_device_pixels(cx.scale_factor());
+            let textures = cx
+                .window
+                .platform_window
+                .as_test()
+                .unwrap()
+                .sprite_textures();
+            crate::rasterize(&cx.window.rendered_frame.scene, size, &textures)
+        })
+        .unwrap()
+    }
+
     /// Returns a stream of notifications whenever the View or Model is updated.
     pub fn notifications<T: 'static>(&mut self, entity: &impl Entity<T>) -> impl Stream<Item = ()> {
         let (tx, rx) = futures::channel::mpsc::unbounded();
@@ -773,6 +797,73 @@ impl VisualTestContext {
         self.update(|cx| cx.window.rendered_frame.debug_bounds.get(selector).copied())
     }
 
+    /// Returns the accessibility tree that was most recently published to the platform for
+    /// this window.
+    pub fn accessibility_tree(&mut self) -> AccessibilityTree {
+        self.run_until_parked();
+        self.test_window(self.window)
+            .0
+            .lock()
+            .accessibility_tree
+            .clone()
+    }
+
+    /// Simulates an assistive technology connecting to this window, which starts the
+    /// publishing of its accessibility tree.
+    pub fn simulate_accessibility_activation(&mut self) {
+        self.test_window(self.window)
+            .simulate_accessibility_activation();
+        self.background_executor.run_until_parked();
+    }
+
+    /// Simulates an assistive technology performing an action on a node in this window's
+    /// accessibility tree.
+    pub fn simulate_accessibility_action(
+        &mut self,
+        target: AccessibilityNodeId,
+        action: AccessibilityAction,
+    ) {
+        self.test_window(self.window)
+            .simulate_accessibility_action(AccessibilityActionRequest { target, action });
+        self.background_executor.run_until_parked();
+    }
+
+    /// Renders the contents of this window to an image on the CPU.
+    pub fn capture_screenshot(&mut self) -> RgbaImage {
+        self.cx.capture_screenshot(self.window)
+    }
+
+    /// Asserts that the contents of this window match the PNG at the given path, which is
+    /// usually built from `env!("CARGO_MANIFEST_DIR")`.
+    ///
+    /// If the `UPDATE_SCREENSHOTS` environment variable is set, the image is written to the
+    /// path instead. Otherwise, when the images differ, the captured image is written next to
+    /// the expected one with an `.actual.png` extension so the two can be compared.
+    #[track_caller]
+    pub fn assert_screenshot(&mut self, path: impl AsRef<Path>, tolerance: ScreenshotTolerance) {
+        let path = path.as_ref();
+        let actual = self.capture_screenshot();
+
+        if std::env::var_os("UPDATE_SCREENSHOTS").is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            actual.save(path).unwrap();
+            return;
+        }
+
+        let result = image::open(path)
+            .with_context(|| {
+                format!("failed to open {path:?}, run with UPDATE_SCREENSHOTS=1 to create it")
+            })
+            .and_then(|expected| compare_screenshots(&actual, &expected.into_rgba8(), tolerance));
+        if let Err(error) = result {
+            let actual_path = path.with_extension("actual.png");
+            actual.save(&actual_path).unwrap();
+            panic!("screenshot mismatch: {error:#}. Captured image written to {actual_path:?}");
+        }
+    }
+
     /// Draw an element to the window. Useful for simulating events or actions
     pub fn draw<E>(
         &mut self,
@@ -859,6 +950,55 @@ impl VisualTestContext {
     }
 }
 
+/// How much a captured screenshot may differ from the expected image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenshotTolerance {
+    /// The largest difference in any channel for two pixels to be considered equal.
+    pub channel: u8,
+    /// The fraction of pixels, between 0 and 1, that are allowed to differ.
+    pub pixels: f32,
+}
+
+impl Default for ScreenshotTolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0.,
+        }
+    }
+}
+
+fn compare_screenshots(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: ScreenshotTolerance,
+) -> Result<()> {
+    if actual.dimensions() != expected.dimensions() {
+        bail!(
+            "expected a {:?} image, got {:?}",
+            expected.dimensions(),
+            actual.dimensions()
+        );
+    }
+
+    let differing_pixels = actual
+        .pixels()
+        .zip(expected.pixels())
+        .filter(|(actual, expected)| {
+            actual
+                .0
+                .iter()
+                .zip(expected.0.iter())
+                .any(|(actual, expected)| actual.abs_diff(*expected) > tolerance.channel)
+        })
+        .count();
+    let pixel_count = (actual.width() * actual.height()).max(1);
+    if differing_pixels as f32 / pixel_count as f32 > tolerance.pixels {
+        bail!("{differing_pixels} of {pixel_count} pixels differ");
+    }
+    Ok(())
+}
+
 impl Context for VisualTestContext {
     type Result<T> = <TestAppContext as Context>::Result<T>;
 
@@ -987,3 +1127,58 @@ impl AnyWindowHandle {
         self.update(cx, |_, cx| cx.new_view(build_view)).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as gpui, div, px, rgb, IntoElement, ParentElement as _, Styled as _};
+
+    struct Swatch;
+
+    impl Render for Swatch {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .bg(rgb(0xffffff))
+                .child(div().size(px(10.)).bg(rgb(0xff0000)))
+        }
+    }
+
+    #[gpui::test]
+    fn test_capture_screenshot(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_| Swatch);
+        let viewport_size = cx.update(|cx| cx.viewport_size());
+        let image = cx.capture_screenshot();
+
+        // Test windows have a scale factor of 2.
+        assert_eq!(
+            image.dimensions(),
+            (
+                (viewport_size.width.0 * 2.) as u32,
+                (viewport_size.height.0 * 2.) as u32
+            )
+        );
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(19, 19).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(20, 20).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_compare_screenshots() {
+        let expected = RgbaImage::from_pixel(10, 10, image::Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, image::Rgba([102, 100, 100, 255]));
+        assert!(compare_screenshots(&actual, &expected, ScreenshotTolerance::default()).is_ok());
+
+        actual.put_pixel(1, 0, image::Rgba([110, 100, 100, 255]));
+        assert!(compare_screenshots(&actual, &expected, ScreenshotTolerance::default()).is_err());
+        let tolerance = ScreenshotTolerance {
+            channel: 2,
+            pixels: 0.01,
+        };
+        assert!(compare_screenshots(&actual, &expected, tolerance).is_ok());
+
+        let smaller = RgbaImage::from_pixel(5, 5, image::Rgba([100, 100, 100, 255]));
+        assert!(compare_screenshots(&smaller, &expected, tolerance).is_err());
+    }
+}
@@ -16,12 +16,13 @@
 //! constructed by combining these two systems into an all-in-one element.
 
 use crate::{
-    point, px, size, Action, AnyDrag, AnyElement, AnyTooltip, AnyView, AppContext, Bounds,
-    ClickEvent, DispatchPhase, Element, ElementId, FocusHandle, Global, GlobalElementId, Hitbox,
-    HitboxId, IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, LayoutId,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
-    StyleRefinement, Styled, Task, TooltipId, View, Visibility, WindowContext,
+    point, px, size, AccessibilityNode, AccessibilityProperties, AccessibilityRole, Action,
+    AnyDrag, AnyElement, AnyTooltip, AnyView, AppContext, Bounds, ClickEvent, DispatchPhase,
+    Element, ElementId, FocusHandle, Global, GlobalElementId, Hitbox, HitboxId, InspectorElementId,
+    IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, LayoutId, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point,
+    Render, ScrollWheelEvent, SharedString, Size, Style, StyleRefinement, Styled, Task, TooltipId,
+    View, Visibility, WindowContext,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -560,6 +561,54 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Expose this element to assistive technologies, such as screen readers, with the given role.
+    fn role(mut self, role: AccessibilityRole) -> Self {
+        self.interactivity().accessibility_mut().role = role;
+        self
+    }
+
+    /// Set the name assistive technologies read out for this element.
+    fn aria_label(mut self, label: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().name = Some(label.into());
+        self
+    }
+
+    /// Set additional information assistive technologies provide about this element.
+    fn aria_description(mut self, description: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().description = Some(description.into());
+        self
+    }
+
+    /// Set the value assistive technologies read out for this element, such as the text of an input.
+    fn aria_value(mut self, value: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().value = Some(value.into());
+        self
+    }
+
+    /// Expose whether this element, such as a checkbox, is checked.
+    fn aria_checked(mut self, checked: bool) -> Self {
+        self.interactivity().accessibility_mut().checked = Some(checked);
+        self
+    }
+
+    /// Expose whether this element, such as a tree item, is expanded.
+    fn aria_expanded(mut self, expanded: bool) -> Self {
+        self.interactivity().accessibility_mut().expanded = Some(expanded);
+        self
+    }
+
+    /// Expose whether this element is selected within its list, tree or tab list.
+    fn aria_selected(mut self, selected: bool) -> Self {
+        self.interactivity().accessibility_mut().selected = Some(selected);
+        self
+    }
+
+    /// Expose whether this element is disabled.
+    fn aria_disabled(mut self, disabled: bool) -> Self {
+        self.interactivity().accessibility_mut().disabled = disabled;
+        self
+    }
+
     /// Apply the given style to this element when the mouse hovers over it
     fn hover(mut self, f: impl FnOnce(StyleRefinement) -> StyleRefinement) -> Self {
         debug_assert!(
@@ -1296,6 +1345,9 @@ pub struct Interactivity {
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut WindowContext)>>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
     pub(crate) occlude_mouse: bool,
+    pub(crate) accessibility: Option<Box<AccessibilityProperties>>,
+    pub(crate) inspector_id: Option<InspectorElementId>,
+    pub(crate) layout_id: Option<LayoutId>,
 
     #[cfg(debug_assertions)]
     pub(crate) location: Option<core::panic::Location<'static>>,
@@ -1305,6 +1357,21 @@ pub struct Interactivity {
 }
 
 impl Interactivity {
+    /// The properties this element exposes to assistive technologies.
+    pub fn accessibility_mut(&mut self) -> &mut AccessibilityProperties {
+        self.accessibility.get_or_insert_with(Default::default)
+    }
+
+    fn source_location(&self) -> Option<core::panic::Location<'static>> {
+        #[cfg(debug_assertions)]
+        let location = self.location;
+
+        #[cfg(not(debug_assertions))]
+        let location = None;
+
+        location
+    }
+
     /// Layout this element according to this interactivity state's configured styles
     pub fn request_layout(
         &mut self,
@@ -1312,6 +1379,8 @@ impl Interactivity {
         cx: &mut WindowContext,
         f: impl FnOnce(Style, &mut WindowContext) -> LayoutId,
     ) -> LayoutId {
+        self.inspector_id = cx.inspect_element(self.source_location(), &mut self.base_style);
+
         cx.with_optional_element_state::<InteractiveElementState, _>(
             global_id,
             |element_state, cx| {
@@ -1362,6 +1431,9 @@ impl Interactivity {
 
                 let style = self.compute_style_internal(None, element_state.as_mut(), cx);
                 let layout_id = f(style, cx);
+                if self.inspector_id.is_some() {
+                    self.layout_id = Some(layout_id);
+                }
                 (layout_id, element_state)
             },
         )
@@ -1380,12 +1452,22 @@ impl Interactivity {
         if let Some(focus_handle) = self.tracked_focus_handle.as_ref() {
             cx.set_focus_handle(focus_handle);
         }
-        cx.with_optional_element_state::<InteractiveElementState, _>(
+        let result = cx.with_optional_element_state::<InteractiveElementState, _>(
             global_id,
             |element_state, cx| {
                 let mut element_state =
                     element_state.map(|element_state| element_state.unwrap_or_default());
                 let style = self.compute_style_internal(None, element_state.as_mut(), cx);
+                if let Some(inspector_id) = self.inspector_id {
+                    cx.push_inspected_element(
+                        inspector_id,
+                        self.source_location(),
+                        self.element_id.clone(),
+                        bounds,
+                        &style,
+                        self.layout_id,
+                    );
+                }
 
                 if let Some(element_state) = element_state.as_ref() {
                     if let Some(clicked_state) = element_state.clicked_state.as_ref() {
@@ -1416,7 +1498,11 @@ impl Interactivity {
                     })
                 })
             },
-        )
+        );
+        if self.inspector_id.is_some() {
+            cx.pop_inspected_element();
+        }
+        result
     }
 
     fn should_insert_hitbox(&self, style: &Style) -> bool {
@@ -1518,47 +1604,67 @@ impl Interactivity {
                     return ((), element_state);
                 }
 
-                cx.with_element_opacity(style.opacity, |cx| {
-                    style.paint(bounds, cx, |cx: &mut WindowContext| {
-                        cx.with_text_style(style.text_style().cloned(), |cx| {
-                            cx.with_content_mask(
-                                style.overflow_mask(bounds, cx.rem_size()),
-                                |cx| {
-                                    if let Some(hitbox) = hitbox {
-                                        #[cfg(debug_assertions)]
-                                        self.paint_debug_info(global_id, hitbox, &style, cx);
-
-                                        if !cx.has_active_drag() {
-                                            if let Some(mouse_cursor) = style.mouse_cursor {
-                                                cx.set_cursor_style(mouse_cursor, hitbox);
+                let accessibility_node =
+                    self.accessibility
+                        .as_deref()
+                        .map(|properties| AccessibilityNode {
+                            properties: properties.clone(),
+                            bounds,
+                            focusable: self.tracked_focus_handle.is_some(),
+                            clickable: !self.click_listeners.is_empty(),
+                            children: Vec::new(),
+                        });
+                let focus_handle = self.tracked_focus_handle.clone();
+
+                let paint_contents = |cx: &mut WindowContext| {
+                    cx.with_element_opacity(style.opacity, |cx| {
+                        style.paint(bounds, cx, |cx: &mut WindowContext| {
+                            cx.with_text_style(style.text_style().cloned(), |cx| {
+                                cx.with_content_mask(
+                                    style.overflow_mask(bounds, cx.rem_size()),
+                                    |cx| {
+                                        if let Some(hitbox) = hitbox {
+                                            #[cfg(debug_assertions)]
+                                            self.paint_debug_info(global_id, hitbox, &style, cx);
+
+                                            if !cx.has_active_drag() {
+                                                if let Some(mouse_cursor) = style.mouse_cursor {
+                                                    cx.set_cursor_style(mouse_cursor, hitbox);
+                                                }
                                             }
-                                        }
 
-                                        if let Some(group) = self.group.clone() {
-                                            GroupHitboxes::push(group, hitbox.id, cx);
-                                        }
+                                            if let Some(group) = self.group.clone() {
+                                                GroupHitboxes::push(group, hitbox.id, cx);
+                                            }
 
-                                        self.paint_mouse_listeners(
-                                            hitbox,
-                                            element_state.as_mut(),
-                                            cx,
-                                        );
-                                        self.paint_scroll_listener(hitbox, &style, cx);
-                                    }
+                                            self.paint_mouse_listeners(
+                                                hitbox,
+                                                element_state.as_mut(),
+                                                cx,
+                                            );
+                                            self.paint_scroll_listener(hitbox, &style, cx);
+                                        }
 
-                                    self.paint_keyboard_listeners(cx);
-                                    f(&style, cx);
+                                        self.paint_keyboard_listeners(cx);
+                                        f(&style, cx);
 
-                                    if hitbox.is_some() {
-                                        if let Some(group) = self.group.as_ref() {
-                                            GroupHitboxes::pop(group, cx);
+                                        if hitbox.is_some() {
+                                            if let Some(group) = self.group.as_ref() {
+                                                GroupHitboxes::pop(group, cx);
+                                            }
                                         }
-                                    }
-                                },
-                            );
+                                    },
+                                );
+                            });
                         });
-                    });
-                });
+                    })
+                };
+
+                if let Some(node) = accessibility_node {
+                    cx.with_accessibility_node(node, focus_handle.as_ref(), paint_contents);
+                } else {
+                    paint_contents(cx);
+                }
 
                 ((), element_state)
             },
@@ -269,6 +269,17 @@ impl ListState {
         }
     }
 
+    /// The range of items that were visible as of the last time the list was laid out.
+    pub fn visible_range(&self) -> Range<usize> {
+        let state = &*self.0.borrow();
+        let height = state
+            .last_layout_bounds
+            .map_or(px(0.), |bounds| bounds.size.height);
+        let range = state.visible_range(height, &state.logical_scroll_top());
+        let count = state.items.summary().count;
+        range.start.min(count)..range.end.min(count)
+    }
+
     /// Set a handler that will be called when the list is scrolled.
     pub fn set_scroll_handler(
         &self,
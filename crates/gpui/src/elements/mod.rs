@@ -6,9 +6,12 @@ mod deferred;
 mod div;
 mod img;
 mod list;
+mod row_selection;
 mod surface;
 mod svg;
+mod table;
 mod text;
+mod tree;
 mod uniform_list;
 
 pub use anchored::*;
@@ -21,5 +24,7 @@ pub use img::*;
 pub use list::*;
 pub use surface::*;
 pub use svg::*;
+pub use table::*;
 pub use text::*;
+pub use tree::*;
 pub use uniform_list::*;
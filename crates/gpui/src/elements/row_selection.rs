@@ -0,0 +1,227 @@
+//! Selection and keyboard navigation shared by the [`Tree`](crate::Tree) and
+//! [`Table`](crate::Table) elements. Rows are addressed by their position in the element,
+//! while the selection is tracked by a key that stays the same when rows move around, such as
+//! when nodes are expanded or a table is sorted.
+
+use crate::{Keystroke, Modifiers};
+use collections::FxHashSet;
+use std::hash::Hash;
+
+/// A keyboard command understood by the row-based elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RowNavigation {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Left,
+    Right,
+    SelectAll,
+}
+
+impl RowNavigation {
+    /// Interprets a keystroke, returning whether it extends the selection.
+    pub(crate) fn from_keystroke(keystroke: &Keystroke) -> Option<(Self, bool)> {
+        let modifiers = keystroke.modifiers;
+        let navigation = match keystroke.key.as_str() {
+            "up" => Self::Up,
+            "down" => Self::Down,
+            "pageup" => Self::PageUp,
+            "pagedown" => Self::PageDown,
+            "home" => Self::Home,
+            "end" => Self::End,
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "a" if modifiers.secondary() && !modifiers.shift => Self::SelectAll,
+            _ => return None,
+        };
+        Some((navigation, modifiers.shift))
+    }
+}
+
+pub(crate) struct RowSelection<K> {
+    selected: FxHashSet<K>,
+    cursor: Option<K>,
+    anchor: Option<K>,
+}
+
+impl<K> Default for RowSelection<K> {
+    fn default() -> Self {
+        Self {
+            selected: FxHashSet::default(),
+            cursor: None,
+            anchor: None,
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash> RowSelection<K> {
+    pub(crate) fn is_selected(&self, key: K) -> bool {
+        self.selected.contains(&key)
+    }
+
+    pub(crate) fn cursor(&self) -> Option<K> {
+        self.cursor
+    }
+
+    pub(crate) fn selected_count(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// Selects only the given row.
+    pub(crate) fn select(&mut self, key: K) {
+        self.selected.clear();
+        self.selected.insert(key);
+        self.cursor = Some(key);
+        self.anchor = Some(key);
+    }
+
+    /// Adds or removes the given row from the selection.
+    pub(crate) fn toggle(&mut self, key: K) {
+        if !self.selected.remove(&key) {
+            self.selected.insert(key);
+        }
+        self.cursor = Some(key);
+        self.anchor = Some(key);
+    }
+
+    /// Selects the rows between the anchor and the given position.
+    pub(crate) fn extend(
+        &mut self,
+        to: usize,
+        position: impl Fn(K) -> Option<usize>,
+        key_at: impl Fn(usize) -> K,
+    ) {
+        let from = self.anchor.and_then(&position).unwrap_or(to);
+        self.selected.clear();
+        self.selected
+            .extend((from.min(to)..=from.max(to)).map(&key_at));
+        self.cursor = Some(key_at(to));
+        self.anchor.get_or_insert(key_at(from));
+    }
+
+    pub(crate) fn select_all(&mut self, keys: impl IntoIterator<Item = K>) {
+        self.selected.extend(keys);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.selected.clear();
+        self.cursor = None;
+        self.anchor = None;
+    }
+
+    /// Drops the rows that no longer exist from the selection.
+    pub(crate) fn retain(&mut self, exists: impl Fn(K) -> bool) {
+        self.selected.retain(|key| exists(*key));
+        self.cursor = self.cursor.filter(|key| exists(*key));
+        self.anchor = self.anchor.filter(|key| exists(*key));
+    }
+
+    /// Applies a mouse click on the row at the given position.
+    pub(crate) fn click(
+        &mut self,
+        ix: usize,
+        modifiers: Modifiers,
+        position: impl Fn(K) -> Option<usize>,
+        key_at: impl Fn(usize) -> K,
+    ) {
+        if modifiers.shift {
+            self.extend(ix, position, key_at);
+        } else if modifiers.secondary() {
+            self.toggle(key_at(ix));
+        } else {
+            self.select(key_at(ix));
+        }
+    }
+
+    /// Moves the cursor vertically, returning the position of the row it moved to.
+    pub(crate) fn navigate(
+        &mut self,
+        navigation: RowNavigation,
+        extend: bool,
+        row_count: usize,
+        page_size: usize,
+        position: impl Fn(K) -> Option<usize>,
+        key_at: impl Fn(usize) -> K,
+    ) -> Option<usize> {
+        if row_count == 0 {
+            return None;
+        }
+        if navigation == RowNavigation::SelectAll {
+            self.select_all((0..row_count).map(key_at));
+            return None;
+        }
+
+        let last = row_count - 1;
+        let target = match (self.cursor.and_then(&position), navigation) {
+            (None, RowNavigation::End) => last,
+            (None, _) => 0,
+            (Some(ix), RowNavigation::Up) => ix.saturating_sub(1),
+            (Some(ix), RowNavigation::Down) => (ix + 1).min(last),
+            (Some(ix), RowNavigation::PageUp) => ix.saturating_sub(page_size.max(1)),
+            (Some(ix), RowNavigation::PageDown) => (ix + page_size.max(1)).min(last),
+            (Some(_), RowNavigation::Home) => 0,
+            (Some(_), RowNavigation::End) => last,
+            (Some(ix), _) => ix,
+        };
+
+        if extend {
+            self.extend(target, position, key_at);
+        } else {
+            self.select(key_at(target));
+        }
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selected(selection: &RowSelection<usize>) -> Vec<usize> {
+        let mut selected = selection.selected.iter().copied().collect::<Vec<_>>();
+        selected.sort();
+        selected
+    }
+
+    #[test]
+    fn test_row_selection() {
+        // Rows are displayed in reverse order of their keys.
+        let count = 10;
+        let key_at = |ix: usize| count - 1 - ix;
+        let position = |key: usize| (key < count).then(|| count - 1 - key);
+        let mut selection = RowSelection::default();
+
+        selection.click(2, Modifiers::none(), position, key_at);
+        assert_eq!(selected(&selection), [7]);
+
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        selection.click(4, shift, position, key_at);
+        assert_eq!(selected(&selection), [5, 6, 7]);
+        assert_eq!(selection.cursor(), Some(5));
+
+        // Extending again keeps the original anchor.
+        let target = selection.navigate(RowNavigation::Up, true, count, 3, position, key_at);
+        assert_eq!(target, Some(3));
+        assert_eq!(selected(&selection), [6, 7]);
+
+        selection.click(9, Modifiers::secondary_key(), position, key_at);
+        assert_eq!(selected(&selection), [0, 6, 7]);
+
+        let target = selection.navigate(RowNavigation::PageUp, false, count, 3, position, key_at);
+        assert_eq!(target, Some(6));
+        assert_eq!(selected(&selection), [3]);
+
+        selection.navigate(RowNavigation::SelectAll, false, count, 3, position, key_at);
+        assert_eq!(selection.selected_count(), count);
+
+        selection.retain(|key| key % 2 == 0);
+        assert_eq!(selected(&selection), [0, 2, 4, 6, 8]);
+        assert_eq!(selection.cursor(), None);
+    }
+}
@@ -0,0 +1,690 @@
+//! A table element that renders only the rows that are visible, so it can display hundreds of
+//! thousands of rows. Rows can have different heights, and columns can be resized by dragging
+//! the edge of their header and sorted by clicking it. The table supports multiple selection,
+//! along with keyboard navigation when it's focused.
+//!
+//! Rows are identified by their index in your data, which stays the same when the table is
+//! sorted. Like [`ListState`], the state of the table is stored on your own view, so you can
+//! coordinate directly with it.
+
+use super::row_selection::{RowNavigation, RowSelection};
+use crate::{
+    div, list, px, AnyElement, Component, ElementId, EmptyView, FocusHandle, InteractiveElement,
+    IntoElement, Keystroke, ListAlignment, ListState, MouseButton, MouseDownEvent, ParentElement,
+    Pixels, RenderOnce, SharedString, StatefulInteractiveElement, StyleRefinement, Styled,
+    WindowContext,
+};
+use refineable::Refineable as _;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    rc::{Rc, Weak},
+};
+
+const RESIZE_HANDLE_WIDTH: Pixels = px(4.);
+
+/// A column of a [`Table`].
+#[derive(Clone, Debug)]
+pub struct TableColumn {
+    /// The name of the column, displayed in its header by default.
+    pub name: SharedString,
+    /// The width of the column, including its resize handle.
+    pub width: Pixels,
+    /// The width below which the column can't be resized.
+    pub min_width: Pixels,
+    /// Whether the column can be resized by dragging the edge of its header.
+    pub resizable: bool,
+    /// Whether the rows can be sorted by clicking the header of the column.
+    pub sortable: bool,
+}
+
+impl TableColumn {
+    /// A resizable and sortable column.
+    pub fn new(name: impl Into<SharedString>, width: Pixels) -> Self {
+        Self {
+            name: name.into(),
+            width,
+            min_width: px(24.),
+            resizable: true,
+            sortable: true,
+        }
+    }
+
+    /// Set the width below which the column can't be resized.
+    pub fn min_width(mut self, min_width: Pixels) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Set whether the column can be resized.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Set whether the rows can be sorted by this column.
+    pub fn sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+}
+
+/// The direction in which a [`Table`] is sorted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    /// The smallest values come first.
+    Ascending,
+    /// The largest values come first.
+    Descending,
+}
+
+/// A cell of a [`Table`], describing what the cell's element should render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableCell {
+    /// The index of the row in your data.
+    pub row: usize,
+    /// The position at which the row is displayed, after sorting.
+    pub display_row: usize,
+    /// The index of the column.
+    pub column: usize,
+    /// Whether the row is selected.
+    pub selected: bool,
+    /// Whether the row is the one keyboard navigation moves from.
+    pub active: bool,
+}
+
+/// Construct a new table element.
+pub fn table(id: impl Into<ElementId>, state: TableState) -> Table {
+    Table {
+        id: id.into(),
+        state,
+        focus_handle: None,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// A table element, see the [module documentation](self) for more.
+pub struct Table {
+    id: ElementId,
+    state: TableState,
+    focus_handle: Option<FocusHandle>,
+    style: StyleRefinement,
+}
+
+impl Table {
+    /// Track the focus of the table with the given handle. Rows are navigated with the
+    /// keyboard while it's focused, and clicking a row focuses it.
+    pub fn track_focus(mut self, focus_handle: &FocusHandle) -> Self {
+        self.focus_handle = Some(focus_handle.clone());
+        self
+    }
+}
+
+impl Styled for Table {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl IntoElement for Table {
+    type Element = Component<Self>;
+
+    fn into_element(self) -> Self::Element {
+        Component::new(self)
+    }
+}
+
+impl RenderOnce for Table {
+    fn render(self, cx: &mut WindowContext) -> impl IntoElement {
+        let state = self.state.clone();
+        let mut container = div()
+            .id(self.id)
+            .flex()
+            .flex_col()
+            .overflow_x_scroll()
+            .on_key_down(move |event, cx| {
+                if state.handle_keystroke(&event.keystroke, cx) {
+                    cx.stop_propagation();
+                }
+            });
+        container.style().refine(&self.style);
+        if let Some(focus_handle) = self.focus_handle {
+            let interactivity = container.interactivity();
+            interactivity.focusable = true;
+            interactivity.tracked_focus_handle = Some(focus_handle.clone());
+            self.state.0.borrow_mut().focus_handle = Some(focus_handle);
+        }
+
+        let (list_state, total_width) = {
+            let state = self.state.0.borrow();
+            (state.list.clone(), state.total_width())
+        };
+        container.child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_1()
+                .w(total_width)
+                .child(self.state.render_header(cx))
+                .child(list(list_state).flex_1()),
+        )
+    }
+}
+
+#[derive(Clone)]
+struct DraggedColumn {
+    table: TableState,
+    column: usize,
+}
+
+/// The table state that views must hold on behalf of the table element.
+#[derive(Clone)]
+pub struct TableState(Rc<RefCell<TableStateInner>>);
+
+struct TableStateInner {
+    list: ListState,
+    columns: Vec<TableColumn>,
+    row_count: usize,
+    /// The data row displayed at each position, or `None` when the table isn't sorted.
+    order: Option<Vec<usize>>,
+    /// The position at which each data row is displayed, the inverse of `order`.
+    positions: Vec<usize>,
+    sort: Option<(usize, SortDirection)>,
+    selection: RowSelection<usize>,
+    focus_handle: Option<FocusHandle>,
+    #[allow(clippy::type_complexity)]
+    comparator: Option<Rc<dyn Fn(usize, usize, usize) -> Ordering>>,
+    #[allow(clippy::type_complexity)]
+    render_header:
+        Option<Rc<dyn Fn(usize, Option<SortDirection>, &mut WindowContext) -> AnyElement>>,
+}
+
+impl TableState {
+    /// Construct a new table state, for storage on a view.
+    ///
+    /// `render_cell` is called for each cell of the rows that scroll into view.
+    pub fn new(
+        columns: Vec<TableColumn>,
+        row_count: usize,
+        mut render_cell: impl FnMut(TableCell, &mut WindowContext) -> AnyElement + 'static,
+    ) -> Self {
+        let inner = Rc::new_cyclic(|this: &Weak<RefCell<TableStateInner>>| {
+            let this = this.clone();
+            let list = ListState::new(row_count, ListAlignment::Top, px(100.), move |ix, cx| {
+                let Some(state) = this.upgrade().map(TableState) else {
+                    return div().into_any_element();
+                };
+                let (row, widths, selected, active) = {
+                    let state = state.0.borrow();
+                    let row = state.data_row(ix);
+                    (
+                        row,
+                        state
+                            .columns
+                            .iter()
+                            .map(|column| column.width)
+                            .collect::<Vec<_>>(),
+                        state.selection.is_selected(row),
+                        state.selection.cursor() == Some(row),
+                    )
+                };
+
+                let cells = widths
+                    .into_iter()
+                    .enumerate()
+                    .map(|(column, width)| {
+                        let cell = TableCell {
+                            row,
+                            display_row: ix,
+                            column,
+                            selected,
+                            active,
+                        };
+                        div()
+                            .flex_none()
+                            .w(width)
+                            .overflow_hidden()
+                            .child(render_cell(cell, cx))
+                    })
+                    .collect::<Vec<_>>();
+                div()
+                    .flex()
+                    .on_mouse_down(MouseButton::Left, move |event, cx| {
+                        state.handle_mouse_down(row, event, cx)
+                    })
+                    .children(cells)
+                    .into_any_element()
+            });
+
+            RefCell::new(TableStateInner {
+                list,
+                columns,
+                row_count,
+                order: None,
+                positions: Vec::new(),
+                sort: None,
+                selection: RowSelection::default(),
+                focus_handle: None,
+                comparator: None,
+                render_header: None,
+            })
+        });
+        Self(inner)
+    }
+
+    /// Render the header of each column with the given function, instead of its name.
+    pub fn with_header(
+        self,
+        render_header: impl Fn(usize, Option<SortDirection>, &mut WindowContext) -> AnyElement + 'static,
+    ) -> Self {
+        self.0.borrow_mut().render_header = Some(Rc::new(render_header));
+        self
+    }
+
+    /// Sort the rows with the given function, which compares two data rows by a column.
+    /// Tables can't be sorted without a comparator.
+    pub fn with_comparator(
+        self,
+        comparator: impl Fn(usize, usize, usize) -> Ordering + 'static,
+    ) -> Self {
+        self.0.borrow_mut().comparator = Some(Rc::new(comparator));
+        self
+    }
+
+    /// The number of rows in the table.
+    pub fn row_count(&self) -> usize {
+        self.0.borrow().row_count
+    }
+
+    /// Replace the rows of the table, keeping its sort order and scroll position.
+    pub fn set_row_count(&self, row_count: usize) {
+        let old_count = {
+            let state = &mut *self.0.borrow_mut();
+            let old_count = state.row_count;
+            state.row_count = row_count;
+            state.selection.retain(|row| row < row_count);
+            old_count
+        };
+        self.sort_by_column(self.sort());
+        self.0.borrow().list.splice(0..old_count, row_count);
+    }
+
+    /// The current width of a column.
+    pub fn column_width(&self, column: usize) -> Pixels {
+        self.0.borrow().columns[column].width
+    }
+
+    /// Resize a column, respecting its minimum width.
+    pub fn set_column_width(&self, column: usize, width: Pixels) {
+        let state = &mut *self.0.borrow_mut();
+        let column = &mut state.columns[column];
+        column.width = width.max(column.min_width);
+    }
+
+    /// The column and direction by which the table is sorted.
+    pub fn sort(&self) -> Option<(usize, SortDirection)> {
+        self.0.borrow().sort
+    }
+
+    /// Sort the table by the given column, or restore the order of the data when the
+    /// direction is `None`.
+    pub fn sort_by(&self, column: usize, direction: Option<SortDirection>) {
+        self.sort_by_column(direction.map(|direction| (column, direction)));
+        let row_count = self.row_count();
+        // Rows keep their heights when sorting, but they're measured by position.
+        self.0.borrow().list.splice(0..row_count, row_count);
+    }
+
+    fn sort_by_column(&self, sort: Option<(usize, SortDirection)>) {
+        let state = &mut *self.0.borrow_mut();
+        let comparator = state.comparator.clone();
+        let (Some((column, direction)), Some(comparator)) = (sort, comparator) else {
+            state.sort = None;
+            state.order = None;
+            state.positions.clear();
+            return;
+        };
+
+        let mut order = (0..state.row_count).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let ordering = comparator(column, *a, *b);
+            match direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+        state.positions.clear();
+        state.positions.resize(order.len(), 0);
+        for (position, row) in order.iter().enumerate() {
+            state.positions[*row] = position;
+        }
+        state.order = Some(order);
+        state.sort = sort;
+    }
+
+    /// The data row displayed at the given position.
+    pub fn data_row(&self, display_row: usize) -> usize {
+        self.0.borrow().data_row(display_row)
+    }
+
+    /// The position at which the given data row is displayed.
+    pub fn display_row(&self, row: usize) -> usize {
+        self.0.borrow().display_row(row)
+    }
+
+    /// The selected data rows, in the order they're displayed.
+    pub fn selected_rows(&self) -> Vec<usize> {
+        let state = self.0.borrow();
+        let mut rows = (0..state.row_count)
+            .filter(|row| state.selection.is_selected(*row))
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|row| state.display_row(*row));
+        rows
+    }
+
+    /// The data row that keyboard navigation moves from, which was clicked or navigated to last.
+    pub fn active_row(&self) -> Option<usize> {
+        self.0.borrow().selection.cursor()
+    }
+
+    /// Select only the given data row.
+    pub fn select_row(&self, row: usize) {
+        self.0.borrow_mut().selection.select(row);
+    }
+
+    /// Scroll the table so that the given data row is fully visible.
+    pub fn scroll_to_reveal_row(&self, row: usize) {
+        let state = self.0.borrow();
+        state.list.scroll_to_reveal_item(state.display_row(row));
+    }
+
+    fn render_header(&self, cx: &mut WindowContext) -> impl IntoElement {
+        let (columns, sort, render_header, can_sort) = {
+            let state = self.0.borrow();
+            (
+                state.columns.clone(),
+                state.sort,
+                state.render_header.clone(),
+                state.comparator.is_some(),
+            )
+        };
+
+        let cells = columns
+            .into_iter()
+            .enumerate()
+            .map(|(ix, column)| {
+                let column_sort = sort.filter(|(sorted, _)| *sorted == ix).map(|(_, dir)| dir);
+                let content = match &render_header {
+                    Some(render_header) => render_header(ix, column_sort, cx),
+                    None => column.name.clone().into_any_element(),
+                };
+
+                let mut cell = div()
+                    .id(("column", ix))
+                    .flex_none()
+                    .w(column.width - RESIZE_HANDLE_WIDTH)
+                    .overflow_hidden()
+                    .child(content);
+                if column.sortable && can_sort {
+                    let state = self.clone();
+                    cell = cell.cursor_pointer().on_click(move |_, cx| {
+                        let direction = match column_sort {
+                            None => Some(SortDirection::Ascending),
+                            Some(SortDirection::Ascending) => Some(SortDirection::Descending),
+                            Some(SortDirection::Descending) => None,
+                        };
+                        state.sort_by(ix, direction);
+                        cx.refresh();
+                    });
+                }
+
+                let mut handle = div()
+                    .id(("column-resize-handle", ix))
+                    .flex_none()
+                    .h_full()
+                    .w(RESIZE_HANDLE_WIDTH);
+                if column.resizable {
+                    handle = handle.cursor_col_resize().on_drag(
+                        DraggedColumn {
+                            table: self.clone(),
+                            column: ix,
+                        },
+                        |_, _, cx| cx.new_view(|_| EmptyView),
+                    );
+                }
+
+                div().flex().flex_none().child(cell).child(handle)
+            })
+            .collect::<Vec<_>>();
+
+        let state = self.clone();
+        div()
+            .flex()
+            .on_drag_move::<DraggedColumn>(move |event, cx| {
+                let drag = event.drag(cx);
+                if !Rc::ptr_eq(&drag.table.0, &state.0) {
+                    return;
+                }
+                let column = drag.column;
+                let left = event.bounds.left()
+                    + (0..column)
+                        .map(|column| state.column_width(column))
+                        .fold(px(0.), |sum, width| sum + width);
+                state.set_column_width(column, event.event.position.x - left);
+                cx.refresh();
+            })
+            .children(cells)
+    }
+
+    fn handle_mouse_down(&self, row: usize, event: &MouseDownEvent, cx: &mut WindowContext) {
+        let focus_handle = self.0.borrow().focus_handle.clone();
+        if let Some(focus_handle) = focus_handle {
+            cx.focus(&focus_handle);
+        }
+
+        let state = &mut *self.0.borrow_mut();
+        let ix = state.display_row(row);
+        let order = state.order.as_deref();
+        let positions = &state.positions;
+        state.selection.click(
+            ix,
+            event.modifiers,
+            |row| Some(positions.get(row).copied().unwrap_or(row)),
+            |ix| order.map_or(ix, |order| order[ix]),
+        );
+        cx.refresh();
+    }
+
+    fn handle_keystroke(&self, keystroke: &Keystroke, cx: &mut WindowContext) -> bool {
+        let Some((navigation, extend)) = RowNavigation::from_keystroke(keystroke) else {
+            return false;
+        };
+        if matches!(navigation, RowNavigation::Left | RowNavigation::Right) {
+            return false;
+        }
+
+        let state = &mut *self.0.borrow_mut();
+        let page_size = state.list.visible_range().len();
+        let order = state.order.as_deref();
+        let positions = &state.positions;
+        if let Some(ix) = state.selection.navigate(
+            navigation,
+            extend,
+            state.row_count,
+            page_size,
+            |row| Some(positions.get(row).copied().unwrap_or(row)),
+            |ix| order.map_or(ix, |order| order[ix]),
+        ) {
+            state.list.scroll_to_reveal_item(ix);
+        }
+        cx.refresh();
+        true
+    }
+}
+
+impl TableStateInner {
+    fn data_row(&self, display_row: usize) -> usize {
+        self.order
+            .as_ref()
+            .map_or(display_row, |order| order[display_row])
+    }
+
+    fn display_row(&self, row: usize) -> usize {
+        self.positions.get(row).copied().unwrap_or(row)
+    }
+
+    fn total_width(&self) -> Pixels {
+        self.columns
+            .iter()
+            .fold(px(0.), |sum, column| sum + column.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, point, Modifiers, Render, TestAppContext, ViewContext, VisualTestContext,
+    };
+    use std::cell::Cell;
+
+    struct Spreadsheet {
+        table: TableState,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for Spreadsheet {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            table("spreadsheet", self.table.clone())
+                .track_focus(&self.focus_handle)
+                .size_full()
+        }
+    }
+
+    const ROW_HEIGHT: f32 = 20.;
+
+    /// Builds a table whose second column holds the data row's index modulo 7, counting how
+    /// many cells are rendered.
+    fn build_table(
+        row_count: usize,
+        cx: &mut TestAppContext,
+    ) -> (TableState, Rc<Cell<usize>>, &mut VisualTestContext) {
+        let rendered_cells = Rc::new(Cell::new(0));
+        let value = |row: usize, column: usize| if column == 0 { row } else { row % 7 };
+        let table_state = TableState::new(
+            vec![
+                TableColumn::new("Row", px(60.)),
+                TableColumn::new("Remainder", px(100.)).min_width(px(40.)),
+            ],
+            row_count,
+            {
+                let rendered_cells = rendered_cells.clone();
+                move |cell, _| {
+                    rendered_cells.set(rendered_cells.get() + 1);
+                    div()
+                        .h(px(ROW_HEIGHT))
+                        .child(value(cell.row, cell.column).to_string())
+                        .into_any_element()
+                }
+            },
+        )
+        .with_header(|column, _, _| {
+            div()
+                .h(px(ROW_HEIGHT))
+                .child(column.to_string())
+                .into_any_element()
+        })
+        .with_comparator(move |column, a, b| value(a, column).cmp(&value(b, column)));
+
+        let (_, cx) = cx.add_window_view({
+            let table_state = table_state.clone();
+            |cx| {
+                let focus_handle = cx.focus_handle();
+                cx.focus(&focus_handle);
+                Spreadsheet {
+                    table: table_state,
+                    focus_handle,
+                }
+            }
+        });
+        (table_state, rendered_cells, cx)
+    }
+
+    #[gpui::test]
+    fn test_table_sorting(cx: &mut TestAppContext) {
+        let (table, _, cx) = build_table(10, cx);
+
+        table.sort_by(1, Some(SortDirection::Ascending));
+        let displayed = (0..10).map(|ix| table.data_row(ix)).collect::<Vec<_>>();
+        assert_eq!(displayed, [0, 7, 1, 8, 2, 9, 3, 4, 5, 6]);
+        assert_eq!(table.display_row(8), 3);
+
+        // Rows with equal values keep their relative order in both directions.
+        table.sort_by(1, Some(SortDirection::Descending));
+        let displayed = (0..10).map(|ix| table.data_row(ix)).collect::<Vec<_>>();
+        assert_eq!(displayed, [6, 5, 4, 3, 2, 9, 1, 8, 0, 7]);
+
+        table.set_row_count(12);
+        assert_eq!(table.data_row(0), 6);
+        assert_eq!(table.display_row(11), 3);
+
+        table.sort_by(1, None);
+        assert_eq!(table.sort(), None);
+        assert_eq!(table.data_row(3), 3);
+
+        // Clicking a column header cycles through the sort directions.
+        cx.run_until_parked();
+        let header = point(px(10.), px(5.));
+        cx.simulate_click(header, Modifiers::none());
+        assert_eq!(table.sort(), Some((0, SortDirection::Ascending)));
+        cx.simulate_click(header, Modifiers::none());
+        assert_eq!(table.sort(), Some((0, SortDirection::Descending)));
+        cx.simulate_click(header, Modifiers::none());
+        assert_eq!(table.sort(), None);
+
+        table.set_column_width(1, px(10.));
+        assert_eq!(table.column_width(1), px(40.));
+    }
+
+    #[gpui::test]
+    fn test_table_selection(cx: &mut TestAppContext) {
+        let (table, _, cx) = build_table(10, cx);
+        table.sort_by(1, Some(SortDirection::Ascending));
+        cx.run_until_parked();
+
+        // The header is as tall as a row.
+        let row_position = |ix: usize| point(px(10.), px(ROW_HEIGHT * (ix + 1) as f32 + 5.));
+        cx.simulate_click(row_position(1), Modifiers::none());
+        assert_eq!(table.active_row(), Some(7));
+
+        cx.simulate_keystrokes("shift-down shift-down");
+        assert_eq!(table.selected_rows(), [7, 1, 8]);
+
+        cx.simulate_click(row_position(5), Modifiers::secondary_key());
+        assert_eq!(table.selected_rows(), [7, 1, 8, 9]);
+
+        cx.simulate_keystrokes("end");
+        assert_eq!(table.selected_rows(), [6]);
+
+        table.set_row_count(5);
+        assert_eq!(table.selected_rows(), Vec::<usize>::new());
+        assert_eq!(table.active_row(), None);
+    }
+
+    #[gpui::test]
+    fn test_table_renders_visible_rows(cx: &mut TestAppContext) {
+        let (table, rendered_cells, cx) = build_table(100_000, cx);
+        cx.run_until_parked();
+
+        let rendered_rows = rendered_cells.get() / 2;
+        assert!(rendered_rows > 0);
+        assert!(rendered_rows < 200, "rendered {rendered_rows} rows");
+
+        table.sort_by(1, Some(SortDirection::Descending));
+        rendered_cells.set(0);
+        cx.simulate_keystrokes("end");
+        cx.run_until_parked();
+        assert_eq!(table.active_row(), Some(table.data_row(99_999)));
+        assert!(rendered_cells.get() / 2 < 200);
+    }
+}
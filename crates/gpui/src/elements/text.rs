@@ -52,7 +52,7 @@ impl Element for &'static str {
         _: &mut (),
         cx: &mut WindowContext,
     ) {
-        text_layout.paint(self, cx)
+        text_layout.paint(SharedString::from(*self), cx)
     }
 }
 
@@ -110,7 +110,7 @@ impl Element for SharedString {
         _: &mut Self::PrepaintState,
         cx: &mut WindowContext,
     ) {
-        text_layout.paint(self.as_ref(), cx)
+        text_layout.paint(self.clone(), cx)
     }
 }
 
@@ -220,7 +220,7 @@ impl Element for StyledText {
         _: &mut Self::PrepaintState,
         cx: &mut WindowContext,
     ) {
-        self.layout.paint(&self.text, cx)
+        self.layout.paint(self.text.clone(), cx)
     }
 }
 
@@ -359,7 +359,7 @@ impl TextLayout {
         element_state.bounds = Some(bounds);
     }
 
-    fn paint(&self, text: &str, cx: &mut WindowContext) {
+    fn paint(&self, text: SharedString, cx: &mut WindowContext) {
         let element_state = self.lock();
         let element_state = element_state
             .as_ref()
@@ -370,6 +370,8 @@ impl TextLayout {
             .ok_or_else(|| anyhow!("prepaint has not been performed on {:?}", text))
             .unwrap();
 
+        cx.paint_accessibility_text(text, bounds);
+
         let line_height = element_state.line_height;
         let mut line_origin = bounds.origin;
         for line in &element_state.lines {
@@ -0,0 +1,636 @@
+//! A tree element that renders only the rows that are visible, so it can display very large
+//! hierarchies. Nodes are identified by a [`TreeNodeId`] chosen by the caller, and the children
+//! of a node are requested when it is first expanded. Rows can have different heights, and the
+//! tree supports multiple selection along with keyboard navigation when it's focused.
+//!
+//! Like [`ListState`], the state of the tree is stored on your own view, so you can
+//! coordinate directly with it.
+
+use super::row_selection::{RowNavigation, RowSelection};
+use crate::{
+    div, list, px, AnyElement, Div, FocusHandle, InteractiveElement, IntoElement, Keystroke,
+    ListAlignment, ListState, MouseButton, MouseDownEvent, ParentElement, StyleRefinement, Styled,
+    WindowContext,
+};
+use collections::{FxHashMap, FxHashSet};
+use refineable::Refineable as _;
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// Identifies a node in a [`Tree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TreeNodeId(pub u64);
+
+impl From<u64> for TreeNodeId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<usize> for TreeNodeId {
+    fn from(id: usize) -> Self {
+        Self(id as u64)
+    }
+}
+
+/// A node in a [`Tree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeNode {
+    /// The identifier of the node.
+    pub id: TreeNodeId,
+    /// Whether the node may have children, which are loaded when it's first expanded.
+    pub expandable: bool,
+}
+
+impl TreeNode {
+    /// A node without children.
+    pub fn leaf(id: impl Into<TreeNodeId>) -> Self {
+        Self {
+            id: id.into(),
+            expandable: false,
+        }
+    }
+
+    /// A node whose children are loaded when it's expanded.
+    pub fn expandable(id: impl Into<TreeNodeId>) -> Self {
+        Self {
+            id: id.into(),
+            expandable: true,
+        }
+    }
+}
+
+/// A visible row of a [`Tree`], describing what the row's element should render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeRow {
+    /// The position of the row among the visible rows.
+    pub ix: usize,
+    /// The node displayed in this row.
+    pub id: TreeNodeId,
+    /// How many ancestors the node has.
+    pub depth: usize,
+    /// Whether the node may have children.
+    pub expandable: bool,
+    /// Whether the node's children are shown.
+    pub expanded: bool,
+    /// Whether the row is selected.
+    pub selected: bool,
+    /// Whether the row is the one keyboard navigation moves from.
+    pub active: bool,
+}
+
+/// Construct a new tree element.
+pub fn tree(state: TreeState) -> Tree {
+    Tree {
+        state,
+        focus_handle: None,
+        style: StyleRefinement::default(),
+    }
+}
+
+/// A tree element, see the [module documentation](self) for more.
+pub struct Tree {
+    state: TreeState,
+    focus_handle: Option<FocusHandle>,
+    style: StyleRefinement,
+}
+
+impl Tree {
+    /// Track the focus of the tree with the given handle. Rows are navigated with the
+    /// keyboard while it's focused, and clicking a row focuses it.
+    pub fn track_focus(mut self, focus_handle: &FocusHandle) -> Self {
+        self.focus_handle = Some(focus_handle.clone());
+        self
+    }
+}
+
+impl Styled for Tree {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl IntoElement for Tree {
+    type Element = Div;
+
+    fn into_element(self) -> Self::Element {
+        let state = self.state.clone();
+        let mut container = div().flex().flex_col().on_key_down(move |event, cx| {
+            if state.handle_keystroke(&event.keystroke, cx) {
+                cx.stop_propagation();
+            }
+        });
+        container.style().refine(&self.style);
+        if let Some(focus_handle) = self.focus_handle {
+            let interactivity = container.interactivity();
+            interactivity.focusable = true;
+            interactivity.tracked_focus_handle = Some(focus_handle.clone());
+            self.state.0.borrow_mut().focus_handle = Some(focus_handle);
+        }
+
+        let list_state = self.state.0.borrow().list.clone();
+        container.child(list(list_state).size_full())
+    }
+}
+
+/// The tree state that views must hold on behalf of the tree element.
+#[derive(Clone)]
+pub struct TreeState(Rc<RefCell<TreeStateInner>>);
+
+struct TreeStateInner {
+    list: ListState,
+    roots: Vec<TreeNode>,
+    children: FxHashMap<TreeNodeId, Vec<TreeNode>>,
+    expanded: FxHashSet<TreeNodeId>,
+    rows: Vec<VisibleRow>,
+    selection: RowSelection<TreeNodeId>,
+    focus_handle: Option<FocusHandle>,
+    #[allow(clippy::type_complexity)]
+    load_children: Option<Box<dyn FnMut(TreeNodeId, &mut WindowContext) -> Vec<TreeNode>>>,
+}
+
+#[derive(Clone, Copy)]
+struct VisibleRow {
+    node: TreeNode,
+    depth: usize,
+}
+
+impl TreeState {
+    /// Construct a new tree state, for storage on a view.
+    ///
+    /// `load_children` is called the first time a node is expanded, and `render_row` is called
+    /// for each row as it scrolls into view.
+    pub fn new(
+        roots: Vec<TreeNode>,
+        load_children: impl FnMut(TreeNodeId, &mut WindowContext) -> Vec<TreeNode> + 'static,
+        mut render_row: impl FnMut(TreeRow, &mut WindowContext) -> AnyElement + 'static,
+    ) -> Self {
+        let inner = Rc::new_cyclic(|this: &Weak<RefCell<TreeStateInner>>| {
+            let this = this.clone();
+            let list = ListState::new(0, ListAlignment::Top, px(100.), move |ix, cx| {
+                let Some(state) = this.upgrade().map(TreeState) else {
+                    return div().into_any_element();
+                };
+                let Some(row) = state.row(ix) else {
+                    return div().into_any_element();
+                };
+                let element = render_row(row, cx);
+                div()
+                    .on_mouse_down(MouseButton::Left, move |event, cx| {
+                        state.handle_mouse_down(row.id, event, cx)
+                    })
+                    .child(element)
+                    .into_any_element()
+            });
+
+            RefCell::new(TreeStateInner {
+                list,
+                roots: Vec::new(),
+                children: FxHashMap::default(),
+                expanded: FxHashSet::default(),
+                rows: Vec::new(),
+                selection: RowSelection::default(),
+                focus_handle: None,
+                load_children: Some(Box::new(load_children)),
+            })
+        });
+
+        let this = Self(inner);
+        this.set_roots(roots);
+        this
+    }
+
+    /// Replace the top-level nodes of the tree. Expanded nodes that are still present stay
+    /// expanded.
+    pub fn set_roots(&self, roots: Vec<TreeNode>) {
+        let state = &mut *self.0.borrow_mut();
+        state.roots = roots;
+        let mut rows = Vec::new();
+        state.flatten(&state.roots, 0, &mut rows);
+        state.rows = rows;
+        state.list.reset(state.rows.len());
+        state.retain_visible_selection();
+    }
+
+    /// Replace the children of a node, updating the tree if they're displayed.
+    pub fn set_children(&self, id: TreeNodeId, children: Vec<TreeNode>) {
+        let state = &mut *self.0.borrow_mut();
+        state.children.insert(id, children);
+        if state.expanded.contains(&id) {
+            state.refresh_descendants(id);
+        }
+    }
+
+    /// Forget the children of a node and collapse it, so they're loaded again when it's next
+    /// expanded.
+    pub fn invalidate_children(&self, id: TreeNodeId) {
+        self.collapse(id);
+        self.0.borrow_mut().children.remove(&id);
+    }
+
+    /// Show the children of a node, loading them if they haven't been loaded yet.
+    pub fn expand(&self, id: TreeNodeId, cx: &mut WindowContext) {
+        let needs_children = {
+            let state = &mut *self.0.borrow_mut();
+            if !state.expanded.insert(id) {
+                return;
+            }
+            !state.children.contains_key(&id)
+        };
+
+        if needs_children {
+            let load_children = self.0.borrow_mut().load_children.take();
+            if let Some(mut load_children) = load_children {
+                let children = load_children(id, cx);
+                let state = &mut *self.0.borrow_mut();
+                state.load_children = Some(load_children);
+                state.children.entry(id).or_insert(children);
+            }
+        }
+
+        self.0.borrow_mut().refresh_descendants(id);
+    }
+
+    /// Hide the children of a node.
+    pub fn collapse(&self, id: TreeNodeId) {
+        let state = &mut *self.0.borrow_mut();
+        if state.expanded.remove(&id) {
+            let cursor_hidden = state.selection.cursor().is_some_and(|cursor| {
+                state
+                    .position(cursor)
+                    .zip(state.position(id))
+                    .is_some_and(|(cursor_ix, ix)| {
+                        cursor_ix > ix && state.rows[cursor_ix].depth > state.rows[ix].depth
+                    })
+            });
+            state.refresh_descendants(id);
+            if cursor_hidden {
+                state.selection.select(id);
+            }
+        }
+    }
+
+    /// Expand a collapsed node, or collapse an expanded one.
+    pub fn toggle_expanded(&self, id: TreeNodeId, cx: &mut WindowContext) {
+        if self.is_expanded(id) {
+            self.collapse(id);
+        } else {
+            self.expand(id, cx);
+        }
+    }
+
+    /// Whether the children of the given node are shown.
+    pub fn is_expanded(&self, id: TreeNodeId) -> bool {
+        self.0.borrow().expanded.contains(&id)
+    }
+
+    /// The number of visible rows.
+    pub fn row_count(&self) -> usize {
+        self.0.borrow().rows.len()
+    }
+
+    /// The visible row at the given position.
+    pub fn row(&self, ix: usize) -> Option<TreeRow> {
+        let state = self.0.borrow();
+        let row = state.rows.get(ix)?;
+        Some(TreeRow {
+            ix,
+            id: row.node.id,
+            depth: row.depth,
+            expandable: row.node.expandable,
+            expanded: state.expanded.contains(&row.node.id),
+            selected: state.selection.is_selected(row.node.id),
+            active: state.selection.cursor() == Some(row.node.id),
+        })
+    }
+
+    /// The selected nodes, in the order they're displayed.
+    pub fn selected_nodes(&self) -> Vec<TreeNodeId> {
+        let state = self.0.borrow();
+        state
+            .rows
+            .iter()
+            .map(|row| row.node.id)
+            .filter(|id| state.selection.is_selected(*id))
+            .collect()
+    }
+
+    /// The node that keyboard navigation moves from, which was clicked or navigated to last.
+    pub fn active_node(&self) -> Option<TreeNodeId> {
+        self.0.borrow().selection.cursor()
+    }
+
+    /// Select only the given node.
+    pub fn select(&self, id: TreeNodeId) {
+        self.0.borrow_mut().selection.select(id);
+    }
+
+    /// Scroll the tree so that the given node is fully visible, if it's displayed.
+    pub fn scroll_to_reveal(&self, id: TreeNodeId) {
+        let state = self.0.borrow();
+        if let Some(ix) = state.position(id) {
+            state.list.scroll_to_reveal_item(ix);
+        }
+    }
+
+    fn handle_mouse_down(&self, id: TreeNodeId, event: &MouseDownEvent, cx: &mut WindowContext) {
+        let focus_handle = self.0.borrow().focus_handle.clone();
+        if let Some(focus_handle) = focus_handle {
+            cx.focus(&focus_handle);
+        }
+
+        let expandable = {
+            let state = &mut *self.0.borrow_mut();
+            let Some(ix) = state.position(id) else {
+                return;
+            };
+            let rows = &state.rows;
+            state.selection.click(
+                ix,
+                event.modifiers,
+                |id| rows.iter().position(|row| row.node.id == id),
+                |ix| rows[ix].node.id,
+            );
+            rows[ix].node.expandable
+        };
+        if event.click_count == 2 && expandable {
+            self.toggle_expanded(id, cx);
+        }
+        cx.refresh();
+    }
+
+    fn handle_keystroke(&self, keystroke: &Keystroke, cx: &mut WindowContext) -> bool {
+        let Some((navigation, extend)) = RowNavigation::from_keystroke(keystroke) else {
+            return false;
+        };
+
+        let active = {
+            let state = self.0.borrow();
+            state
+                .selection
+                .cursor()
+                .and_then(|id| Some((id, state.position(id)?)))
+        };
+        match (navigation, active) {
+            (RowNavigation::Left, Some((id, ix))) => {
+                if self.is_expanded(id) {
+                    self.collapse(id);
+                } else {
+                    let parent = {
+                        let state = self.0.borrow();
+                        let depth = state.rows[ix].depth;
+                        state.rows[..ix]
+                            .iter()
+                            .rev()
+                            .find(|row| row.depth < depth)
+                            .map(|row| row.node.id)
+                    };
+                    if let Some(parent) = parent {
+                        self.select(parent);
+                        self.scroll_to_reveal(parent);
+                    }
+                }
+            }
+            (RowNavigation::Right, Some((id, ix))) => {
+                let row = self.row(ix);
+                if row.is_some_and(|row| row.expandable && !row.expanded) {
+                    self.expand(id, cx);
+                } else if let Some(child) = self
+                    .row(ix + 1)
+                    .filter(|child| row.is_some_and(|row| row.expanded && child.depth > row.depth))
+                {
+                    self.select(child.id);
+                    self.scroll_to_reveal(child.id);
+                }
+            }
+            (RowNavigation::Left | RowNavigation::Right, None) => return false,
+            _ => {
+                let state = &mut *self.0.borrow_mut();
+                let page_size = state.list.visible_range().len();
+                let rows = &state.rows;
+                if let Some(ix) = state.selection.navigate(
+                    navigation,
+                    extend,
+                    rows.len(),
+                    page_size,
+                    |id| rows.iter().position(|row| row.node.id == id),
+                    |ix| rows[ix].node.id,
+                ) {
+                    state.list.scroll_to_reveal_item(ix);
+                }
+            }
+        }
+
+        cx.refresh();
+        true
+    }
+}
+
+impl TreeStateInner {
+    fn flatten(&self, nodes: &[TreeNode], depth: usize, rows: &mut Vec<VisibleRow>) {
+        for node in nodes {
+            rows.push(VisibleRow { node: *node, depth });
+            if self.expanded.contains(&node.id) {
+                if let Some(children) = self.children.get(&node.id) {
+                    self.flatten(children, depth + 1, rows);
+                }
+            }
+        }
+    }
+
+    fn position(&self, id: TreeNodeId) -> Option<usize> {
+        self.rows.iter().position(|row| row.node.id == id)
+    }
+
+    /// Rebuilds the rows below a node after its children or expansion changed.
+    fn refresh_descendants(&mut self, id: TreeNodeId) {
+        let Some(ix) = self.position(id) else {
+            return;
+        };
+        let depth = self.rows[ix].depth;
+        let old_end = self.rows[ix + 1..]
+            .iter()
+            .position(|row| row.depth <= depth)
+            .map_or(self.rows.len(), |end| ix + 1 + end);
+
+        let mut descendants = Vec::new();
+        if self.expanded.contains(&id) {
+            if let Some(children) = self.children.get(&id) {
+                self.flatten(children, depth + 1, &mut descendants);
+            }
+        }
+        let new_count = descendants.len();
+        self.rows.splice(ix + 1..old_end, descendants);
+        self.list.splice(ix + 1..old_end, new_count);
+        self.retain_visible_selection();
+    }
+
+    fn retain_visible_selection(&mut self) {
+        let visible = self
+            .rows
+            .iter()
+            .map(|row| row.node.id)
+            .collect::<FxHashSet<_>>();
+        self.selection.retain(|id| visible.contains(&id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as gpui, Render, TestAppContext, ViewContext, VisualTestContext};
+
+    struct Outline {
+        tree: TreeState,
+        focus_handle: FocusHandle,
+    }
+
+    impl Render for Outline {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            tree(self.tree.clone())
+                .track_focus(&self.focus_handle)
+                .size_full()
+        }
+    }
+
+    fn build_outline(cx: &mut TestAppContext) -> (TreeState, &mut VisualTestContext) {
+        // Every node has three children, whose ids are the parent's id followed by a digit.
+        let tree_state = TreeState::new(
+            vec![TreeNode::expandable(1_u64), TreeNode::expandable(2_u64)],
+            |id, _| {
+                (1..=3)
+                    .map(|digit| TreeNode::expandable(id.0 * 10 + digit))
+                    .collect()
+            },
+            |row, _| {
+                div()
+                    .h(px(20.))
+                    .child(format!("{}", row.id.0))
+                    .into_any_element()
+            },
+        );
+        let (_, cx) = cx.add_window_view({
+            let tree_state = tree_state.clone();
+            |cx| {
+                let focus_handle = cx.focus_handle();
+                cx.focus(&focus_handle);
+                Outline {
+                    tree: tree_state,
+                    focus_handle,
+                }
+            }
+        });
+        (tree_state, cx)
+    }
+
+    fn visible_rows(tree: &TreeState) -> Vec<String> {
+        (0..tree.row_count())
+            .map(|ix| {
+                let row = tree.row(ix).unwrap();
+                format!(
+                    "{}{}{}",
+                    "  ".repeat(row.depth),
+                    row.id.0,
+                    if row.selected { " *" } else { "" }
+                )
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_tree_expansion(cx: &mut TestAppContext) {
+        let (tree, cx) = build_outline(cx);
+        assert_eq!(visible_rows(&tree), ["1", "2"]);
+
+        cx.update(|cx| {
+            tree.expand(TreeNodeId(1), cx);
+            tree.expand(TreeNodeId(12), cx);
+        });
+        assert_eq!(
+            visible_rows(&tree),
+            ["1", "  11", "  12", "    121", "    122", "    123", "  13", "2"]
+        );
+
+        // Collapsing a node remembers which of its descendants were expanded.
+        tree.collapse(TreeNodeId(1));
+        assert_eq!(visible_rows(&tree), ["1", "2"]);
+        cx.update(|cx| tree.expand(TreeNodeId(1), cx));
+        assert_eq!(tree.row_count(), 8);
+
+        tree.set_children(TreeNodeId(12), vec![TreeNode::leaf(129_u64)]);
+        assert_eq!(
+            visible_rows(&tree),
+            ["1", "  11", "  12", "    129", "  13", "2"]
+        );
+
+        tree.invalidate_children(TreeNodeId(12));
+        assert_eq!(visible_rows(&tree), ["1", "  11", "  12", "  13", "2"]);
+        cx.update(|cx| tree.expand(TreeNodeId(12), cx));
+        assert_eq!(tree.row_count(), 8);
+    }
+
+    #[gpui::test]
+    fn test_tree_keyboard_navigation(cx: &mut TestAppContext) {
+        let (tree, cx) = build_outline(cx);
+        cx.run_until_parked();
+
+        cx.simulate_keystrokes("down right right");
+        assert_eq!(visible_rows(&tree), ["1", "  11 *", "  12", "  13", "2"]);
+
+        cx.simulate_keystrokes("right right shift-down");
+        assert_eq!(
+            visible_rows(&tree),
+            [
+                "1",
+                "  11",
+                "    111 *",
+                "    112 *",
+                "    113",
+                "  12",
+                "  13",
+                "2"
+            ]
+        );
+
+        cx.simulate_keystrokes("left left");
+        assert_eq!(visible_rows(&tree), ["1", "  11 *", "  12", "  13", "2"]);
+
+        cx.simulate_keystrokes("left");
+        assert_eq!(visible_rows(&tree), ["1 *", "  11", "  12", "  13", "2"]);
+
+        cx.simulate_keystrokes("left end");
+        assert_eq!(visible_rows(&tree), ["1", "2 *"]);
+        assert_eq!(tree.selected_nodes(), [TreeNodeId(2)]);
+    }
+
+    #[gpui::test]
+    fn test_tree_mouse_selection(cx: &mut TestAppContext) {
+        let (tree, cx) = build_outline(cx);
+        cx.update(|cx| tree.expand(TreeNodeId(1), cx));
+        cx.run_until_parked();
+
+        let row_position = |ix: usize| crate::point(px(10.), px(20. * ix as f32 + 10.));
+        cx.simulate_click(row_position(1), crate::Modifiers::none());
+        cx.simulate_click(
+            row_position(3),
+            crate::Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            tree.selected_nodes(),
+            [TreeNodeId(11), TreeNodeId(12), TreeNodeId(13)]
+        );
+
+        cx.simulate_click(row_position(2), crate::Modifiers::secondary_key());
+        assert_eq!(tree.selected_nodes(), [TreeNodeId(11), TreeNodeId(13)]);
+        assert_eq!(tree.active_node(), Some(TreeNodeId(12)));
+
+        // Collapsing a node deselects its hidden descendants.
+        tree.collapse(TreeNodeId(1));
+        assert_eq!(tree.selected_nodes(), [TreeNodeId(1)]);
+    }
+}
@@ -65,6 +65,7 @@
 #![allow(unused_mut)] // False positives in platform specific code
 
 #[macro_use]
+mod accessibility;
 mod action;
 mod app;
 
@@ -79,6 +80,7 @@ mod executor;
 mod geometry;
 mod global;
 mod input;
+mod inspector;
 mod interactive;
 mod key_dispatch;
 mod keymap;
@@ -114,6 +116,7 @@ mod seal {
     pub trait Sealed {}
 }
 
+pub use accessibility::*;
 pub use action::*;
 pub use anyhow::Result;
 pub use app::*;
@@ -130,6 +133,7 @@ pub use global::*;
 pub use gpui_macros::{register_action, test, IntoElement, Render};
 pub use http_client;
 pub use input::*;
+pub use inspector::*;
 pub use interactive::*;
 use key_dispatch::*;
 pub use keymap::*;
@@ -0,0 +1,635 @@
+//! An inspector for debugging the layout of a window. While it's open, every element backed
+//! by [`Interactivity`](crate::Interactivity) is recorded along with its bounds, computed
+//! [`Style`] and taffy layout, and a panel beside the window's contents lets you pick elements
+//! with the mouse, browse the element tree and tweak style values live.
+
+use crate::{
+    div, fill, hash, hsla, outline, prelude::*, px, AbsoluteLength, AnyElement, Bounds,
+    DefiniteLength, Display, Edges, ElementId, EntityId, FlexDirection, Hsla, Length, Pixels,
+    Point, SharedString, Size, Style, StyleRefinement, WindowContext,
+};
+use collections::FxHashMap;
+use std::{fmt::Debug, mem, panic::Location, path::Path};
+
+/// The width of the inspector panel shown beside the window's contents.
+pub(crate) const PANEL_WIDTH: Pixels = px(360.);
+
+/// Identifies an element shown in the inspector. It's derived from the element's position in
+/// the element id stack and the location it was constructed at, so it stays the same across
+/// frames as long as the shape of the element tree doesn't change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InspectorElementId(pub u64);
+
+/// An element recorded by the inspector while drawing a frame.
+#[derive(Clone, Debug)]
+pub struct InspectedElement {
+    /// The inspector's identifier for this element.
+    pub id: InspectorElementId,
+    /// How many inspected elements this one is nested in.
+    pub depth: usize,
+    /// Where the element was constructed. Only tracked in debug builds.
+    pub source_location: Option<Location<'static>>,
+    /// The name of the type of the view that rendered this element.
+    pub view_type: Option<&'static str>,
+    /// The id given to the element, if any.
+    pub element_id: Option<ElementId>,
+    /// The bounds of the element in the window.
+    pub bounds: Bounds<Pixels>,
+    /// The style the element was laid out and painted with.
+    pub style: Style,
+    /// The layout taffy computed for the element.
+    pub layout: InspectorLayout,
+}
+
+/// The layout taffy computed for an element.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InspectorLayout {
+    /// The order in which the element was laid out relative to its siblings.
+    pub order: u32,
+    /// The origin of the element, relative to its parent.
+    pub origin: Point<Pixels>,
+    /// The size of the element.
+    pub size: Size<Pixels>,
+}
+
+pub(crate) struct Inspector {
+    pub(crate) picking: bool,
+    pub(crate) hovered: Option<InspectorElementId>,
+    pub(crate) selected: Option<InspectorElementId>,
+    pub(crate) elements: Vec<InspectedElement>,
+    next_elements: Vec<InspectedElement>,
+    element_stack: Vec<usize>,
+    instance_counts: FxHashMap<u64, usize>,
+    recording: bool,
+    view_types: FxHashMap<EntityId, &'static str>,
+    style_overrides: FxHashMap<InspectorElementId, StyleRefinement>,
+}
+
+impl Inspector {
+    pub(crate) fn new() -> Self {
+        Self {
+            picking: true,
+            hovered: None,
+            selected: None,
+            elements: Vec::new(),
+            next_elements: Vec::new(),
+            element_stack: Vec::new(),
+            instance_counts: FxHashMap::default(),
+            recording: false,
+            view_types: FxHashMap::default(),
+            style_overrides: FxHashMap::default(),
+        }
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.next_elements.clear();
+        self.element_stack.clear();
+        self.instance_counts.clear();
+        self.recording = true;
+    }
+
+    /// Makes the elements recorded since [`Self::begin_frame`] the ones shown by the inspector.
+    /// Elements drawn afterwards, like the inspector panel itself, aren't recorded.
+    pub(crate) fn finish_frame(&mut self) {
+        self.elements = mem::take(&mut self.next_elements);
+        self.recording = false;
+        if self
+            .selected
+            .is_some_and(|selected| self.element(selected).is_none())
+        {
+            self.selected = None;
+        }
+    }
+
+    pub(crate) fn next_element_id(
+        &mut self,
+        element_id_stack: &[ElementId],
+        source_location: Option<Location<'static>>,
+    ) -> InspectorElementId {
+        let path = hash(&(element_id_stack, source_location));
+        let instance = self.instance_counts.entry(path).or_default();
+        let id = InspectorElementId(hash(&(path, *instance)));
+        *instance += 1;
+        id
+    }
+
+    pub(crate) fn push_element(&mut self, mut element: InspectedElement) {
+        element.depth = self.element_stack.len();
+        self.element_stack.push(self.next_elements.len());
+        self.next_elements.push(element);
+    }
+
+    pub(crate) fn pop_element(&mut self) {
+        self.element_stack.pop();
+    }
+
+    pub(crate) fn register_view(&mut self, view_id: EntityId, type_name: &'static str) {
+        self.view_types.insert(view_id, type_name);
+    }
+
+    pub(crate) fn view_type(&self, view_id: EntityId) -> Option<&'static str> {
+        self.view_types.get(&view_id).copied()
+    }
+
+    pub(crate) fn style_override(&self, id: InspectorElementId) -> Option<&StyleRefinement> {
+        self.style_overrides.get(&id)
+    }
+
+    pub(crate) fn override_style(&mut self, id: InspectorElementId, style: &StyleRefinement) {
+        self.style_overrides.entry(id).or_default().refine(style);
+    }
+
+    pub(crate) fn clear_style_overrides(&mut self, id: InspectorElementId) {
+        self.style_overrides.remove(&id);
+    }
+
+    pub(crate) fn element(&self, id: InspectorElementId) -> Option<&InspectedElement> {
+        self.elements.iter().find(|element| element.id == id)
+    }
+
+    /// Returns the innermost element drawn last at the given position.
+    pub(crate) fn element_at(&self, position: Point<Pixels>) -> Option<InspectorElementId> {
+        self.elements
+            .iter()
+            .rev()
+            .find(|element| element.bounds.contains(&position))
+            .map(|element| element.id)
+    }
+
+    /// Highlights the element hovered in the window or in the panel, or else the selected one.
+    pub(crate) fn paint_highlight(&self, cx: &mut WindowContext) {
+        let highlighted = self.hovered.or(self.selected);
+        let Some(element) = highlighted.and_then(|id| self.element(id)) else {
+            return;
+        };
+
+        let rem_size = cx.rem_size();
+        let bounds = element.bounds;
+        let padding = element
+            .style
+            .padding
+            .to_pixels(bounds.size.map(AbsoluteLength::Pixels), rem_size);
+        let border = element.style.border_widths.to_pixels(rem_size);
+        let content = Bounds::from_corners(
+            bounds.origin + Point::new(padding.left + border.left, padding.top + border.top),
+            bounds.lower_right()
+                - Point::new(padding.right + border.right, padding.bottom + border.bottom),
+        );
+
+        cx.paint_quad(fill(bounds, hsla(0.33, 0.6, 0.5, 0.25)));
+        cx.paint_quad(fill(content, hsla(0.58, 0.8, 0.6, 0.35)));
+        cx.paint_quad(outline(bounds, hsla(0.58, 0.8, 0.5, 1.)));
+    }
+
+    pub(crate) fn render_panel(&self, rem_size: Pixels) -> AnyElement {
+        let selected = self.selected.and_then(|id| self.element(id));
+
+        div()
+            .id("inspector")
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(background())
+            .border_l_1()
+            .border_color(border_color())
+            .text_color(text_color())
+            .text_xs()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .p_1()
+                    .border_b_1()
+                    .border_color(border_color())
+                    .child("Inspector")
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .gap_1()
+                            .child(
+                                button("inspector-pick", "Pick", |cx| {
+                                    cx.set_inspector_picking(true)
+                                })
+                                .when(self.picking, |this| this.bg(selection_color())),
+                            )
+                            .child(button("inspector-close", "Close", |cx| {
+                                cx.toggle_inspector()
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .id("inspector-elements")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .children(self.elements.iter().map(|element| {
+                        let id = element.id;
+                        div()
+                            .id(("inspector-element", id.0))
+                            .pl(px(4. + element.depth as f32 * 8.))
+                            .whitespace_nowrap()
+                            .cursor_pointer()
+                            .when(Some(id) == self.selected, |this| this.bg(selection_color()))
+                            .hover(|style| style.bg(hover_color()))
+                            .on_hover(move |hovered, cx| {
+                                cx.set_inspector_hovered(hovered.then_some(id))
+                            })
+                            .on_click(move |_, cx| cx.select_inspected_element(Some(id)))
+                            .child(element_label(element))
+                    })),
+            )
+            .children(selected.map(|element| {
+                div()
+                    .id("inspector-details")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .p_1()
+                    .border_t_1()
+                    .border_color(border_color())
+                    .child(render_details(element))
+                    .child(render_style_editor(element, rem_size))
+            }))
+            .into_any_element()
+    }
+}
+
+fn render_details(element: &InspectedElement) -> impl IntoElement {
+    let style = &element.style;
+    let bounds = element.bounds;
+    let layout = element.layout;
+    let source_location = element.source_location.map_or_else(
+        || "unknown".to_string(),
+        |location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        },
+    );
+
+    div()
+        .flex()
+        .flex_col()
+        .child(property("source", source_location))
+        .child(property("view", element.view_type.unwrap_or("none")))
+        .children(
+            element
+                .element_id
+                .as_ref()
+                .map(|element_id| property("id", element_id.to_string())),
+        )
+        .child(property(
+            "bounds",
+            format!(
+                "{:?}, {:?} {:?} × {:?}",
+                bounds.origin.x, bounds.origin.y, bounds.size.width, bounds.size.height
+            ),
+        ))
+        .child(property(
+            "layout",
+            format!(
+                "#{} at {:?}, {:?} {:?} × {:?}",
+                layout.order,
+                layout.origin.x,
+                layout.origin.y,
+                layout.size.width,
+                layout.size.height
+            ),
+        ))
+        .child(property("display", format!("{:?}", style.display)))
+        .child(property("position", format!("{:?}", style.position)))
+        .child(property(
+            "flex",
+            format!(
+                "{:?} {:?}, grow {}, shrink {}, basis {:?}",
+                style.flex_direction,
+                style.flex_wrap,
+                style.flex_grow,
+                style.flex_shrink,
+                style.flex_basis
+            ),
+        ))
+        .child(property("size", format_size(&style.size)))
+        .child(property("min size", format_size(&style.min_size)))
+        .child(property("max size", format_size(&style.max_size)))
+        .child(property("margin", format_edges(&style.margin)))
+        .child(property("padding", format_edges(&style.padding)))
+        .child(property("border", format_edges(&style.border_widths)))
+        .child(property("gap", format_size(&style.gap)))
+        .child(property(
+            "overflow",
+            format!("{:?} {:?}", style.overflow.x, style.overflow.y),
+        ))
+        .child(property(
+            "background",
+            style
+                .background
+                .as_ref()
+                .map_or_else(|| "none".to_string(), |fill| format!("{fill:?}")),
+        ))
+}
+
+/// Controls for adjusting the selected element's style, which are applied as overrides on
+/// top of its own style until the inspector is closed.
+fn render_style_editor(element: &InspectedElement, rem_size: Pixels) -> impl IntoElement {
+    let id = element.id;
+    let style = &element.style;
+    let bounds = element.bounds;
+    let parent_size = bounds.size.map(AbsoluteLength::Pixels);
+    let padding = style.padding.to_pixels(parent_size, rem_size).top;
+    let margin = match style.margin.top {
+        Length::Definite(length) => length.to_pixels(parent_size.width, rem_size),
+        Length::Auto => Pixels::ZERO,
+    };
+    let gap = style.gap.width.to_pixels(parent_size.width, rem_size);
+
+    let next_display = match style.display {
+        Display::Block => Display::Flex,
+        Display::Flex => Display::Grid,
+        Display::Grid | Display::None => Display::Block,
+    };
+    let next_flex_direction = match style.flex_direction {
+        FlexDirection::Row => FlexDirection::Column,
+        FlexDirection::Column => FlexDirection::RowReverse,
+        FlexDirection::RowReverse => FlexDirection::ColumnReverse,
+        FlexDirection::ColumnReverse => FlexDirection::Row,
+    };
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .mt_2()
+        .child(stepper("width", id, bounds.size.width, |style, width| {
+            style.size.width = Some(width.into());
+        }))
+        .child(stepper(
+            "height",
+            id,
+            bounds.size.height,
+            |style, height| {
+                style.size.height = Some(height.into());
+            },
+        ))
+        .child(stepper("padding", id, padding, |style, padding| {
+            let padding = DefiniteLength::from(padding);
+            style.padding.top = Some(padding);
+            style.padding.right = Some(padding);
+            style.padding.bottom = Some(padding);
+            style.padding.left = Some(padding);
+        }))
+        .child(stepper("margin", id, margin, |style, margin| {
+            let margin = Length::from(margin);
+            style.margin.top = Some(margin);
+            style.margin.right = Some(margin);
+            style.margin.bottom = Some(margin);
+            style.margin.left = Some(margin);
+        }))
+        .child(stepper("gap", id, gap, |style, gap| {
+            style.gap.width = Some(gap.into());
+            style.gap.height = Some(gap.into());
+        }))
+        .child(
+            div()
+                .flex()
+                .flex_row()
+                .gap_1()
+                .child(button(
+                    "inspector-display",
+                    format!("display: {next_display:?}"),
+                    move |cx| {
+                        let mut style = StyleRefinement::default();
+                        style.display = Some(next_display);
+                        cx.override_inspected_style(id, &style);
+                    },
+                ))
+                .child(button(
+                    "inspector-flex-direction",
+                    format!("direction: {next_flex_direction:?}"),
+                    move |cx| {
+                        let mut style = StyleRefinement::default();
+                        style.flex_direction = Some(next_flex_direction);
+                        cx.override_inspected_style(id, &style);
+                    },
+                ))
+                .child(button("inspector-reset", "Reset", move |cx| {
+                    cx.clear_inspected_style_overrides(id)
+                })),
+        )
+}
+
+fn stepper(
+    name: &'static str,
+    id: InspectorElementId,
+    value: Pixels,
+    apply: fn(&mut StyleRefinement, Pixels),
+) -> impl IntoElement {
+    let step = move |delta: f32| {
+        move |cx: &mut WindowContext| {
+            let mut style = StyleRefinement::default();
+            apply(&mut style, (value + px(delta)).max(Pixels::ZERO));
+            cx.override_inspected_style(id, &style);
+        }
+    };
+
+    div()
+        .id(name)
+        .flex()
+        .flex_row()
+        .items_center()
+        .gap_1()
+        .child(div().w(px(64.)).child(name))
+        .child(button("decrease", "-", step(-1.)))
+        .child(div().w(px(48.)).child(format!("{value:?}")))
+        .child(button("increase", "+", step(1.)))
+}
+
+fn button(
+    id: impl Into<ElementId>,
+    label: impl Into<SharedString>,
+    on_click: impl Fn(&mut WindowContext) + 'static,
+) -> impl IntoElement + Styled {
+    div()
+        .id(id)
+        .px_1()
+        .rounded_sm()
+        .border_1()
+        .border_color(border_color())
+        .cursor_pointer()
+        .hover(|style| style.bg(hover_color()))
+        .on_click(move |_, cx| on_click(cx))
+        .child(label.into())
+}
+
+fn property(name: &'static str, value: impl Into<SharedString>) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_row()
+        .gap_1()
+        .child(
+            div()
+                .w(px(64.))
+                .flex_none()
+                .text_color(muted_color())
+                .child(name),
+        )
+        .child(value.into())
+}
+
+fn element_label(element: &InspectedElement) -> String {
+    let mut label = String::new();
+    if let Some(element_id) = &element.element_id {
+        label.push_str(&format!("#{element_id} "));
+    }
+    match element.source_location {
+        Some(location) => {
+            let file_name = Path::new(location.file())
+                .file_name()
+                .map_or(location.file().into(), |name| name.to_string_lossy());
+            label.push_str(&format!("{file_name}:{}", location.line()));
+        }
+        None => label.push_str("element"),
+    }
+    label
+}
+
+fn format_size<T: Debug>(size: &Size<T>) -> String {
+    format!("{:?} × {:?}", size.width, size.height)
+}
+
+fn format_edges<T: Debug>(edges: &Edges<T>) -> String {
+    format!(
+        "{:?} {:?} {:?} {:?}",
+        edges.top, edges.right, edges.bottom, edges.left
+    )
+}
+
+fn background() -> Hsla {
+    hsla(0., 0., 0.12, 1.)
+}
+
+fn border_color() -> Hsla {
+    hsla(0., 0., 0.3, 1.)
+}
+
+fn text_color() -> Hsla {
+    hsla(0., 0., 0.9, 1.)
+}
+
+fn muted_color() -> Hsla {
+    hsla(0., 0., 0.6, 1.)
+}
+
+fn hover_color() -> Hsla {
+    hsla(0., 0., 0.2, 1.)
+}
+
+fn selection_color() -> Hsla {
+    hsla(0.58, 0.5, 0.3, 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, point, size, Modifiers, ParentElement as _, Render, TestAppContext,
+        ViewContext,
+    };
+
+    struct Toolbar {
+        clicks: usize,
+    }
+
+    impl Render for Toolbar {
+        fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .flex()
+                .p_2()
+                .gap_1()
+                .child(div().id("save").w(px(40.)).h(px(20.)))
+                .child(
+                    div()
+                        .id("close")
+                        .w(px(40.))
+                        .h(px(20.))
+                        .on_click(cx.listener(|this, _, _| this.clicks += 1)),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_inspector(cx: &mut TestAppContext) {
+        let (toolbar, cx) = cx.add_window_view(|_| Toolbar { clicks: 0 });
+        cx.update(|cx| cx.toggle_inspector());
+        cx.run_until_parked();
+
+        let elements = cx.update(|cx| cx.inspected_elements().to_vec());
+        assert_eq!(
+            elements
+                .iter()
+                .map(|element| (element.depth, element.element_id.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (0, None),
+                (1, Some("save".into())),
+                (1, Some("close".into()))
+            ]
+        );
+        let close = &elements[2];
+        assert_eq!(close.view_type, Some(std::any::type_name::<Toolbar>()));
+        assert_eq!(
+            close.bounds,
+            Bounds::new(point(px(52.), px(8.)), size(px(40.), px(20.)))
+        );
+        assert_eq!(close.layout.origin, point(px(52.), px(8.)));
+        #[cfg(debug_assertions)]
+        assert!(close
+            .source_location
+            .unwrap()
+            .file()
+            .ends_with("inspector.rs"));
+
+        // While picking, clicking an element selects it instead of reaching it.
+        let position = point(px(60.), px(10.));
+        cx.simulate_mouse_move(position, None, Modifiers::none());
+        cx.simulate_click(position, Modifiers::none());
+        assert_eq!(
+            cx.update(|cx| cx.selected_inspected_element().map(|element| element.id)),
+            Some(close.id)
+        );
+        assert_eq!(toolbar.update(cx, |toolbar, _| toolbar.clicks), 0);
+        cx.simulate_click(position, Modifiers::none());
+        assert_eq!(toolbar.update(cx, |toolbar, _| toolbar.clicks), 1);
+
+        // Style overrides apply until they're cleared, and element ids are stable across frames.
+        cx.update(|cx| {
+            let mut style = StyleRefinement::default();
+            style.size.width = Some(px(100.).into());
+            cx.override_inspected_style(close.id, &style);
+        });
+        cx.run_until_parked();
+        let selected = cx.update(|cx| cx.selected_inspected_element().cloned().unwrap());
+        assert_eq!(selected.id, close.id);
+        assert_eq!(selected.bounds.size.width, px(100.));
+
+        cx.update(|cx| cx.clear_inspected_style_overrides(close.id));
+        cx.run_until_parked();
+        let selected = cx.update(|cx| cx.selected_inspected_element().cloned().unwrap());
+        assert_eq!(selected.bounds.size.width, px(40.));
+
+        cx.update(|cx| cx.toggle_inspector());
+        cx.run_until_parked();
+        assert!(cx.update(|cx| cx.inspected_elements().is_empty()));
+    }
+}
@@ -27,11 +27,12 @@ mod test;
 mod windows;
 
 use crate::{
-    point, Action, AnyWindowHandle, AsyncWindowContext, BackgroundExecutor, Bounds, DevicePixels,
-    DispatchEventResult, Font, FontId, FontMetrics, FontRun, ForegroundExecutor, GPUSpecs, GlyphId,
-    ImageSource, Keymap, LineLayout, Pixels, PlatformInput, Point, RenderGlyphParams, RenderImage,
-    RenderImageParams, RenderSvgParams, ScaledPixels, Scene, SharedString, Size, SvgRenderer,
-    SvgSize, Task, TaskLabel, WindowContext, DEFAULT_WINDOW_SIZE,
+    point, AccessibilityActionRequest, AccessibilityTreeUpdate, Action, AnyWindowHandle,
+    AsyncWindowContext, BackgroundExecutor, Bounds, DevicePixels, DispatchEventResult, Font,
+    FontId, FontMetrics, FontRun, ForegroundExecutor, GPUSpecs, GlyphId, ImageSource, Keymap,
+    LineLayout, Pixels, PlatformInput, Point, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, ScaledPixels, Scene, SharedString, Size, SvgRenderer, SvgSize, Task,
+    TaskLabel, WindowContext, DEFAULT_WINDOW_SIZE,
 };
 use anyhow::{anyhow, Result};
 use async_task::Runnable;
@@ -409,6 +410,14 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
 
     fn update_ime_position(&self, _bounds: Bounds<ScaledPixels>);
 
+    // Accessibility
+    fn is_accessibility_active(&self) -> bool {
+        false
+    }
+    fn on_accessibility_activated(&self, _callback: Box<dyn FnMut()>) {}
+    fn update_accessibility_tree(&self, _update: AccessibilityTreeUpdate) {}
+    fn on_accessibility_action(&self, _callback: Box<dyn FnMut(AccessibilityActionRequest)>) {}
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_test(&mut self) -> Option<&mut TestWindow> {
         None
@@ -0,0 +1,294 @@
+//! Publishes the accessibility tree of a window over AT-SPI, using [AccessKit].
+//!
+//! [AccessKit]: https://accesskit.dev
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use accesskit::{
+    Action, ActionRequest, NodeBuilder, NodeId, Rect, Role, TextPosition, TextSelection, Toggled,
+    Tree, TreeUpdate,
+};
+use futures::{channel::mpsc, StreamExt};
+
+use crate::{
+    AccessibilityAction, AccessibilityActionRequest, AccessibilityNode, AccessibilityNodeId,
+    AccessibilityRole, AccessibilityTreeUpdate, ForegroundExecutor, Task,
+};
+
+type ActivatedCallback = Rc<RefCell<Option<Box<dyn FnMut()>>>>;
+type ActionCallback = Rc<RefCell<Option<Box<dyn FnMut(AccessibilityActionRequest)>>>>;
+
+pub(crate) struct LinuxAccessibility {
+    adapter: accesskit_unix::Adapter,
+    /// Whether an assistive technology is listening, as last reported to the main thread.
+    active: Rc<Cell<bool>>,
+    activated_callback: ActivatedCallback,
+    action_callback: ActionCallback,
+    _handle_activations: Task<()>,
+    _handle_actions: Task<()>,
+}
+
+impl LinuxAccessibility {
+    pub fn new(executor: &ForegroundExecutor) -> Self {
+        // AccessKit calls the handlers on its own thread, so activations and action requests
+        // are forwarded to the main thread before reaching the window.
+        let (activation_tx, mut activation_rx) = mpsc::unbounded();
+        let active = Rc::new(Cell::new(false));
+        let activated_callback = ActivatedCallback::default();
+        let handle_activations = executor.spawn({
+            let active = active.clone();
+            let activated_callback = activated_callback.clone();
+            async move {
+                while let Some(is_active) = activation_rx.next().await {
+                    active.set(is_active);
+                    if !is_active {
+                        continue;
+                    }
+                    let callback = activated_callback.borrow_mut().take();
+                    if let Some(mut callback) = callback {
+                        callback();
+                        activated_callback.borrow_mut().get_or_insert(callback);
+                    }
+                }
+            }
+        });
+
+        let (action_tx, mut action_rx) = mpsc::unbounded();
+        let action_callback = ActionCallback::default();
+        let handle_actions = executor.spawn({
+            let action_callback = action_callback.clone();
+            async move {
+                while let Some(request) = action_rx.next().await {
+                    let callback = action_callback.borrow_mut().take();
+                    if let Some(mut callback) = callback {
+                        callback(request);
+                        action_callback.borrow_mut().get_or_insert(callback);
+                    }
+                }
+            }
+        });
+
+        let adapter = accesskit_unix::Adapter::new(
+            ActivationHandler(activation_tx.clone()),
+            ActionHandler(action_tx),
+            DeactivationHandler(activation_tx),
+        );
+
+        Self {
+            adapter,
+            active,
+            activated_callback,
+            action_callback,
+            _handle_activations: handle_activations,
+            _handle_actions: handle_actions,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    pub fn on_activated(&self, callback: Box<dyn FnMut()>) {
+        self.activated_callback.borrow_mut().replace(callback);
+    }
+
+    pub fn on_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.action_callback.borrow_mut().replace(callback);
+    }
+
+    /// Publishes changes to the tree. The window starts over from an empty tree once it's told
+    /// about an activation, so the first update after it holds the whole tree.
+    pub fn update(&mut self, update: AccessibilityTreeUpdate, scale_factor: f32) {
+        if !self.active.get() {
+            return;
+        }
+
+        self.adapter.update_if_active(|| TreeUpdate {
+            nodes: update
+                .nodes
+                .iter()
+                .flat_map(|(id, node)| to_accesskit_nodes(*id, node, scale_factor))
+                .collect(),
+            tree: Some(Tree::new(NodeId(update.root.0))),
+            focus: NodeId(update.focus.0),
+        });
+    }
+
+    pub fn set_window_focused(&mut self, focused: bool) {
+        self.adapter.update_window_focus_state(focused);
+    }
+}
+
+struct ActivationHandler(mpsc::UnboundedSender<bool>);
+
+impl accesskit::ActivationHandler for ActivationHandler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        // The tree isn't built while nothing listens, so the window sends it with its next
+        // frame instead.
+        self.0.unbounded_send(true).ok();
+        None
+    }
+}
+
+struct ActionHandler(mpsc::UnboundedSender<AccessibilityActionRequest>);
+
+impl accesskit::ActionHandler for ActionHandler {
+    fn do_action(&mut self, request: ActionRequest) {
+        let action = match request.action {
+            Action::Focus => AccessibilityAction::Focus,
+            Action::Default => AccessibilityAction::Click,
+            _ => return,
+        };
+        self.0
+            .unbounded_send(AccessibilityActionRequest {
+                target: AccessibilityNodeId(request.target.0),
+                action,
+            })
+            .ok();
+    }
+}
+
+struct DeactivationHandler(mpsc::UnboundedSender<bool>);
+
+impl accesskit::DeactivationHandler for DeactivationHandler {
+    fn deactivate_accessibility(&mut self) {
+        self.0.unbounded_send(false).ok();
+    }
+}
+
+/// Text is exposed to AccessKit through a child node holding the text of the input, which
+/// the selection refers to.
+fn text_run_id(id: AccessibilityNodeId) -> NodeId {
+    NodeId(id.0 ^ (1 << 63))
+}
+
+fn to_accesskit_nodes(
+    id: AccessibilityNodeId,
+    node: &AccessibilityNode,
+    scale_factor: f32,
+) -> Vec<(NodeId, accesskit::Node)> {
+    let properties = &node.properties;
+    let bounds = Rect::new(
+        (node.bounds.origin.x.0 * scale_factor) as f64,
+        (node.bounds.origin.y.0 * scale_factor) as f64,
+        (node.bounds.lower_right().x.0 * scale_factor) as f64,
+        (node.bounds.lower_right().y.0 * scale_factor) as f64,
+    );
+
+    let mut builder = NodeBuilder::new(to_accesskit_role(properties.role));
+    builder.set_bounds(bounds);
+    if let Some(name) = &properties.name {
+        builder.set_name(name.to_string());
+    }
+    if let Some(description) = &properties.description {
+        builder.set_description(description.to_string());
+    }
+    if let Some(checked) = properties.checked {
+        builder.set_toggled(if checked {
+            Toggled::True
+        } else {
+            Toggled::False
+        });
+    }
+    if let Some(expanded) = properties.expanded {
+        builder.set_expanded(expanded);
+    }
+    if let Some(selected) = properties.selected {
+        builder.set_selected(selected);
+    }
+    if properties.disabled {
+        builder.set_disabled();
+    }
+    if properties.read_only {
+        builder.set_read_only();
+    }
+    if node.focusable {
+        builder.add_action(Action::Focus);
+    }
+    if node.clickable {
+        builder.add_action(Action::Default);
+    }
+
+    let mut children = node
+        .children
+        .iter()
+        .map(|child| NodeId(child.0))
+        .collect::<Vec<_>>();
+    let mut nodes = Vec::new();
+    if let Some(value) = &properties.value {
+        builder.set_value(value.to_string());
+
+        if matches!(
+            properties.role,
+            AccessibilityRole::TextInput | AccessibilityRole::MultilineTextInput
+        ) {
+            let run_id = text_run_id(id);
+            let mut run = NodeBuilder::new(Role::InlineTextBox);
+            run.set_bounds(bounds);
+            run.set_value(value.to_string());
+            run.set_character_lengths(
+                value
+                    .chars()
+                    .map(|character| character.len_utf8() as u8)
+                    .collect::<Vec<_>>(),
+            );
+            nodes.push((run_id, run.build()));
+            children.insert(0, run_id);
+
+            if let Some(selection) = &properties.text_selection {
+                let position = |offset: usize| TextPosition {
+                    node: run_id,
+                    character_index: value
+                        .get(..offset.min(value.len()))
+                        .map_or(0, |prefix| prefix.chars().count()),
+                };
+                builder.set_text_selection(TextSelection {
+                    anchor: position(selection.start),
+                    focus: position(selection.end),
+                });
+            }
+        }
+    }
+    builder.set_children(children);
+
+    nodes.insert(0, (NodeId(id.0), builder.build()));
+    nodes
+}
+
+fn to_accesskit_role(role: AccessibilityRole) -> Role {
+    match role {
+        AccessibilityRole::Window => Role::Window,
+        AccessibilityRole::Group => Role::Group,
+        AccessibilityRole::Button => Role::Button,
+        AccessibilityRole::CheckBox => Role::CheckBox,
+        AccessibilityRole::RadioButton => Role::RadioButton,
+        AccessibilityRole::Switch => Role::Switch,
+        AccessibilityRole::Link => Role::Link,
+        AccessibilityRole::Label => Role::Label,
+        AccessibilityRole::Heading => Role::Heading,
+        AccessibilityRole::Image => Role::Image,
+        AccessibilityRole::TextInput => Role::TextInput,
+        AccessibilityRole::MultilineTextInput => Role::MultilineTextInput,
+        AccessibilityRole::List => Role::List,
+        AccessibilityRole::ListItem => Role::ListItem,
+        AccessibilityRole::Tree => Role::Tree,
+        AccessibilityRole::TreeItem => Role::TreeItem,
+        AccessibilityRole::Table => Role::Table,
+        AccessibilityRole::Row => Role::Row,
+        AccessibilityRole::Cell => Role::Cell,
+        AccessibilityRole::TabList => Role::TabList,
+        AccessibilityRole::Tab => Role::Tab,
+        AccessibilityRole::TabPanel => Role::TabPanel,
+        AccessibilityRole::Menu => Role::Menu,
+        AccessibilityRole::MenuItem => Role::MenuItem,
+        AccessibilityRole::Toolbar => Role::Toolbar,
+        AccessibilityRole::Dialog => Role::Dialog,
+        AccessibilityRole::Tooltip => Role::Tooltip,
+        AccessibilityRole::ScrollView => Role::ScrollView,
+        AccessibilityRole::ProgressIndicator => Role::ProgressIndicator,
+        AccessibilityRole::Slider => Role::Slider,
+    }
+}
@@ -25,10 +25,11 @@ use crate::platform::linux::wayland::serial::SerialKind;
 use crate::platform::{PlatformAtlas, PlatformInputHandler, PlatformWindow};
 use crate::scene::Scene;
 use crate::{
-    px, size, AnyWindowHandle, Bounds, Decorations, GPUSpecs, Globals, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
-    ScaledPixels, Size, Tiling, WaylandClientStatePtr, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations, WindowParams,
+    px, size, AccessibilityActionRequest, AccessibilityTreeUpdate, AnyWindowHandle, Bounds,
+    Decorations, GPUSpecs, Globals, LinuxAccessibility, Modifiers, Output, Pixels, PlatformDisplay,
+    PlatformInput, Point, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Size, Tiling,
+    WaylandClientStatePtr, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControls, WindowDecorations, WindowParams,
 };
 
 #[derive(Default)]
@@ -103,6 +104,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     inset: Option<Pixels>,
+    accessibility: LinuxAccessibility,
 }
 
 #[derive(Clone)]
@@ -156,6 +158,8 @@ impl WaylandWindowState {
             transparent: true,
         };
 
+        let accessibility = LinuxAccessibility::new(&globals.executor);
+
         Ok(Self {
             xdg_surface,
             acknowledged_first_configure: false,
@@ -187,6 +191,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             inset: None,
+            accessibility,
         })
     }
 
@@ -699,7 +704,10 @@ impl WaylandWindowStatePtr {
     }
 
     pub fn set_focused(&self, focus: bool) {
-        self.state.borrow_mut().active = focus;
+        let mut state = self.state.borrow_mut();
+        state.active = focus;
+        state.accessibility.set_window_focused(focus);
+        drop(state);
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -1018,6 +1026,24 @@ impl PlatformWindow for WaylandWindow {
     fn gpu_specs(&self) -> Option<GPUSpecs> {
         self.borrow().renderer.gpu_specs().into()
     }
+
+    fn is_accessibility_active(&self) -> bool {
+        self.borrow().accessibility.is_active()
+    }
+
+    fn on_accessibility_activated(&self, callback: Box<dyn FnMut()>) {
+        self.borrow().accessibility.on_activated(callback);
+    }
+
+    fn update_accessibility_tree(&self, update: AccessibilityTreeUpdate) {
+        let mut state = self.borrow_mut();
+        let scale = state.scale;
+        state.accessibility.update(update, scale);
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.borrow().accessibility.on_action(callback);
+    }
 }
 
 fn update_window(mut state: RefMut<WaylandWindowState>) {
@@ -2,11 +2,12 @@ use anyhow::Context;
 
 use crate::{
     platform::blade::{BladeRenderer, BladeSurfaceConfig},
-    px, size, AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GPUSpecs,
-    Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
-    PlatformWindow, Point, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowDecorations,
-    WindowKind, WindowParams, X11ClientStatePtr,
+    px, size, AccessibilityActionRequest, AccessibilityTreeUpdate, AnyWindowHandle, Bounds,
+    Decorations, DevicePixels, ForegroundExecutor, GPUSpecs, LinuxAccessibility, Modifiers, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size, Tiling,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowKind,
+    WindowParams, X11ClientStatePtr,
 };
 
 use blade_graphics as gpu;
@@ -265,6 +266,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    accessibility: LinuxAccessibility,
 }
 
 impl X11WindowState {
@@ -538,6 +540,7 @@ impl X11WindowState {
 
         Ok(Self {
             client,
+            accessibility: LinuxAccessibility::new(&executor),
             executor,
             display: Rc::new(
                 X11Display::new(xcb_connection, scale_factor, x_screen_index).unwrap(),
@@ -957,6 +960,10 @@ impl X11WindowStatePtr {
     }
 
     pub fn set_active(&self, focus: bool) {
+        self.state
+            .borrow_mut()
+            .accessibility
+            .set_window_focused(focus);
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -1435,4 +1442,22 @@ impl PlatformWindow for X11Window {
     fn gpu_specs(&self) -> Option<GPUSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
+
+    fn is_accessibility_active(&self) -> bool {
+        self.0.state.borrow().accessibility.is_active()
+    }
+
+    fn on_accessibility_activated(&self, callback: Box<dyn FnMut()>) {
+        self.0.state.borrow().accessibility.on_activated(callback);
+    }
+
+    fn update_accessibility_tree(&self, update: AccessibilityTreeUpdate) {
+        let mut state = self.0.state.borrow_mut();
+        let scale_factor = state.scale_factor;
+        state.accessibility.update(update, scale_factor);
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.0.state.borrow().accessibility.on_action(callback);
+    }
 }
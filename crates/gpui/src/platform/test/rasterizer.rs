@@ -0,0 +1,706 @@
+use crate::{
+    AtlasTextureId, AtlasTile, Bounds, ContentMask, Corners, DevicePixels, Hsla, MonochromeSprite,
+    Path, PolychromeSprite, PrimitiveBatch, Quad, Rgba, ScaledPixels, Scene, Shadow, Size,
+    Underline,
+};
+use collections::HashMap;
+use image::RgbaImage;
+use std::{f32::consts::PI, ops::Range, sync::Arc};
+
+const GRAYSCALE_FACTORS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// The contents of a texture in the test atlas.
+#[derive(Clone)]
+pub(crate) struct TestAtlasTexture {
+    pub size: Size<DevicePixels>,
+    pub bytes: Arc<[u8]>,
+}
+
+/// Renders a [`Scene`] to an image on the CPU.
+///
+/// Each primitive is shaded the same way as in the shaders of the GPU renderers, sampling
+/// every pixel at its center. Colors are blended in linear space and converted to sRGB at
+/// the end.
+pub(crate) fn rasterize(
+    scene: &Scene,
+    size: Size<DevicePixels>,
+    textures: &HashMap<AtlasTextureId, TestAtlasTexture>,
+) -> RgbaImage {
+    let mut canvas = Canvas {
+        width: size.width.0.max(0) as usize,
+        height: size.height.0.max(0) as usize,
+        pixels: vec![[0.; 4]; (size.width.0.max(0) * size.height.0.max(0)) as usize],
+    };
+
+    for batch in scene.batches() {
+        match batch {
+            PrimitiveBatch::Shadows(shadows) => {
+                for shadow in shadows {
+                    canvas.draw_shadow(shadow);
+                }
+            }
+            PrimitiveBatch::Quads(quads) => {
+                for quad in quads {
+                    canvas.draw_quad(quad);
+                }
+            }
+            PrimitiveBatch::Paths(paths) => {
+                for path in paths {
+                    canvas.draw_path(path);
+                }
+            }
+            PrimitiveBatch::Underlines(underlines) => {
+                for underline in underlines {
+                    canvas.draw_underline(underline);
+                }
+            }
+            PrimitiveBatch::MonochromeSprites {
+                texture_id,
+                sprites,
+            } => {
+                if let Some(texture) = textures.get(&texture_id) {
+                    for sprite in sprites {
+                        canvas.draw_monochrome_sprite(sprite, texture);
+                    }
+                }
+            }
+            PrimitiveBatch::PolychromeSprites {
+                texture_id,
+                sprites,
+            } => {
+                if let Some(texture) = textures.get(&texture_id) {
+                    for sprite in sprites {
+                        canvas.draw_polychrome_sprite(sprite, texture);
+                    }
+                }
+            }
+            // Surfaces are only produced by video playback on macOS.
+            PrimitiveBatch::Surfaces(_) => {}
+        }
+    }
+
+    canvas.into_image()
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    /// Premultiplied, linear RGBA.
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    /// Returns the rows and columns of the pixels whose centers lie within the given bounds
+    /// and content mask.
+    fn pixels_within(
+        &self,
+        bounds: &Bounds<ScaledPixels>,
+        content_mask: &ContentMask<ScaledPixels>,
+    ) -> (Range<usize>, Range<usize>) {
+        let bounds = bounds.intersect(&content_mask.bounds);
+        let pixel_range = |start: f32, end: f32, len: usize| {
+            let start = (start - 0.5).ceil().clamp(0., len as f32) as usize;
+            let end = ((end - 0.5).floor() + 1.).clamp(0., len as f32) as usize;
+            start..end.max(start)
+        };
+        (
+            pixel_range(
+                bounds.origin.y.0,
+                bounds.origin.y.0 + bounds.size.height.0,
+                self.height,
+            ),
+            pixel_range(
+                bounds.origin.x.0,
+                bounds.origin.x.0 + bounds.size.width.0,
+                self.width,
+            ),
+        )
+    }
+
+    /// Blends a straight-alpha linear color over the given pixel.
+    fn blend(&mut self, x: usize, y: usize, color: [f32; 4], alpha_factor: f32) {
+        let alpha = color[3] * alpha_factor;
+        if alpha.is_nan() || alpha <= 0. {
+            return;
+        }
+        let pixel = &mut self.pixels[y * self.width + x];
+        let source = [color[0] * alpha, color[1] * alpha, color[2] * alpha, alpha];
+        for (destination, source) in pixel.iter_mut().zip(source) {
+            *destination = source + *destination * (1. - alpha);
+        }
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let background = linear_rgba(quad.background);
+        let border_color = linear_rgba(quad.border_color);
+        let corner_radii = corners(&quad.corner_radii);
+        let border_widths = [
+            quad.border_widths.top.0,
+            quad.border_widths.right.0,
+            quad.border_widths.bottom.0,
+            quad.border_widths.left.0,
+        ];
+        let is_plain = corner_radii.iter().all(|radius| *radius == 0.)
+            && border_widths.iter().all(|width| *width == 0.);
+        let (origin, half_size) = center_and_half_size(&quad.bounds);
+
+        let (rows, columns) = self.pixels_within(&quad.bounds, &quad.content_mask);
+        for y in rows {
+            for x in columns.clone() {
+                if is_plain {
+                    self.blend(x, y, background, 1.);
+                    continue;
+                }
+
+                let center_to_point = [x as f32 + 0.5 - origin[0], y as f32 + 0.5 - origin[1]];
+                let corner_radius = pick_corner_radius(center_to_point, corner_radii);
+                let distance = rounded_rect_sdf(center_to_point, half_size, corner_radius);
+
+                let vertical_border = if center_to_point[0] > 0. {
+                    border_widths[1]
+                } else {
+                    border_widths[3]
+                };
+                let horizontal_border = if center_to_point[1] > 0. {
+                    border_widths[2]
+                } else {
+                    border_widths[0]
+                };
+                let point_to_inset_corner = [
+                    center_to_point[0].abs() - (half_size[0] - corner_radius - vertical_border),
+                    center_to_point[1].abs() - (half_size[1] - corner_radius - horizontal_border),
+                ];
+                let border_width = if point_to_inset_corner[0] < 0. && point_to_inset_corner[1] < 0.
+                {
+                    0.
+                } else if point_to_inset_corner[1] > point_to_inset_corner[0] {
+                    horizontal_border
+                } else {
+                    vertical_border
+                };
+
+                let mut color = background;
+                if border_width > 0. {
+                    let inset_distance = distance + border_width;
+                    let blended_border = over(background, border_color);
+                    color = mix(blended_border, background, saturate(0.5 - inset_distance));
+                }
+                self.blend(x, y, color, saturate(0.5 - distance));
+            }
+        }
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let blur_radius = shadow.blur_radius.0;
+        if blur_radius <= 0. {
+            return;
+        }
+
+        let color = linear_rgba(shadow.color);
+        let corner_radii = corners(&shadow.corner_radii);
+        let (origin, half_size) = center_and_half_size(&shadow.bounds);
+        let mut bounds = shadow.bounds;
+        bounds.dilate(ScaledPixels(3. * blur_radius));
+
+        let (rows, columns) = self.pixels_within(&bounds, &shadow.content_mask);
+        for y in rows {
+            for x in columns.clone() {
+                let center_to_point = [x as f32 + 0.5 - origin[0], y as f32 + 0.5 - origin[1]];
+                let corner_radius = pick_corner_radius(center_to_point, corner_radii);
+
+                // The signal is only non-zero in a limited range, so don't waste samples.
+                let low = center_to_point[1] - half_size[1];
+                let high = center_to_point[1] + half_size[1];
+                let start = (-3. * blur_radius).clamp(low, high);
+                let end = (3. * blur_radius).clamp(low, high);
+
+                let step = (end - start) / 4.;
+                let mut sample_y = start + step * 0.5;
+                let mut alpha = 0.;
+                for _ in 0..4 {
+                    let blur = blur_along_x(
+                        center_to_point[0],
+                        center_to_point[1] - sample_y,
+                        blur_radius,
+                        corner_radius,
+                        half_size,
+                    );
+                    alpha += blur * gaussian(sample_y, blur_radius) * step;
+                    sample_y += step;
+                }
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let color = linear_rgba(path.color);
+        let (rows, columns) = self.pixels_within(&path.bounds, &path.content_mask);
+        if rows.is_empty() || columns.is_empty() {
+            return;
+        }
+
+        // Accumulate the coverage of each triangle, like the GPU renderers do in the path
+        // atlas, so that overlapping triangles cancel out.
+        let mut coverage = vec![0f32; rows.len() * columns.len()];
+        for triangle in path.vertices.chunks_exact(3) {
+            let [a, b, c] =
+                [0, 1, 2].map(|ix| [triangle[ix].xy_position.x.0, triangle[ix].xy_position.y.0]);
+            let st = [0, 1, 2].map(|ix| [triangle[ix].st_position.x, triangle[ix].st_position.y]);
+            let area = edge_function(a, b, c);
+            if area.abs() < f32::EPSILON {
+                continue;
+            }
+
+            // The texture coordinates vary linearly across the triangle, so their
+            // derivatives are constant.
+            let derivatives = |values: [f32; 3]| {
+                let dx = ((values[1] - values[0]) * (c[1] - a[1])
+                    - (values[2] - values[0]) * (b[1] - a[1]))
+                    / area;
+                let dy = ((values[2] - values[0]) * (b[0] - a[0])
+                    - (values[1] - values[0]) * (c[0] - a[0]))
+                    / area;
+                (dx, dy)
+            };
+            let (ds_dx, ds_dy) = derivatives([st[0][0], st[1][0], st[2][0]]);
+            let (dt_dx, dt_dy) = derivatives([st[0][1], st[1][1], st[2][1]]);
+
+            for (row_ix, y) in rows.clone().enumerate() {
+                for (column_ix, x) in columns.clone().enumerate() {
+                    let point = [x as f32 + 0.5, y as f32 + 0.5];
+                    let weights = [
+                        edge_function(b, c, point) / area,
+                        edge_function(c, a, point) / area,
+                        edge_function(a, b, point) / area,
+                    ];
+                    if weights.iter().any(|weight| *weight < 0.) {
+                        continue;
+                    }
+
+                    let s = weights[0] * st[0][0] + weights[1] * st[1][0] + weights[2] * st[2][0];
+                    let t = weights[0] * st[0][1] + weights[1] * st[1][1] + weights[2] * st[2][1];
+                    let gradient = [2. * s * ds_dx - dt_dx, 2. * s * ds_dy - dt_dy];
+                    let distance = (s * s - t) / gradient[0].hypot(gradient[1]);
+                    let sample = saturate(0.5 - distance);
+                    if !sample.is_nan() {
+                        coverage[row_ix * columns.len() + column_ix] += sample;
+                    }
+                }
+            }
+        }
+
+        for (row_ix, y) in rows.enumerate() {
+            for (column_ix, x) in columns.clone().enumerate() {
+                let sample = coverage[row_ix * columns.len() + column_ix];
+                let mask = 1. - (1. - sample % 2.).abs();
+                self.blend(x, y, color, mask);
+            }
+        }
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        let color = linear_rgba(underline.color);
+        let bounds = &underline.bounds;
+        let (rows, columns) = self.pixels_within(bounds, &underline.content_mask);
+        for y in rows {
+            for x in columns.clone() {
+                if !underline.wavy {
+                    self.blend(x, y, color, color[3]);
+                    continue;
+                }
+
+                let thickness = underline.thickness.0;
+                let height = bounds.size.height.0;
+                let st = [
+                    (x as f32 + 0.5 - bounds.origin.x.0) / height,
+                    (y as f32 + 0.5 - bounds.origin.y.0) / height - 0.5,
+                ];
+                let frequency = PI * 3. * thickness / 3.;
+                let amplitude = 1. / (4. * thickness);
+                let sine = (st[0] * frequency).sin() * amplitude;
+                let sine_derivative = (st[0] * frequency).cos() * amplitude * frequency;
+                let distance = (st[1] - sine) / (1. + sine_derivative * sine_derivative).sqrt();
+                let distance_in_pixels = distance * height;
+                let distance_from_top_border = distance_in_pixels - thickness * 0.5;
+                let distance_from_bottom_border = distance_in_pixels + thickness * 0.5;
+                let alpha =
+                    saturate(0.5 - (-distance_from_bottom_border).max(distance_from_top_border));
+                self.blend(x, y, color, alpha * color[3]);
+            }
+        }
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, texture: &TestAtlasTexture) {
+        let color = linear_rgba(sprite.color);
+        let transformation = &sprite.transformation;
+        let [[a, b], [c, d]] = transformation.rotation_scale;
+        let determinant = a * d - b * c;
+        if determinant.abs() < f32::EPSILON {
+            return;
+        }
+
+        // Find the pixels covered by the transformed sprite, then map each of them back
+        // into the sprite's untransformed bounds.
+        let bounds = &sprite.bounds;
+        let corners = [
+            (bounds.origin.x.0, bounds.origin.y.0),
+            (bounds.origin.x.0 + bounds.size.width.0, bounds.origin.y.0),
+            (bounds.origin.x.0, bounds.origin.y.0 + bounds.size.height.0),
+            (
+                bounds.origin.x.0 + bounds.size.width.0,
+                bounds.origin.y.0 + bounds.size.height.0,
+            ),
+        ]
+        .map(|(x, y)| {
+            [
+                a * x + b * y + transformation.translation[0],
+                c * x + d * y + transformation.translation[1],
+            ]
+        });
+        let min = corners
+            .iter()
+            .fold([f32::MAX; 2], |min, p| [min[0].min(p[0]), min[1].min(p[1])]);
+        let max = corners
+            .iter()
+            .fold([f32::MIN; 2], |max, p| [max[0].max(p[0]), max[1].max(p[1])]);
+        let transformed_bounds = Bounds::from_corners(
+            crate::point(ScaledPixels(min[0]), ScaledPixels(min[1])),
+            crate::point(ScaledPixels(max[0]), ScaledPixels(max[1])),
+        );
+        let unclipped = ContentMask {
+            bounds: transformed_bounds,
+        };
+
+        let (rows, columns) = self.pixels_within(&transformed_bounds, &unclipped);
+        for y in rows {
+            for x in columns.clone() {
+                let px = x as f32 + 0.5 - transformation.translation[0];
+                let py = y as f32 + 0.5 - transformation.translation[1];
+                let position = [
+                    (d * px - b * py) / determinant,
+                    (a * py - c * px) / determinant,
+                ];
+                let Some(unit) = unit_position(position, bounds, &sprite.content_mask) else {
+                    continue;
+                };
+                let sample = sample_texture(texture, &sprite.tile, unit, 1)[0];
+                self.blend(x, y, color, sample);
+            }
+        }
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, texture: &TestAtlasTexture) {
+        let corner_radii = corners(&sprite.corner_radii);
+        let (origin, half_size) = center_and_half_size(&sprite.bounds);
+        let (rows, columns) = self.pixels_within(&sprite.bounds, &sprite.content_mask);
+        for y in rows {
+            for x in columns.clone() {
+                let point = [x as f32 + 0.5, y as f32 + 0.5];
+                let Some(unit) = unit_position(point, &sprite.bounds, &sprite.content_mask) else {
+                    continue;
+                };
+
+                // Textures are stored as sRGB BGRA, and sampled in linear space.
+                let [blue, green, red, alpha]: [f32; 4] =
+                    sample_texture(texture, &sprite.tile, unit, 4)
+                        .try_into()
+                        .unwrap();
+                let mut color = [red, green, blue, alpha];
+                if sprite.grayscale {
+                    let grayscale = color[0] * GRAYSCALE_FACTORS[0]
+                        + color[1] * GRAYSCALE_FACTORS[1]
+                        + color[2] * GRAYSCALE_FACTORS[2];
+                    color = [grayscale, grayscale, grayscale, alpha];
+                }
+
+                let center_to_point = [point[0] - origin[0], point[1] - origin[1]];
+                let corner_radius = pick_corner_radius(center_to_point, corner_radii);
+                let distance = rounded_rect_sdf(center_to_point, half_size, corner_radius);
+                self.blend(x, y, color, sprite.opacity * saturate(0.5 - distance));
+            }
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width as u32, self.height as u32);
+        for (pixel, color) in image.pixels_mut().zip(self.pixels) {
+            let alpha = color[3];
+            let channel = |value: f32| {
+                let value = if alpha > 0. { value / alpha } else { 0. };
+                (linear_to_srgb(value) * 255.).round() as u8
+            };
+            pixel.0 = [
+                channel(color[0]),
+                channel(color[1]),
+                channel(color[2]),
+                (alpha.clamp(0., 1.) * 255.).round() as u8,
+            ];
+        }
+        image
+    }
+}
+
+/// Returns the position of the given point within the bounds, from 0 to 1 along each axis,
+/// if it lies within both the bounds and the content mask.
+fn unit_position(
+    point: [f32; 2],
+    bounds: &Bounds<ScaledPixels>,
+    content_mask: &ContentMask<ScaledPixels>,
+) -> Option<[f32; 2]> {
+    let mask = &content_mask.bounds;
+    if point[0] < mask.origin.x.0
+        || point[1] < mask.origin.y.0
+        || point[0] > mask.origin.x.0 + mask.size.width.0
+        || point[1] > mask.origin.y.0 + mask.size.height.0
+    {
+        return None;
+    }
+
+    let unit = [
+        (point[0] - bounds.origin.x.0) / bounds.size.width.0,
+        (point[1] - bounds.origin.y.0) / bounds.size.height.0,
+    ];
+    unit.iter()
+        .all(|value| (0. ..=1.).contains(value))
+        .then_some(unit)
+}
+
+/// Samples the tile at the given position with bilinear filtering, returning the linear
+/// value of each channel.
+fn sample_texture(
+    texture: &TestAtlasTexture,
+    tile: &AtlasTile,
+    unit: [f32; 2],
+    channels: usize,
+) -> Vec<f32> {
+    let origin = [
+        tile.bounds.origin.x.0.max(0) as usize,
+        tile.bounds.origin.y.0.max(0) as usize,
+    ];
+    let width = tile.bounds.size.width.0.max(1) as usize;
+    let height = tile.bounds.size.height.0.max(1) as usize;
+    let texel = |x: usize, y: usize, channel: usize| -> f32 {
+        let offset = ((origin[1] + y) * texture.size.width.0 as usize + origin[0] + x) * channels;
+        let value = texture.bytes.get(offset + channel).copied().unwrap_or(0) as f32 / 255.;
+        // The alpha channel and monochrome masks aren't gamma encoded.
+        if channels == 4 && channel < 3 {
+            srgb_to_linear(value)
+        } else {
+            value
+        }
+    };
+
+    let x = (unit[0] * width as f32 - 0.5).clamp(0., (width - 1) as f32);
+    let y = (unit[1] * height as f32 - 0.5).clamp(0., (height - 1) as f32);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    (0..channels)
+        .map(|channel| {
+            let top = texel(x0, y0, channel) * (1. - fx) + texel(x1, y0, channel) * fx;
+            let bottom = texel(x0, y1, channel) * (1. - fx) + texel(x1, y1, channel) * fx;
+            top * (1. - fy) + bottom * fy
+        })
+        .collect()
+}
+
+fn center_and_half_size(bounds: &Bounds<ScaledPixels>) -> ([f32; 2], [f32; 2]) {
+    let half_size = [bounds.size.width.0 / 2., bounds.size.height.0 / 2.];
+    (
+        [
+            bounds.origin.x.0 + half_size[0],
+            bounds.origin.y.0 + half_size[1],
+        ],
+        half_size,
+    )
+}
+
+/// Returns the radii in the order top left, top right, bottom right, bottom left.
+fn corners(corners: &Corners<ScaledPixels>) -> [f32; 4] {
+    [
+        corners.top_left.0,
+        corners.top_right.0,
+        corners.bottom_right.0,
+        corners.bottom_left.0,
+    ]
+}
+
+fn pick_corner_radius(point: [f32; 2], radii: [f32; 4]) -> f32 {
+    match (point[0] < 0., point[1] < 0.) {
+        (true, true) => radii[0],
+        (false, true) => radii[1],
+        (false, false) => radii[2],
+        (true, false) => radii[3],
+    }
+}
+
+fn rounded_rect_sdf(center_to_point: [f32; 2], half_size: [f32; 2], corner_radius: f32) -> f32 {
+    let rounded_edge_to_point = [
+        center_to_point[0].abs() - half_size[0] + corner_radius,
+        center_to_point[1].abs() - half_size[1] + corner_radius,
+    ];
+    rounded_edge_to_point[0]
+        .max(0.)
+        .hypot(rounded_edge_to_point[1].max(0.))
+        + rounded_edge_to_point[0]
+            .max(rounded_edge_to_point[1])
+            .min(0.)
+        - corner_radius
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+}
+
+/// Approximates the error function.
+fn erf(value: f32) -> f32 {
+    let sign = value.signum();
+    let a = value.abs();
+    let r1 = 1. + (0.278393 + (0.230389 + (0.000972 + 0.078108 * a) * a) * a) * a;
+    let r2 = r1 * r1;
+    sign - sign / (r2 * r2)
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: [f32; 2]) -> f32 {
+    let delta = (half_size[1] - corner - y.abs()).min(0.);
+    let curved = half_size[0] - corner + (corner * corner - delta * delta).max(0.).sqrt();
+    let integral = |x: f32| 0.5 + 0.5 * erf(x * (0.5f32.sqrt() / sigma));
+    integral(x + curved) - integral(x - curved)
+}
+
+fn edge_function(a: [f32; 2], b: [f32; 2], point: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+fn linear_rgba(color: Hsla) -> [f32; 4] {
+    let color = Rgba::from(color);
+    [
+        srgb_to_linear(color.r),
+        srgb_to_linear(color.g),
+        srgb_to_linear(color.b),
+        color.a,
+    ]
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value < 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0., 1.);
+    if value < 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0., 1.)
+}
+
+/// Composites one straight-alpha color over another.
+fn over(below: [f32; 4], above: [f32; 4]) -> [f32; 4] {
+    let alpha = above[3] + below[3] * (1. - above[3]);
+    if alpha == 0. {
+        return [0.; 4];
+    }
+    [0, 1, 2, 3].map(|channel| {
+        if channel == 3 {
+            alpha
+        } else {
+            (above[channel] * above[3] + below[channel] * below[3] * (1. - above[3])) / alpha
+        }
+    })
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], amount: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|channel| a[channel] * (1. - amount) + b[channel] * amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point, px, rgb, size, Edges};
+
+    fn scaled_bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+        Bounds::new(
+            point(ScaledPixels(x), ScaledPixels(y)),
+            size(ScaledPixels(width), ScaledPixels(height)),
+        )
+    }
+
+    fn render(scene: &mut Scene) -> RgbaImage {
+        scene.finish();
+        rasterize(
+            scene,
+            size(DevicePixels(8), DevicePixels(8)),
+            &HashMap::default(),
+        )
+    }
+
+    #[test]
+    fn test_rasterize_quads() {
+        let mut scene = Scene::default();
+        let content_mask = ContentMask {
+            bounds: scaled_bounds(0., 0., 8., 8.),
+        };
+        scene.insert_primitive(Quad {
+            bounds: scaled_bounds(0., 0., 8., 8.),
+            content_mask: content_mask.clone(),
+            background: rgb(0x0000ff).into(),
+            border_color: rgb(0xff0000).into(),
+            border_widths: Edges {
+                top: ScaledPixels(2.),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        scene.insert_primitive(Quad {
+            bounds: scaled_bounds(4., 4., 4., 4.),
+            content_mask: ContentMask {
+                bounds: scaled_bounds(0., 0., 6., 6.),
+            },
+            background: Hsla::from(rgb(0x00ff00)).opacity(0.5),
+            ..Default::default()
+        });
+
+        let image = render(&mut scene);
+        // The border is drawn over the top of the background.
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 2).0, [0, 0, 255, 255]);
+        // Translucent colors are blended in linear space, and quads are clipped to their
+        // content masks.
+        assert_eq!(image.get_pixel(5, 5).0, [0, 188, 188, 255]);
+        assert_eq!(image.get_pixel(6, 6).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_rasterize_paths() {
+        let mut path = Path::new(point(px(0.), px(0.)));
+        path.line_to(point(px(8.), px(0.)));
+        path.line_to(point(px(0.), px(8.)));
+        path.line_to(point(px(0.), px(0.)));
+        path.color = rgb(0xffffff).into();
+        path.content_mask = ContentMask {
+            bounds: Bounds::new(point(px(0.), px(0.)), size(px(8.), px(8.))),
+        };
+
+        let mut scene = Scene::default();
+        scene.insert_primitive(path.scale(1.));
+        let image = render(&mut scene);
+
+        // Only the pixels whose centers are above the diagonal are filled.
+        assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(6, 0).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(7, 7).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(4, 4).0, [0, 0, 0, 0]);
+    }
+}
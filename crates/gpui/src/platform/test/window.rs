@@ -1,8 +1,9 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GPUSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, RequestFrameOptions, ScaledPixels, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowParams,
+    AccessibilityActionRequest, AccessibilityTree, AccessibilityTreeUpdate, AnyWindowHandle,
+    AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GPUSpecs, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    RequestFrameOptions, ScaledPixels, Size, TestAtlasTexture, TestPlatform, TileId,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowParams,
 };
 use collections::HashMap;
 use parking_lot::Mutex;
@@ -19,7 +20,7 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    sprite_atlas: Arc<TestAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
@@ -28,6 +29,10 @@ pub(crate) struct TestWindowState {
     moved_callback: Option<Box<dyn FnMut()>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    pub(crate) accessibility_tree: AccessibilityTree,
+    accessibility_active: bool,
+    accessibility_activated_callback: Option<Box<dyn FnMut()>>,
+    accessibility_action_callback: Option<Box<dyn FnMut(AccessibilityActionRequest)>>,
 }
 
 #[derive(Clone)]
@@ -72,6 +77,10 @@ impl TestWindow {
             moved_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            accessibility_tree: AccessibilityTree::default(),
+            accessibility_active: false,
+            accessibility_activated_callback: None,
+            accessibility_action_callback: None,
         })))
     }
 
@@ -107,6 +116,32 @@ impl TestWindow {
         self.0.lock().input_callback = Some(callback);
         !result.propagate
     }
+
+    pub(crate) fn simulate_accessibility_activation(&self) {
+        let mut lock = self.0.lock();
+        lock.accessibility_active = true;
+        let Some(mut callback) = lock.accessibility_activated_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback();
+        self.0.lock().accessibility_activated_callback = Some(callback);
+    }
+
+    pub(crate) fn simulate_accessibility_action(&self, request: AccessibilityActionRequest) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.accessibility_action_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(request);
+        self.0.lock().accessibility_action_callback = Some(callback);
+    }
+
+    /// Returns the contents of the textures that have been rasterized for this window.
+    pub(crate) fn sprite_textures(&self) -> HashMap<AtlasTextureId, TestAtlasTexture> {
+        self.0.lock().sprite_atlas.textures()
+    }
 }
 
 impl PlatformWindow for TestWindow {
@@ -276,6 +311,22 @@ impl PlatformWindow for TestWindow {
 
     fn update_ime_position(&self, _bounds: Bounds<ScaledPixels>) {}
 
+    fn is_accessibility_active(&self) -> bool {
+        self.0.lock().accessibility_active
+    }
+
+    fn on_accessibility_activated(&self, callback: Box<dyn FnMut()>) {
+        self.0.lock().accessibility_activated_callback = Some(callback);
+    }
+
+    fn update_accessibility_tree(&self, update: AccessibilityTreeUpdate) {
+        self.0.lock().accessibility_tree.apply(update);
+    }
+
+    fn on_accessibility_action(&self, callback: Box<dyn FnMut(AccessibilityActionRequest)>) {
+        self.0.lock().accessibility_action_callback = Some(callback);
+    }
+
     fn gpu_specs(&self) -> Option<GPUSpecs> {
         None
     }
@@ -284,6 +335,7 @@ impl PlatformWindow for TestWindow {
 pub(crate) struct TestAtlasState {
     next_id: u32,
     tiles: HashMap<AtlasKey, AtlasTile>,
+    textures: HashMap<AtlasTextureId, TestAtlasTexture>,
 }
 
 pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
@@ -293,8 +345,14 @@ impl TestAtlas {
         TestAtlas(Mutex::new(TestAtlasState {
             next_id: 0,
             tiles: HashMap::default(),
+            textures: HashMap::default(),
         }))
     }
+
+    /// Returns the contents of every texture that has been inserted into the atlas.
+    pub(crate) fn textures(&self) -> HashMap<AtlasTextureId, TestAtlasTexture> {
+        self.0.lock().textures.clone()
+    }
 }
 
 impl PlatformAtlas for TestAtlas {
@@ -311,7 +369,7 @@ impl PlatformAtlas for TestAtlas {
         }
         drop(state);
 
-        let Some((size, _)) = build()? else {
+        let Some((size, bytes)) = build()? else {
             return Ok(None);
         };
 
@@ -320,14 +378,22 @@ impl PlatformAtlas for TestAtlas {
         let texture_id = state.next_id;
         state.next_id += 1;
         let tile_id = state.next_id;
+        let texture_id = AtlasTextureId {
+            index: texture_id,
+            kind: key.texture_kind(),
+        };
 
+        state.textures.insert(
+            texture_id,
+            TestAtlasTexture {
+                size,
+                bytes: bytes.into_owned().into(),
+            },
+        );
         state.tiles.insert(
             key.clone(),
             crate::AtlasTile {
-                texture_id: AtlasTextureId {
-                    index: texture_id,
-                    kind: crate::AtlasTextureKind::Path,
-                },
+                texture_id,
                 tile_id: TileId(tile_id),
                 padding: 0,
                 bounds: crate::Bounds {
@@ -8,14 +8,15 @@ use crate::{
     black, phi, point, quad, rems, size, AbsoluteLength, Bounds, ContentMask, Corners,
     CornersRefinement, CursorStyle, DefiniteLength, DevicePixels, Edges, EdgesRefinement, Font,
     FontFallbacks, FontFeatures, FontStyle, FontWeight, Hsla, Length, Pixels, Point,
-    PointRefinement, Rgba, SharedString, Size, SizeRefinement, Styled, TextRun, WindowContext,
+    PointRefinement, Rems, Rgba, SharedString, Size, SizeRefinement, Styled, TextRun,
+    WindowContext,
 };
 use collections::HashSet;
 use refineable::Refineable;
 use smallvec::SmallVec;
 pub use taffy::style::{
-    AlignContent, AlignItems, AlignSelf, Display, FlexDirection, FlexWrap, JustifyContent,
-    Overflow, Position,
+    AlignContent, AlignItems, AlignSelf, Display, FlexDirection, FlexWrap, GridAutoFlow,
+    JustifyContent, Overflow, Position,
 };
 
 /// Use this struct for interfacing with the 'debug_below' styling from your own elements.
@@ -215,6 +216,22 @@ pub struct Style {
     /// The relative rate at which this item shrinks when it is contracting to fit into space, 1.0 is the default value, and this value must be positive.
     pub flex_shrink: f32,
 
+    // Grid properties
+    /// The sizes of the explicit rows of a grid container
+    pub grid_template_rows: Vec<TrackSize>,
+    /// The sizes of the explicit columns of a grid container
+    pub grid_template_columns: Vec<TrackSize>,
+    /// The sizes of the rows a grid container creates to place items outside of its explicit rows
+    pub grid_auto_rows: Vec<TrackSize>,
+    /// The sizes of the columns a grid container creates to place items outside of its explicit columns
+    pub grid_auto_columns: Vec<TrackSize>,
+    /// How a grid container places items that don't specify their own position
+    pub grid_auto_flow: GridAutoFlow,
+    /// Which rows of its grid container this item occupies
+    pub grid_row: GridPlacement,
+    /// Which columns of its grid container this item occupies
+    pub grid_column: GridPlacement,
+
     /// The fill color of this element
     pub background: Option<Fill>,
 
@@ -275,6 +292,72 @@ pub struct BoxShadow {
     pub spread_radius: Pixels,
 }
 
+/// The size of a row or column of a grid, similar to a CSS track size like `100px` or `1fr`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TrackSize {
+    /// The track has the given length, which may be a fraction of the grid container's size
+    Definite(DefiniteLength),
+    /// The track takes the given share of the space left over once the other tracks are sized,
+    /// but is never smaller than its contents, like the CSS `fr` unit
+    Fr(f32),
+    /// The track takes the given share of the leftover space, even if that is smaller than its
+    /// contents, like `minmax(0, 1fr)` in CSS
+    Flex(f32),
+    /// The track is sized to fit its contents, and may grow into leftover space
+    Auto,
+    /// The track is as small as its contents can be without overflowing
+    MinContent,
+    /// The track is as large as its contents would be without wrapping
+    MaxContent,
+}
+
+impl From<DefiniteLength> for TrackSize {
+    fn from(length: DefiniteLength) -> Self {
+        Self::Definite(length)
+    }
+}
+
+impl From<AbsoluteLength> for TrackSize {
+    fn from(length: AbsoluteLength) -> Self {
+        Self::Definite(length.into())
+    }
+}
+
+impl From<Pixels> for TrackSize {
+    fn from(pixels: Pixels) -> Self {
+        Self::Definite(pixels.into())
+    }
+}
+
+impl From<Rems> for TrackSize {
+    fn from(rems: Rems) -> Self {
+        Self::Definite(rems.into())
+    }
+}
+
+/// The rows or columns occupied by an item of a grid, similar to the CSS `grid-row` and
+/// `grid-column` properties
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridPlacement {
+    /// Where the item starts
+    pub start: GridLine,
+    /// Where the item ends
+    pub end: GridLine,
+}
+
+/// One edge of the placement of a grid item
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GridLine {
+    /// The edge is determined by the other edge, or by the grid's auto-placement
+    #[default]
+    Auto,
+    /// The edge is at the given line of the grid. Lines are numbered from 1, and negative numbers
+    /// count back from the last line of the explicit grid
+    Line(i16),
+    /// The item spans the given number of tracks from its other edge
+    Span(u16),
+}
+
 /// How to handle whitespace in text
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum WhiteSpace {
@@ -692,6 +775,14 @@ impl Default for Style {
             flex_grow: 0.0,
             flex_shrink: 1.0,
             flex_basis: Length::Auto,
+            // Grid
+            grid_template_rows: Vec::new(),
+            grid_template_columns: Vec::new(),
+            grid_auto_rows: Vec::new(),
+            grid_auto_columns: Vec::new(),
+            grid_auto_flow: GridAutoFlow::Row,
+            grid_row: GridPlacement::default(),
+            grid_column: GridPlacement::default(),
             background: None,
             border_color: None,
             corner_radii: Corners::default(),
@@ -1,7 +1,8 @@
 use crate::{
     self as gpui, px, relative, rems, AbsoluteLength, AlignItems, CursorStyle, DefiniteLength,
-    Fill, FlexDirection, FlexWrap, Font, FontStyle, FontWeight, Hsla, JustifyContent, Length,
-    SharedString, StrikethroughStyle, StyleRefinement, WhiteSpace,
+    Fill, FlexDirection, FlexWrap, Font, FontStyle, FontWeight, GridAutoFlow, GridLine,
+    GridPlacement, Hsla, JustifyContent, Length, SharedString, StrikethroughStyle, StyleRefinement,
+    TrackSize, WhiteSpace,
 };
 use crate::{TextStyleRefinement, Truncate};
 pub use gpui_macros::{
@@ -41,6 +42,13 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets the display type of the element to `grid`.
+    /// [Docs](https://tailwindcss.com/docs/display)
+    fn grid(mut self) -> Self {
+        self.style().display = Some(Display::Grid);
+        self
+    }
+
     /// Sets the whitespace of the element to `normal`.
     /// [Docs](https://tailwindcss.com/docs/whitespace#normal)
     fn whitespace_normal(mut self) -> Self {
@@ -313,6 +321,162 @@ pub trait Styled: Sized {
         self
     }
 
+    /// Sets the grid to have the given number of columns of equal width.
+    /// [Docs](https://tailwindcss.com/docs/grid-template-columns)
+    fn grid_cols(mut self, count: u16) -> Self {
+        self.style().grid_template_columns = Some(vec![TrackSize::Flex(1.); count as usize]);
+        self
+    }
+
+    /// Sets the grid to have the given number of rows of equal height.
+    /// [Docs](https://tailwindcss.com/docs/grid-template-rows)
+    fn grid_rows(mut self, count: u16) -> Self {
+        self.style().grid_template_rows = Some(vec![TrackSize::Flex(1.); count as usize]);
+        self
+    }
+
+    /// Sets the sizes of the columns of the grid.
+    /// [Docs](https://tailwindcss.com/docs/grid-template-columns#arbitrary-values)
+    fn grid_template_cols<T: Into<TrackSize>>(
+        mut self,
+        tracks: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.style().grid_template_columns = Some(tracks.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the sizes of the rows of the grid.
+    /// [Docs](https://tailwindcss.com/docs/grid-template-rows#arbitrary-values)
+    fn grid_template_rows<T: Into<TrackSize>>(
+        mut self,
+        tracks: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.style().grid_template_rows = Some(tracks.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the size of the columns the grid creates for items placed outside of its template.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-columns)
+    fn grid_auto_cols(mut self, track: impl Into<TrackSize>) -> Self {
+        self.style().grid_auto_columns = Some(vec![track.into()]);
+        self
+    }
+
+    /// Sets the size of the rows the grid creates for items placed outside of its template.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-rows)
+    fn grid_auto_rows(mut self, track: impl Into<TrackSize>) -> Self {
+        self.style().grid_auto_rows = Some(vec![track.into()]);
+        self
+    }
+
+    /// Sets the grid to place items by filling each row in turn, adding rows as needed.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-flow)
+    fn grid_flow_row(mut self) -> Self {
+        self.style().grid_auto_flow = Some(GridAutoFlow::Row);
+        self
+    }
+
+    /// Sets the grid to place items by filling each column in turn, adding columns as needed.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-flow)
+    fn grid_flow_col(mut self) -> Self {
+        self.style().grid_auto_flow = Some(GridAutoFlow::Column);
+        self
+    }
+
+    /// Sets the grid to place items by row, filling any holes left by larger items.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-flow)
+    fn grid_flow_row_dense(mut self) -> Self {
+        self.style().grid_auto_flow = Some(GridAutoFlow::RowDense);
+        self
+    }
+
+    /// Sets the grid to place items by column, filling any holes left by larger items.
+    /// [Docs](https://tailwindcss.com/docs/grid-auto-flow)
+    fn grid_flow_col_dense(mut self) -> Self {
+        self.style().grid_auto_flow = Some(GridAutoFlow::ColumnDense);
+        self
+    }
+
+    /// Sets the grid item to span the given number of columns.
+    /// [Docs](https://tailwindcss.com/docs/grid-column#spanning-columns)
+    fn col_span(mut self, count: u16) -> Self {
+        self.style().grid_column = Some(GridPlacement {
+            start: GridLine::Span(count),
+            end: GridLine::Auto,
+        });
+        self
+    }
+
+    /// Sets the grid item to span all of the columns of the grid.
+    /// [Docs](https://tailwindcss.com/docs/grid-column#spanning-columns)
+    fn col_span_full(mut self) -> Self {
+        self.style().grid_column = Some(GridPlacement {
+            start: GridLine::Line(1),
+            end: GridLine::Line(-1),
+        });
+        self
+    }
+
+    /// Sets the grid item to start at the given column line, counting from 1.
+    /// [Docs](https://tailwindcss.com/docs/grid-column#starting-and-ending-lines)
+    fn col_start(mut self, line: i16) -> Self {
+        self.style()
+            .grid_column
+            .get_or_insert_with(Default::default)
+            .start = GridLine::Line(line);
+        self
+    }
+
+    /// Sets the grid item to end at the given column line, counting from 1.
+    /// [Docs](https://tailwindcss.com/docs/grid-column#starting-and-ending-lines)
+    fn col_end(mut self, line: i16) -> Self {
+        self.style()
+            .grid_column
+            .get_or_insert_with(Default::default)
+            .end = GridLine::Line(line);
+        self
+    }
+
+    /// Sets the grid item to span the given number of rows.
+    /// [Docs](https://tailwindcss.com/docs/grid-row#spanning-rows)
+    fn row_span(mut self, count: u16) -> Self {
+        self.style().grid_row = Some(GridPlacement {
+            start: GridLine::Span(count),
+            end: GridLine::Auto,
+        });
+        self
+    }
+
+    /// Sets the grid item to span all of the rows of the grid.
+    /// [Docs](https://tailwindcss.com/docs/grid-row#spanning-rows)
+    fn row_span_full(mut self) -> Self {
+        self.style().grid_row = Some(GridPlacement {
+            start: GridLine::Line(1),
+            end: GridLine::Line(-1),
+        });
+        self
+    }
+
+    /// Sets the grid item to start at the given row line, counting from 1.
+    /// [Docs](https://tailwindcss.com/docs/grid-row#starting-and-ending-lines)
+    fn row_start(mut self, line: i16) -> Self {
+        self.style()
+            .grid_row
+            .get_or_insert_with(Default::default)
+            .start = GridLine::Line(line);
+        self
+    }
+
+    /// Sets the grid item to end at the given row line, counting from 1.
+    /// [Docs](https://tailwindcss.com/docs/grid-row#starting-and-ending-lines)
+    fn row_end(mut self, line: i16) -> Self {
+        self.style()
+            .grid_row
+            .get_or_insert_with(Default::default)
+            .end = GridLine::Line(line);
+        self
+    }
+
     /// Sets the background color of the element.
     fn bg<F>(mut self, fill: F) -> Self
     where
@@ -1,12 +1,14 @@
 use crate::{
-    AbsoluteLength, Bounds, DefiniteLength, Edges, Length, Pixels, Point, Size, Style,
-    WindowContext,
+    AbsoluteLength, Bounds, DefiniteLength, Edges, GridLine, GridPlacement, InspectorLayout,
+    Length, Pixels, Point, Size, Style, TrackSize, WindowContext,
 };
 use collections::{FxHashMap, FxHashSet};
 use smallvec::SmallVec;
 use std::fmt::Debug;
 use taffy::{
-    geometry::{Point as TaffyPoint, Rect as TaffyRect, Size as TaffySize},
+    geometry::{
+        Line as TaffyLine, MinMax, Point as TaffyPoint, Rect as TaffyRect, Size as TaffySize,
+    },
     style::AvailableSpace as TaffyAvailableSpace,
     tree::NodeId,
     TaffyTree, TraversePartialTree as _,
@@ -192,6 +194,15 @@ impl TaffyLayoutEngine {
         // println!("compute_layout took {:?}", started_at.elapsed());
     }
 
+    pub fn inspector_layout(&self, id: LayoutId) -> InspectorLayout {
+        let layout = self.taffy.layout(id.into()).expect(EXPECT_MESSAGE);
+        InspectorLayout {
+            order: layout.order,
+            origin: layout.location.into(),
+            size: layout.size.into(),
+        }
+    }
+
     pub fn layout_bounds(&mut self, id: LayoutId) -> Bounds<Pixels> {
         if let Some(layout) = self.absolute_layout_bounds.get(&id).cloned() {
             return layout;
@@ -265,7 +276,69 @@ impl ToTaffy<taffy::style::Style> for Style {
             flex_basis: self.flex_basis.to_taffy(rem_size),
             flex_grow: self.flex_grow,
             flex_shrink: self.flex_shrink,
-            ..Default::default() // Ignore grid properties for now
+            grid_template_rows: self
+                .grid_template_rows
+                .iter()
+                .map(|track| taffy::style::TrackSizingFunction::Single(track.to_taffy(rem_size)))
+                .collect(),
+            grid_template_columns: self
+                .grid_template_columns
+                .iter()
+                .map(|track| taffy::style::TrackSizingFunction::Single(track.to_taffy(rem_size)))
+                .collect(),
+            grid_auto_rows: self
+                .grid_auto_rows
+                .iter()
+                .map(|track| track.to_taffy(rem_size))
+                .collect(),
+            grid_auto_columns: self
+                .grid_auto_columns
+                .iter()
+                .map(|track| track.to_taffy(rem_size))
+                .collect(),
+            grid_auto_flow: self.grid_auto_flow,
+            grid_row: self.grid_row.to_taffy(rem_size),
+            grid_column: self.grid_column.to_taffy(rem_size),
+            ..Default::default()
+        }
+    }
+}
+
+impl ToTaffy<taffy::style::NonRepeatedTrackSizingFunction> for TrackSize {
+    fn to_taffy(&self, rem_size: Pixels) -> taffy::style::NonRepeatedTrackSizingFunction {
+        use taffy::style::{MaxTrackSizingFunction as Max, MinTrackSizingFunction as Min};
+
+        let (min, max) = match self {
+            TrackSize::Definite(length) => {
+                let length: taffy::style::LengthPercentage = length.to_taffy(rem_size);
+                (Min::Fixed(length), Max::Fixed(length))
+            }
+            TrackSize::Fr(fraction) => (Min::Auto, Max::Fraction(*fraction)),
+            TrackSize::Flex(fraction) => (
+                Min::Fixed(taffy::style::LengthPercentage::Length(0.)),
+                Max::Fraction(*fraction),
+            ),
+            TrackSize::Auto => (Min::Auto, Max::Auto),
+            TrackSize::MinContent => (Min::MinContent, Max::MinContent),
+            TrackSize::MaxContent => (Min::MaxContent, Max::MaxContent),
+        };
+        MinMax { min, max }
+    }
+}
+
+impl ToTaffy<TaffyLine<taffy::style::GridPlacement>> for GridPlacement {
+    fn to_taffy(&self, _rem_size: Pixels) -> TaffyLine<taffy::style::GridPlacement> {
+        fn to_taffy(line: GridLine) -> taffy::style::GridPlacement {
+            match line {
+                GridLine::Auto => taffy::style::GridPlacement::Auto,
+                GridLine::Line(index) => taffy::style_helpers::line(index),
+                GridLine::Span(count) => taffy::style::GridPlacement::Span(count),
+            }
+        }
+
+        TaffyLine {
+            start: to_taffy(self.start),
+            end: to_taffy(self.end),
         }
     }
 }
@@ -492,3 +565,85 @@ impl From<Size<Pixels>> for Size<AvailableSpace> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as gpui, div, point, px, size, AnyElement, Bounds, InteractiveElement as _,
+        IntoElement, ParentElement as _, Pixels, Render, Styled as _, TestAppContext, TrackSize,
+        ViewContext,
+    };
+
+    struct TestView(fn() -> AnyElement);
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            (self.0)()
+        }
+    }
+
+    fn item(selector: &'static str) -> gpui::Div {
+        div().debug_selector(|| selector.into())
+    }
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<Pixels> {
+        Bounds::new(point(px(x), px(y)), size(px(width), px(height)))
+    }
+
+    #[gpui::test]
+    fn test_grid_layout(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_| {
+            TestView(|| {
+                div()
+                    .grid()
+                    .w(px(420.))
+                    .grid_template_cols([
+                        TrackSize::from(px(100.)),
+                        TrackSize::Fr(1.),
+                        TrackSize::Fr(2.),
+                    ])
+                    .grid_auto_rows(px(20.))
+                    .gap_x(px(10.))
+                    .gap_y(px(5.))
+                    .child(item("header").col_span_full())
+                    .child(item("a"))
+                    .child(item("b").col_span(2))
+                    .child(item("c").row_span(2))
+                    .child(item("d"))
+                    .child(item("e").row_start(4).col_start(3))
+                    .into_any_element()
+            })
+        });
+
+        assert_eq!(cx.debug_bounds("header"), Some(bounds(0., 0., 420., 20.)));
+        assert_eq!(cx.debug_bounds("a"), Some(bounds(0., 25., 100., 20.)));
+        assert_eq!(cx.debug_bounds("b"), Some(bounds(110., 25., 310., 20.)));
+        assert_eq!(cx.debug_bounds("c"), Some(bounds(0., 50., 100., 45.)));
+        assert_eq!(cx.debug_bounds("d"), Some(bounds(110., 50., 100., 20.)));
+        assert_eq!(cx.debug_bounds("e"), Some(bounds(220., 75., 200., 20.)));
+    }
+
+    #[gpui::test]
+    fn test_grid_equal_columns(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_| {
+            TestView(|| {
+                div()
+                    .grid()
+                    .w(px(300.))
+                    .grid_cols(3)
+                    .grid_flow_col()
+                    .grid_rows(1)
+                    .child(item("wide").w(px(150.)))
+                    .child(item("narrow"))
+                    .child(item("last"))
+                    .into_any_element()
+            })
+        });
+
+        // Columns share the space equally, even when their contents are wider.
+        let mut x = |selector| cx.debug_bounds(selector).unwrap().origin.x;
+        assert_eq!(x("wide"), px(0.));
+        assert_eq!(x("narrow"), px(100.));
+        assert_eq!(x("last"), px(200.));
+    }
+}
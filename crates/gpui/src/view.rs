@@ -103,6 +103,7 @@ impl<V: Render> Element for View<V> {
         _id: Option<&GlobalElementId>,
         cx: &mut WindowContext,
     ) -> (LayoutId, Self::RequestLayoutState) {
+        cx.register_inspected_view(self.entity_id(), type_name::<V>());
         let mut element = self.update(cx, |view, cx| view.render(cx).into_any_element());
         let layout_id = element.request_layout(cx);
         (layout_id, element)
@@ -332,6 +333,7 @@ impl Element for AnyView {
                         && element_state.cache_key.text_style == text_style
                         && !cx.window.dirty_views.contains(&self.entity_id())
                         && !cx.window.refreshing
+                        && cx.window.inspector.is_none()
                     {
                         let prepaint_start = cx.prepaint_index();
                         cx.reuse_prepaint(element_state.prepaint_range.clone());
@@ -457,11 +459,13 @@ impl std::fmt::Debug for AnyWeakView {
 
 mod any_view {
     use crate::{AnyElement, AnyView, IntoElement, Render, WindowContext};
+    use std::any::type_name;
 
     pub(crate) fn render<V: 'static + Render>(
         view: &AnyView,
         cx: &mut WindowContext,
     ) -> AnyElement {
+        cx.register_inspected_view(view.entity_id(), type_name::<V>());
         let view = view.clone().downcast::<V>().unwrap();
         view.update(cx, |view, cx| view.render(cx).into_any_element())
     }
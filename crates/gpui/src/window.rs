@@ -1,19 +1,22 @@
 use crate::{
-    point, prelude::*, px, size, transparent_black, Action, AnyDrag, AnyElement, AnyTooltip,
-    AnyView, AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Bounds, BoxShadow,
-    Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
+    inspector, point, prelude::*, px, size, transparent_black, AccessibilityAction,
+    AccessibilityActionRequest, AccessibilityNode, AccessibilityNodeId, AccessibilityOperation,
+    AccessibilityProperties, AccessibilityRole, AccessibilityTree, Action, AnyDrag, AnyElement,
+    AnyTooltip, AnyView, AppContext, Arena, Asset, AsyncWindowContext, AvailableSpace, Bounds,
+    BoxShadow, Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
     DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
     FileDropEvent, Flatten, FontId, GPUSpecs, Global, GlobalElementId, GlyphId, Hsla, InputHandler,
-    IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent,
-    KeystrokeObserver, LayoutId, LineLayoutIndex, Model, ModelContext, Modifiers,
-    ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent,
-    Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
-    PlatformWindow, Point, PolychromeSprite, PromptLevel, Quad, Render, RenderGlyphParams,
-    RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge, ScaledPixels, Scene,
-    Shadow, SharedString, Size, StrikethroughStyle, Style, SubscriberSet, Subscription,
-    TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement, TransformationMatrix, Underline,
-    UnderlineStyle, View, VisualContext, WeakView, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
+    InspectedElement, Inspector, InspectorElementId, InspectorLayout, IsZero, KeyBinding,
+    KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, KeystrokeObserver, LayoutId,
+    LineLayoutIndex, Model, ModelContext, Modifiers, ModifiersChangedEvent, MonochromeSprite,
+    MouseButton, MouseDownEvent, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PolychromeSprite, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Replay, ResizeEdge, ScaledPixels, Scene, Shadow, SharedString, Size,
+    StrikethroughStyle, Style, StyleRefinement, SubscriberSet, Subscription, TaffyLayoutEngine,
+    Task, TextStyle, TextStyleRefinement, TransformationMatrix, Underline, UnderlineStyle, View,
+    VisualContext, WeakView, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
     SUBPIXEL_VARIANTS,
 };
 use anyhow::{anyhow, Context as _, Result};
@@ -408,6 +411,7 @@ pub(crate) struct Frame {
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
+    pub(crate) accessibility_operations: Vec<AccessibilityOperation>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
 }
@@ -428,6 +432,7 @@ pub(crate) struct PaintIndex {
     mouse_listeners_index: usize,
     input_handlers_index: usize,
     cursor_styles_index: usize,
+    accessibility_operations_index: usize,
     accessed_element_states_index: usize,
     line_layout_index: LineLayoutIndex,
 }
@@ -447,6 +452,7 @@ impl Frame {
             input_handlers: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
+            accessibility_operations: Vec::new(),
 
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
@@ -462,6 +468,7 @@ impl Frame {
         self.input_handlers.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
+        self.accessibility_operations.clear();
         self.hitboxes.clear();
         self.deferred_draws.clear();
         self.focus = None;
@@ -556,6 +563,10 @@ pub struct Window {
     pending_modifier: ModifierState,
     pending_input_observers: SubscriberSet<(), AnyObserver>,
     prompt: Option<RenderablePromptHandle>,
+    title: Option<SharedString>,
+    accessibility_tree: AccessibilityTree,
+    accessibility_focus_ids: FxHashMap<AccessibilityNodeId, FocusId>,
+    pub(crate) inspector: Option<Inspector>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -779,6 +790,28 @@ impl Window {
                     .unwrap_or(DispatchEventResult::default())
             })
         });
+        platform_window.on_accessibility_activated(Box::new({
+            let mut cx = cx.to_async();
+            move || {
+                handle
+                    .update(&mut cx, |_, cx| {
+                        // The platform starts out with an empty tree, so the next frame
+                        // publishes all of it.
+                        cx.window.accessibility_tree = AccessibilityTree::default();
+                        cx.window.accessibility_focus_ids.clear();
+                        cx.refresh();
+                    })
+                    .log_err();
+            }
+        }));
+        platform_window.on_accessibility_action(Box::new({
+            let mut cx = cx.to_async();
+            move |request| {
+                handle
+                    .update(&mut cx, |_, cx| cx.perform_accessibility_action(request))
+                    .log_err();
+            }
+        }));
 
         if let Some(app_id) = app_id {
             platform_window.set_app_id(&app_id);
@@ -834,6 +867,10 @@ impl Window {
             pending_modifier: ModifierState::default(),
             pending_input_observers: SubscriberSet::new(),
             prompt: None,
+            title: None,
+            accessibility_tree: AccessibilityTree::default(),
+            accessibility_focus_ids: FxHashMap::default(),
+            inspector: None,
         })
     }
     fn new_focus_listener(&self, value: AnyWindowFocusListener) -> (Subscription, impl FnOnce()) {
@@ -1282,6 +1319,7 @@ impl<'a> WindowContext<'a> {
 
     /// Updates the window's title at the platform level.
     pub fn set_window_title(&mut self, title: &str) {
+        self.window.title = Some(SharedString::from(title.to_string()));
         self.window.platform_window.set_title(title);
     }
 
@@ -1454,6 +1492,10 @@ impl<'a> WindowContext<'a> {
         let previous_window_active = self.window.rendered_frame.window_active;
         mem::swap(&mut self.window.rendered_frame, &mut self.window.next_frame);
         self.window.next_frame.clear();
+        // Building the tree is only worth it while an assistive technology is listening.
+        if self.is_accessibility_active() {
+            self.update_accessibility_tree();
+        }
         let current_focus_path = self.window.rendered_frame.focus_path();
         let current_window_active = self.window.rendered_frame.window_active;
 
@@ -1500,13 +1542,225 @@ impl<'a> WindowContext<'a> {
         profiling::finish_frame!();
     }
 
+    fn update_accessibility_tree(&mut self) {
+        let root = AccessibilityProperties {
+            role: AccessibilityRole::Window,
+            name: self.window.title.clone(),
+            ..Default::default()
+        };
+        let (mut tree, focus_ids) = AccessibilityTree::build(
+            root,
+            &self.window.rendered_frame.accessibility_operations,
+            self.window.rendered_frame.focus,
+        );
+        if let Some(root) = tree.nodes.get_mut(&tree.root) {
+            root.bounds = Bounds::new(Point::default(), self.window.viewport_size);
+        }
+
+        if let Some(update) = self.window.accessibility_tree.diff(&tree) {
+            self.window
+                .platform_window
+                .update_accessibility_tree(update);
+        }
+        self.window.accessibility_tree = tree;
+        self.window.accessibility_focus_ids = focus_ids;
+    }
+
+    /// Returns the accessibility tree built from the most recently drawn frame, which is empty
+    /// unless an assistive technology is active.
+    pub fn accessibility_tree(&self) -> &AccessibilityTree {
+        &self.window.accessibility_tree
+    }
+
+    /// Opens the inspector, starting out picking an element with the mouse, or closes it and
+    /// discards any style overrides made with it.
+    pub fn toggle_inspector(&mut self) {
+        self.window.inspector = match self.window.inspector.take() {
+            Some(_) => None,
+            None => Some(Inspector::new()),
+        };
+        self.refresh();
+    }
+
+    /// Returns whether the inspector is open in this window.
+    pub fn is_inspector_open(&self) -> bool {
+        self.window.inspector.is_some()
+    }
+
+    /// Returns the elements the inspector recorded in the most recently drawn frame, in the
+    /// order they were drawn.
+    pub fn inspected_elements(&self) -> &[InspectedElement] {
+        self.window
+            .inspector
+            .as_ref()
+            .map_or(&[], |inspector| &inspector.elements)
+    }
+
+    /// Returns the element selected in the inspector.
+    pub fn selected_inspected_element(&self) -> Option<&InspectedElement> {
+        let inspector = self.window.inspector.as_ref()?;
+        inspector.element(inspector.selected?)
+    }
+
+    /// Selects an element in the inspector, which stops picking one with the mouse.
+    pub fn select_inspected_element(&mut self, id: Option<InspectorElementId>) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.selected = id;
+            inspector.picking = false;
+            self.refresh();
+        }
+    }
+
+    /// Sets whether hovering and clicking the window's contents picks the element to inspect.
+    pub fn set_inspector_picking(&mut self, picking: bool) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.picking = picking;
+            inspector.hovered = None;
+            self.refresh();
+        }
+    }
+
+    pub(crate) fn set_inspector_hovered(&mut self, id: Option<InspectorElementId>) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            if inspector.hovered != id {
+                inspector.hovered = id;
+                self.refresh();
+            }
+        }
+    }
+
+    /// Refines the style of an inspected element, on top of its own style and any overrides
+    /// made previously, until the inspector is closed.
+    pub fn override_inspected_style(&mut self, id: InspectorElementId, style: &StyleRefinement) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.override_style(id, style);
+            self.refresh();
+        }
+    }
+
+    /// Restores the original style of an inspected element.
+    pub fn clear_inspected_style_overrides(&mut self, id: InspectorElementId) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.clear_style_overrides(id);
+            self.refresh();
+        }
+    }
+
+    /// Assigns an inspector id to an element being laid out while the inspector is open,
+    /// applying the style overrides made for it.
+    pub(crate) fn inspect_element(
+        &mut self,
+        source_location: Option<core::panic::Location<'static>>,
+        style: &mut StyleRefinement,
+    ) -> Option<InspectorElementId> {
+        let inspector = self
+            .window
+            .inspector
+            .as_mut()
+            .filter(|inspector| inspector.is_recording())?;
+        let id = inspector.next_element_id(&self.window.element_id_stack, source_location);
+        if let Some(style_override) = inspector.style_override(id) {
+            style.refine(style_override);
+        }
+        Some(id)
+    }
+
+    /// Records an inspected element during prepaint. Elements recorded until the matching call
+    /// to [`Self::pop_inspected_element`] are nested within it.
+    pub(crate) fn push_inspected_element(
+        &mut self,
+        id: InspectorElementId,
+        source_location: Option<core::panic::Location<'static>>,
+        element_id: Option<ElementId>,
+        bounds: Bounds<Pixels>,
+        style: &Style,
+        layout_id: Option<LayoutId>,
+    ) {
+        let layout = layout_id.map_or_else(InspectorLayout::default, |layout_id| {
+            self.window
+                .layout_engine
+                .as_ref()
+                .unwrap()
+                .inspector_layout(layout_id)
+        });
+        let view_id = self.parent_view_id();
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            let view_type = view_id.and_then(|view_id| inspector.view_type(view_id));
+            inspector.push_element(InspectedElement {
+                id,
+                depth: 0,
+                source_location,
+                view_type,
+                element_id,
+                bounds,
+                style: style.clone(),
+                layout,
+            });
+        }
+    }
+
+    pub(crate) fn pop_inspected_element(&mut self) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.pop_element();
+        }
+    }
+
+    pub(crate) fn register_inspected_view(&mut self, view_id: EntityId, type_name: &'static str) {
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.register_view(view_id, type_name);
+        }
+    }
+
+    fn perform_accessibility_action(&mut self, request: AccessibilityActionRequest) {
+        let Some(node) = self.window.accessibility_tree.node(request.target) else {
+            return;
+        };
+
+        match request.action {
+            AccessibilityAction::Focus => {
+                if let Some(handle) = self
+                    .window
+                    .accessibility_focus_ids
+                    .get(&request.target)
+                    .and_then(|id| FocusHandle::for_id(*id, &self.window.focus_handles))
+                {
+                    self.focus(&handle);
+                }
+            }
+            AccessibilityAction::Click => {
+                let position = node.bounds.center();
+                let modifiers = self.window.modifiers;
+                self.dispatch_event(PlatformInput::MouseDown(MouseDownEvent {
+                    button: MouseButton::Left,
+                    position,
+                    modifiers,
+                    click_count: 1,
+                    first_mouse: false,
+                }));
+                self.dispatch_event(PlatformInput::MouseUp(MouseUpEvent {
+                    button: MouseButton::Left,
+                    position,
+                    modifiers,
+                    click_count: 1,
+                }));
+            }
+        }
+    }
+
     fn draw_roots(&mut self) {
         self.window.draw_phase = DrawPhase::Prepaint;
         self.window.tooltip_bounds.take();
 
+        // When the inspector is open, the root view makes room for its panel.
+        let mut root_size = self.window.viewport_size;
+        if let Some(inspector) = self.window.inspector.as_mut() {
+            inspector.begin_frame();
+            root_size.width = (root_size.width - inspector::PANEL_WIDTH).max(Pixels::ZERO);
+        }
+
         // Layout all root elements.
         let mut root_element = self.window.root_view.as_ref().unwrap().clone().into_any();
-        root_element.prepaint_as_root(Point::default(), self.window.viewport_size.into(), self);
+        root_element.prepaint_as_root(Point::default(), root_size.into(), self);
 
         let mut sorted_deferred_draws =
             (0..self.window.next_frame.deferred_draws.len()).collect::<SmallVec<[_; 8]>>();
@@ -1531,6 +1785,8 @@ impl<'a> WindowContext<'a> {
             tooltip_element = self.prepaint_tooltip();
         }
 
+        let mut inspector_element = self.prepaint_inspector(root_size.width);
+
         self.window.mouse_hit_test = self.window.next_frame.hit_test(self.window.mouse_position);
 
         // Now actually paint the elements.
@@ -1539,6 +1795,10 @@ impl<'a> WindowContext<'a> {
 
         self.paint_deferred_draws(&sorted_deferred_draws);
 
+        if let Some(inspector_element) = inspector_element.as_mut() {
+            self.paint_inspector(inspector_element, root_size.width);
+        }
+
         if let Some(mut prompt_element) = prompt_element {
             prompt_element.paint(self);
         } else if let Some(mut drag_element) = active_drag_element {
@@ -1548,6 +1808,58 @@ impl<'a> WindowContext<'a> {
         }
     }
 
+    fn prepaint_inspector(&mut self, panel_x: Pixels) -> Option<AnyElement> {
+        let inspector = self.window.inspector.as_mut()?;
+        inspector.finish_frame();
+        let mut element = inspector.render_panel(self.window.rem_size);
+        let panel_size = size(
+            self.window.viewport_size.width - panel_x,
+            self.window.viewport_size.height,
+        );
+        element.prepaint_as_root(point(panel_x, Pixels::ZERO), panel_size.into(), self);
+        Some(element)
+    }
+
+    fn paint_inspector(&mut self, panel: &mut AnyElement, panel_x: Pixels) {
+        if let Some(inspector) = self.window.inspector.take() {
+            inspector.paint_highlight(self);
+            self.window.inspector = Some(inspector);
+        }
+        panel.paint(self);
+
+        // While picking, clicks select the element under the mouse instead of reaching it.
+        if !self
+            .window
+            .inspector
+            .as_ref()
+            .is_some_and(|inspector| inspector.picking)
+        {
+            return;
+        }
+        self.on_mouse_event(move |event: &MouseMoveEvent, phase, cx| {
+            if phase.capture() && event.position.x < panel_x {
+                if let Some(inspector) = cx.window.inspector.as_mut() {
+                    let hovered = inspector.element_at(event.position);
+                    if hovered != inspector.hovered {
+                        inspector.hovered = hovered;
+                        cx.refresh();
+                    }
+                }
+            }
+        });
+        self.on_mouse_event(move |event: &MouseDownEvent, phase, cx| {
+            if phase.capture() && event.position.x < panel_x {
+                if let Some(inspector) = cx.window.inspector.as_mut() {
+                    inspector.selected = inspector.element_at(event.position);
+                    inspector.hovered = None;
+                    inspector.picking = false;
+                    cx.stop_propagation();
+                    cx.refresh();
+                }
+            }
+        });
+    }
+
     fn prepaint_tooltip(&mut self) -> Option<AnyElement> {
         let tooltip_request = self.window.next_frame.tooltip_requests.last().cloned()?;
         let tooltip_request = tooltip_request.unwrap();
@@ -1726,6 +2038,7 @@ impl<'a> WindowContext<'a> {
             mouse_listeners_index: self.window.next_frame.mouse_listeners.len(),
             input_handlers_index: self.window.next_frame.input_handlers.len(),
             cursor_styles_index: self.window.next_frame.cursor_styles.len(),
+            accessibility_operations_index: self.window.next_frame.accessibility_operations.len(),
             accessed_element_states_index: self.window.next_frame.accessed_element_states.len(),
             line_layout_index: self.window.text_system.layout_index(),
         }
@@ -1752,6 +2065,14 @@ impl<'a> WindowContext<'a> {
                 .iter_mut()
                 .map(|listener| listener.take()),
         );
+        window.next_frame.accessibility_operations.extend(
+            window.rendered_frame.accessibility_operations[range
+                .start
+                .accessibility_operations_index
+                ..range.end.accessibility_operations_index]
+                .iter()
+                .cloned(),
+        );
         window.next_frame.accessed_element_states.extend(
             window.rendered_frame.accessed_element_states[range.start.accessed_element_states_index
                 ..range.end.accessed_element_states_index]
@@ -1767,6 +2088,70 @@ impl<'a> WindowContext<'a> {
         );
     }
 
+    /// Returns whether an assistive technology is listening to this window. Elements can skip
+    /// describing themselves with [`Self::with_accessibility_node`] otherwise.
+    pub fn is_accessibility_active(&self) -> bool {
+        self.window.platform_window.is_accessibility_active()
+    }
+
+    /// Paints the elements drawn by the given function as children of a node in the window's
+    /// accessibility tree. The node's identifier is derived from the current element's ID, so
+    /// that it's stable across frames. This method should only be called as part of the paint
+    /// phase of element drawing.
+    pub fn with_accessibility_node<R>(
+        &mut self,
+        node: AccessibilityNode,
+        focus_handle: Option<&FocusHandle>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        debug_assert_eq!(
+            self.window.draw_phase,
+            DrawPhase::Paint,
+            "this method can only be called during paint"
+        );
+        if !self.is_accessibility_active() {
+            return f(self);
+        }
+
+        let id = (!self.window.element_id_stack.is_empty()).then(|| {
+            AccessibilityNodeId::for_element(&GlobalElementId(self.window.element_id_stack.clone()))
+        });
+        self.window
+            .next_frame
+            .accessibility_operations
+            .push(AccessibilityOperation::Push {
+                id,
+                node,
+                focus_id: focus_handle.map(|handle| handle.id),
+            });
+        let result = f(self);
+        self.window
+            .next_frame
+            .accessibility_operations
+            .push(AccessibilityOperation::Pop);
+        result
+    }
+
+    /// Exposes text painted in the window to assistive technologies. The text names the
+    /// enclosing accessibility node if its role is named from its contents, and is otherwise
+    /// added to it as a label. This method should only be called as part of the paint phase
+    /// of element drawing.
+    pub fn paint_accessibility_text(&mut self, text: SharedString, bounds: Bounds<Pixels>) {
+        debug_assert_eq!(
+            self.window.draw_phase,
+            DrawPhase::Paint,
+            "this method can only be called during paint"
+        );
+        if !self.is_accessibility_active() {
+            return;
+        }
+
+        self.window
+            .next_frame
+            .accessibility_operations
+            .push(AccessibilityOperation::Text { text, bounds });
+    }
+
     /// Push a text style onto the stack, and call a function with that style active.
     /// Use [`AppContext::text_style`] to get the current, combined text style. This method
     /// should only be called as part of element drawing.
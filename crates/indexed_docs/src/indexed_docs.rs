@@ -4,6 +4,8 @@ mod registry;
 mod store;
 
 pub use crate::extension_indexed_docs_provider::ExtensionIndexedDocsProvider;
+pub use crate::providers::npm::*;
+pub use crate::providers::python::*;
 pub use crate::providers::rustdoc::*;
 pub use crate::registry::*;
 pub use crate::store::*;
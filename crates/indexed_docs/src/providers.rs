@@ -1 +1,59 @@
+pub mod npm;
+pub mod python;
 pub mod rustdoc;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use fs::Fs;
+use futures::StreamExt;
+
+/// The largest source file that will be indexed.
+const MAX_SOURCE_FILE_SIZE: u64 = 512 * 1024;
+
+/// Returns the files beneath `dir` that satisfy `include_file`, in a stable order.
+///
+/// Symlinked directories are not followed, and directories for which
+/// `skip_dir` returns `true` are not descended into.
+async fn find_files(
+    fs: &dyn Fs,
+    dir: &Path,
+    skip_dir: impl Fn(&str) -> bool,
+    include_file: impl Fn(&str) -> bool,
+    limit: usize,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs_to_visit = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs_to_visit.pop() {
+        let mut entries = fs.read_dir(&dir).await?;
+        let mut child_dirs = Vec::new();
+        while let Some(entry) = entries.next().await {
+            let path = entry?;
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(metadata) = fs.metadata(&path).await? else {
+                continue;
+            };
+
+            if metadata.is_dir {
+                if !metadata.is_symlink && !skip_dir(name) {
+                    child_dirs.push(path);
+                }
+            } else if include_file(name) && metadata.len <= MAX_SOURCE_FILE_SIZE {
+                files.push(path);
+                if files.len() >= limit {
+                    files.sort();
+                    return Ok(files);
+                }
+            }
+        }
+
+        // Visit the children in order, by pushing them in reverse.
+        child_dirs.sort_by(|a, b| b.cmp(a));
+        dirs_to_visit.extend(child_dirs);
+    }
+
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,355 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use fs::Fs;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use crate::providers::find_files;
+use crate::{IndexedDocsDatabase, IndexedDocsProvider, PackageName, ProviderId};
+
+/// The most declaration files that will be indexed for a single package.
+const MAX_DECLARATION_FILES: usize = 500;
+
+/// Indexes the READMEs and TypeScript declarations of the packages installed in
+/// a project's `node_modules`.
+pub struct LocalNpmDocsProvider {
+    fs: Arc<dyn Fs>,
+    project_root: PathBuf,
+}
+
+impl LocalNpmDocsProvider {
+    pub fn id() -> ProviderId {
+        ProviderId("npm".into())
+    }
+
+    pub fn new(fs: Arc<dyn Fs>, project_root: PathBuf) -> Self {
+        Self { fs, project_root }
+    }
+
+    async fn find_readme(&self, package_dir: &Path) -> Result<Option<String>> {
+        let mut entries = self.fs.read_dir(package_dir).await?;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let is_readme = entry
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.to_lowercase().starts_with("readme"));
+            if is_readme {
+                if let Ok(readme) = self.fs.load(&entry).await {
+                    return Ok(Some(readme));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn find_declaration_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        find_files(
+            self.fs.as_ref(),
+            dir,
+            |dir| dir == "node_modules" || dir.starts_with('.'),
+            |file| {
+                [".d.ts", ".d.mts", ".d.cts"]
+                    .iter()
+                    .any(|ext| file.ends_with(ext))
+            },
+            MAX_DECLARATION_FILES,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl IndexedDocsProvider for LocalNpmDocsProvider {
+    fn id(&self) -> ProviderId {
+        Self::id()
+    }
+
+    fn database_path(&self) -> PathBuf {
+        paths::support_dir().join("docs/npm/npm-db.0.mdb")
+    }
+
+    async fn suggest_packages(&self) -> Result<Vec<PackageName>> {
+        let mut packages = BTreeSet::new();
+        for (file_name, parse) in [
+            (
+                "package-lock.json",
+                packages_in_package_lock as fn(&str) -> Result<Vec<String>>,
+            ),
+            ("yarn.lock", packages_in_yarn_lock),
+            ("pnpm-lock.yaml", packages_in_pnpm_lock),
+        ] {
+            let Ok(contents) = self.fs.load(&self.project_root.join(file_name)).await else {
+                continue;
+            };
+            packages.extend(parse(&contents)?);
+        }
+
+        // Without a lockfile, suggest the project's direct dependencies.
+        if packages.is_empty() {
+            if let Ok(package_json) = self.fs.load(&self.project_root.join("package.json")).await {
+                packages.extend(packages_in_package_json(&package_json)?);
+            }
+        }
+
+        Ok(packages
+            .iter()
+            .map(|package| PackageName::from(package.as_str()))
+            .collect())
+    }
+
+    async fn index(&self, package: PackageName, database: Arc<IndexedDocsDatabase>) -> Result<()> {
+        let node_modules = self.project_root.join("node_modules");
+        let package_dir = node_modules.join(package.as_ref());
+        if !self.fs.is_dir(&package_dir).await {
+            bail!("no package named '{package}' in node_modules. try running `npm install`");
+        }
+
+        let readme = match self.find_readme(&package_dir).await? {
+            Some(readme) => readme,
+            None => format!("# {package}\n"),
+        };
+        database.insert(package.to_string(), readme).await?;
+
+        // Fall back to the types from DefinitelyTyped, which are published as
+        // `@types/{name}`, or `@types/{scope}__{name}` for scoped packages.
+        let mut declarations_dir = package_dir;
+        let mut declaration_files = self.find_declaration_files(&declarations_dir).await?;
+        if declaration_files.is_empty() {
+            let types_package = package.trim_start_matches('@').replace('/', "__");
+            declarations_dir = node_modules.join("@types").join(types_package);
+            if self.fs.is_dir(&declarations_dir).await {
+                declaration_files = self.find_declaration_files(&declarations_dir).await?;
+            }
+        }
+
+        for file in declaration_files {
+            let Ok(source) = self.fs.load(&file).await else {
+                continue;
+            };
+            let Some(module) = file
+                .strip_prefix(&declarations_dir)
+                .ok()
+                .and_then(|path| path.to_str())
+            else {
+                continue;
+            };
+            let module = module
+                .replace('\\', "/")
+                .trim_end_matches(".ts")
+                .trim_end_matches(".mts")
+                .trim_end_matches(".cts")
+                .trim_end_matches(".d")
+                .to_string();
+
+            database
+                .insert(
+                    format!("{package}::{module}"),
+                    convert_declarations_to_markdown(&module, &source),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn convert_declarations_to_markdown(module: &str, source: &str) -> String {
+    let source = source
+        .lines()
+        .filter(|line| !line.starts_with("//# sourceMappingURL="))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("# `{module}`\n\n```typescript\n{}\n```\n", source.trim())
+}
+
+#[derive(Deserialize)]
+struct PackageLock {
+    /// The installed packages, keyed by their path, in lockfile versions 2 and 3.
+    #[serde(default)]
+    packages: serde_json::Map<String, serde_json::Value>,
+    /// The installed packages, keyed by their name, in lockfile version 1.
+    #[serde(default)]
+    dependencies: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Returns the packages in a `package-lock.json` file.
+fn packages_in_package_lock(contents: &str) -> Result<Vec<String>> {
+    let lockfile: PackageLock = serde_json::from_str(contents)?;
+    Ok(lockfile
+        .packages
+        .into_iter()
+        .filter_map(|(path, _)| {
+            let (_, name) = path.rsplit_once("node_modules/")?;
+            Some(name.to_string())
+        })
+        .chain(lockfile.dependencies.into_iter().map(|(name, _)| name))
+        .collect())
+}
+
+/// Returns the packages in a `yarn.lock` file, whose entries are keyed by one
+/// or more comma-separated `{name}@{range}` descriptors.
+fn packages_in_yarn_lock(contents: &str) -> Result<Vec<String>> {
+    Ok(contents
+        .lines()
+        .filter(|line| !line.starts_with([' ', '#']))
+        .filter_map(|line| line.strip_suffix(':'))
+        .flat_map(|descriptors| descriptors.split(", "))
+        .filter_map(|descriptor| package_name_from_descriptor(descriptor.trim_matches('"')))
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Returns the packages in a `pnpm-lock.yaml` file, whose `packages` are keyed
+/// by `/{name}@{version}` (or `/{name}/{version}` before lockfile version 6).
+fn packages_in_pnpm_lock(contents: &str) -> Result<Vec<String>> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+    for line in contents.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+
+        let Some(key) = line
+            .strip_prefix("  ")
+            .filter(|key| !key.starts_with(' '))
+            .and_then(|key| key.trim_end().strip_suffix(':'))
+        else {
+            continue;
+        };
+        if !in_packages {
+            continue;
+        }
+
+        let key = key.trim_matches(['\'', '"']).trim_start_matches('/');
+        let name = package_name_from_descriptor(key).or_else(|| {
+            let (name, _version) = key.rsplit_once('/')?;
+            Some(name)
+        });
+        packages.extend(name.map(ToString::to_string));
+    }
+    Ok(packages)
+}
+
+/// Returns the name in a `{name}@{version}` descriptor, where the name may
+/// itself start with an `@` if the package is scoped.
+fn package_name_from_descriptor(descriptor: &str) -> Option<&str> {
+    let version_start = descriptor.get(1..)?.find('@')? + 1;
+    Some(&descriptor[..version_start])
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJson {
+    #[serde(default)]
+    dependencies: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    dev_dependencies: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Returns the dependencies in a `package.json` file.
+fn packages_in_package_json(contents: &str) -> Result<Vec<String>> {
+    let package_json: PackageJson = serde_json::from_str(contents)?;
+    Ok(package_json
+        .dependencies
+        .into_iter()
+        .chain(package_json.dev_dependencies)
+        .map(|(name, _)| name)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_packages_in_lockfiles() {
+        let package_lock = indoc! {r#"
+            {
+                "name": "app",
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {"name": "app"},
+                    "node_modules/@babel/core": {"version": "7.25.2"},
+                    "node_modules/lodash": {"version": "4.17.21"},
+                    "node_modules/a/node_modules/ms": {"version": "2.1.3"}
+                }
+            }
+        "#};
+        assert_eq!(
+            packages_in_package_lock(package_lock).unwrap(),
+            ["@babel/core", "lodash", "ms"]
+        );
+
+        let yarn_lock = indoc! {r#"
+            # THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+            # yarn lockfile v1
+
+
+            "@babel/core@^7.0.0", "@babel/core@^7.12.3":
+              version "7.25.2"
+
+            lodash@^4.17.21:
+              version "4.17.21"
+              dependencies:
+                ms "^2.1.3"
+        "#};
+        assert_eq!(
+            packages_in_yarn_lock(yarn_lock).unwrap(),
+            ["@babel/core", "@babel/core", "lodash"]
+        );
+
+        let pnpm_lock = indoc! {r#"
+            lockfileVersion: '9.0'
+
+            importers:
+              .:
+                dependencies:
+                  react:
+                    specifier: ^18.3.1
+                    version: 18.3.1
+
+            packages:
+
+              '@types/react@18.3.11':
+                resolution: {integrity: sha512-abc}
+
+              react@18.3.1:
+                resolution: {integrity: sha512-def}
+              /loose-envify/1.4.0:
+                resolution: {integrity: sha512-ghi}
+
+            snapshots:
+              react@18.3.1:
+                dependencies:
+                  loose-envify: 1.4.0
+        "#};
+        assert_eq!(
+            packages_in_pnpm_lock(pnpm_lock).unwrap(),
+            ["@types/react", "react", "loose-envify"]
+        );
+
+        let package_json = indoc! {r#"
+            {
+                "name": "app",
+                "dependencies": {"react": "^18.3.1"},
+                "devDependencies": {"typescript": "^5.6.3"}
+            }
+        "#};
+        assert_eq!(
+            packages_in_package_json(package_json).unwrap(),
+            ["react", "typescript"]
+        );
+    }
+}
@@ -0,0 +1,462 @@
+mod to_markdown;
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use fs::Fs;
+use futures::future::Shared;
+use futures::StreamExt;
+use gpui::Task;
+use serde::Deserialize;
+pub use to_markdown::convert_python_module_to_markdown;
+
+use crate::providers::find_files;
+use crate::{IndexedDocsDatabase, IndexedDocsProvider, PackageName, ProviderId};
+
+/// The most modules that will be indexed for a single package.
+const MAX_MODULES: usize = 2000;
+
+/// Indexes the docstrings of the Python packages installed in a project's
+/// environment.
+pub struct LocalPythonDocsProvider {
+    fs: Arc<dyn Fs>,
+    project_root: PathBuf,
+    /// The path to the interpreter of the project's active toolchain.
+    interpreter: Shared<Task<Option<PathBuf>>>,
+}
+
+impl LocalPythonDocsProvider {
+    pub fn id() -> ProviderId {
+        ProviderId("python".into())
+    }
+
+    pub fn new(
+        fs: Arc<dyn Fs>,
+        project_root: PathBuf,
+        interpreter: Shared<Task<Option<PathBuf>>>,
+    ) -> Self {
+        Self {
+            fs,
+            project_root,
+            interpreter,
+        }
+    }
+
+    /// Returns the `site-packages` directories of the active environment, or of
+    /// a virtual environment in the project if no toolchain is active.
+    async fn site_packages_dirs(&self) -> Result<Vec<PathBuf>> {
+        let environment_roots = match self.interpreter.clone().await {
+            // Interpreters are installed in `bin` (or `Scripts` on Windows).
+            Some(interpreter) => interpreter
+                .parent()
+                .and_then(|bin| bin.parent())
+                .map(|root| vec![root.to_path_buf()])
+                .unwrap_or_default(),
+            None => vec![
+                self.project_root.join(".venv"),
+                self.project_root.join("venv"),
+            ],
+        };
+
+        let mut dirs = Vec::new();
+        for root in environment_roots {
+            let windows_site_packages = root.join("Lib").join("site-packages");
+            if self.fs.is_dir(&windows_site_packages).await {
+                dirs.push(windows_site_packages);
+            }
+
+            let Ok(mut lib_dirs) = self.fs.read_dir(&root.join("lib")).await else {
+                continue;
+            };
+            while let Some(lib_dir) = lib_dirs.next().await {
+                let lib_dir = lib_dir?;
+                let is_python_dir = lib_dir
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with("python"));
+                if !is_python_dir {
+                    continue;
+                }
+                for name in ["site-packages", "dist-packages"] {
+                    let dir = lib_dir.join(name);
+                    if self.fs.is_dir(&dir).await {
+                        dirs.push(dir);
+                    }
+                }
+            }
+        }
+
+        if dirs.is_empty() {
+            bail!("no Python environment found. select a Python toolchain for the project");
+        }
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    /// Returns the `.dist-info` directories of the installed distributions.
+    async fn installed_distributions(&self, site_packages: &Path) -> Result<Vec<PathBuf>> {
+        let mut distributions = Vec::new();
+        let mut entries = self.fs.read_dir(site_packages).await?;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            if entry
+                .extension()
+                .map_or(false, |extension| extension == "dist-info")
+            {
+                distributions.push(entry);
+            }
+        }
+        distributions.sort();
+        Ok(distributions)
+    }
+}
+
+#[async_trait]
+impl IndexedDocsProvider for LocalPythonDocsProvider {
+    fn id(&self) -> ProviderId {
+        Self::id()
+    }
+
+    fn database_path(&self) -> PathBuf {
+        paths::support_dir().join("docs/python/python-db.0.mdb")
+    }
+
+    async fn suggest_packages(&self) -> Result<Vec<PackageName>> {
+        let mut packages = BTreeSet::new();
+        for (file_name, parse) in [
+            (
+                "uv.lock",
+                packages_in_toml_lockfile as fn(&str) -> Result<Vec<String>>,
+            ),
+            ("poetry.lock", packages_in_toml_lockfile),
+            ("pdm.lock", packages_in_toml_lockfile),
+            ("Pipfile.lock", packages_in_pipfile_lock),
+            ("requirements.txt", packages_in_requirements),
+        ] {
+            let Ok(contents) = self.fs.load(&self.project_root.join(file_name)).await else {
+                continue;
+            };
+            packages.extend(parse(&contents)?);
+        }
+
+        // Without a lockfile, suggest whatever is installed.
+        if packages.is_empty() {
+            for site_packages in self.site_packages_dirs().await? {
+                for distribution in self.installed_distributions(&site_packages).await? {
+                    if let Some(name) = distribution_name(&distribution) {
+                        packages.insert(name.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(packages
+            .iter()
+            .map(|package| PackageName::from(package.as_str()))
+            .collect())
+    }
+
+    async fn index(&self, package: PackageName, database: Arc<IndexedDocsDatabase>) -> Result<()> {
+        let normalized_package = normalize_package_name(&package);
+
+        let mut metadata = None;
+        let mut modules = Vec::new();
+        for site_packages in self.site_packages_dirs().await? {
+            let mut top_level_modules = Vec::new();
+            for distribution in self.installed_distributions(&site_packages).await? {
+                if distribution_name(&distribution).map(normalize_package_name)
+                    != Some(normalized_package.clone())
+                {
+                    continue;
+                }
+
+                metadata = self.fs.load(&distribution.join("METADATA")).await.ok();
+                if let Ok(top_level) = self.fs.load(&distribution.join("top_level.txt")).await {
+                    top_level_modules.extend(
+                        top_level
+                            .lines()
+                            .map(str::trim)
+                            .filter(|module| !module.is_empty() && !module.starts_with('_'))
+                            .map(ToString::to_string),
+                    );
+                }
+            }
+            if top_level_modules.is_empty() {
+                top_level_modules.push(normalized_package.replace('-', "_"));
+            }
+
+            for module in top_level_modules {
+                let package_dir = site_packages.join(&module);
+                if self.fs.is_dir(&package_dir).await {
+                    let files = find_files(
+                        self.fs.as_ref(),
+                        &package_dir,
+                        |dir| dir == "__pycache__" || dir == "tests" || dir.starts_with('.'),
+                        |file| file.ends_with(".py"),
+                        MAX_MODULES,
+                    )
+                    .await?;
+                    for file in files {
+                        if let Some(module_path) = module_path(&site_packages, &file) {
+                            modules.push((module_path, file));
+                        }
+                    }
+                } else {
+                    let file = site_packages.join(format!("{module}.py"));
+                    if self.fs.is_file(&file).await {
+                        modules.push((module, file));
+                    }
+                }
+            }
+
+            if !modules.is_empty() {
+                break;
+            }
+        }
+
+        if modules.is_empty() && metadata.is_none() {
+            bail!("no installed Python package named '{package}'");
+        }
+
+        let mut package_markdown = metadata
+            .as_deref()
+            .map(convert_metadata_to_markdown)
+            .unwrap_or_else(|| format!("# {package}\n"));
+        let mut documented_modules = Vec::new();
+        for (module, file) in modules {
+            let Ok(source) = self.fs.load(&file).await else {
+                continue;
+            };
+            if let Some(markdown) = convert_python_module_to_markdown(&module, &source) {
+                database
+                    .insert(format!("{package}::{module}"), markdown)
+                    .await?;
+                documented_modules.push(module);
+            }
+        }
+
+        if !documented_modules.is_empty() {
+            package_markdown.push_str("\n## Modules\n\n");
+            for module in documented_modules {
+                writeln!(package_markdown, "- `{module}`").ok();
+            }
+        }
+        database
+            .insert(package.to_string(), package_markdown)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the dotted path of the module defined by the given file.
+fn module_path(site_packages: &Path, file: &Path) -> Option<String> {
+    let relative_path = file.strip_prefix(site_packages).ok()?.with_extension("");
+    let mut components = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    if components.last() == Some(&"__init__") {
+        components.pop();
+    }
+    if components
+        .iter()
+        .skip(1)
+        .any(|component| component.starts_with('_'))
+    {
+        return None;
+    }
+    Some(components.join("."))
+}
+
+/// Returns the name of the distribution installed in the given `.dist-info`
+/// directory, which is named `{name}-{version}.dist-info`.
+fn distribution_name(dist_info: &Path) -> Option<&str> {
+    let stem = dist_info.file_stem()?.to_str()?;
+    Some(stem.rsplit_once('-').map_or(stem, |(name, _version)| name))
+}
+
+/// Normalizes a package name, as described in PEP 503.
+fn normalize_package_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Converts the core metadata of a distribution to Markdown, using its name,
+/// summary and long description.
+fn convert_metadata_to_markdown(metadata: &str) -> String {
+    let (headers, description) = metadata
+        .split_once("\n\n")
+        .unwrap_or((metadata, Default::default()));
+    let header = |name: &str| {
+        headers.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key == name).then(|| value.trim())
+        })
+    };
+
+    let mut markdown = format!("# {}\n", header("Name").unwrap_or_default());
+    if let Some(summary) = header("Summary") {
+        write!(markdown, "\n{summary}\n").ok();
+    }
+    let description = description.trim();
+    if !description.is_empty() {
+        write!(markdown, "\n{description}\n").ok();
+    }
+    markdown
+}
+
+#[derive(Deserialize)]
+struct TomlLockfile {
+    #[serde(default)]
+    package: Vec<TomlLockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct TomlLockedPackage {
+    name: String,
+}
+
+/// Returns the packages in a `uv.lock`, `poetry.lock` or `pdm.lock` file.
+fn packages_in_toml_lockfile(contents: &str) -> Result<Vec<String>> {
+    let lockfile: TomlLockfile = toml::from_str(contents)?;
+    Ok(lockfile
+        .package
+        .into_iter()
+        .map(|package| package.name)
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct PipfileLock {
+    #[serde(default)]
+    default: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    develop: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Returns the packages in a `Pipfile.lock` file.
+fn packages_in_pipfile_lock(contents: &str) -> Result<Vec<String>> {
+    let lockfile: PipfileLock = serde_json::from_str(contents)?;
+    Ok(lockfile
+        .default
+        .into_iter()
+        .chain(lockfile.develop)
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Returns the packages required in a `requirements.txt` file, ignoring options
+/// and requirements given as URLs.
+fn packages_in_requirements(contents: &str) -> Result<Vec<String>> {
+    Ok(contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-') && !line.contains("://"))
+        .filter_map(|line| {
+            let name_len = line
+                .find(|c: char| !c.is_alphanumeric() && !matches!(c, '-' | '_' | '.'))
+                .unwrap_or(line.len());
+            let name = &line[..name_len];
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_packages_in_lockfiles() {
+        let uv_lock = indoc! {r#"
+            version = 1
+            requires-python = ">=3.12"
+
+            [[package]]
+            name = "certifi"
+            version = "2024.8.30"
+            source = { registry = "https://pypi.org/simple" }
+
+            [[package]]
+            name = "requests"
+            version = "2.32.3"
+            dependencies = [
+                { name = "certifi" },
+            ]
+        "#};
+        assert_eq!(
+            packages_in_toml_lockfile(uv_lock).unwrap(),
+            ["certifi", "requests"]
+        );
+
+        let pipfile_lock = indoc! {r#"
+            {
+                "_meta": {"hash": {"sha256": "abc"}},
+                "default": {"flask": {"version": "==3.0.3"}},
+                "develop": {"pytest": {"version": "==8.3.3"}}
+            }
+        "#};
+        assert_eq!(
+            packages_in_pipfile_lock(pipfile_lock).unwrap(),
+            ["flask", "pytest"]
+        );
+
+        let requirements = indoc! {r#"
+            # Runtime dependencies.
+            -r base.txt
+            --index-url https://example.com/simple
+            Django>=4.2,<5
+            uvicorn[standard]==0.30.6  # the server
+            zope.interface
+            git+https://github.com/psf/black.git
+            typing_extensions ; python_version < "3.11"
+        "#};
+        assert_eq!(
+            packages_in_requirements(requirements).unwrap(),
+            ["Django", "uvicorn", "zope.interface", "typing_extensions"]
+        );
+    }
+
+    #[test]
+    fn test_distribution_names() {
+        let dist_info = Path::new("/venv/lib/python3.12/site-packages/PyYAML-6.0.2.dist-info");
+        assert_eq!(distribution_name(dist_info), Some("PyYAML"));
+        assert_eq!(normalize_package_name("PyYAML"), "pyyaml");
+        assert_eq!(
+            normalize_package_name("typing_extensions"),
+            "typing-extensions"
+        );
+        assert_eq!(normalize_package_name("zope..Interface"), "zope-interface");
+
+        let site_packages = Path::new("/venv/lib/python3.12/site-packages");
+        assert_eq!(
+            module_path(site_packages, &site_packages.join("requests/__init__.py")),
+            Some("requests".to_string())
+        );
+        assert_eq!(
+            module_path(site_packages, &site_packages.join("requests/adapters.py")),
+            Some("requests.adapters".to_string())
+        );
+        assert_eq!(
+            module_path(site_packages, &site_packages.join("requests/_internal.py")),
+            None
+        );
+    }
+}
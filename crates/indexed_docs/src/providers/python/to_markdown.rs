@@ -0,0 +1,312 @@
+use std::fmt::Write as _;
+
+/// Converts the docstrings of a Python module to Markdown.
+///
+/// The module docstring is followed by the signature and docstring of each
+/// public function and class, and of the public methods of those classes.
+/// Returns `None` if the module has nothing documented.
+pub fn convert_python_module_to_markdown(module: &str, source: &str) -> Option<String> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut markdown = String::new();
+    let mut has_docs = false;
+
+    let first_statement = lines.iter().position(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let mut ix = 0;
+    if let Some((docstring, end)) = first_statement.and_then(|start| read_docstring(&lines, start))
+    {
+        markdown.push_str(&docstring);
+        markdown.push_str("\n\n");
+        has_docs = true;
+        ix = end;
+    }
+
+    // The class being documented, along with its indentation and the
+    // indentation of its methods.
+    let mut current_class: Option<(usize, Option<usize>)> = None;
+    while ix < lines.len() {
+        let line = lines[ix];
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        let definition = trimmed
+            .strip_prefix("class ")
+            .map(|rest| (true, rest))
+            .or_else(|| {
+                trimmed
+                    .strip_prefix("def ")
+                    .or_else(|| trimmed.strip_prefix("async def "))
+                    .map(|rest| (false, rest))
+            });
+        let Some((is_class, rest)) = definition else {
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                if let Some((class_indent, _)) = current_class {
+                    if indent <= class_indent {
+                        current_class = None;
+                    }
+                }
+            }
+            ix += 1;
+            continue;
+        };
+
+        let is_method = match &mut current_class {
+            Some((class_indent, method_indent)) if indent > *class_indent => {
+                *method_indent.get_or_insert(indent) == indent && !is_class
+            }
+            _ => {
+                current_class = None;
+                false
+            }
+        };
+        if indent > 0 && !is_method {
+            ix += 1;
+            continue;
+        }
+
+        let name = rest
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or_default();
+        let (signature, body_start, has_body) = read_signature(&lines, ix);
+        let is_public = !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__"));
+        if is_class {
+            current_class = is_public.then_some((indent, None));
+        }
+        if !is_public {
+            ix = body_start;
+            continue;
+        }
+
+        let heading = if is_method { "###" } else { "##" };
+        writeln!(
+            markdown,
+            "{heading} `{name}`\n\n```python\n{signature}\n```\n"
+        )
+        .ok();
+        let next_statement = (body_start..lines.len())
+            .find(|ix| !lines[*ix].trim().is_empty())
+            .filter(|_| !has_body);
+        ix = body_start;
+        if let Some((docstring, end)) =
+            next_statement.and_then(|start| read_docstring(&lines, start))
+        {
+            markdown.push_str(&docstring);
+            markdown.push_str("\n\n");
+            ix = end;
+        }
+        has_docs = true;
+    }
+
+    has_docs.then(|| format!("# `{module}`\n\n{}", markdown.trim_end()))
+}
+
+/// Reads the signature of the definition starting at the given line, joining
+/// signatures that span multiple lines. Returns the signature, the line
+/// following it, and whether the body of the definition follows the signature
+/// on the same line.
+fn read_signature(lines: &[&str], start: usize) -> (String, usize, bool) {
+    let mut signature = String::new();
+    for (ix, line) in lines.iter().enumerate().skip(start) {
+        let line = line.trim().trim_end_matches('\\').trim_end();
+        if !signature.is_empty() && !signature.ends_with('(') && !line.starts_with(')') {
+            signature.push(' ');
+        }
+        signature.push_str(line);
+
+        let mut depth = 0;
+        for (offset, c) in signature.char_indices() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                ':' if depth == 0 => {
+                    let has_body = !signature[offset + 1..].trim().is_empty();
+                    let signature = signature[..offset].trim_end().replace(",)", ")");
+                    return (signature, ix + 1, has_body);
+                }
+                _ => {}
+            }
+        }
+    }
+    (signature, lines.len(), false)
+}
+
+/// Reads the string literal starting at the given line, if there is one,
+/// returning its contents with indentation removed and the line following it.
+fn read_docstring(lines: &[&str], start: usize) -> Option<(String, usize)> {
+    let line = lines[start].trim();
+    let literal = line.trim_start_matches(['r', 'R', 'u', 'U']);
+    if literal.len() + 1 < line.len() {
+        return None;
+    }
+    let quote = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quote| literal.starts_with(quote))?;
+    let literal = &literal[quote.len()..];
+
+    if let Some(end) = literal.find(quote) {
+        return Some((literal[..end].trim().to_string(), start + 1));
+    } else if quote.len() == 1 {
+        return None;
+    }
+
+    let mut docstring_lines = vec![literal];
+    for (ix, line) in lines.iter().enumerate().skip(start + 1) {
+        if let Some(end) = line.find(quote) {
+            docstring_lines.push(&line[..end]);
+            return Some((clean_docstring(&docstring_lines), ix + 1));
+        }
+        docstring_lines.push(line);
+    }
+    None
+}
+
+/// Removes the indentation shared by the lines of a docstring, other than the
+/// first, along with any leading and trailing blank lines.
+fn clean_docstring(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let lines = lines
+        .iter()
+        .enumerate()
+        .map(|(ix, line)| {
+            if ix == 0 {
+                line.trim()
+            } else {
+                line.get(indent..).unwrap_or_default().trim_end()
+            }
+        })
+        .collect::<Vec<_>>();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_convert_python_module_to_markdown() {
+        let source = indoc! {r#"
+            # A comment before the docstring.
+            """Tools for sending HTTP requests.
+
+            Use :func:`get` for simple requests.
+            """
+
+            import json
+
+
+            def get(url, params=None, **kwargs):
+                """Sends a GET request.
+
+                :param url: The URL to request.
+                """
+                return request("get", url, params=params, **kwargs)
+
+
+            def _helper():
+                """Not public."""
+
+
+            async def post(
+                url,
+                data=None,
+            ) -> Response:
+                r'''Sends a POST request.'''
+                def inner():
+                    """Not documented."""
+
+
+            class Session(object):
+                """A persistent session."""
+
+                def __init__(self):
+                    self.headers = {}
+
+                def close(self): ...
+
+                def _reset(self):
+                    """Not public."""
+
+
+            class _Private:
+                def hidden(self):
+                    """Not public."""
+
+
+            def main(): pass
+        "#};
+
+        assert_eq!(
+            convert_python_module_to_markdown("requests.api", source).unwrap(),
+            indoc! {r#"
+                # `requests.api`
+
+                Tools for sending HTTP requests.
+
+                Use :func:`get` for simple requests.
+
+                ## `get`
+
+                ```python
+                def get(url, params=None, **kwargs)
+                ```
+
+                Sends a GET request.
+
+                :param url: The URL to request.
+
+                ## `post`
+
+                ```python
+                async def post(url, data=None) -> Response
+                ```
+
+                Sends a POST request.
+
+                ## `Session`
+
+                ```python
+                class Session(object)
+                ```
+
+                A persistent session.
+
+                ### `__init__`
+
+                ```python
+                def __init__(self)
+                ```
+
+                ### `close`
+
+                ```python
+                def close(self)
+                ```
+
+                ## `main`
+
+                ```python
+                def main()
+                ```"#}
+        );
+    }
+
+    #[test]
+    fn test_undocumented_module() {
+        assert_eq!(
+            convert_python_module_to_markdown("pkg._impl", "import os\n\ndef _f():\n    pass\n"),
+            None
+        );
+    }
+}
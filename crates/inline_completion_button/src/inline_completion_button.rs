@@ -1,6 +1,7 @@
 use anyhow::Result;
 use copilot::{Copilot, CopilotCodeVerification, Status};
 use editor::{scroll::Autoscroll, Editor};
+use fim_completion::{FimApi, FimCompletionSettings};
 use fs::Fs;
 use gpui::{
     div, Action, AnchorCorner, AppContext, AsyncWindowContext, Entity, IntoElement, ParentElement,
@@ -196,6 +197,28 @@ impl Render for InlineCompletionButton {
                         ),
                 );
             }
+
+            InlineCompletionProvider::Fim => {
+                let settings = FimCompletionSettings::get_global(cx);
+                let icon = match settings.api {
+                    FimApi::Ollama => IconName::AiOllama,
+                    FimApi::OpenAi => IconName::AiOpenAi,
+                };
+                let tooltip_text = format!("Completions from {}", settings.model);
+                let this = cx.view().clone();
+
+                div().child(
+                    PopoverMenu::new("fim")
+                        .menu(move |cx| {
+                            Some(this.update(cx, |this, cx| this.build_fim_context_menu(cx)))
+                        })
+                        .anchor(AnchorCorner::BottomRight)
+                        .trigger(
+                            IconButton::new("fim-icon", icon)
+                                .tooltip(move |cx| Tooltip::text(tooltip_text.clone(), cx)),
+                        ),
+                )
+            }
         }
     }
 }
@@ -328,6 +351,10 @@ impl InlineCompletionButton {
         })
     }
 
+    fn build_fim_context_menu(&self, cx: &mut ViewContext<Self>) -> View<ContextMenu> {
+        ContextMenu::build(cx, |menu, cx| self.build_language_settings_menu(menu, cx))
+    }
+
     pub fn update_enabled(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
         let editor = editor.read(cx);
         let snapshot = editor.buffer().read(cx).snapshot(cx);
@@ -233,6 +233,8 @@ pub struct Diagnostic {
     pub is_disk_based: bool,
     /// Whether this diagnostic marks unnecessary code.
     pub is_unnecessary: bool,
+    /// Whether this diagnostic was pulled from the language server, rather than published by it.
+    pub is_pulled: bool,
     /// Data from language server that produced this diagnostic. Passed back to the LS when we request code actions for this diagnostic.
     pub data: Option<Value>,
 }
@@ -813,6 +815,38 @@ impl Buffer {
         }
     }
 
+    /// Builds a [`BufferSnapshot`] for the given text without creating a [`Buffer`].
+    ///
+    /// The returned future parses the text with the given language, and can be
+    /// awaited on a background thread.
+    pub fn build_snapshot(
+        text: Rope,
+        language: Option<Arc<Language>>,
+        language_registry: Option<Arc<LanguageRegistry>>,
+        cx: &mut AppContext,
+    ) -> impl Future<Output = BufferSnapshot> {
+        let entity_id = cx.reserve_model::<Self>().entity_id();
+        let buffer_id = entity_id.as_non_zero_u64().into();
+        async move {
+            let text =
+                TextBuffer::new_normalized(0, buffer_id, Default::default(), text).snapshot();
+            let mut syntax = SyntaxMap::new(&text).snapshot();
+            if let Some(language) = language.clone() {
+                syntax.reparse(&text, language_registry, language);
+            }
+            BufferSnapshot {
+                git_diff: git::diff::BufferDiff::new(&text),
+                text,
+                syntax,
+                file: None,
+                diagnostics: Default::default(),
+                remote_selections: Default::default(),
+                language,
+                non_text_state_update_count: 0,
+            }
+        }
+    }
+
     /// Retrieve a snapshot of the buffer's current state. This is computationally
     /// cheap, and allows reading from the buffer on a background thread.
     pub fn snapshot(&self) -> BufferSnapshot {
@@ -3912,6 +3946,18 @@ impl BufferSnapshot {
         !self.diagnostics.is_empty()
     }
 
+    /// Returns the diagnostics reported by the given language server.
+    pub fn diagnostics_for_server(
+        &self,
+        server_id: LanguageServerId,
+    ) -> impl '_ + Iterator<Item = &DiagnosticEntry<Anchor>> {
+        self.diagnostics
+            .binary_search_by_key(&server_id, |e| e.0)
+            .ok()
+            .into_iter()
+            .flat_map(move |ix| self.diagnostics[ix].1.iter())
+    }
+
     /// Returns all the diagnostics intersecting the given range.
     pub fn diagnostics_in_range<'a, T, O>(
         &'a self,
@@ -4338,6 +4384,7 @@ impl Default for Diagnostic {
             is_primary: false,
             is_disk_based: false,
             is_unnecessary: false,
+            is_pulled: false,
             data: None,
         }
     }
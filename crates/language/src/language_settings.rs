@@ -197,6 +197,8 @@ pub enum InlineCompletionProvider {
     #[default]
     Copilot,
     Supermaven,
+    /// Fill-in-the-middle completions from a local Ollama model or an OpenAI-compatible endpoint.
+    Fim,
 }
 
 /// The settings for inline completions, such as [GitHub Copilot](https://github.com/features/copilot)
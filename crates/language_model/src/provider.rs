@@ -1,6 +1,7 @@
 pub mod anthropic;
 pub mod cloud;
 pub mod copilot_chat;
+pub mod extension;
 #[cfg(any(test, feature = "test-support"))]
 pub mod fake;
 pub mod google;
@@ -3,8 +3,8 @@ use crate::{
     LanguageModelId, LanguageModelName, LanguageModelProvider, LanguageModelProviderId,
     LanguageModelProviderName, LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
-use crate::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason};
-use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent};
+use crate::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
+use anthropic::{AnthropicError, ContentDelta, Event, ResponseContent, Usage};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap};
 use editor::{Editor, EditorElement, EditorStyle};
@@ -443,12 +443,14 @@ pub fn map_to_language_model_completion_events(
     struct State {
         events: Pin<Box<dyn Send + Stream<Item = Result<Event, AnthropicError>>>>,
         tool_uses_by_index: HashMap<usize, RawToolUse>,
+        usage: TokenUsage,
     }
 
     futures::stream::unfold(
         State {
             events,
             tool_uses_by_index: HashMap::default(),
+            usage: TokenUsage::default(),
         },
         |mut state| async move {
             while let Some(event) = state.events.next().await {
@@ -515,14 +517,16 @@ pub fn map_to_language_model_completion_events(
                             }
                         }
                         Event::MessageStart { message } => {
+                            update_usage(&mut state.usage, &message.usage);
                             return Some((
                                 Some(Ok(LanguageModelCompletionEvent::StartMessage {
                                     message_id: message.id,
                                 })),
                                 state,
-                            ))
+                            ));
                         }
-                        Event::MessageDelta { delta, .. } => {
+                        Event::MessageDelta { delta, usage } => {
+                            update_usage(&mut state.usage, &usage);
                             if let Some(stop_reason) = delta.stop_reason.as_deref() {
                                 let stop_reason = match stop_reason {
                                     "end_turn" => StopReason::EndTurn,
@@ -537,6 +541,12 @@ pub fn map_to_language_model_completion_events(
                                 ));
                             }
                         }
+                        Event::MessageStop => {
+                            return Some((
+                                Some(Ok(LanguageModelCompletionEvent::UsageUpdate(state.usage))),
+                                state,
+                            ));
+                        }
                         Event::Error { error } => {
                             return Some((
                                 Some(Err(anyhow!(AnthropicError::ApiError(error)))),
@@ -557,6 +567,23 @@ pub fn map_to_language_model_completion_events(
     .filter_map(|event| async move { event })
 }
 
+/// Updates the token usage with the counts reported by an event. Events only
+/// report the counts that changed since the start of the message.
+fn update_usage(token_usage: &mut TokenUsage, new: &Usage) {
+    if let Some(input_tokens) = new.input_tokens {
+        token_usage.input_tokens = input_tokens;
+    }
+    if let Some(output_tokens) = new.output_tokens {
+        token_usage.output_tokens = output_tokens;
+    }
+    if let Some(cache_creation_input_tokens) = new.cache_creation_input_tokens {
+        token_usage.cache_creation_input_tokens = cache_creation_input_tokens;
+    }
+    if let Some(cache_read_input_tokens) = new.cache_read_input_tokens {
+        token_usage.cache_read_input_tokens = cache_read_input_tokens;
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: View<Editor>,
     state: gpui::Model<State>,
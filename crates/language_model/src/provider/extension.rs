@@ -0,0 +1,820 @@
+use anyhow::{anyhow, Result};
+use editor::{Editor, EditorElement, EditorStyle};
+use extension::{Extension, LanguageModelInfo, LanguageModelProviderManifestEntry};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{
+    AnyView, AppContext, AsyncAppContext, BackgroundExecutor, FontStyle, ModelContext, Task,
+    TextStyle, View, WhiteSpace,
+};
+use settings::Settings;
+use std::sync::Arc;
+use theme::ThemeSettings;
+use ui::{prelude::*, Icon, IconName, Tooltip};
+use util::ResultExt;
+
+use crate::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelRequestTool,
+    LanguageModelToolUse, MessageContent, RateLimiter, Role, StopReason,
+};
+
+/// A language model provider that is implemented by an extension.
+pub struct ExtensionLanguageModelProvider {
+    id: LanguageModelProviderId,
+    name: LanguageModelProviderName,
+    extension: Arc<dyn Extension>,
+    state: gpui::Model<State>,
+}
+
+pub struct State {
+    provider_id: Arc<str>,
+    extension: Arc<dyn Extension>,
+    auth: Option<extension::LanguageModelProviderAuthEntry>,
+    credentials_url: String,
+    api_key: Option<String>,
+    api_key_from_env: bool,
+    models: Vec<LanguageModelInfo>,
+    fetch_models_task: Option<Task<()>>,
+}
+
+impl State {
+    fn is_authenticated(&self) -> bool {
+        self.auth.is_none() || self.api_key.is_some()
+    }
+
+    fn reset_api_key(&self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.auth.is_none() {
+            return Task::ready(Ok(()));
+        }
+        let delete_credentials = cx.delete_credentials(&self.credentials_url);
+        cx.spawn(|this, mut cx| async move {
+            delete_credentials.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.api_key = None;
+                this.api_key_from_env = false;
+                this.models.clear();
+                cx.notify();
+            })
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: String, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.auth.is_none() {
+            return Task::ready(Ok(()));
+        }
+        let write_credentials =
+            cx.write_credentials(&self.credentials_url, "Bearer", api_key.as_bytes());
+
+        cx.spawn(|this, mut cx| async move {
+            write_credentials.await?;
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.fetch_models(cx);
+                cx.notify();
+            })
+        })
+    }
+
+    fn authenticate(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            if self.models.is_empty() && self.fetch_models_task.is_none() {
+                self.fetch_models(cx);
+            }
+            return Task::ready(Ok(()));
+        }
+        let Some(auth) = self.auth.clone() else {
+            return Task::ready(Ok(()));
+        };
+        let credentials_url = self.credentials_url.clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let env_api_key = auth
+                .env_var
+                .as_ref()
+                .and_then(|env_var| std::env::var(env_var).ok());
+            let (api_key, from_env) = if let Some(api_key) = env_api_key {
+                (api_key, true)
+            } else {
+                let (_, api_key) = cx
+                    .update(|cx| cx.read_credentials(&credentials_url))?
+                    .await?
+                    .ok_or_else(|| anyhow!("credentials not found"))?;
+                (String::from_utf8(api_key)?, false)
+            };
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.api_key_from_env = from_env;
+                this.fetch_models(cx);
+                cx.notify();
+            })
+        })
+    }
+
+    fn fetch_models(&mut self, cx: &mut ModelContext<Self>) {
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        let api_key = self.api_key.clone();
+        self.fetch_models_task = Some(cx.spawn(|this, mut cx| async move {
+            let models = extension
+                .language_model_provider_models(provider_id, api_key)
+                .await
+                .log_err();
+            this.update(&mut cx, |this, cx| {
+                this.models = models.unwrap_or_default();
+                this.fetch_models_task = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+}
+
+impl ExtensionLanguageModelProvider {
+    pub fn new(
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        provider: LanguageModelProviderManifestEntry,
+        cx: &mut AppContext,
+    ) -> Self {
+        let extension_id = extension.manifest().id.clone();
+        let state = cx.new_model(|_| State {
+            provider_id: provider_id.clone(),
+            extension: extension.clone(),
+            auth: provider.auth,
+            credentials_url: format!("extension://{extension_id}/{provider_id}"),
+            api_key: None,
+            api_key_from_env: false,
+            models: Vec::new(),
+            fetch_models_task: None,
+        });
+
+        Self {
+            id: Self::registered_id(&extension_id, &provider_id),
+            name: LanguageModelProviderName(provider.name.into()),
+            extension,
+            state,
+        }
+    }
+
+    /// The ID the provider is registered under, prefixed with the ID of its extension so that
+    /// providers of different extensions don't collide.
+    pub fn registered_id(extension_id: &str, provider_id: &str) -> LanguageModelProviderId {
+        LanguageModelProviderId(SharedString::from(format!("{extension_id}/{provider_id}")))
+    }
+}
+
+impl LanguageModelProviderState for ExtensionLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<gpui::Model<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for ExtensionLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        self.name.clone()
+    }
+
+    fn provided_models(&self, cx: &AppContext) -> Vec<Arc<dyn LanguageModel>> {
+        let state = self.state.read(cx);
+        state
+            .models
+            .iter()
+            .map(|model| {
+                Arc::new(ExtensionLanguageModel {
+                    id: LanguageModelId::from(model.id.clone()),
+                    model: model.clone(),
+                    provider_id: self.id.clone(),
+                    extension_provider_id: state.provider_id.clone(),
+                    provider_name: self.name.clone(),
+                    extension: self.extension.clone(),
+                    state: self.state.clone(),
+                    request_limiter: RateLimiter::new(4),
+                }) as Arc<dyn LanguageModel>
+            })
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &AppContext) -> bool {
+        self.state.read(cx).is_authenticated()
+    }
+
+    fn authenticate(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, cx: &mut WindowContext) -> AnyView {
+        let name = self.name.clone();
+        cx.new_view(|cx| ConfigurationView::new(name, self.state.clone(), cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.reset_api_key(cx))
+    }
+}
+
+pub struct ExtensionLanguageModel {
+    id: LanguageModelId,
+    model: LanguageModelInfo,
+    provider_id: LanguageModelProviderId,
+    /// The ID of the provider within its extension, as passed to the extension.
+    extension_provider_id: Arc<str>,
+    provider_name: LanguageModelProviderName,
+    extension: Arc<dyn Extension>,
+    state: gpui::Model<State>,
+    request_limiter: RateLimiter,
+}
+
+impl LanguageModel for ExtensionLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.model.display_name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.provider_id.clone()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.provider_name.clone()
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("{}/{}", self.provider_id.0, self.model.id)
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.model.max_output_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        let extension = self.extension.clone();
+        let provider_id = self.extension_provider_id.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        async move {
+            // Extensions that can't count tokens get a rough estimate instead.
+            let estimate = request
+                .messages
+                .iter()
+                .map(|message| message.string_contents().chars().count())
+                .sum::<usize>()
+                / 4;
+            let token_count = extension
+                .language_model_count_tokens(provider_id, model_id, into_extension_request(request))
+                .await;
+            Ok(token_count.unwrap_or(estimate))
+        }
+        .boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<LanguageModelCompletionEvent>>>> {
+        let Ok(api_key) = cx.read_model(&self.state, |state, _| state.api_key.clone()) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+        let extension = self.extension.clone();
+        let executor = cx.background_executor().clone();
+        let provider_id = self.extension_provider_id.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        let request = into_extension_request(request);
+
+        let future = self.request_limiter.stream(async move {
+            let stream_id = extension
+                .language_model_stream_completion(provider_id, model_id, request, api_key)
+                .await?;
+            Ok(completion_events(CompletionStream {
+                extension,
+                stream_id,
+                executor,
+            }))
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        mut request: LanguageModelRequest,
+        tool_name: String,
+        tool_description: String,
+        schema: serde_json::Value,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        request.tools = vec![LanguageModelRequestTool {
+            name: tool_name.clone(),
+            description: tool_description,
+            input_schema: schema,
+        }];
+
+        let events = self.stream_completion(request, cx);
+        async move {
+            let events = events.await?;
+            Ok(events
+                .filter_map(move |event| {
+                    let tool_name = tool_name.clone();
+                    async move {
+                        match event {
+                            Ok(LanguageModelCompletionEvent::ToolUse(tool_use))
+                                if tool_use.name == tool_name =>
+                            {
+                                Some(Ok(tool_use.input.to_string()))
+                            }
+                            Ok(_) => None,
+                            Err(error) => Some(Err(error)),
+                        }
+                    }
+                })
+                .boxed())
+        }
+        .boxed()
+    }
+}
+
+/// A completion being streamed by an extension, which is dropped on the
+/// extension's side once it is no longer read.
+struct CompletionStream {
+    extension: Arc<dyn Extension>,
+    stream_id: u64,
+    executor: BackgroundExecutor,
+}
+
+impl Drop for CompletionStream {
+    fn drop(&mut self) {
+        let extension = self.extension.clone();
+        let stream_id = self.stream_id;
+        self.executor
+            .spawn(async move {
+                extension
+                    .language_model_drop_completion_stream(stream_id)
+                    .await
+                    .log_err();
+            })
+            .detach();
+    }
+}
+
+fn completion_events(
+    stream: CompletionStream,
+) -> impl futures::Stream<Item = Result<LanguageModelCompletionEvent>> {
+    futures::stream::unfold(Some(stream), |stream| async move {
+        let stream = stream?;
+        match stream
+            .extension
+            .language_model_next_completion_event(stream.stream_id)
+            .await
+        {
+            Ok(Some(event)) => Some((from_extension_event(event), Some(stream))),
+            Ok(None) => None,
+            Err(error) => Some((Err(error), None)),
+        }
+    })
+}
+
+fn into_extension_request(request: LanguageModelRequest) -> extension::CompletionRequest {
+    extension::CompletionRequest {
+        messages: request
+            .messages
+            .into_iter()
+            .map(|message| extension::RequestMessage {
+                role: match message.role {
+                    Role::User => extension::MessageRole::User,
+                    Role::Assistant => extension::MessageRole::Assistant,
+                    Role::System => extension::MessageRole::System,
+                },
+                content: message
+                    .content
+                    .into_iter()
+                    .filter_map(|content| match content {
+                        MessageContent::Text(text) => Some(extension::MessageContent::Text(text)),
+                        MessageContent::ToolUse(tool_use) => {
+                            Some(extension::MessageContent::ToolUse(extension::ToolUse {
+                                id: tool_use.id,
+                                name: tool_use.name,
+                                input: tool_use.input.to_string(),
+                            }))
+                        }
+                        MessageContent::ToolResult(tool_result) => Some(
+                            extension::MessageContent::ToolResult(extension::ToolResult {
+                                tool_use_id: tool_result.tool_use_id,
+                                is_error: tool_result.is_error,
+                                content: tool_result.content,
+                            }),
+                        ),
+                        // Images are not supported by the extension API yet.
+                        MessageContent::Image(_) => None,
+                    })
+                    .collect(),
+                cache: message.cache,
+            })
+            .collect(),
+        tools: request
+            .tools
+            .into_iter()
+            .map(|tool| extension::ToolDefinition {
+                name: tool.name,
+                description: tool.description,
+                input_schema: tool.input_schema.to_string(),
+            })
+            .collect(),
+        stop: request.stop,
+        temperature: request.temperature,
+    }
+}
+
+fn from_extension_event(event: extension::CompletionEvent) -> Result<LanguageModelCompletionEvent> {
+    Ok(match event {
+        extension::CompletionEvent::StartMessage { message_id } => {
+            LanguageModelCompletionEvent::StartMessage { message_id }
+        }
+        extension::CompletionEvent::Text(text) => LanguageModelCompletionEvent::Text(text),
+        extension::CompletionEvent::ToolUse(tool_use) => {
+            let input = if tool_use.input.trim().is_empty() {
+                serde_json::Value::Object(Default::default())
+            } else {
+                serde_json::from_str(&tool_use.input)?
+            };
+            LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                id: tool_use.id,
+                name: tool_use.name,
+                input,
+            })
+        }
+        extension::CompletionEvent::Stop(reason) => {
+            LanguageModelCompletionEvent::Stop(match reason {
+                extension::StopReason::EndTurn => StopReason::EndTurn,
+                extension::StopReason::MaxTokens => StopReason::MaxTokens,
+                extension::StopReason::ToolUse => StopReason::ToolUse,
+            })
+        }
+    })
+}
+
+struct ConfigurationView {
+    provider_name: LanguageModelProviderName,
+    api_key_editor: View<Editor>,
+    state: gpui::Model<State>,
+    load_credentials_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(
+        provider_name: LanguageModelProviderName,
+        state: gpui::Model<State>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let api_key_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Enter your API key", cx);
+            editor
+        });
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let load_credentials_task = Some(cx.spawn({
+            let state = state.clone();
+            |this, mut cx| async move {
+                if let Some(task) = state
+                    .update(&mut cx, |state, cx| state.authenticate(cx))
+                    .log_err()
+                {
+                    // We don't log an error, because "not signed in" is also an error.
+                    let _ = task.await;
+                }
+
+                this.update(&mut cx, |this, cx| {
+                    this.load_credentials_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            provider_name,
+            api_key_editor,
+            state,
+            load_credentials_task,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.set_api_key(api_key, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn reset_api_key(&mut self, cx: &mut ViewContext<Self>) {
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.reset_api_key(cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_fallbacks: settings.ui_font.fallbacks.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+            truncate: None,
+        };
+        EditorElement::new(
+            &self.api_key_editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let needs_api_key = state.auth.is_some();
+        let is_authenticated = state.is_authenticated();
+        let env_var = state.auth.as_ref().and_then(|auth| auth.env_var.clone());
+        let env_var_set = state.api_key_from_env;
+
+        if self.load_credentials_task.is_some() {
+            div().child(Label::new("Loading credentials...")).into_any()
+        } else if !needs_api_key {
+            h_flex()
+                .size_full()
+                .gap_1()
+                .child(Icon::new(IconName::Check).color(Color::Success))
+                .child(Label::new(format!(
+                    "{} doesn't need any configuration.",
+                    self.provider_name.0
+                )))
+                .into_any()
+        } else if !is_authenticated {
+            v_flex()
+                .size_full()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(format!(
+                    "To use Zed's assistant with {}, paste your API key below and hit enter.",
+                    self.provider_name.0
+                )))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .my_2()
+                        .px_2()
+                        .py_1()
+                        .bg(cx.theme().colors().editor_background)
+                        .rounded_md()
+                        .child(self.render_api_key_editor(cx)),
+                )
+                .when_some(env_var, |this, env_var| {
+                    this.child(
+                        Label::new(format!(
+                            "You can also assign the {env_var} environment variable and restart Zed."
+                        ))
+                        .size(LabelSize::Small),
+                    )
+                })
+                .into_any()
+        } else {
+            h_flex()
+                .size_full()
+                .justify_between()
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(Icon::new(IconName::Check).color(Color::Success))
+                        .child(Label::new(match env_var.as_ref().filter(|_| env_var_set) {
+                            Some(env_var) => {
+                                format!("API key set in {env_var} environment variable.")
+                            }
+                            None => "API key configured.".to_string(),
+                        })),
+                )
+                .child(
+                    Button::new("reset-key", "Reset key")
+                        .icon(Some(IconName::Trash))
+                        .icon_size(IconSize::Small)
+                        .icon_position(IconPosition::Start)
+                        .disabled(env_var_set)
+                        .when_some(env_var.filter(|_| env_var_set), |this, env_var| {
+                            this.tooltip(move |cx| {
+                                Tooltip::text(
+                                    format!(
+                                        "To reset your API key, unset the {env_var} environment variable."
+                                    ),
+                                    cx,
+                                )
+                            })
+                        })
+                        .on_click(cx.listener(|this, _, cx| this.reset_api_key(cx))),
+                )
+                .into_any()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelRequestMessage, LanguageModelToolResult};
+    use serde_json::json;
+
+    #[test]
+    fn test_into_extension_request() {
+        let request = into_extension_request(LanguageModelRequest {
+            messages: vec![
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: vec![MessageContent::Text("Be brief.".into())],
+                    cache: true,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::Assistant,
+                    content: vec![
+                        MessageContent::Text("Let me look.".into()),
+                        MessageContent::ToolUse(LanguageModelToolUse {
+                            id: "tool-1".into(),
+                            name: "read_file".into(),
+                            input: json!({ "path": "src/main.rs" }),
+                        }),
+                    ],
+                    cache: false,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec![MessageContent::ToolResult(LanguageModelToolResult {
+                        tool_use_id: "tool-1".into(),
+                        is_error: true,
+                        content: "file not found".into(),
+                    })],
+                    cache: false,
+                },
+            ],
+            tools: vec![LanguageModelRequestTool {
+                name: "read_file".into(),
+                description: "Reads a file".into(),
+                input_schema: json!({ "type": "object" }),
+            }],
+            stop: vec!["\n\n".into()],
+            temperature: Some(0.5),
+        });
+
+        let roles = request
+            .messages
+            .iter()
+            .map(|message| message.role)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            [
+                extension::MessageRole::System,
+                extension::MessageRole::Assistant,
+                extension::MessageRole::User
+            ]
+        );
+        assert!(request.messages[0].cache);
+
+        let [extension::MessageContent::Text(text), extension::MessageContent::ToolUse(tool_use)] =
+            &request.messages[1].content[..]
+        else {
+            panic!("unexpected content: {:?}", request.messages[1].content);
+        };
+        assert_eq!(text, "Let me look.");
+        assert_eq!(tool_use.id, "tool-1");
+        assert_eq!(tool_use.name, "read_file");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&tool_use.input).unwrap(),
+            json!({ "path": "src/main.rs" })
+        );
+
+        let [extension::MessageContent::ToolResult(tool_result)] = &request.messages[2].content[..]
+        else {
+            panic!("unexpected content: {:?}", request.messages[2].content);
+        };
+        assert_eq!(tool_result.tool_use_id, "tool-1");
+        assert!(tool_result.is_error);
+        assert_eq!(tool_result.content, "file not found");
+
+        assert_eq!(request.tools.len(), 1);
+        assert_eq!(request.tools[0].name, "read_file");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&request.tools[0].input_schema).unwrap(),
+            json!({ "type": "object" })
+        );
+        assert_eq!(request.stop, ["\n\n"]);
+        assert_eq!(request.temperature, Some(0.5));
+    }
+
+    #[test]
+    fn test_from_extension_event() {
+        assert_eq!(
+            from_extension_event(extension::CompletionEvent::StartMessage {
+                message_id: "msg-1".into()
+            })
+            .unwrap(),
+            LanguageModelCompletionEvent::StartMessage {
+                message_id: "msg-1".into()
+            }
+        );
+        assert_eq!(
+            from_extension_event(extension::CompletionEvent::Text("Hello".into())).unwrap(),
+            LanguageModelCompletionEvent::Text("Hello".into())
+        );
+        assert_eq!(
+            from_extension_event(extension::CompletionEvent::ToolUse(extension::ToolUse {
+                id: "tool-1".into(),
+                name: "read_file".into(),
+                input: r#"{"path": "src/main.rs"}"#.into(),
+            }))
+            .unwrap(),
+            LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                id: "tool-1".into(),
+                name: "read_file".into(),
+                input: json!({ "path": "src/main.rs" }),
+            })
+        );
+        // Tools without parameters may be used with an empty input.
+        assert_eq!(
+            from_extension_event(extension::CompletionEvent::ToolUse(extension::ToolUse {
+                id: "tool-2".into(),
+                name: "now".into(),
+                input: " ".into(),
+            }))
+            .unwrap(),
+            LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                id: "tool-2".into(),
+                name: "now".into(),
+                input: json!({}),
+            })
+        );
+        assert!(
+            from_extension_event(extension::CompletionEvent::ToolUse(extension::ToolUse {
+                id: "tool-3".into(),
+                name: "read_file".into(),
+                input: "{\"path\":".into(),
+            }))
+            .is_err()
+        );
+        assert_eq!(
+            from_extension_event(extension::CompletionEvent::Stop(
+                extension::StopReason::ToolUse
+            ))
+            .unwrap(),
+            LanguageModelCompletionEvent::Stop(StopReason::ToolUse)
+        );
+    }
+}
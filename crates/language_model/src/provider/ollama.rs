@@ -1,30 +1,38 @@
 use anyhow::{anyhow, bail, Result};
-use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, Stream, StreamExt};
 use gpui::{AnyView, AppContext, AsyncAppContext, ModelContext, Subscription, Task};
 use http_client::HttpClient;
 use ollama::{
-    get_models, preload_model, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest,
-    ChatResponseDelta, KeepAlive, OllamaToolCall,
+    get_models, preload_model, show_model, stream_chat_completion, ChatMessage, ChatOptions,
+    ChatRequest, ChatResponseDelta, KeepAlive, OllamaFunctionCall, OllamaFunctionTool, OllamaTool,
+    OllamaToolCall,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::value::to_raw_value;
 use settings::{Settings, SettingsStore};
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use ui::{prelude::*, ButtonLike, Indicator};
 use util::ResultExt;
 
-use crate::LanguageModelCompletionEvent;
 use crate::{
     settings::AllLanguageModelSettings, LanguageModel, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
+use crate::{
+    LanguageModelCompletionEvent, LanguageModelToolUse, MessageContent, StopReason, TokenUsage,
+};
 
 const OLLAMA_DOWNLOAD_URL: &str = "https://ollama.com/download";
 const OLLAMA_LIBRARY_URL: &str = "https://ollama.com/library";
 const OLLAMA_SITE: &str = "https://ollama.com/";
 
-const PROVIDER_ID: &str = "ollama";
+pub const PROVIDER_ID: &str = "ollama";
 const PROVIDER_NAME: &str = "Ollama";
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -43,6 +51,8 @@ pub struct AvailableModel {
     pub max_tokens: usize,
     /// The number of seconds to keep the connection open after the last request
     pub keep_alive: Option<KeepAlive>,
+    /// Whether the model can call tools. Defaults to what Ollama reports about the model.
+    pub supports_tools: Option<bool>,
 }
 
 pub struct OllamaLanguageModelProvider {
@@ -71,14 +81,28 @@ impl State {
         cx.spawn(|this, mut cx| async move {
             let models = get_models(http_client.as_ref(), &api_url, None).await?;
 
-            let mut models: Vec<ollama::Model> = models
-                .into_iter()
-                // Since there is no metadata from the Ollama API
-                // indicating which models are embedding models,
-                // simply filter out models with "-embed" in their name
-                .filter(|model| !model.name.contains("-embed"))
-                .map(|model| ollama::Model::new(&model.name, None, None))
-                .collect();
+            let http_client = &http_client;
+            let api_url = &api_url;
+            let mut models: Vec<ollama::Model> = futures::future::join_all(
+                models
+                    .into_iter()
+                    // Since there is no metadata from the Ollama API
+                    // indicating which models are embedding models,
+                    // simply filter out models with "-embed" in their name
+                    .filter(|model| !model.name.contains("-embed"))
+                    .map(|model| async move {
+                        let mut ollama_model = ollama::Model::new(&model.name, None, None);
+                        // Ollama rejects requests offering tools to models that can't call
+                        // them, so only offer tools to the models that report they can.
+                        ollama_model.supports_tools =
+                            show_model(http_client.as_ref(), api_url, &model.name)
+                                .await
+                                .log_err()
+                                .map(|info| info.supports_tools());
+                        ollama_model
+                    }),
+            )
+            .await;
 
             models.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -169,6 +193,9 @@ impl LanguageModelProvider for OllamaLanguageModelProvider {
             .available_models
             .iter()
         {
+            let detected_supports_tools = models
+                .get(&model.name)
+                .and_then(|model| model.supports_tools);
             models.insert(
                 model.name.clone(),
                 ollama::Model {
@@ -176,6 +203,7 @@ impl LanguageModelProvider for OllamaLanguageModelProvider {
                     display_name: model.display_name.clone(),
                     max_tokens: model.max_tokens,
                     keep_alive: model.keep_alive.clone(),
+                    supports_tools: model.supports_tools.or(detected_supports_tools),
                 },
             );
         }
@@ -229,24 +257,61 @@ pub struct OllamaLanguageModel {
 
 impl OllamaLanguageModel {
     fn to_ollama_request(&self, request: LanguageModelRequest) -> ChatRequest {
+        let mut messages = Vec::new();
+        for message in request.messages {
+            match message.role {
+                Role::User => {
+                    let mut has_tool_results = false;
+                    for content in &message.content {
+                        if let MessageContent::ToolResult(tool_result) = content {
+                            has_tool_results = true;
+                            messages.push(ChatMessage::Tool {
+                                content: tool_result.content.clone(),
+                            });
+                        }
+                    }
+
+                    let content = message
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            MessageContent::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<String>();
+                    if !content.is_empty() || !has_tool_results {
+                        messages.push(ChatMessage::User { content });
+                    }
+                }
+                Role::Assistant => {
+                    let tool_calls = message
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            MessageContent::ToolUse(tool_use) => {
+                                let arguments = to_raw_value(&tool_use.input).log_err()?;
+                                Some(OllamaToolCall::Function(OllamaFunctionCall {
+                                    name: tool_use.name.clone(),
+                                    arguments,
+                                }))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    messages.push(ChatMessage::Assistant {
+                        content: message.string_contents(),
+                        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                    });
+                }
+                Role::System => messages.push(ChatMessage::System {
+                    content: message.string_contents(),
+                }),
+            }
+        }
+
         ChatRequest {
             model: self.model.name.clone(),
-            messages: request
-                .messages
-                .into_iter()
-                .map(|msg| match msg.role {
-                    Role::User => ChatMessage::User {
-                        content: msg.string_contents(),
-                    },
-                    Role::Assistant => ChatMessage::Assistant {
-                        content: msg.string_contents(),
-                        tool_calls: None,
-                    },
-                    Role::System => ChatMessage::System {
-                        content: msg.string_contents(),
-                    },
-                })
-                .collect(),
+            messages,
             keep_alive: self.model.keep_alive.clone().unwrap_or_default(),
             stream: true,
             options: Some(ChatOptions {
@@ -255,9 +320,24 @@ impl OllamaLanguageModel {
                 temperature: request.temperature.or(Some(1.0)),
                 ..Default::default()
             }),
-            tools: vec![],
+            tools: if self.model.supports_tools() {
+                request
+                    .tools
+                    .into_iter()
+                    .map(|tool| OllamaTool::Function {
+                        function: OllamaFunctionTool {
+                            name: tool.name,
+                            description: Some(tool.description),
+                            parameters: Some(tool.input_schema),
+                        },
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
         }
     }
+
     fn request_completion(
         &self,
         request: ChatRequest,
@@ -335,31 +415,11 @@ impl LanguageModel for OllamaLanguageModel {
 
         let future = self.request_limiter.stream(async move {
             let response = stream_chat_completion(http_client.as_ref(), &api_url, request).await?;
-            let stream = response
-                .filter_map(|response| async move {
-                    match response {
-                        Ok(delta) => {
-                            let content = match delta.message {
-                                ChatMessage::User { content } => content,
-                                ChatMessage::Assistant { content, .. } => content,
-                                ChatMessage::System { content } => content,
-                            };
-                            Some(Ok(content))
-                        }
-                        Err(error) => Some(Err(error)),
-                    }
-                })
-                .boxed();
+            let stream = map_to_language_model_completion_events(response).boxed();
             Ok(stream)
         });
 
-        async move {
-            Ok(future
-                .await?
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(future.await?.boxed()) }.boxed()
     }
 
     fn use_any_tool(
@@ -370,7 +430,6 @@ impl LanguageModel for OllamaLanguageModel {
         schema: serde_json::Value,
         cx: &AsyncAppContext,
     ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
-        use ollama::{OllamaFunctionTool, OllamaTool};
         let function = OllamaFunctionTool {
             name: tool_name.clone(),
             description: Some(tool_description),
@@ -405,6 +464,82 @@ impl LanguageModel for OllamaLanguageModel {
     }
 }
 
+/// Ollama streams text as it's generated, while tool calls are reported in a single chunk,
+/// usually the last one before the response is done.
+fn map_to_language_model_completion_events(
+    events: BoxStream<'static, Result<ChatResponseDelta>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    // Ollama does not assign ids to tool calls, so we make up ones that are unique
+    // within the conversation.
+    let id_prefix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let mut tool_call_count = 0;
+
+    events.flat_map(move |delta| {
+        let mut completion_events = Vec::new();
+        match delta {
+            Ok(delta) => {
+                let usage = usage_for_response(&delta);
+                let (content, tool_calls) = match delta.message {
+                    ChatMessage::Assistant {
+                        content,
+                        tool_calls,
+                    } => (content, tool_calls.unwrap_or_default()),
+                    ChatMessage::User { content }
+                    | ChatMessage::System { content }
+                    | ChatMessage::Tool { content } => (content, Vec::new()),
+                };
+                if !content.is_empty() {
+                    completion_events.push(Ok(LanguageModelCompletionEvent::Text(content)));
+                }
+                for OllamaToolCall::Function(function) in tool_calls {
+                    let id = format!("call_{id_prefix}_{tool_call_count}");
+                    tool_call_count += 1;
+                    completion_events.push(
+                        serde_json::from_str(function.arguments.get())
+                            .map(|input| {
+                                LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                                    id,
+                                    name: function.name,
+                                    input,
+                                })
+                            })
+                            .map_err(|err| anyhow!(err)),
+                    );
+                }
+
+                if delta.done {
+                    let stop_reason = if tool_call_count > 0 {
+                        StopReason::ToolUse
+                    } else {
+                        StopReason::EndTurn
+                    };
+                    completion_events.push(Ok(LanguageModelCompletionEvent::Stop(stop_reason)));
+                }
+                completion_events.extend(
+                    usage.map(|usage| Ok(LanguageModelCompletionEvent::UsageUpdate(usage))),
+                );
+            }
+            Err(error) => completion_events.push(Err(error)),
+        }
+        futures::stream::iter(completion_events)
+    })
+}
+
+/// Returns the token usage reported by the final response of a completion.
+fn usage_for_response(response: &ChatResponseDelta) -> Option<TokenUsage> {
+    if response.prompt_eval_count.is_none() && response.eval_count.is_none() {
+        return None;
+    }
+    Some(TokenUsage {
+        input_tokens: response.prompt_eval_count.unwrap_or(0),
+        output_tokens: response.eval_count.unwrap_or(0),
+        ..Default::default()
+    })
+}
+
 struct ConfigurationView {
     state: gpui::Model<State>,
     loading_models_task: Option<Task<()>>,
@@ -551,3 +686,74 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::value::RawValue;
+
+    fn delta(
+        content: &str,
+        tool_calls: Option<Vec<OllamaToolCall>>,
+        done: bool,
+    ) -> Result<ChatResponseDelta> {
+        Ok(ChatResponseDelta {
+            model: "llama3.2".into(),
+            created_at: String::new(),
+            message: ChatMessage::Assistant {
+                content: content.into(),
+                tool_calls,
+            },
+            done_reason: done.then(|| "stop".into()),
+            done,
+            prompt_eval_count: done.then_some(120),
+            eval_count: done.then_some(30),
+        })
+    }
+
+    #[gpui::test]
+    async fn test_streamed_tool_calls() {
+        let events = futures::stream::iter(vec![
+            delta("Let me ", None, false),
+            delta("look.", None, false),
+            delta(
+                "",
+                Some(vec![OllamaToolCall::Function(OllamaFunctionCall {
+                    name: "read_file".into(),
+                    arguments: RawValue::from_string("{\"path\":\"a.rs\"}".into()).unwrap(),
+                })]),
+                false,
+            ),
+            delta("", None, true),
+        ]);
+
+        let completion_events = map_to_language_model_completion_events(events.boxed())
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        let [text_1, text_2, LanguageModelCompletionEvent::ToolUse(tool_use), stop, usage] =
+            completion_events.as_slice()
+        else {
+            panic!("unexpected events: {completion_events:?}");
+        };
+        assert_eq!(
+            *text_1,
+            LanguageModelCompletionEvent::Text("Let me ".into())
+        );
+        assert_eq!(*text_2, LanguageModelCompletionEvent::Text("look.".into()));
+        assert_eq!(tool_use.name, "read_file");
+        assert_eq!(tool_use.input, serde_json::json!({ "path": "a.rs" }));
+        assert_eq!(
+            *stop,
+            LanguageModelCompletionEvent::Stop(StopReason::ToolUse)
+        );
+        assert_eq!(
+            *usage,
+            LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..Default::default()
+            })
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use collections::BTreeMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, FutureExt, StreamExt};
+use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
 use gpui::{
     AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
     View, WhiteSpace,
@@ -13,20 +13,21 @@ use open_ai::{
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
+use std::pin::Pin;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 use theme::ThemeSettings;
 use ui::{prelude::*, Icon, IconName, Tooltip};
 use util::ResultExt;
 
-use crate::LanguageModelCompletionEvent;
 use crate::{
     settings::AllLanguageModelSettings, LanguageModel, LanguageModelId, LanguageModelName,
     LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
     LanguageModelProviderState, LanguageModelRequest, RateLimiter, Role,
 };
+use crate::{LanguageModelCompletionEvent, LanguageModelToolUse, StopReason, TokenUsage};
 
-const PROVIDER_ID: &str = "openai";
+pub const PROVIDER_ID: &str = "openai";
 const PROVIDER_NAME: &str = "OpenAI";
 
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -293,12 +294,8 @@ impl LanguageModel for OpenAiLanguageModel {
     > {
         let request = request.into_open_ai(self.model.id().into(), self.max_output_tokens());
         let completions = self.stream_completion(request, cx);
-        async move {
-            Ok(open_ai::extract_text_from_events(completions.await?)
-                .map(|result| result.map(LanguageModelCompletionEvent::Text))
-                .boxed())
-        }
-        .boxed()
+        async move { Ok(map_to_language_model_completion_events(completions.await?).boxed()) }
+            .boxed()
     }
 
     fn use_any_tool(
@@ -373,6 +370,106 @@ pub fn count_open_ai_tokens(
         .boxed()
 }
 
+pub fn map_to_language_model_completion_events(
+    events: Pin<Box<dyn Send + Stream<Item = Result<ResponseStreamEvent>>>>,
+) -> impl Stream<Item = Result<LanguageModelCompletionEvent>> {
+    #[derive(Default)]
+    struct RawToolCall {
+        id: String,
+        name: String,
+        arguments: String,
+    }
+
+    struct State {
+        events: Pin<Box<dyn Send + Stream<Item = Result<ResponseStreamEvent>>>>,
+        tool_calls_by_index: BTreeMap<usize, RawToolCall>,
+    }
+
+    futures::stream::unfold(
+        State {
+            events,
+            tool_calls_by_index: BTreeMap::default(),
+        },
+        |mut state| async move {
+            let event = match state.events.next().await? {
+                Ok(event) => event,
+                Err(error) => return Some((vec![Err(error)], state)),
+            };
+
+            // When usage is requested, it is reported by a final event without any choices.
+            let mut completion_events = Vec::new();
+            if let Some(usage) = event.usage {
+                let cached_tokens = usage
+                    .prompt_tokens_details
+                    .map_or(0, |details| details.cached_tokens);
+                completion_events.push(Ok(LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                    input_tokens: usage.prompt_tokens.saturating_sub(cached_tokens),
+                    output_tokens: usage.completion_tokens,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: cached_tokens,
+                })));
+            }
+            let Some(choice) = event.choices.into_iter().next() else {
+                return Some((completion_events, state));
+            };
+
+            if let Some(content) = choice.delta.content.filter(|content| !content.is_empty()) {
+                completion_events.push(Ok(LanguageModelCompletionEvent::Text(content)));
+            }
+
+            for tool_call in choice.delta.tool_calls.into_iter().flatten() {
+                let entry = state
+                    .tool_calls_by_index
+                    .entry(tool_call.index)
+                    .or_default();
+                if let Some(id) = tool_call.id {
+                    entry.id = id;
+                }
+                if let Some(function) = tool_call.function {
+                    if let Some(name) = function.name {
+                        entry.name = name;
+                    }
+                    if let Some(arguments) = function.arguments {
+                        entry.arguments.push_str(&arguments);
+                    }
+                }
+            }
+
+            if let Some(finish_reason) = choice.finish_reason.as_deref() {
+                for (_, tool_call) in std::mem::take(&mut state.tool_calls_by_index) {
+                    completion_events.push(parse_tool_arguments(&tool_call.arguments).map(
+                        |input| {
+                            LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                                id: tool_call.id,
+                                name: tool_call.name,
+                                input,
+                            })
+                        },
+                    ));
+                }
+
+                let stop_reason = match finish_reason {
+                    "length" => StopReason::MaxTokens,
+                    "tool_calls" => StopReason::ToolUse,
+                    _ => StopReason::EndTurn,
+                };
+                completion_events.push(Ok(LanguageModelCompletionEvent::Stop(stop_reason)));
+            }
+
+            Some((completion_events, state))
+        },
+    )
+    .flat_map(futures::stream::iter)
+}
+
+fn parse_tool_arguments(arguments: &str) -> Result<serde_json::Value> {
+    if arguments.trim().is_empty() {
+        Ok(serde_json::Value::Object(Default::default()))
+    } else {
+        serde_json::from_str(arguments).map_err(|err| anyhow!(err))
+    }
+}
+
 struct ConfigurationView {
     api_key_editor: View<Editor>,
     state: gpui::Model<State>,
@@ -566,3 +663,137 @@ impl Render for ConfigurationView {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_ai::{
+        ChoiceDelta, FunctionChunk, PromptTokensDetails, ResponseMessageDelta, ToolCallChunk, Usage,
+    };
+
+    fn event(
+        content: Option<&str>,
+        tool_calls: Option<Vec<ToolCallChunk>>,
+        finish_reason: Option<&str>,
+    ) -> Result<ResponseStreamEvent> {
+        Ok(ResponseStreamEvent {
+            created: 0,
+            model: "gpt-4o".into(),
+            choices: vec![ChoiceDelta {
+                index: 0,
+                delta: ResponseMessageDelta {
+                    role: None,
+                    content: content.map(Into::into),
+                    tool_calls,
+                },
+                finish_reason: finish_reason.map(Into::into),
+            }],
+            usage: None,
+        })
+    }
+
+    fn tool_call_chunk(
+        index: usize,
+        id: Option<&str>,
+        name: Option<&str>,
+        arguments: &str,
+    ) -> ToolCallChunk {
+        ToolCallChunk {
+            index,
+            id: id.map(Into::into),
+            function: Some(FunctionChunk {
+                name: name.map(Into::into),
+                arguments: Some(arguments.into()),
+            }),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_streamed_tool_calls() {
+        let events = futures::stream::iter(vec![
+            event(Some("Let me look."), None, None),
+            event(
+                None,
+                Some(vec![tool_call_chunk(
+                    0,
+                    Some("call_1"),
+                    Some("read_file"),
+                    "{\"path\":",
+                )]),
+                None,
+            ),
+            event(
+                None,
+                Some(vec![tool_call_chunk(0, None, None, "\"a.rs\"}")]),
+                None,
+            ),
+            event(
+                None,
+                Some(vec![tool_call_chunk(1, Some("call_2"), Some("now"), "")]),
+                None,
+            ),
+            event(None, None, Some("tool_calls")),
+        ]);
+
+        let completion_events = map_to_language_model_completion_events(Box::pin(events))
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            completion_events,
+            vec![
+                LanguageModelCompletionEvent::Text("Let me look.".into()),
+                LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                    id: "call_1".into(),
+                    name: "read_file".into(),
+                    input: serde_json::json!({ "path": "a.rs" }),
+                }),
+                LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                    id: "call_2".into(),
+                    name: "now".into(),
+                    input: serde_json::json!({}),
+                }),
+                LanguageModelCompletionEvent::Stop(StopReason::ToolUse),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamed_usage() {
+        let events = futures::stream::iter(vec![
+            event(Some("Hello"), None, None),
+            event(None, None, Some("stop")),
+            Ok(ResponseStreamEvent {
+                created: 0,
+                model: "gpt-4o".into(),
+                choices: Vec::new(),
+                usage: Some(Usage {
+                    prompt_tokens: 1200,
+                    completion_tokens: 30,
+                    total_tokens: 1230,
+                    prompt_tokens_details: Some(PromptTokensDetails {
+                        cached_tokens: 1024,
+                    }),
+                }),
+            }),
+        ]);
+
+        let completion_events = map_to_language_model_completion_events(Box::pin(events))
+            .map(|event| event.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            completion_events,
+            vec![
+                LanguageModelCompletionEvent::Text("Hello".into()),
+                LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+                LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+                    input_tokens: 176,
+                    output_tokens: 30,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 1024,
+                }),
+            ]
+        );
+    }
+}
@@ -242,29 +242,87 @@ pub struct LanguageModelRequest {
 impl LanguageModelRequest {
     pub fn into_open_ai(self, model: String, max_output_tokens: Option<u32>) -> open_ai::Request {
         let stream = !model.starts_with("o1-");
+        let mut messages = Vec::new();
+        for message in self.messages {
+            match message.role {
+                Role::User => {
+                    let mut has_tool_results = false;
+                    for content in &message.content {
+                        if let MessageContent::ToolResult(tool_result) = content {
+                            has_tool_results = true;
+                            messages.push(open_ai::RequestMessage::Tool {
+                                content: tool_result.content.clone(),
+                                tool_call_id: tool_result.tool_use_id.clone(),
+                            });
+                        }
+                    }
+
+                    let content = message
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            MessageContent::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<String>();
+                    if !content.is_empty() || !has_tool_results {
+                        messages.push(open_ai::RequestMessage::User { content });
+                    }
+                }
+                Role::Assistant => {
+                    let tool_calls = message
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            MessageContent::ToolUse(tool_use) => Some(open_ai::ToolCall {
+                                id: tool_use.id.clone(),
+                                content: open_ai::ToolCallContent::Function {
+                                    function: open_ai::FunctionContent {
+                                        name: tool_use.name.clone(),
+                                        arguments: tool_use.input.to_string(),
+                                    },
+                                },
+                            }),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    let content = message.string_contents();
+                    messages.push(open_ai::RequestMessage::Assistant {
+                        content: if content.is_empty() && !tool_calls.is_empty() {
+                            None
+                        } else {
+                            Some(content)
+                        },
+                        tool_calls,
+                    });
+                }
+                Role::System => messages.push(open_ai::RequestMessage::System {
+                    content: message.string_contents(),
+                }),
+            }
+        }
+
         open_ai::Request {
             model,
-            messages: self
-                .messages
-                .into_iter()
-                .map(|msg| match msg.role {
-                    Role::User => open_ai::RequestMessage::User {
-                        content: msg.string_contents(),
-                    },
-                    Role::Assistant => open_ai::RequestMessage::Assistant {
-                        content: Some(msg.string_contents()),
-                        tool_calls: Vec::new(),
-                    },
-                    Role::System => open_ai::RequestMessage::System {
-                        content: msg.string_contents(),
-                    },
-                })
-                .collect(),
+            messages,
             stream,
+            stream_options: stream.then_some(open_ai::StreamOptions {
+                include_usage: true,
+            }),
             stop: self.stop,
             temperature: self.temperature.unwrap_or(1.0),
             max_tokens: max_output_tokens,
-            tools: Vec::new(),
+            tools: self
+                .tools
+                .into_iter()
+                .map(|tool| open_ai::ToolDefinition::Function {
+                    function: open_ai::FunctionDefinition {
+                        name: tool.name,
+                        description: Some(tool.description),
+                        parameters: Some(tool.input_schema),
+                    },
+                })
+                .collect(),
             tool_choice: None,
         }
     }
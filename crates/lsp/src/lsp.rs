@@ -639,7 +639,7 @@ impl LanguageServer {
                         refresh_support: Some(true),
                     }),
                     diagnostic: Some(DiagnosticWorkspaceClientCapabilities {
-                        refresh_support: None,
+                        refresh_support: Some(true),
                     }),
                     workspace_edit: Some(WorkspaceEditClientCapabilities {
                         resource_operations: Some(vec![
@@ -651,6 +651,15 @@ impl LanguageServer {
                         snippet_edit_support: Some(true),
                         ..WorkspaceEditClientCapabilities::default()
                     }),
+                    file_operations: Some(WorkspaceFileOperationsClientCapabilities {
+                        dynamic_registration: None,
+                        did_create: Some(true),
+                        will_create: Some(true),
+                        did_rename: Some(true),
+                        will_rename: Some(true),
+                        did_delete: Some(true),
+                        will_delete: Some(true),
+                    }),
                     ..Default::default()
                 }),
                 text_document: Some(TextDocumentClientCapabilities {
@@ -754,6 +763,12 @@ impl LanguageServer {
                         }),
                         ..SignatureHelpClientCapabilities::default()
                     }),
+                    document_link: Some(DocumentLinkClientCapabilities::default()),
+                    color_provider: Some(DocumentColorClientCapabilities::default()),
+                    diagnostic: Some(DiagnosticClientCapabilities {
+                        related_document_support: Some(true),
+                        ..DiagnosticClientCapabilities::default()
+                    }),
                     synchronization: Some(TextDocumentSyncClientCapabilities {
                         did_save: Some(true),
                         ..TextDocumentSyncClientCapabilities::default()
@@ -68,6 +68,8 @@ pub struct Model {
     pub display_name: Option<String>,
     pub max_tokens: usize,
     pub keep_alive: Option<KeepAlive>,
+    /// Whether the model can call tools. Assumed to be false when not specified.
+    pub supports_tools: Option<bool>,
 }
 
 fn get_max_tokens(name: &str) -> usize {
@@ -98,6 +100,7 @@ impl Model {
                 .or_else(|| name.strip_suffix(":latest").map(ToString::to_string)),
             max_tokens: max_tokens.unwrap_or_else(|| get_max_tokens(name)),
             keep_alive: Some(KeepAlive::indefinite()),
+            supports_tools: None,
         }
     }
 
@@ -109,6 +112,10 @@ impl Model {
         self.display_name.as_ref().unwrap_or(&self.name)
     }
 
+    pub fn supports_tools(&self) -> bool {
+        self.supports_tools.unwrap_or(false)
+    }
+
     pub fn max_token_count(&self) -> usize {
         self.max_tokens
     }
@@ -127,6 +134,10 @@ pub enum ChatMessage {
     System {
         content: String,
     },
+    /// The result of a tool called by the assistant.
+    Tool {
+        content: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -182,6 +193,25 @@ pub struct ChatOptions {
     pub top_p: Option<f32>,
 }
 
+/// A request to generate a completion for a raw prompt, such as a fill-in-the-middle prompt.
+#[derive(Serialize, Debug)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub prompt: String,
+    /// Whether to send the prompt as is, without applying the model's prompt template.
+    pub raw: bool,
+    pub stream: bool,
+    pub keep_alive: KeepAlive,
+    pub options: Option<ChatOptions>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerateResponse {
+    pub response: String,
+    #[allow(unused)]
+    pub done: bool,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ChatResponseDelta {
     #[allow(unused)]
@@ -193,6 +223,12 @@ pub struct ChatResponseDelta {
     pub done_reason: Option<String>,
     #[allow(unused)]
     pub done: bool,
+    /// The number of prompt tokens, reported with the final response.
+    #[serde(default)]
+    pub prompt_eval_count: Option<u32>,
+    /// The number of generated tokens, reported with the final response.
+    #[serde(default)]
+    pub eval_count: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -217,6 +253,23 @@ pub struct LocalModel {
     pub details: ModelDetails,
 }
 
+/// The information about a model returned by `/api/show`.
+#[derive(Deserialize, Debug)]
+pub struct ModelInfo {
+    /// What the model can do, such as `completion` or `tools`. Only reported by recent
+    /// versions of Ollama.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl ModelInfo {
+    pub fn supports_tools(&self) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability == "tools")
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ModelDetails {
     pub format: String,
@@ -258,6 +311,37 @@ pub async fn complete(
     }
 }
 
+pub async fn generate(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: GenerateRequest,
+) -> Result<GenerateResponse> {
+    let uri = format!("{api_url}/api/generate");
+    let request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    let mut request_body = request;
+    request_body.stream = false;
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request_body)?))?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("Unable to parse generate response")
+    } else {
+        Err(anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 pub async fn stream_chat_completion(
     client: &dyn HttpClient,
     api_url: &str,
@@ -329,6 +413,32 @@ pub async fn get_models(
     }
 }
 
+pub async fn show_model(client: &dyn HttpClient, api_url: &str, model: &str) -> Result<ModelInfo> {
+    let uri = format!("{api_url}/api/show");
+    let request = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .body(AsyncBody::from(serde_json::to_string(
+            &serde_json::json!({ "model": model }),
+        )?))?;
+
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        serde_json::from_str(&body).context("Unable to parse Ollama model information")
+    } else {
+        Err(anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 /// Sends an empty request to Ollama to trigger loading the model
 pub async fn preload_model(client: Arc<dyn HttpClient>, api_url: &str, model: &str) -> Result<()> {
     let uri = format!("{api_url}/api/generate");
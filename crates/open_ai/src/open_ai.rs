@@ -159,6 +159,8 @@ pub struct Request {
     pub messages: Vec<RequestMessage>,
     pub stream: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub stop: Vec<String>,
@@ -169,6 +171,12 @@ pub struct Request {
     pub tools: Vec<ToolDefinition>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Whether to send a final event reporting the token usage of the request.
+    pub include_usage: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ToolChoice {
@@ -261,6 +269,15 @@ pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PromptTokensDetails {
+    /// The number of prompt tokens that were read from the prompt cache.
+    #[serde(default)]
+    pub cached_tokens: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -355,6 +372,66 @@ pub async fn complete(
     }
 }
 
+/// A request to the legacy `/completions` endpoint, which takes a raw prompt
+/// instead of chat messages.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextCompletionRequest {
+    pub model: String,
+    pub prompt: String,
+    pub stream: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    pub temperature: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextCompletionResponse {
+    pub choices: Vec<TextCompletionChoice>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextCompletionChoice {
+    pub text: String,
+    pub finish_reason: Option<String>,
+}
+
+pub async fn text_completion(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    request: TextCompletionRequest,
+) -> Result<TextCompletionResponse> {
+    let uri = format!("{api_url}/completions");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+
+    let mut request_body = request;
+    request_body.stream = false;
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request_body)?))?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+
+    if response.status().is_success() {
+        Ok(serde_json::from_str(&body)?)
+    } else {
+        Err(anyhow!(
+            "Failed to connect to OpenAI API: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 fn adapt_response_to_stream(response: Response) -> ResponseStreamEvent {
     ResponseStreamEvent {
         created: response.created as u32,
@@ -1,9 +1,10 @@
 mod signature_help;
 
 use crate::{
-    lsp_store::LspStore, CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock,
-    HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip,
-    InlayHintTooltip, Location, LocationLink, MarkupContent, ProjectTransaction, ResolveState,
+    lsp_store::LspStore, CodeAction, ColorPresentation, CoreCompletion, DocumentColor,
+    DocumentHighlight, DocumentLink, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
+    InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink,
+    MarkupContent, ProjectTransaction, ResolveState,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -180,6 +181,15 @@ pub(crate) struct InlayHints {
 pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+#[derive(Debug)]
+pub(crate) struct GetDocumentColors;
+#[derive(Debug)]
+pub(crate) struct GetColorPresentations {
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
 
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
@@ -3022,3 +3032,477 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::DocumentLink>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        // Links without a target are only resolved when they are followed.
+        let mut lsp_links = message.unwrap_or_default();
+        lsp_links.sort_unstable_by_key(|link| (link.range.start, Reverse(link.range.end)));
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_links
+                .into_iter()
+                .map(|link| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(link.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(link.range.end), Bias::Left);
+                    DocumentLink {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        target: link.target.clone(),
+                        server_id,
+                        lsp_link: link,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: response
+                .into_iter()
+                .map(|link| proto::DocumentLink {
+                    start: Some(serialize_anchor(&link.range.start)),
+                    end: Some(serialize_anchor(&link.range.end)),
+                    target: link.target.map(|target| target.to_string()),
+                    language_server_id: link.server_id.0 as u64,
+                    lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let mut links = Vec::new();
+        for link in message.links {
+            let start = link
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing link start"))?;
+            let end = link
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing link end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            links.push(DocumentLink {
+                range: start..end,
+                target: link
+                    .target
+                    .map(|target| lsp::Url::parse(&target))
+                    .transpose()
+                    .context("invalid link target")?,
+                server_id: LanguageServerId(link.language_server_id as usize),
+                lsp_link: serde_json::from_slice(&link.lsp_link)?,
+            });
+        }
+        Ok(links)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn color_to_proto(color: lsp::Color) -> proto::LspColor {
+    proto::LspColor {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    }
+}
+
+fn color_from_proto(color: proto::LspColor) -> lsp::Color {
+    lsp::Color {
+        red: color.red,
+        green: color.green,
+        blue: color.blue,
+        alpha: color.alpha,
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentColors {
+    type Response = Vec<DocumentColor>;
+    type LspRequest = lsp::request::DocumentColor;
+    type ProtoRequest = proto::GetDocumentColors;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            capabilities.server_capabilities.color_provider,
+            None | Some(lsp::ColorProviderCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentColorParams {
+        lsp::DocumentColorParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        mut message: Vec<lsp::ColorInformation>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        message.sort_unstable_by_key(|color| (color.range.start, Reverse(color.range.end)));
+        buffer.read_with(&cx, |buffer, _| {
+            message
+                .into_iter()
+                .map(|color| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(color.range.start), Bias::Left);
+                    let end = buffer.clip_point_utf16(point_from_lsp(color.range.end), Bias::Left);
+                    DocumentColor {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        color: color.color,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentColors {
+        proto::GetDocumentColors {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentColors,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentColor>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentColorsResponse {
+        proto::GetDocumentColorsResponse {
+            colors: response
+                .into_iter()
+                .map(|color| proto::DocumentColor {
+                    start: Some(serialize_anchor(&color.range.start)),
+                    end: Some(serialize_anchor(&color.range.end)),
+                    color: Some(color_to_proto(color.color)),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentColorsResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let mut colors = Vec::new();
+        for color in message.colors {
+            let start = color
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing color start"))?;
+            let end = color
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing color end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            colors.push(DocumentColor {
+                range: start..end,
+                color: color_from_proto(color.color.context("missing color")?),
+            });
+        }
+        Ok(colors)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentColors) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetColorPresentations {
+    type Response = Vec<ColorPresentation>;
+    type LspRequest = lsp::request::ColorPresentationRequest;
+    type ProtoRequest = proto::GetColorPresentations;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            capabilities.server_capabilities.color_provider,
+            None | Some(lsp::ColorProviderCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::ColorPresentationParams {
+        let range = self.range.to_point_utf16(buffer);
+        lsp::ColorPresentationParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            color: self.color,
+            range: range_to_lsp(range),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Vec<lsp::ColorPresentation>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer.read_with(&cx, |buffer, _| {
+            let edit_from_lsp = |edit: lsp::TextEdit| {
+                let start = buffer.clip_point_utf16(point_from_lsp(edit.range.start), Bias::Left);
+                let end = buffer.clip_point_utf16(point_from_lsp(edit.range.end), Bias::Left);
+                (
+                    buffer.anchor_after(start)..buffer.anchor_before(end),
+                    edit.new_text,
+                )
+            };
+            message
+                .into_iter()
+                .map(|presentation| ColorPresentation {
+                    label: presentation.label,
+                    text_edit: presentation.text_edit.map(edit_from_lsp),
+                    additional_text_edits: presentation
+                        .additional_text_edits
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(edit_from_lsp)
+                        .collect(),
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetColorPresentations {
+        proto::GetColorPresentations {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            start: Some(serialize_anchor(&self.range.start)),
+            end: Some(serialize_anchor(&self.range.end)),
+            color: Some(color_to_proto(self.color)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetColorPresentations,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let start = message
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = message
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            range: start..end,
+            color: color_from_proto(message.color.context("missing color")?),
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<ColorPresentation>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetColorPresentationsResponse {
+        let edit_to_proto =
+            |(range, new_text): (Range<Anchor>, String)| proto::ColorPresentationEdit {
+                start: Some(serialize_anchor(&range.start)),
+                end: Some(serialize_anchor(&range.end)),
+                new_text,
+            };
+        proto::GetColorPresentationsResponse {
+            presentations: response
+                .into_iter()
+                .map(|presentation| proto::ColorPresentation {
+                    label: presentation.label,
+                    text_edit: presentation.text_edit.map(edit_to_proto),
+                    additional_text_edits: presentation
+                        .additional_text_edits
+                        .into_iter()
+                        .map(edit_to_proto)
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetColorPresentationsResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let edit_from_proto = |edit: proto::ColorPresentationEdit| {
+            let start = edit
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing edit start"))?;
+            let end = edit
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing edit end"))?;
+            anyhow::Ok((start..end, edit.new_text))
+        };
+        message
+            .presentations
+            .into_iter()
+            .map(|presentation| {
+                Ok(ColorPresentation {
+                    label: presentation.label,
+                    text_edit: presentation.text_edit.map(edit_from_proto).transpose()?,
+                    additional_text_edits: presentation
+                        .additional_text_edits
+                        .into_iter()
+                        .map(edit_from_proto)
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetColorPresentations) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
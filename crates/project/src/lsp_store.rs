@@ -10,7 +10,7 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, Item as _, ProjectPath,
+    CodeAction, Completion, CoreCompletion, DocumentLink, Hover, InlayHint, Item as _, ProjectPath,
     ProjectTransaction, ResolveState, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
@@ -23,7 +23,7 @@ use futures::{
     stream::FuturesUnordered,
     AsyncWriteExt, Future, FutureExt, StreamExt,
 };
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
 use gpui::{
     AppContext, AsyncAppContext, Context, Entity, EventEmitter, Model, ModelContext, PromptLevel,
     Task, WeakModel,
@@ -91,6 +91,8 @@ pub use worktree::{
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
+pub(crate) const DIAGNOSTICS_PULL_DEBOUNCE: Duration = Duration::from_millis(250);
+const FILE_OPERATION_EDITS_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
@@ -146,9 +148,21 @@ pub struct LocalLspStore {
         HashMap<LanguageServerId, (LanguageServerName, Arc<LanguageServer>)>,
     prettier_store: Model<PrettierStore>,
     current_lsp_settings: HashMap<LanguageServerName, LspSettings>,
+    pulled_diagnostics: HashMap<LanguageServerId, PulledDiagnostics>,
     _subscription: gpui::Subscription,
 }
 
+/// The state of the diagnostics pulled from a language server that supports
+/// `textDocument/diagnostic`.
+#[derive(Default)]
+struct PulledDiagnostics {
+    /// The result id of the last report for each document, sent back to the server
+    /// so that it can reply with an unchanged report.
+    result_ids: HashMap<lsp::Url, String>,
+    document_pulls: HashMap<BufferId, Task<()>>,
+    workspace_pull: Option<Task<()>>,
+}
+
 impl LocalLspStore {
     fn shutdown_language_servers(
         &mut self,
@@ -798,6 +812,7 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
         client.add_model_request_handler(Self::handle_on_type_formatting);
@@ -814,6 +829,9 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentColors>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetColorPresentations>);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -906,6 +924,7 @@ impl LspStore {
                 language_server_watched_paths: Default::default(),
                 language_server_watcher_registrations: Default::default(),
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                pulled_diagnostics: Default::default(),
                 buffers_being_formatted: Default::default(),
                 prettier_store,
                 environment,
@@ -1763,6 +1782,66 @@ impl LspStore {
         }
     }
 
+    pub fn resolve_document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<lsp::Url>>> {
+        if link.target.is_some() {
+            return Task::ready(Ok(link.target));
+        }
+
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                language_server_id: link.server_id.0 as u64,
+                lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+            };
+            cx.background_executor().spawn(async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                response
+                    .target
+                    .map(|target| lsp::Url::parse(&target))
+                    .transpose()
+                    .context("invalid link target")
+            })
+        } else {
+            self.resolve_lsp_document_link(link.server_id, link.lsp_link, cx)
+        }
+    }
+
+    fn resolve_lsp_document_link(
+        &self,
+        server_id: LanguageServerId,
+        lsp_link: lsp::DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<lsp::Url>>> {
+        let Some(server) = self.language_server_for_id(server_id) else {
+            return Task::ready(Ok(None));
+        };
+        let can_resolve = server
+            .capabilities()
+            .document_link_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false);
+        if !can_resolve {
+            return Task::ready(Ok(None));
+        }
+        cx.background_executor().spawn(async move {
+            let resolved = server
+                .request::<lsp::request::DocumentLinkResolve>(lsp_link)
+                .await
+                .context("document link resolve LSP request")?;
+            Ok(resolved.target)
+        })
+    }
+
     pub(crate) fn linked_edit(
         &self,
         buffer: &Model<Buffer>,
@@ -2932,7 +3011,8 @@ impl LspStore {
         buffer: Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) -> Option<()> {
-        let buffer = buffer.read(cx);
+        let buffer_handle = buffer;
+        let buffer = buffer_handle.read(cx);
         let file = File::from_dyn(buffer.file())?;
         let abs_path = file.as_local()?.abs_path(cx);
         let uri = lsp::Url::from_file_path(abs_path).unwrap();
@@ -2943,7 +3023,7 @@ impl LspStore {
             .map(|i| i.1.clone())
             .collect();
 
-        for language_server in language_servers {
+        for language_server in &language_servers {
             let language_server = language_server.clone();
 
             let buffer_snapshots = self
@@ -3023,6 +3103,8 @@ impl LspStore {
                 .log_err();
         }
 
+        self.pull_diagnostics_after_edit(&buffer_handle, &language_servers, cx);
+
         None
     }
 
@@ -3063,6 +3145,220 @@ impl LspStore {
         None
     }
 
+    /// Asks the language servers interested in the creation of the given path for the edits
+    /// to make beforehand, and applies them.
+    pub fn will_create_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        is_dir: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction> {
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return Task::ready(ProjectTransaction::default());
+        };
+        let servers =
+            self.servers_for_file_operation(worktree_id, abs_path, is_dir, |ops| ops.will_create);
+        self.request_file_operation_edits::<lsp::request::WillCreateFiles>(
+            servers,
+            lsp::CreateFilesParams {
+                files: vec![lsp::FileCreate {
+                    uri: uri.to_string(),
+                }],
+            },
+            cx,
+        )
+    }
+
+    pub fn did_create_entry(&self, worktree_id: WorktreeId, abs_path: &Path, is_dir: bool) {
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return;
+        };
+        for server in
+            self.servers_for_file_operation(worktree_id, abs_path, is_dir, |ops| ops.did_create)
+        {
+            server
+                .notify::<lsp::notification::DidCreateFiles>(lsp::CreateFilesParams {
+                    files: vec![lsp::FileCreate {
+                        uri: uri.to_string(),
+                    }],
+                })
+                .log_err();
+        }
+    }
+
+    /// Asks the language servers interested in the renaming of the given path for the edits
+    /// to make beforehand, such as updating imports, and applies them.
+    pub fn will_rename_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        old_abs_path: &Path,
+        new_abs_path: &Path,
+        is_dir: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction> {
+        let Some(file) = file_rename(old_abs_path, new_abs_path) else {
+            return Task::ready(ProjectTransaction::default());
+        };
+        let servers = self
+            .servers_for_file_operation(worktree_id, old_abs_path, is_dir, |ops| ops.will_rename);
+        self.request_file_operation_edits::<lsp::request::WillRenameFiles>(
+            servers,
+            lsp::RenameFilesParams { files: vec![file] },
+            cx,
+        )
+    }
+
+    pub fn did_rename_entry(
+        &self,
+        worktree_id: WorktreeId,
+        old_abs_path: &Path,
+        new_abs_path: &Path,
+        is_dir: bool,
+    ) {
+        let Some(file) = file_rename(old_abs_path, new_abs_path) else {
+            return;
+        };
+        for server in
+            self.servers_for_file_operation(worktree_id, old_abs_path, is_dir, |ops| ops.did_rename)
+        {
+            server
+                .notify::<lsp::notification::DidRenameFiles>(lsp::RenameFilesParams {
+                    files: vec![file.clone()],
+                })
+                .log_err();
+        }
+    }
+
+    /// Asks the language servers interested in the deletion of the given path for the edits
+    /// to make beforehand, and applies them.
+    pub fn will_delete_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        is_dir: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction> {
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return Task::ready(ProjectTransaction::default());
+        };
+        let servers =
+            self.servers_for_file_operation(worktree_id, abs_path, is_dir, |ops| ops.will_delete);
+        self.request_file_operation_edits::<lsp::request::WillDeleteFiles>(
+            servers,
+            lsp::DeleteFilesParams {
+                files: vec![lsp::FileDelete {
+                    uri: uri.to_string(),
+                }],
+            },
+            cx,
+        )
+    }
+
+    pub fn did_delete_entry(&self, worktree_id: WorktreeId, abs_path: &Path, is_dir: bool) {
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return;
+        };
+        for server in
+            self.servers_for_file_operation(worktree_id, abs_path, is_dir, |ops| ops.did_delete)
+        {
+            server
+                .notify::<lsp::notification::DidDeleteFiles>(lsp::DeleteFilesParams {
+                    files: vec![lsp::FileDelete {
+                        uri: uri.to_string(),
+                    }],
+                })
+                .log_err();
+        }
+    }
+
+    /// Returns the language servers of the worktree whose `workspace.fileOperations`
+    /// filters for the given operation match the path.
+    fn servers_for_file_operation(
+        &self,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        is_dir: bool,
+        operation: impl Fn(
+            lsp::WorkspaceFileOperationsServerCapabilities,
+        ) -> Option<lsp::FileOperationRegistrationOptions>,
+    ) -> Vec<Arc<LanguageServer>> {
+        self.language_servers_for_worktree(worktree_id)
+            .filter(|server| {
+                file_operation_matcher(server, &operation)
+                    .map_or(false, |matcher| matcher.matches(abs_path, is_dir))
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn request_file_operation_edits<R>(
+        &mut self,
+        servers: Vec<Arc<LanguageServer>>,
+        params: R::Params,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction>
+    where
+        R: lsp::request::Request<Result = Option<lsp::WorkspaceEdit>> + 'static,
+        R::Params: Clone,
+    {
+        let requests = servers
+            .into_iter()
+            .filter_map(|server| {
+                let adapter = self.language_server_adapter_for_id(server.server_id())?;
+                let request = server.request::<R>(params.clone());
+                Some((adapter, server, request))
+            })
+            .collect::<Vec<_>>();
+        if requests.is_empty() {
+            return Task::ready(ProjectTransaction::default());
+        }
+
+        cx.spawn(move |this, mut cx| async move {
+            let mut project_transaction = ProjectTransaction::default();
+            for (adapter, server, request) in requests {
+                // The file operation waits for these edits, so don't let a slow server block it.
+                let timeout = cx.background_executor().timer(FILE_OPERATION_EDITS_TIMEOUT);
+                let edit = smol::future::or(async { request.await.log_err().flatten() }, async {
+                    timeout.await;
+                    log::warn!("{} did not answer {} in time", server.name(), R::METHOD);
+                    None
+                })
+                .await;
+                let (Some(edit), Some(this)) = (edit, this.upgrade()) else {
+                    continue;
+                };
+                if let Some(transaction) =
+                    Self::deserialize_workspace_edit(this, edit, true, adapter, server, &mut cx)
+                        .await
+                        .log_err()
+                {
+                    // Several servers may edit the same buffer, in which case their edits are
+                    // undone together.
+                    for (buffer, transaction) in transaction.0 {
+                        match project_transaction.0.get_mut(&buffer) {
+                            Some(first_transaction) => {
+                                buffer
+                                    .update(&mut cx, |buffer, _| {
+                                        buffer.merge_transactions(
+                                            transaction.id,
+                                            first_transaction.id,
+                                        )
+                                    })
+                                    .ok();
+                                first_transaction.edit_ids.extend(transaction.edit_ids);
+                            }
+                            None => {
+                                project_transaction.0.insert(buffer, transaction);
+                            }
+                        }
+                    }
+                }
+            }
+            project_transaction
+        })
+    }
+
     pub(crate) async fn refresh_workspace_configurations(
         this: &WeakModel<Self>,
         mut cx: AsyncAppContext,
@@ -3376,6 +3672,7 @@ impl LspStore {
                         .entry(buffer_id)
                         .or_default()
                         .insert(server.server_id(), vec![snapshot]);
+                    self.pull_document_diagnostics(buffer_handle, server.server_id(), cx);
                 }
             }
         }
@@ -3427,6 +3724,28 @@ impl LspStore {
         version: Option<i32>,
         diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
         cx: &mut ModelContext<Self>,
+    ) -> Result<(), anyhow::Error> {
+        self.update_diagnostic_entries_from_source(
+            server_id,
+            abs_path,
+            version,
+            diagnostics,
+            None,
+            cx,
+        )
+    }
+
+    /// Replaces the diagnostics of a language server for a file. When `is_pulled` is given,
+    /// only the diagnostics that were pulled, or published, are replaced, and the ones from
+    /// the other source are kept.
+    fn update_diagnostic_entries_from_source(
+        &mut self,
+        server_id: LanguageServerId,
+        abs_path: PathBuf,
+        version: Option<i32>,
+        mut diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+        is_pulled: Option<bool>,
+        cx: &mut ModelContext<Self>,
     ) -> Result<(), anyhow::Error> {
         let Some((worktree, relative_path)) =
             self.worktree_store.read(cx).find_worktree(&abs_path, cx)
@@ -3440,8 +3759,41 @@ impl LspStore {
             path: relative_path.into(),
         };
 
-        if let Some(buffer) = self.buffer_store.read(cx).get_by_path(&project_path, cx) {
-            self.update_buffer_diagnostics(&buffer, server_id, version, diagnostics.clone(), cx)?;
+        let buffer = self.buffer_store.read(cx).get_by_path(&project_path, cx);
+        if let Some(buffer) = &buffer {
+            self.update_buffer_diagnostics_from_source(
+                buffer,
+                server_id,
+                version,
+                diagnostics.clone(),
+                is_pulled,
+                cx,
+            )?;
+        }
+
+        if let Some(is_pulled) = is_pulled {
+            match &buffer {
+                // The kept diagnostics may have been reported for another version of the
+                // buffer, so store the merged ones as they are now positioned in it.
+                Some(buffer) => {
+                    let snapshot = buffer.read(cx).snapshot();
+                    diagnostics = snapshot
+                        .diagnostics_for_server(server_id)
+                        .map(|entry| {
+                            let entry = entry.resolve::<PointUtf16>(&snapshot);
+                            DiagnosticEntry {
+                                range: Unclipped(entry.range.start)..Unclipped(entry.range.end),
+                                diagnostic: entry.diagnostic,
+                            }
+                        })
+                        .collect();
+                }
+                None => diagnostics.extend(
+                    self.diagnostic_entries_for_path(server_id, &abs_path, cx)
+                        .filter(|entry| entry.diagnostic.is_pulled != is_pulled)
+                        .cloned(),
+                ),
+            }
         }
 
         let updated = worktree.update(cx, |worktree, cx| {
@@ -3666,11 +4018,30 @@ impl LspStore {
     }
 
     pub(crate) fn update_buffer_diagnostics(
+        &mut self,
+        buffer: &Model<Buffer>,
+        server_id: LanguageServerId,
+        version: Option<i32>,
+        diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        self.update_buffer_diagnostics_from_source(
+            buffer,
+            server_id,
+            version,
+            diagnostics,
+            None,
+            cx,
+        )
+    }
+
+    fn update_buffer_diagnostics_from_source(
         &mut self,
         buffer: &Model<Buffer>,
         server_id: LanguageServerId,
         version: Option<i32>,
         mut diagnostics: Vec<DiagnosticEntry<Unclipped<PointUtf16>>>,
+        is_pulled: Option<bool>,
         cx: &mut ModelContext<Self>,
     ) -> Result<()> {
         fn compare_diagnostics(a: &Diagnostic, b: &Diagnostic) -> Ordering {
@@ -3731,7 +4102,35 @@ impl LspStore {
         }
         drop(edits_since_save);
 
-        let set = DiagnosticSet::new(sanitized_diagnostics, &snapshot);
+        let set = match is_pulled {
+            None => DiagnosticSet::new(sanitized_diagnostics, &snapshot),
+            // The kept diagnostics were anchored in the version of the buffer they were
+            // reported for, while the new ones are anchored in the version of this report.
+            Some(is_pulled) => {
+                let current_snapshot = buffer.read(cx).snapshot();
+                let mut entries = sanitized_diagnostics
+                    .into_iter()
+                    .map(|entry| DiagnosticEntry {
+                        range: snapshot.anchor_before(entry.range.start)
+                            ..snapshot.anchor_before(entry.range.end),
+                        diagnostic: entry.diagnostic,
+                    })
+                    .chain(
+                        current_snapshot
+                            .diagnostics_for_server(server_id)
+                            .filter(|entry| entry.diagnostic.is_pulled != is_pulled)
+                            .cloned(),
+                    )
+                    .collect::<Vec<_>>();
+                entries.sort_unstable_by(|a, b| {
+                    a.range
+                        .start
+                        .cmp(&b.range.start, &current_snapshot)
+                        .then_with(|| b.range.end.cmp(&a.range.end, &current_snapshot))
+                });
+                DiagnosticSet::from_sorted_entries(entries, &current_snapshot)
+            }
+        };
         buffer.update(cx, |buffer, cx| {
             buffer.update_diagnostics(server_id, set, cx)
         });
@@ -4762,6 +5161,27 @@ impl LspStore {
         }
     }
 
+    /// Starts pulling diagnostics from a language server that registered the
+    /// `textDocument/diagnostic` capability after it was initialized.
+    fn on_lsp_register_diagnostics(
+        &mut self,
+        language_server_id: LanguageServerId,
+        options: Option<lsp::DiagnosticRegistrationOptions>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(server) = self.language_server_for_id(language_server_id) else {
+            return;
+        };
+        let provider = match options {
+            Some(options) => lsp::DiagnosticServerCapabilities::RegistrationOptions(options),
+            None => lsp::DiagnosticServerCapabilities::Options(Default::default()),
+        };
+        server.update_capabilities(|capabilities| {
+            capabilities.diagnostic_provider = Some(provider);
+        });
+        self.pull_diagnostics_for_server(language_server_id, cx);
+    }
+
     fn on_lsp_unregister_did_change_watched_files(
         &mut self,
         language_server_id: LanguageServerId,
@@ -5091,6 +5511,23 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let lsp_link = serde_json::from_slice(&envelope.payload.lsp_link)?;
+        let server_id = LanguageServerId(envelope.payload.language_server_id as usize);
+        let target = this
+            .update(&mut cx, |this, cx| {
+                this.resolve_lsp_document_link(server_id, lsp_link, cx)
+            })?
+            .await?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            target: target.map(|target| target.to_string()),
+        })
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -5842,10 +6279,9 @@ impl LspStore {
                     });
                 }
 
-                self.as_local_mut()
-                    .unwrap()
-                    .language_server_watched_paths
-                    .remove(&server_id);
+                let local = self.as_local_mut().unwrap();
+                local.language_server_watched_paths.remove(&server_id);
+                local.pulled_diagnostics.remove(&server_id);
                 self.language_server_statuses.remove(&server_id);
                 cx.notify();
 
@@ -6164,6 +6600,15 @@ impl LspStore {
                                         anyhow::Ok(())
                                     })??;
                                 }
+                                "textDocument/diagnostic" => {
+                                    let options = reg
+                                        .register_options
+                                        .map(serde_json::from_value)
+                                        .transpose()?;
+                                    this.update(&mut cx, |this, cx| {
+                                        this.on_lsp_register_diagnostics(server_id, options, cx);
+                                    })?;
+                                }
                                 _ => log::warn!("unhandled capability registration: {reg:?}"),
                             }
                         }
@@ -6188,6 +6633,16 @@ impl LspStore {
                                         );
                                     })?;
                                 }
+                                "textDocument/diagnostic" => {
+                                    this.update(&mut cx, |this, _| {
+                                        if let Some(server) = this.language_server_for_id(server_id)
+                                        {
+                                            server.update_capabilities(|capabilities| {
+                                                capabilities.diagnostic_provider = None
+                                            })
+                                        }
+                                    })?;
+                                }
                                 "textDocument/rename" => {
                                     this.update(&mut cx, |this, _| {
                                         if let Some(server) = this.language_server_for_id(server_id)
@@ -6276,6 +6731,21 @@ impl LspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.pull_diagnostics_for_server(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -6446,10 +6916,23 @@ impl LspStore {
     }
 
     pub fn update_diagnostics(
+        &mut self,
+        language_server_id: LanguageServerId,
+        params: lsp::PublishDiagnosticsParams,
+        disk_based_sources: &[String],
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        self.merge_diagnostics(language_server_id, params, disk_based_sources, false, cx)
+    }
+
+    /// Replaces the diagnostics that the language server published for a file, or that were
+    /// pulled from it, keeping the ones from the other source.
+    fn merge_diagnostics(
         &mut self,
         language_server_id: LanguageServerId,
         mut params: lsp::PublishDiagnosticsParams,
         disk_based_sources: &[String],
+        is_pulled: bool,
         cx: &mut ModelContext<Self>,
     ) -> Result<()> {
         let abs_path = params
@@ -6513,6 +6996,7 @@ impl LspStore {
                         is_primary: true,
                         is_disk_based,
                         is_unnecessary,
+                        is_pulled,
                         data: diagnostic.data.clone(),
                     },
                 });
@@ -6531,6 +7015,7 @@ impl LspStore {
                                     is_primary: false,
                                     is_disk_based,
                                     is_unnecessary: false,
+                                    is_pulled,
                                     data: diagnostic.data.clone(),
                                 },
                             });
@@ -6557,37 +7042,364 @@ impl LspStore {
             }
         }
 
-        self.update_diagnostic_entries(
+        self.update_diagnostic_entries_from_source(
             language_server_id,
             abs_path,
             params.version,
             diagnostics,
+            Some(is_pulled),
             cx,
         )?;
         Ok(())
     }
 
-    fn insert_newly_running_language_server(
+    fn diagnostic_entries_for_path<'a>(
+        &'a self,
+        server_id: LanguageServerId,
+        abs_path: &Path,
+        cx: &'a AppContext,
+    ) -> impl 'a + Iterator<Item = &'a DiagnosticEntry<Unclipped<PointUtf16>>> {
+        self.worktree_store
+            .read(cx)
+            .find_worktree(abs_path, cx)
+            .and_then(|(worktree, relative_path)| {
+                let diagnostics_by_server_id = self
+                    .diagnostics
+                    .get(&worktree.read(cx).id())?
+                    .get(relative_path.as_path())?;
+                let ix = diagnostics_by_server_id
+                    .binary_search_by_key(&server_id, |e| e.0)
+                    .ok()?;
+                Some(&diagnostics_by_server_id[ix].1)
+            })
+            .into_iter()
+            .flatten()
+    }
+
+    /// Pulls the diagnostics of every buffer open in the given language server, and of the
+    /// whole workspace if the server supports it.
+    fn pull_diagnostics_for_server(
         &mut self,
-        language: LanguageName,
-        adapter: Arc<CachedLspAdapter>,
-        language_server: Arc<LanguageServer>,
         server_id: LanguageServerId,
-        key: (WorktreeId, LanguageServerName),
         cx: &mut ModelContext<Self>,
     ) {
-        // If the language server for this key doesn't match the server id, don't store the
-        // server. Which will cause it to be dropped, killing the process
-        if self
-            .language_server_ids
-            .get(&key)
-            .map(|id| id != &server_id)
-            .unwrap_or(false)
-        {
+        let Some(options) = self
+            .language_server_for_id(server_id)
+            .and_then(|server| diagnostic_pull_options(&server))
+        else {
             return;
-        }
+        };
 
-        // Update language_servers collection with Running variant of LanguageServerState
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                self.buffer_snapshots
+                    .get(&buffer.read(cx).remote_id())
+                    .map_or(false, |snapshots| snapshots.contains_key(&server_id))
+            })
+            .collect::<Vec<_>>();
+        for buffer in &buffers {
+            self.pull_document_diagnostics(buffer, server_id, cx);
+        }
+        if options.workspace_diagnostics {
+            self.pull_workspace_diagnostics(server_id, cx);
+        }
+    }
+
+    fn pull_diagnostics_after_edit(
+        &mut self,
+        buffer: &Model<Buffer>,
+        language_servers: &[Arc<LanguageServer>],
+        cx: &mut ModelContext<Self>,
+    ) {
+        for server in language_servers {
+            let Some(options) = diagnostic_pull_options(server) else {
+                continue;
+            };
+            // Edits can change the diagnostics of other files when the server reports
+            // inter-file dependencies, so those have to be pulled again as well.
+            if options.inter_file_dependencies {
+                self.pull_diagnostics_for_server(server.server_id(), cx);
+            } else {
+                self.pull_document_diagnostics(buffer, server.server_id(), cx);
+            }
+        }
+    }
+
+    fn pull_document_diagnostics(
+        &mut self,
+        buffer: &Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self
+            .language_server_for_id(server_id)
+            .and_then(|server| diagnostic_pull_options(&server))
+            .is_none()
+        {
+            return;
+        }
+
+        let buffer_id = buffer.read(cx).remote_id();
+        let buffer = buffer.downgrade();
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(DIAGNOSTICS_PULL_DEBOUNCE)
+                .await;
+
+            let Some((server, uri, version, params)) = this
+                .update(&mut cx, |this, cx| {
+                    let server = this.language_server_for_id(server_id)?;
+                    let options = diagnostic_pull_options(&server)?;
+                    let buffer = buffer.upgrade()?;
+                    let file = File::from_dyn(buffer.read(cx).file())?;
+                    let uri = lsp::Url::from_file_path(file.as_local()?.abs_path(cx)).ok()?;
+                    let version = this
+                        .buffer_snapshots
+                        .get(&buffer_id)?
+                        .get(&server_id)?
+                        .last()?
+                        .version;
+                    let previous_result_id = this
+                        .as_local()?
+                        .pulled_diagnostics
+                        .get(&server_id)
+                        .and_then(|pulled| pulled.result_ids.get(&uri).cloned());
+                    let params = lsp::DocumentDiagnosticParams {
+                        text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                        identifier: options.identifier,
+                        previous_result_id,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    };
+                    Some((server, uri, version, params))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            let Some(report) = server
+                .request::<lsp::request::DocumentDiagnosticRequest>(params)
+                .await
+                .log_err()
+            else {
+                return;
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let related_documents = match report {
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Full(report),
+                    ) => {
+                        this.apply_pulled_diagnostics(
+                            server_id,
+                            uri,
+                            Some(version),
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                            cx,
+                        );
+                        report.related_documents
+                    }
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Unchanged(report),
+                    ) => {
+                        this.apply_pulled_diagnostics(
+                            server_id,
+                            uri,
+                            Some(version),
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                            cx,
+                        );
+                        report.related_documents
+                    }
+                    lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                        report.related_documents
+                    }
+                };
+                for (uri, report) in related_documents.into_iter().flatten() {
+                    this.apply_pulled_diagnostics(server_id, uri, None, report, cx);
+                }
+            })
+            .ok();
+        });
+
+        if let Some(local) = self.as_local_mut() {
+            local
+                .pulled_diagnostics
+                .entry(server_id)
+                .or_default()
+                .document_pulls
+                .insert(buffer_id, task);
+        }
+    }
+
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(DIAGNOSTICS_PULL_DEBOUNCE)
+                .await;
+
+            let Some((server, params)) = this
+                .update(&mut cx, |this, _| {
+                    let server = this.language_server_for_id(server_id)?;
+                    let options = diagnostic_pull_options(&server)?;
+                    let previous_result_ids = this
+                        .as_local()?
+                        .pulled_diagnostics
+                        .get(&server_id)
+                        .map(|pulled| {
+                            pulled
+                                .result_ids
+                                .iter()
+                                .map(|(uri, value)| lsp::PreviousResultId {
+                                    uri: uri.clone(),
+                                    value: value.clone(),
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let params = lsp::WorkspaceDiagnosticParams {
+                        identifier: options.identifier,
+                        previous_result_ids,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    };
+                    Some((server, params))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            let Some(report) = server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(params)
+                .await
+                .log_err()
+            else {
+                return;
+            };
+
+            let items = match report {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    let version = version.and_then(|version| i32::try_from(version).ok());
+                    this.apply_pulled_diagnostics(server_id, uri, version, report, cx);
+                }
+            })
+            .ok();
+        });
+
+        if let Some(local) = self.as_local_mut() {
+            local
+                .pulled_diagnostics
+                .entry(server_id)
+                .or_default()
+                .workspace_pull = Some(task);
+        }
+    }
+
+    /// Replaces the server's diagnostics for the given document with a pulled report, and
+    /// remembers its result id for the next pull.
+    fn apply_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let result_id = match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                if let Some(adapter) = self.language_server_adapter_for_id(server_id) {
+                    let mut params = lsp::PublishDiagnosticsParams {
+                        uri: uri.clone(),
+                        diagnostics: report.items,
+                        version,
+                    };
+                    adapter.process_diagnostics(&mut params);
+                    self.merge_diagnostics(
+                        server_id,
+                        params,
+                        &adapter.disk_based_diagnostic_sources,
+                        true,
+                        cx,
+                    )
+                    .log_err();
+                }
+                report.result_id
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => Some(report.result_id),
+        };
+
+        let Some(pulled) = self
+            .as_local_mut()
+            .and_then(|local| local.pulled_diagnostics.get_mut(&server_id))
+        else {
+            return;
+        };
+        match result_id {
+            Some(result_id) => {
+                pulled.result_ids.insert(uri, result_id);
+            }
+            None => {
+                pulled.result_ids.remove(&uri);
+            }
+        }
+    }
+
+    fn insert_newly_running_language_server(
+        &mut self,
+        language: LanguageName,
+        adapter: Arc<CachedLspAdapter>,
+        language_server: Arc<LanguageServer>,
+        server_id: LanguageServerId,
+        key: (WorktreeId, LanguageServerName),
+        cx: &mut ModelContext<Self>,
+    ) {
+        // If the language server for this key doesn't match the server id, don't store the
+        // server. Which will cause it to be dropped, killing the process
+        if self
+            .language_server_ids
+            .get(&key)
+            .map(|id| id != &server_id)
+            .unwrap_or(false)
+        {
+            return;
+        }
+
+        // Update language_servers collection with Running variant of LanguageServerState
         // indicating that the server is up and running and ready
         if let Some(local) = self.as_local_mut() {
             local.language_servers.insert(
@@ -6707,6 +7519,7 @@ impl LspStore {
             }
         });
 
+        self.pull_diagnostics_for_server(server_id, cx);
         cx.notify();
     }
 
@@ -7074,94 +7887,12 @@ impl LspStore {
     ) -> Result<ProjectTransaction> {
         let fs = this.read_with(cx, |this, _| this.as_local().unwrap().fs.clone())?;
 
-        let mut operations = Vec::new();
-        if let Some(document_changes) = edit.document_changes {
-            match document_changes {
-                lsp::DocumentChanges::Edits(edits) => {
-                    operations.extend(edits.into_iter().map(lsp::DocumentChangeOperation::Edit))
-                }
-                lsp::DocumentChanges::Operations(ops) => operations = ops,
-            }
-        } else if let Some(changes) = edit.changes {
-            operations.extend(changes.into_iter().map(|(uri, edits)| {
-                lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
-                    text_document: lsp::OptionalVersionedTextDocumentIdentifier {
-                        uri,
-                        version: None,
-                    },
-                    edits: edits.into_iter().map(Edit::Plain).collect(),
-                })
-            }));
-        }
+        let operations = workspace_edit_operations(edit);
 
         let mut project_transaction = ProjectTransaction::default();
         for operation in operations {
             match operation {
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(op)) => {
-                    let abs_path = op
-                        .uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-
-                    if let Some(parent_path) = abs_path.parent() {
-                        fs.create_dir(parent_path).await?;
-                    }
-                    if abs_path.ends_with("/") {
-                        fs.create_dir(&abs_path).await?;
-                    } else {
-                        fs.create_file(
-                            &abs_path,
-                            op.options
-                                .map(|options| fs::CreateOptions {
-                                    overwrite: options.overwrite.unwrap_or(false),
-                                    ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
-                                })
-                                .unwrap_or_default(),
-                        )
-                        .await?;
-                    }
-                }
-
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(op)) => {
-                    let source_abs_path = op
-                        .old_uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    let target_abs_path = op
-                        .new_uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    fs.rename(
-                        &source_abs_path,
-                        &target_abs_path,
-                        op.options
-                            .map(|options| fs::RenameOptions {
-                                overwrite: options.overwrite.unwrap_or(false),
-                                ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
-                            })
-                            .unwrap_or_default(),
-                    )
-                    .await?;
-                }
-
-                lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Delete(op)) => {
-                    let abs_path = op
-                        .uri
-                        .to_file_path()
-                        .map_err(|_| anyhow!("can't convert URI to path"))?;
-                    let options = op
-                        .options
-                        .map(|options| fs::RemoveOptions {
-                            recursive: options.recursive.unwrap_or(false),
-                            ignore_if_not_exists: options.ignore_if_not_exists.unwrap_or(false),
-                        })
-                        .unwrap_or_default();
-                    if abs_path.ends_with("/") {
-                        fs.remove_dir(&abs_path, options).await?;
-                    } else {
-                        fs.remove_file(&abs_path, options).await?;
-                    }
-                }
+                lsp::DocumentChangeOperation::Op(op) => apply_resource_op(fs.as_ref(), op).await?,
 
                 lsp::DocumentChangeOperation::Edit(op) => {
                     let buffer_to_edit = this
@@ -7277,6 +8008,188 @@ impl LspStore {
         Ok(project_transaction)
     }
 
+    /// Asks the primary language server of the buffer for the edits that renaming the symbol
+    /// at the given position would make, without applying them.
+    pub fn preview_rename(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        new_name: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<WorkspaceEditPreview>> {
+        if self.as_local().is_none() {
+            return Task::ready(Err(anyhow!(
+                "rename previews are only available in local projects"
+            )));
+        }
+
+        let buffer = buffer.read(cx);
+        let Some((adapter, server)) = self
+            .primary_language_server_for_buffer(buffer, cx)
+            .map(|(adapter, server)| (adapter.clone(), server.clone()))
+        else {
+            return Task::ready(Ok(WorkspaceEditPreview::default()));
+        };
+        let can_rename = match server.capabilities().rename_provider {
+            Some(lsp::OneOf::Left(enabled)) => enabled,
+            Some(lsp::OneOf::Right(_)) => true,
+            None => false,
+        };
+        if !can_rename {
+            return Task::ready(Ok(WorkspaceEditPreview::default()));
+        }
+        let Some(file) = File::from_dyn(buffer.file()).and_then(File::as_local) else {
+            return Task::ready(Ok(WorkspaceEditPreview::default()));
+        };
+        let request = PerformRename {
+            position,
+            new_name,
+            push_to_history: false,
+        };
+        let params = request.to_lsp(&file.abs_path(cx), buffer, &server, cx);
+
+        cx.spawn(move |this, mut cx| async move {
+            let Some(edit) = server.request::<lsp::request::Rename>(params).await? else {
+                return Ok(WorkspaceEditPreview::default());
+            };
+            let this = this.upgrade().context("lsp store was dropped")?;
+            Self::preview_workspace_edit(this, edit, adapter, server, &mut cx).await
+        })
+    }
+
+    /// Resolves the changes of a workspace edit against the buffers they would modify,
+    /// without applying them.
+    pub async fn preview_workspace_edit(
+        this: Model<Self>,
+        mut edit: lsp::WorkspaceEdit,
+        lsp_adapter: Arc<CachedLspAdapter>,
+        language_server: Arc<LanguageServer>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<WorkspaceEditPreview> {
+        let mut preview = WorkspaceEditPreview {
+            changes: Vec::new(),
+            annotations: edit
+                .change_annotations
+                .take()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+        };
+
+        for operation in workspace_edit_operations(edit) {
+            let op = match operation {
+                lsp::DocumentChangeOperation::Op(op) => {
+                    preview.changes.push(WorkspaceEditChange::Resource(op));
+                    continue;
+                }
+                lsp::DocumentChangeOperation::Edit(op) => op,
+            };
+
+            let buffer = this
+                .update(cx, |this, cx| {
+                    this.open_local_buffer_via_lsp(
+                        op.text_document.uri.clone(),
+                        language_server.server_id(),
+                        lsp_adapter.name.clone(),
+                        cx,
+                    )
+                })?
+                .await?;
+            for edit in op.edits {
+                let (text_edit, annotation_id) = match edit {
+                    Edit::Plain(edit) => (edit, None),
+                    Edit::Annotated(edit) => (edit.text_edit, Some(edit.annotation_id)),
+                    Edit::Snippet(edit) => {
+                        let Ok(snippet) = Snippet::parse(&edit.snippet.value) else {
+                            continue;
+                        };
+                        let text_edit = TextEdit {
+                            range: edit.range,
+                            new_text: snippet.text,
+                        };
+                        (text_edit, edit.annotation_id)
+                    }
+                };
+                let edits = this
+                    .update(cx, |this, cx| {
+                        this.edits_from_lsp(
+                            &buffer,
+                            [text_edit],
+                            language_server.server_id(),
+                            op.text_document.version,
+                            cx,
+                        )
+                    })?
+                    .await?;
+                preview.changes.push(WorkspaceEditChange::Edit {
+                    buffer: buffer.clone(),
+                    edits,
+                    annotation_id,
+                });
+            }
+        }
+
+        Ok(preview)
+    }
+
+    /// Applies the changes of a previewed workspace edit in order, grouping the edits made
+    /// to each buffer into a single transaction.
+    pub fn apply_workspace_edit_preview(
+        &mut self,
+        preview: WorkspaceEditPreview,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(fs) = self.as_local().map(|local| local.fs.clone()) else {
+            return Task::ready(Err(anyhow!(
+                "workspace edit previews can only be applied in local projects"
+            )));
+        };
+
+        cx.spawn(move |_, mut cx| async move {
+            let mut project_transaction = ProjectTransaction::default();
+            for change in preview.changes {
+                let (buffer, edits) = match change {
+                    WorkspaceEditChange::Resource(op) => {
+                        apply_resource_op(fs.as_ref(), op).await?;
+                        continue;
+                    }
+                    WorkspaceEditChange::Edit { buffer, edits, .. } => (buffer, edits),
+                };
+
+                let first_transaction_id = project_transaction
+                    .0
+                    .get(&buffer)
+                    .map(|transaction| transaction.id);
+                let transaction = buffer.update(&mut cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    for (range, text) in edits {
+                        buffer.edit([(range, text)], None, cx);
+                    }
+                    buffer.end_transaction(cx)?;
+                    let transaction = buffer.finalize_last_transaction().cloned()?;
+                    if let Some(first_transaction_id) = first_transaction_id {
+                        buffer.merge_transactions(transaction.id, first_transaction_id);
+                    }
+                    Some(transaction)
+                })?;
+                let Some(transaction) = transaction else {
+                    continue;
+                };
+                match project_transaction.0.get_mut(&buffer) {
+                    Some(first_transaction) => {
+                        first_transaction.edit_ids.extend(transaction.edit_ids);
+                    }
+                    None => {
+                        project_transaction.0.insert(buffer, transaction);
+                    }
+                }
+            }
+
+            Ok(project_transaction)
+        })
+    }
+
     fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
         proto::Symbol {
             language_server_name: symbol.language_server_name.0.to_string(),
@@ -8106,6 +9019,220 @@ async fn populate_labels_for_symbols(
     }
 }
 
+/// A workspace edit returned by a language server that hasn't been applied yet, so that its
+/// changes can be reviewed and applied selectively.
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceEditPreview {
+    pub changes: Vec<WorkspaceEditChange>,
+    /// The annotations referenced by the changes, by identifier.
+    pub annotations: HashMap<String, lsp::ChangeAnnotation>,
+}
+
+#[derive(Clone, Debug)]
+pub enum WorkspaceEditChange {
+    /// A text edit of a buffer, split into several edits when it spans multiple lines.
+    Edit {
+        buffer: Model<Buffer>,
+        edits: Vec<(Range<Anchor>, String)>,
+        annotation_id: Option<String>,
+    },
+    /// The creation, renaming or deletion of a file.
+    Resource(lsp::ResourceOp),
+}
+
+impl WorkspaceEditPreview {
+    pub fn annotation(&self, change: &WorkspaceEditChange) -> Option<&lsp::ChangeAnnotation> {
+        self.annotations.get(change.annotation_id()?)
+    }
+
+    /// Whether the server asked for the change to be confirmed by the user before it's applied.
+    pub fn needs_confirmation(&self, change: &WorkspaceEditChange) -> bool {
+        self.annotation(change)
+            .and_then(|annotation| annotation.needs_confirmation)
+            .unwrap_or(false)
+    }
+}
+
+impl WorkspaceEditChange {
+    pub fn annotation_id(&self) -> Option<&str> {
+        match self {
+            Self::Edit { annotation_id, .. } => annotation_id.as_deref(),
+            Self::Resource(lsp::ResourceOp::Create(op)) => op.annotation_id.as_deref(),
+            Self::Resource(lsp::ResourceOp::Rename(op)) => op.annotation_id.as_deref(),
+            Self::Resource(lsp::ResourceOp::Delete(op)) => op.annotation_id.as_deref(),
+        }
+    }
+}
+
+/// Matches paths against the filters of one of a server's `workspace.fileOperations`
+/// capabilities.
+struct FileOperationMatcher {
+    globs: Vec<(GlobMatcher, Option<lsp::FileOperationPatternKind>)>,
+}
+
+impl FileOperationMatcher {
+    fn new(options: lsp::FileOperationRegistrationOptions) -> Self {
+        let globs = options
+            .filters
+            .into_iter()
+            .filter(|filter| {
+                filter
+                    .scheme
+                    .as_deref()
+                    .map_or(true, |scheme| scheme == "file")
+            })
+            .filter_map(|filter| {
+                let ignore_case = filter
+                    .pattern
+                    .options
+                    .and_then(|options| options.ignore_case)
+                    .unwrap_or(false);
+                let glob = GlobBuilder::new(&filter.pattern.glob)
+                    .case_insensitive(ignore_case)
+                    .literal_separator(true)
+                    .build()
+                    .log_err()?;
+                Some((glob.compile_matcher(), filter.pattern.matches))
+            })
+            .collect();
+        Self { globs }
+    }
+
+    fn matches(&self, abs_path: &Path, is_dir: bool) -> bool {
+        self.globs.iter().any(|(glob, kind)| {
+            let kind_matches = match kind {
+                Some(lsp::FileOperationPatternKind::File) => !is_dir,
+                Some(lsp::FileOperationPatternKind::Folder) => is_dir,
+                None => true,
+            };
+            kind_matches && glob.is_match(abs_path)
+        })
+    }
+}
+
+fn file_operation_matcher(
+    server: &LanguageServer,
+    operation: impl FnOnce(
+        lsp::WorkspaceFileOperationsServerCapabilities,
+    ) -> Option<lsp::FileOperationRegistrationOptions>,
+) -> Option<FileOperationMatcher> {
+    let file_operations = server.capabilities().workspace?.file_operations?;
+    Some(FileOperationMatcher::new(operation(file_operations)?))
+}
+
+/// Flattens the changes of a workspace edit into the operations to perform, in order.
+fn workspace_edit_operations(edit: lsp::WorkspaceEdit) -> Vec<lsp::DocumentChangeOperation> {
+    let mut operations = Vec::new();
+    if let Some(document_changes) = edit.document_changes {
+        match document_changes {
+            lsp::DocumentChanges::Edits(edits) => {
+                operations.extend(edits.into_iter().map(lsp::DocumentChangeOperation::Edit))
+            }
+            lsp::DocumentChanges::Operations(ops) => operations = ops,
+        }
+    } else if let Some(changes) = edit.changes {
+        operations.extend(changes.into_iter().map(|(uri, edits)| {
+            lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
+                text_document: lsp::OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits.into_iter().map(Edit::Plain).collect(),
+            })
+        }));
+    }
+    operations
+}
+
+async fn apply_resource_op(fs: &dyn Fs, op: lsp::ResourceOp) -> Result<()> {
+    match op {
+        lsp::ResourceOp::Create(op) => {
+            let abs_path = op
+                .uri
+                .to_file_path()
+                .map_err(|_| anyhow!("can't convert URI to path"))?;
+
+            if let Some(parent_path) = abs_path.parent() {
+                fs.create_dir(parent_path).await?;
+            }
+            if abs_path.ends_with("/") {
+                fs.create_dir(&abs_path).await?;
+            } else {
+                fs.create_file(
+                    &abs_path,
+                    op.options
+                        .map(|options| fs::CreateOptions {
+                            overwrite: options.overwrite.unwrap_or(false),
+                            ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
+                        })
+                        .unwrap_or_default(),
+                )
+                .await?;
+            }
+        }
+
+        lsp::ResourceOp::Rename(op) => {
+            let source_abs_path = op
+                .old_uri
+                .to_file_path()
+                .map_err(|_| anyhow!("can't convert URI to path"))?;
+            let target_abs_path = op
+                .new_uri
+                .to_file_path()
+                .map_err(|_| anyhow!("can't convert URI to path"))?;
+            fs.rename(
+                &source_abs_path,
+                &target_abs_path,
+                op.options
+                    .map(|options| fs::RenameOptions {
+                        overwrite: options.overwrite.unwrap_or(false),
+                        ignore_if_exists: options.ignore_if_exists.unwrap_or(false),
+                    })
+                    .unwrap_or_default(),
+            )
+            .await?;
+        }
+
+        lsp::ResourceOp::Delete(op) => {
+            let abs_path = op
+                .uri
+                .to_file_path()
+                .map_err(|_| anyhow!("can't convert URI to path"))?;
+            let options = op
+                .options
+                .map(|options| fs::RemoveOptions {
+                    recursive: options.recursive.unwrap_or(false),
+                    ignore_if_not_exists: options.ignore_if_not_exists.unwrap_or(false),
+                })
+                .unwrap_or_default();
+            if abs_path.ends_with("/") {
+                fs.remove_dir(&abs_path, options).await?;
+            } else {
+                fs.remove_file(&abs_path, options).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn file_rename(old_abs_path: &Path, new_abs_path: &Path) -> Option<lsp::FileRename> {
+    Some(lsp::FileRename {
+        old_uri: lsp::Url::from_file_path(old_abs_path)
+            .log_err()?
+            .to_string(),
+        new_uri: lsp::Url::from_file_path(new_abs_path)
+            .log_err()?
+            .to_string(),
+    })
+}
+
+/// Returns the server's pull diagnostics options, if it supports `textDocument/diagnostic`.
+fn diagnostic_pull_options(server: &lsp::LanguageServer) -> Option<lsp::DiagnosticOptions> {
+    match server.capabilities().diagnostic_provider? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options)
+        }
+    }
+}
+
 fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     match server.capabilities().text_document_sync.as_ref()? {
         lsp::TextDocumentSyncCapability::Kind(kind) => match *kind {
@@ -92,7 +92,8 @@ pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use task_inventory::{
-    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
+    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskChain, TaskChainStep,
+    TaskChainStepStatus, TaskSourceKind,
 };
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
@@ -103,8 +104,8 @@ pub use worktree::{
 pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
     DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
-    LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
-    SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent, WorkspaceEditChange,
+    WorkspaceEditPreview, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 pub use toolchain_store::ToolchainStore;
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -271,6 +272,10 @@ pub enum Event {
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
+    FileOperationEditsApplied {
+        title: String,
+        transaction: ProjectTransaction,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
@@ -449,6 +454,33 @@ pub struct DocumentHighlight {
     pub kind: DocumentHighlightKind,
 }
 
+/// A range of a buffer that the language server links to a document or web page.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    pub range: Range<language::Anchor>,
+    /// Servers can leave the target out until the link is resolved with
+    /// [`Project::resolve_document_link`].
+    pub target: Option<lsp::Url>,
+    pub server_id: LanguageServerId,
+    pub(crate) lsp_link: lsp::DocumentLink,
+}
+
+/// A color literal that the language server found in a buffer.
+#[derive(Clone, Debug)]
+pub struct DocumentColor {
+    pub range: Range<language::Anchor>,
+    pub color: lsp::Color,
+}
+
+/// One of the ways a color can be written, offered by the language server
+/// as a replacement for a [`DocumentColor`].
+#[derive(Clone, Debug)]
+pub struct ColorPresentation {
+    pub label: String,
+    pub text_edit: Option<(Range<language::Anchor>, String)>,
+    pub additional_text_edits: Vec<(Range<language::Anchor>, String)>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Symbol {
     pub language_server_name: LanguageServerName,
@@ -696,6 +728,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -829,6 +862,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1034,6 +1068,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problems: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -1462,8 +1497,39 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.create_entry(project_path.path, is_directory, cx)
+        let Some(abs_path) = self
+            .is_local()
+            .then(|| worktree.read(cx).absolutize(&project_path.path).log_err())
+            .flatten()
+        else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.create_entry(project_path.path, is_directory, cx)
+            });
+        };
+
+        let worktree_id = project_path.worktree_id;
+        let lsp_store = self.lsp_store.clone();
+        let will_create = lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.will_create_entry(worktree_id, &abs_path, is_directory, cx)
+        });
+        cx.spawn(move |this, mut cx| async move {
+            let transaction = will_create.await;
+            this.update(&mut cx, |_, cx| {
+                Self::file_operation_edits_applied(
+                    format!("Create {}", project_path.path.display()),
+                    transaction,
+                    cx,
+                )
+            })?;
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.create_entry(project_path.path, is_directory, cx)
+                })?
+                .await?;
+            lsp_store.update(&mut cx, |lsp_store, _| {
+                lsp_store.did_create_entry(worktree_id, &abs_path, is_directory)
+            })?;
+            Ok(entry)
         })
     }
 
@@ -1491,8 +1557,49 @@ impl Project {
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Err(anyhow!(format!("No worktree for entry {entry_id:?}"))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.rename_entry(entry_id, new_path, cx)
+        let new_path = new_path.into();
+        let Some((worktree_id, old_path, old_abs_path, new_abs_path, is_dir)) = self
+            .is_local()
+            .then(|| {
+                let worktree = worktree.read(cx);
+                let entry = worktree.entry_for_id(entry_id)?;
+                Some((
+                    worktree.id(),
+                    entry.path.clone(),
+                    worktree.absolutize(&entry.path).log_err()?,
+                    worktree.absolutize(&new_path).log_err()?,
+                    entry.is_dir(),
+                ))
+            })
+            .flatten()
+        else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.rename_entry(entry_id, new_path, cx)
+            });
+        };
+
+        let lsp_store = self.lsp_store.clone();
+        let will_rename = lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.will_rename_entry(worktree_id, &old_abs_path, &new_abs_path, is_dir, cx)
+        });
+        cx.spawn(move |this, mut cx| async move {
+            let transaction = will_rename.await;
+            this.update(&mut cx, |_, cx| {
+                Self::file_operation_edits_applied(
+                    format!("Rename {} to {}", old_path.display(), new_path.display()),
+                    transaction,
+                    cx,
+                )
+            })?;
+            let entry = worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.rename_entry(entry_id, new_path, cx)
+                })?
+                .await?;
+            lsp_store.update(&mut cx, |lsp_store, _| {
+                lsp_store.did_rename_entry(worktree_id, &old_abs_path, &new_abs_path, is_dir)
+            })?;
+            Ok(entry)
         })
     }
 
@@ -1503,9 +1610,61 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
-        worktree.update(cx, |worktree, cx| {
-            worktree.delete_entry(entry_id, trash, cx)
-        })
+        let Some((worktree_id, path, abs_path, is_dir)) = self
+            .is_local()
+            .then(|| {
+                let worktree = worktree.read(cx);
+                let entry = worktree.entry_for_id(entry_id)?;
+                Some((
+                    worktree.id(),
+                    entry.path.clone(),
+                    worktree.absolutize(&entry.path).log_err()?,
+                    entry.is_dir(),
+                ))
+            })
+            .flatten()
+        else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.delete_entry(entry_id, trash, cx)
+            });
+        };
+
+        let lsp_store = self.lsp_store.clone();
+        let will_delete = lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.will_delete_entry(worktree_id, &abs_path, is_dir, cx)
+        });
+        Some(cx.spawn(move |this, mut cx| async move {
+            let transaction = will_delete.await;
+            this.update(&mut cx, |_, cx| {
+                Self::file_operation_edits_applied(
+                    format!("Delete {}", path.display()),
+                    transaction,
+                    cx,
+                )
+            })?;
+            worktree
+                .update(&mut cx, |worktree, cx| {
+                    worktree.delete_entry(entry_id, trash, cx)
+                })?
+                .ok_or_else(|| anyhow!("no entry {entry_id:?} to delete"))?
+                .await?;
+            lsp_store.update(&mut cx, |lsp_store, _| {
+                lsp_store.did_delete_entry(worktree_id, &abs_path, is_dir)
+            })?;
+            Ok(())
+        }))
+    }
+
+    /// Lets the workspace show the edits that language servers asked to make before a file
+    /// operation, so that they can be reviewed and undone together.
+    fn file_operation_edits_applied(
+        title: String,
+        transaction: ProjectTransaction,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !transaction.0.is_empty() {
+            cx.emit(Event::FileOperationEditsApplied { title, transaction });
+        }
     }
 
     pub fn expand_entry(
@@ -2690,6 +2849,59 @@ impl Project {
         self.document_highlights_impl(buffer, position, cx)
     }
 
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        )
+    }
+
+    /// Asks the language server for the target of a link that was reported without one.
+    pub fn resolve_document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<lsp::Url>>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(buffer, link, cx)
+        })
+    }
+
+    pub fn document_colors(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentColor>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentColors,
+            cx,
+        )
+    }
+
+    pub fn color_presentations(
+        &mut self,
+        buffer: &Model<Buffer>,
+        range: Range<Anchor>,
+        color: lsp::Color,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<ColorPresentation>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetColorPresentations { range, color },
+            cx,
+        )
+    }
+
     pub fn symbols(&self, query: &str, cx: &mut ModelContext<Self>) -> Task<Result<Vec<Symbol>>> {
         self.lsp_store
             .update(cx, |lsp_store, cx| lsp_store.symbols(query, cx))
@@ -2906,6 +3118,31 @@ impl Project {
         self.perform_rename_impl(buffer, position, new_name, true, cx)
     }
 
+    /// Returns the edits that renaming the symbol at the given position would make, so that
+    /// they can be reviewed before being applied with [`Self::apply_workspace_edit_preview`].
+    pub fn preview_rename<T: ToPointUtf16>(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: T,
+        new_name: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<WorkspaceEditPreview>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.preview_rename(&buffer, position, new_name, cx)
+        })
+    }
+
+    pub fn apply_workspace_edit_preview(
+        &mut self,
+        preview: WorkspaceEditPreview,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_workspace_edit_preview(preview, cx)
+        })
+    }
+
     pub fn on_type_format<T: ToPointUtf16>(
         &mut self,
         buffer: Model<Buffer>,
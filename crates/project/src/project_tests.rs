@@ -2064,6 +2064,427 @@ async fn test_empty_diagnostic_ranges(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_pulled_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "let a = b;" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let diagnostics = |buffer: &Model<Buffer>, cx: &mut gpui::TestAppContext| {
+        buffer.update(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // The document is pulled once the server has started.
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id, None);
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                        result_id: Some("1".into()),
+                        items: vec![lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 8),
+                                lsp::Position::new(0, 9),
+                            ),
+                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                            message: "unknown variable 'b'".into(),
+                            ..Default::default()
+                        }],
+                    },
+                }),
+            ))
+        },
+    );
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diagnostics(&buffer, cx),
+        [(
+            Point::new(0, 8)..Point::new(0, 9),
+            "unknown variable 'b'".to_string()
+        )]
+    );
+
+    // Editing the buffer pulls it again with the previous result id, and an unchanged
+    // report keeps the existing diagnostics.
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id.as_deref(), Some("1"));
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Unchanged(
+                    lsp::RelatedUnchangedDocumentDiagnosticReport {
+                        related_documents: None,
+                        unchanged_document_diagnostic_report:
+                            lsp::UnchangedDocumentDiagnosticReport {
+                                result_id: "2".into(),
+                            },
+                    },
+                ),
+            ))
+        },
+    );
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diagnostics(&buffer, cx),
+        [(
+            Point::new(1, 8)..Point::new(1, 9),
+            "unknown variable 'b'".to_string()
+        )]
+    );
+
+    // A refresh request from the server pulls the document again.
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.previous_result_id.as_deref(), Some("2"));
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                        result_id: Some("3".into()),
+                        items: Vec::new(),
+                    },
+                }),
+            ))
+        },
+    );
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert!(diagnostics(&buffer, cx).is_empty());
+}
+
+#[gpui::test]
+async fn test_pulled_and_pushed_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "let a = b;" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let diagnostics = |buffer: &Model<Buffer>, cx: &mut gpui::TestAppContext| {
+        buffer.update(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+    let report = |items: Vec<lsp::Diagnostic>| {
+        lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Full(
+            lsp::RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                    result_id: None,
+                    items,
+                },
+            },
+        ))
+    };
+
+    let pulled_diagnostic = lsp::Diagnostic {
+        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 9)),
+        severity: Some(lsp::DiagnosticSeverity::ERROR),
+        message: "unknown variable 'b'".into(),
+        ..Default::default()
+    };
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(move |_, _| {
+            let report = report(vec![pulled_diagnostic.clone()]);
+            async move { Ok(report) }
+        });
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    // Published diagnostics are added to the pulled ones, and replace the ones that were
+    // published before.
+    for message in ["unused variable 'a'", "variable 'a' is never read"] {
+        fake_server.notify::<lsp::notification::PublishDiagnostics>(
+            lsp::PublishDiagnosticsParams {
+                uri: Url::from_file_path("/dir/a.rs").unwrap(),
+                version: None,
+                diagnostics: vec![lsp::Diagnostic {
+                    range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 5)),
+                    severity: Some(lsp::DiagnosticSeverity::WARNING),
+                    message: message.into(),
+                    ..Default::default()
+                }],
+            },
+        );
+        cx.executor().run_until_parked();
+        assert_eq!(
+            diagnostics(&buffer, cx),
+            [
+                (Point::new(0, 4)..Point::new(0, 5), message.to_string()),
+                (
+                    Point::new(0, 8)..Point::new(0, 9),
+                    "unknown variable 'b'".to_string()
+                ),
+            ]
+        );
+    }
+
+    // Pulling again replaces only the pulled diagnostics.
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(move |_, _| {
+            let report = report(Vec::new());
+            async move { Ok(report) }
+        });
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diagnostics(&buffer, cx),
+        [(
+            Point::new(0, 4)..Point::new(0, 5),
+            "variable 'a' is never read".to_string()
+        )]
+    );
+}
+
+#[gpui::test]
+async fn test_pulled_diagnostics_kept_across_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "let a = b;" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(|_, _| async {
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                        result_id: None,
+                        items: vec![lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 8),
+                                lsp::Position::new(0, 9),
+                            ),
+                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                            message: "unknown variable 'b'".into(),
+                            ..Default::default()
+                        }],
+                    },
+                }),
+            ))
+        });
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    // Diagnostics are published for a version of the buffer in which the pulled diagnostic
+    // has moved to the next line, before it is pulled again.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: Url::from_file_path("/dir/a.rs").unwrap(),
+        version: None,
+        diagnostics: vec![lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 5)),
+            severity: Some(lsp::DiagnosticSeverity::WARNING),
+            message: "unused variable 'a'".into(),
+            ..Default::default()
+        }],
+    });
+    cx.executor().run_until_parked();
+
+    let diagnostics = buffer.update(cx, |buffer, _| {
+        buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message))
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(
+        diagnostics,
+        [
+            (
+                Point::new(1, 4)..Point::new(1, 5),
+                "unused variable 'a'".to_string()
+            ),
+            (
+                Point::new(1, 8)..Point::new(1, 9),
+                "unknown variable 'b'".to_string()
+            ),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_pulled_diagnostics_after_dynamic_registration(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "let a = b;" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(|_, _| async {
+            Ok(lsp::DocumentDiagnosticReportResult::Report(
+                lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                        result_id: None,
+                        items: vec![lsp::Diagnostic {
+                            range: lsp::Range::new(
+                                lsp::Position::new(0, 8),
+                                lsp::Position::new(0, 9),
+                            ),
+                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                            message: "unknown variable 'b'".into(),
+                            ..Default::default()
+                        }],
+                    },
+                }),
+            ))
+        });
+
+    // The server only supports pulling diagnostics once it registers the capability.
+    fake_server
+        .request::<lsp::request::RegisterCapability>(lsp::RegistrationParams {
+            registrations: vec![lsp::Registration {
+                id: "diagnostics".to_string(),
+                method: "textDocument/diagnostic".to_string(),
+                register_options: serde_json::to_value(lsp::DiagnosticRegistrationOptions {
+                    text_document_registration_options: lsp::TextDocumentRegistrationOptions {
+                        document_selector: None,
+                    },
+                    diagnostic_options: lsp::DiagnosticOptions::default(),
+                    static_registration_options: lsp::StaticRegistrationOptions { id: None },
+                })
+                .ok(),
+            }],
+        })
+        .await
+        .unwrap();
+    cx.executor()
+        .advance_clock(lsp_store::DIAGNOSTICS_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    let diagnostics = buffer.update(cx, |buffer, _| {
+        buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message))
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(
+        diagnostics,
+        [(
+            Point::new(0, 8)..Point::new(0, 9),
+            "unknown variable 'b'".to_string()
+        )]
+    );
+}
+
 #[gpui::test]
 async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3330,6 +3751,238 @@ async fn test_buffer_identity_across_renames(cx: &mut gpui::TestAppContext) {
     buffer.update(cx, |buffer, _| assert!(!buffer.is_dirty()));
 }
 
+#[gpui::test]
+async fn test_file_operation_notifications(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "mod one;",
+            "one.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".into(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(rust_files.clone()),
+                        did_rename: Some(rust_files),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let mut fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(|params, _| async move {
+        assert_eq!(
+            params.files,
+            [lsp::FileRename {
+                old_uri: "file:///dir/one.rs".into(),
+                new_uri: "file:///dir/two.rs".into(),
+            }]
+        );
+        Ok(Some(lsp::WorkspaceEdit {
+            changes: Some(
+                [(
+                    lsp::Url::from_file_path("/dir/main.rs").unwrap(),
+                    vec![lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 7)),
+                        "two".into(),
+                    )],
+                )]
+                .into_iter()
+                .collect(),
+            ),
+            ..Default::default()
+        }))
+    });
+
+    let entry_id = project.update(cx, |project, cx| {
+        let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+        project
+            .entry_for_path(&(worktree_id, Path::new("one.rs")).into(), cx)
+            .unwrap()
+            .id
+    });
+    let events = cx.events(&project);
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, Path::new("two.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    // The edits are applied before the file is renamed, and the server is notified afterwards.
+    buffer.update(cx, |buffer, _| assert_eq!(buffer.text(), "mod two;"));
+    assert_eq!(
+        fake_server
+            .receive_notification::<lsp::notification::DidRenameFiles>()
+            .await
+            .files,
+        [lsp::FileRename {
+            old_uri: "file:///dir/one.rs".into(),
+            new_uri: "file:///dir/two.rs".into(),
+        }]
+    );
+    let edits_applied = events
+        .filter(|event| future::ready(matches!(event, Event::FileOperationEditsApplied { .. })))
+        .next()
+        .await
+        .unwrap();
+    let Event::FileOperationEditsApplied { transaction, .. } = edits_applied else {
+        unreachable!()
+    };
+    assert!(transaction.0.keys().eq([&buffer]));
+}
+
+#[gpui::test]
+async fn test_file_operation_edits_from_multiple_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "mod one;\nuse one::one;",
+            "one.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".into(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_servers = ["first-server", "second-server"].map(|name| {
+        language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                name,
+                capabilities: lsp::ServerCapabilities {
+                    workspace: Some(lsp::WorkspaceServerCapabilities {
+                        workspace_folders: None,
+                        file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                            will_rename: Some(rust_files.clone()),
+                            ..Default::default()
+                        }),
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    // Each server renames the module on a different line.
+    let mut running_servers = Vec::new();
+    for (row, fake_servers) in fake_servers.iter_mut().enumerate() {
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(move |_, _| async move {
+            Ok(Some(lsp::WorkspaceEdit {
+                changes: Some(
+                    [(
+                        lsp::Url::from_file_path("/dir/main.rs").unwrap(),
+                        vec![lsp::TextEdit::new(
+                            lsp::Range::new(
+                                lsp::Position::new(row as u32, 4),
+                                lsp::Position::new(row as u32, 7),
+                            ),
+                            "two".into(),
+                        )],
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                ..Default::default()
+            }))
+        });
+        running_servers.push(fake_server);
+    }
+    cx.executor().run_until_parked();
+
+    let entry_id = project.update(cx, |project, cx| {
+        let worktree_id = project.worktrees(cx).next().unwrap().read(cx).id();
+        project
+            .entry_for_path(&(worktree_id, Path::new("one.rs")).into(), cx)
+            .unwrap()
+            .id
+    });
+    let events = cx.events(&project);
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, Path::new("two.rs"), cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "mod two;\nuse two::one;")
+    });
+
+    // The edits of both servers form a single transaction, which is undone at once.
+    let edits_applied = events
+        .filter(|event| future::ready(matches!(event, Event::FileOperationEditsApplied { .. })))
+        .next()
+        .await
+        .unwrap();
+    let Event::FileOperationEditsApplied { transaction, .. } = edits_applied else {
+        unreachable!()
+    };
+    assert!(transaction.0.keys().eq([&buffer]));
+    let transaction_id = transaction.0[&buffer].id;
+    buffer.update(cx, |buffer, cx| {
+        buffer.undo_transaction(transaction_id, cx);
+        assert_eq!(buffer.text(), "mod one;\nuse one::one;");
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_deduping(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4046,6 +4699,191 @@ async fn test_rename(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_rename_preview(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;"
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                rename_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let response = project.update(cx, |project, cx| {
+        project.preview_rename(buffer.clone(), 7, "THREE".to_string(), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::Rename, _, _>(|params, _| async move {
+            assert_eq!(params.new_name, "THREE");
+            let edit = |uri: &str, edits| {
+                lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
+                    text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                        uri: lsp::Url::from_file_path(uri).unwrap(),
+                        version: None,
+                    },
+                    edits,
+                })
+            };
+            Ok(Some(lsp::WorkspaceEdit {
+                document_changes: Some(lsp::DocumentChanges::Operations(vec![
+                    edit(
+                        "/dir/one.rs",
+                        vec![lsp::Edit::Plain(lsp::TextEdit::new(
+                            lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 9)),
+                            "THREE".to_string(),
+                        ))],
+                    ),
+                    edit(
+                        "/dir/two.rs",
+                        vec![
+                            lsp::Edit::Plain(lsp::TextEdit::new(
+                                lsp::Range::new(
+                                    lsp::Position::new(0, 24),
+                                    lsp::Position::new(0, 27),
+                                ),
+                                "THREE".to_string(),
+                            )),
+                            lsp::Edit::Annotated(lsp::AnnotatedTextEdit {
+                                text_edit: lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(0, 35),
+                                        lsp::Position::new(0, 38),
+                                    ),
+                                    "THREE".to_string(),
+                                ),
+                                annotation_id: "unsure".to_string(),
+                            }),
+                        ],
+                    ),
+                    lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(lsp::CreateFile {
+                        uri: lsp::Url::from_file_path("/dir/three.rs").unwrap(),
+                        options: None,
+                        annotation_id: None,
+                    })),
+                ])),
+                change_annotations: Some(
+                    [(
+                        "unsure".to_string(),
+                        lsp::ChangeAnnotation {
+                            label: "Possible reference".to_string(),
+                            needs_confirmation: Some(true),
+                            description: None,
+                        },
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+                ..Default::default()
+            }))
+        })
+        .next()
+        .await
+        .unwrap();
+    let preview = response.await.unwrap();
+
+    // Previewing the rename doesn't modify anything.
+    assert_eq!(preview.changes.len(), 4);
+    assert_eq!(
+        buffer.update(cx, |buffer, _| buffer.text()),
+        "const ONE: usize = 1;"
+    );
+    assert!(!fs.is_file(Path::new("/dir/three.rs")).await);
+    let needs_confirmation = preview
+        .changes
+        .iter()
+        .map(|change| preview.needs_confirmation(change))
+        .collect::<Vec<_>>();
+    assert_eq!(needs_confirmation, [false, false, true, false]);
+
+    // Apply all the changes but the one that needs confirmation.
+    let mut selected = preview.clone();
+    selected.changes.remove(2);
+    let mut transaction = project
+        .update(cx, |project, cx| {
+            project.apply_workspace_edit_preview(selected, cx)
+        })
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(transaction.len(), 2);
+    assert_eq!(
+        transaction
+            .remove_entry(&buffer)
+            .unwrap()
+            .0
+            .update(cx, |buffer, _| buffer.text()),
+        "const THREE: usize = 1;"
+    );
+    assert_eq!(
+        transaction
+            .into_keys()
+            .next()
+            .unwrap()
+            .update(cx, |buffer, _| buffer.text()),
+        "const TWO: usize = one::THREE + one::ONE;"
+    );
+    assert!(fs.is_file(Path::new("/dir/three.rs")).await);
+}
+
+#[gpui::test]
+async fn test_rename_preview_without_rename_provider(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "one.rs": "const ONE: usize = 1;" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+    let _fake_server = fake_servers.next().await.unwrap();
+
+    // The server isn't asked to rename, as it doesn't support it.
+    let preview = project
+        .update(cx, |project, cx| {
+            project.preview_rename(buffer.clone(), 7, "THREE".to_string(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(preview.changes.is_empty());
+}
+
 #[gpui::test]
 async fn test_search(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -28,6 +28,38 @@ use crate::worktree_store::WorktreeStore;
 pub struct Inventory {
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
     templates_from_settings: ParsedTemplates,
+    task_chains: Vec<TaskChain>,
+}
+
+/// Progress of a task that runs the tasks it depends on first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskChain {
+    /// Id of the task the chain was spawned for.
+    pub task_id: TaskId,
+    /// Label of the task the chain was spawned for.
+    pub label: String,
+    /// All tasks of the chain, the tasks depended on coming before the tasks depending on them.
+    pub steps: Vec<TaskChainStep>,
+}
+
+/// A task of a [`TaskChain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskChainStep {
+    pub label: String,
+    pub status: TaskChainStepStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskChainStepStatus {
+    /// Waits for the tasks it depends on.
+    Pending,
+    Running,
+    /// A background task that is running and ready for the tasks depending on it.
+    Ready,
+    Succeeded,
+    Failed,
+    /// Not run, as the chain was cancelled or another task of it failed.
+    Cancelled,
 }
 
 #[derive(Debug, Default)]
@@ -174,12 +206,9 @@ impl Inventory {
             .templates_from_settings(worktree)
             .chain(language_tasks)
             .filter_map(|(kind, task)| {
-                let id_base = kind.to_id_base();
-                Some((
-                    kind,
-                    task.resolve_task(&id_base, task_context)?,
-                    not_used_score,
-                ))
+                let resolved_task =
+                    self.resolve_task_with_dependencies(worktree, &kind, &task, task_context)?;
+                Some((kind, resolved_task, not_used_score))
             })
             .filter(|(_, resolved_task, _)| {
                 match task_labels_to_ids.entry(resolved_task.resolved_label.clone()) {
@@ -200,6 +229,33 @@ impl Inventory {
         (previously_spawned_tasks, new_resolved_tasks)
     }
 
+    /// Resolves the task template given with the [`TaskContext`], along with the tasks it depends on.
+    /// Dependencies are looked up by their labels among the global tasks and the tasks of the task's worktree.
+    pub fn resolve_task_with_dependencies(
+        &self,
+        worktree: Option<WorktreeId>,
+        task_source_kind: &TaskSourceKind,
+        template: &TaskTemplate,
+        task_context: &TaskContext,
+    ) -> Option<ResolvedTask> {
+        let id_base = task_source_kind.to_id_base();
+        if template.depends_on.is_empty() {
+            return template.resolve_task(&id_base, task_context);
+        }
+
+        let worktree = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => worktree,
+        };
+        let templates = self
+            .templates_from_settings(worktree)
+            .map(|(kind, template)| (kind.to_id_base(), template))
+            .collect::<Vec<_>>();
+        template
+            .resolve_task_with_dependencies(&id_base, task_context, &templates)
+            .log_err()
+    }
+
     /// Returns the last scheduled task by task_id if provided.
     /// Otherwise, returns the last scheduled task.
     pub fn last_scheduled_task(
@@ -229,6 +285,28 @@ impl Inventory {
         }
     }
 
+    /// Task chains that are currently running, in the order they were spawned.
+    pub fn task_chains(&self) -> &[TaskChain] {
+        &self.task_chains
+    }
+
+    /// Stores the progress of a running task chain, replacing the previous progress of the same chain.
+    pub fn update_task_chain(&mut self, task_chain: TaskChain) {
+        match self
+            .task_chains
+            .iter_mut()
+            .find(|existing| existing.task_id == task_chain.task_id)
+        {
+            Some(existing) => *existing = task_chain,
+            None => self.task_chains.push(task_chain),
+        }
+    }
+
+    pub fn remove_task_chain(&mut self, task_id: &TaskId) {
+        self.task_chains
+            .retain(|task_chain| &task_chain.task_id != task_id);
+    }
+
     /// Deletes a resolved task from history, using its id.
     /// A similar may still resurface in `used_and_current_resolved_tasks` when its [`TaskTemplate`] is resolved again.
     pub fn delete_previously_used(&mut self, id: &TaskId) {
@@ -1,17 +1,18 @@
 use crate::Project;
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, WeakModel};
 use itertools::Itertools;
+use lsp::LanguageServerId;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
     borrow::Cow,
     env::{self},
     iter,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
-use task::{Shell, SpawnInTerminal};
+use task::{HideStrategy, Problem, ProblemSeverity, Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{self, TerminalSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -23,6 +24,16 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    pub(crate) task_problems: HashMap<TaskId, TaskProblems>,
+}
+
+/// The problems found in the output of a task's last run, reported as diagnostics
+/// of a language server of their own.
+pub(crate) struct TaskProblems {
+    server_id: LanguageServerId,
+    /// The directory relative paths in the task's output are resolved against.
+    cwd: Option<PathBuf>,
+    problems_by_path: HashMap<PathBuf, Vec<Problem>>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -33,6 +44,53 @@ pub enum TerminalKind {
     Shell(Option<PathBuf>),
     /// Run a task.
     Task(SpawnInTerminal),
+    /// Run a shell below the output saved from a terminal of a previous session.
+    Restored(RestoredTerminal),
+}
+
+/// A terminal saved when Zed quit, to be reopened when it restarts.
+#[derive(Debug)]
+pub struct RestoredTerminal {
+    pub working_directory: Option<PathBuf>,
+    /// The output of the terminal, shown above the new shell's prompt.
+    pub scrollback: Option<String>,
+    /// The task the terminal ran, if it was a task terminal.
+    pub task: Option<RestoredTask>,
+}
+
+#[derive(Debug)]
+pub struct RestoredTask {
+    pub id: TaskId,
+    pub full_label: String,
+    pub label: String,
+    pub command_label: String,
+    pub status: TaskStatus,
+}
+
+fn problem_to_diagnostic(problem: &Problem) -> lsp::Diagnostic {
+    let start = lsp::Position::new(
+        problem.row.saturating_sub(1),
+        problem.column.unwrap_or(1).saturating_sub(1),
+    );
+    let end = match (problem.end_row, problem.end_column) {
+        (Some(end_row), Some(end_column)) => {
+            lsp::Position::new(end_row.saturating_sub(1), end_column.saturating_sub(1))
+        }
+        (None, Some(end_column)) => lsp::Position::new(start.line, end_column.saturating_sub(1)),
+        _ => start,
+    };
+    lsp::Diagnostic {
+        range: lsp::Range::new(start, end),
+        severity: Some(match problem.severity {
+            ProblemSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+            ProblemSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+            ProblemSeverity::Info => lsp::DiagnosticSeverity::INFORMATION,
+        }),
+        code: problem.code.clone().map(lsp::NumberOrString::String),
+        source: Some(problem.source.clone()),
+        message: problem.message.clone(),
+        ..lsp::Diagnostic::default()
+    }
 }
 
 /// SshCommand describes how to connect to a remote server
@@ -90,6 +148,7 @@ impl Project {
     ) -> anyhow::Result<Model<Terminal>> {
         let path = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| path.to_path_buf()),
+            TerminalKind::Restored(restored) => restored.working_directory.clone(),
             TerminalKind::Task(spawn_task) => {
                 if let Some(cwd) = &spawn_task.cwd {
                     Some(cwd.clone())
@@ -132,9 +191,39 @@ impl Project {
             .as_ref()
             .and_then(|path| self.python_venv_directory(path, settings, cx));
         let mut python_venv_activate_command = None;
+        let mut restored_scrollback = None;
+
+        let task_with_problem_matchers = match &kind {
+            TerminalKind::Task(spawn_task) => Some((
+                spawn_task.id.clone(),
+                !spawn_task.problem_matchers.is_empty(),
+            )),
+            _ => None,
+        };
 
         let (spawn_task, shell) = match kind {
-            TerminalKind::Shell(_) => {
+            TerminalKind::Shell(_) | TerminalKind::Restored(_) => {
+                // A restored task does not run again: its terminal keeps the task's
+                // label and exit status, and gets a shell below the saved output.
+                let task_state = match kind {
+                    TerminalKind::Restored(restored) => {
+                        restored_scrollback = restored.scrollback;
+                        restored.task.map(|task| TaskState {
+                            id: task.id,
+                            full_label: task.full_label,
+                            label: task.label,
+                            command_label: task.command_label,
+                            hide: HideStrategy::Never,
+                            status: task.status,
+                            completion_rx,
+                            problem_matchers: Vec::new(),
+                            is_background: false,
+                            ready_pattern: None,
+                        })
+                    }
+                    _ => None,
+                };
+
                 if let Some(python_venv_directory) = python_venv_directory {
                     python_venv_activate_command =
                         self.python_activate_command(&python_venv_directory, settings);
@@ -155,7 +244,7 @@ impl Project {
                             wrap_for_ssh(ssh_command, None, path.as_deref(), env, None);
                         env = HashMap::default();
                         (
-                            None,
+                            task_state,
                             Shell::WithArguments {
                                 program,
                                 args,
@@ -163,7 +252,7 @@ impl Project {
                             },
                         )
                     }
-                    None => (None, settings.shell.clone()),
+                    None => (task_state, settings.shell.clone()),
                 }
             }
             TerminalKind::Task(spawn_task) => {
@@ -175,6 +264,9 @@ impl Project {
                     hide: spawn_task.hide,
                     status: TaskStatus::Running,
                     completion_rx,
+                    problem_matchers: spawn_task.problem_matchers,
+                    is_background: spawn_task.is_background,
+                    ready_pattern: spawn_task.ready_pattern,
                 });
 
                 env.extend(spawn_task.env);
@@ -235,6 +327,8 @@ impl Project {
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
             ssh_details.is_some(),
+            settings.shell_integration,
+            restored_scrollback,
             window,
             completion_tx,
             cx,
@@ -263,12 +357,118 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 self.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+
+            if let Some((task_id, has_problem_matchers)) = task_with_problem_matchers {
+                // The problems of the previous run are outdated once the task reruns.
+                self.clear_task_problems(&task_id, cx);
+                if has_problem_matchers {
+                    self.terminals.task_problems.insert(
+                        task_id.clone(),
+                        TaskProblems {
+                            server_id: self.languages.next_language_server_id(),
+                            cwd: path.clone(),
+                            problems_by_path: HashMap::default(),
+                        },
+                    );
+                    cx.subscribe(&terminal_handle, move |project, _, event, cx| {
+                        if let terminal::Event::TaskProblems(problems) = event {
+                            project.add_task_problems(&task_id, problems, cx);
+                        }
+                    })
+                    .detach();
+                }
+            }
             terminal_handle
         });
 
         terminal
     }
 
+    fn add_task_problems(
+        &mut self,
+        task_id: &TaskId,
+        problems: &[Problem],
+        cx: &mut ModelContext<Self>,
+    ) {
+        let fallback_cwd = self.first_project_directory(cx);
+        let Some(task_problems) = self.terminals.task_problems.get_mut(task_id) else {
+            return;
+        };
+
+        let mut updated_paths = HashSet::default();
+        for problem in problems {
+            let abs_path = match task_problems.cwd.as_ref().or(fallback_cwd.as_ref()) {
+                Some(cwd) => cwd.join(&problem.path),
+                None => problem.path.clone(),
+            };
+            let abs_path = abs_path
+                .components()
+                .filter(|component| *component != Component::CurDir)
+                .collect::<PathBuf>();
+            let path_problems = task_problems
+                .problems_by_path
+                .entry(abs_path.clone())
+                .or_default();
+            // Output lines may be scanned more than once, when the terminal trims its scrollback.
+            let is_duplicate = path_problems.iter().any(|existing| {
+                existing.row == problem.row
+                    && existing.column == problem.column
+                    && existing.severity == problem.severity
+                    && existing.message == problem.message
+            });
+            if !is_duplicate {
+                path_problems.push(problem.clone());
+                updated_paths.insert(abs_path);
+            }
+        }
+
+        let server_id = task_problems.server_id;
+        let updates = updated_paths
+            .into_iter()
+            .map(|abs_path| {
+                let diagnostics = task_problems.problems_by_path[&abs_path]
+                    .iter()
+                    .map(problem_to_diagnostic)
+                    .collect::<Vec<_>>();
+                (abs_path, diagnostics)
+            })
+            .collect::<Vec<_>>();
+        for (abs_path, diagnostics) in updates {
+            self.publish_task_diagnostics(server_id, &abs_path, diagnostics, cx);
+        }
+    }
+
+    fn clear_task_problems(&mut self, task_id: &TaskId, cx: &mut ModelContext<Self>) {
+        if let Some(task_problems) = self.terminals.task_problems.remove(task_id) {
+            for abs_path in task_problems.problems_by_path.keys() {
+                self.publish_task_diagnostics(task_problems.server_id, abs_path, Vec::new(), cx);
+            }
+        }
+    }
+
+    fn publish_task_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        abs_path: &Path,
+        diagnostics: Vec<lsp::Diagnostic>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return;
+        };
+        self.update_diagnostics(
+            server_id,
+            lsp::PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            },
+            &[],
+            cx,
+        )
+        .log_err();
+    }
+
     pub fn python_venv_directory(
         &self,
         abs_path: &Path,
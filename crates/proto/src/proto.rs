@@ -196,6 +196,12 @@ messages!(
     (GetDeclarationResponse, Background),
     (GetDocumentHighlights, Background),
     (GetDocumentHighlightsResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GetDocumentColors, Background),
+    (GetDocumentColorsResponse, Background),
+    (GetColorPresentations, Background),
+    (GetColorPresentationsResponse, Background),
     (GetHover, Background),
     (GetHoverResponse, Background),
     (GetNotifications, Foreground),
@@ -271,6 +277,8 @@ messages!(
     (RequestContact, Foreground),
     (ResolveCompletionDocumentation, Background),
     (ResolveCompletionDocumentationResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
     (ResolveInlayHint, Background),
     (ResolveInlayHintResponse, Background),
     (RespondToChannelInvite, Foreground),
@@ -401,6 +409,9 @@ request_messages!(
     (GetDeclaration, GetDeclarationResponse),
     (GetImplementation, GetImplementationResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (GetDocumentColors, GetDocumentColorsResponse),
+    (GetColorPresentations, GetColorPresentationsResponse),
     (GetHover, GetHoverResponse),
     (GetLlmToken, GetLlmTokenResponse),
     (GetNotifications, GetNotificationsResponse),
@@ -449,6 +460,7 @@ request_messages!(
         ResolveCompletionDocumentation,
         ResolveCompletionDocumentationResponse
     ),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
     (ResolveInlayHint, ResolveInlayHintResponse),
     (RespondToChannelInvite, Ack),
     (RespondToContactRequest, Ack),
@@ -516,6 +528,9 @@ entity_messages!(
     GetDeclaration,
     GetImplementation,
     GetDocumentHighlights,
+    GetDocumentLinks,
+    GetDocumentColors,
+    GetColorPresentations,
     GetHover,
     GetProjectSymbols,
     GetReferences,
@@ -539,6 +554,7 @@ entity_messages!(
     RemoveProjectCollaborator,
     RenameProjectEntry,
     ResolveCompletionDocumentation,
+    ResolveDocumentLink,
     ResolveInlayHint,
     SaveBuffer,
     StartLanguageServer,
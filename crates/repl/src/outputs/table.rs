@@ -54,8 +54,14 @@
 //! # Display the DataFrame
 //! display(df)
 //! ```
-use gpui::{AnyElement, ClipboardItem, TextRun};
-use runtimelib::datatable::TableSchema;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use gpui::{
+    table, AnyElement, ClipboardItem, Div, FocusHandle, SortDirection, TableColumn, TableState,
+    TextRun,
+};
+use runtimelib::datatable::{FieldType, TableSchema};
 use runtimelib::media::datatable::TabularDataResource;
 use serde_json::Value;
 use settings::Settings;
@@ -64,11 +70,12 @@ use ui::{div, prelude::*, v_flex, IntoElement, Styled};
 
 use crate::outputs::OutputContent;
 
-/// TableView renders a static table inline in a buffer.
+/// TableView renders a table inline in a buffer, only laying out the rows that are scrolled into view.
 /// It uses the https://specs.frictionlessdata.io/tabular-data-resource/ specification for data interchange.
 pub struct TableView {
     pub table: TabularDataResource,
-    pub widths: Vec<Pixels>,
+    state: TableState,
+    focus_handle: FocusHandle,
     cached_clipboard_content: ClipboardItem,
 }
 
@@ -83,14 +90,44 @@ fn cell_content(row: &Value, field: &str) -> String {
     }
 }
 
+/// Orders numbers numerically and everything else by its displayed content, with empty cells first.
+fn compare_cells(a: &Value, b: &Value, field: &str) -> Ordering {
+    match (a.get(field), b.get(field)) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        _ => cell_content(a, field).cmp(&cell_content(b, field)),
+    }
+}
+
+fn is_right_aligned(field_type: &FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Number
+            | FieldType::Integer
+            | FieldType::Date
+            | FieldType::Time
+            | FieldType::Datetime
+            | FieldType::Year
+            | FieldType::Duration
+            | FieldType::Yearmonth
+    )
+}
+
 // Declare constant for the padding multiple on the line height
 const TABLE_Y_PADDING_MULTIPLE: f32 = 0.5;
 
-impl TableView {
-    pub fn new(table: &TabularDataResource, cx: &mut WindowContext) -> Self {
-        let mut widths = Vec::with_capacity(table.schema.fields.len());
+/// Only the first rows are measured to size the columns, so that large dataframes display immediately.
+/// Columns can be resized by dragging their header.
+const MEASURED_ROWS: usize = 100;
 
-        let text_system = cx.text_system();
+/// Taller tables scroll within the output.
+const MAX_VISIBLE_ROWS: usize = 15;
+
+impl TableView {
+    pub fn new(table: &TabularDataResource, cx: &mut ViewContext<Self>) -> Self {
+        let text_system = cx.text_system().clone();
         let text_style = cx.text_style();
         let text_font = ThemeSettings::get_global(cx).buffer_font.clone();
         let font_size = ThemeSettings::get_global(cx).buffer_font_size;
@@ -102,39 +139,51 @@ impl TableView {
             underline: None,
             strikethrough: None,
         }];
-
-        for field in table.schema.fields.iter() {
-            runs[0].len = field.name.len();
-            let mut width = text_system
-                .layout_line(&field.name, font_size, &runs)
+        let mut text_width = |text: &str| {
+            runs[0].len = text.len();
+            text_system
+                .layout_line(text, font_size, &runs)
                 .map(|layout| layout.width)
-                .unwrap_or(px(0.));
-
-            let Some(data) = table.data.as_ref() else {
-                widths.push(width);
-                continue;
-            };
-
-            for row in data {
-                let content = cell_content(row, &field.name);
-                runs[0].len = content.len();
-                let cell_width = cx
-                    .text_system()
-                    .layout_line(&content, font_size, &runs)
-                    .map(|layout| layout.width)
-                    .unwrap_or(px(0.));
-
-                width = width.max(cell_width)
-            }
-
-            widths.push(width)
-        }
+                .unwrap_or(px(0.))
+        };
+
+        let schema = Rc::new(table.schema.clone());
+        let data = Rc::new(table.data.clone().unwrap_or_default());
+
+        let columns = schema
+            .fields
+            .iter()
+            .map(|field| {
+                let mut width = text_width(&field.name);
+                for row in data.iter().take(MEASURED_ROWS) {
+                    width = width.max(text_width(&cell_content(row, &field.name)));
+                }
+                // Width fudge factor: border + 2 (heading), padding
+                TableColumn::new(field.name.clone(), width + px(22.))
+            })
+            .collect();
+
+        let state = TableState::new(columns, data.len(), {
+            let schema = schema.clone();
+            let data = data.clone();
+            move |cell, cx| Self::render_cell(&schema, &data[cell.row], cell.column, cx)
+        })
+        .with_header({
+            let schema = schema.clone();
+            move |column, sort, cx| Self::render_header(&schema, column, sort, cx)
+        })
+        .with_comparator({
+            let schema = schema.clone();
+            let data = data.clone();
+            move |column, a, b| compare_cells(&data[a], &data[b], &schema.fields[column].name)
+        });
 
         let cached_clipboard_content = Self::create_clipboard_content(table);
 
         Self {
             table: table.clone(),
-            widths,
+            state,
+            focus_handle: cx.focus_handle(),
             cached_clipboard_content: ClipboardItem::new_string(cached_clipboard_content),
         }
     }
@@ -194,92 +243,68 @@ impl TableView {
         markdown
     }
 
-    pub fn render_row(
-        &self,
-        schema: &TableSchema,
-        is_header: bool,
-        row: &Value,
-        cx: &WindowContext,
-    ) -> AnyElement {
-        let theme = cx.theme();
-
+    fn cell_container(field_type: &FieldType, cx: &WindowContext) -> Div {
         let line_height = cx.line_height();
+        let container = if is_right_aligned(field_type) {
+            v_flex().items_end()
+        } else {
+            div()
+        };
 
-        let row_cells = schema
-            .fields
-            .iter()
-            .zip(self.widths.iter())
-            .map(|(field, width)| {
-                let container = match field.field_type {
-                    runtimelib::datatable::FieldType::String => div(),
-
-                    runtimelib::datatable::FieldType::Number
-                    | runtimelib::datatable::FieldType::Integer
-                    | runtimelib::datatable::FieldType::Date
-                    | runtimelib::datatable::FieldType::Time
-                    | runtimelib::datatable::FieldType::Datetime
-                    | runtimelib::datatable::FieldType::Year
-                    | runtimelib::datatable::FieldType::Duration
-                    | runtimelib::datatable::FieldType::Yearmonth => v_flex().items_end(),
-
-                    _ => div(),
-                };
-
-                let value = cell_content(row, &field.name);
-
-                let mut cell = container
-                    .min_w(*width + px(22.))
-                    .w(*width + px(22.))
-                    .child(value)
-                    .px_2()
-                    .py((TABLE_Y_PADDING_MULTIPLE / 2.0) * line_height)
-                    .border_color(theme.colors().border);
-
-                if is_header {
-                    cell = cell.border_1().bg(theme.colors().border_focused)
-                } else {
-                    cell = cell.border_1()
-                }
-                cell
-            })
-            .collect::<Vec<_>>();
+        container
+            .size_full()
+            .px_2()
+            .py((TABLE_Y_PADDING_MULTIPLE / 2.0) * line_height)
+            .border_1()
+            .border_color(cx.theme().colors().border)
+    }
 
-        let mut total_width = px(0.);
-        for width in self.widths.iter() {
-            // Width fudge factor: border + 2 (heading), padding
-            total_width += *width + px(22.);
-        }
+    fn render_header(
+        schema: &TableSchema,
+        column: usize,
+        sort: Option<SortDirection>,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        let field = &schema.fields[column];
+        let indicator = match sort {
+            Some(SortDirection::Ascending) => " ▲",
+            Some(SortDirection::Descending) => " ▼",
+            None => "",
+        };
 
-        h_flex()
-            .w(total_width)
-            .children(row_cells)
+        Self::cell_container(&field.field_type, cx)
+            .bg(cx.theme().colors().border_focused)
+            .child(format!("{}{}", field.name, indicator))
+            .into_any_element()
+    }
+
+    fn render_cell(
+        schema: &TableSchema,
+        row: &Value,
+        column: usize,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        let field = &schema.fields[column];
+        Self::cell_container(&field.field_type, cx)
+            .child(cell_content(row, &field.name))
             .into_any_element()
     }
 }
 
 impl Render for TableView {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let data = match &self.table.data {
-            Some(data) => data,
-            None => return div().into_any_element(),
-        };
-
-        let mut headings = serde_json::Map::new();
-        for field in &self.table.schema.fields {
-            headings.insert(field.name.clone(), Value::String(field.name.clone()));
+        if self.table.data.is_none() {
+            return div().into_any_element();
         }
-        let header = self.render_row(&self.table.schema, true, &Value::Object(headings), cx);
 
-        let body = data
-            .iter()
-            .map(|row| self.render_row(&self.table.schema, false, row, cx));
+        // Rows are a line tall, plus their padding and borders. The extra row is the header.
+        let row_height = cx.line_height() * (1.0 + TABLE_Y_PADDING_MULTIPLE) + px(2.);
+        let visible_rows = self.state.row_count().min(MAX_VISIBLE_ROWS) + 1;
 
-        v_flex()
-            .id("table")
-            .overflow_x_scroll()
+        table("table", self.state.clone())
+            .track_focus(&self.focus_handle)
             .w_full()
-            .child(header)
-            .children(body)
+            .h(row_height * visible_rows as f32)
             .into_any_element()
     }
 }
@@ -0,0 +1,231 @@
+use crate::EmbeddingProvider;
+#[cfg(feature = "local-embeddings")]
+use crate::{Embedding, TextToEmbed};
+#[cfg(feature = "local-embeddings")]
+use anyhow::Context as _;
+use anyhow::{anyhow, Result};
+#[cfg(feature = "local-embeddings")]
+use futures::{future::BoxFuture, FutureExt};
+use gpui::BackgroundExecutor;
+#[cfg(feature = "local-embeddings")]
+use ort::{
+    session::{builder::GraphOptimizationLevel, Session},
+    value::{DynValue, Tensor},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+#[cfg(feature = "local-embeddings")]
+use tokenizers::{Tokenizer, TruncationParams};
+
+/// A sentence embedding model that runs in-process, on the CPU.
+///
+/// The model is loaded from a directory containing a BERT-style model exported
+/// to ONNX (`model.onnx`) and its tokenizer (`tokenizer.json`), such as
+/// `all-MiniLM-L6-v2` or `bge-small-en-v1.5`. Token embeddings are mean-pooled.
+///
+/// Running the model requires Zed to be built with the `local-embeddings` feature, and the ONNX
+/// Runtime shared library to be installed, or pointed to by `ORT_DYLIB_PATH`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalEmbeddingModel {
+    pub model_path: PathBuf,
+    pub tokenizer_path: PathBuf,
+    /// Longer texts are truncated to this many tokens.
+    pub max_tokens: usize,
+}
+
+impl LocalEmbeddingModel {
+    pub fn from_dir(dir: &Path) -> Self {
+        Self {
+            model_path: dir.join("model.onnx"),
+            tokenizer_path: dir.join("tokenizer.json"),
+            max_tokens: 512,
+        }
+    }
+
+    /// A name identifying the model, used to keep its embeddings apart from
+    /// those of other models.
+    pub fn name(&self) -> String {
+        self.model_path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map_or_else(
+                || "local".to_string(),
+                |name| name.to_string_lossy().to_string(),
+            )
+    }
+
+    pub fn provider(&self, executor: BackgroundExecutor) -> Result<Arc<dyn EmbeddingProvider>> {
+        #[cfg(feature = "local-embeddings")]
+        {
+            Ok(Arc::new(LocalEmbeddingProvider::new(self, executor)?))
+        }
+        #[cfg(not(feature = "local-embeddings"))]
+        {
+            drop(executor);
+            Err(anyhow!(
+                "local embedding models require building with the `local-embeddings` feature"
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+pub struct LocalEmbeddingProvider {
+    model: Arc<LoadedModel>,
+    executor: BackgroundExecutor,
+}
+
+#[cfg(feature = "local-embeddings")]
+struct LoadedModel {
+    session: Session,
+    tokenizer: Tokenizer,
+    uses_token_type_ids: bool,
+}
+
+#[cfg(feature = "local-embeddings")]
+impl LocalEmbeddingProvider {
+    pub fn new(model: &LocalEmbeddingModel, executor: BackgroundExecutor) -> Result<Self> {
+        let mut tokenizer = Tokenizer::from_file(&model.tokenizer_path)
+            .map_err(|error| anyhow!(error))
+            .with_context(|| format!("failed to load tokenizer {:?}", model.tokenizer_path))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: model.max_tokens,
+                ..Default::default()
+            }))
+            .map_err(|error| anyhow!(error))?;
+        tokenizer.with_padding(None);
+
+        let session = Session::builder()?
+            .with_optimization_level(GraphOptimizationLevel::Level3)?
+            .with_intra_threads(executor.num_cpus())?
+            .commit_from_file(&model.model_path)
+            .with_context(|| format!("failed to load embedding model {:?}", model.model_path))?;
+        let uses_token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+
+        Ok(Self {
+            model: Arc::new(LoadedModel {
+                session,
+                tokenizer,
+                uses_token_type_ids,
+            }),
+            executor,
+        })
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+impl LoadedModel {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Embedding>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(|error| anyhow!(error))?;
+        let batch_size = encodings.len();
+        let sequence_len = encodings
+            .iter()
+            .map(|encoding| encoding.len())
+            .max()
+            .unwrap_or(0);
+
+        // Pad every sequence to the length of the longest one.
+        let mut input_ids = vec![0i64; batch_size * sequence_len];
+        let mut attention_mask = vec![0i64; batch_size * sequence_len];
+        let mut token_type_ids = vec![0i64; batch_size * sequence_len];
+        for (row, encoding) in encodings.iter().enumerate() {
+            let offset = row * sequence_len;
+            for (column, ((id, mask), type_id)) in encoding
+                .get_ids()
+                .iter()
+                .zip(encoding.get_attention_mask())
+                .zip(encoding.get_type_ids())
+                .enumerate()
+            {
+                input_ids[offset + column] = *id as i64;
+                attention_mask[offset + column] = *mask as i64;
+                token_type_ids[offset + column] = *type_id as i64;
+            }
+        }
+
+        let shape = [batch_size, sequence_len];
+        let mut inputs: Vec<(&str, DynValue)> = vec![
+            (
+                "input_ids",
+                Tensor::from_array((shape, input_ids))?.into_dyn(),
+            ),
+            (
+                "attention_mask",
+                Tensor::from_array((shape, attention_mask.clone()))?.into_dyn(),
+            ),
+        ];
+        if self.uses_token_type_ids {
+            inputs.push((
+                "token_type_ids",
+                Tensor::from_array((shape, token_type_ids))?.into_dyn(),
+            ));
+        }
+
+        let outputs = self.session.run(inputs)?;
+        let (output_shape, hidden_states) = outputs[0].try_extract_raw_tensor::<f32>()?;
+        let dimensions = match output_shape {
+            [batch, sequence, dimensions]
+                if *batch as usize == batch_size && *sequence as usize == sequence_len =>
+            {
+                *dimensions as usize
+            }
+            _ => return Err(anyhow!("unexpected model output shape {output_shape:?}")),
+        };
+
+        // Average the embeddings of the tokens in each sequence, ignoring padding.
+        let mut embeddings = Vec::with_capacity(batch_size);
+        for row in 0..batch_size {
+            let mut embedding = vec![0f32; dimensions];
+            let mut token_count = 0.;
+            for column in 0..sequence_len {
+                if attention_mask[row * sequence_len + column] == 0 {
+                    continue;
+                }
+                let offset = (row * sequence_len + column) * dimensions;
+                for (value, hidden_state) in embedding
+                    .iter_mut()
+                    .zip(&hidden_states[offset..offset + dimensions])
+                {
+                    *value += hidden_state;
+                }
+                token_count += 1.;
+            }
+            if token_count > 0. {
+                embedding.iter_mut().for_each(|value| *value /= token_count);
+            }
+            embeddings.push(Embedding::new(embedding));
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let texts = texts
+            .iter()
+            .map(|to_embed| to_embed.text.to_string())
+            .collect();
+        let model = self.model.clone();
+        self.executor
+            .spawn(async move { model.embed(texts) })
+            .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        32
+    }
+}
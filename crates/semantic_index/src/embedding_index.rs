@@ -2,6 +2,7 @@ use crate::{
     chunking::{self, Chunk},
     embedding::{Embedding, EmbeddingProvider, TextToEmbed},
     indexing::{IndexingEntryHandle, IndexingEntrySet},
+    keyword_search::ChunkTerms,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::Bound;
@@ -338,9 +339,12 @@ impl EmbeddingIndex {
                         chunked_file.chunks.into_iter().zip(embeddings.by_ref())
                     {
                         if let Some(embedding) = embedding {
-                            embedded_file
-                                .chunks
-                                .push(EmbeddedChunk { chunk, embedding });
+                            let terms = ChunkTerms::new(&chunked_file.text[chunk.range.clone()]);
+                            embedded_file.chunks.push(EmbeddedChunk {
+                                chunk,
+                                embedding,
+                                terms,
+                            });
                         } else {
                             embedded_all_chunks = false;
                         }
@@ -473,6 +477,8 @@ pub struct EmbeddedFile {
 pub struct EmbeddedChunk {
     pub chunk: Chunk,
     pub embedding: Embedding,
+    /// The terms of the chunk, for keyword search.
+    pub terms: ChunkTerms,
 }
 
 fn db_key_for_path(path: &Arc<Path>) -> String {
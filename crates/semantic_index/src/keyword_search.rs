@@ -0,0 +1,194 @@
+use collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Controls how quickly the score of a term saturates as it repeats in a chunk.
+const K1: f32 = 1.2;
+/// Controls how much longer chunks are penalized.
+const B: f32 = 0.75;
+/// Dampens the weight of the top ranks when fusing rankings.
+const RRF_K: f32 = 60.;
+
+/// The terms of a chunk, along with the number of times each one occurs.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkTerms {
+    /// Sorted by term.
+    pub counts: Vec<(String, u32)>,
+    pub len: u32,
+}
+
+impl ChunkTerms {
+    pub fn new(text: &str) -> Self {
+        let mut counts = HashMap::<String, u32>::default();
+        let mut len = 0;
+        for term in tokenize(text) {
+            *counts.entry(term).or_default() += 1;
+            len += 1;
+        }
+
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_unstable();
+        Self { counts, len }
+    }
+
+    fn count(&self, term: &str) -> u32 {
+        self.counts
+            .binary_search_by(|(probe, _)| probe.as_str().cmp(term))
+            .map_or(0, |ix| self.counts[ix].1)
+    }
+}
+
+/// Splits text into lowercase terms. Identifiers are split at underscores and
+/// case changes, and the parts of a `camelCase` identifier are indexed in
+/// addition to the whole identifier.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+
+        let mut parts = Vec::new();
+        let mut part_start = 0;
+        let mut prev: Option<char> = None;
+        for (ix, c) in word.char_indices() {
+            if let Some(prev) = prev {
+                if prev.is_lowercase() && c.is_uppercase() {
+                    parts.push(&word[part_start..ix]);
+                    part_start = ix;
+                }
+            }
+            prev = Some(c);
+        }
+        parts.push(&word[part_start..]);
+
+        if parts.len() > 1 {
+            terms.push(word.to_lowercase());
+        }
+        terms.extend(
+            parts
+                .into_iter()
+                .filter(|part| part.chars().count() > 1)
+                .map(|part| part.to_lowercase()),
+        );
+    }
+    terms
+}
+
+/// Scores chunks against a query with the Okapi BM25 ranking function.
+pub struct Bm25 {
+    /// The inverse document frequency of each term in the query.
+    query_terms: Vec<(String, f32)>,
+    average_len: f32,
+}
+
+impl Bm25 {
+    pub fn new<'a>(query: &str, chunks: impl IntoIterator<Item = &'a ChunkTerms>) -> Self {
+        let mut query_terms = tokenize(query);
+        query_terms.sort_unstable();
+        query_terms.dedup();
+
+        let mut chunk_count = 0;
+        let mut total_len = 0;
+        let mut frequencies = vec![0; query_terms.len()];
+        for chunk in chunks {
+            chunk_count += 1;
+            total_len += chunk.len as usize;
+            for (term, frequency) in query_terms.iter().zip(&mut frequencies) {
+                if chunk.count(term) > 0 {
+                    *frequency += 1;
+                }
+            }
+        }
+
+        let query_terms = query_terms
+            .into_iter()
+            .zip(frequencies)
+            .map(|(term, frequency)| {
+                let frequency = frequency as f32;
+                let idf = ((chunk_count as f32 - frequency + 0.5) / (frequency + 0.5)).ln_1p();
+                (term, idf)
+            })
+            .collect();
+
+        Self {
+            query_terms,
+            average_len: total_len as f32 / chunk_count.max(1) as f32,
+        }
+    }
+
+    pub fn score(&self, chunk: &ChunkTerms) -> f32 {
+        let len_ratio = chunk.len as f32 / self.average_len.max(1.);
+        self.query_terms
+            .iter()
+            .map(|(term, idf)| {
+                let count = chunk.count(term) as f32;
+                idf * count * (K1 + 1.) / (count + K1 * (1. - B + B * len_ratio))
+            })
+            .sum()
+    }
+}
+
+/// Adds the reciprocal rank of each item in the given scores to `fused`, so that
+/// several rankings can be combined without normalizing their scores. Items
+/// without a score are left out of the ranking.
+pub fn fuse_ranking(scores: &[Option<f32>], fused: &mut [f32]) {
+    let mut ranking = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, score)| Some((ix, (*score)?)))
+        .collect::<Vec<_>>();
+    ranking.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    for (rank, (ix, _)) in ranking.into_iter().enumerate() {
+        fused[ix] += 1. / (RRF_K + rank as f32 + 1.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn parseHttpRequest(raw_bytes: &[u8]) -> a"),
+            [
+                "fn",
+                "parsehttprequest",
+                "parse",
+                "http",
+                "request",
+                "raw",
+                "bytes",
+                "u8"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bm25() {
+        let chunks = [
+            ChunkTerms::new("fn parse_request(request: &str) -> Request { todo!() }"),
+            ChunkTerms::new("fn render(frame: &Frame) { draw(frame) }"),
+            ChunkTerms::new("fn send_request(client: &Client) { client.send() }"),
+        ];
+
+        let bm25 = Bm25::new("parse the request", &chunks);
+        let scores = chunks
+            .iter()
+            .map(|chunk| bm25.score(chunk))
+            .collect::<Vec<_>>();
+        assert!(scores[0] > scores[2]);
+        assert!(scores[2] > 0.);
+        assert_eq!(scores[1], 0.);
+
+        let keyword_scores = scores
+            .iter()
+            .map(|score| (*score > 0.).then_some(*score))
+            .collect::<Vec<_>>();
+        let mut fused = vec![0.; chunks.len()];
+        fuse_ranking(&keyword_scores, &mut fused);
+        fuse_ranking(&[Some(0.1), Some(0.5), Some(0.9)], &mut fused);
+        assert!(fused[2] > fused[0]);
+        assert!(fused[0] > fused[1]);
+    }
+}
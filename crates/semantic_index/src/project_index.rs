@@ -1,5 +1,7 @@
 use crate::{
-    embedding::{EmbeddingProvider, TextToEmbed},
+    embedding::{Embedding, EmbeddingProvider, TextToEmbed},
+    embedding_index::EmbeddedChunk,
+    keyword_search::{fuse_ranking, Bm25, ChunkTerms},
     summary_index::FileSummary,
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
@@ -21,6 +23,7 @@ use std::{
     num::NonZeroUsize,
     ops::{Range, RangeInclusive},
     path::{Path, PathBuf},
+    slice,
     sync::Arc,
 };
 use util::ResultExt;
@@ -43,6 +46,15 @@ pub struct LoadedSearchResult {
     pub query_index: usize,
 }
 
+/// A chunk of the index, as exported for inspection.
+#[derive(Debug, Serialize)]
+pub struct ExportedChunk {
+    pub path: PathBuf,
+    pub range: Range<usize>,
+    pub embedding: Embedding,
+    pub terms: ChunkTerms,
+}
+
 pub struct WorktreeSearchResult {
     pub worktree_id: WorktreeId,
     pub path: Arc<Path>,
@@ -228,12 +240,14 @@ impl ProjectIndex {
         }
     }
 
-    pub fn search(
+    /// Streams every embedded chunk of the project's worktrees.
+    fn scan_chunks(
         &self,
-        queries: Vec<String>,
-        limit: usize,
         cx: &AppContext,
-    ) -> Task<Result<Vec<SearchResult>>> {
+    ) -> (
+        channel::Receiver<(WorktreeId, Arc<Path>, EmbeddedChunk)>,
+        Vec<Task<Result<()>>>,
+    ) {
         let (chunks_tx, chunks_rx) = channel::bounded(1024);
         let mut worktree_scan_tasks = Vec::new();
         for worktree_index in self.worktree_indices.values() {
@@ -272,6 +286,16 @@ impl ProjectIndex {
             }));
         }
         drop(chunks_tx);
+        (chunks_rx, worktree_scan_tasks)
+    }
+
+    pub fn search(
+        &self,
+        queries: Vec<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let (chunks_rx, worktree_scan_tasks) = self.scan_chunks(cx);
 
         let project = self.project.clone();
         let embedding_provider = self.embedding_provider.clone();
@@ -371,6 +395,149 @@ impl ProjectIndex {
         })
     }
 
+    /// Searches the project by fusing the ranking of each chunk's embedding
+    /// similarity with the ranking of its BM25 keyword score. This finds chunks
+    /// containing the exact identifiers in a query even when their embeddings
+    /// aren't the closest.
+    pub fn hybrid_search(
+        &self,
+        queries: Vec<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let (chunks_rx, worktree_scan_tasks) = self.scan_chunks(cx);
+        let project = self.project.clone();
+        let embedding_provider = self.embedding_provider.clone();
+        cx.spawn(|cx| async move {
+            log::info!("Searching for {queries:?} with keywords");
+            let texts = queries
+                .iter()
+                .map(|query| TextToEmbed::new(query.as_str()))
+                .collect::<Vec<_>>();
+            let query_embeddings = embedding_provider.embed(&texts).await?;
+            if query_embeddings.len() != queries.len() {
+                return Err(anyhow!(
+                    "The number of query embeddings does not match the number of queries"
+                ));
+            }
+
+            let mut chunks = Vec::new();
+            while let Ok(chunk) = chunks_rx.recv().await {
+                chunks.push(chunk);
+            }
+            for scan_task in futures::future::join_all(worktree_scan_tasks).await {
+                scan_task.log_err();
+            }
+
+            let results = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut scores = vec![0.; chunks.len()];
+                    let mut query_indices = vec![0; chunks.len()];
+                    for (query_index, (query, query_embedding)) in
+                        queries.iter().zip(&query_embeddings).enumerate()
+                    {
+                        let mut fused_scores = vec![0.; chunks.len()];
+                        let similarities = chunks
+                            .iter()
+                            .map(|(_, _, chunk)| {
+                                Some(
+                                    chunk
+                                        .embedding
+                                        .similarity(slice::from_ref(query_embedding))
+                                        .0,
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        fuse_ranking(&similarities, &mut fused_scores);
+
+                        let bm25 =
+                            Bm25::new(query, chunks.iter().map(|(_, _, chunk)| &chunk.terms));
+                        let keyword_scores = chunks
+                            .iter()
+                            .map(|(_, _, chunk)| {
+                                let score = bm25.score(&chunk.terms);
+                                (score > 0.).then_some(score)
+                            })
+                            .collect::<Vec<_>>();
+                        fuse_ranking(&keyword_scores, &mut fused_scores);
+
+                        for (ix, score) in fused_scores.into_iter().enumerate() {
+                            if score > scores[ix] {
+                                scores[ix] = score;
+                                query_indices[ix] = query_index;
+                            }
+                        }
+                    }
+
+                    let mut ranking = (0..chunks.len()).collect::<Vec<_>>();
+                    ranking.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+                    ranking.truncate(limit);
+                    ranking
+                        .into_iter()
+                        .map(|ix| {
+                            let (worktree_id, path, chunk) = &chunks[ix];
+                            WorktreeSearchResult {
+                                worktree_id: *worktree_id,
+                                path: path.clone(),
+                                range: chunk.chunk.range.clone(),
+                                query_index: query_indices[ix],
+                                score: scores[ix],
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+
+            project.read_with(&cx, |project, cx| {
+                results
+                    .into_iter()
+                    .filter_map(|result| {
+                        Some(SearchResult {
+                            worktree: project.worktree_for_id(result.worktree_id, cx)?,
+                            path: result.path,
+                            range: result.range,
+                            score: result.score,
+                            query_index: result.query_index,
+                        })
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    /// Returns every chunk in the index, along with its embedding and terms.
+    pub fn export(&self, cx: &AppContext) -> Task<Result<Vec<ExportedChunk>>> {
+        let (chunks_rx, worktree_scan_tasks) = self.scan_chunks(cx);
+        let project = self.project.clone();
+        cx.spawn(|cx| async move {
+            let mut chunks = Vec::new();
+            while let Ok(chunk) = chunks_rx.recv().await {
+                chunks.push(chunk);
+            }
+            for scan_task in futures::future::join_all(worktree_scan_tasks).await {
+                scan_task?;
+            }
+
+            project.read_with(&cx, |project, cx| {
+                chunks
+                    .into_iter()
+                    .filter_map(|(worktree_id, path, chunk)| {
+                        let worktree = project.worktree_for_id(worktree_id, cx)?;
+                        let mut full_path = PathBuf::from(worktree.read(cx).root_name());
+                        full_path.push(&path);
+                        Some(ExportedChunk {
+                            path: full_path,
+                            range: chunk.chunk.range,
+                            embedding: chunk.embedding,
+                            terms: chunk.terms,
+                        })
+                    })
+                    .collect()
+            })
+        })
+    }
+
     #[cfg(test)]
     pub fn path_count(&self, cx: &AppContext) -> Result<u64> {
         let mut result = 0;
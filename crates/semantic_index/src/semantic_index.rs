@@ -2,8 +2,10 @@ mod chunking;
 mod embedding;
 mod embedding_index;
 mod indexing;
+mod keyword_search;
 mod project_index;
 mod project_index_debug_view;
+mod semantic_index_settings;
 mod summary_backlog;
 mod summary_index;
 mod worktree_index;
@@ -24,8 +26,10 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
-pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
+pub use keyword_search::ChunkTerms;
+pub use project_index::{ExportedChunk, LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use semantic_index_settings::SemanticIndexSettings;
 pub use summary_index::FileSummary;
 
 pub struct SemanticDb {
@@ -438,6 +442,17 @@ mod tests {
         let content = content[range.clone()].to_owned();
 
         assert!(content.contains("garbage in, garbage out"));
+
+        // Every chunk has the same embedding for this query, so the keywords decide.
+        let results = cx
+            .update(|cx| {
+                let project_index = project_index.read(cx);
+                project_index.hybrid_search(vec!["needle in a haystack".into()], 1, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path.to_string_lossy(), "fixture/needle.md");
     }
 
     #[gpui::test]
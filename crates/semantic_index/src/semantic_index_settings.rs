@@ -0,0 +1,31 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::path::PathBuf;
+
+/// Settings for indexing the project for semantic search.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct SemanticIndexSettings {
+    /// The directory of a local embedding model to run on the CPU instead of
+    /// Zed's hosted model. It must contain the model exported to ONNX
+    /// (`model.onnx`) and its tokenizer (`tokenizer.json`).
+    #[serde(default)]
+    pub local_embedding_model: Option<PathBuf>,
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
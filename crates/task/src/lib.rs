@@ -1,6 +1,7 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +14,11 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use problem_matcher::{
+    Problem, ProblemCollector, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern,
+    ProblemPatterns, ProblemSeverity,
+};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -51,6 +56,23 @@ pub struct SpawnInTerminal {
     pub hide: HideStrategy,
     /// Which shell to use when spawning the task.
     pub shell: Shell,
+    /// Problem matchers to turn the task's output into diagnostics with.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Tasks to run before this one, with the tasks they depend on in turn.
+    pub depends_on: Vec<SpawnInTerminal>,
+    /// In which order to run the tasks in `depends_on`.
+    pub depends_order: DependsOrder,
+    /// Whether the task keeps running in the background, being ready for its dependents before it finishes.
+    pub is_background: bool,
+    /// A regex that marks a background task as ready, once a line of its output matches it.
+    pub ready_pattern: Option<String>,
+}
+
+impl SpawnInTerminal {
+    /// Whether the task has a command to spawn, rather than only running the tasks it depends on.
+    pub fn has_command(&self) -> bool {
+        !self.command.trim().is_empty()
+    }
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
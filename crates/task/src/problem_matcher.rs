@@ -0,0 +1,630 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+/// A way to find problems (errors, warnings, etc.) in the output of a task, similar to VS Code's problem matchers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the problem matchers provided by Zed: `rustc` (also `cargo`), `tsc`, `gcc` or `eslint`
+    /// (also `eslint-compact`).
+    /// A leading `$` is accepted, as in VS Code.
+    Builtin(String),
+    /// A problem matcher defined by the user.
+    Custom(ProblemMatcherDefinition),
+}
+
+impl ProblemMatcher {
+    /// Returns the definition of the problem matcher, `None` for an unknown built-in one.
+    pub fn definition(&self) -> Option<ProblemMatcherDefinition> {
+        match self {
+            Self::Builtin(name) => builtin_problem_matcher(name.trim_start_matches('$')),
+            Self::Custom(definition) => Some(definition.clone()),
+        }
+    }
+}
+
+/// A problem matcher, describing how problems are printed by a tool.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcherDefinition {
+    /// The name of the tool reporting the problems, shown as the source of the diagnostics.
+    pub owner: String,
+    /// The severity of problems for which the pattern captures none.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// The pattern for a problem printed on a single line, or the patterns of the consecutive lines of a problem.
+    pub pattern: ProblemPatterns,
+}
+
+/// The patterns of the lines a problem is printed on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    /// A problem printed on a single line.
+    Single(ProblemPattern),
+    /// A problem printed on several consecutive lines, each matched by the pattern at the same position.
+    Multiple(Vec<ProblemPattern>),
+}
+
+impl ProblemPatterns {
+    /// The patterns, in the order of the lines they match.
+    pub fn as_slice(&self) -> &[ProblemPattern] {
+        match self {
+            Self::Single(pattern) => std::slice::from_ref(pattern),
+            Self::Multiple(patterns) => patterns,
+        }
+    }
+}
+
+/// A regular expression matching a line of a problem, with the indices of the groups capturing its parts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// The group capturing the path of the file, absolute or relative to the task's working directory.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The group capturing the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The group capturing the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The group capturing the 1-based line the problem ends on.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// The group capturing the 1-based column the problem ends on.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// The group capturing the severity, such as `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The group capturing the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The group capturing the message of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of a multi-line problem matcher may match several lines in a row,
+    /// reporting a problem for each of them.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message, such as a note or a hint.
+    Info,
+}
+
+impl ProblemSeverity {
+    pub(crate) fn parse(severity: &str) -> Option<Self> {
+        let severity = severity.trim().to_lowercase();
+        if severity.starts_with("err") || severity.starts_with("fatal") {
+            Some(Self::Error)
+        } else if severity.starts_with("warn") {
+            Some(Self::Warning)
+        } else if severity.starts_with("info")
+            || severity.starts_with("note")
+            || severity.starts_with("hint")
+        {
+            Some(Self::Info)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Problem {
+    /// The path of the file, as printed by the tool.
+    pub path: PathBuf,
+    /// The 1-based line of the problem.
+    pub row: u32,
+    /// The 1-based column of the problem.
+    pub column: Option<u32>,
+    /// The 1-based line the problem ends on.
+    pub end_row: Option<u32>,
+    /// The 1-based column the problem ends on.
+    pub end_column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The message of the problem.
+    pub message: String,
+    /// The code of the problem, such as `E0425`.
+    pub code: Option<String>,
+    /// The owner of the problem matcher that found the problem.
+    pub source: String,
+    /// The index of the output line the problem's location was printed on,
+    /// counting the lines passed to the [`ProblemCollector`].
+    pub location_line: usize,
+}
+
+/// Finds problems in the output of a task, fed to it line by line.
+pub struct ProblemCollector {
+    matchers: Vec<MatcherState>,
+    lines_seen: usize,
+}
+
+struct MatcherState {
+    definition: ProblemMatcherDefinition,
+    regexes: Vec<Regex>,
+    /// The index of the pattern to match the next line with, if a problem is being matched.
+    next_pattern: Option<usize>,
+    /// Whether the last pattern matched the previous line and may match the next one too.
+    looping: bool,
+    partial: PartialProblem,
+}
+
+#[derive(Clone, Default)]
+struct PartialProblem {
+    path: Option<String>,
+    row: Option<u32>,
+    column: Option<u32>,
+    end_row: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    message: Option<String>,
+    code: Option<String>,
+    location_line: Option<usize>,
+}
+
+impl ProblemCollector {
+    /// Creates a collector for the given problem matchers, skipping the ones that are unknown or invalid.
+    pub fn new(matchers: &[ProblemMatcher]) -> Self {
+        let matchers = matchers
+            .iter()
+            .filter_map(|matcher| {
+                let definition = matcher.definition();
+                if definition.is_none() {
+                    log::error!("Unknown problem matcher {matcher:?}");
+                }
+                let definition = definition?;
+                let regexes = definition
+                    .pattern
+                    .as_slice()
+                    .iter()
+                    .map(|pattern| Regex::new(&pattern.regexp).log_err())
+                    .collect::<Option<Vec<_>>>()?;
+                if regexes.is_empty() {
+                    return None;
+                }
+                Some(MatcherState {
+                    definition,
+                    regexes,
+                    next_pattern: None,
+                    looping: false,
+                    partial: PartialProblem::default(),
+                })
+            })
+            .collect();
+        Self {
+            matchers,
+            lines_seen: 0,
+        }
+    }
+
+    /// Whether the collector has no problem matchers to look for problems with.
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+    }
+
+    /// How many lines of output were looked at so far.
+    pub fn lines_seen(&self) -> usize {
+        self.lines_seen
+    }
+
+    /// Looks for problems in the next line of the output, returning the problems it completes.
+    pub fn push_line(&mut self, line: &str) -> Vec<Problem> {
+        let line_ix = self.lines_seen;
+        self.lines_seen += 1;
+        self.matchers
+            .iter_mut()
+            .filter_map(|matcher| matcher.push_line(line, line_ix))
+            .collect()
+    }
+}
+
+impl MatcherState {
+    fn push_line(&mut self, line: &str, line_ix: usize) -> Option<Problem> {
+        let last_pattern = self.regexes.len() - 1;
+        if self.looping {
+            if let Some(problem) = self.match_pattern(last_pattern, line, line_ix) {
+                return problem;
+            }
+            self.reset();
+        } else if let Some(next_pattern) = self.next_pattern {
+            if let Some(problem) = self.match_pattern(next_pattern, line, line_ix) {
+                return problem;
+            }
+            self.reset();
+        }
+        self.match_pattern(0, line, line_ix).flatten()
+    }
+
+    /// Matches the line with the pattern at the given index, returning `None` if it does not match,
+    /// and the problem completed by the line if it does.
+    fn match_pattern(
+        &mut self,
+        pattern_ix: usize,
+        line: &str,
+        line_ix: usize,
+    ) -> Option<Option<Problem>> {
+        let captures = self.regexes[pattern_ix].captures(line)?;
+        let pattern = &self.definition.pattern.as_slice()[pattern_ix];
+        let group = |ix: Option<usize>| {
+            ix.and_then(|ix| captures.get(ix))
+                .map(|capture| capture.as_str().trim().to_string())
+                .filter(|capture| !capture.is_empty())
+        };
+        let number = |ix: Option<usize>| group(ix).and_then(|number| number.parse::<u32>().ok());
+
+        if pattern_ix == 0 {
+            self.partial = PartialProblem::default();
+        }
+        // A looping pattern describes a new problem on each line, sharing what the previous patterns captured.
+        let mut partial = self.partial.clone();
+        if let Some(path) = group(pattern.file) {
+            partial.path = Some(path);
+            partial.location_line = Some(line_ix);
+        }
+        if let Some(row) = number(pattern.line) {
+            partial.row = Some(row);
+            partial.location_line = Some(line_ix);
+        }
+        partial.column = number(pattern.column).or(partial.column);
+        partial.end_row = number(pattern.end_line).or(partial.end_row);
+        partial.end_column = number(pattern.end_column).or(partial.end_column);
+        partial.severity = group(pattern.severity)
+            .and_then(|severity| ProblemSeverity::parse(&severity))
+            .or(partial.severity);
+        partial.code = group(pattern.code).or(partial.code);
+        partial.message = group(pattern.message).or(partial.message);
+
+        let last_pattern = self.regexes.len() - 1;
+        if pattern_ix < last_pattern {
+            self.partial = partial;
+            self.next_pattern = Some(pattern_ix + 1);
+            return Some(None);
+        }
+
+        if pattern.repeat && last_pattern > 0 {
+            self.looping = true;
+            self.next_pattern = None;
+        } else {
+            self.reset();
+        }
+        Some(self.to_problem(partial))
+    }
+
+    fn to_problem(&self, partial: PartialProblem) -> Option<Problem> {
+        Some(Problem {
+            path: PathBuf::from(partial.path?),
+            row: partial.row?,
+            column: partial.column,
+            end_row: partial.end_row,
+            end_column: partial.end_column,
+            severity: partial.severity.unwrap_or(self.definition.severity),
+            message: partial.message.unwrap_or_default(),
+            code: partial.code,
+            source: self.definition.owner.clone(),
+            location_line: partial.location_line?,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.next_pattern = None;
+        self.looping = false;
+        self.partial = PartialProblem::default();
+    }
+}
+
+fn builtin_problem_matcher(name: &str) -> Option<ProblemMatcherDefinition> {
+    let definition = match name {
+        "rustc" | "cargo" => ProblemMatcherDefinition {
+            owner: "rustc".to_string(),
+            severity: ProblemSeverity::Error,
+            pattern: ProblemPatterns::Multiple(vec![
+                ProblemPattern {
+                    regexp: r"^(warning|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s*-->\s+(.*?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..ProblemPattern::default()
+                },
+            ]),
+        },
+        "tsc" | "tsc-watch" => ProblemMatcherDefinition {
+            owner: "tsc".to_string(),
+            severity: ProblemSeverity::Error,
+            pattern: ProblemPatterns::Single(ProblemPattern {
+                regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+TS(\d+)\s*:\s*(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..ProblemPattern::default()
+            }),
+        },
+        "gcc" => ProblemMatcherDefinition {
+            owner: "gcc".to_string(),
+            severity: ProblemSeverity::Error,
+            pattern: ProblemPatterns::Single(ProblemPattern {
+                regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..ProblemPattern::default()
+            }),
+        },
+        "eslint" | "eslint-stylish" => ProblemMatcherDefinition {
+            owner: "eslint".to_string(),
+            severity: ProblemSeverity::Warning,
+            pattern: ProblemPatterns::Multiple(vec![
+                ProblemPattern {
+                    regexp: r"^([^\s].*)$".to_string(),
+                    file: Some(1),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                        .to_string(),
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    repeat: true,
+                    ..ProblemPattern::default()
+                },
+            ]),
+        },
+        "eslint-compact" => ProblemMatcherDefinition {
+            owner: "eslint".to_string(),
+            severity: ProblemSeverity::Warning,
+            pattern: ProblemPatterns::Single(ProblemPattern {
+                regexp: r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+                ..ProblemPattern::default()
+            }),
+        },
+        _ => return None,
+    };
+    Some(definition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(matcher: &str, output: &str) -> Vec<Problem> {
+        let mut collector = ProblemCollector::new(&[ProblemMatcher::Builtin(matcher.to_string())]);
+        output
+            .lines()
+            .flat_map(|line| collector.push_line(line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let problems = collect(
+            "$rustc",
+            r#"   Compiling foo v0.1.0 (/home/user/foo)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:20
+  |
+2 |     println!("{}", x);
+  |                    ^ not found in this scope
+
+warning: unused variable: `y`
+  --> src/lib.rs:10:9
+   |
+warning: `foo` (bin "foo") generated 1 warning
+error: could not compile `foo` (bin "foo") due to 1 previous error"#,
+        );
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: PathBuf::from("src/main.rs"),
+                    row: 2,
+                    column: Some(20),
+                    end_row: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    message: "cannot find value `x` in this scope".to_string(),
+                    code: Some("E0425".to_string()),
+                    source: "rustc".to_string(),
+                    location_line: 2,
+                },
+                Problem {
+                    path: PathBuf::from("src/lib.rs"),
+                    row: 10,
+                    column: Some(9),
+                    end_row: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    message: "unused variable: `y`".to_string(),
+                    code: None,
+                    source: "rustc".to_string(),
+                    location_line: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_problem_matchers() {
+        let problems = collect(
+            "tsc",
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+             src/util.ts:12:1 - warning TS6133: 'foo' is declared but its value is never read.",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().to_string(),
+                    problem.row,
+                    problem.column,
+                    problem.severity,
+                    problem.code.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "src/index.ts".to_string(),
+                    3,
+                    Some(7),
+                    ProblemSeverity::Error,
+                    Some("2322".to_string())
+                ),
+                (
+                    "src/util.ts".to_string(),
+                    12,
+                    Some(1),
+                    ProblemSeverity::Warning,
+                    Some("6133".to_string())
+                ),
+            ]
+        );
+
+        let problems = collect(
+            "gcc",
+            "main.c:4:5: error: implicit declaration of function 'foo'\n\
+             main.c: In function 'main':\n\
+             main.c:7:1: warning: control reaches end of non-void function",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (problem.row, problem.severity, problem.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    4,
+                    ProblemSeverity::Error,
+                    "implicit declaration of function 'foo'"
+                ),
+                (
+                    7,
+                    ProblemSeverity::Warning,
+                    "control reaches end of non-void function"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_looping_problem_matcher() {
+        let problems = collect(
+            "eslint",
+            "/home/user/app/src/a.js\n  \
+               1:10  error    'foo' is defined but never used  no-unused-vars\n  \
+               3:1   warning  Unexpected console statement     no-console\n\
+             \n\
+             /home/user/app/src/b.js\n  \
+               7:3  error  Missing semicolon  semi\n\
+             \n\
+             ✖ 3 problems (2 errors, 1 warning)",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().to_string(),
+                    problem.row,
+                    problem.severity,
+                    problem.code.clone(),
+                    problem.location_line
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/home/user/app/src/a.js".to_string(),
+                    1,
+                    ProblemSeverity::Error,
+                    Some("no-unused-vars".to_string()),
+                    1
+                ),
+                (
+                    "/home/user/app/src/a.js".to_string(),
+                    3,
+                    ProblemSeverity::Warning,
+                    Some("no-console".to_string()),
+                    2
+                ),
+                (
+                    "/home/user/app/src/b.js".to_string(),
+                    7,
+                    ProblemSeverity::Error,
+                    Some("semi".to_string()),
+                    5
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher_deserialization() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "owner": "mytool",
+                "severity": "warning",
+                "pattern": { "regexp": "^(.*)@(\\d+): (.*)$", "file": 1, "line": 2, "message": 3 }
+            }"#,
+        )
+        .unwrap();
+        let mut collector = ProblemCollector::new(&[matcher]);
+        assert_eq!(
+            collector.push_line("lib/a.py@12: something odd"),
+            vec![Problem {
+                path: PathBuf::from("lib/a.py"),
+                row: 12,
+                column: None,
+                end_row: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                message: "something odd".to_string(),
+                code: None,
+                source: "mytool".to_string(),
+                location_line: 0,
+            }]
+        );
+
+        let collector = ProblemCollector::new(&[ProblemMatcher::Builtin("$unknown".to_string())]);
+        assert!(collector.is_empty());
+    }
+}
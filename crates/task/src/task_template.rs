@@ -8,7 +8,7 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
+    ProblemMatcher, ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
     ZED_VARIABLE_NAME_PREFIX,
 };
 
@@ -57,6 +57,38 @@ pub struct TaskTemplate {
     /// Which shell to use when spawning the task.
     #[serde(default)]
     pub shell: Shell,
+    /// Problem matchers to find errors and warnings in the task's output with, shown as project diagnostics.
+    /// Either the name of a built-in one (`rustc`, `tsc`, `gcc`, `eslint`) or a custom definition.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Labels of the tasks to run before this one, resolved with the same context.
+    /// A task with dependencies may have no command, to only run its dependencies.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// In which order to run the tasks this task depends on:
+    /// * `parallel` — run all of them at once (default)
+    /// * `sequence` — run them one after another, in the order they are listed
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Whether the task keeps running in the background, like a watcher or a server.
+    /// Tasks depending on a background task start once it is ready rather than once it finishes.
+    #[serde(default)]
+    pub is_background: bool,
+    /// A regex that marks a background task as ready, once a line of its output matches it.
+    /// Without it, a background task is ready as soon as it is started.
+    #[serde(default)]
+    pub ready_pattern: Option<String>,
+}
+
+/// In which order to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Run all dependencies at once.
+    #[default]
+    Parallel,
+    /// Run the dependencies one after another, in the order they are listed.
+    Sequence,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -108,8 +140,18 @@ impl TaskTemplate {
     ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
+    ///
+    /// Tasks without a command are not resolved: those that only run their dependencies have to be resolved
+    /// with [`Self::resolve_task_with_dependencies`].
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.command.trim().is_empty() {
+            return None;
+        }
+        self.resolve(id_base, cx)
+    }
+
+    fn resolve(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
+        if self.label.trim().is_empty() {
             return None;
         }
 
@@ -230,9 +272,72 @@ impl TaskTemplate {
                 reveal: self.reveal,
                 hide: self.hide,
                 shell: self.shell.clone(),
+                problem_matchers: self.problem_matchers.clone(),
+                depends_on: Vec::new(),
+                depends_order: self.depends_order,
+                is_background: self.is_background,
+                ready_pattern: self.ready_pattern.clone(),
             }),
         })
     }
+
+    /// Resolves the task and, recursively, the tasks it depends on with the same [`TaskContext`].
+    /// Dependencies are looked up by their labels in the `templates` given, together with the id base of their source.
+    ///
+    /// Fails if any of the dependencies cannot be found or resolved, or if they depend on each other in a cycle.
+    pub fn resolve_task_with_dependencies(
+        &self,
+        id_base: &str,
+        cx: &TaskContext,
+        templates: &[(String, TaskTemplate)],
+    ) -> anyhow::Result<ResolvedTask> {
+        self.resolve_with_dependencies(id_base, cx, templates, &mut Vec::new())
+    }
+
+    fn resolve_with_dependencies(
+        &self,
+        id_base: &str,
+        cx: &TaskContext,
+        templates: &[(String, TaskTemplate)],
+        dependents: &mut Vec<String>,
+    ) -> anyhow::Result<ResolvedTask> {
+        if self.depends_on.is_empty() {
+            return self
+                .resolve_task(id_base, cx)
+                .with_context(|| format!("resolving task `{}`", self.label));
+        }
+        // Tasks that depend on others may have no command, and only run their dependencies.
+        let mut resolved_task = self
+            .resolve(id_base, cx)
+            .with_context(|| format!("resolving task `{}`", self.label))?;
+
+        dependents.push(self.label.clone());
+        let mut dependencies = Vec::with_capacity(self.depends_on.len());
+        for label in &self.depends_on {
+            if dependents.contains(label) {
+                bail!("task `{label}` depends on itself, through `{}`", self.label);
+            }
+            let (dependency_id_base, dependency) = templates
+                .iter()
+                .find(|(_, template)| &template.label == label)
+                .with_context(|| {
+                    format!("task `{label}`, required by `{}`, not found", self.label)
+                })?;
+            let resolved_dependency = dependency.resolve_with_dependencies(
+                dependency_id_base,
+                cx,
+                templates,
+                dependents,
+            )?;
+            dependencies.extend(resolved_dependency.resolved);
+        }
+        dependents.pop();
+
+        if let Some(resolved) = resolved_task.resolved.as_mut() {
+            resolved.depends_on = dependencies;
+        }
+        Ok(resolved_task)
+    }
 }
 
 const MAX_DISPLAY_VARIABLE_LENGTH: usize = 15;
@@ -806,4 +911,100 @@ mod tests {
             "overwritten"
         );
     }
+
+    #[test]
+    fn test_resolving_task_dependencies() {
+        let build = TaskTemplate {
+            label: "build".to_string(),
+            command: "cargo build".to_string(),
+            ..TaskTemplate::default()
+        };
+        let migrate = TaskTemplate {
+            label: "migrate".to_string(),
+            command: "cargo run --bin migrate".to_string(),
+            depends_on: vec!["build".to_string()],
+            ..TaskTemplate::default()
+        };
+        let server = TaskTemplate {
+            label: "server".to_string(),
+            command: "cargo run --bin server".to_string(),
+            depends_on: vec!["build".to_string(), "migrate".to_string()],
+            depends_order: DependsOrder::Sequence,
+            is_background: true,
+            ready_pattern: Some("listening on".to_string()),
+            ..TaskTemplate::default()
+        };
+        let all = TaskTemplate {
+            label: "all".to_string(),
+            depends_on: vec!["server".to_string()],
+            ..TaskTemplate::default()
+        };
+        let templates = [&build, &migrate, &server, &all]
+            .into_iter()
+            .map(|template| (TEST_ID_BASE.to_string(), template.clone()))
+            .collect::<Vec<_>>();
+
+        let resolved = all
+            .resolve_task_with_dependencies(TEST_ID_BASE, &TaskContext::default(), &templates)
+            .unwrap()
+            .resolved
+            .unwrap();
+        assert!(!resolved.has_command());
+        assert_eq!(resolved.depends_on.len(), 1);
+        assert_eq!(
+            all.resolve_task(TEST_ID_BASE, &TaskContext::default()),
+            None,
+            "tasks without a command are only resolved along with their dependencies"
+        );
+        let resolved_server = &resolved.depends_on[0];
+        assert_eq!(resolved_server.label, "server");
+        assert_eq!(resolved_server.depends_order, DependsOrder::Sequence);
+        assert!(resolved_server.is_background);
+        assert_eq!(
+            resolved_server.ready_pattern.as_deref(),
+            Some("listening on")
+        );
+        assert_eq!(
+            resolved_server
+                .depends_on
+                .iter()
+                .map(|task| task.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["build", "migrate"]
+        );
+        assert_eq!(
+            resolved_server.depends_on[1]
+                .depends_on
+                .iter()
+                .map(|task| task.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["build"]
+        );
+
+        let missing_dependency = TaskTemplate {
+            label: "deploy".to_string(),
+            command: "./deploy.sh".to_string(),
+            depends_on: vec!["package".to_string()],
+            ..TaskTemplate::default()
+        };
+        assert!(missing_dependency
+            .resolve_task_with_dependencies(TEST_ID_BASE, &TaskContext::default(), &templates)
+            .is_err());
+
+        let cyclic_build = TaskTemplate {
+            depends_on: vec!["migrate".to_string()],
+            ..build.clone()
+        };
+        let cyclic_templates = [&cyclic_build, &migrate]
+            .into_iter()
+            .map(|template| (TEST_ID_BASE.to_string(), template.clone()))
+            .collect::<Vec<_>>();
+        assert!(migrate
+            .resolve_task_with_dependencies(
+                TEST_ID_BASE,
+                &TaskContext::default(),
+                &cyclic_templates
+            )
+            .is_err());
+    }
 }
@@ -3,7 +3,13 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    DependsOrder, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern, ProblemPatterns,
+    ProblemSeverity, TaskTemplate, TaskTemplates, VariableName,
+};
+
+/// The output that marks a watching `tsc` as done compiling, as VS Code's `$tsc-watch` problem matcher looks for it.
+const TSC_WATCH_READY_PATTERN: &str = r"Found \d+ errors?\. Watching for file changes\.";
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +47,141 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatchers {
+    Single(VsCodeProblemMatcher),
+    Multiple(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Definition(VsCodeProblemMatcherDefinition),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcherDefinition {
+    base: Option<String>,
+    owner: Option<String>,
+    severity: Option<String>,
+    pattern: Option<VsCodeProblemPatterns>,
+    background: Option<VsCodeBackgroundMatcher>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeBackgroundMatcher {
+    ends_pattern: Option<VsCodeBackgroundPattern>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeBackgroundPattern {
+    Regex(String),
+    Object { regexp: String },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeDependsOn {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    Named(String),
+    Single(VsCodeProblemPattern),
+    Multiple(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default, rename = "loop")]
+    repeat: bool,
+}
+
+impl VsCodeProblemMatcher {
+    /// The pattern that marks a background task using this problem matcher as ready.
+    fn ready_pattern(&self) -> Option<String> {
+        match self {
+            Self::Named(name) => {
+                (name == "$tsc-watch").then(|| TSC_WATCH_READY_PATTERN.to_string())
+            }
+            Self::Definition(definition) => {
+                match definition
+                    .background
+                    .as_ref()
+                    .and_then(|background| background.ends_pattern.as_ref())
+                {
+                    Some(VsCodeBackgroundPattern::Regex(regexp))
+                    | Some(VsCodeBackgroundPattern::Object { regexp }) => Some(regexp.clone()),
+                    None => Self::Named(definition.base.clone()?).ready_pattern(),
+                }
+            }
+        }
+    }
+
+    fn into_zed_format(self) -> Option<ProblemMatcher> {
+        let definition = match self {
+            Self::Named(name) => return Some(ProblemMatcher::Builtin(name)),
+            Self::Definition(definition) => definition,
+        };
+        let patterns = match definition.pattern {
+            // Patterns are looked up by name in VS Code, we only know the ones of our built-in problem matchers.
+            Some(VsCodeProblemPatterns::Named(name)) => {
+                return Some(ProblemMatcher::Builtin(name));
+            }
+            Some(VsCodeProblemPatterns::Single(pattern)) => vec![pattern],
+            Some(VsCodeProblemPatterns::Multiple(patterns)) => patterns,
+            None => return definition.base.map(ProblemMatcher::Builtin),
+        };
+        let mut patterns = patterns
+            .into_iter()
+            .map(|pattern| ProblemPattern {
+                regexp: pattern.regexp,
+                file: pattern.file,
+                line: pattern.line,
+                column: pattern.column,
+                end_line: pattern.end_line,
+                end_column: pattern.end_column,
+                severity: pattern.severity,
+                code: pattern.code,
+                message: pattern.message,
+                repeat: pattern.repeat,
+            })
+            .collect::<Vec<_>>();
+        let pattern = if patterns.len() == 1 {
+            ProblemPatterns::Single(patterns.remove(0))
+        } else {
+            ProblemPatterns::Multiple(patterns)
+        };
+        Some(ProblemMatcher::Custom(ProblemMatcherDefinition {
+            owner: definition.owner.unwrap_or_else(|| "task".to_string()),
+            severity: definition
+                .severity
+                .as_deref()
+                .and_then(ProblemSeverity::parse)
+                .unwrap_or_default(),
+            pattern,
+        }))
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -85,21 +226,23 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let depends_on = match self.other_attributes.get("dependsOn") {
+            Some(depends_on) => {
+                match serde_json_lenient::from_value::<VsCodeDependsOn>(depends_on.clone())? {
+                    VsCodeDependsOn::Single(label) => vec![label],
+                    VsCodeDependsOn::Multiple(labels) => labels,
+                }
+            }
+            None => Vec::new(),
         };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // `type` might not be set in tasks that only run the tasks they depend on;
+        // we still want to deserialize the whole object though (hence command is an Option).
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,12 +251,45 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
             ..Default::default()
         };
+        if let Some(depends_order) = self.other_attributes.get("dependsOrder") {
+            ret.depends_order = match depends_order.as_str() {
+                Some("sequence") => DependsOrder::Sequence,
+                Some("parallel") => DependsOrder::Parallel,
+                _ => bail!("Unknown `dependsOrder` value: {depends_order}"),
+            };
+        }
+        ret.is_background = self
+            .other_attributes
+            .get("isBackground")
+            .and_then(|is_background| is_background.as_bool())
+            .unwrap_or(false);
         if let Some(options) = self.options {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
         }
+        if let Some(problem_matchers) = self.other_attributes.get("problemMatcher") {
+            let problem_matchers = match serde_json_lenient::from_value::<VsCodeProblemMatchers>(
+                problem_matchers.clone(),
+            )
+            .log_err()
+            {
+                Some(VsCodeProblemMatchers::Single(problem_matcher)) => vec![problem_matcher],
+                Some(VsCodeProblemMatchers::Multiple(problem_matchers)) => problem_matchers,
+                None => Vec::new(),
+            };
+            if ret.is_background {
+                ret.ready_pattern = problem_matchers
+                    .iter()
+                    .find_map(VsCodeProblemMatcher::ready_pattern);
+            }
+            ret.problem_matchers = problem_matchers
+                .into_iter()
+                .filter_map(VsCodeProblemMatcher::into_zed_format)
+                .collect();
+        }
         Ok(ret)
     }
 }
@@ -154,8 +330,8 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        vscode_format::{Command, VsCodeTaskDefinition, TSC_WATCH_READY_PATTERN},
+        DependsOrder, ProblemMatcher, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -257,6 +433,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +445,23 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc-watch".to_string())],
+                is_background: true,
+                ready_pattern: Some(TSC_WATCH_READY_PATTERN.to_string()),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -360,32 +542,114 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc-watch".to_string())],
+                is_background: true,
+                ready_pattern: Some(TSC_WATCH_READY_PATTERN.to_string()),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$tsc".to_string())],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                problem_matchers: vec![ProblemMatcher::Builtin("$rustc".to_string())],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                problem_matchers: vec![ProblemMatcher::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn test_task_dependencies_and_background_tasks() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "version": "2.0.0",
+                "tasks": [
+                    {
+                        "label": "serve",
+                        "type": "shell",
+                        "command": "npm run serve",
+                        "isBackground": true,
+                        "problemMatcher": {
+                            "owner": "webpack",
+                            "pattern": { "regexp": "^ERROR in (.*)$", "file": 1 },
+                            "background": {
+                                "activeBegin": true,
+                                "beginsPattern": "Compiling",
+                                "endsPattern": { "regexp": "Compiled (successfully|with warnings)" }
+                            }
+                        }
+                    },
+                    {
+                        "label": "test",
+                        "type": "shell",
+                        "command": "npm test",
+                        "dependsOn": ["lint", "serve"],
+                        "dependsOrder": "sequence"
+                    },
+                    {
+                        "label": "ci",
+                        "dependsOn": "test"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0.len(), 3);
+
+        assert!(tasks.0[0].is_background);
+        assert_eq!(
+            tasks.0[0].ready_pattern.as_deref(),
+            Some("Compiled (successfully|with warnings)")
+        );
+        assert_eq!(
+            tasks.0[1].depends_on,
+            vec!["lint".to_string(), "serve".to_string()]
+        );
+        assert_eq!(tasks.0[1].depends_order, DependsOrder::Sequence);
+        assert!(!tasks.0[1].is_background);
+        assert_eq!(
+            tasks.0[2],
+            TaskTemplate {
+                label: "ci".to_string(),
+                depends_on: vec!["test".to_string()],
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -8,13 +8,13 @@ use gpui::{
     View, ViewContext, VisualContext, WeakView,
 };
 use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
-use project::{task_store::TaskStore, TaskSourceKind};
+use project::{task_store::TaskStore, TaskChain, TaskChainStepStatus, TaskSourceKind};
 use task::{ResolvedTask, TaskContext, TaskId, TaskTemplate};
 use ui::{
     div, h_flex, v_flex, ActiveTheme, Button, ButtonCommon, ButtonSize, Clickable, Color,
     FluentBuilder as _, Icon, IconButton, IconButtonShape, IconName, IconSize, IntoElement,
-    KeyBinding, LabelSize, ListItem, ListItemSpacing, RenderOnce, Selectable, Tooltip,
-    WindowContext,
+    KeyBinding, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing, RenderOnce, Selectable,
+    Tooltip, WindowContext,
 };
 use util::ResultExt;
 use workspace::{tasks::schedule_resolved_task, ModalView, Workspace};
@@ -59,7 +59,15 @@ pub struct Rerun {
     pub task_id: Option<TaskId>,
 }
 
-impl_actions!(task, [Rerun, Spawn]);
+/// Cancel a task chain: kill the tasks it runs and do not start the rest of them.
+/// Dismisses the chain, if it already failed.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct CancelChain {
+    /// If present, cancel the chain of the task with this ID, otherwise cancel all chains.
+    pub task_id: Option<TaskId>,
+}
+
+impl_actions!(task, [CancelChain, Rerun, Spawn]);
 
 /// A modal used to spawn new tasks.
 pub(crate) struct TasksModalDelegate {
@@ -134,7 +142,8 @@ impl TasksModalDelegate {
 
 pub(crate) struct TasksModal {
     picker: View<Picker<TasksModalDelegate>>,
-    _subscription: Subscription,
+    task_store: Model<TaskStore>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl TasksModal {
@@ -146,25 +155,96 @@ impl TasksModal {
     ) -> Self {
         let picker = cx.new_view(|cx| {
             Picker::uniform_list(
-                TasksModalDelegate::new(task_store, task_context, workspace),
+                TasksModalDelegate::new(task_store.clone(), task_context, workspace),
                 cx,
             )
         });
-        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+        let mut _subscriptions = vec![cx.subscribe(&picker, |_, _, _, cx| {
             cx.emit(DismissEvent);
-        });
+        })];
+        if let Some(inventory) = task_store.read(cx).task_inventory() {
+            _subscriptions.push(cx.observe(inventory, |_, _, cx| cx.notify()));
+        }
         Self {
             picker,
-            _subscription,
+            task_store,
+            _subscriptions,
         }
     }
+
+    fn render_task_chain(task_chain: &TaskChain, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let is_running = task_chain.steps.iter().any(|step| {
+            matches!(
+                step.status,
+                TaskChainStepStatus::Pending
+                    | TaskChainStepStatus::Running
+                    | TaskChainStepStatus::Ready
+            )
+        });
+        let task_id = task_chain.task_id.clone();
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_1()
+                    .flex_wrap()
+                    .child(Label::new(task_chain.label.clone()).size(LabelSize::Small))
+                    .children(task_chain.steps.iter().map(|step| {
+                        let (icon, color) = match step.status {
+                            TaskChainStepStatus::Pending => (IconName::Dash, Color::Muted),
+                            TaskChainStepStatus::Running => (IconName::ArrowCircle, Color::Accent),
+                            TaskChainStepStatus::Ready => (IconName::Play, Color::Accent),
+                            TaskChainStepStatus::Succeeded => (IconName::Check, Color::Success),
+                            TaskChainStepStatus::Failed => (IconName::XCircle, Color::Error),
+                            TaskChainStepStatus::Cancelled => (IconName::Stop, Color::Muted),
+                        };
+                        h_flex()
+                            .gap_0p5()
+                            .child(Icon::new(icon).size(IconSize::XSmall).color(color))
+                            .child(
+                                Label::new(step.label.clone())
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                    })),
+            )
+            .child(
+                Button::new(
+                    SharedString::from(format!("cancel-task-chain-{}", task_id.0)),
+                    if is_running { "Cancel" } else { "Dismiss" },
+                )
+                .label_size(LabelSize::Small)
+                .on_click(move |_, cx| {
+                    cx.dispatch_action(Box::new(CancelChain {
+                        task_id: Some(task_id.clone()),
+                    }));
+                }),
+            )
+    }
 }
 
 impl Render for TasksModal {
-    fn render(&mut self, _: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+        let task_chains = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .map(|inventory| inventory.read(cx).task_chains().to_vec())
+            .unwrap_or_default();
         v_flex()
             .key_context("TasksModal")
             .w(rems(34.))
+            .children(
+                task_chains
+                    .iter()
+                    .map(|task_chain| Self::render_task_chain(task_chain, cx)),
+            )
             .child(self.picker.clone())
     }
 }
@@ -142,4 +142,9 @@ impl PtyProcessInfo {
         }
         has_changed
     }
+
+    /// Kills the process currently running in the PTY, returns whether it was killed
+    pub fn kill_current_process(&mut self) -> bool {
+        self.refresh().map_or(false, |process| process.kill())
+    }
 }
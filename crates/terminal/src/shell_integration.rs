@@ -0,0 +1,845 @@
+//! Shell integration: Zed injects small scripts into bash, zsh and fish, which report
+//! prompt and command boundaries with OSC 133 ("semantic prompt") sequences and the
+//! working directory with OSC 7.
+//!
+//! Alacritty ignores both sequences, so the PTY output is scanned before it reaches
+//! the terminal parser. To attribute every marker to the right grid position, reads are
+//! cut right after each marker: the event loop parses the preceding bytes, gives up the
+//! terminal lock, and only then is the cursor position recorded.
+
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::{io, path::Path, sync::OnceLock};
+
+use alacritty_terminal::{
+    grid::{Dimensions, Row},
+    index::Line,
+    term::{cell::Cell, TermMode},
+    Term,
+};
+#[cfg(unix)]
+use collections::HashMap;
+#[cfg(unix)]
+use task::Shell;
+#[cfg(unix)]
+use util::ResultExt;
+
+#[cfg(unix)]
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+#[cfg(unix)]
+const ZSH_ENV_SCRIPT: &str = include_str!("shell_integration/.zshenv");
+#[cfg(unix)]
+const ZSH_PROFILE_SCRIPT: &str = include_str!("shell_integration/.zprofile");
+#[cfg(unix)]
+const ZSH_RC_SCRIPT: &str = include_str!("shell_integration/.zshrc");
+#[cfg(unix)]
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+/// OSC payloads are short, anything longer is not a sequence we are interested in.
+const MAX_OSC_LEN: usize = 4096;
+
+/// A marker reported by the shell integration scripts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMarker {
+    /// `OSC 133 ; A`: the shell is about to print the prompt.
+    PromptStart,
+    /// `OSC 133 ; B`: the prompt is printed, user input starts here.
+    CommandStart,
+    /// `OSC 133 ; C`: the command was submitted, its output starts here.
+    CommandExecuted,
+    /// `OSC 133 ; D [; exit code]`: the command finished.
+    CommandFinished { exit_code: Option<i32> },
+    /// `OSC 7 ; file://host/path`: the shell changed its working directory.
+    WorkingDirectory(PathBuf),
+}
+
+/// A grid point, with the line counted from the first line that was ever printed to the terminal.
+///
+/// Unlike alacritty's [`alacritty_terminal::index::Line`], it does not change when more
+/// output is appended to the terminal, or when lines are dropped from a full scrollback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScrollbackPoint {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ScrollbackPoint {
+    #[cfg(unix)]
+    pub(crate) fn cursor<T>(term: &Term<T>, dropped_lines: usize) -> Self {
+        let grid = term.grid();
+        Self {
+            line: dropped_lines + grid.history_size() + grid.cursor.point.line.0.max(0) as usize,
+            column: grid.cursor.point.column.0,
+        }
+    }
+
+    /// Converts the point back into alacritty's coordinates, if it is still in the scrollback.
+    pub(crate) fn to_alac_point<T>(
+        self,
+        term: &Term<T>,
+        dropped_lines: usize,
+    ) -> Option<alacritty_terminal::index::Point> {
+        let line = self.line.checked_sub(dropped_lines)? as i32 - term.grid().history_size() as i32;
+        let line = Line(line);
+        if line < term.topmost_line() || line > term.bottommost_line() {
+            return None;
+        }
+        Some(alacritty_terminal::index::Point::new(
+            line,
+            alacritty_terminal::index::Column(self.column.min(term.columns().saturating_sub(1))),
+        ))
+    }
+}
+
+/// How many rows at the bottom of the scrollback are remembered to correct the count.
+const ANCHOR_ROWS: usize = 4;
+
+/// How far from the counted lines the remembered rows are looked up.
+const ANCHOR_SEARCH_LINES: usize = 256;
+
+/// Counts the lines dropped from the top of the scrollback once it is full, which
+/// [`ScrollbackPoint`]s are relative to.
+///
+/// Alacritty does not report dropped lines, so they are counted from the linefeeds handed to
+/// the terminal parser: every linefeed that does not move the cursor down scrolls a line into
+/// the scrollback, and every line that does not grow it drops one from the top.
+///
+/// Long lines wrap into the scrollback without a linefeed, so the last rows of the scrollback
+/// are also remembered and looked up again close to the counted position.
+pub(crate) struct ScrollbackTracker {
+    max_history_size: usize,
+    dropped_lines: usize,
+    /// Linefeeds handed to the terminal parser since the last update.
+    linefeeds: usize,
+    history_size: usize,
+    cursor_line: i32,
+    anchor: Option<ScrollbackAnchor>,
+}
+
+struct ScrollbackAnchor {
+    /// The first remembered row, counted from the top of the scrollback.
+    line: usize,
+    rows: Vec<Row<Cell>>,
+}
+
+impl ScrollbackTracker {
+    pub(crate) fn new(max_history_size: usize) -> Self {
+        Self {
+            max_history_size,
+            dropped_lines: 0,
+            linefeeds: 0,
+            history_size: 0,
+            cursor_line: 0,
+            anchor: None,
+        }
+    }
+
+    /// Records the linefeeds in output that is about to be parsed by the terminal.
+    pub(crate) fn count_linefeeds(&mut self, bytes: &[u8]) {
+        self.linefeeds += bytes
+            .iter()
+            .filter(|byte| matches!(byte, b'\n' | b'\x0b' | b'\x0c'))
+            .count();
+    }
+
+    /// Accounts for the lines dropped since the last call, and returns how many lines were
+    /// dropped in total.
+    pub(crate) fn update<T>(&mut self, term: &Term<T>) -> usize {
+        let linefeeds = std::mem::take(&mut self.linefeeds);
+        // The alternate screen has no scrollback.
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return self.dropped_lines;
+        }
+
+        let grid = term.grid();
+        let history_size = grid.history_size();
+        let cursor_line = grid.cursor.point.line.0;
+        let row_at = |line: usize| &grid[Line(line as i32 - history_size as i32)];
+
+        let moved_down = (cursor_line - self.cursor_line).max(0) as usize;
+        let grown_by = history_size.saturating_sub(self.history_size);
+        let counted = linefeeds
+            .saturating_sub(moved_down)
+            .saturating_sub(grown_by);
+        self.history_size = history_size;
+        self.cursor_line = cursor_line;
+
+        let mut moved_by = if history_size == self.max_history_size {
+            counted
+        } else {
+            0
+        };
+        if let Some(anchor) = self.anchor.as_mut() {
+            if history_size == self.max_history_size && history_size >= anchor.line + ANCHOR_ROWS {
+                let found_at = |moved_by: usize| {
+                    moved_by <= anchor.line
+                        && anchor
+                            .rows
+                            .iter()
+                            .enumerate()
+                            .all(|(ix, row)| row_at(anchor.line - moved_by + ix) == row)
+                };
+                // Repeated output matches at many positions, so prefer the closest one to the
+                // counted lines.
+                let found = (0..=ANCHOR_SEARCH_LINES).find_map(|distance| {
+                    [Some(counted + distance), counted.checked_sub(distance)]
+                        .into_iter()
+                        .flatten()
+                        .find(|moved_by| found_at(*moved_by))
+                });
+                let searched_all =
+                    counted <= ANCHOR_SEARCH_LINES && anchor.line <= counted + ANCHOR_SEARCH_LINES;
+                match found {
+                    Some(found) => moved_by = found,
+                    // Not finding the rows anywhere means that they were dropped as well.
+                    None if searched_all => moved_by = moved_by.max(anchor.line + 1),
+                    None => {}
+                }
+                anchor.line = anchor.line.saturating_sub(moved_by);
+            }
+        }
+        self.dropped_lines += moved_by;
+
+        // Blank rows are found anywhere, keep the previous anchor rather than remembering them.
+        if history_size >= ANCHOR_ROWS {
+            let line = history_size - ANCHOR_ROWS;
+            let rows = (line..history_size).map(row_at).collect::<Vec<_>>();
+            if rows.iter().any(|row| !row.is_clear()) {
+                self.anchor = Some(ScrollbackAnchor {
+                    line,
+                    rows: rows.into_iter().cloned().collect(),
+                });
+            }
+        }
+
+        self.dropped_lines
+    }
+}
+
+/// Incrementally scans PTY output for the OSC sequences emitted by the integration scripts.
+#[derive(Default)]
+pub(crate) struct MarkerParser {
+    state: ParserState,
+    payload: Vec<u8>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ParserState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+impl MarkerParser {
+    /// Feeds a single byte, returning a marker when it terminates one.
+    pub(crate) fn advance(&mut self, byte: u8) -> Option<ShellMarker> {
+        match (self.state, byte) {
+            (ParserState::Ground, 0x1b) => self.state = ParserState::Escape,
+            (ParserState::Ground, _) => {}
+            (ParserState::Escape, b']') => {
+                self.payload.clear();
+                self.state = ParserState::Osc;
+            }
+            (ParserState::Escape, 0x1b) => {}
+            (ParserState::Escape, _) => self.state = ParserState::Ground,
+            (ParserState::Osc, 0x07) => {
+                self.state = ParserState::Ground;
+                return parse_osc(&self.payload);
+            }
+            (ParserState::Osc, 0x1b) => self.state = ParserState::OscEscape,
+            (ParserState::Osc, _) => {
+                if self.payload.len() < MAX_OSC_LEN {
+                    self.payload.push(byte);
+                } else {
+                    self.state = ParserState::Ground;
+                }
+            }
+            (ParserState::OscEscape, b'\\') => {
+                self.state = ParserState::Ground;
+                return parse_osc(&self.payload);
+            }
+            // Any other escape aborts the OSC sequence and may start a new one.
+            (ParserState::OscEscape, b']') => {
+                self.payload.clear();
+                self.state = ParserState::Osc;
+            }
+            (ParserState::OscEscape, 0x1b) => self.state = ParserState::Escape,
+            (ParserState::OscEscape, _) => self.state = ParserState::Ground,
+        }
+        None
+    }
+}
+
+fn parse_osc(payload: &[u8]) -> Option<ShellMarker> {
+    let payload = std::str::from_utf8(payload).ok()?;
+    let (command, params) = payload.split_once(';').unwrap_or((payload, ""));
+    match command {
+        "133" => {
+            let mut params = params.split(';');
+            match params.next()? {
+                "A" => Some(ShellMarker::PromptStart),
+                "B" => Some(ShellMarker::CommandStart),
+                "C" => Some(ShellMarker::CommandExecuted),
+                "D" => Some(ShellMarker::CommandFinished {
+                    exit_code: params.next().and_then(|code| code.trim().parse().ok()),
+                }),
+                _ => None,
+            }
+        }
+        "7" => {
+            let url = params.strip_prefix("file://")?;
+            // Skip the host name, the path starts with the first slash after it.
+            let path = &url[url.find('/')?..];
+            Some(ShellMarker::WorkingDirectory(PathBuf::from(
+                percent_decode(path),
+            )))
+        }
+        _ => None,
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] == b'%' && ix + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[ix + 1..ix + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                ix += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[ix]);
+        ix += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(unix)]
+pub(crate) use unix::ShellIntegrationPty;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs::File,
+        io::{self, Read, Write},
+        os::unix::net::UnixStream,
+        sync::Arc,
+    };
+
+    use alacritty_terminal::{
+        event::{OnResize, WindowSize},
+        sync::FairMutex,
+        tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+        Term,
+    };
+    use futures::channel::mpsc::UnboundedSender;
+    use parking_lot::Mutex;
+    use polling::{Event, PollMode, Poller};
+
+    use super::{MarkerParser, ScrollbackPoint, ScrollbackTracker, ShellMarker};
+    use crate::ZedListener;
+
+    /// Mirrors alacritty's (private) poller key for PTY reads and writes.
+    const PTY_READ_WRITE_TOKEN: usize = 0;
+
+    /// A [`Pty`] whose output is scanned for shell integration markers.
+    pub(crate) struct ShellIntegrationPty {
+        pty: Pty,
+        reader: MarkerReader,
+    }
+
+    impl ShellIntegrationPty {
+        pub(crate) fn new(
+            pty: Pty,
+            term: Arc<FairMutex<Term<ZedListener>>>,
+            scrollback: Arc<Mutex<ScrollbackTracker>>,
+            markers_tx: UnboundedSender<(ShellMarker, ScrollbackPoint)>,
+        ) -> io::Result<Self> {
+            let reader = MarkerReader {
+                file: pty.file().try_clone()?,
+                waker: Waker::new()?,
+                parser: MarkerParser::default(),
+                term,
+                scrollback,
+                markers_tx,
+                leftover: Vec::new(),
+                pending: None,
+                yielded: false,
+            };
+            Ok(Self { pty, reader })
+        }
+    }
+
+    impl EventedReadWrite for ShellIntegrationPty {
+        type Reader = MarkerReader;
+        type Writer = File;
+
+        unsafe fn register(
+            &mut self,
+            poller: &Arc<Poller>,
+            interest: Event,
+            mode: PollMode,
+        ) -> io::Result<()> {
+            self.pty.register(poller, interest, mode)?;
+            poller.add_with_mode(
+                &self.reader.waker.rx,
+                Event::readable(PTY_READ_WRITE_TOKEN),
+                mode,
+            )
+        }
+
+        fn reregister(
+            &mut self,
+            poller: &Arc<Poller>,
+            interest: Event,
+            mode: PollMode,
+        ) -> io::Result<()> {
+            self.pty.reregister(poller, interest, mode)?;
+            poller.modify_with_mode(
+                &self.reader.waker.rx,
+                Event::readable(PTY_READ_WRITE_TOKEN),
+                mode,
+            )
+        }
+
+        fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+            self.pty.deregister(poller)?;
+            poller.delete(&self.reader.waker.rx)
+        }
+
+        fn reader(&mut self) -> &mut Self::Reader {
+            &mut self.reader
+        }
+
+        fn writer(&mut self) -> &mut Self::Writer {
+            self.pty.writer()
+        }
+    }
+
+    impl EventedPty for ShellIntegrationPty {
+        fn next_child_event(&mut self) -> Option<ChildEvent> {
+            self.pty.next_child_event()
+        }
+    }
+
+    impl OnResize for ShellIntegrationPty {
+        fn on_resize(&mut self, window_size: WindowSize) {
+            self.pty.on_resize(window_size)
+        }
+    }
+
+    /// Reads the PTY output, forwarding every marker together with the grid position it was printed at.
+    pub(crate) struct MarkerReader {
+        file: File,
+        waker: Waker,
+        parser: MarkerParser,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        scrollback: Arc<Mutex<ScrollbackTracker>>,
+        markers_tx: UnboundedSender<(ShellMarker, ScrollbackPoint)>,
+        /// Bytes read past a marker, handed out once the marker's position is known.
+        leftover: Vec<u8>,
+        pending: Option<ShellMarker>,
+        yielded: bool,
+    }
+
+    impl MarkerReader {
+        /// Scans the bytes, returning how many of them can be handed to the terminal parser right away.
+        fn scan(&mut self, bytes: &[u8]) -> usize {
+            for (ix, byte) in bytes.iter().enumerate() {
+                if let Some(marker) = self.parser.advance(*byte) {
+                    self.pending = Some(marker);
+                    return ix + 1;
+                }
+            }
+            bytes.len()
+        }
+    }
+
+    impl Read for MarkerReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.waker.drain();
+
+            if let Some(marker) = self.pending.take() {
+                if !self.yielded {
+                    // Pretend there is nothing to read, so that the event loop parses everything up
+                    // to the marker and releases the terminal lock before the position is taken.
+                    self.pending = Some(marker);
+                    self.yielded = true;
+                    self.waker.wake();
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+                self.yielded = false;
+                let term = self.term.lock_unfair();
+                let dropped_lines = self.scrollback.lock().update(&term);
+                let point = ScrollbackPoint::cursor(&term, dropped_lines);
+                drop(term);
+                self.markers_tx.unbounded_send((marker, point)).ok();
+            }
+
+            let len = if self.leftover.is_empty() {
+                self.file.read(buf)?
+            } else {
+                let len = self.leftover.len().min(buf.len());
+                buf[..len].copy_from_slice(&self.leftover[..len]);
+                self.leftover.drain(..len);
+                len
+            };
+
+            let handed_out = self.scan(&buf[..len]);
+            self.scrollback.lock().count_linefeeds(&buf[..handed_out]);
+            if handed_out < len {
+                self.leftover
+                    .splice(0..0, buf[handed_out..len].iter().copied());
+            }
+            if self.pending.is_some() || !self.leftover.is_empty() {
+                self.waker.wake();
+            }
+            Ok(handed_out)
+        }
+    }
+
+    /// A socket pair registered next to the PTY. It makes the event loop read again when
+    /// the PTY itself has no new data, but the reader still holds some.
+    struct Waker {
+        rx: UnixStream,
+        tx: UnixStream,
+    }
+
+    impl Waker {
+        fn new() -> io::Result<Self> {
+            let (rx, tx) = UnixStream::pair()?;
+            rx.set_nonblocking(true)?;
+            tx.set_nonblocking(true)?;
+            Ok(Self { rx, tx })
+        }
+
+        fn wake(&self) {
+            (&self.tx).write(&[0]).ok();
+        }
+
+        fn drain(&self) {
+            let mut buf = [0; 64];
+            while matches!((&self.rx).read(&mut buf), Ok(len) if len > 0) {}
+        }
+    }
+}
+
+/// Makes the shell load Zed's integration script, by adjusting its arguments and environment.
+/// Shells that are not supported are returned unchanged.
+#[cfg(unix)]
+pub(crate) fn inject(shell: Shell, env: &mut HashMap<String, String>) -> Shell {
+    let program = match &shell {
+        Shell::System => std::env::var("SHELL").ok(),
+        Shell::Program(program) => Some(program.clone()),
+        Shell::WithArguments { program, .. } => Some(program.clone()),
+    };
+    let Some(program) = program else {
+        return shell;
+    };
+    let Some(scripts_dir) = scripts_dir() else {
+        return shell;
+    };
+    let shell_name = Path::new(&program)
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('-').to_string())
+        .unwrap_or_default();
+
+    let inherited_var = |env: &HashMap<String, String>, name: &str| {
+        env.get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    };
+
+    match shell_name.as_str() {
+        "zsh" => {
+            if let Some(zdotdir) = inherited_var(env, "ZDOTDIR") {
+                env.insert("ZED_USER_ZDOTDIR".to_string(), zdotdir);
+            }
+            env.insert(
+                "ZDOTDIR".to_string(),
+                scripts_dir.join("zsh").to_string_lossy().to_string(),
+            );
+            shell
+        }
+        "fish" => {
+            let data_dirs = match inherited_var(env, "XDG_DATA_DIRS") {
+                Some(data_dirs) => {
+                    env.insert("ZED_USER_XDG_DATA_DIRS".to_string(), data_dirs.clone());
+                    data_dirs
+                }
+                // The defaults from the XDG Base Directory specification.
+                None => "/usr/local/share:/usr/share".to_string(),
+            };
+            env.insert(
+                "XDG_DATA_DIRS".to_string(),
+                format!("{}:{data_dirs}", scripts_dir.to_string_lossy()),
+            );
+            shell
+        }
+        "bash" => {
+            let rcfile_args = vec![
+                "--rcfile".to_string(),
+                scripts_dir.join("zed.bash").to_string_lossy().to_string(),
+            ];
+            match shell {
+                Shell::Program(program) => Shell::WithArguments {
+                    program,
+                    args: rcfile_args,
+                    title_override: None,
+                },
+                // On macOS, the system shell is started as a login shell, which ignores `--rcfile`.
+                Shell::System if cfg!(not(target_os = "macos")) => Shell::WithArguments {
+                    program,
+                    args: rcfile_args,
+                    title_override: None,
+                },
+                // Custom arguments may already decide which startup files are read, leave them be.
+                shell => shell,
+            }
+        }
+        _ => shell,
+    }
+}
+
+#[cfg(unix)]
+fn scripts_dir() -> Option<&'static Path> {
+    static SCRIPTS_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    SCRIPTS_DIR
+        .get_or_init(|| {
+            let dir = paths::temp_dir().join("shell_integration");
+            write_scripts(&dir).log_err()?;
+            Some(dir)
+        })
+        .as_deref()
+}
+
+#[cfg(unix)]
+fn write_scripts(dir: &Path) -> io::Result<()> {
+    let zsh_dir = dir.join("zsh");
+    let fish_dir = dir.join("fish").join("vendor_conf.d");
+    std::fs::create_dir_all(&zsh_dir)?;
+    std::fs::create_dir_all(&fish_dir)?;
+    std::fs::write(dir.join("zed.bash"), BASH_SCRIPT)?;
+    std::fs::write(zsh_dir.join(".zshenv"), ZSH_ENV_SCRIPT)?;
+    std::fs::write(zsh_dir.join(".zprofile"), ZSH_PROFILE_SCRIPT)?;
+    std::fs::write(zsh_dir.join(".zshrc"), ZSH_RC_SCRIPT)?;
+    std::fs::write(fish_dir.join("zed.fish"), FISH_SCRIPT)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::VoidListener,
+        index::{Column, Point},
+        term::Config,
+        vte::ansi::Processor,
+    };
+
+    use super::*;
+    use crate::TerminalSize;
+    use std::ops::Range;
+
+    fn markers(input: &[u8]) -> Vec<ShellMarker> {
+        let mut parser = MarkerParser::default();
+        input
+            .iter()
+            .filter_map(|byte| parser.advance(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_semantic_prompt_markers() {
+        assert_eq!(
+            markers(b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a b\r\n\x1b]133;D;1\x07"),
+            vec![
+                ShellMarker::PromptStart,
+                ShellMarker::CommandStart,
+                ShellMarker::CommandExecuted,
+                ShellMarker::CommandFinished { exit_code: Some(1) },
+            ]
+        );
+        // String terminator instead of BEL, missing and extra parameters.
+        assert_eq!(
+            markers(b"\x1b]133;D\x1b\\\x1b]133;A;cl=m\x1b\\"),
+            vec![
+                ShellMarker::CommandFinished { exit_code: None },
+                ShellMarker::PromptStart,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unrelated_sequences_are_ignored() {
+        assert_eq!(
+            markers(b"\x1b]0;title\x07\x1b[31mred\x1b[0m\x1b]8;;http://zed.dev\x1b\\"),
+            Vec::new()
+        );
+        // An interrupted sequence does not swallow the following one.
+        assert_eq!(
+            markers(b"\x1b]133;A\x1b]133;C\x07"),
+            vec![ShellMarker::CommandExecuted]
+        );
+    }
+
+    #[test]
+    fn test_working_directory() {
+        assert_eq!(
+            markers(b"\x1b]7;file://my-host/home/user/my%20project\x07"),
+            vec![ShellMarker::WorkingDirectory(PathBuf::from(
+                "/home/user/my project"
+            ))]
+        );
+        assert_eq!(
+            markers(b"\x1b]7;file:///tmp\x1b\\"),
+            vec![ShellMarker::WorkingDirectory(PathBuf::from("/tmp"))]
+        );
+    }
+
+    fn test_term() -> Term<VoidListener> {
+        let config = Config {
+            scrolling_history: 20,
+            ..Config::default()
+        };
+        let term = Term::new(config, &TerminalSize::default(), VoidListener);
+        assert_eq!(term.screen_lines(), 6);
+        term
+    }
+
+    /// Prints the text like the event loop would, after the tracker saw it.
+    fn print(
+        term: &mut Term<VoidListener>,
+        processor: &mut Processor,
+        tracker: &mut ScrollbackTracker,
+        text: &str,
+    ) {
+        tracker.count_linefeeds(text.as_bytes());
+        for byte in text.bytes() {
+            processor.advance(term, byte);
+        }
+    }
+
+    fn text_at(term: &Term<VoidListener>, line: usize, dropped_lines: usize) -> Option<String> {
+        let point = ScrollbackPoint { line, column: 0 }.to_alac_point(term, dropped_lines)?;
+        let end = Point::new(point.line, Column(term.columns() - 1));
+        Some(term.bounds_to_string(point, end).trim().to_string())
+    }
+
+    #[test]
+    fn test_lines_dropped_from_full_scrollback() {
+        let mut term = test_term();
+        let mut processor: Processor = Processor::new();
+        let mut tracker = ScrollbackTracker::new(20);
+        let mut print_lines = |term: &mut Term<VoidListener>,
+                               tracker: &mut ScrollbackTracker,
+                               lines: Range<usize>,
+                               blank: bool| {
+            for line in lines {
+                let text = if blank {
+                    "\r\n".to_string()
+                } else {
+                    format!("line {line}\r\n")
+                };
+                print(term, &mut processor, tracker, &text);
+            }
+        };
+
+        // The scrollback is full, with the cursor on the line after the last one printed.
+        print_lines(&mut term, &mut tracker, 0..25, false);
+        assert_eq!(tracker.update(&term), 0);
+        assert_eq!(text_at(&term, 0, 0).as_deref(), Some("line 0"));
+
+        print_lines(&mut term, &mut tracker, 25..30, false);
+        let dropped_lines = tracker.update(&term);
+        assert_eq!(dropped_lines, 5);
+        assert_eq!(text_at(&term, 4, dropped_lines), None);
+        assert_eq!(text_at(&term, 5, dropped_lines).as_deref(), Some("line 5"));
+
+        for batch in 0..5 {
+            print_lines(
+                &mut term,
+                &mut tracker,
+                30 + batch * 6..36 + batch * 6,
+                false,
+            );
+            tracker.update(&term);
+        }
+        let dropped_lines = tracker.update(&term);
+        assert_eq!(dropped_lines, 35);
+        assert_eq!(
+            text_at(&term, 40, dropped_lines).as_deref(),
+            Some("line 40")
+        );
+
+        // Blank rows are not remembered, the rows above them are looked up instead.
+        print_lines(&mut term, &mut tracker, 60..70, true);
+        assert_eq!(tracker.update(&term), 45);
+        print_lines(&mut term, &mut tracker, 70..73, false);
+        let dropped_lines = tracker.update(&term);
+        assert_eq!(dropped_lines, 48);
+        assert_eq!(
+            text_at(&term, 72, dropped_lines).as_deref(),
+            Some("line 72")
+        );
+    }
+
+    #[test]
+    fn test_lines_dropped_with_repeated_output() {
+        let mut term = test_term();
+        let mut processor: Processor = Processor::new();
+        let mut tracker = ScrollbackTracker::new(20);
+
+        for line in 0..25 {
+            print(
+                &mut term,
+                &mut processor,
+                &mut tracker,
+                &format!("line {line}\r\n"),
+            );
+        }
+        assert_eq!(tracker.update(&term), 0);
+
+        // Identical rows are found at every position, the linefeeds tell how far they moved.
+        print(&mut term, &mut processor, &mut tracker, &"y\r\n".repeat(30));
+        assert_eq!(tracker.update(&term), 30);
+        for _ in 0..3 {
+            print(&mut term, &mut processor, &mut tracker, &"y\r\n".repeat(7));
+            tracker.update(&term);
+        }
+        let dropped_lines = tracker.update(&term);
+        assert_eq!(dropped_lines, 51);
+        assert_eq!(text_at(&term, 50, dropped_lines), None);
+        assert_eq!(text_at(&term, 51, dropped_lines).as_deref(), Some("y"));
+
+        for line in 76..86 {
+            print(
+                &mut term,
+                &mut processor,
+                &mut tracker,
+                &format!("line {line}\r\n"),
+            );
+        }
+        assert_eq!(tracker.update(&term), 61);
+
+        // Lines wrapping into the scrollback are found through the remembered rows.
+        let long_line = "x".repeat(term.columns() * 2);
+        print(
+            &mut term,
+            &mut processor,
+            &mut tracker,
+            &format!("{long_line}\r\nline 88\r\n"),
+        );
+        let dropped_lines = tracker.update(&term);
+        assert_eq!(dropped_lines, 64);
+        assert_eq!(
+            text_at(&term, 88, dropped_lines).as_deref(),
+            Some("line 88")
+        );
+    }
+}
@@ -0,0 +1,62 @@
+# Zed shell integration for bash.
+#
+# Loaded through `bash --rcfile`, so the user's own startup file is sourced first.
+if [ -z "$ZED_SHELL_INTEGRATION_LOADED" ]; then
+    ZED_SHELL_INTEGRATION_LOADED=1
+
+    if [ -f "$HOME/.bashrc" ]; then
+        . "$HOME/.bashrc"
+    fi
+
+    __zed_command_running=
+    __zed_at_prompt=
+
+    # Percent-encodes a path for use in a file URL, into `__zed_encoded_path`.
+    __zed_encode_path() {
+        local LC_ALL=C path="$1" char ix
+        __zed_encoded_path=
+        for ((ix = 0; ix < ${#path}; ix++)); do
+            char="${path:ix:1}"
+            case "$char" in
+                [a-zA-Z0-9/._~-]) __zed_encoded_path+="$char" ;;
+                *) builtin printf -v char '%%%02X' "'$char"; __zed_encoded_path+="$char" ;;
+            esac
+        done
+    }
+
+    __zed_prompt_start() {
+        local status=$?
+        if [ -n "$__zed_command_running" ]; then
+            builtin printf '\e]133;D;%s\a' "$status"
+        fi
+        __zed_command_running=
+        __zed_encode_path "$PWD"
+        builtin printf '\e]7;file://%s%s\a' "$HOSTNAME" "$__zed_encoded_path"
+        builtin printf '\e]133;A\a'
+    }
+
+    __zed_prompt_end() {
+        # Other prompt commands may have rewritten PS1, so re-append the marker every time.
+        if [[ "$PS1" != *'\[\e]133;B\a\]' ]]; then
+            PS1="$PS1"'\[\e]133;B\a\]'
+        fi
+        __zed_at_prompt=1
+    }
+
+    __zed_preexec() {
+        if [ -n "$__zed_at_prompt" ]; then
+            case "$BASH_COMMAND" in
+                __zed_prompt_start*|__zed_prompt_end*) return ;;
+            esac
+            __zed_at_prompt=
+            __zed_command_running=1
+            builtin printf '\e]133;C\a'
+        fi
+    }
+
+    PROMPT_COMMAND="__zed_prompt_start${PROMPT_COMMAND:+; $PROMPT_COMMAND}; __zed_prompt_end"
+    # Do not clobber a DEBUG trap the user has set up, we only lose the output start marker then.
+    if [ -z "$(trap -p DEBUG)" ]; then
+        trap '__zed_preexec' DEBUG
+    fi
+fi
@@ -0,0 +1,37 @@
+# Zed shell integration for fish.
+#
+# Loaded from `vendor_conf.d` through XDG_DATA_DIRS, restore the original value
+# so that programs started from this shell do not see Zed's directory.
+if set -q ZED_USER_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS $ZED_USER_XDG_DATA_DIRS
+    set -e ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+if status is-interactive; and not set -q ZED_SHELL_INTEGRATION_LOADED
+    set -g ZED_SHELL_INTEGRATION_LOADED 1
+
+    function __zed_prompt --on-event fish_prompt
+        printf '\e]7;file://%s%s\a' (hostname) (string escape --style=url -- "$PWD")
+        printf '\e]133;A\a'
+
+        # The user's config defines `fish_prompt` after vendor files are loaded,
+        # wrap it lazily right before it is drawn for the first time.
+        if not functions -q __zed_original_fish_prompt
+            functions -c fish_prompt __zed_original_fish_prompt
+            function fish_prompt
+                __zed_original_fish_prompt
+                printf '\e]133;B\a'
+            end
+        end
+    end
+
+    function __zed_preexec --on-event fish_preexec
+        printf '\e]133;C\a'
+    end
+
+    function __zed_postexec --on-event fish_postexec
+        printf '\e]133;D;%s\a' $status
+    end
+end
@@ -0,0 +1,176 @@
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::cell::Flags,
+    Term,
+};
+use collections::VecDeque;
+use regex::Regex;
+use std::ops::RangeInclusive;
+use task::{Problem, ProblemCollector, ProblemMatcher};
+
+use crate::ScrollbackPoint;
+
+/// How many scanned lines are remembered to link the problems found to, as a multi-line
+/// problem is only complete after its location was printed.
+const MAX_REMEMBERED_LINES: usize = 64;
+
+/// Looks for problems in the output of a task as it is printed, and for the line that marks
+/// a background task as ready.
+pub(crate) struct TaskOutputScanner {
+    collector: ProblemCollector,
+    ready_pattern: Option<Regex>,
+    ready: bool,
+    /// The first line of the output that was not scanned yet.
+    next_line: usize,
+    /// The first and last scrollback lines of the most recently scanned output lines.
+    recent_lines: VecDeque<(usize, usize)>,
+    links: Vec<ProblemLink>,
+    /// Whether the task finished and all of its output was scanned.
+    finished: bool,
+}
+
+/// The location of a problem in the output, opened when clicked.
+struct ProblemLink {
+    start: ScrollbackPoint,
+    end: ScrollbackPoint,
+    target: String,
+}
+
+impl TaskOutputScanner {
+    pub fn new(problem_matchers: &[ProblemMatcher], ready_pattern: Option<Regex>) -> Option<Self> {
+        let collector = ProblemCollector::new(problem_matchers);
+        if collector.is_empty() && ready_pattern.is_none() {
+            return None;
+        }
+        Some(Self {
+            collector,
+            ready_pattern,
+            ready: false,
+            next_line: 0,
+            recent_lines: VecDeque::new(),
+            links: Vec::new(),
+            finished: false,
+        })
+    }
+
+    /// Scans the lines printed since the last scan. The line the cursor is on is only scanned
+    /// once the task has `finished`, as more may be printed on it.
+    pub fn scan<T>(
+        &mut self,
+        term: &Term<T>,
+        dropped_lines: usize,
+        finished: bool,
+    ) -> Vec<Problem> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = finished;
+        let history_size = term.grid().history_size() as i32;
+        let last_column = term.last_column();
+        let is_wrapped = |line: i32| {
+            term.grid()[Line(line)][last_column]
+                .flags
+                .contains(Flags::WRAPLINE)
+        };
+
+        let start = (self.next_line.saturating_sub(dropped_lines) as i32 - history_size)
+            .max(term.topmost_line().0);
+        let mut end = if finished {
+            term.bottommost_line().0 + 1
+        } else {
+            term.grid().cursor.point.line.0
+        };
+        // Wait for the rest of a line that wrapped onto the cursor line.
+        while !finished && end > start && is_wrapped(end - 1) {
+            end -= 1;
+        }
+
+        let mut problems = Vec::new();
+        let mut line = start;
+        while line < end {
+            let mut last = line;
+            while last + 1 < end && is_wrapped(last) {
+                last += 1;
+            }
+            let text = term.bounds_to_string(
+                AlacPoint::new(Line(line), Column(0)),
+                AlacPoint::new(Line(last), last_column),
+            );
+
+            if self.recent_lines.len() == MAX_REMEMBERED_LINES {
+                self.recent_lines.pop_front();
+            }
+            self.recent_lines.push_back((
+                (line + history_size) as usize + dropped_lines,
+                (last + history_size) as usize + dropped_lines,
+            ));
+            if !self.ready {
+                self.ready = self
+                    .ready_pattern
+                    .as_ref()
+                    .is_some_and(|ready_pattern| ready_pattern.is_match(&text));
+            }
+            for problem in self.collector.push_line(text.trim_end()) {
+                self.add_link(&problem, last_column.0);
+                problems.push(problem);
+            }
+            line = last + 1;
+        }
+        self.next_line = (end.max(start) + history_size) as usize + dropped_lines;
+        problems
+    }
+
+    /// Whether a line of the output matched the ready pattern.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    fn add_link(&mut self, problem: &Problem, last_column: usize) {
+        let lines_ago = self
+            .collector
+            .lines_seen()
+            .saturating_sub(problem.location_line + 1);
+        let Some(&(first_line, last_line)) = self
+            .recent_lines
+            .len()
+            .checked_sub(lines_ago + 1)
+            .and_then(|ix| self.recent_lines.get(ix))
+        else {
+            return;
+        };
+
+        let mut target = problem.path.to_string_lossy().to_string();
+        target.push_str(&format!(":{}", problem.row));
+        if let Some(column) = problem.column {
+            target.push_str(&format!(":{column}"));
+        }
+        self.links.push(ProblemLink {
+            start: ScrollbackPoint {
+                line: first_line,
+                column: 0,
+            },
+            end: ScrollbackPoint {
+                line: last_line,
+                column: last_column,
+            },
+            target,
+        });
+    }
+
+    /// Returns the `path:row:column` location of the problem printed at the given point, if any.
+    pub fn link_at<T>(
+        &self,
+        term: &Term<T>,
+        dropped_lines: usize,
+        point: AlacPoint,
+    ) -> Option<(String, RangeInclusive<AlacPoint>)> {
+        self.links.iter().rev().find_map(|link| {
+            let start = link.start.to_alac_point(term, dropped_lines)?;
+            let end = link.end.to_alac_point(term, dropped_lines)?;
+            (start..=end)
+                .contains(&point)
+                .then(|| (link.target.clone(), start..=end))
+        })
+    }
+}
@@ -3,6 +3,8 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+mod shell_integration;
+mod task_output;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -21,6 +23,7 @@ use alacritty_terminal::{
     vi_mode::{ViModeCursor, ViMotion},
     vte::ansi::{
         ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
+        Processor,
     },
     Term,
 };
@@ -35,17 +38,22 @@ use mappings::mouse::{
     alt_scroll, grid_point, grid_point_and_side, mouse_button_report, mouse_moved_report,
     scroll_report,
 };
+use parking_lot::Mutex;
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
 use pty_info::PtyProcessInfo;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::ScrollbackTracker;
+pub use shell_integration::{ScrollbackPoint, ShellMarker};
 use smol::channel::{Receiver, Sender};
-use task::{HideStrategy, Shell, TaskId};
+use task::{HideStrategy, Problem, ProblemMatcher, Shell, TaskId};
+use task_output::TaskOutputScanner;
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
-use util::{paths::home_dir, truncate_and_trailoff};
+use util::{paths::home_dir, truncate_and_trailoff, ResultExt};
 
 use std::{
     cmp::{self, min},
@@ -80,6 +88,10 @@ actions!(
         ScrollToTop,
         ScrollToBottom,
         ToggleViMode,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        SelectLastCommandOutput,
+        CopyLastCommandOutput,
     ]
 );
 
@@ -95,6 +107,8 @@ const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
 const DEBUG_LINE_HEIGHT: Pixels = px(5.);
+/// How many shell commands are remembered per terminal, older ones are dropped.
+const MAX_COMMAND_BLOCKS: usize = 1000;
 
 ///Upward flowing events, for changing the title and such
 #[derive(Clone, Debug)]
@@ -108,6 +122,8 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task's problem matchers found new problems in its output.
+    TaskProblems(Vec<Problem>),
 }
 
 #[derive(Clone, Debug)]
@@ -144,6 +160,8 @@ enum InternalEvent {
     // Vi mode events
     ToggleViMode,
     ViMotion(ViMotion),
+    ScrollToPrompt(AlacDirection),
+    SelectLastCommandOutput,
 }
 
 ///A translation struct for Alacritty to communicate with us from their event loop
@@ -318,6 +336,7 @@ const WORD_REGEX: &str =
 pub struct TerminalBuilder {
     terminal: Terminal,
     events_rx: UnboundedReceiver<AlacTermEvent>,
+    shell_markers_rx: UnboundedReceiver<(ShellMarker, ScrollbackPoint)>,
 }
 
 impl TerminalBuilder {
@@ -331,6 +350,8 @@ impl TerminalBuilder {
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
         is_ssh_terminal: bool,
+        shell_integration: bool,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
         cx: &AppContext,
@@ -351,6 +372,16 @@ impl TerminalBuilder {
             release_channel::AppVersion::global(cx).to_string(),
         );
 
+        // Tasks run their own command, not an interactive shell we could hook into.
+        #[cfg(unix)]
+        let shell = if shell_integration && task.is_none() && !is_ssh_terminal {
+            shell_integration::inject(shell, &mut env)
+        } else {
+            shell
+        };
+        #[cfg(not(unix))]
+        let _ = shell_integration;
+
         let mut terminal_title_override = None;
 
         let pty_options = {
@@ -414,7 +445,13 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Written before the process starts, so that its output follows the restored one.
+        if let Some(scrollback) = restored_scrollback.filter(|scrollback| !scrollback.is_empty()) {
+            write_restored_scrollback(&mut term, &scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
+        let scrollback = Arc::new(Mutex::new(ScrollbackTracker::new(scrolling_history)));
 
         //Setup the pty...
         let pty = match tty::new(
@@ -434,6 +471,17 @@ impl TerminalBuilder {
 
         let pty_info = PtyProcessInfo::new(&pty);
 
+        let (shell_markers_tx, shell_markers_rx) = unbounded();
+        #[cfg(unix)]
+        let pty = shell_integration::ShellIntegrationPty::new(
+            pty,
+            term.clone(),
+            scrollback.clone(),
+            shell_markers_tx,
+        )?;
+        #[cfg(not(unix))]
+        drop(shell_markers_tx);
+
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
@@ -447,11 +495,28 @@ impl TerminalBuilder {
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
 
+        let ready_pattern = task
+            .as_ref()
+            .filter(|task| task.is_background)
+            .and_then(|task| task.ready_pattern.as_deref())
+            .and_then(|ready_pattern| Regex::new(ready_pattern).log_err());
+        let (task_ready_tx, task_ready_rx) = smol::channel::bounded(1);
+        // A background task with no pattern to wait for is ready as soon as it is started.
+        if task.as_ref().is_some_and(|task| task.is_background) && ready_pattern.is_none() {
+            task_ready_tx.close();
+        }
+        let task_output = task
+            .as_ref()
+            .and_then(|task| TaskOutputScanner::new(&task.problem_matchers, ready_pattern));
         let terminal = Terminal {
             task,
+            task_output,
             pty_tx: Notifier(pty_tx),
             completion_tx,
+            task_ready_tx,
+            task_ready_rx,
             term,
+            scrollback,
             term_config: config,
             title_override: terminal_title_override,
             events: VecDeque::with_capacity(10), //Should never get this high.
@@ -471,15 +536,29 @@ impl TerminalBuilder {
             word_regex: RegexSearch::new(WORD_REGEX).unwrap(),
             vi_mode_enabled: false,
             is_ssh_terminal,
+            command_blocks: VecDeque::new(),
+            shell_working_directory: None,
         };
 
         Ok(TerminalBuilder {
             terminal,
             events_rx,
+            shell_markers_rx,
         })
     }
 
     pub fn subscribe(mut self, cx: &ModelContext<Terminal>) -> Terminal {
+        let mut shell_markers_rx = self.shell_markers_rx;
+        cx.spawn(|terminal, mut cx| async move {
+            while let Some((marker, point)) = shell_markers_rx.next().await {
+                terminal.update(&mut cx, |terminal, cx| {
+                    terminal.process_shell_marker(marker, point, cx);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+
         //Event loop
         cx.spawn(|terminal, mut cx| async move {
             while let Some(event) = self.events_rx.next().await {
@@ -563,6 +642,9 @@ pub struct TerminalContent {
     pub cells: Vec<IndexedCell>,
     pub mode: TermMode,
     pub display_offset: usize,
+    pub history_size: usize,
+    /// How many lines were dropped from the top of the full scrollback, see [`ScrollbackPoint`].
+    pub dropped_lines: usize,
     pub selection_text: Option<String>,
     pub selection: Option<SelectionRange>,
     pub cursor: RenderableCursor,
@@ -584,6 +666,8 @@ impl Default for TerminalContent {
             cells: Default::default(),
             mode: Default::default(),
             display_offset: Default::default(),
+            history_size: Default::default(),
+            dropped_lines: Default::default(),
             selection_text: Default::default(),
             selection: Default::default(),
             cursor: RenderableCursor {
@@ -606,7 +690,12 @@ pub enum SelectionPhase {
 pub struct Terminal {
     pty_tx: Notifier,
     completion_tx: Sender<()>,
+    /// Closed once the task is ready for the tasks that depend on it.
+    task_ready_tx: Sender<()>,
+    task_ready_rx: Receiver<()>,
     term: Arc<FairMutex<Term<ZedListener>>>,
+    /// Shared with the PTY reader, which records the shell integration markers.
+    scrollback: Arc<Mutex<ScrollbackTracker>>,
     term_config: Config,
     events: VecDeque<InternalEvent>,
     /// This is only used for mouse mode cell change detection
@@ -627,8 +716,35 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    task_output: Option<TaskOutputScanner>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    command_blocks: VecDeque<CommandBlock>,
+    /// The working directory reported by the shell integration, if the shell has it.
+    shell_working_directory: Option<PathBuf>,
+}
+
+/// A shell command, as delimited by the shell integration markers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// Where the shell started to print the prompt.
+    pub prompt_start: ScrollbackPoint,
+    /// Where the command output starts, once the command was submitted.
+    pub output_start: Option<ScrollbackPoint>,
+    /// Where the command output ends, once the command finished.
+    pub output_end: Option<ScrollbackPoint>,
+    /// The exit code, if the command finished and the shell reported one.
+    pub exit_code: Option<i32>,
+}
+
+impl CommandBlock {
+    pub fn is_finished(&self) -> bool {
+        self.output_end.is_some()
+    }
+
+    pub fn failed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+    }
 }
 
 pub struct TaskState {
@@ -639,6 +755,12 @@ pub struct TaskState {
     pub status: TaskStatus,
     pub completion_rx: Receiver<()>,
     pub hide: HideStrategy,
+    /// The problem matchers to look for problems in the task's output with.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Whether the task keeps running in the background, being ready before it finishes.
+    pub is_background: bool,
+    /// The regex that marks a background task as ready, once its output matches it.
+    pub ready_pattern: Option<String>,
 }
 
 /// A status of the current terminal tab's task.
@@ -708,6 +830,7 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.scan_task_output(false, cx);
 
                 if self.pty_info.has_changed() {
                     cx.emit(Event::TitleChanged);
@@ -733,6 +856,62 @@ impl Terminal {
         }
     }
 
+    fn process_shell_marker(
+        &mut self,
+        marker: ShellMarker,
+        point: ScrollbackPoint,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match marker {
+            ShellMarker::PromptStart => {
+                // A command interrupted before it could report its status still ends here.
+                if let Some(block) = self.command_blocks.back_mut() {
+                    if block.output_end.is_none() {
+                        block.output_end = Some(point);
+                    }
+                }
+                // Clearing the screen moves the prompt back up, the blocks below are gone.
+                while self
+                    .command_blocks
+                    .back()
+                    .is_some_and(|block| block.prompt_start >= point)
+                {
+                    self.command_blocks.pop_back();
+                }
+                if self.command_blocks.len() == MAX_COMMAND_BLOCKS {
+                    self.command_blocks.pop_front();
+                }
+                self.command_blocks.push_back(CommandBlock {
+                    prompt_start: point,
+                    output_start: None,
+                    output_end: None,
+                    exit_code: None,
+                });
+            }
+            ShellMarker::CommandStart => {}
+            ShellMarker::CommandExecuted => {
+                if let Some(block) = self.command_blocks.back_mut() {
+                    block.output_start = Some(point);
+                }
+            }
+            ShellMarker::CommandFinished { exit_code } => {
+                if let Some(block) = self.command_blocks.back_mut() {
+                    if block.output_start.is_some() {
+                        block.output_end = Some(point);
+                        block.exit_code = exit_code;
+                    }
+                }
+                cx.notify();
+            }
+            ShellMarker::WorkingDirectory(path) => {
+                if self.shell_working_directory.as_ref() != Some(&path) {
+                    self.shell_working_directory = Some(path);
+                    cx.emit(Event::TitleChanged);
+                }
+            }
+        }
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -878,6 +1057,39 @@ impl Terminal {
             InternalEvent::ViMotion(motion) => {
                 term.vi_motion(*motion);
             }
+            InternalEvent::ScrollToPrompt(direction) => {
+                let dropped_lines = self.scrollback.lock().update(term);
+                let history_size = dropped_lines + term.grid().history_size();
+                let viewport_top = history_size - term.grid().display_offset();
+                let prompt_line = match direction {
+                    AlacDirection::Left => self
+                        .command_blocks
+                        .iter()
+                        .rev()
+                        .map(|block| block.prompt_start.line)
+                        .find(|line| *line < viewport_top),
+                    AlacDirection::Right => self
+                        .command_blocks
+                        .iter()
+                        .map(|block| block.prompt_start.line)
+                        .find(|line| *line > viewport_top),
+                };
+                if let Some(prompt_line) = prompt_line {
+                    let display_offset = history_size.saturating_sub(prompt_line);
+                    let delta = display_offset as i32 - term.grid().display_offset() as i32;
+                    term.scroll_display(AlacScroll::Delta(delta));
+                    self.refresh_hovered_word();
+                }
+            }
+            InternalEvent::SelectLastCommandOutput => {
+                if let Some(range) = self.last_command_output_range(term) {
+                    let selection = make_selection(&range);
+                    term.selection = Some(selection);
+                    self.selection_head = Some(*range.end());
+                    term.scroll_to_point(*range.start());
+                    cx.emit(Event::SelectionsChanged)
+                }
+            }
             InternalEvent::FindHyperlink(position, open) => {
                 let prev_hovered_word = self.last_content.last_hovered_word.take();
 
@@ -918,6 +1130,13 @@ impl Terminal {
                     let url_match = min_index..=max_index;
 
                     Some((url, true, url_match))
+                } else if let Some((location, location_match)) =
+                    self.task_output.as_ref().and_then(|task_output| {
+                        let dropped_lines = self.scrollback.lock().update(term);
+                        task_output.link_at(term, dropped_lines, point)
+                    })
+                {
+                    Some((location, false, location_match))
                 } else if let Some(url_match) = regex_match_at(term, point, &mut self.url_regex) {
                     let url = term.bounds_to_string(*url_match.start(), *url_match.end());
                     Some((url, true, url_match))
@@ -1070,6 +1289,62 @@ impl Terminal {
         }
     }
 
+    /// Commands run in this terminal, known from the shell integration.
+    pub fn command_blocks(&self) -> impl Iterator<Item = &CommandBlock> {
+        self.command_blocks.iter()
+    }
+
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Left));
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Right));
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        self.events
+            .push_back(InternalEvent::SelectLastCommandOutput);
+    }
+
+    /// The text printed by the last finished command, if the shell integration reported one.
+    pub fn last_command_output(&self) -> Option<String> {
+        let term = self.term.lock();
+        let range = self.last_command_output_range(&term)?;
+        Some(term.bounds_to_string(*range.start(), *range.end()))
+    }
+
+    fn last_command_output_range(
+        &self,
+        term: &Term<ZedListener>,
+    ) -> Option<RangeInclusive<AlacPoint>> {
+        let block = self
+            .command_blocks
+            .iter()
+            .rev()
+            .find(|block| block.is_finished())?;
+        let (start, end) = (block.output_start?, block.output_end?);
+        let dropped_lines = self.scrollback.lock().update(term);
+        // The output ends right before the position the command finished at.
+        let end = match end.column {
+            0 if end.line > start.line => ScrollbackPoint {
+                line: end.line - 1,
+                column: term.columns().saturating_sub(1),
+            },
+            0 => return None,
+            column => ScrollbackPoint {
+                line: end.line,
+                column: column - 1,
+            },
+        };
+        if end < start {
+            return None;
+        }
+        Some(start.to_alac_point(term, dropped_lines)?..=end.to_alac_point(term, dropped_lines)?)
+    }
+
     pub fn select_all(&mut self) {
         let term = self.term.lock();
         let start = AlacPoint::new(term.topmost_line(), Column(0));
@@ -1303,10 +1578,25 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        let dropped_lines = self.scrollback.lock().update(&terminal);
+        // Forget the commands whose output was dropped from the scrollback entirely.
+        while self
+            .command_blocks
+            .front()
+            .and_then(|block| block.output_end)
+            .is_some_and(|output_end| output_end.line < dropped_lines)
+        {
+            self.command_blocks.pop_front();
+        }
+
+        self.last_content = Self::make_content(&terminal, dropped_lines, &self.last_content);
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        dropped_lines: usize,
+        last_content: &TerminalContent,
+    ) -> TerminalContent {
         let content = term.renderable_content();
         TerminalContent {
             cells: content
@@ -1325,6 +1615,8 @@ impl Terminal {
                 .collect::<Vec<IndexedCell>>(),
             mode: content.mode,
             display_offset: content.display_offset,
+            history_size: term.grid().history_size(),
+            dropped_lines,
             selection_text: term.selection_to_string(),
             selection: content.selection,
             cursor: content.cursor,
@@ -1653,13 +1945,18 @@ impl Terminal {
     /// That means it returns the working directory of the local shell or program
     /// that's running inside the terminal.
     ///
+    /// Shells with Zed's shell integration report their directory themselves,
+    /// otherwise it is looked up from the process.
+    ///
     /// This does *not* return the working directory of the shell that runs on the
     /// remote host, in case Zed is connected to a remote host.
     fn client_side_working_directory(&self) -> Option<PathBuf> {
-        self.pty_info
-            .current
-            .as_ref()
-            .map(|process| process.cwd.clone())
+        self.shell_working_directory.clone().or_else(|| {
+            self.pty_info
+                .current
+                .as_ref()
+                .map(|process| process.cwd.clone())
+        })
     }
 
     pub fn title(&self, truncate: bool) -> String {
@@ -1681,8 +1978,10 @@ impl Terminal {
                         .current
                         .as_ref()
                         .map(|fpi| {
-                            let process_file = fpi
-                                .cwd
+                            let process_file = self
+                                .shell_working_directory
+                                .as_ref()
+                                .unwrap_or(&fpi.cwd)
                                 .file_name()
                                 .map(|name| name.to_string_lossy().to_string())
                                 .unwrap_or_default();
@@ -1716,6 +2015,19 @@ impl Terminal {
         self.secondary_pressed && self.hovered_word
     }
 
+    /// The last `max_lines` lines of the terminal output, to be shown again when the terminal
+    /// is restored. Leaves out the prompt the shell waits at, as the new shell prints its own.
+    pub fn scrollback_text(&self, max_lines: usize) -> Option<String> {
+        let term = self.term.lock();
+        let dropped_lines = self.scrollback.lock().update(&term);
+        let waiting_prompt = self
+            .command_blocks
+            .back()
+            .filter(|block| block.output_start.is_none())
+            .and_then(|block| block.prompt_start.to_alac_point(&*term, dropped_lines));
+        scrollback_text(&term, waiting_prompt, max_lines)
+    }
+
     pub fn task(&self) -> Option<&TaskState> {
         self.task.as_ref()
     }
@@ -1732,12 +2044,54 @@ impl Terminal {
         Task::ready(())
     }
 
+    /// Resolves once the task is ready for the tasks that depend on it: once it finished or,
+    /// for a background task, once its output matched its ready pattern.
+    pub fn wait_for_ready_task(&self, cx: &AppContext) -> Task<()> {
+        match self.task() {
+            Some(task) if task.status == TaskStatus::Running => {
+                let task_ready_rx = self.task_ready_rx.clone();
+                cx.background_executor().spawn(async move {
+                    task_ready_rx.recv().await.ok();
+                })
+            }
+            _ => Task::ready(()),
+        }
+    }
+
+    /// Kills the process of the task running in the terminal, if any.
+    pub fn kill_active_task(&mut self) {
+        if self
+            .task()
+            .is_some_and(|task| task.status == TaskStatus::Running)
+        {
+            self.pty_info.kill_current_process();
+        }
+    }
+
+    fn scan_task_output(&mut self, finished: bool, cx: &mut ModelContext<Self>) {
+        if let Some(task_output) = &mut self.task_output {
+            let term = self.term.lock();
+            let dropped_lines = self.scrollback.lock().update(&term);
+            let problems = task_output.scan(&term, dropped_lines, finished);
+            drop(term);
+            if task_output.is_ready() {
+                self.task_ready_tx.close();
+            }
+            if !problems.is_empty() {
+                cx.emit(Event::TaskProblems(problems));
+            }
+        }
+    }
+
     fn register_task_finished(
         &mut self,
         error_code: Option<i32>,
         cx: &mut ModelContext<'_, Terminal>,
     ) {
         self.completion_tx.try_send(()).ok();
+        self.task_ready_tx.close();
+        // Scanned before the task summary is appended to the output.
+        self.scan_task_output(true, cx);
         let task = match &mut self.task {
             Some(task) => task,
             None => {
@@ -1835,6 +2189,40 @@ unsafe fn append_text_to_term(term: &mut Term<ZedListener>, text_lines: &[&str])
     }
 }
 
+/// The last `max_lines` lines of the output, up to the prompt the shell waits at, if any.
+fn scrollback_text<T>(
+    term: &Term<T>,
+    waiting_prompt: Option<AlacPoint>,
+    max_lines: usize,
+) -> Option<String> {
+    // Full screen applications do not leave anything meaningful to restore.
+    if max_lines == 0 || term.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    let topmost = AlacPoint::new(term.topmost_line(), Column(0));
+    let end = match waiting_prompt {
+        Some(prompt_start) if prompt_start <= topmost => return None,
+        Some(prompt_start) => prompt_start.sub(term, Boundary::Grid, 1),
+        None => AlacPoint::new(term.bottommost_line(), term.last_column()),
+    };
+    let start_line = Line(end.line.0 - (max_lines as i32 - 1)).max(topmost.line);
+
+    let text = term.bounds_to_string(AlacPoint::new(start_line, Column(0)), end);
+    let text = text.trim_start_matches('\n').trim_end();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Writes the output saved by a previous session into the terminal, dimmed to set it apart
+/// from the output of the new process.
+fn write_restored_scrollback<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    let mut processor: Processor = Processor::new();
+    let text = format!("\x1b[2m{}\x1b[0m\r\n", scrollback.replace('\n', "\r\n"));
+    for byte in text.bytes() {
+        processor.advance(term, byte);
+    }
+}
+
 impl Drop for Terminal {
     fn drop(&mut self) {
         self.pty_tx.0.send(Msg::Shutdown).ok();
@@ -1977,14 +2365,21 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{
+            cell::{Cell, Flags},
+            Config,
+        },
+        vte::ansi::Processor,
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, scrollback_text, write_restored_scrollback,
+        IndexedCell, TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -2162,4 +2557,37 @@ mod tests {
             vec!["Main.cs:20:5:Error", "desc"],
         );
     }
+
+    #[test]
+    fn test_scrollback_round_trip() {
+        let new_term = || Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut term = new_term();
+        let mut processor: Processor = Processor::new();
+        for byte in b"$ ls\r\n\x1b[31mCargo.toml\x1b[0m  src\r\n$ echo done\r\ndone\r\n$ " {
+            processor.advance(&mut term, *byte);
+        }
+        let waiting_prompt = Some(AlacPoint::new(Line(4), Column(0)));
+        let text = scrollback_text(&term, waiting_prompt, 100).unwrap();
+        assert_eq!(text, "$ ls\nCargo.toml  src\n$ echo done\ndone");
+        assert_eq!(
+            scrollback_text(&term, waiting_prompt, 2).as_deref(),
+            Some("$ echo done\ndone")
+        );
+        assert_eq!(scrollback_text(&term, waiting_prompt, 0), None);
+
+        let mut restored = new_term();
+        write_restored_scrollback(&mut restored, &text);
+        assert_eq!(
+            scrollback_text(&restored, None, 100).as_deref(),
+            Some(text.as_str())
+        );
+        assert!(restored.grid()[Line(0)][Column(0)]
+            .flags
+            .contains(Flags::DIM));
+        // The output of the new shell starts on the line after the restored one.
+        assert_eq!(
+            restored.grid().cursor.point,
+            AlacPoint::new(Line(4), Column(0))
+        );
+    }
 }
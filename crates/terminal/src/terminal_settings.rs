@@ -47,6 +47,8 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub shell_integration: bool,
+    pub restored_scrollback_lines: usize,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -187,6 +189,19 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish.
+    /// It reports where prompts and commands start and end, their exit
+    /// status and the shell's working directory.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// The maximum number of lines of each terminal's output to save when
+    /// Zed quits and show again, above a new prompt, when it restarts.
+    /// 0 disables restoring the terminal output.
+    ///
+    /// Default: 1000
+    pub restored_scrollback_lines: Option<usize>,
 }
 
 impl settings::Settings for TerminalSettings {
@@ -1,8 +1,254 @@
 use anyhow::Result;
+use async_recursion::async_recursion;
+use futures::future::join_all;
+use gpui::{AppContext, AsyncWindowContext, Axis, Entity, Model, Pixels, View, WeakView};
+use project::{terminals::RestoredTask, Project};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use util::ResultExt as _;
 
 use db::{define_connection, query, sqlez::statement::Statement, sqlez_macros::sql};
-use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+use terminal::{TaskState, TaskStatus};
+use workspace::{
+    item::SerializableItem, ItemId, Member, Pane, PaneAxis, PaneGroup, Workspace, WorkspaceDb,
+    WorkspaceId,
+};
+
+use crate::{
+    terminal_panel::{new_terminal_pane, TerminalPanel},
+    TerminalView,
+};
+
+/// The layout of the terminal panel, stored in the key-value store.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SerializedTerminalPanel {
+    pub items: SerializedItems,
+    /// The active item of the single pane the panel had before it could be split.
+    pub active_item_id: Option<u64>,
+    pub width: Option<Pixels>,
+    pub height: Option<Pixels>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum SerializedItems {
+    /// The items of the single pane the panel had before it could be split.
+    NoSplits(Vec<u64>),
+    WithSplits(SerializedPaneGroup),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) enum SerializedPaneGroup {
+    Pane(SerializedPane),
+    Group {
+        axis: SerializedAxis,
+        flexes: Option<Vec<f32>>,
+        children: Vec<SerializedPaneGroup>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SerializedPane {
+    active: bool,
+    items: Vec<u64>,
+    active_item_id: Option<u64>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(crate) enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<Axis> for SerializedAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<SerializedAxis> for Axis {
+    fn from(axis: SerializedAxis) -> Self {
+        match axis {
+            SerializedAxis::Horizontal => Self::Horizontal,
+            SerializedAxis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+pub(crate) fn serialize_pane_group(
+    pane_group: &PaneGroup,
+    active_pane: &View<Pane>,
+    cx: &AppContext,
+) -> SerializedPaneGroup {
+    serialize_member(&pane_group.root, active_pane, cx)
+}
+
+fn serialize_member(
+    member: &Member,
+    active_pane: &View<Pane>,
+    cx: &AppContext,
+) -> SerializedPaneGroup {
+    match member {
+        Member::Pane(pane) => {
+            SerializedPaneGroup::Pane(serialize_pane(pane, pane == active_pane, cx))
+        }
+        Member::Axis(pane_axis) => SerializedPaneGroup::Group {
+            axis: pane_axis.axis.into(),
+            flexes: Some(pane_axis.flexes.lock().clone()),
+            children: pane_axis
+                .members
+                .iter()
+                .map(|member| serialize_member(member, active_pane, cx))
+                .collect(),
+        },
+    }
+}
+
+fn serialize_pane(pane: &View<Pane>, active: bool, cx: &AppContext) -> SerializedPane {
+    let pane = pane.read(cx);
+    let items = pane
+        .items()
+        .filter(|item| item.act_as::<TerminalView>(cx).is_some())
+        .map(|item| item.item_id().as_u64())
+        .collect::<Vec<_>>();
+    let active_item_id = pane
+        .active_item()
+        .map(|item| item.item_id().as_u64())
+        .filter(|active_id| items.contains(active_id));
+    SerializedPane {
+        active,
+        items,
+        active_item_id,
+    }
+}
+
+/// Recreates the panes of a serialized group, with their terminals. Returns the group,
+/// the pane that was active in it and the ids of the terminals restored.
+#[async_recursion(?Send)]
+pub(crate) async fn deserialize_pane_group(
+    terminal_panel: WeakView<TerminalPanel>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    workspace_id: WorkspaceId,
+    serialized_pane_group: &SerializedPaneGroup,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Member, Option<View<Pane>>, Vec<ItemId>)> {
+    match serialized_pane_group {
+        SerializedPaneGroup::Group {
+            axis,
+            flexes,
+            children,
+        } => {
+            let mut members = Vec::new();
+            let mut active_pane = None;
+            let mut item_ids = Vec::new();
+            for child in children {
+                if let Some((member, child_active_pane, child_item_ids)) = deserialize_pane_group(
+                    terminal_panel.clone(),
+                    workspace.clone(),
+                    project.clone(),
+                    workspace_id,
+                    child,
+                    cx,
+                )
+                .await
+                {
+                    members.push(member);
+                    active_pane = active_pane.or(child_active_pane);
+                    item_ids.extend(child_item_ids);
+                }
+            }
+
+            match members.len() {
+                0 => None,
+                1 => Some((members.remove(0), active_pane, item_ids)),
+                len => {
+                    // Children that failed to load leave the stored flexes unusable.
+                    let flexes = flexes.clone().filter(|flexes| flexes.len() == len);
+                    let axis = PaneAxis::load((*axis).into(), members, flexes);
+                    Some((Member::Axis(axis), active_pane, item_ids))
+                }
+            }
+        }
+        SerializedPaneGroup::Pane(serialized_pane) => {
+            let pane = terminal_panel
+                .update(cx, |terminal_panel, cx| {
+                    let pane = new_terminal_pane(workspace.clone(), project.clone(), cx);
+                    terminal_panel.subscribe_to_pane(&pane, cx);
+                    terminal_panel.apply_tab_bar_buttons(&pane, cx);
+                    pane
+                })
+                .log_err()?;
+            let item_ids = deserialize_terminals(
+                &pane,
+                &serialized_pane.items,
+                serialized_pane.active_item_id,
+                workspace,
+                project,
+                workspace_id,
+                cx,
+            )
+            .await
+            .log_err()?;
+            if item_ids.is_empty() {
+                return None;
+            }
+            let active_pane = serialized_pane.active.then(|| pane.clone());
+            Some((Member::Pane(pane), active_pane, item_ids))
+        }
+    }
+}
+
+/// Adds the serialized terminals to the pane and returns the ids of those restored.
+pub(crate) async fn deserialize_terminals(
+    pane: &View<Pane>,
+    item_ids: &[u64],
+    active_item_id: Option<u64>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    workspace_id: WorkspaceId,
+    cx: &mut AsyncWindowContext,
+) -> Result<Vec<ItemId>> {
+    let items = pane.update(cx, |_, cx| {
+        item_ids
+            .iter()
+            .map(|item_id| {
+                TerminalView::deserialize(
+                    project.clone(),
+                    workspace.clone(),
+                    workspace_id,
+                    *item_id,
+                    cx,
+                )
+            })
+            .collect::<Vec<_>>()
+    })?;
+    let items = join_all(items).await;
+
+    let mut alive_item_ids = Vec::new();
+    pane.update(cx, |pane, cx| {
+        let mut active_ix = None;
+        for (serialized_id, item) in item_ids.iter().zip(items) {
+            if let Some(item) = item.log_err() {
+                let item_id = item.entity_id().as_u64();
+                pane.add_item(Box::new(item), false, false, None, cx);
+                alive_item_ids.push(item_id as ItemId);
+                // The restored views get new ids, the active one is recorded by its old id.
+                if Some(*serialized_id) == active_item_id {
+                    active_ix = Some(pane.items_len() - 1);
+                }
+            }
+        }
+
+        if let Some(active_ix) = active_ix {
+            pane.activate_item(active_ix, false, false, cx)
+        }
+    })?;
+    Ok(alive_item_ids)
+}
 
 define_connection! {
     pub static ref TERMINAL_DB: TerminalDb<WorkspaceDb> =
@@ -35,9 +281,56 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        // Keep the terminal output and, for task terminals, the task,
+        // to show them again after a restart.
+        sql!(
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN task TEXT;
         )];
 }
 
+/// The task a terminal ran, stored as JSON next to the terminal's output.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SerializedTask {
+    id: String,
+    full_label: String,
+    label: String,
+    command_label: String,
+    /// Whether the task succeeded, `None` if it did not report its exit status.
+    success: Option<bool>,
+}
+
+impl From<&TaskState> for SerializedTask {
+    fn from(task: &TaskState) -> Self {
+        Self {
+            id: task.id.0.clone(),
+            full_label: task.full_label.clone(),
+            label: task.label.clone(),
+            command_label: task.command_label.clone(),
+            success: match task.status {
+                TaskStatus::Completed { success } => Some(success),
+                TaskStatus::Running | TaskStatus::Unknown => None,
+            },
+        }
+    }
+}
+
+impl From<SerializedTask> for RestoredTask {
+    fn from(task: SerializedTask) -> Self {
+        Self {
+            id: task::TaskId(task.id),
+            full_label: task.full_label,
+            label: task.label,
+            command_label: task.command_label,
+            status: match task.success {
+                Some(success) => TaskStatus::Completed { success },
+                None => TaskStatus::Unknown,
+            },
+        }
+    }
+}
+
 impl TerminalDb {
     query! {
        pub async fn update_workspace_id(
@@ -52,19 +345,21 @@ impl TerminalDb {
     }
 
     query! {
-        pub async fn save_working_directory(
+        pub async fn save_terminal(
             item_id: ItemId,
             workspace_id: WorkspaceId,
-            working_directory: PathBuf
+            working_directory: Option<PathBuf>,
+            scrollback: Option<String>,
+            task: Option<String>
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory, scrollback, task)
+            VALUES (?, ?, ?, ?, ?)
         }
     }
 
     query! {
-        pub fn get_working_directory(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
-            SELECT working_directory
+        pub fn get_terminal(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<PathBuf>, Option<String>, Option<String>)>> {
+            SELECT working_directory, scrollback, task
             FROM terminals
             WHERE item_id = ? AND workspace_id = ?
         }
@@ -96,3 +391,61 @@ impl TerminalDb {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_get_terminal() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let task = SerializedTask {
+            id: "cargo test".to_string(),
+            full_label: "cargo test --workspace".to_string(),
+            label: "cargo test".to_string(),
+            command_label: "cargo test --workspace".to_string(),
+            success: Some(false),
+        };
+        TERMINAL_DB
+            .save_terminal(
+                1234,
+                workspace_id,
+                Some(PathBuf::from("/tmp/project")),
+                Some("$ cargo test\nerror: 1 test failed".to_string()),
+                Some(serde_json::to_string(&task).unwrap()),
+            )
+            .await
+            .unwrap();
+
+        let (working_directory, scrollback, task) = TERMINAL_DB
+            .get_terminal(1234, workspace_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(working_directory, Some(PathBuf::from("/tmp/project")));
+        assert_eq!(
+            scrollback.as_deref(),
+            Some("$ cargo test\nerror: 1 test failed")
+        );
+        let restored_task =
+            RestoredTask::from(serde_json::from_str::<SerializedTask>(&task.unwrap()).unwrap());
+        assert_eq!(restored_task.id, task::TaskId("cargo test".to_string()));
+        assert_eq!(restored_task.full_label, "cargo test --workspace");
+        assert_eq!(restored_task.label, "cargo test");
+        assert_eq!(restored_task.command_label, "cargo test --workspace");
+        assert_eq!(
+            restored_task.status,
+            TaskStatus::Completed { success: false }
+        );
+
+        // Saving again replaces the output and the task.
+        TERMINAL_DB
+            .save_terminal(1234, workspace_id, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            TERMINAL_DB.get_terminal(1234, workspace_id).unwrap(),
+            Some((None, None, None))
+        );
+    }
+}
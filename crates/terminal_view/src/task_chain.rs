@@ -0,0 +1,266 @@
+use std::{
+    cell::RefCell,
+    mem,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use async_recursion::async_recursion;
+use collections::HashMap;
+use futures::{
+    future::{try_join_all, LocalBoxFuture, Shared},
+    FutureExt as _, TryFutureExt as _,
+};
+use gpui::{AsyncWindowContext, Model, Task, WeakModel, WeakView};
+use project::{TaskChain, TaskChainStep, TaskChainStepStatus};
+use task::{DependsOrder, SpawnInTerminal, TaskId};
+use terminal::{TaskStatus, Terminal};
+
+use crate::terminal_panel::TerminalPanel;
+
+/// A task of a chain along with the tasks it depends on.
+pub(crate) struct ChainedTask {
+    /// Index of the task's step in the [`TaskChain`].
+    step_ix: usize,
+    task: SpawnInTerminal,
+    dependencies: Vec<ChainedTask>,
+}
+
+impl ChainedTask {
+    /// Flattens the task and its dependencies into the chain steps given, dependencies first.
+    /// Tasks with the same id share a step, so that a task several others depend on runs once.
+    pub(crate) fn new(task: SpawnInTerminal, steps: &mut Vec<TaskChainStep>) -> Self {
+        Self::flatten(task, steps, &mut HashMap::default())
+    }
+
+    fn flatten(
+        mut task: SpawnInTerminal,
+        steps: &mut Vec<TaskChainStep>,
+        step_ixs: &mut HashMap<TaskId, usize>,
+    ) -> Self {
+        let dependencies = mem::take(&mut task.depends_on)
+            .into_iter()
+            .map(|dependency| Self::flatten(dependency, steps, step_ixs))
+            .collect();
+        let step_ix = *step_ixs.entry(task.id.clone()).or_insert_with(|| {
+            steps.push(TaskChainStep {
+                label: task.label.clone(),
+                status: TaskChainStepStatus::Pending,
+            });
+            steps.len() - 1
+        });
+        Self {
+            step_ix,
+            task,
+            dependencies,
+        }
+    }
+}
+
+/// A task chain being run by the terminal panel.
+pub(crate) struct RunningTaskChain {
+    pub progress: TaskChain,
+    /// Terminals spawned for the tasks of the chain, killed when the chain gets cancelled.
+    pub terminals: Vec<WeakModel<Terminal>>,
+    pub _run: Task<()>,
+}
+
+/// The runs of the steps of a chain started so far, awaited by every task depending on them.
+type StepRuns =
+    RefCell<HashMap<usize, Shared<LocalBoxFuture<'static, Result<(), Arc<anyhow::Error>>>>>>;
+
+/// Runs the tasks the task given depends on, then the task itself, and waits for it to be ready.
+pub(crate) async fn run_chained_task(
+    chained_task: ChainedTask,
+    chain_id: TaskId,
+    terminal_panel: WeakView<TerminalPanel>,
+    cx: AsyncWindowContext,
+) -> Result<()> {
+    // The runs only reference the map weakly, for them to be dropped when the chain gets cancelled.
+    let step_runs = Rc::new(StepRuns::default());
+    run_step(
+        chained_task,
+        chain_id,
+        terminal_panel,
+        Rc::downgrade(&step_runs),
+        cx,
+    )
+    .await
+}
+
+/// Runs the step of the task given, or waits for the run of the step started by another task
+/// of the chain depending on it.
+async fn run_step(
+    chained_task: ChainedTask,
+    chain_id: TaskId,
+    terminal_panel: WeakView<TerminalPanel>,
+    step_runs: Weak<StepRuns>,
+    cx: AsyncWindowContext,
+) -> Result<()> {
+    let run = {
+        let step_ix = chained_task.step_ix;
+        let all_runs = step_runs.upgrade().context("task chain was dropped")?;
+        let started_run = all_runs.borrow().get(&step_ix).cloned();
+        match started_run {
+            Some(run) => run,
+            None => {
+                let run = run_task(chained_task, chain_id, terminal_panel, step_runs, cx)
+                    .map_err(Arc::new)
+                    .boxed_local()
+                    .shared();
+                all_runs.borrow_mut().insert(step_ix, run.clone());
+                run
+            }
+        }
+    };
+    run.await.map_err(|error| anyhow!("{error:#}"))
+}
+
+/// Runs the task's dependencies, then the task itself.
+#[async_recursion(?Send)]
+async fn run_task(
+    chained_task: ChainedTask,
+    chain_id: TaskId,
+    terminal_panel: WeakView<TerminalPanel>,
+    step_runs: Weak<StepRuns>,
+    mut cx: AsyncWindowContext,
+) -> Result<()> {
+    let ChainedTask {
+        step_ix,
+        task,
+        dependencies,
+    } = chained_task;
+    match task.depends_order {
+        DependsOrder::Sequence => {
+            for dependency in dependencies {
+                run_step(
+                    dependency,
+                    chain_id.clone(),
+                    terminal_panel.clone(),
+                    step_runs.clone(),
+                    cx.clone(),
+                )
+                .await?;
+            }
+        }
+        DependsOrder::Parallel => {
+            try_join_all(dependencies.into_iter().map(|dependency| {
+                run_step(
+                    dependency,
+                    chain_id.clone(),
+                    terminal_panel.clone(),
+                    step_runs.clone(),
+                    cx.clone(),
+                )
+            }))
+            .await?;
+        }
+    }
+
+    if !task.has_command() {
+        terminal_panel.update(&mut cx, |terminal_panel, cx| {
+            terminal_panel.set_task_chain_step_status(
+                &chain_id,
+                step_ix,
+                TaskChainStepStatus::Succeeded,
+                cx,
+            )
+        })?;
+        return Ok(());
+    }
+
+    let label = task.label.clone();
+    let is_background = task.is_background;
+    let terminal: Model<Terminal> = terminal_panel
+        .update(&mut cx, |terminal_panel, cx| {
+            terminal_panel.set_task_chain_step_status(
+                &chain_id,
+                step_ix,
+                TaskChainStepStatus::Running,
+                cx,
+            );
+            terminal_panel.spawn_task(&task, cx)
+        })?
+        .await?;
+    let task_ready = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+        terminal_panel.add_task_chain_terminal(&chain_id, &terminal);
+        terminal.read(cx).wait_for_ready_task(cx)
+    })?;
+    // Not to keep the terminal alive when its tab gets closed while waiting.
+    let terminal = terminal.downgrade();
+    task_ready.await;
+
+    let task_status = terminal
+        .update(&mut cx, |terminal, _| {
+            terminal.task().map(|task| task.status)
+        })
+        .with_context(|| format!("terminal of task `{label}` was closed"))?;
+    let step_status = match task_status {
+        Some(TaskStatus::Running) if is_background => TaskChainStepStatus::Ready,
+        Some(TaskStatus::Completed { success: true }) => TaskChainStepStatus::Succeeded,
+        _ => TaskChainStepStatus::Failed,
+    };
+    terminal_panel.update(&mut cx, |terminal_panel, cx| {
+        terminal_panel.set_task_chain_step_status(&chain_id, step_ix, step_status, cx)
+    })?;
+    anyhow::ensure!(
+        step_status != TaskChainStepStatus::Failed,
+        "task `{label}` failed"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, depends_on: Vec<SpawnInTerminal>) -> SpawnInTerminal {
+        SpawnInTerminal {
+            id: TaskId(id.to_string()),
+            full_label: id.to_string(),
+            label: id.to_string(),
+            command: format!("echo {id}"),
+            args: Vec::new(),
+            command_label: format!("echo {id}"),
+            cwd: None,
+            env: Default::default(),
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: Default::default(),
+            hide: Default::default(),
+            shell: Default::default(),
+            problem_matchers: Vec::new(),
+            depends_on,
+            depends_order: Default::default(),
+            is_background: false,
+            ready_pattern: None,
+        }
+    }
+
+    #[test]
+    fn test_shared_dependency_has_one_step() {
+        let server = task(
+            "server",
+            vec![
+                task("build", Vec::new()),
+                task("migrate", vec![task("build", Vec::new())]),
+            ],
+        );
+        let mut steps = Vec::new();
+        let chained_task = ChainedTask::new(server, &mut steps);
+
+        let labels = steps
+            .iter()
+            .map(|step| step.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["build", "migrate", "server"]);
+        assert_eq!(chained_task.step_ix, 2);
+        let [build, migrate] = &chained_task.dependencies[..] else {
+            panic!("expected two dependencies");
+        };
+        assert_eq!(build.step_ix, 0);
+        assert_eq!(migrate.step_ix, 1);
+        assert_eq!(migrate.dependencies[0].step_ix, build.step_ix);
+    }
+}
@@ -28,7 +28,11 @@ use ui::{ParentElement, Tooltip};
 use workspace::Workspace;
 
 use std::mem;
-use std::{fmt::Debug, ops::RangeInclusive, rc::Rc};
+use std::{
+    fmt::Debug,
+    ops::{Range, RangeInclusive},
+    rc::Rc,
+};
 
 use crate::{BlockContext, BlockProperties, TerminalView};
 
@@ -47,6 +51,9 @@ pub struct LayoutState {
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
+    /// Display lines of the commands that exited with a non-zero status.
+    failed_command_lines: Vec<Range<i32>>,
+    failed_command_color: Hsla,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -732,6 +739,8 @@ impl Element for TerminalElement {
                     cells,
                     mode,
                     display_offset,
+                    history_size,
+                    dropped_lines,
                     cursor_char,
                     selection,
                     cursor,
@@ -740,6 +749,29 @@ impl Element for TerminalElement {
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let failed_command_lines = {
+                    let first_visible_line = (dropped_lines + history_size - display_offset) as i32;
+                    let visible_lines = 0..dimensions.num_lines() as i32;
+                    self.terminal
+                        .read(cx)
+                        .command_blocks()
+                        .filter(|block| block.failed())
+                        .filter_map(|block| {
+                            let output_end = block.output_end?;
+                            // The command finished at the start of the line following its output.
+                            let end_line = if output_end.column == 0 {
+                                output_end.line
+                            } else {
+                                output_end.line + 1
+                            };
+                            let start = block.prompt_start.line as i32 - first_visible_line;
+                            let end = end_line as i32 - first_visible_line;
+                            let lines = start.max(visible_lines.start)..end.min(visible_lines.end);
+                            (!lines.is_empty()).then_some(lines)
+                        })
+                        .collect::<Vec<_>>()
+                };
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -857,6 +889,8 @@ impl Element for TerminalElement {
                     gutter,
                     last_hovered_word,
                     block_below_cursor_element,
+                    failed_command_lines,
+                    failed_command_color: theme.status().error,
                 }
             })
     }
@@ -919,6 +953,15 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    for lines in &layout.failed_command_lines {
+                        let line_height = layout.dimensions.line_height;
+                        let marker_bounds = Bounds::new(
+                            point(bounds.origin.x, origin.y + lines.start as f32 * line_height),
+                            size(px(2.), lines.len() as f32 * line_height),
+                        );
+                        cx.paint_quad(fill(marker_bounds, layout.failed_command_color));
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
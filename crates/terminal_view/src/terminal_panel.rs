@@ -1,18 +1,28 @@
 use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 
-use crate::{default_working_directory, TerminalView};
+use crate::{
+    default_working_directory,
+    persistence::{
+        deserialize_pane_group, deserialize_terminals, serialize_pane_group, SerializedItems,
+        SerializedTerminalPanel,
+    },
+    task_chain::{run_chained_task, ChainedTask, RunningTaskChain},
+    TerminalView,
+};
 use collections::{HashMap, HashSet};
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{channel::oneshot, future::join_all};
 use gpui::{
-    actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
-    Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, EntityId, EventEmitter,
+    ExternalPaths, FocusHandle, FocusableView, InteractiveElement, IntoElement, Model,
+    ParentElement, Pixels, Render, Styled, Subscription, Task, View, ViewContext, VisualContext,
+    WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{terminals::TerminalKind, Fs, ProjectEntryId};
+use project::{
+    terminals::TerminalKind, Fs, Inventory, Project, ProjectEntryId, TaskChain, TaskChainStepStatus,
+};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
-use serde::{Deserialize, Serialize};
 use settings::Settings;
 use task::{RevealStrategy, Shell, SpawnInTerminal, TaskId};
 use terminal::{
@@ -26,13 +36,14 @@ use ui::{
 use util::{ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
-    item::SerializableItem,
-    pane,
+    move_item, pane,
     ui::IconName,
-    DraggedTab, ItemId, NewTerminal, Pane, ToggleZoom, Workspace,
+    ActivateNextPane, ActivatePane, ActivatePaneInDirection, ActivatePreviousPane, DraggedTab,
+    Member, NewTerminal, Pane, PaneGroup, SplitDirection, SwapPaneInDirection, ToggleZoom,
+    Workspace,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Context as _, Result};
 use zed_actions::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
@@ -44,6 +55,18 @@ pub fn init(cx: &mut AppContext) {
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
             workspace.register_action(TerminalPanel::new_terminal);
             workspace.register_action(TerminalPanel::open_terminal);
+            workspace.register_action(|workspace, action: &tasks_ui::CancelChain, cx| {
+                let Some(terminal_panel) = workspace.panel::<TerminalPanel>(cx) else {
+                    return;
+                };
+                let action = action.clone();
+                // The task inventory is reached through the workspace, which is being updated now.
+                cx.window_context().defer(move |cx| {
+                    terminal_panel.update(cx, |terminal_panel, cx| {
+                        terminal_panel.cancel_task_chains(&action, cx)
+                    });
+                });
+            });
             workspace.register_action(|workspace, _: &ToggleFocus, cx| {
                 if workspace
                     .panel::<TerminalPanel>(cx)
@@ -59,15 +82,19 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    pub(crate) active_pane: View<Pane>,
+    pub(crate) center: PaneGroup,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    /// Subscriptions to the panes in `center`, dropped when a pane is removed.
+    pane_subscriptions: HashMap<EntityId, Vec<Subscription>>,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
+    task_chains: HashMap<TaskId, RunningTaskChain>,
     enabled: bool,
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
@@ -75,81 +102,13 @@ pub struct TerminalPanel {
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                NewTerminal.boxed_clone(),
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(None);
-            pane.set_should_display_tab_bar(|_| true);
-
-            let is_local = workspace.project().read(cx).is_local();
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if is_local {
-                    if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                        add_paths_to_terminal(pane, paths.paths(), cx);
-                    }
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let project = workspace.project().read(cx);
-        let enabled = project.supports_terminal(cx);
-        let this = Self {
-            pane,
+        let project = workspace.project();
+        let pane = new_terminal_pane(workspace.weak_handle(), project.clone(), cx);
+        let center = PaneGroup::new(pane.clone());
+        let enabled = project.read(cx).supports_terminal(cx);
+        let mut this = Self {
+            active_pane: pane.clone(),
+            center,
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
             pending_serialization: Task::ready(None),
@@ -157,20 +116,33 @@ impl TerminalPanel {
             height: None,
             pending_terminals_to_add: 0,
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            task_chains: HashMap::default(),
+            pane_subscriptions: HashMap::default(),
+            _subscriptions: Vec::new(),
             enabled,
             assistant_enabled: false,
             assistant_tab_bar_button: None,
         };
-        this.apply_tab_bar_buttons(cx);
+        this.subscribe_to_pane(&pane, cx);
+        this.apply_tab_bar_buttons(&pane, cx);
         this
     }
 
+    pub(crate) fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        self.pane_subscriptions.insert(
+            pane.entity_id(),
+            vec![
+                cx.subscribe(pane, Self::handle_pane_event),
+                cx.observe(pane, |_, _, cx| cx.notify()),
+            ],
+        );
+    }
+
     pub fn asssistant_enabled(&mut self, enabled: bool, cx: &mut ViewContext<Self>) {
         self.assistant_enabled = enabled;
         if enabled {
             let focus_handle = self
-                .pane
+                .active_pane
                 .read(cx)
                 .active_item()
                 .map(|item| item.focus_handle(cx))
@@ -182,12 +154,14 @@ impl TerminalPanel {
         } else {
             self.assistant_tab_bar_button = None;
         }
-        self.apply_tab_bar_buttons(cx);
+        for pane in self.center.panes() {
+            self.apply_tab_bar_buttons(pane, cx);
+        }
     }
 
-    fn apply_tab_bar_buttons(&self, cx: &mut ViewContext<Self>) {
+    pub(crate) fn apply_tab_bar_buttons(&self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
         let assistant_tab_bar_button = self.assistant_tab_bar_button.clone();
-        self.pane.update(cx, |pane, cx| {
+        pane.update(cx, |pane, cx| {
             pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
                 if !pane.has_focus(cx) && !pane.context_menu_focused(cx) {
                     return (None, None);
@@ -264,36 +238,9 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, project, database_id) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some((serialized_panel, database_id)) =
-                serialized_panel.as_ref().zip(workspace.database_id())
-            {
-                panel.update(cx, |panel, cx| {
-                    cx.notify();
-                    panel.height = serialized_panel.height.map(|h| h.round());
-                    panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    database_id,
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
-                })
-            } else {
-                Vec::new()
-            };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            (panel, workspace.project().clone(), workspace.database_id())
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -303,7 +250,14 @@ impl TerminalPanel {
                         &workspace,
                         |terminal_panel, _, e, cx| {
                             if let workspace::Event::SpawnTask(spawn_in_terminal) = e {
-                                terminal_panel.spawn_task(spawn_in_terminal, cx);
+                                if spawn_in_terminal.depends_on.is_empty() {
+                                    terminal_panel
+                                        .spawn_task(spawn_in_terminal, cx)
+                                        .detach_and_log_err(cx);
+                                } else {
+                                    terminal_panel
+                                        .spawn_task_chain(spawn_in_terminal.as_ref().clone(), cx);
+                                }
                             };
                         },
                     ))
@@ -311,29 +265,47 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
         let mut alive_item_ids = Vec::new();
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    alive_item_ids.push(item_id as ItemId);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        if let Some((serialized_panel, database_id)) = serialized_panel.zip(database_id) {
+            panel.update(&mut cx, |panel, cx| {
+                cx.notify();
+                panel.height = serialized_panel.height.map(|h| h.round());
+                panel.width = serialized_panel.width.map(|w| w.round());
+            })?;
+
+            match &serialized_panel.items {
+                SerializedItems::NoSplits(item_ids) => {
+                    let pane = panel.update(&mut cx, |panel, _| panel.active_pane.clone())?;
+                    alive_item_ids = deserialize_terminals(
+                        &pane,
+                        item_ids,
+                        serialized_panel.active_item_id,
+                        workspace.clone(),
+                        project,
+                        database_id,
+                        &mut cx,
+                    )
+                    .await?;
+                }
+                SerializedItems::WithSplits(serialized_pane_group) => {
+                    if let Some((center, active_pane, item_ids)) = deserialize_pane_group(
+                        panel.downgrade(),
+                        workspace.clone(),
+                        project,
+                        database_id,
+                        serialized_pane_group,
+                        &mut cx,
+                    )
+                    .await
+                    {
+                        alive_item_ids = item_ids;
+                        panel.update(&mut cx, |panel, cx| {
+                            panel.set_center(center, active_pane, cx)
+                        })?;
                     }
                 }
             }
-
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
-            }
-        })?;
+        }
 
         // Since panels/docks are loaded outside from the workspace, we cleanup here, instead of through the workspace.
         if let Some(workspace) = workspace.upgrade() {
@@ -350,30 +322,125 @@ impl TerminalPanel {
         Ok(panel)
     }
 
+    /// Replaces the panes of the panel with a restored group.
+    fn set_center(
+        &mut self,
+        center: Member,
+        active_pane: Option<View<Pane>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.center = PaneGroup::with_root(center);
+        self.active_pane = active_pane.unwrap_or_else(|| self.center.first_pane());
+        // Forget the panes that were replaced, or failed to load.
+        let pane_ids = self
+            .center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.entity_id())
+            .collect::<HashSet<_>>();
+        self.pane_subscriptions
+            .retain(|pane_id, _| pane_ids.contains(pane_id));
+        cx.notify();
+    }
+
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemovedItem { .. } => self.serialize(cx),
-            pane::Event::Remove { .. } => cx.emit(PanelEvent::Close),
+            pane::Event::Remove { focus_on_pane } => {
+                if self.center.panes().len() == 1 {
+                    cx.emit(PanelEvent::Close);
+                    return;
+                }
+                self.center.remove(&pane).log_err();
+                self.pane_subscriptions.remove(&pane.entity_id());
+                if self.active_pane == pane {
+                    self.active_pane = focus_on_pane
+                        .clone()
+                        .unwrap_or_else(|| self.center.first_pane());
+                }
+                cx.focus_view(&self.active_pane);
+                self.serialize(cx);
+                cx.notify();
+            }
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
+            pane::Event::Split(direction) => {
+                let Some(new_pane) = self.new_pane_with_cloned_active_terminal(&pane, cx) else {
+                    return;
+                };
+                self.center.split(&pane, &new_pane, *direction).log_err();
+                cx.focus_view(&new_pane);
+                self.serialize(cx);
+                cx.notify();
+            }
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    self.serialize(cx);
+                }
+            }
 
             _ => {}
         }
     }
 
+    /// Creates a pane with a new terminal, started in the working directory of the terminal
+    /// active in `pane`.
+    fn new_pane_with_cloned_active_terminal(
+        &mut self,
+        pane: &View<Pane>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Pane>> {
+        let workspace = self.workspace.upgrade()?;
+        let working_directory = pane
+            .read(cx)
+            .active_item()
+            .and_then(|item| item.downcast::<TerminalView>())
+            .and_then(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .working_directory()
+            })
+            .or_else(|| default_working_directory(workspace.read(cx), cx));
+        let project = workspace.read(cx).project().clone();
+        let window = cx.window_handle();
+        let terminal = project
+            .update(cx, |project, cx| {
+                project.create_terminal(TerminalKind::Shell(working_directory), window, cx)
+            })
+            .log_err()?;
+        let terminal_view = Box::new(cx.new_view(|cx| {
+            TerminalView::new(
+                terminal,
+                workspace.downgrade(),
+                workspace.read(cx).database_id(),
+                cx,
+            )
+        }));
+
+        let new_pane = new_terminal_pane(workspace.downgrade(), project, cx);
+        self.subscribe_to_pane(&new_pane, cx);
+        self.apply_tab_bar_buttons(&new_pane, cx);
+        new_pane.update(cx, |pane, cx| {
+            pane.add_item(terminal_view, true, true, None, cx);
+        });
+        Some(new_pane)
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -394,7 +461,12 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
-    fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+    /// Spawns the task in a terminal of the panel, returning the terminal once it is created.
+    pub fn spawn_task(
+        &mut self,
+        spawn_in_terminal: &SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
         let mut spawn_task = spawn_in_terminal.clone();
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
         let Some((shell, mut user_args)) = (match spawn_in_terminal.shell.clone() {
@@ -410,13 +482,13 @@ impl TerminalPanel {
                             Some(("\"${SHELL:-sh}\"".to_string(), Vec::new()))
                         }
                     }
-                    Err(_no_window_e) => return,
+                    Err(no_window_e) => return Task::ready(Err(no_window_e)),
                 }
             }
             Shell::Program(shell) => Some((shell, Vec::new())),
             Shell::WithArguments { program, args, .. } => Some((program, args)),
         }) else {
-            return;
+            return Task::ready(Err(anyhow!("no shell to spawn the task in was found")));
         };
         #[cfg(target_os = "windows")]
         let windows_shell_type = to_windows_shell_type(&shell);
@@ -479,18 +551,14 @@ impl TerminalPanel {
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
         if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
 
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
         if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let (existing_item_index, task_pane, existing_terminal) = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -499,33 +567,218 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+            Task::ready(
+                self.replace_terminal(
+                    spawn_task,
+                    task_pane,
+                    existing_item_index,
+                    existing_terminal,
+                    cx,
+                )
+                .context("replacing the task terminal"),
+            )
         } else {
+            let (terminal_tx, terminal_rx) = oneshot::channel();
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
                 cx.spawn(|terminal_panel, mut cx| async move {
                     wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
+                    let terminal = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        if use_new_terminal {
+                            terminal_panel.spawn_in_new_terminal(spawn_task, cx)
+                        } else {
+                            Task::ready(
                                 terminal_panel
-                                    .spawn_in_new_terminal(spawn_task, cx)
-                                    .detach_and_log_err(cx);
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
+                                    .replace_terminal(
+                                        spawn_task,
+                                        task_pane,
+                                        existing_item_index,
+                                        existing_terminal,
+                                        cx,
+                                    )
+                                    .context("replacing the task terminal"),
+                            )
+                        }
+                    });
+                    let terminal = match terminal {
+                        Ok(terminal) => terminal.await,
+                        Err(e) => Err(e),
+                    };
+                    terminal_tx.send(terminal).ok();
                 }),
             );
+            cx.spawn(|_, _| async move {
+                terminal_rx
+                    .await
+                    .context("the task was spawned again before it started")?
+            })
         }
     }
 
+    /// Runs the tasks the task given depends on before it, tracking their progress in the task inventory.
+    fn spawn_task_chain(&mut self, spawn_in_terminal: SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        let chain_id = spawn_in_terminal.id.clone();
+        // Spawning a chain again replaces the one that still runs.
+        self.cancel_task_chain(&chain_id, cx);
+
+        let label = spawn_in_terminal.label.clone();
+        let mut steps = Vec::new();
+        let chained_task = ChainedTask::new(spawn_in_terminal, &mut steps);
+        let progress = TaskChain {
+            task_id: chain_id.clone(),
+            label,
+            steps,
+        };
+        if let Some(inventory) = self.task_inventory(cx) {
+            let progress = progress.clone();
+            inventory.update(cx, |inventory, cx| {
+                inventory.update_task_chain(progress);
+                cx.notify();
+            });
+        }
+
+        let run = cx.spawn({
+            let chain_id = chain_id.clone();
+            |terminal_panel, mut cx| async move {
+                let result = run_chained_task(
+                    chained_task,
+                    chain_id.clone(),
+                    terminal_panel.clone(),
+                    cx.clone(),
+                )
+                .await;
+                terminal_panel
+                    .update(&mut cx, |terminal_panel, cx| {
+                        terminal_panel.finish_task_chain(&chain_id, result, cx)
+                    })
+                    .ok();
+            }
+        });
+        self.task_chains.insert(
+            chain_id,
+            RunningTaskChain {
+                progress,
+                terminals: Vec::new(),
+                _run: run,
+            },
+        );
+    }
+
+    pub(crate) fn set_task_chain_step_status(
+        &mut self,
+        chain_id: &TaskId,
+        step_ix: usize,
+        status: TaskChainStepStatus,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(task_chain) = self.task_chains.get_mut(chain_id) else {
+            return;
+        };
+        if let Some(step) = task_chain.progress.steps.get_mut(step_ix) {
+            step.status = status;
+        }
+        let progress = task_chain.progress.clone();
+        if let Some(inventory) = self.task_inventory(cx) {
+            inventory.update(cx, |inventory, cx| {
+                inventory.update_task_chain(progress);
+                cx.notify();
+            });
+        }
+    }
+
+    pub(crate) fn add_task_chain_terminal(
+        &mut self,
+        chain_id: &TaskId,
+        terminal: &Model<Terminal>,
+    ) {
+        if let Some(task_chain) = self.task_chains.get_mut(chain_id) {
+            task_chain.terminals.push(terminal.downgrade());
+        }
+    }
+
+    /// Stops tracking a chain that is done running. A chain that failed is kept in the task inventory,
+    /// for its progress to be shown until it is dismissed.
+    fn finish_task_chain(
+        &mut self,
+        chain_id: &TaskId,
+        result: Result<()>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(mut task_chain) = self.task_chains.remove(chain_id) else {
+            return;
+        };
+        let Some(inventory) = self.task_inventory(cx) else {
+            return;
+        };
+        match result.with_context(|| format!("running task `{}`", task_chain.progress.label)) {
+            Ok(()) => inventory.update(cx, |inventory, cx| {
+                inventory.remove_task_chain(chain_id);
+                cx.notify();
+            }),
+            Err(e) => {
+                log::error!("{e:#}");
+                for step in &mut task_chain.progress.steps {
+                    if step.status == TaskChainStepStatus::Pending {
+                        step.status = TaskChainStepStatus::Cancelled;
+                    }
+                }
+                inventory.update(cx, |inventory, cx| {
+                    inventory.update_task_chain(task_chain.progress);
+                    cx.notify();
+                });
+            }
+        }
+    }
+
+    /// Kills the tasks of the chain and does not start the rest of them, or dismisses the chain if it is not running anymore.
+    fn cancel_task_chain(&mut self, chain_id: &TaskId, cx: &mut ViewContext<Self>) {
+        if let Some(task_chain) = self.task_chains.remove(chain_id) {
+            for terminal in task_chain.terminals {
+                terminal
+                    .update(cx, |terminal, _| terminal.kill_active_task())
+                    .ok();
+            }
+        }
+        if let Some(inventory) = self.task_inventory(cx) {
+            inventory.update(cx, |inventory, cx| {
+                inventory.remove_task_chain(chain_id);
+                cx.notify();
+            });
+        }
+    }
+
+    fn cancel_task_chains(&mut self, action: &tasks_ui::CancelChain, cx: &mut ViewContext<Self>) {
+        match &action.task_id {
+            Some(chain_id) => self.cancel_task_chain(chain_id, cx),
+            None => {
+                let chain_ids = self
+                    .task_inventory(cx)
+                    .map(|inventory| {
+                        inventory
+                            .read(cx)
+                            .task_chains()
+                            .iter()
+                            .map(|task_chain| task_chain.task_id.clone())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(self.task_chains.keys().cloned())
+                    .unique()
+                    .collect::<Vec<_>>();
+                for chain_id in chain_ids {
+                    self.cancel_task_chain(&chain_id, cx);
+                }
+            }
+        }
+    }
+
+    fn task_inventory(&self, cx: &AppContext) -> Option<Model<Inventory>> {
+        let workspace = self.workspace.upgrade()?;
+        let project = workspace.read(cx).project().read(cx);
+        project.task_store().read(cx).task_inventory().cloned()
+    }
+
     pub fn spawn_in_new_terminal(
         &mut self,
         spawn_task: SpawnInTerminal,
@@ -558,25 +811,36 @@ impl TerminalPanel {
         &self,
         label: &str,
         cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.full_label == label {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    ) -> Vec<(usize, View<Pane>, View<TerminalView>)> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .enumerate()
+                    .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
+                    .filter_map(|(index, terminal_view)| {
+                        let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
+                        if &task_state.full_label == label {
+                            Some((index, pane.clone(), terminal_view))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, focus: bool, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
+    fn activate_terminal_view(
+        &self,
+        pane: &View<Pane>,
+        item_index: usize,
+        focus: bool,
+        cx: &mut WindowContext,
+    ) {
+        pane.update(cx, |pane, cx| {
             pane.activate_item(item_index, true, focus, cx)
         })
     }
@@ -597,7 +861,7 @@ impl TerminalPanel {
         self.pending_terminals_to_add += 1;
 
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             let result = workspace.update(&mut cx, |workspace, cx| {
                 let window = cx.window_handle();
                 let terminal = workspace
@@ -636,28 +900,8 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+        let items =
+            SerializedItems::WithSplits(serialize_pane_group(&self.center, &self.active_pane, cx));
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -667,7 +911,7 @@ impl TerminalPanel {
                         TERMINAL_PANEL_KEY.into(),
                         serde_json::to_string(&SerializedTerminalPanel {
                             items,
-                            active_item_id,
+                            active_item_id: None,
                             height,
                             width,
                         })?,
@@ -682,10 +926,11 @@ impl TerminalPanel {
     fn replace_terminal(
         &self,
         spawn_task: SpawnInTerminal,
+        task_pane: View<Pane>,
         terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
-    ) -> Option<()> {
+    ) -> Option<Model<Terminal>> {
         let project = self
             .workspace
             .update(cx, |workspace, _| workspace.project().clone())
@@ -699,12 +944,12 @@ impl TerminalPanel {
                 .log_err()
         })?;
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
-            terminal_to_replace.set_terminal(new_terminal, cx);
+            terminal_to_replace.set_terminal(new_terminal.clone(), cx);
         });
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, true, cx);
+                self.activate_terminal_view(&task_pane, terminal_item_index, true, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -714,7 +959,7 @@ impl TerminalPanel {
                 .detach();
             }
             RevealStrategy::NoFocus => {
-                self.activate_terminal_view(terminal_item_index, false, cx);
+                self.activate_terminal_view(&task_pane, terminal_item_index, false, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -726,11 +971,28 @@ impl TerminalPanel {
             RevealStrategy::Never => {}
         }
 
-        Some(())
+        Some(new_terminal)
+    }
+
+    fn activate_pane_at_offset(&mut self, offset: isize, cx: &mut ViewContext<Self>) {
+        let panes = self.center.panes();
+        if let Some(ix) = panes.iter().position(|pane| **pane == self.active_pane) {
+            let next_ix = (ix as isize + offset).rem_euclid(panes.len() as isize) as usize;
+            let next_pane = panes[next_ix].clone();
+            cx.focus_view(&next_pane);
+        }
     }
 
     fn has_no_terminals(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
+        self.items_len(cx) == 0 && self.pending_terminals_to_add == 0
+    }
+
+    fn items_len(&self, cx: &AppContext) -> usize {
+        self.center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum()
     }
 
     pub fn assistant_enabled(&self) -> bool {
@@ -739,10 +1001,10 @@ impl TerminalPanel {
 }
 
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<(usize, View<Pane>, View<TerminalView>)>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|(_, _, terminal)| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -770,6 +1032,99 @@ fn add_paths_to_terminal(pane: &mut Pane, paths: &[PathBuf], cx: &mut ViewContex
     }
 }
 
+pub(crate) fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    let is_local = project.read(cx).is_local();
+    let terminal_panel = cx.view().downgrade();
+    let pane = cx.new_view(|cx| {
+        let mut pane = Pane::new(
+            workspace.clone(),
+            project.clone(),
+            Default::default(),
+            None,
+            NewTerminal.boxed_clone(),
+            cx,
+        );
+        pane.set_can_split(true, cx);
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(None);
+        pane.set_should_display_tab_bar(|_| true);
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let source = tab.pane.clone();
+                let item = if source == *cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    source.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        let Some(split_direction) = pane.drag_split_direction() else {
+                            return ControlFlow::Continue(());
+                        };
+                        let item_id = item.item_id();
+                        let terminal_panel = terminal_panel.clone();
+                        let this_pane = cx.view().downgrade();
+                        let workspace = workspace.clone();
+                        let project = project.clone();
+                        cx.window_context().defer(move |cx| {
+                            let Some(this_pane) = this_pane.upgrade() else {
+                                return;
+                            };
+                            terminal_panel
+                                .update(cx, |terminal_panel, cx| {
+                                    let new_pane = new_terminal_pane(workspace, project, cx);
+                                    terminal_panel.subscribe_to_pane(&new_pane, cx);
+                                    terminal_panel.apply_tab_bar_buttons(&new_pane, cx);
+                                    terminal_panel
+                                        .center
+                                        .split(&this_pane, &new_pane, split_direction)
+                                        .log_err();
+                                    cx.notify();
+                                    new_pane
+                                })
+                                .ok()
+                                .map(|new_pane| {
+                                    move_item(&source, &new_pane, item_id, 0, cx);
+                                    cx.focus_view(&new_pane);
+                                });
+                        });
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = project.read(cx).absolute_path(&project_path, cx)
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                let project = project.read(cx);
+                if let Some(entry_path) = project
+                    .path_for_entry(entry_id, cx)
+                    .and_then(|project_path| project.absolute_path(&project_path, cx))
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if is_local {
+                if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                    add_paths_to_terminal(pane, paths.paths(), cx);
+                }
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    });
+
+    pane
+}
+
 impl EventEmitter<PanelEvent> for TerminalPanel {}
 
 impl Render for TerminalPanel {
@@ -777,7 +1132,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -786,13 +1141,83 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let center = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                self.center
+                    .render(
+                        workspace.project(),
+                        &HashMap::default(),
+                        None,
+                        &self.active_pane,
+                        workspace.zoomed_item(),
+                        workspace.app_state(),
+                        cx,
+                    )
+                    .into_any_element()
+            })
+            .ok();
+        registrar
+            .into_div()
+            .size_full()
+            .children(center)
+            .on_action(
+                cx.listener(|terminal_panel, action: &ActivatePaneInDirection, cx| {
+                    if let Some(pane) = terminal_panel.center.find_pane_in_direction(
+                        &terminal_panel.active_pane,
+                        action.0,
+                        cx,
+                    ) {
+                        cx.focus_view(pane);
+                    } else {
+                        cx.propagate();
+                    }
+                }),
+            )
+            .on_action(
+                cx.listener(|terminal_panel, action: &SwapPaneInDirection, cx| {
+                    if let Some(to) = terminal_panel
+                        .center
+                        .find_pane_in_direction(&terminal_panel.active_pane, action.0, cx)
+                        .cloned()
+                    {
+                        terminal_panel.center.swap(&terminal_panel.active_pane, &to);
+                        terminal_panel.serialize(cx);
+                        cx.notify();
+                    }
+                }),
+            )
+            .on_action(cx.listener(|terminal_panel, _: &ActivateNextPane, cx| {
+                terminal_panel.activate_pane_at_offset(1, cx);
+            }))
+            .on_action(cx.listener(|terminal_panel, _: &ActivatePreviousPane, cx| {
+                terminal_panel.activate_pane_at_offset(-1, cx);
+            }))
+            .on_action(cx.listener(|terminal_panel, action: &ActivatePane, cx| {
+                let panes = terminal_panel.center.panes();
+                if let Some(pane) = panes.get(action.0).map(|p| (*p).clone()) {
+                    cx.focus_view(&pane);
+                } else if let Some(new_pane) = terminal_panel
+                    .new_pane_with_cloned_active_terminal(&terminal_panel.active_pane.clone(), cx)
+                {
+                    terminal_panel
+                        .center
+                        .split(
+                            &terminal_panel.active_pane,
+                            &new_pane,
+                            SplitDirection::Right,
+                        )
+                        .log_err();
+                    cx.focus_view(&new_pane);
+                    terminal_panel.serialize(cx);
+                }
+            }))
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -844,11 +1269,14 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.active_pane.read(cx).is_zoomed()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
+        cx.notify();
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
@@ -868,7 +1296,7 @@ impl Panel for TerminalPanel {
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self.items_len(cx);
         if count == 0 {
             None
         } else {
@@ -897,7 +1325,7 @@ impl Panel for TerminalPanel {
     }
 
     fn pane(&self) -> Option<View<Pane>> {
-        Some(self.pane.clone())
+        Some(self.active_pane.clone())
     }
 }
 
@@ -919,14 +1347,6 @@ impl Render for InlineAssistTabBarButton {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct SerializedTerminalPanel {
-    items: Vec<u64>,
-    active_item_id: Option<u64>,
-    width: Option<Pixels>,
-    height: Option<Pixels>,
-}
-
 fn retrieve_system_shell() -> Option<String> {
     #[cfg(not(target_os = "windows"))]
     {
@@ -1020,3 +1440,106 @@ enum WindowsShellType {
     Cmd,
     Other,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::SerializedPaneGroup;
+    use gpui::{Axis, TestAppContext};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_serialize_and_restore_split_panes(cx: &mut TestAppContext) {
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            Project::init_settings(cx);
+            language::init(cx);
+        });
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let terminal_panel = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| TerminalPanel::new(workspace, cx))
+        });
+        terminal_panel
+            .update(cx, |terminal_panel, cx| {
+                terminal_panel.add_terminal(TerminalKind::Shell(None), RevealStrategy::Never, cx)
+            })
+            .await
+            .unwrap();
+
+        // Split the pane right, then split the right pane down and activate its lower half.
+        let serialized = terminal_panel.update(cx, |terminal_panel, cx| {
+            let left = terminal_panel.active_pane.clone();
+            let right = terminal_panel
+                .new_pane_with_cloned_active_terminal(&left, cx)
+                .unwrap();
+            terminal_panel
+                .center
+                .split(&left, &right, SplitDirection::Right)
+                .unwrap();
+            let bottom_right = terminal_panel
+                .new_pane_with_cloned_active_terminal(&right, cx)
+                .unwrap();
+            terminal_panel
+                .center
+                .split(&right, &bottom_right, SplitDirection::Down)
+                .unwrap();
+            terminal_panel.active_pane = bottom_right;
+            assert_eq!(terminal_panel.pane_subscriptions.len(), 3);
+            serialize_pane_group(&terminal_panel.center, &terminal_panel.active_pane, cx)
+        });
+        let serialized = serde_json::from_str::<SerializedPaneGroup>(
+            &serde_json::to_string(&serialized).unwrap(),
+        )
+        .unwrap();
+
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let (center, active_pane, item_ids) = cx
+            .update(|cx| {
+                let terminal_panel = terminal_panel.downgrade();
+                let workspace = workspace.downgrade();
+                cx.spawn(|mut cx| async move {
+                    deserialize_pane_group(
+                        terminal_panel,
+                        workspace,
+                        project,
+                        workspace_id,
+                        &serialized,
+                        &mut cx,
+                    )
+                    .await
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(item_ids.len(), 3);
+
+        terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.set_center(center, active_pane, cx);
+
+            let Member::Axis(root) = &terminal_panel.center.root else {
+                panic!("expected the restored panes to be split");
+            };
+            assert_eq!(root.axis, Axis::Horizontal);
+            assert_eq!(root.members.len(), 2);
+            let Member::Axis(right) = &root.members[1] else {
+                panic!("expected the right pane to be split");
+            };
+            assert_eq!(right.axis, Axis::Vertical);
+            assert_eq!(right.members.len(), 2);
+
+            let panes = terminal_panel.center.panes();
+            assert_eq!(panes.len(), 3);
+            for pane in &panes {
+                assert_eq!(pane.read(cx).items_len(), 1);
+            }
+            assert!(&terminal_panel.active_pane == panes[2]);
+            // Only the restored panes are still subscribed to.
+            assert_eq!(terminal_panel.pane_subscriptions.len(), 3);
+            assert!(panes.iter().all(|pane| terminal_panel
+                .pane_subscriptions
+                .contains_key(&pane.entity_id())));
+        });
+    }
+}
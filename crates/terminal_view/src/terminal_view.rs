@@ -1,4 +1,5 @@
 mod persistence;
+mod task_chain;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -6,23 +7,28 @@ use collections::HashSet;
 use editor::{actions::SelectAll, scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task, View,
-    VisualContext, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AppContext, ClipboardItem, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task,
+    View, VisualContext, WeakView,
 };
 use language::Bias;
-use persistence::TERMINAL_DB;
-use project::{search::SearchQuery, terminals::TerminalKind, Fs, Metadata, Project};
+use persistence::{SerializedTask, TERMINAL_DB};
+use project::{
+    search::SearchQuery,
+    terminals::{RestoredTerminal, TerminalKind},
+    Fs, Metadata, Project,
+};
 use terminal::{
     alacritty_terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, ScrollLineDown,
+    ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectLastCommandOutput, ShowCharacterPalette, TaskStatus,
+    Terminal, TerminalSize, ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -41,7 +47,7 @@ use anyhow::Context;
 use serde::Deserialize;
 use settings::{Settings, SettingsStore};
 use smol::Timer;
-use zed_actions::InlineAssist;
+use zed_actions::{InlineAssist, QuoteTerminalOutput};
 
 use std::{
     cmp,
@@ -175,6 +181,19 @@ impl TerminalView {
         let cursor_shape = TerminalSettings::get_global(cx)
             .cursor_shape
             .unwrap_or_default();
+        // Items are only serialized when their tab changes, so the latest output is saved on quit.
+        let item_id = cx.entity_id().as_u64();
+        let weak_view = cx.view().downgrade();
+        let save_on_quit = cx.on_app_quit(move |cx| {
+            let save = weak_view
+                .upgrade()
+                .and_then(|view| view.read(cx).save_to_db(item_id, cx));
+            async move {
+                if let Some(save) = save {
+                    save.await.log_err();
+                }
+            }
+        });
 
         Self {
             terminal,
@@ -196,11 +215,35 @@ impl TerminalView {
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                save_on_quit,
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
     }
 
+    /// Saves the terminal's working directory, output and task, to reopen it after a restart.
+    fn save_to_db(
+        &self,
+        item_id: workspace::ItemId,
+        cx: &AppContext,
+    ) -> Option<Task<gpui::Result<()>>> {
+        let workspace_id = self.workspace_id?;
+        let terminal = self.terminal.read(cx);
+        let working_directory = terminal.working_directory();
+        let scrollback =
+            terminal.scrollback_text(TerminalSettings::get_global(cx).restored_scrollback_lines);
+        let task = terminal
+            .task()
+            .map(|task| serde_json::to_string(&SerializedTask::from(task)))
+            .transpose()
+            .log_err()?;
+        Some(cx.background_executor().spawn(async move {
+            TERMINAL_DB
+                .save_terminal(item_id, workspace_id, working_directory, scrollback, task)
+                .await
+        }))
+    }
+
     pub fn model(&self) -> &Model<Terminal> {
         &self.terminal
     }
@@ -226,6 +269,11 @@ impl TerminalView {
             .map_or(false, |terminal_panel| {
                 terminal_panel.read(cx).assistant_enabled()
             });
+        let has_command_output = self
+            .terminal
+            .read(cx)
+            .command_blocks()
+            .any(|block| block.is_finished());
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal))
@@ -234,9 +282,23 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .when(has_command_output, |menu| {
+                    menu.separator()
+                        .action(
+                            "Select Last Command Output",
+                            Box::new(SelectLastCommandOutput),
+                        )
+                        .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
+                        .when(has_command_output, |menu| {
+                            menu.action(
+                                "Send Last Command Output to Assistant",
+                                Box::new(QuoteTerminalOutput),
+                            )
+                        })
                 })
                 .separator()
                 .action("Close", Box::new(CloseActiveItem { save_intent: None }))
@@ -431,6 +493,38 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_last_command_output(
+        &mut self,
+        _: &SelectLastCommandOutput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_last_command_output());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -988,6 +1082,10 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_last_command_output))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1162,14 +1260,12 @@ impl Item for TerminalView {
     }
 
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
-        if self.terminal().read(cx).task().is_none() {
-            if let Some((new_id, old_id)) = workspace.database_id().zip(self.workspace_id) {
-                cx.background_executor()
-                    .spawn(TERMINAL_DB.update_workspace_id(new_id, old_id, cx.entity_id().as_u64()))
-                    .detach();
-            }
-            self.workspace_id = workspace.database_id();
+        if let Some((new_id, old_id)) = workspace.database_id().zip(self.workspace_id) {
+            cx.background_executor()
+                .spawn(TERMINAL_DB.update_workspace_id(new_id, old_id, cx.entity_id().as_u64()))
+                .detach();
         }
+        self.workspace_id = workspace.database_id();
     }
 
     fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
@@ -1197,20 +1293,7 @@ impl SerializableItem for TerminalView {
         _closing: bool,
         cx: &mut ViewContext<Self>,
     ) -> Option<Task<gpui::Result<()>>> {
-        let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
-            return None;
-        }
-
-        if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
-            Some(cx.background_executor().spawn(async move {
-                TERMINAL_DB
-                    .save_working_directory(item_id, workspace_id, cwd)
-                    .await
-            }))
-        } else {
-            None
-        }
+        self.save_to_db(item_id, cx)
     }
 
     fn should_serialize(&self, event: &Self::Event) -> bool {
@@ -1226,12 +1309,18 @@ impl SerializableItem for TerminalView {
     ) -> Task<anyhow::Result<View<Self>>> {
         let window = cx.window_handle();
         cx.spawn(|pane, mut cx| async move {
+            let (from_db, scrollback, task) = TERMINAL_DB
+                .get_terminal(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .unwrap_or_default();
+            let task = task
+                .map(|task| serde_json::from_str::<SerializedTask>(&task))
+                .transpose()
+                .log_err()
+                .flatten();
             let cwd = cx
                 .update(|cx| {
-                    let from_db = TERMINAL_DB
-                        .get_working_directory(item_id, workspace_id)
-                        .log_err()
-                        .flatten();
                     if from_db
                         .as_ref()
                         .is_some_and(|from_db| !from_db.as_os_str().is_empty())
@@ -1246,8 +1335,13 @@ impl SerializableItem for TerminalView {
                 .ok()
                 .flatten();
 
+            let kind = TerminalKind::Restored(RestoredTerminal {
+                working_directory: cwd,
+                scrollback,
+                task: task.map(Into::into),
+            });
             let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(TerminalKind::Shell(cwd), window, cx)
+                project.create_terminal(kind, window, cx)
             })??;
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, Some(workspace_id), cx))
@@ -628,6 +628,11 @@ impl Pane {
         cx.notify();
     }
 
+    /// The side of the pane a dragged item would be split out to, if dropped now.
+    pub fn drag_split_direction(&self) -> Option<SplitDirection> {
+        self.drag_split_direction
+    }
+
     pub fn set_can_navigate(&mut self, can_navigate: bool, cx: &mut ViewContext<Self>) {
         self.toolbar.update(cx, |toolbar, cx| {
             toolbar.set_can_navigate(can_navigate, cx);
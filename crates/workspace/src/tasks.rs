@@ -26,9 +26,22 @@ pub fn schedule_task(
         }
     }
 
-    if let Some(spawn_in_terminal) =
-        task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx)
+    let resolved_task = match workspace
+        .project
+        .read(cx)
+        .task_store()
+        .read(cx)
+        .task_inventory()
     {
+        Some(inventory) => inventory.read(cx).resolve_task_with_dependencies(
+            None,
+            &task_source_kind,
+            task_to_resolve,
+            task_cx,
+        ),
+        None => task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx),
+    };
+    if let Some(spawn_in_terminal) = resolved_task {
         schedule_resolved_task(
             workspace,
             task_source_kind,
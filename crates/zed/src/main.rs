@@ -27,6 +27,7 @@ use gpui::{
 use http_client::{read_proxy_from_env, Uri};
 use indexed_docs::IndexedDocsRegistry;
 use language::LanguageRegistry;
+use language_model::LanguageModelRegistry;
 use log::LevelFilter;
 use reqwest_client::ReqwestClient;
 
@@ -387,6 +388,7 @@ fn main() {
             cx,
         );
         supermaven::init(app_state.client.clone(), cx);
+        fim_completion::init(cx);
         language_model::init(
             app_state.user_store.clone(),
             app_state.client.clone(),
@@ -414,6 +416,7 @@ fn main() {
             SnippetRegistry::global(cx),
             app_state.languages.clone(),
             ContextServerFactoryRegistry::global(cx),
+            LanguageModelRegistry::global(cx),
             cx,
         );
         extension_host::init(
@@ -295,6 +295,9 @@ pub fn initialize_workspace(
             .register_action(|_, _: &ToggleFullScreen, cx| {
                 cx.toggle_fullscreen();
             })
+            .register_action(|_, _: &DebugElements, cx| {
+                cx.toggle_inspector();
+            })
             .register_action(|_, action: &OpenZedUrl, cx| {
                 OpenListener::global(cx).open_urls(vec![action.url.clone()])
             })
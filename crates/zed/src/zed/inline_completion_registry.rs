@@ -4,6 +4,7 @@ use client::telemetry::Telemetry;
 use collections::HashMap;
 use copilot::{Copilot, CopilotCompletionProvider};
 use editor::{Editor, EditorMode};
+use fim_completion::FimCompletionProvider;
 use gpui::{AnyWindowHandle, AppContext, Context, ViewContext, WeakView};
 use language::language_settings::all_language_settings;
 use settings::SettingsStore;
@@ -131,5 +132,11 @@ fn assign_inline_completion_provider(
                 editor.set_inline_completion_provider(Some(provider), cx);
             }
         }
+        language::language_settings::InlineCompletionProvider::Fim => {
+            let provider = cx.new_model(|_| {
+                FimCompletionProvider::new(editor.project()).with_telemetry(telemetry.clone())
+            });
+            editor.set_inline_completion_provider(Some(provider), cx);
+        }
     }
 }
@@ -49,3 +49,5 @@ pub struct InlineAssist {
 }
 
 impl_actions!(assistant, [InlineAssist]);
+
+actions!(assistant, [QuoteTerminalOutput]);
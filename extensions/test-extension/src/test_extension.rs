@@ -1,6 +1,11 @@
+use std::collections::VecDeque;
 use std::fs;
+use zed::language_model::{
+    CompletionEvent, CompletionRequest, CompletionStream, LanguageModelInfo, MessageContent,
+    StopReason, ToolUse,
+};
 use zed::lsp::CompletionKind;
-use zed::{CodeLabel, CodeLabelSpan, LanguageServerId};
+use zed::{CodeLabel, CodeLabelSpan, LanguageModelProviderId, LanguageServerId};
 use zed_extension_api::{self as zed, Result};
 
 struct TestExtension {
@@ -138,10 +143,83 @@ impl zed::Extension for TestExtension {
             code,
         })
     }
+
+    fn language_model_provider_models(
+        &self,
+        _provider_id: &LanguageModelProviderId,
+        _api_key: Option<String>,
+    ) -> Result<Vec<LanguageModelInfo>> {
+        Ok(vec![LanguageModelInfo {
+            id: "echo".into(),
+            display_name: "Echo".into(),
+            max_token_count: 1000,
+            max_output_tokens: None,
+        }])
+    }
+
+    fn language_model_count_tokens(
+        &self,
+        _provider_id: &LanguageModelProviderId,
+        _model_id: &str,
+        request: &CompletionRequest,
+    ) -> Result<u32> {
+        Ok(request_text(request).split_whitespace().count() as u32)
+    }
+
+    fn language_model_stream_completion(
+        &mut self,
+        _provider_id: &LanguageModelProviderId,
+        model_id: &str,
+        request: CompletionRequest,
+        api_key: Option<String>,
+    ) -> Result<Box<dyn CompletionStream>> {
+        let api_key = api_key.ok_or("missing API key")?;
+        let mut events = VecDeque::from([
+            CompletionEvent::StartMessage(format!("{model_id}-{api_key}")),
+            CompletionEvent::Text(request_text(&request)),
+        ]);
+        // Use the first tool with the text of the request as its input.
+        if let Some(tool) = request.tools.first() {
+            events.push_back(CompletionEvent::ToolUse(ToolUse {
+                id: "tool-use-1".into(),
+                name: tool.name.clone(),
+                input: zed::serde_json::json!({ "text": request_text(&request) }).to_string(),
+            }));
+            events.push_back(CompletionEvent::Stop(StopReason::ToolUse));
+        } else {
+            events.push_back(CompletionEvent::Stop(StopReason::EndTurn));
+        }
+        Ok(Box::new(EchoCompletionStream { events }))
+    }
 }
 
 zed::register_extension!(TestExtension);
 
+/// Replays the events of a completion echoing the request.
+struct EchoCompletionStream {
+    events: VecDeque<CompletionEvent>,
+}
+
+impl CompletionStream for EchoCompletionStream {
+    fn next_event(&mut self) -> Result<Option<CompletionEvent>> {
+        Ok(self.events.pop_front())
+    }
+}
+
+/// Returns the text of the messages of a completion request.
+fn request_text(request: &CompletionRequest) -> String {
+    request
+        .messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|content| match content {
+            MessageContent::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Removes newlines from the completion detail.
 ///
 /// The Gleam LSP can return types containing newlines, which causes formatting